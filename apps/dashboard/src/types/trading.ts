@@ -20,7 +20,7 @@ export interface AuthState {
 
 // Order Types
 export type OrderSide = 'buy' | 'sell';
-export type OrderType = 'market' | 'limit' | 'stop' | 'stop_limit';
+export type OrderType = 'market' | 'limit' | 'stop' | 'stop_limit' | 'trailing_stop';
 export type OrderStatus =
     | 'pending'
     | 'accepted'
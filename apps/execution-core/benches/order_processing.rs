@@ -9,6 +9,7 @@ use std::collections::HashMap;
 
 // Simplified order struct for benchmarking
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct Order {
     id: Uuid,
     symbol: String,
@@ -29,7 +30,7 @@ impl Position {
             self.quantity = qty;
         } else {
             let total_cost = (self.quantity * self.avg_price) + (qty * price);
-            self.quantity = self.quantity + qty;
+            self.quantity += qty;
             if self.quantity != dec!(0) {
                 self.avg_price = total_cost / self.quantity;
             }
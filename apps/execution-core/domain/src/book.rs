@@ -0,0 +1,292 @@
+//! Order Book
+//! Open orders indexed per symbol into price-time priority bid and ask books, so a tick or a
+//! new limit order only visits the price levels it can reach
+
+use crate::order::{check_order, visible_quantity, Order};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use uuid::Uuid;
+
+/// Orders at one price, oldest first
+type Level = VecDeque<Uuid>;
+
+#[derive(Debug, Clone, Default)]
+struct SymbolBook {
+    bids: BTreeMap<Decimal, Level>,
+    asks: BTreeMap<Decimal, Level>,
+    /// Market and stop orders: no resting limit price, so every tick checks them
+    unpriced: Level,
+}
+
+impl SymbolBook {
+    fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty() && self.unpriced.is_empty()
+    }
+}
+
+/// Where an order rests: its side's book at its limit price, or with the unpriced orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Bid(Decimal),
+    Ask(Decimal),
+    Unpriced,
+}
+
+fn slot_of<M>(order: &Order<M>) -> Slot {
+    match (order.order_type.as_str(), order.side.as_str(), order.price) {
+        ("limit", "buy", Some(price)) => Slot::Bid(price),
+        ("limit", "sell", Some(price)) => Slot::Ask(price),
+        _ => Slot::Unpriced,
+    }
+}
+
+/// What the resting limit orders at one price show
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepthLevel {
+    pub price: Decimal,
+    /// Visible quantity; an iceberg counts only its current slice
+    pub quantity: Decimal,
+    pub orders: usize,
+}
+
+/// Price levels of a symbol's book, each side best price first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Depth {
+    pub bids: Vec<DepthLevel>,
+    pub asks: Vec<DepthLevel>,
+}
+
+impl Depth {
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+
+    /// Levels that differ from `previous`: new or changed levels as they are now, and levels
+    /// gone since with quantity zero. Applying them to `previous` gives `self`.
+    pub fn changes_since(&self, previous: &Depth) -> Depth {
+        let mut bids = changed_levels(&self.bids, &previous.bids);
+        bids.sort_by_key(|level| std::cmp::Reverse(level.price));
+        let mut asks = changed_levels(&self.asks, &previous.asks);
+        asks.sort_by_key(|level| level.price);
+        Depth { bids, asks }
+    }
+}
+
+fn changed_levels(current: &[DepthLevel], previous: &[DepthLevel]) -> Vec<DepthLevel> {
+    let before: BTreeMap<Decimal, &DepthLevel> = previous.iter().map(|level| (level.price, level)).collect();
+    let now: BTreeMap<Decimal, &DepthLevel> = current.iter().map(|level| (level.price, level)).collect();
+
+    let changed = current
+        .iter()
+        .filter(|level| before.get(&level.price) != Some(level))
+        .cloned();
+    let removed = previous
+        .iter()
+        .filter(|level| !now.contains_key(&level.price))
+        .map(|level| DepthLevel { price: level.price, quantity: Decimal::ZERO, orders: 0 });
+    changed.chain(removed).collect()
+}
+
+/// The engine's open orders by id, with every order also queued in its symbol's book. An
+/// order keeps its place in a level while its price, side and type stay the same; changing
+/// any of them through [`OrderBook::insert`] or [`OrderBook::update`] requeues it at the back
+/// of its new level.
+#[derive(Debug, Clone)]
+pub struct OrderBook<M = serde_json::Value> {
+    orders: HashMap<Uuid, Order<M>>,
+    books: HashMap<String, SymbolBook>,
+}
+
+impl<M> Default for OrderBook<M> {
+    fn default() -> Self {
+        Self {
+            orders: HashMap::new(),
+            books: HashMap::new(),
+        }
+    }
+}
+
+impl<M> OrderBook<M> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<&Order<M>> {
+        self.orders.get(id)
+    }
+
+    pub fn contains_key(&self, id: &Uuid) -> bool {
+        self.orders.contains_key(id)
+    }
+
+    /// Every open order, in no particular order
+    pub fn values(&self) -> impl Iterator<Item = &Order<M>> {
+        self.orders.values()
+    }
+
+    /// Add or replace an order, returning the one it replaced
+    pub fn insert(&mut self, order: Order<M>) -> Option<Order<M>> {
+        debug_assert_eq!(check_order(&order), Ok(()), "order {} entering the book", order.id);
+        let previous = self.orders.remove(&order.id);
+        let keeps_place = previous
+            .as_ref()
+            .is_some_and(|p| p.symbol == order.symbol && slot_of(p) == slot_of(&order));
+        if !keeps_place {
+            if let Some(previous) = &previous {
+                self.unqueue(previous);
+            }
+            self.queue(&order);
+        }
+        self.orders.insert(order.id, order);
+        previous
+    }
+
+    pub fn remove(&mut self, id: &Uuid) -> Option<Order<M>> {
+        let order = self.orders.remove(id)?;
+        self.unqueue(&order);
+        Some(order)
+    }
+
+    /// Change an order in place, moving it to its new level when its price, side or type
+    /// changed. `None` when the order is not in the book.
+    pub fn update<T>(&mut self, id: &Uuid, change: impl FnOnce(&mut Order<M>) -> T) -> Option<T> {
+        let order = self.orders.get_mut(id)?;
+        let (symbol, slot) = (order.symbol.clone(), slot_of(order));
+        let changed = change(order);
+        debug_assert_eq!(check_order(order), Ok(()), "order {} after an update", order.id);
+
+        if order.symbol != symbol || slot_of(order) != slot {
+            let order = self.orders.remove(id)?;
+            self.unqueue_at(&symbol, slot, id);
+            self.queue(&order);
+            self.orders.insert(order.id, order);
+        }
+        Some(changed)
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Order<M>) -> bool) {
+        let dropped: Vec<Uuid> = self.orders.values().filter(|o| !keep(o)).map(|o| o.id).collect();
+        for id in dropped {
+            self.remove(&id);
+        }
+    }
+
+    /// Every open order of `symbol`: bids best first, then asks best first, then the
+    /// unpriced orders, each level oldest first
+    pub fn symbol(&self, symbol: &str) -> impl Iterator<Item = &Order<M>> {
+        let book = self.books.get(symbol);
+        let bids = book.into_iter().flat_map(|b| b.bids.values().rev()).flatten();
+        let asks = book.into_iter().flat_map(|b| b.asks.values()).flatten();
+        let unpriced = book.into_iter().flat_map(|b| b.unpriced.iter());
+        self.resolve(bids.chain(asks).chain(unpriced))
+    }
+
+    /// Market and stop orders of `symbol`, oldest first
+    pub fn unpriced(&self, symbol: &str) -> impl Iterator<Item = &Order<M>> {
+        let ids = self.books.get(symbol).into_iter().flat_map(|b| b.unpriced.iter());
+        self.resolve(ids)
+    }
+
+    /// Orders of `symbol` a tick at `price` can fill: bids at or above it best first, asks at
+    /// or below it best first, then every unpriced order
+    pub fn matching(&self, symbol: &str, price: Decimal) -> impl Iterator<Item = &Order<M>> {
+        let book = self.books.get(symbol);
+        let bids = book.into_iter().flat_map(move |b| b.bids.range(price..).rev()).flat_map(|(_, level)| level);
+        let asks = book.into_iter().flat_map(move |b| b.asks.range(..=price)).flat_map(|(_, level)| level);
+        let unpriced = book.into_iter().flat_map(|b| b.unpriced.iter());
+        self.resolve(bids.chain(asks).chain(unpriced))
+    }
+
+    /// Resting limit orders of `symbol` a new `side` order limited at `limit` reaches, best
+    /// price first and oldest first at a price
+    pub fn crossing(&self, symbol: &str, side: &str, limit: Decimal) -> impl Iterator<Item = &Order<M>> {
+        let book = self.books.get(symbol);
+        let buying = side == "buy";
+        let asks = book
+            .into_iter()
+            .filter(move |_| buying)
+            .flat_map(move |b| b.asks.range(..=limit))
+            .flat_map(|(_, level)| level);
+        let bids = book
+            .into_iter()
+            .filter(move |_| !buying)
+            .flat_map(move |b| b.bids.range(limit..).rev())
+            .flat_map(|(_, level)| level);
+        self.resolve(asks.chain(bids))
+    }
+
+    /// Symbols with open orders
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.books.keys().map(String::as_str)
+    }
+
+    /// The resting limit orders of `symbol` by price, at most `levels` prices a side
+    pub fn depth(&self, symbol: &str, levels: usize) -> Depth {
+        let Some(book) = self.books.get(symbol) else {
+            return Depth::default();
+        };
+        let level = |(price, ids): (&Decimal, &Level)| {
+            let (quantity, orders) = self
+                .resolve(ids.iter())
+                .fold((Decimal::ZERO, 0), |(quantity, orders), order| (quantity + visible_quantity(order), orders + 1));
+            DepthLevel { price: *price, quantity, orders }
+        };
+        Depth {
+            bids: book.bids.iter().rev().take(levels).map(level).collect(),
+            asks: book.asks.iter().take(levels).map(level).collect(),
+        }
+    }
+
+    fn resolve<'a>(&'a self, ids: impl Iterator<Item = &'a Uuid> + 'a) -> impl Iterator<Item = &'a Order<M>> + 'a {
+        ids.filter_map(|id| self.orders.get(id))
+    }
+
+    fn queue(&mut self, order: &Order<M>) {
+        let book = self.books.entry(order.symbol.clone()).or_default();
+        match slot_of(order) {
+            Slot::Bid(price) => book.bids.entry(price).or_default().push_back(order.id),
+            Slot::Ask(price) => book.asks.entry(price).or_default().push_back(order.id),
+            Slot::Unpriced => book.unpriced.push_back(order.id),
+        }
+    }
+
+    fn unqueue(&mut self, order: &Order<M>) {
+        self.unqueue_at(&order.symbol, slot_of(order), &order.id);
+    }
+
+    fn unqueue_at(&mut self, symbol: &str, slot: Slot, id: &Uuid) {
+        if let Some(book) = self.books.get_mut(symbol) {
+            dequeue(book, slot, id);
+            if book.is_empty() {
+                self.books.remove(symbol);
+            }
+        }
+    }
+}
+
+fn dequeue(book: &mut SymbolBook, slot: Slot, id: &Uuid) {
+    let (levels, price) = match slot {
+        Slot::Bid(price) => (&mut book.bids, price),
+        Slot::Ask(price) => (&mut book.asks, price),
+        Slot::Unpriced => {
+            book.unpriced.retain(|queued| queued != id);
+            return;
+        }
+    };
+    if let Some(level) = levels.get_mut(&price) {
+        level.retain(|queued| queued != id);
+        if level.is_empty() {
+            levels.remove(&price);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Cache Consistency
+//! Comparison of the open orders and positions an engine holds in memory with the rows they
+//! mirror, for tests to call after each operation and for the engine's consistency check mode
+
+use crate::order::{check_order, Order};
+use crate::position::{check_position, Position};
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use uuid::Uuid;
+
+/// One way a cache disagrees with the stored rows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Mismatch {
+    /// `order` or `position`
+    pub kind: &'static str,
+    /// Order id, or `account_id/symbol` for a position
+    pub key: String,
+    /// The column that differs; `missing` for a row the cache lacks, `extra` for a cached
+    /// entry with no row, `invariant` for a cached entry that breaks one
+    pub field: &'static str,
+    pub cached: String,
+    pub stored: String,
+}
+
+fn compare<T: PartialEq + Display>(
+    out: &mut Vec<Mismatch>,
+    kind: &'static str,
+    key: &str,
+    field: &'static str,
+    cached: T,
+    stored: T,
+) {
+    if cached != stored {
+        out.push(Mismatch { kind, key: key.to_string(), field, cached: cached.to_string(), stored: stored.to_string() });
+    }
+}
+
+fn optional<T: Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "none".into(), |v| v.to_string())
+}
+
+/// Differences between the cached open orders and the open order rows they mirror, by id.
+/// Times are not compared: the database truncates them to microseconds.
+pub fn compare_orders<'a, A: 'a, B>(
+    cached: impl IntoIterator<Item = &'a Order<A>>,
+    stored: &[Order<B>],
+) -> Vec<Mismatch> {
+    let mut out = Vec::new();
+    let mut rows: HashMap<Uuid, &Order<B>> = stored.iter().map(|o| (o.id, o)).collect();
+
+    for order in cached {
+        let key = order.id.to_string();
+        if let Err(broken) = check_order(order) {
+            out.push(Mismatch { kind: "order", key: key.clone(), field: "invariant", cached: broken, stored: String::new() });
+        }
+        let Some(row) = rows.remove(&order.id) else {
+            out.push(Mismatch { kind: "order", key, field: "extra", cached: order.status.clone(), stored: String::new() });
+            continue;
+        };
+        compare(&mut out, "order", &key, "status", &order.status, &row.status);
+        compare(&mut out, "order", &key, "symbol", &order.symbol, &row.symbol);
+        compare(&mut out, "order", &key, "side", &order.side, &row.side);
+        compare(&mut out, "order", &key, "order_type", &order.order_type, &row.order_type);
+        compare(&mut out, "order", &key, "quantity", order.quantity, row.quantity);
+        compare(&mut out, "order", &key, "filled_quantity", order.filled_quantity, row.filled_quantity);
+        compare(&mut out, "order", &key, "price", optional(order.price), optional(row.price));
+        compare(&mut out, "order", &key, "stop_price", optional(order.stop_price), optional(row.stop_price));
+        compare(&mut out, "order", &key, "avg_fill_price", optional(order.avg_fill_price), optional(row.avg_fill_price));
+        compare(&mut out, "order", &key, "display_quantity", optional(order.display_quantity), optional(row.display_quantity));
+    }
+
+    let mut missing: Vec<&Order<B>> = rows.into_values().collect();
+    missing.sort_by_key(|o| o.id);
+    out.extend(missing.into_iter().map(|row| Mismatch {
+        kind: "order",
+        key: row.id.to_string(),
+        field: "missing",
+        cached: String::new(),
+        stored: row.status.clone(),
+    }));
+    out
+}
+
+/// Differences between the cached open positions and the non-flat position rows they mirror,
+/// by account and symbol. Unrealized P&L is not compared: it is marked outside the fill path.
+pub fn compare_positions<'a>(
+    cached: impl IntoIterator<Item = &'a Position>,
+    stored: &[Position],
+) -> Vec<Mismatch> {
+    let mut out = Vec::new();
+    let mut rows: HashMap<(Uuid, &str), &Position> =
+        stored.iter().map(|p| ((p.account_id, p.symbol.as_str()), p)).collect();
+
+    for pos in cached {
+        let key = format!("{}/{}", pos.account_id, pos.symbol);
+        if let Err(broken) = check_position(pos) {
+            out.push(Mismatch { kind: "position", key: key.clone(), field: "invariant", cached: broken, stored: String::new() });
+        }
+        let Some(row) = rows.remove(&(pos.account_id, pos.symbol.as_str())) else {
+            out.push(Mismatch { kind: "position", key, field: "extra", cached: pos.net_quantity.to_string(), stored: String::new() });
+            continue;
+        };
+        compare(&mut out, "position", &key, "net_quantity", pos.net_quantity, row.net_quantity);
+        compare(&mut out, "position", &key, "avg_price", pos.avg_price, row.avg_price);
+        compare(&mut out, "position", &key, "realized_pnl", pos.realized_pnl, row.realized_pnl);
+        compare(&mut out, "position", &key, "cost_basis", pos.cost_basis, row.cost_basis);
+        compare(&mut out, "position", &key, "sequence", pos.sequence, row.sequence);
+    }
+
+    let mut missing: Vec<&Position> = rows.into_values().collect();
+    missing.sort_by(|a, b| (a.account_id, &a.symbol).cmp(&(b.account_id, &b.symbol)));
+    out.extend(missing.into_iter().map(|row| Mismatch {
+        kind: "position",
+        key: format!("{}/{}", row.account_id, row.symbol),
+        field: "missing",
+        cached: String::new(),
+        stored: row.net_quantity.to_string(),
+    }));
+    out
+}
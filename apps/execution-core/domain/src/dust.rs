@@ -0,0 +1,21 @@
+//! Dust
+//! Holdings worth less than a threshold, and the trade that closes a dusty position
+
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Value of `quantity` at `price`, truncated to the ledger's precision so the house never
+/// credits more than the holding is worth
+pub fn dust_value(quantity: Decimal, price: Decimal) -> Decimal {
+    (quantity.abs() * price).round_dp_with_strategy(8, RoundingStrategy::ToZero)
+}
+
+/// A holding is dust when it is not flat and worth less than `threshold`; a zero threshold
+/// makes nothing dust
+pub fn is_dust(quantity: Decimal, price: Decimal, threshold: Decimal) -> bool {
+    !quantity.is_zero() && price > Decimal::ZERO && quantity.abs() * price < threshold
+}
+
+/// Side of the trade that flattens a position of `net_quantity`
+pub fn closing_side(net_quantity: Decimal) -> &'static str {
+    if net_quantity > Decimal::ZERO { "sell" } else { "buy" }
+}
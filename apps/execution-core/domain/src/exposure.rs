@@ -0,0 +1,57 @@
+//! Time-Weighted Exposure
+//! How long a position is held at each size, accrued per session. A session is a UTC
+//! calendar day.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+
+/// Exposure accrued within one session while the position held a constant quantity
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExposureAccrual {
+    pub session: NaiveDate,
+    /// Integral of the absolute quantity over time, in quantity-seconds
+    pub gross_quantity_seconds: Decimal,
+    /// Integral of the signed quantity: shorts offset longs
+    pub net_quantity_seconds: Decimal,
+    /// Time the position was not flat, in seconds
+    pub held_seconds: Decimal,
+}
+
+pub fn session_of(at: DateTime<Utc>) -> NaiveDate {
+    at.date_naive()
+}
+
+pub fn session_start(session: NaiveDate) -> DateTime<Utc> {
+    session.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc()
+}
+
+/// Exposure of holding `quantity` from `from` to `to`, split at session boundaries.
+/// Nothing accrues for an empty or backwards interval.
+pub fn accrue(quantity: Decimal, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<ExposureAccrual> {
+    let mut accruals = Vec::new();
+    let mut start = from;
+
+    while start < to {
+        let session = session_of(start);
+        let end = to.min(session_start(session) + Duration::days(1));
+        let seconds = Decimal::new((end - start).num_milliseconds(), 3);
+
+        accruals.push(ExposureAccrual {
+            session,
+            gross_quantity_seconds: quantity.abs() * seconds,
+            net_quantity_seconds: quantity * seconds,
+            held_seconds: if quantity.is_zero() { Decimal::ZERO } else { seconds },
+        });
+        start = end;
+    }
+
+    accruals
+}
+
+/// Average quantity over `elapsed_seconds`, zero before any time has passed
+pub fn average_quantity(quantity_seconds: Decimal, elapsed_seconds: Decimal) -> Decimal {
+    if elapsed_seconds <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    (quantity_seconds / elapsed_seconds).round_dp(8)
+}
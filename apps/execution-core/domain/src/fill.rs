@@ -0,0 +1,31 @@
+//! Fills
+//! Executions applied to positions, with the key that makes applying them idempotent
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub account_id: Uuid,
+    pub symbol: String,
+    pub side: String,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    /// Live fills carry their idempotency key; replayed history does not
+    pub key: Option<FillKey>,
+}
+
+/// Idempotency key of a fill: its order and how much of the order was filled before it,
+/// which a redelivered or replayed fill from the same order state shares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillKey {
+    pub order_id: Uuid,
+    pub filled_before: Decimal,
+}
+
+impl FillKey {
+    /// Key of a fill that takes the whole order in one piece, such as a strategy leg's
+    pub fn full(order_id: Uuid) -> Self {
+        Self { order_id, filled_before: Decimal::ZERO }
+    }
+}
@@ -0,0 +1,25 @@
+//! Enthropic Domain
+//! Order, fill and position types shared by the execution engine, the backtester,
+//! simulators and client tooling, together with the validation and position math the
+//! engine runs on them. Enable `sqlx` to map `Order` and `Position` from database rows.
+
+pub mod book;
+pub mod consistency;
+pub mod dust;
+pub mod exposure;
+pub mod fill;
+pub mod order;
+pub mod position;
+pub mod rebate;
+pub mod session;
+pub mod trigger;
+pub mod twap;
+pub mod vwap;
+
+pub use book::{Depth, DepthLevel, OrderBook};
+pub use fill::{Fill, FillKey};
+pub use order::{AmendOrderRequest, BracketSpec, NewOrderRequest, Order};
+pub use position::Position;
+pub use session::TradingSession;
+pub use trigger::{CrossDirection, TriggerAction, TriggerCondition, TriggerSpec};
+pub use twap::{ExecutionStrategy, TwapRequest};
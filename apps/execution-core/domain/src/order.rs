@@ -0,0 +1,845 @@
+//! Orders
+//! Stored orders, the requests that create and amend them, and the validation and price
+//! rules they follow
+
+use crate::trigger::{TriggerAction, TriggerSpec};
+
+use chrono::{DateTime, SubsecRound, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// An order as stored. `M` is how its metadata is held: plain JSON by default, the
+/// encrypted-at-rest wrapper inside the engine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Order<M = serde_json::Value> {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub client_order_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub order_type: String,
+    pub quantity: Decimal,
+    pub price: Option<Decimal>,
+    /// Price whose trade turns a stop or stop-limit order into a market or limit order
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub stop_price: Option<Decimal>,
+    /// Distance a trailing stop keeps from the best price since submission, as an amount
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub trail_amount: Option<Decimal>,
+    /// Distance a trailing stop keeps from the best price since submission, in percent
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub trail_percent: Option<Decimal>,
+    /// When a good-till-date order expires if still open
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub filled_quantity: Decimal,
+    pub avg_fill_price: Option<Decimal>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Set when the order is a leg of a multi-leg strategy
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub strategy_id: Option<Uuid>,
+    /// Opaque client data echoed back on every report
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub metadata: Option<M>,
+    /// Set on the replacement of an amended order
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub replaces_order_id: Option<Uuid>,
+    /// One-cancels-other group the order belongs to
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub group_id: Option<Uuid>,
+    /// Entry order of the bracket this take-profit or stop-loss belongs to
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub parent_order_id: Option<Uuid>,
+    /// Slice of an iceberg order shown and matched at a time; the rest stays hidden
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub display_quantity: Option<Decimal>,
+    /// Rejected rather than accepted when it would execute against the last trade
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    #[serde(default)]
+    pub post_only: bool,
+    /// Only ever fills against the account's open position in the symbol
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    #[serde(default)]
+    pub reduce_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewOrderRequest {
+    #[serde(alias = "client_order_id", default = "generate_order_id")]
+    pub client_order_id: String,
+
+    #[serde(alias = "accountId")]
+    pub account_id: Option<String>,
+
+    pub symbol: String,
+    pub side: String,
+
+    /// Defaults to the account's default, then to limit with a price and market without
+    /// (trailing_stop when a trail is given, stop_limit and stop when a stop price is)
+    #[serde(alias = "order_type", default)]
+    pub order_type: Option<String>,
+
+    /// Left out (zero) when the order is given as a `notional`
+    #[serde(default)]
+    pub quantity: Decimal,
+
+    /// Quote-currency amount to trade instead of a quantity, converted to one at the
+    /// order's price or the last trade
+    #[serde(default)]
+    pub notional: Option<Decimal>,
+
+    pub price: Option<Decimal>,
+
+    /// Required by stop and stop_limit orders, refused on the others
+    #[serde(alias = "stop_price", default)]
+    pub stop_price: Option<Decimal>,
+
+    /// Trailing stops take exactly one of these; the stop price then follows the market
+    #[serde(alias = "trail_amount", default)]
+    pub trail_amount: Option<Decimal>,
+
+    #[serde(alias = "trail_percent", default)]
+    pub trail_percent: Option<Decimal>,
+
+    /// ioc fills against the last trade on submission and cancels otherwise; fok is rejected instead
+    #[serde(alias = "time_in_force", default)]
+    pub time_in_force: Option<String>,
+
+    /// Required by gtd orders, which default to gtd when it is given
+    #[serde(alias = "expires_at", default)]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Protective limit for a market order without a price, in basis points from the last trade
+    #[serde(alias = "max_slippage_bps", default)]
+    pub max_slippage_bps: Option<Decimal>,
+
+    /// Optional condition that activates or cancels the order
+    #[serde(default)]
+    pub trigger: Option<TriggerSpec>,
+
+    /// Client references stored with the order, at most `MAX_ORDER_METADATA_BYTES`
+    #[serde(alias = "tags", default)]
+    pub metadata: Option<serde_json::Value>,
+
+    /// Open order of the account on the same symbol to link with; the first fill of
+    /// either cancels the other
+    #[serde(alias = "oco_with", default)]
+    pub oco_with: Option<Uuid>,
+
+    /// Take-profit and stop-loss exits that activate once the order has filled
+    #[serde(default)]
+    pub bracket: Option<BracketSpec>,
+
+    /// Makes a limit order an iceberg that shows only this much of its quantity at a time
+    #[serde(alias = "display_quantity", default)]
+    pub display_quantity: Option<Decimal>,
+
+    /// Rejects a limit order that would execute against the last trade, so it only ever
+    /// rests in the book and adds liquidity
+    #[serde(alias = "post_only", default)]
+    pub post_only: bool,
+
+    /// Caps the order at the position it closes and rejects it when there is none, so it can
+    /// never increase exposure
+    #[serde(alias = "reduce_only", default)]
+    pub reduce_only: bool,
+}
+
+/// Where generated client ids come from once the engine installs its id generator
+static CLIENT_ID_SOURCE: OnceLock<Box<dyn Fn() -> Uuid + Send + Sync>> = OnceLock::new();
+
+/// Draw the client ids of requests that leave theirs out from `source`; only the first call
+/// takes effect, and until one is made they are random
+pub fn set_client_id_source(source: impl Fn() -> Uuid + Send + Sync + 'static) {
+    let _ = CLIENT_ID_SOURCE.set(Box::new(source));
+}
+
+/// Client id given to requests that leave theirs out
+pub fn generate_order_id() -> String {
+    CLIENT_ID_SOURCE.get().map_or_else(Uuid::new_v4, |next| next()).to_string()
+}
+
+/// What an order was submitted with beyond its `Order` fields: its time in force, and the
+/// terms of the request that no column keeps as stated, stored with the order as JSON
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatedTerms {
+    /// Kept in the order's own column rather than with the rest
+    #[serde(skip)]
+    pub time_in_force: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notional: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<TriggerSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bracket: Option<BracketSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oco_with: Option<Uuid>,
+}
+
+impl StatedTerms {
+    /// The terms of `req` as the client sent it, before defaults
+    pub fn of(req: &NewOrderRequest) -> Self {
+        Self {
+            time_in_force: req.time_in_force.as_deref().map(str::to_lowercase),
+            notional: req.notional,
+            trigger: req.trigger.clone(),
+            bracket: req.bracket.clone(),
+            oco_with: req.oco_with,
+        }
+    }
+
+    /// Whether there is anything to store beside the order's columns
+    pub fn is_empty(&self) -> bool {
+        self.notional.is_none() && self.trigger.is_none() && self.bracket.is_none() && self.oco_with.is_none()
+    }
+}
+
+/// Terms a resubmitted request gives differently from the order its client_order_id already
+/// names, `stated` being what that order was submitted with beyond its fields; empty for a
+/// retry of the same order. Only what the client stated is compared: terms left to defaults,
+/// a notional's resolved quantity, a reduce-only order's cut-down quantity, a trailing stop's
+/// moving stop price and a market order's slippage limit are not, and a triggered stop
+/// matches its type.
+pub fn changed_terms<M>(order: &Order<M>, stated: &StatedTerms, req: &NewOrderRequest) -> Vec<&'static str> {
+    let mut changed = Vec::new();
+    if order.symbol != req.symbol {
+        changed.push("symbol");
+    }
+    if !order.side.eq_ignore_ascii_case(&req.side) {
+        changed.push("side");
+    }
+    if let Some(order_type) = req.order_type.as_deref().map(str::to_lowercase) {
+        if order.order_type != order_type && triggered_type(&order_type) != Some(order.order_type.as_str()) {
+            changed.push("orderType");
+        }
+    }
+    if !req.quantity.is_zero() && !req.reduce_only && order.quantity != req.quantity {
+        changed.push("quantity");
+    }
+    // A market order may have been given a protective limit from the account's slippage default
+    if order.price != req.price && (req.price.is_some() || order.order_type != "market") {
+        changed.push("price");
+    }
+    let trailing = req.trail_amount.is_some() || req.trail_percent.is_some();
+    if !trailing && req.stop_price.is_some() && order.stop_price != req.stop_price {
+        changed.push("stopPrice");
+    }
+    if order.trail_amount != req.trail_amount || order.trail_percent != req.trail_percent {
+        changed.push("trail");
+    }
+    if let Some(time_in_force) = req.time_in_force.as_deref().map(str::to_lowercase) {
+        if stated.time_in_force.as_ref() != Some(&time_in_force) {
+            changed.push("timeInForce");
+        }
+    }
+    // Stored to the microsecond
+    if order.expires_at != req.expires_at.map(|at| at.trunc_subsecs(6)) {
+        changed.push("expiresAt");
+    }
+    if order.display_quantity != req.display_quantity {
+        changed.push("displayQuantity");
+    }
+    if order.post_only != req.post_only {
+        changed.push("postOnly");
+    }
+    if order.reduce_only != req.reduce_only {
+        changed.push("reduceOnly");
+    }
+    if stated.notional != req.notional {
+        changed.push("notional");
+    }
+    if stated.trigger != req.trigger {
+        changed.push("trigger");
+    }
+    if stated.bracket != req.bracket {
+        changed.push("bracket");
+    }
+    if stated.oco_with != req.oco_with {
+        changed.push("ocoWith");
+    }
+    changed
+}
+
+/// Exits of a bracket order. Each is an opposite-side order for the full quantity under
+/// the client_order_id of the entry suffixed `:tp` or `:sl`; with both they are
+/// one-cancels-other. They wait until the entry fills completely and are cancelled with it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BracketSpec {
+    /// Limit price of the take-profit order
+    #[serde(alias = "take_profit", default)]
+    pub take_profit: Option<Decimal>,
+
+    /// Stop price of the stop-loss order
+    #[serde(alias = "stop_loss", default)]
+    pub stop_loss: Option<Decimal>,
+}
+
+/// Exits must sit on either side of the entry: above and below it for a buy, the other
+/// way round for a sell. `(code, reason)` like `validate_stop`.
+pub fn validate_bracket(req: &NewOrderRequest) -> Result<(), (&'static str, String)> {
+    let Some(bracket) = &req.bracket else {
+        return Ok(());
+    };
+    let invalid = |reason: String| Err(("INVALID_BRACKET", reason));
+
+    if bracket.take_profit.is_none() && bracket.stop_loss.is_none() {
+        return invalid("bracket needs a takeProfit or stopLoss".into());
+    }
+    if [bracket.take_profit, bracket.stop_loss].into_iter().flatten().any(|p| p <= Decimal::ZERO) {
+        return invalid("bracket prices must be positive".into());
+    }
+    if req.trigger.as_ref().is_some_and(|t| t.action == TriggerAction::Cancel) {
+        return invalid("bracket entries cannot carry a cancel trigger".into());
+    }
+
+    // Profit is above the entry for a buy; `sign` flips the comparisons for a sell
+    let sign = if req.side == "buy" { Decimal::ONE } else { Decimal::NEGATIVE_ONE };
+    let entry = req.price.or(req.stop_price);
+    if let (Some(tp), Some(entry)) = (bracket.take_profit, entry) {
+        if (tp - entry) * sign <= Decimal::ZERO {
+            return invalid(format!("takeProfit must be {} the entry price", if req.side == "buy" { "above" } else { "below" }));
+        }
+    }
+    if let (Some(sl), Some(entry)) = (bracket.stop_loss, entry) {
+        if (entry - sl) * sign <= Decimal::ZERO {
+            return invalid(format!("stopLoss must be {} the entry price", if req.side == "buy" { "below" } else { "above" }));
+        }
+    }
+    if let (Some(tp), Some(sl)) = (bracket.take_profit, bracket.stop_loss) {
+        if (tp - sl) * sign <= Decimal::ZERO {
+            return invalid("takeProfit and stopLoss are on the wrong sides of each other".into());
+        }
+    }
+    Ok(())
+}
+
+/// Cancel-replace of an open order: the replacement carries the new price and quantity
+/// under its own client_order_id
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendOrderRequest {
+    #[serde(alias = "order_id")]
+    pub order_id: Uuid,
+
+    /// Of the replacement; a retried amend with the same one returns it as a duplicate
+    #[serde(alias = "client_order_id", default = "generate_order_id")]
+    pub client_order_id: String,
+
+    /// New limit price; only orders with a limit price can change it
+    #[serde(default)]
+    pub price: Option<Decimal>,
+
+    /// New total quantity including what already filled, which stays with the replaced order
+    #[serde(default)]
+    pub quantity: Option<Decimal>,
+}
+
+/// Price and remaining quantity of the replacement, or why the amend is invalid
+pub fn amended_terms<M>(order: &Order<M>, req: &AmendOrderRequest) -> Result<(Option<Decimal>, Decimal), String> {
+    if req.price.is_none() && req.quantity.is_none() {
+        return Err("price or quantity is required".into());
+    }
+    if req.price.is_some() && order.price.is_none() {
+        return Err(format!("{} orders have no limit price to amend", order.order_type));
+    }
+    if req.price.is_some_and(|p| p <= Decimal::ZERO) {
+        return Err("price must be positive".into());
+    }
+
+    let quantity = req.quantity.unwrap_or(order.quantity);
+    if quantity <= order.filled_quantity {
+        return Err(format!("quantity must be above the filled {}", order.filled_quantity));
+    }
+    Ok((req.price.or(order.price), quantity - order.filled_quantity))
+}
+
+/// Check the stop price against the order type. Errors are `(code, reason)`: the rejection
+/// code and why.
+pub fn validate_stop(req: &NewOrderRequest) -> Result<(), (&'static str, String)> {
+    let order_type = req.order_type.as_deref().unwrap_or_default();
+    let invalid = |reason: &str| Err(("INVALID_STOP", reason.to_string()));
+
+    if order_type == "trailing_stop" {
+        return validate_trail(req).map_err(|reason| ("INVALID_STOP", reason.to_string()));
+    }
+    if req.trail_amount.is_some() || req.trail_percent.is_some() {
+        return invalid("trail_amount and trail_percent are only accepted on trailing_stop orders");
+    }
+
+    match (triggered_type(order_type), req.stop_price) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => invalid("stop_price is only accepted on stop and stop_limit orders"),
+        (Some(_), None) => invalid("stop and stop_limit orders need a stop_price"),
+        (Some(_), Some(stop)) if stop <= Decimal::ZERO => invalid("stop_price must be positive"),
+        (Some("limit"), Some(_)) if req.price.is_none() => invalid("stop_limit orders need a price"),
+        (Some("market"), Some(_)) if req.price.is_some() => invalid("stop orders take no price, use stop_limit"),
+        (Some(_), Some(_)) => Ok(()),
+    }
+}
+
+/// IOC and FOK orders execute when submitted or never, so nothing may hold them back:
+/// `(code, reason)` like `validate_stop`
+pub fn validate_time_in_force(req: &NewOrderRequest) -> Result<(), (&'static str, String)> {
+    if !executes_immediately(req.time_in_force.as_deref()) {
+        return Ok(());
+    }
+    if triggered_type(req.order_type.as_deref().unwrap_or_default()).is_some() {
+        return Err(("INVALID_TIME_IN_FORCE", "stop orders cannot be ioc or fok".into()));
+    }
+    if req.trigger.is_some() {
+        return Err(("INVALID_TIME_IN_FORCE", "ioc and fok orders cannot carry a trigger".into()));
+    }
+    Ok(())
+}
+
+/// A gtd order needs an expiry still ahead of `now`, and only gtd orders take one
+pub fn validate_expiry(req: &NewOrderRequest, now: DateTime<Utc>) -> Result<(), (&'static str, String)> {
+    let invalid = |reason: &str| Err(("INVALID_EXPIRY", reason.to_string()));
+
+    match (req.time_in_force.as_deref(), req.expires_at) {
+        (Some("gtd"), None) => invalid("gtd orders need an expires_at"),
+        (Some("gtd"), Some(expires_at)) if expires_at <= now => invalid("expires_at must be in the future"),
+        (Some("gtd"), Some(_)) | (_, None) => Ok(()),
+        (_, Some(_)) => invalid("expires_at is only accepted on gtd orders"),
+    }
+}
+
+/// Whether the time in force executes at submission and never rests in the book
+pub fn executes_immediately(time_in_force: Option<&str>) -> bool {
+    matches!(time_in_force, Some("ioc" | "fok"))
+}
+
+/// A trailing stop takes one positive trail and no prices: its stop price is set by the market
+pub fn validate_trail(req: &NewOrderRequest) -> Result<(), &'static str> {
+    if req.stop_price.is_some() {
+        return Err("trailing_stop orders take no stop_price, it follows the market");
+    }
+    if req.price.is_some() {
+        return Err("trailing_stop orders take no price");
+    }
+    match (req.trail_amount, req.trail_percent) {
+        (Some(_), Some(_)) | (None, None) => Err("trailing_stop orders need one of trail_amount and trail_percent"),
+        (Some(amount), None) if amount <= Decimal::ZERO => Err("trail_amount must be positive"),
+        (None, Some(pct)) if pct <= Decimal::ZERO || pct >= Decimal::ONE_HUNDRED => {
+            Err("trail_percent must be between 0 and 100")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Stop level a trail puts at `distance` from `best`: below it for sells, above it for buys.
+/// None when a sell trail is wider than the price itself.
+pub fn trail_stop(side: &str, amount: Option<Decimal>, percent: Option<Decimal>, best: Decimal) -> Option<Decimal> {
+    let distance = amount.or_else(|| percent.map(|pct| best * pct / Decimal::ONE_HUNDRED))?;
+    let stop = if side == "buy" { best + distance } else { best - distance };
+    let stop = stop.round_dp(8);
+    (stop > Decimal::ZERO).then_some(stop)
+}
+
+/// New stop price of a trailing stop after a tick at `tick_price`, if the tick moves it.
+/// Sell stops only ever rise and buy stops only ever fall.
+pub fn ratchet<M>(order: &Order<M>, tick_price: Decimal) -> Option<Decimal> {
+    if order.order_type != "trailing_stop" {
+        return None;
+    }
+    let stop = trail_stop(&order.side, order.trail_amount, order.trail_percent, tick_price)?;
+    match (order.side.as_str(), order.stop_price) {
+        (_, None) => Some(stop),
+        ("sell", Some(current)) => (stop > current).then_some(stop),
+        ("buy", Some(current)) => (stop < current).then_some(stop),
+        _ => None,
+    }
+}
+
+/// Order type a stop order becomes once triggered
+pub fn triggered_type(order_type: &str) -> Option<&'static str> {
+    match order_type {
+        "stop" | "trailing_stop" => Some("market"),
+        "stop_limit" => Some("limit"),
+        _ => None,
+    }
+}
+
+/// Whether a tick at `tick_price` triggers a stop order: buy stops trigger at or above
+/// their stop price, sell stops at or below it
+pub fn stop_triggered<M>(order: &Order<M>, tick_price: Decimal) -> bool {
+    if triggered_type(&order.order_type).is_none() {
+        return false;
+    }
+    match (order.side.as_str(), order.stop_price) {
+        ("buy", Some(stop)) => tick_price >= stop,
+        ("sell", Some(stop)) => tick_price <= stop,
+        _ => false,
+    }
+}
+
+/// An iceberg is a resting limit order below its full quantity: `(code, reason)` like
+/// `validate_stop`
+pub fn validate_display(req: &NewOrderRequest) -> Result<(), (&'static str, String)> {
+    let Some(display) = req.display_quantity else {
+        return Ok(());
+    };
+    let invalid = |reason: &str| Err(("INVALID_DISPLAY", reason.to_string()));
+
+    if req.order_type.as_deref() != Some("limit") {
+        return invalid("display_quantity is only accepted on limit orders");
+    }
+    if executes_immediately(req.time_in_force.as_deref()) {
+        return invalid("ioc and fok orders cannot be icebergs");
+    }
+    if display <= Decimal::ZERO || display >= req.quantity {
+        return invalid("display_quantity must be positive and below the order quantity");
+    }
+    Ok(())
+}
+
+/// A post-only order is a resting limit order whose price does not reach the last trade:
+/// `(code, reason)` like `validate_stop`. Without a last trade there is nothing to take.
+pub fn validate_post_only(req: &NewOrderRequest, last_price: Option<Decimal>) -> Result<(), (&'static str, String)> {
+    if !req.post_only {
+        return Ok(());
+    }
+    let invalid = |reason: &str| Err(("INVALID_POST_ONLY", reason.to_string()));
+
+    if req.order_type.as_deref() != Some("limit") {
+        return invalid("post_only is only accepted on limit orders");
+    }
+    if executes_immediately(req.time_in_force.as_deref()) {
+        return invalid("ioc and fok orders cannot be post_only");
+    }
+    // It would join the book later, at whatever the market is then
+    if req.trigger.is_some() {
+        return invalid("post_only orders cannot wait on a trigger");
+    }
+
+    match (req.price, last_price) {
+        (Some(limit), Some(last)) if crosses(&req.side, limit, last) => Err((
+            "POST_ONLY_WOULD_TAKE",
+            format!("Post-only order at {} would execute against the last trade at {}", limit, last),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The resting order a post-only `side` order of `account_id` at `limit` would take when it
+/// crosses internally, of those `OrderBook::crossing` gives for it: an open limit order of
+/// another account on the other side that internal crossing fills against
+pub fn post_only_contra<'a, M: 'a>(
+    account_id: Uuid,
+    side: &str,
+    limit: Decimal,
+    now: DateTime<Utc>,
+    crossing: impl IntoIterator<Item = &'a Order<M>>,
+) -> Option<&'a Order<M>> {
+    crossing.into_iter().find(|o| {
+        o.account_id != account_id
+            && o.side != side
+            && o.order_type == "limit"
+            && matches!(o.status.as_str(), "pending" | "partially_filled")
+            && !o.reduce_only
+            && o.strategy_id.is_none()
+            && o.expires_at.is_none_or(|expires_at| expires_at > now)
+            && o.price.is_some_and(|price| crosses(side, limit, price))
+    })
+}
+
+/// Quantity rules of an instrument, from its `instruments` row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LotSize {
+    /// Quantities are whole multiples of this
+    pub size: Decimal,
+    pub min_quantity: Decimal,
+}
+
+impl Default for LotSize {
+    /// An instrument without a row trades at the orders' precision
+    fn default() -> Self {
+        Self { size: Decimal::new(1, 8), min_quantity: Decimal::ZERO }
+    }
+}
+
+/// Give a notional order its base quantity: the notional over the order's limit (or stop)
+/// price, else the last trade, rounded down to whole lots so the order never spends more
+/// than the notional. `(code, reason)` like `validate_stop`.
+pub fn resolve_notional(
+    req: &mut NewOrderRequest,
+    last_price: Option<Decimal>,
+    lot: &LotSize,
+) -> Result<(), (&'static str, String)> {
+    let Some(notional) = req.notional else {
+        if req.quantity <= Decimal::ZERO {
+            return Err(("INVALID_QUANTITY", "quantity must be positive".into()));
+        }
+        return Ok(());
+    };
+    let invalid = |reason: &str| Err(("INVALID_NOTIONAL", reason.to_string()));
+
+    if !req.quantity.is_zero() {
+        return invalid("quantity and notional cannot both be given");
+    }
+    if notional <= Decimal::ZERO {
+        return invalid("notional must be positive");
+    }
+
+    let Some(price) = req.price.or(req.stop_price).or(last_price).filter(|p| *p > Decimal::ZERO) else {
+        return Err((
+            "NO_REFERENCE_PRICE",
+            format!("No price to convert the notional of a {} order at; give a limit price", req.symbol),
+        ));
+    };
+
+    let quantity = ((notional / price / lot.size).floor() * lot.size).normalize();
+    if quantity.is_zero() || quantity < lot.min_quantity {
+        return Err((
+            "NOTIONAL_BELOW_MIN_QUANTITY",
+            format!(
+                "Notional {} at {} comes to {}, below the minimum quantity of {}",
+                notional, price, quantity, lot.min_quantity.max(lot.size)
+            ),
+        ));
+    }
+    req.quantity = quantity;
+    Ok(())
+}
+
+/// Trading rules of an instrument, from its `instruments` row
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstrumentRules {
+    pub symbol: String,
+    /// Prices are whole multiples of this
+    pub tick_size: Decimal,
+    pub lot: LotSize,
+    pub max_quantity: Option<Decimal>,
+    /// Smallest quantity times price an order may have
+    pub min_notional: Option<Decimal>,
+    pub active: bool,
+}
+
+impl InstrumentRules {
+    /// A limit, stop or exit price off the tick grid is rejected
+    pub fn check_price(&self, label: &str, price: Decimal) -> Result<(), (&'static str, String)> {
+        if !(price % self.tick_size).is_zero() {
+            return Err((
+                "INVALID_TICK_SIZE",
+                format!("{} {} of {} is not a multiple of the tick size {}", label, price, self.symbol, self.tick_size),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Quantity in whole lots, within the instrument's minimum and maximum
+    pub fn check_quantity(&self, quantity: Decimal) -> Result<(), (&'static str, String)> {
+        if !(quantity % self.lot.size).is_zero() {
+            return Err((
+                "INVALID_LOT_SIZE",
+                format!("Quantity {} of {} is not a multiple of the lot size {}", quantity, self.symbol, self.lot.size),
+            ));
+        }
+        if quantity < self.lot.min_quantity {
+            return Err((
+                "BELOW_MIN_QUANTITY",
+                format!("Quantity {} is below the minimum of {} for {}", quantity, self.lot.min_quantity, self.symbol),
+            ));
+        }
+        match self.max_quantity {
+            Some(max) if quantity > max => Err((
+                "ABOVE_MAX_QUANTITY",
+                format!("Quantity {} is above the maximum of {} for {}", quantity, max, self.symbol),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check a new order against its instrument: trading enabled, prices on the tick grid,
+/// quantity in whole lots within bounds, and at least the minimum notional at the order's
+/// price, else the last trade. Run after `resolve_notional`, on the base quantity.
+/// `(code, reason)` like `validate_stop`.
+pub fn validate_instrument(
+    req: &NewOrderRequest,
+    rules: &InstrumentRules,
+    last_price: Option<Decimal>,
+) -> Result<(), (&'static str, String)> {
+    if !rules.active {
+        return Err(("INSTRUMENT_INACTIVE", format!("{} is not open for trading", rules.symbol)));
+    }
+
+    if let Some(price) = req.price {
+        rules.check_price("Price", price)?;
+    }
+    if let Some(stop) = req.stop_price {
+        rules.check_price("Stop price", stop)?;
+    }
+    if let Some(bracket) = &req.bracket {
+        if let Some(take_profit) = bracket.take_profit {
+            rules.check_price("Take-profit price", take_profit)?;
+        }
+        if let Some(stop_loss) = bracket.stop_loss {
+            rules.check_price("Stop-loss price", stop_loss)?;
+        }
+    }
+
+    rules.check_quantity(req.quantity)?;
+
+    // Without any price the notional is unknown until the order fills; the ledger hold
+    // still bounds it
+    let price = req.price.or(req.stop_price).or(last_price);
+    match (rules.min_notional, price) {
+        (Some(min), Some(price)) if req.quantity * price < min => Err((
+            "BELOW_MIN_NOTIONAL",
+            format!(
+                "Notional {} at {} is below the minimum of {} for {}",
+                (req.quantity * price).normalize(), price, min, rules.symbol
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// A reduce-only order closes a position it already holds; a bracket would open the
+/// opposite one on its exits
+pub fn validate_reduce_only(req: &NewOrderRequest) -> Result<(), (&'static str, String)> {
+    if req.reduce_only && req.bracket.is_some() {
+        return Err(("INVALID_REDUCE_ONLY", "reduce_only orders cannot carry a bracket".into()));
+    }
+    Ok(())
+}
+
+/// How much of `quantity` on `side` reduces a position of `net_quantity` without flipping
+/// it: sells reduce longs, buys reduce shorts. Zero when it would only add exposure.
+pub fn reducible_quantity(side: &str, quantity: Decimal, net_quantity: Decimal) -> Decimal {
+    let open = if side == "buy" { -net_quantity } else { net_quantity };
+    quantity.min(open).max(Decimal::ZERO)
+}
+
+/// Whether a limit order at `limit` executes on a trade at `price`: buys at or above it,
+/// sells at or below it
+pub fn crosses(side: &str, limit: Decimal, price: Decimal) -> bool {
+    if side == "buy" { price <= limit } else { price >= limit }
+}
+
+/// Quantity an order shows to the next tick. An iceberg shows what is left of its current
+/// slice, replenished from the hidden remainder each time a slice fills completely.
+pub fn visible_quantity<M>(order: &Order<M>) -> Decimal {
+    let remaining = order.quantity - order.filled_quantity;
+    match order.display_quantity {
+        Some(display) if display > Decimal::ZERO => (display - order.filled_quantity % display).min(remaining),
+        _ => remaining,
+    }
+}
+
+/// An order with the price and quantity a tick fills it at
+pub type AllocatedFill<M> = (Order<M>, Decimal, Decimal);
+
+/// Where an order queues for a tick, as the book matches them: limit bids highest first, then
+/// limit asks lowest first, then unpriced orders
+fn price_priority<M>(order: &Order<M>) -> (u8, Decimal) {
+    match (order.order_type.as_str(), order.side.as_str(), order.price) {
+        ("limit", "buy", Some(price)) => (0, -price),
+        ("limit", "sell", Some(price)) => (1, price),
+        _ => (2, Decimal::ZERO),
+    }
+}
+
+/// Split a tick's available volume across the orders it matched, best price first and oldest
+/// first at a price. Each takes what it shows or what is left; orders left with nothing wait
+/// for the next tick.
+pub fn allocate_volume<M>(
+    mut matched: Vec<(Order<M>, Decimal)>,
+    available: Option<Decimal>,
+) -> Vec<(Order<M>, Decimal, Decimal)> {
+    matched.sort_by_key(|(o, _)| (price_priority(o), o.created_at, o.id));
+
+    let mut left = available;
+    let mut fills = Vec::with_capacity(matched.len());
+    for (order, price) in matched {
+        let visible = visible_quantity(&order);
+        let quantity = left.map_or(visible, |left| visible.min(left));
+        if quantity <= Decimal::ZERO {
+            break;
+        }
+        if let Some(left) = left.as_mut() {
+            *left -= quantity;
+        }
+        fills.push((order, price, quantity));
+    }
+    fills
+}
+
+/// Split a tick's allocated fills into those that commit together and those that fill one at
+/// a time after them: reduce-only orders, sized by the position the fills before them leave,
+/// and an account's orders after its first, whose position and market-maker protection the
+/// first one moves. Both keep the allocation order.
+pub fn batch_fills<M>(fills: Vec<AllocatedFill<M>>) -> (Vec<AllocatedFill<M>>, Vec<AllocatedFill<M>>) {
+    let mut accounts = HashSet::new();
+    fills
+        .into_iter()
+        .partition(|(order, _, _)| !order.reduce_only && accounts.insert(order.account_id))
+}
+
+/// Resting orders a new limit order crosses in the internal book, with the price and
+/// quantity of each cross. Best price first and oldest first at a price; each cross takes
+/// what the resting order shows at its own limit price, until the new order is filled.
+/// Only open limit orders of other accounts on the other side can cross.
+pub fn plan_crosses<M>(incoming: &Order<M>, resting: Vec<Order<M>>) -> Vec<(Order<M>, Decimal, Decimal)> {
+    let Some(limit) = incoming.price.filter(|_| incoming.order_type == "limit") else {
+        return Vec::new();
+    };
+
+    let mut contra: Vec<(Order<M>, Decimal)> = resting
+        .into_iter()
+        .filter(|o| o.account_id != incoming.account_id && o.id != incoming.id && o.side != incoming.side)
+        .filter(|o| o.order_type == "limit" && matches!(o.status.as_str(), "pending" | "partially_filled"))
+        .filter_map(|o| o.price.filter(|price| crosses(&incoming.side, limit, *price)).map(|price| (o, price)))
+        .collect();
+    // Cheapest offers for a buy, highest bids for a sell
+    contra.sort_by(|(a, a_price), (b, b_price)| {
+        let by_price = if incoming.side == "buy" { a_price.cmp(b_price) } else { b_price.cmp(a_price) };
+        by_price.then((a.created_at, a.id).cmp(&(b.created_at, b.id)))
+    });
+
+    let mut left = incoming.quantity - incoming.filled_quantity;
+    let mut crosses = Vec::new();
+    for (order, price) in contra {
+        if left <= Decimal::ZERO {
+            break;
+        }
+        let quantity = visible_quantity(&order).min(left);
+        if quantity <= Decimal::ZERO {
+            continue;
+        }
+        left -= quantity;
+        crosses.push((order, price, quantity));
+    }
+    crosses
+}
+
+/// Average price of an order's fills once `quantity` more fills at `price`
+pub fn average_fill_price<M>(order: &Order<M>, quantity: Decimal, price: Decimal) -> Decimal {
+    let filled = order.avg_fill_price.unwrap_or_default() * order.filled_quantity;
+    ((filled + price * quantity) / (order.filled_quantity + quantity)).round_dp(8)
+}
+
+/// Check what must hold of an order in every state: its fills never run past its quantity and
+/// never below zero, and their average price is not negative. The first one broken, if any.
+pub fn check_order<M>(order: &Order<M>) -> Result<(), String> {
+    if order.filled_quantity < Decimal::ZERO {
+        return Err(format!("filled_quantity {} is negative", order.filled_quantity));
+    }
+    if order.filled_quantity > order.quantity {
+        return Err(format!("filled_quantity {} exceeds quantity {}", order.filled_quantity, order.quantity));
+    }
+    if let Some(avg) = order.avg_fill_price.filter(|avg| *avg < Decimal::ZERO) {
+        return Err(format!("avg_fill_price {} is negative", avg));
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! Positions
+//! Net positions and the weighted average price math that moves them on each fill
+
+use crate::fill::Fill;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Position {
+    pub account_id: Uuid,
+    pub symbol: String,
+    pub net_quantity: Decimal,
+    pub avg_price: Decimal,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub cost_basis: Decimal,
+    /// Fills and split adjustments applied since the position was opened (or its sandbox reset)
+    pub sequence: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Quantity, average price and realized P&L of a position after a fill, using weighted
+/// average rules
+pub fn calculate_new_position(pos: &Position, fill: &Fill) -> (Decimal, Decimal, Decimal) {
+    let fill_qty_signed = if fill.side == "buy" {
+        fill.quantity
+    } else {
+        -fill.quantity
+    };
+
+    let new_quantity = pos.net_quantity + fill_qty_signed;
+
+    // Helper: get sign multiplier for a decimal
+    let sign_multiplier = |d: Decimal| -> Decimal {
+        if d > dec!(0) { dec!(1) } else { dec!(-1) }
+    };
+
+    // Helper: check if same direction
+    let same_direction = (pos.net_quantity > dec!(0) && fill_qty_signed > dec!(0)) ||
+        (pos.net_quantity < dec!(0) && fill_qty_signed < dec!(0));
+
+    // Rule 1: Increasing position (same direction)
+    if same_direction {
+        let total_cost = pos.net_quantity.abs() * pos.avg_price + fill.quantity * fill.price;
+        let new_avg = total_cost / new_quantity.abs();
+        return (new_quantity, new_avg, dec!(0));
+    }
+
+    // Rule 2: Reducing position (opposite direction, same sign result)
+    let still_same_side = (pos.net_quantity > dec!(0) && new_quantity > dec!(0)) ||
+        (pos.net_quantity < dec!(0) && new_quantity < dec!(0));
+
+    if new_quantity != dec!(0) && still_same_side {
+        let realized = fill.quantity * (fill.price - pos.avg_price) * sign_multiplier(pos.net_quantity);
+        return (new_quantity, pos.avg_price, realized);
+    }
+
+    // Rule 3: Closing position exactly
+    if new_quantity == dec!(0) {
+        let realized = pos.net_quantity.abs() * (fill.price - pos.avg_price) * sign_multiplier(pos.net_quantity);
+        return (dec!(0), dec!(0), realized);
+    }
+
+    // Rule 4: Crossing zero (close old + open new)
+    let close_qty = pos.net_quantity.abs();
+    let realized = close_qty * (fill.price - pos.avg_price) * sign_multiplier(pos.net_quantity);
+    let new_avg = fill.price; // New position at fill price
+    (new_quantity, new_avg, realized)
+}
+
+/// Quantity, average price and realized P&L of a position opened by a fill
+pub fn calculate_new_position_from_zero(fill: &Fill) -> (Decimal, Decimal, Decimal) {
+    let qty = if fill.side == "buy" { fill.quantity } else { -fill.quantity };
+    (qty, fill.price, dec!(0))
+}
+
+/// Apply a fill to `pos` as the positions table stores it, each column rounded to its scale
+/// and the cost basis recomputed. Returns the P&L the fill realized.
+pub fn apply_fill(pos: &mut Position, fill: &Fill) -> Decimal {
+    let (quantity, avg_price, realized) = if pos.net_quantity.is_zero() {
+        calculate_new_position_from_zero(fill)
+    } else {
+        calculate_new_position(pos, fill)
+    };
+    pos.net_quantity = round_column(quantity);
+    pos.avg_price = round_column(avg_price);
+    pos.realized_pnl = round_column(pos.realized_pnl + realized);
+    pos.cost_basis = round_column(pos.net_quantity.abs() * pos.avg_price);
+    pos.sequence += 1;
+    debug_assert_eq!(check_position(pos), Ok(()), "position {} {} after a fill", pos.account_id, pos.symbol);
+    realized
+}
+
+/// Apply a stock split of `ratio` new shares per old one: the quantity grows by it and the
+/// average price shrinks by it, leaving the cost basis where it was up to rounding
+pub fn apply_split(pos: &mut Position, ratio: Decimal) {
+    pos.net_quantity = round_column(pos.net_quantity * ratio);
+    pos.avg_price = round_column(pos.avg_price / ratio);
+    pos.cost_basis = round_column(pos.net_quantity.abs() * pos.avg_price);
+    pos.sequence += 1;
+    debug_assert_eq!(check_position(pos), Ok(()), "position {} {} after a split", pos.account_id, pos.symbol);
+}
+
+/// Check what must hold of a position after every fill and split: its average price and cost
+/// basis are not negative, and a flat position carries no average price
+pub fn check_position(pos: &Position) -> Result<(), String> {
+    if pos.avg_price < Decimal::ZERO {
+        return Err(format!("avg_price {} is negative", pos.avg_price));
+    }
+    if pos.cost_basis < Decimal::ZERO {
+        return Err(format!("cost_basis {} is negative", pos.cost_basis));
+    }
+    if pos.net_quantity.is_zero() && !pos.avg_price.is_zero() {
+        return Err(format!("flat position has avg_price {}", pos.avg_price));
+    }
+    Ok(())
+}
+
+/// Round like a NUMERIC(20, 8) column does on write
+pub fn round_column(value: Decimal) -> Decimal {
+    value.round_dp_with_strategy(8, RoundingStrategy::MidpointAwayFromZero)
+}
@@ -0,0 +1,52 @@
+//! Rebates
+//! Maker rebates per fill, monthly volume-tier rebates and referral revenue shares, and the
+//! calendar months (UTC) they are settled over
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+
+/// Monthly traded notional from which a rebate rate applies
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RebateTier {
+    pub min_volume: Decimal,
+    pub bps: Decimal,
+}
+
+/// Limit orders rest in the book until a tick trades through them, so they add liquidity;
+/// market orders take it
+pub fn is_maker(order_type: &str) -> bool {
+    order_type == "limit"
+}
+
+/// `bps` basis points of `notional`, truncated to the ledger's precision so the house
+/// never credits more than the rate
+pub fn rebate_amount(notional: Decimal, bps: Decimal) -> Decimal {
+    (notional.abs() * bps / Decimal::from(10_000)).round_dp_with_strategy(8, RoundingStrategy::ToZero)
+}
+
+/// Highest tier `volume` reaches; it applies to the whole month's volume
+pub fn tier_for(tiers: &[RebateTier], volume: Decimal) -> Option<&RebateTier> {
+    tiers
+        .iter()
+        .filter(|t| volume >= t.min_volume)
+        .max_by(|a, b| a.min_volume.cmp(&b.min_volume))
+}
+
+/// First day of the month `date` falls in
+pub fn period_of(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("first of the month is valid")
+}
+
+/// Start and end of a monthly period
+pub fn period_bounds(period: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = period_of(period);
+    let end = start + Months::new(1);
+    let midnight = |d: NaiveDate| d.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc();
+    (midnight(start), midnight(end))
+}
+
+/// The last month that has ended by `now`
+pub fn last_closed_period(now: DateTime<Utc>) -> NaiveDate {
+    period_of(now.date_naive()) - Months::new(1)
+}
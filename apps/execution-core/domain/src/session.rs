@@ -0,0 +1,36 @@
+//! Trading Sessions
+//! The daily session close that day orders live until
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+
+/// A trading day closing at the same UTC time every day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradingSession {
+    pub close: NaiveTime,
+}
+
+impl TradingSession {
+    /// `HH:MM` or `HH:MM:SS` in UTC
+    pub fn parse(close: &str) -> Option<Self> {
+        NaiveTime::parse_from_str(close, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(close, "%H:%M:%S"))
+            .ok()
+            .map(|close| Self { close })
+    }
+
+    /// The latest close at or before `at`
+    pub fn last_close(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let today = at.date_naive().and_time(self.close).and_utc();
+        if today <= at { today } else { today - Duration::days(1) }
+    }
+
+    /// The first close after `at`
+    pub fn next_close(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        self.last_close(at) + Duration::days(1)
+    }
+
+    /// A day order placed at `created_at` is over once a close has passed since
+    pub fn day_order_closed(&self, created_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        created_at < self.last_close(now)
+    }
+}
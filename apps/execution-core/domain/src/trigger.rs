@@ -0,0 +1,100 @@
+//! Trigger Specs
+//! Conditions an order can be armed with to activate or cancel it when they fire
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Side of a reference line a price or fast average moves to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossDirection {
+    Above,
+    Below,
+}
+
+impl CrossDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CrossDirection::Above => "above",
+            CrossDirection::Below => "below",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerCondition {
+    /// Fires once the parent order has filled at least `filled_quantity` (default: all of it)
+    ParentFill {
+        #[serde(alias = "parentOrderId")]
+        parent_order_id: Uuid,
+        #[serde(alias = "filledQuantity", default)]
+        filled_quantity: Option<Decimal>,
+    },
+    /// Fires on the tick where the fast moving average crosses the slow one
+    MaCross {
+        /// Defaults to the order's symbol
+        #[serde(default)]
+        symbol: Option<String>,
+        #[serde(alias = "fastPeriod")]
+        fast_period: usize,
+        #[serde(alias = "slowPeriod")]
+        slow_period: usize,
+        direction: CrossDirection,
+    },
+    /// Fires on the first tick where the last price is above (or below) the session VWAP
+    PriceVwap {
+        #[serde(default)]
+        symbol: Option<String>,
+        direction: CrossDirection,
+    },
+}
+
+impl TriggerCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TriggerCondition::ParentFill { .. } => "parent_fill",
+            TriggerCondition::MaCross { .. } => "ma_cross",
+            TriggerCondition::PriceVwap { .. } => "price_vwap",
+        }
+    }
+
+    /// Symbol whose market data the condition is evaluated on
+    pub fn watched_symbol(&self) -> Option<&str> {
+        match self {
+            TriggerCondition::ParentFill { .. } => None,
+            TriggerCondition::MaCross { symbol, .. } | TriggerCondition::PriceVwap { symbol, .. } => {
+                symbol.as_deref()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerAction {
+    /// The order waits outside the book until the trigger fires
+    #[default]
+    Activate,
+    /// The order is live immediately and cancelled when the trigger fires
+    Cancel,
+}
+
+impl TriggerAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TriggerAction::Activate => "activate",
+            TriggerAction::Cancel => "cancel",
+        }
+    }
+}
+
+/// Trigger attached to a new order request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriggerSpec {
+    #[serde(flatten)]
+    pub condition: TriggerCondition,
+    #[serde(default)]
+    pub action: TriggerAction,
+}
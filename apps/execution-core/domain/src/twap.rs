@@ -0,0 +1,119 @@
+//! Time-Weighted Execution
+//! TWAP requests and the schedule that slices a parent quantity into child orders spread
+//! evenly over a duration, equal in size or weighted by volume (see `vwap`)
+
+use crate::order::generate_order_id;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_TWAP_SLICES: u32 = 1_000;
+
+/// Longest a TWAP may run
+pub const MAX_TWAP_DURATION_SECS: u32 = 7 * 86_400;
+
+/// Slice quantities keep the precision of the orders table
+const QUANTITY_SCALE: u32 = 8;
+
+/// How slice quantities are sized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionStrategy {
+    /// Equal slices
+    #[default]
+    Twap,
+    /// Slices in proportion to the symbol's historical volume over each slice's window
+    Vwap,
+}
+
+impl ExecutionStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Twap => "twap",
+            Self::Vwap => "vwap",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapRequest {
+    /// Child orders take this id suffixed with their 1-based slice number
+    #[serde(alias = "client_twap_id", default = "generate_order_id")]
+    pub client_twap_id: String,
+
+    pub symbol: String,
+    pub side: String,
+    pub quantity: Decimal,
+
+    /// Limit price of every slice; slices are market orders without one
+    #[serde(default)]
+    pub price: Option<Decimal>,
+
+    /// Time from the first slice to the end of the last slice's interval
+    #[serde(alias = "duration_secs")]
+    pub duration_secs: u32,
+
+    pub slices: u32,
+
+    #[serde(default)]
+    pub strategy: ExecutionStrategy,
+
+    /// Copied onto every child order
+    #[serde(alias = "tags", default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+pub fn validate_twap(req: &TwapRequest) -> Result<(), String> {
+    if req.client_twap_id.is_empty() || req.client_twap_id.len() > 90 {
+        return Err("clientTwapId must be 1 to 90 characters".into());
+    }
+    if req.side != "buy" && req.side != "sell" {
+        return Err("side must be buy or sell".into());
+    }
+    if req.quantity <= Decimal::ZERO {
+        return Err("quantity must be positive".into());
+    }
+    if matches!(req.price, Some(p) if p <= Decimal::ZERO) {
+        return Err("price must be positive".into());
+    }
+    if req.slices == 0 || req.slices > MAX_TWAP_SLICES {
+        return Err(format!("slices must be 1 to {}", MAX_TWAP_SLICES));
+    }
+    if req.duration_secs > MAX_TWAP_DURATION_SECS {
+        return Err(format!("durationSecs must be at most {}", MAX_TWAP_DURATION_SECS));
+    }
+    // The scheduler runs once a second, so slices closer together would bunch up
+    if req.duration_secs < req.slices {
+        return Err("durationSecs must allow at least one second per slice".into());
+    }
+    if slice_quantities(req.quantity, req.slices)[0].is_zero() {
+        return Err("quantity is too small to split into that many slices".into());
+    }
+    Ok(())
+}
+
+/// Equal slices truncated to the orders' precision; the last one takes the remainder so
+/// the slices always add up to `total`
+pub fn slice_quantities(total: Decimal, slices: u32) -> Vec<Decimal> {
+    if slices == 0 {
+        return Vec::new();
+    }
+
+    let each = (total / Decimal::from(slices)).trunc_with_scale(QUANTITY_SCALE);
+    let mut quantities = vec![each; slices as usize];
+    quantities[slices as usize - 1] = total - each * Decimal::from(slices - 1);
+    quantities
+}
+
+/// When slice `index` (0-based) is due: the first at `start`, the rest one interval apart
+pub fn slice_due_at(start: DateTime<Utc>, duration_secs: u32, slices: u32, index: u32) -> DateTime<Utc> {
+    let offset_ms = i64::from(duration_secs) * 1_000 * i64::from(index) / i64::from(slices.max(1));
+    start + Duration::milliseconds(offset_ms)
+}
+
+/// Client id of slice `index` (0-based)
+pub fn slice_client_id(client_twap_id: &str, index: u32) -> String {
+    format!("{}:{}", client_twap_id, index + 1)
+}
@@ -0,0 +1,90 @@
+//! Volume-Weighted Execution
+//! VWAP schedules that size each slice by the volume the symbol has traded in the same
+//! window on previous days, and the benchmark the execution is measured against
+
+use crate::twap::{slice_due_at, slice_quantities};
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+
+/// Longest history a volume profile is built from
+pub const MAX_PROFILE_DAYS: u32 = 60;
+
+/// Slice quantities keep the precision of the orders table
+const QUANTITY_SCALE: u32 = 8;
+
+/// Traded volume of a symbol over one bar
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeBar {
+    pub start: DateTime<Utc>,
+    pub volume: Decimal,
+    /// Sum of price times size of the trades in the bar
+    pub notional: Decimal,
+}
+
+/// Volume expected in each slice of a schedule starting at `start`: the volume of the bars
+/// that fell in the same slice window on each of the previous `lookback_days` days
+pub fn volume_profile(
+    bars: &[VolumeBar],
+    start: DateTime<Utc>,
+    duration_secs: u32,
+    slices: u32,
+    lookback_days: u32,
+) -> Vec<Decimal> {
+    let mut profile = vec![Decimal::ZERO; slices as usize];
+    if slices == 0 || duration_secs == 0 {
+        return profile;
+    }
+
+    let end = slice_due_at(start, duration_secs, slices, slices);
+    for bar in bars {
+        for days in 1..=i64::from(lookback_days) {
+            let shifted = bar.start + Duration::days(days);
+            if shifted < start || shifted >= end {
+                continue;
+            }
+            let offset_ms = (shifted - start).num_milliseconds();
+            let index = offset_ms * i64::from(slices) / (i64::from(duration_secs) * 1_000);
+            profile[(index as usize).min(slices as usize - 1)] += bar.volume;
+        }
+    }
+    profile
+}
+
+/// Split `total` in proportion to `weights`, truncated to the orders' precision; the last
+/// weighted slice takes the remainder so the slices always add up to `total`. Slices with
+/// no weight get nothing, and without any weight the split is even.
+pub fn weighted_quantities(total: Decimal, weights: &[Decimal]) -> Vec<Decimal> {
+    let weight_sum: Decimal = weights.iter().filter(|w| **w > Decimal::ZERO).sum();
+    if weight_sum.is_zero() {
+        return slice_quantities(total, weights.len() as u32);
+    }
+
+    let mut quantities: Vec<Decimal> = weights
+        .iter()
+        .map(|w| (total * w.max(&Decimal::ZERO) / weight_sum).trunc_with_scale(QUANTITY_SCALE))
+        .collect();
+
+    if let Some(last) = weights.iter().rposition(|w| *w > Decimal::ZERO) {
+        let others: Decimal = quantities.iter().enumerate().filter(|(i, _)| *i != last).map(|(_, q)| *q).sum();
+        quantities[last] = total - others;
+    }
+    quantities
+}
+
+/// Market VWAP over the bars, or None when nothing traded
+pub fn benchmark_vwap(bars: &[VolumeBar]) -> Option<Decimal> {
+    let volume: Decimal = bars.iter().map(|b| b.volume).sum();
+    let notional: Decimal = bars.iter().map(|b| b.notional).sum();
+    (volume > Decimal::ZERO).then(|| (notional / volume).round_dp(8))
+}
+
+/// Slippage of an average fill price against the benchmark in basis points, positive when
+/// the execution did worse: paid more on a buy or received less on a sell
+pub fn slippage_bps(side: &str, avg_fill_price: Decimal, benchmark: Decimal) -> Option<Decimal> {
+    if benchmark <= Decimal::ZERO {
+        return None;
+    }
+    let difference = if side == "buy" { avg_fill_price - benchmark } else { benchmark - avg_fill_price };
+    Some((difference / benchmark * Decimal::from(10_000)).round_dp(2))
+}
@@ -0,0 +1,183 @@
+//! Unit Tests for the Order Book
+//! How open orders are queued per symbol, which levels a tick or a new order reaches, and the
+//! depth the book shows
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::{Depth, DepthLevel, Order, OrderBook};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod book_tests {
+    use super::*;
+
+    fn order(n: u128, side: &str, order_type: &str, price: Option<Decimal>) -> Order {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        Order {
+            id: Uuid::from_u128(n),
+            account_id: Uuid::nil(),
+            client_order_id: format!("order-{}", n),
+            symbol: "AAPL".into(),
+            side: side.into(),
+            order_type: order_type.into(),
+            quantity: dec!(10),
+            price,
+            stop_price: None,
+            trail_amount: None,
+            trail_percent: None,
+            expires_at: None,
+            filled_quantity: Decimal::ZERO,
+            avg_fill_price: None,
+            status: "pending".into(),
+            created_at: at,
+            updated_at: at,
+            strategy_id: None,
+            metadata: None,
+            replaces_order_id: None,
+            group_id: None,
+            parent_order_id: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+        }
+    }
+
+    fn limit(n: u128, side: &str, price: Decimal) -> Order {
+        order(n, side, "limit", Some(price))
+    }
+
+    fn ids<'a>(orders: impl Iterator<Item = &'a Order>) -> Vec<u128> {
+        orders.map(|o| o.id.as_u128()).collect()
+    }
+
+    fn book(orders: Vec<Order>) -> OrderBook {
+        let mut book = OrderBook::new();
+        for order in orders {
+            book.insert(order);
+        }
+        book
+    }
+
+    #[test]
+    fn test_tick_reaches_only_marketable_levels() {
+        let book = book(vec![
+            limit(1, "buy", dec!(99)),
+            limit(2, "buy", dec!(101)),
+            limit(3, "buy", dec!(100)),
+            limit(4, "sell", dec!(100)),
+            limit(5, "sell", dec!(102)),
+            order(6, "sell", "market", None),
+        ]);
+
+        // Bids best first, asks best first, then unpriced orders
+        assert_eq!(ids(book.matching("AAPL", dec!(100))), vec![2, 3, 4, 6]);
+        assert_eq!(ids(book.matching("AAPL", dec!(103))), vec![4, 5, 6]);
+        assert!(book.matching("MSFT", dec!(100)).next().is_none());
+    }
+
+    #[test]
+    fn test_level_keeps_time_priority() {
+        let mut book = book(vec![limit(1, "sell", dec!(100)), limit(2, "sell", dec!(100)), limit(3, "sell", dec!(99))]);
+        assert_eq!(ids(book.crossing("AAPL", "buy", dec!(100))), vec![3, 1, 2]);
+
+        // A fill changes the order but not its place
+        let mut filled = limit(1, "sell", dec!(100));
+        filled.filled_quantity = dec!(4);
+        book.insert(filled);
+        assert_eq!(ids(book.crossing("AAPL", "buy", dec!(100))), vec![3, 1, 2]);
+        assert_eq!(book.get(&Uuid::from_u128(1)).unwrap().filled_quantity, dec!(4));
+
+        // A new price goes to the back of its new level
+        book.update(&Uuid::from_u128(3), |o| o.price = Some(dec!(100)));
+        assert_eq!(ids(book.crossing("AAPL", "buy", dec!(100))), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_crossing_reaches_the_other_side_only() {
+        let book = book(vec![
+            limit(1, "buy", dec!(99)),
+            limit(2, "buy", dec!(101)),
+            limit(3, "sell", dec!(100)),
+            limit(4, "sell", dec!(102)),
+        ]);
+
+        assert_eq!(ids(book.crossing("AAPL", "buy", dec!(101))), vec![3]);
+        assert_eq!(ids(book.crossing("AAPL", "sell", dec!(99))), vec![2, 1]);
+        assert!(book.crossing("AAPL", "sell", dec!(102)).next().is_none());
+    }
+
+    #[test]
+    fn test_stops_wait_unpriced_until_triggered() {
+        let mut book = book(vec![order(1, "sell", "stop_limit", Some(dec!(95))), limit(2, "buy", dec!(90))]);
+        assert_eq!(ids(book.unpriced("AAPL")), vec![1]);
+        assert!(book.crossing("AAPL", "buy", dec!(100)).next().is_none());
+
+        book.update(&Uuid::from_u128(1), |o| o.order_type = "limit".into());
+        assert!(book.unpriced("AAPL").next().is_none());
+        assert_eq!(ids(book.crossing("AAPL", "buy", dec!(100))), vec![1]);
+        assert_eq!(ids(book.symbol("AAPL")), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_remove_and_retain_empty_the_book() {
+        let mut book = book(vec![limit(1, "buy", dec!(99)), limit(2, "sell", dec!(100)), order(3, "buy", "market", None)]);
+
+        assert_eq!(book.remove(&Uuid::from_u128(1)).map(|o| o.id.as_u128()), Some(1));
+        assert!(book.remove(&Uuid::from_u128(1)).is_none());
+        assert_eq!(ids(book.matching("AAPL", dec!(99))), vec![3]);
+
+        book.retain(|o| o.side == "buy");
+        assert_eq!(book.len(), 1);
+        assert_eq!(ids(book.symbol("AAPL")), vec![3]);
+
+        book.retain(|_| false);
+        assert!(book.is_empty());
+        assert!(book.symbol("AAPL").next().is_none());
+    }
+
+    fn level(price: Decimal, quantity: Decimal, orders: usize) -> DepthLevel {
+        DepthLevel { price, quantity, orders }
+    }
+
+    #[test]
+    fn test_depth_aggregates_visible_quantity_per_level() {
+        let mut iceberg = limit(3, "buy", dec!(100));
+        iceberg.display_quantity = Some(dec!(2));
+        let mut partial = limit(4, "sell", dec!(101));
+        partial.filled_quantity = dec!(6);
+        let book = book(vec![
+            limit(1, "buy", dec!(99)),
+            limit(2, "buy", dec!(100)),
+            iceberg,
+            partial,
+            limit(5, "sell", dec!(102)),
+            order(6, "sell", "stop", None),
+        ]);
+
+        let depth = book.depth("AAPL", 10);
+        assert_eq!(depth.bids, vec![level(dec!(100), dec!(12), 2), level(dec!(99), dec!(10), 1)]);
+        assert_eq!(depth.asks, vec![level(dec!(101), dec!(4), 1), level(dec!(102), dec!(10), 1)]);
+
+        let top = book.depth("AAPL", 1);
+        assert_eq!((top.bids.len(), top.asks.len()), (1, 1));
+        assert!(book.depth("MSFT", 10).is_empty());
+    }
+
+    #[test]
+    fn test_changes_since_rebuild_the_book() {
+        let before = Depth {
+            bids: vec![level(dec!(100), dec!(5), 1), level(dec!(99), dec!(10), 2)],
+            asks: vec![level(dec!(101), dec!(3), 1)],
+        };
+        let after = Depth {
+            bids: vec![level(dec!(100), dec!(5), 1), level(dec!(98), dec!(1), 1)],
+            asks: vec![level(dec!(101), dec!(7), 2)],
+        };
+
+        let changes = after.changes_since(&before);
+        assert_eq!(changes.bids, vec![level(dec!(99), dec!(0), 0), level(dec!(98), dec!(1), 1)]);
+        assert_eq!(changes.asks, vec![level(dec!(101), dec!(7), 2)]);
+        assert!(after.changes_since(&after).is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+//! Unit Tests for Generated Client Ids
+//! Requests without a client id take one from the source the engine installs
+
+use enthropic_domain::order::{set_client_id_source, AmendOrderRequest};
+use enthropic_domain::NewOrderRequest;
+use std::sync::atomic::{AtomicU64, Ordering};
+use uuid::Uuid;
+
+#[cfg(test)]
+mod client_id_tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_client_ids_come_from_the_installed_source() {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        set_client_id_source(|| Uuid::from_u64_pair(0, NEXT.fetch_add(1, Ordering::Relaxed)));
+        // Only the first source installed is used
+        set_client_id_source(Uuid::new_v4);
+
+        let order: NewOrderRequest =
+            serde_json::from_value(serde_json::json!({ "symbol": "AAPL", "side": "buy", "quantity": "1" })).unwrap();
+        let amend: AmendOrderRequest = serde_json::from_value(serde_json::json!({ "orderId": Uuid::nil() })).unwrap();
+
+        assert_eq!(order.client_order_id, Uuid::from_u64_pair(0, 1).to_string());
+        assert_eq!(amend.client_order_id, Uuid::from_u64_pair(0, 2).to_string());
+
+        // A client id the request states is kept
+        let stated: NewOrderRequest = serde_json::from_value(
+            serde_json::json!({ "clientOrderId": "mine", "symbol": "AAPL", "side": "buy", "quantity": "1" }),
+        )
+            .unwrap();
+        assert_eq!(stated.client_order_id, "mine");
+    }
+}
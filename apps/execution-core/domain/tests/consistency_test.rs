@@ -0,0 +1,174 @@
+//! Unit Tests for Invariants and Cache Consistency
+//! What every order and position must satisfy, and how cached ones are compared with their rows
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::consistency::{compare_orders, compare_positions};
+use enthropic_domain::order::check_order;
+use enthropic_domain::position::{apply_fill, check_position};
+use enthropic_domain::{Fill, Order, OrderBook, Position};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod consistency_tests {
+    use super::*;
+
+    fn order(id: u128, quantity: Decimal, filled: Decimal) -> Order {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        Order {
+            id: Uuid::from_u128(id),
+            account_id: Uuid::from_u128(100),
+            client_order_id: format!("c-{}", id),
+            symbol: "AAPL".into(),
+            side: "buy".into(),
+            order_type: "limit".into(),
+            quantity,
+            price: Some(dec!(100)),
+            stop_price: None,
+            trail_amount: None,
+            trail_percent: None,
+            expires_at: None,
+            filled_quantity: filled,
+            avg_fill_price: (!filled.is_zero()).then_some(dec!(100)),
+            status: if filled.is_zero() { "pending" } else { "partially_filled" }.into(),
+            created_at: at,
+            updated_at: at,
+            strategy_id: None,
+            metadata: None,
+            replaces_order_id: None,
+            group_id: None,
+            parent_order_id: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+        }
+    }
+
+    fn position(account: u128, net_quantity: Decimal, avg_price: Decimal) -> Position {
+        Position {
+            account_id: Uuid::from_u128(account),
+            symbol: "AAPL".into(),
+            net_quantity,
+            avg_price,
+            realized_pnl: Decimal::ZERO,
+            unrealized_pnl: Decimal::ZERO,
+            cost_basis: net_quantity.abs() * avg_price,
+            sequence: 1,
+            updated_at: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+        }
+    }
+
+    fn fill(side: &str, quantity: Decimal, price: Decimal) -> Fill {
+        Fill { account_id: Uuid::nil(), symbol: "AAPL".into(), side: side.into(), quantity, price, key: None }
+    }
+
+    #[test]
+    fn test_order_invariants() {
+        assert_eq!(check_order(&order(1, dec!(10), dec!(4))), Ok(()));
+        assert_eq!(check_order(&order(1, dec!(10), dec!(10))), Ok(()));
+
+        let overfilled = order(1, dec!(10), dec!(10.5));
+        assert!(check_order(&overfilled).unwrap_err().contains("exceeds quantity"));
+        let negative = order(1, dec!(10), dec!(-1));
+        assert!(check_order(&negative).unwrap_err().contains("negative"));
+
+        let mut bad_price = order(1, dec!(10), dec!(2));
+        bad_price.avg_fill_price = Some(dec!(-0.01));
+        assert!(check_order(&bad_price).unwrap_err().contains("avg_fill_price"));
+    }
+
+    #[test]
+    fn test_position_invariants() {
+        assert_eq!(check_position(&position(1, dec!(-5), dec!(100))), Ok(()));
+        assert_eq!(check_position(&position(1, dec!(0), dec!(0))), Ok(()));
+
+        assert!(check_position(&position(1, dec!(5), dec!(-1))).unwrap_err().contains("avg_price"));
+        assert!(check_position(&position(1, dec!(0), dec!(100))).unwrap_err().contains("flat"));
+
+        // The fill math keeps them through a flip and a close
+        let mut pos = position(1, dec!(0), dec!(0));
+        for (side, quantity, price) in [("buy", dec!(3), dec!(100)), ("sell", dec!(5), dec!(90)), ("buy", dec!(2), dec!(80))] {
+            apply_fill(&mut pos, &fill(side, quantity, price));
+            assert_eq!(check_position(&pos), Ok(()));
+        }
+        assert!(pos.net_quantity.is_zero());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "entering the book")]
+    fn test_book_rejects_overfilled_orders_in_debug_builds() {
+        let mut book: OrderBook = OrderBook::new();
+        book.insert(order(1, dec!(10), dec!(11)));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "after an update")]
+    fn test_book_update_checks_the_changed_order() {
+        let mut book: OrderBook = OrderBook::new();
+        book.insert(order(1, dec!(10), dec!(4)));
+        book.update(&Uuid::from_u128(1), |o| o.quantity = dec!(3));
+    }
+
+    #[test]
+    fn test_matching_cache_has_no_mismatches() {
+        let rows = vec![order(1, dec!(10), dec!(0)), order(2, dec!(5), dec!(2))];
+        let mut book: OrderBook = OrderBook::new();
+        for row in rows.clone() {
+            book.insert(row);
+        }
+        assert!(compare_orders(book.values(), &rows).is_empty());
+
+        // Scale of a decimal is not a difference
+        let mut rescaled = rows.clone();
+        rescaled[0].quantity = dec!(10.00000000);
+        assert!(compare_orders(book.values(), &rescaled).is_empty());
+    }
+
+    #[test]
+    fn test_order_mismatches_are_named() {
+        let cached = [order(1, dec!(10), dec!(4)), order(2, dec!(5), dec!(0))];
+        let mut stale = order(1, dec!(10), dec!(6));
+        stale.avg_fill_price = Some(dec!(101));
+        let rows = vec![stale, order(3, dec!(1), dec!(0))];
+
+        let mismatches = compare_orders(cached.iter(), &rows);
+        let found: Vec<(String, &str)> = mismatches.iter().map(|m| (m.key.clone(), m.field)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (Uuid::from_u128(1).to_string(), "filled_quantity"),
+                (Uuid::from_u128(1).to_string(), "avg_fill_price"),
+                (Uuid::from_u128(2).to_string(), "extra"),
+                (Uuid::from_u128(3).to_string(), "missing"),
+            ]
+        );
+        assert_eq!((mismatches[0].cached.as_str(), mismatches[0].stored.as_str()), ("4", "6"));
+    }
+
+    #[test]
+    fn test_position_mismatches_are_named() {
+        let cached = [position(1, dec!(5), dec!(100)), position(2, dec!(0), dec!(7))];
+        let mut moved = position(1, dec!(8), dec!(100));
+        moved.sequence = 2;
+        let rows = vec![moved, position(3, dec!(-2), dec!(50))];
+
+        let fields: Vec<(&str, &str)> = compare_positions(cached.iter(), &rows)
+            .iter()
+            .map(|m| (m.kind, m.field))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("position", "net_quantity"),
+                ("position", "cost_basis"),
+                ("position", "sequence"),
+                ("position", "invariant"),
+                ("position", "extra"),
+                ("position", "missing"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Unit Tests for Internal Crossing
+//! Which resting orders a new limit order crosses, in what order and for how much
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::order::plan_crosses;
+use enthropic_domain::Order;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod crossing_tests {
+    use super::*;
+
+    fn order(account: u128, side: &str, price: Decimal, quantity: Decimal, minute: u32) -> Order {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, minute, 0).unwrap();
+        Order {
+            id: Uuid::new_v4(),
+            account_id: Uuid::from_u128(account),
+            client_order_id: format!("order-{}", minute),
+            symbol: "AAPL".into(),
+            side: side.into(),
+            order_type: "limit".into(),
+            quantity,
+            price: Some(price),
+            stop_price: None,
+            trail_amount: None,
+            trail_percent: None,
+            expires_at: None,
+            filled_quantity: Decimal::ZERO,
+            avg_fill_price: None,
+            status: "pending".into(),
+            created_at: at,
+            updated_at: at,
+            strategy_id: None,
+            metadata: None,
+            replaces_order_id: None,
+            group_id: None,
+            parent_order_id: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+        }
+    }
+
+    fn planned(crosses: &[(Order, Decimal, Decimal)]) -> Vec<(String, Decimal, Decimal)> {
+        crosses
+            .iter()
+            .map(|(o, price, quantity)| (o.client_order_id.clone(), *price, *quantity))
+            .collect()
+    }
+
+    #[test]
+    fn test_best_price_first_at_resting_price() {
+        let buy = order(1, "buy", dec!(101), dec!(15), 30);
+        let resting = vec![
+            order(2, "sell", dec!(100.5), dec!(10), 1),
+            order(3, "sell", dec!(99.5), dec!(10), 2),
+        ];
+
+        assert_eq!(
+            planned(&plan_crosses(&buy, resting)),
+            vec![("order-2".into(), dec!(99.5), dec!(10)), ("order-1".into(), dec!(100.5), dec!(5))]
+        );
+    }
+
+    #[test]
+    fn test_oldest_first_at_same_price() {
+        let sell = order(1, "sell", dec!(100), dec!(5), 30);
+        let resting = vec![
+            order(2, "buy", dec!(100), dec!(10), 7),
+            order(3, "buy", dec!(100), dec!(10), 3),
+            order(4, "buy", dec!(100.5), dec!(2), 9),
+        ];
+
+        assert_eq!(
+            planned(&plan_crosses(&sell, resting)),
+            vec![("order-9".into(), dec!(100.5), dec!(2)), ("order-3".into(), dec!(100), dec!(3))]
+        );
+    }
+
+    #[test]
+    fn test_same_account_and_same_side_never_cross() {
+        let buy = order(1, "buy", dec!(101), dec!(10), 30);
+        let resting = vec![
+            order(1, "sell", dec!(100), dec!(10), 1),
+            order(2, "buy", dec!(100), dec!(10), 2),
+        ];
+
+        assert!(plan_crosses(&buy, resting).is_empty());
+    }
+
+    #[test]
+    fn test_prices_that_do_not_meet_are_left() {
+        let buy = order(1, "buy", dec!(100), dec!(10), 30);
+        let resting = vec![order(2, "sell", dec!(100.01), dec!(10), 1)];
+        assert!(plan_crosses(&buy, resting).is_empty());
+
+        // Only open limit orders rest in the book
+        let mut stop = order(2, "sell", dec!(99), dec!(10), 1);
+        stop.order_type = "stop_limit".into();
+        let mut filled = order(3, "sell", dec!(99), dec!(10), 2);
+        filled.status = "filled".into();
+        assert!(plan_crosses(&buy, vec![stop, filled]).is_empty());
+
+        let mut market = order(1, "buy", dec!(100), dec!(10), 30);
+        market.order_type = "market".into();
+        assert!(plan_crosses(&market, vec![order(2, "sell", dec!(99), dec!(10), 1)]).is_empty());
+    }
+
+    #[test]
+    fn test_iceberg_crosses_only_its_visible_slice() {
+        let buy = order(1, "buy", dec!(100), dec!(50), 30);
+        let mut iceberg = order(2, "sell", dec!(100), dec!(100), 1);
+        iceberg.display_quantity = Some(dec!(10));
+        iceberg.filled_quantity = dec!(4);
+
+        assert_eq!(planned(&plan_crosses(&buy, vec![iceberg])), vec![("order-1".into(), dec!(100), dec!(6))]);
+    }
+
+    #[test]
+    fn test_partly_filled_order_crosses_what_is_left() {
+        let mut buy = order(1, "buy", dec!(100), dec!(10), 30);
+        buy.filled_quantity = dec!(8);
+
+        assert_eq!(
+            planned(&plan_crosses(&buy, vec![order(2, "sell", dec!(100), dec!(10), 1)])),
+            vec![("order-1".into(), dec!(100), dec!(2))]
+        );
+    }
+}
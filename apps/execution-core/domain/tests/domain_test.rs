@@ -0,0 +1,160 @@
+//! Unit Tests for the Domain Library
+//! Position math and order validation exactly as the engine runs them
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::order::{allocate_volume, average_fill_price, validate_bracket, validate_stop};
+use enthropic_domain::position::{calculate_new_position, calculate_new_position_from_zero, round_column};
+use enthropic_domain::{BracketSpec, Fill, NewOrderRequest, Order, Position};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod domain_tests {
+    use super::*;
+
+    fn fill(side: &str, quantity: Decimal, price: Decimal) -> Fill {
+        Fill {
+            account_id: Uuid::nil(),
+            symbol: "AAPL".into(),
+            side: side.into(),
+            quantity,
+            price,
+            key: None,
+        }
+    }
+
+    fn position(net_quantity: Decimal, avg_price: Decimal) -> Position {
+        Position {
+            account_id: Uuid::nil(),
+            symbol: "AAPL".into(),
+            net_quantity,
+            avg_price,
+            realized_pnl: Decimal::ZERO,
+            unrealized_pnl: Decimal::ZERO,
+            cost_basis: net_quantity.abs() * avg_price,
+            sequence: 1,
+            updated_at: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+        }
+    }
+
+    fn order(quantity: Decimal, filled_quantity: Decimal, minute: u32) -> Order {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, minute, 0).unwrap();
+        Order {
+            id: Uuid::new_v4(),
+            account_id: Uuid::nil(),
+            client_order_id: format!("order-{}", minute),
+            symbol: "AAPL".into(),
+            side: "buy".into(),
+            order_type: "limit".into(),
+            quantity,
+            price: Some(dec!(100)),
+            stop_price: None,
+            trail_amount: None,
+            trail_percent: None,
+            expires_at: None,
+            filled_quantity,
+            avg_fill_price: (filled_quantity > Decimal::ZERO).then_some(dec!(100)),
+            status: "pending".into(),
+            created_at: at,
+            updated_at: at,
+            strategy_id: None,
+            metadata: None,
+            replaces_order_id: None,
+            group_id: None,
+            parent_order_id: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+        }
+    }
+
+    fn request(side: &str, order_type: &str, price: Option<Decimal>, stop_price: Option<Decimal>) -> NewOrderRequest {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "AAPL",
+            "side": side,
+            "orderType": order_type,
+            "quantity": "10",
+            "price": price,
+            "stopPrice": stop_price,
+        }))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_adding_to_a_position_averages_its_price() {
+        let after = calculate_new_position(&position(dec!(100), dec!(50)), &fill("buy", dec!(100), dec!(60)));
+        assert_eq!(after, (dec!(200), dec!(55), dec!(0)));
+    }
+
+    #[test]
+    fn test_reducing_and_closing_realize_pnl() {
+        let long = position(dec!(100), dec!(50));
+        assert_eq!(calculate_new_position(&long, &fill("sell", dec!(40), dec!(60))), (dec!(60), dec!(50), dec!(400)));
+        assert_eq!(calculate_new_position(&long, &fill("sell", dec!(100), dec!(45))), (dec!(0), dec!(0), dec!(-500)));
+
+        let short = position(dec!(-100), dec!(50));
+        assert_eq!(calculate_new_position(&short, &fill("buy", dec!(100), dec!(40))), (dec!(0), dec!(0), dec!(1000)));
+    }
+
+    #[test]
+    fn test_crossing_zero_opens_at_the_fill_price() {
+        let after = calculate_new_position(&position(dec!(100), dec!(50)), &fill("sell", dec!(150), dec!(55)));
+        assert_eq!(after, (dec!(-50), dec!(55), dec!(500)));
+        assert_eq!(calculate_new_position_from_zero(&fill("sell", dec!(5), dec!(10))), (dec!(-5), dec!(10), dec!(0)));
+    }
+
+    #[test]
+    fn test_round_column_matches_numeric_scale() {
+        assert_eq!(round_column(dec!(1.000000005)), dec!(1.00000001));
+        assert_eq!(round_column(dec!(-1.000000005)), dec!(-1.00000001));
+    }
+
+    #[test]
+    fn test_fills_average_and_allocate_oldest_first() {
+        let partly_filled = order(dec!(10), dec!(4), 0);
+        assert_eq!(average_fill_price(&partly_filled, dec!(6), dec!(110)), dec!(106));
+
+        let newer = order(dec!(5), dec!(0), 2);
+        let older = order(dec!(5), dec!(0), 1);
+        let fills = allocate_volume(vec![(newer.clone(), dec!(100)), (older.clone(), dec!(100))], Some(dec!(7)));
+        let allocated: Vec<_> = fills.iter().map(|(o, _, q)| (o.id, *q)).collect();
+        assert_eq!(allocated, vec![(older.id, dec!(5)), (newer.id, dec!(2))]);
+    }
+
+    #[test]
+    fn test_allocation_takes_the_better_price_before_the_older_order() {
+        // A tick at 99 matches both bids but has volume for less than the two together
+        let older_lower = Order { price: Some(dec!(100)), ..order(dec!(5), dec!(0), 1) };
+        let newer_higher = Order { price: Some(dec!(101)), ..order(dec!(5), dec!(0), 2) };
+        let fills = allocate_volume(
+            vec![(older_lower.clone(), dec!(99)), (newer_higher.clone(), dec!(99))],
+            Some(dec!(7)),
+        );
+        let allocated: Vec<_> = fills.iter().map(|(o, _, q)| (o.id, *q)).collect();
+        assert_eq!(allocated, vec![(newer_higher.id, dec!(5)), (older_lower.id, dec!(2))]);
+
+        // Asks the other way round: the lower offer goes first
+        let ask = |price, minute| Order { side: "sell".into(), price: Some(price), ..order(dec!(5), dec!(0), minute) };
+        let (older_higher, newer_lower) = (ask(dec!(101), 1), ask(dec!(100), 2));
+        let fills = allocate_volume(
+            vec![(older_higher.clone(), dec!(102)), (newer_lower.clone(), dec!(102))],
+            Some(dec!(7)),
+        );
+        let allocated: Vec<_> = fills.iter().map(|(o, _, q)| (o.id, *q)).collect();
+        assert_eq!(allocated, vec![(newer_lower.id, dec!(5)), (older_higher.id, dec!(2))]);
+    }
+
+    #[test]
+    fn test_order_validation() {
+        assert!(validate_stop(&request("buy", "stop", None, Some(dec!(105)))).is_ok());
+        assert_eq!(validate_stop(&request("buy", "stop", None, None)).unwrap_err().0, "INVALID_STOP");
+        assert_eq!(validate_stop(&request("buy", "limit", Some(dec!(100)), Some(dec!(99)))).unwrap_err().0, "INVALID_STOP");
+
+        let mut entry = request("buy", "limit", Some(dec!(100)), None);
+        entry.bracket = Some(BracketSpec { take_profit: Some(dec!(110)), stop_loss: Some(dec!(95)) });
+        assert!(validate_bracket(&entry).is_ok());
+        entry.bracket = Some(BracketSpec { take_profit: Some(dec!(90)), stop_loss: None });
+        assert_eq!(validate_bracket(&entry).unwrap_err().0, "INVALID_BRACKET");
+    }
+}
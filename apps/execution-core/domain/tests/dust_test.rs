@@ -0,0 +1,38 @@
+//! Unit Tests for Dust
+//! Which holdings are dust, what they are worth and how a dusty position is closed
+
+use enthropic_domain::dust::{closing_side, dust_value, is_dust};
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod dust_tests {
+    use super::*;
+
+    #[test]
+    fn test_below_threshold_is_dust() {
+        assert!(is_dust(dec!(0.0001), dec!(40000), dec!(5)));
+        assert!(is_dust(dec!(-0.0001), dec!(40000), dec!(5)));
+        assert!(!is_dust(dec!(0.001), dec!(40000), dec!(5)));
+        // Worth exactly the threshold is not dust
+        assert!(!is_dust(dec!(0.000125), dec!(40000), dec!(5)));
+    }
+
+    #[test]
+    fn test_flat_unpriced_or_disabled_is_not_dust() {
+        assert!(!is_dust(dec!(0), dec!(40000), dec!(5)));
+        assert!(!is_dust(dec!(0.0001), dec!(0), dec!(5)));
+        assert!(!is_dust(dec!(0.0001), dec!(40000), dec!(0)));
+    }
+
+    #[test]
+    fn test_value_truncates_to_ledger_precision() {
+        assert_eq!(dust_value(dec!(0.00012345), dec!(3.33333333)), dec!(0.00041149));
+        assert_eq!(dust_value(dec!(-2), dec!(1.5)), dec!(3));
+    }
+
+    #[test]
+    fn test_closing_side() {
+        assert_eq!(closing_side(dec!(0.5)), "sell");
+        assert_eq!(closing_side(dec!(-0.5)), "buy");
+    }
+}
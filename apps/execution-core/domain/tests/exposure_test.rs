@@ -0,0 +1,87 @@
+//! Unit Tests for Time-Weighted Exposure
+//! How holding a quantity accrues per session and averages over it
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use enthropic_domain::exposure::{accrue, average_quantity, session_of, session_start};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod exposure_tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_within_one_session() {
+        let accruals = accrue(dec!(10), at(1, 9), at(1, 15));
+        assert_eq!(accruals.len(), 1);
+        assert_eq!(accruals[0].session, day(1));
+        assert_eq!(accruals[0].held_seconds, dec!(21600));
+        assert_eq!(accruals[0].gross_quantity_seconds, dec!(216000));
+        assert_eq!(accruals[0].net_quantity_seconds, dec!(216000));
+    }
+
+    #[test]
+    fn test_short_position_offsets_net_only() {
+        let accruals = accrue(dec!(-4), at(1, 0), at(1, 1));
+        assert_eq!(accruals[0].gross_quantity_seconds, dec!(14400));
+        assert_eq!(accruals[0].net_quantity_seconds, dec!(-14400));
+    }
+
+    #[test]
+    fn test_split_at_session_boundaries() {
+        let accruals = accrue(dec!(1), at(1, 18), at(3, 6));
+        let sessions: Vec<_> = accruals.iter().map(|a| (a.session, a.held_seconds)).collect();
+        assert_eq!(sessions, vec![
+            (day(1), dec!(21600)),
+            (day(2), dec!(86400)),
+            (day(3), dec!(21600)),
+        ]);
+    }
+
+    #[test]
+    fn test_flat_position_accrues_time_but_no_exposure() {
+        let accruals = accrue(Decimal::ZERO, at(1, 0), at(1, 12));
+        assert_eq!(accruals[0].held_seconds, Decimal::ZERO);
+        assert_eq!(accruals[0].gross_quantity_seconds, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_empty_or_backwards_interval() {
+        assert!(accrue(dec!(5), at(1, 12), at(1, 12)).is_empty());
+        assert!(accrue(dec!(5), at(1, 12), at(1, 6)).is_empty());
+    }
+
+    #[test]
+    fn test_millisecond_precision() {
+        let from = at(1, 12);
+        let accruals = accrue(dec!(2), from, from + Duration::milliseconds(1_500));
+        assert_eq!(accruals[0].held_seconds, dec!(1.5));
+        assert_eq!(accruals[0].gross_quantity_seconds, dec!(3.0));
+    }
+
+    #[test]
+    fn test_average_over_session() {
+        // 10 held for 6 hours, then 20 for 18 hours
+        let gross: Decimal = accrue(dec!(10), at(1, 0), at(1, 6))
+            .into_iter()
+            .chain(accrue(dec!(20), at(1, 6), at(2, 0)))
+            .map(|a| a.gross_quantity_seconds)
+            .sum();
+        assert_eq!(average_quantity(gross, dec!(86400)), dec!(17.5));
+        assert_eq!(average_quantity(gross, Decimal::ZERO), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_sessions_are_utc_days() {
+        assert_eq!(session_of(at(1, 23)), day(1));
+        assert_eq!(session_start(day(2)), at(2, 0));
+    }
+}
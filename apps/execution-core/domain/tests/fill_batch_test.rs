@@ -0,0 +1,91 @@
+//! Unit Tests for Batched Tick Fills
+//! Which of a tick's fills commit together and which fill one at a time after them
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::order::{allocate_volume, batch_fills};
+use enthropic_domain::Order;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod fill_batch_tests {
+    use super::*;
+
+    fn order(account: u128, minute: u32, reduce_only: bool) -> Order {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, minute, 0).unwrap();
+        Order {
+            id: Uuid::new_v4(),
+            account_id: Uuid::from_u128(account),
+            client_order_id: format!("order-{}", minute),
+            symbol: "AAPL".into(),
+            side: "buy".into(),
+            order_type: "limit".into(),
+            quantity: dec!(10),
+            price: Some(dec!(100)),
+            stop_price: None,
+            trail_amount: None,
+            trail_percent: None,
+            expires_at: None,
+            filled_quantity: Decimal::ZERO,
+            avg_fill_price: None,
+            status: "pending".into(),
+            created_at: at,
+            updated_at: at,
+            strategy_id: None,
+            metadata: None,
+            replaces_order_id: None,
+            group_id: None,
+            parent_order_id: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only,
+        }
+    }
+
+    fn minutes(fills: &[(Order, Decimal, Decimal)]) -> Vec<u32> {
+        fills.iter().map(|(o, _, _)| o.client_order_id[6..].parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_distinct_accounts_commit_together() {
+        let matched = vec![(order(1, 0, false), dec!(100)), (order(2, 1, false), dec!(100)), (order(3, 2, false), dec!(100))];
+        let (batch, singles) = batch_fills(allocate_volume(matched, None));
+
+        assert_eq!(minutes(&batch), vec![0, 1, 2]);
+        assert!(singles.is_empty());
+    }
+
+    #[test]
+    fn test_later_orders_of_an_account_fill_alone() {
+        let matched = vec![
+            (order(1, 2, false), dec!(100)),
+            (order(2, 1, false), dec!(100)),
+            (order(1, 0, false), dec!(100)),
+        ];
+        let (batch, singles) = batch_fills(allocate_volume(matched, None));
+
+        // The account's oldest order joins the batch, in allocation order
+        assert_eq!(minutes(&batch), vec![0, 1]);
+        assert_eq!(minutes(&singles), vec![2]);
+    }
+
+    #[test]
+    fn test_reduce_only_fills_alone_without_taking_the_accounts_place() {
+        let matched = vec![(order(1, 0, true), dec!(100)), (order(1, 1, false), dec!(100)), (order(2, 2, true), dec!(100))];
+        let (batch, singles) = batch_fills(allocate_volume(matched, None));
+
+        assert_eq!(minutes(&batch), vec![1]);
+        assert_eq!(minutes(&singles), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_batch_keeps_allocated_quantities() {
+        let matched = vec![(order(1, 0, false), dec!(100)), (order(2, 1, false), dec!(100)), (order(3, 2, false), dec!(100))];
+        let (batch, singles) = batch_fills(allocate_volume(matched, Some(dec!(15))));
+
+        let quantities: Vec<Decimal> = batch.iter().map(|(_, _, q)| *q).collect();
+        assert_eq!(quantities, vec![dec!(10), dec!(5)]);
+        assert!(singles.is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+//! Unit Tests for Iceberg Orders
+//! Which icebergs are accepted, how much of one a tick sees and how slices replenish
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::order::{allocate_volume, validate_display, visible_quantity};
+use enthropic_domain::{NewOrderRequest, Order};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod iceberg_tests {
+    use super::*;
+
+    fn iceberg(quantity: Decimal, display: Option<Decimal>, filled_quantity: Decimal, minute: u32) -> Order {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, minute, 0).unwrap();
+        Order {
+            id: Uuid::new_v4(),
+            account_id: Uuid::nil(),
+            client_order_id: format!("iceberg-{}", minute),
+            symbol: "AAPL".into(),
+            side: "sell".into(),
+            order_type: "limit".into(),
+            quantity,
+            price: Some(dec!(100)),
+            stop_price: None,
+            trail_amount: None,
+            trail_percent: None,
+            expires_at: None,
+            filled_quantity,
+            avg_fill_price: None,
+            status: "pending".into(),
+            created_at: at,
+            updated_at: at,
+            strategy_id: None,
+            metadata: None,
+            replaces_order_id: None,
+            group_id: None,
+            parent_order_id: None,
+            display_quantity: display,
+            post_only: false,
+            reduce_only: false,
+        }
+    }
+
+    fn request(order_type: &str, time_in_force: &str, display: &str) -> NewOrderRequest {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "AAPL",
+            "side": "sell",
+            "orderType": order_type,
+            "timeInForce": time_in_force,
+            "quantity": "100",
+            "price": "100",
+            "displayQuantity": display,
+        }))
+            .unwrap()
+    }
+
+    /// Fill an order's visible slice, as one tick without an available volume does
+    fn fill_tick(order: &mut Order) -> Decimal {
+        let quantity = allocate_volume(vec![(order.clone(), dec!(100))], None)[0].2;
+        order.filled_quantity += quantity;
+        quantity
+    }
+
+    #[test]
+    fn test_only_resting_limit_orders_can_be_icebergs() {
+        assert!(validate_display(&request("limit", "gtc", "10")).is_ok());
+        assert_eq!(validate_display(&request("market", "gtc", "10")).unwrap_err().0, "INVALID_DISPLAY");
+        assert_eq!(validate_display(&request("limit", "ioc", "10")).unwrap_err().0, "INVALID_DISPLAY");
+        assert_eq!(validate_display(&request("limit", "gtc", "100")).unwrap_err().0, "INVALID_DISPLAY");
+        assert_eq!(validate_display(&request("limit", "gtc", "0")).unwrap_err().0, "INVALID_DISPLAY");
+    }
+
+    #[test]
+    fn test_slice_replenishes_after_each_fill() {
+        let mut order = iceberg(dec!(25), Some(dec!(10)), dec!(0), 0);
+        assert_eq!(fill_tick(&mut order), dec!(10));
+        assert_eq!(fill_tick(&mut order), dec!(10));
+        // The last slice is what is left of the order
+        assert_eq!(fill_tick(&mut order), dec!(5));
+        assert_eq!(order.filled_quantity, dec!(25));
+    }
+
+    #[test]
+    fn test_partly_taken_slice_shows_its_rest() {
+        let order = iceberg(dec!(100), Some(dec!(10)), dec!(14), 0);
+        assert_eq!(visible_quantity(&order), dec!(6));
+        let plain = iceberg(dec!(100), None, dec!(14), 0);
+        assert_eq!(visible_quantity(&plain), dec!(86));
+    }
+
+    #[test]
+    fn test_hidden_quantity_does_not_take_volume_from_later_orders() {
+        let iceberg_order = iceberg(dec!(100), Some(dec!(10)), dec!(0), 0);
+        let later = iceberg(dec!(20), None, dec!(0), 1);
+        let fills = allocate_volume(vec![(later.clone(), dec!(100)), (iceberg_order.clone(), dec!(100))], Some(dec!(25)));
+        let allocated: Vec<_> = fills.iter().map(|(o, _, q)| (o.id, *q)).collect();
+        assert_eq!(allocated, vec![(iceberg_order.id, dec!(10)), (later.id, dec!(15))]);
+    }
+}
@@ -0,0 +1,104 @@
+//! Unit Tests for Instrument Rules
+//! Which orders an instrument's tick size, lot size, quantity bounds and minimum notional reject
+
+use enthropic_domain::order::{validate_instrument, InstrumentRules, LotSize};
+use enthropic_domain::NewOrderRequest;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod instrument_tests {
+    use super::*;
+
+    fn request(quantity: Decimal, price: Option<Decimal>) -> NewOrderRequest {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "BTC-USD",
+            "side": "buy",
+            "orderType": "limit",
+            "quantity": quantity,
+            "price": price,
+        }))
+            .unwrap()
+    }
+
+    fn rules() -> InstrumentRules {
+        InstrumentRules {
+            symbol: "BTC-USD".into(),
+            tick_size: dec!(0.5),
+            lot: LotSize { size: dec!(0.001), min_quantity: dec!(0.002) },
+            max_quantity: Some(dec!(10)),
+            min_notional: Some(dec!(10)),
+            active: true,
+        }
+    }
+
+    fn code(req: &NewOrderRequest, rules: &InstrumentRules, last: Option<Decimal>) -> &'static str {
+        validate_instrument(req, rules, last).unwrap_err().0
+    }
+
+    #[test]
+    fn test_valid_order_passes() {
+        let req = request(dec!(0.01), Some(dec!(40000.5)));
+        assert!(validate_instrument(&req, &rules(), None).is_ok());
+    }
+
+    #[test]
+    fn test_price_off_tick_is_rejected() {
+        let req = request(dec!(0.01), Some(dec!(40000.25)));
+        assert_eq!(code(&req, &rules(), None), "INVALID_TICK_SIZE");
+
+        let mut stop = request(dec!(0.01), None);
+        stop.stop_price = Some(dec!(39000.1));
+        assert_eq!(code(&stop, &rules(), Some(dec!(40000))), "INVALID_TICK_SIZE");
+    }
+
+    #[test]
+    fn test_quantity_off_lot_is_rejected() {
+        let req = request(dec!(0.0105), Some(dec!(40000)));
+        assert_eq!(code(&req, &rules(), None), "INVALID_LOT_SIZE");
+    }
+
+    #[test]
+    fn test_quantity_bounds() {
+        let small = request(dec!(0.001), Some(dec!(40000)));
+        assert_eq!(code(&small, &rules(), None), "BELOW_MIN_QUANTITY");
+
+        let large = request(dec!(10.001), Some(dec!(40000)));
+        assert_eq!(code(&large, &rules(), None), "ABOVE_MAX_QUANTITY");
+
+        let mut unbounded = rules();
+        unbounded.max_quantity = None;
+        assert!(validate_instrument(&large, &unbounded, None).is_ok());
+    }
+
+    #[test]
+    fn test_min_notional_uses_limit_then_last_trade() {
+        // 0.002 at 4000 is 8, under the minimum of 10
+        let limit = request(dec!(0.002), Some(dec!(4000)));
+        assert_eq!(code(&limit, &rules(), Some(dec!(40000))), "BELOW_MIN_NOTIONAL");
+
+        let market = request(dec!(0.002), None);
+        assert_eq!(code(&market, &rules(), Some(dec!(4000))), "BELOW_MIN_NOTIONAL");
+        assert!(validate_instrument(&market, &rules(), Some(dec!(40000))).is_ok());
+
+        // Nothing to price it at yet
+        assert!(validate_instrument(&market, &rules(), None).is_ok());
+    }
+
+    #[test]
+    fn test_inactive_instrument_rejects_everything() {
+        let mut halted = rules();
+        halted.active = false;
+        let req = request(dec!(0.01), Some(dec!(40000)));
+        assert_eq!(code(&req, &halted, None), "INSTRUMENT_INACTIVE");
+    }
+
+    #[test]
+    fn test_amend_checks() {
+        let rules = rules();
+        assert_eq!(rules.check_price("Price", dec!(100.2)).unwrap_err().0, "INVALID_TICK_SIZE");
+        assert!(rules.check_price("Price", dec!(100.5)).is_ok());
+        assert_eq!(rules.check_quantity(dec!(11)).unwrap_err().0, "ABOVE_MAX_QUANTITY");
+        assert!(rules.check_quantity(dec!(5)).is_ok());
+    }
+}
@@ -0,0 +1,95 @@
+//! Unit Tests for Notional Orders
+//! How a quote-currency notional converts to a base quantity and when it is rejected
+
+use enthropic_domain::order::{resolve_notional, LotSize};
+use enthropic_domain::NewOrderRequest;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod notional_tests {
+    use super::*;
+
+    fn request(notional: Decimal, price: Option<Decimal>) -> NewOrderRequest {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "BTC-USD",
+            "side": "buy",
+            "notional": notional,
+            "price": price,
+        }))
+            .unwrap()
+    }
+
+    fn lot(size: Decimal, min_quantity: Decimal) -> LotSize {
+        LotSize { size, min_quantity }
+    }
+
+    #[test]
+    fn test_converts_at_last_trade() {
+        let mut req = request(dec!(500), None);
+        resolve_notional(&mut req, Some(dec!(40000)), &lot(dec!(0.0001), dec!(0.0001))).unwrap();
+        assert_eq!(req.quantity, dec!(0.0125));
+    }
+
+    #[test]
+    fn test_limit_price_wins_over_last_trade() {
+        let mut req = request(dec!(500), Some(dec!(50000)));
+        resolve_notional(&mut req, Some(dec!(40000)), &LotSize::default()).unwrap();
+        assert_eq!(req.quantity, dec!(0.01));
+    }
+
+    #[test]
+    fn test_rounds_down_to_whole_lots() {
+        let mut req = request(dec!(1000), None);
+        resolve_notional(&mut req, Some(dec!(30)), &lot(dec!(1), dec!(1))).unwrap();
+        // 33.33 shares, never more than the notional
+        assert_eq!(req.quantity, dec!(33));
+
+        let mut req = request(dec!(100), None);
+        resolve_notional(&mut req, Some(dec!(3)), &LotSize::default()).unwrap();
+        assert_eq!(req.quantity, dec!(33.33333333));
+    }
+
+    #[test]
+    fn test_below_min_quantity_is_rejected() {
+        let mut req = request(dec!(5), None);
+        let (code, _) = resolve_notional(&mut req, Some(dec!(40000)), &lot(dec!(0.0001), dec!(0.001))).unwrap_err();
+        assert_eq!(code, "NOTIONAL_BELOW_MIN_QUANTITY");
+
+        // Less than one lot rounds down to nothing
+        let mut req = request(dec!(20), None);
+        let (code, _) = resolve_notional(&mut req, Some(dec!(30)), &lot(dec!(1), dec!(0))).unwrap_err();
+        assert_eq!(code, "NOTIONAL_BELOW_MIN_QUANTITY");
+    }
+
+    #[test]
+    fn test_needs_a_price() {
+        let mut req = request(dec!(500), None);
+        let (code, _) = resolve_notional(&mut req, None, &LotSize::default()).unwrap_err();
+        assert_eq!(code, "NO_REFERENCE_PRICE");
+    }
+
+    #[test]
+    fn test_invalid_notionals() {
+        let mut both = request(dec!(500), None);
+        both.quantity = dec!(1);
+        let (code, _) = resolve_notional(&mut both, Some(dec!(100)), &LotSize::default()).unwrap_err();
+        assert_eq!(code, "INVALID_NOTIONAL");
+
+        let mut negative = request(dec!(-500), None);
+        let (code, _) = resolve_notional(&mut negative, Some(dec!(100)), &LotSize::default()).unwrap_err();
+        assert_eq!(code, "INVALID_NOTIONAL");
+    }
+
+    #[test]
+    fn test_quantity_orders_pass_through() {
+        let mut req = request(dec!(500), None);
+        req.notional = None;
+        let (code, _) = resolve_notional(&mut req, Some(dec!(100)), &LotSize::default()).unwrap_err();
+        assert_eq!(code, "INVALID_QUANTITY");
+
+        req.quantity = dec!(2);
+        resolve_notional(&mut req, Some(dec!(100)), &LotSize::default()).unwrap();
+        assert_eq!(req.quantity, dec!(2));
+    }
+}
@@ -0,0 +1,179 @@
+//! Golden-File Tests for Position P&L
+//! Replays the fills and splits of each fixture under `tests/scenarios/pnl` through the position
+//! math the engine stores and rebuilds positions with, and checks the position after every
+//! step against the fixture. A change to the accounting shows up as a diff of the fixtures:
+//! run with `UPDATE_PNL_FIXTURES=1` to rewrite them from the current math, then review the diff.
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::position::{apply_fill, apply_split};
+use enthropic_domain::{Fill, Position};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+#[cfg(test)]
+mod pnl_scenario_tests {
+    use super::*;
+
+    const FIXTURES: &str = "tests/scenarios/pnl";
+    const UPDATE_VAR: &str = "UPDATE_PNL_FIXTURES";
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Scenario {
+        description: String,
+        #[serde(default = "default_symbol")]
+        symbol: String,
+        steps: Vec<Step>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Step {
+        #[serde(flatten)]
+        movement: Movement,
+        /// The position after the step
+        expect: Expected,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Movement {
+        Fill { side: String, quantity: Decimal, price: Decimal },
+        Split { ratio: Decimal },
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Expected {
+        net_quantity: Decimal,
+        avg_price: Decimal,
+        /// Cumulative, as the position row holds it
+        realized_pnl: Decimal,
+        cost_basis: Decimal,
+        /// P&L this step realized on its own
+        #[serde(default)]
+        step_pnl: Decimal,
+    }
+
+    fn default_symbol() -> String {
+        "AAPL".into()
+    }
+
+    fn flat(symbol: &str) -> Position {
+        Position {
+            account_id: Uuid::nil(),
+            symbol: symbol.into(),
+            net_quantity: Decimal::ZERO,
+            avg_price: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            unrealized_pnl: Decimal::ZERO,
+            cost_basis: Decimal::ZERO,
+            sequence: 0,
+            updated_at: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+        }
+    }
+
+    /// The position after each step of `scenario`
+    fn replay(scenario: &Scenario) -> Vec<Expected> {
+        let mut pos = flat(&scenario.symbol);
+        scenario
+            .steps
+            .iter()
+            .map(|step| {
+                let step_pnl = match &step.movement {
+                    Movement::Fill { side, quantity, price } => apply_fill(
+                        &mut pos,
+                        &Fill {
+                            account_id: Uuid::nil(),
+                            symbol: scenario.symbol.clone(),
+                            side: side.clone(),
+                            quantity: *quantity,
+                            price: *price,
+                            key: None,
+                        },
+                    ),
+                    Movement::Split { ratio } => {
+                        apply_split(&mut pos, *ratio);
+                        Decimal::ZERO
+                    }
+                };
+                Expected {
+                    net_quantity: pos.net_quantity.normalize(),
+                    avg_price: pos.avg_price.normalize(),
+                    realized_pnl: pos.realized_pnl.normalize(),
+                    cost_basis: pos.cost_basis.normalize(),
+                    step_pnl: step_pnl.normalize(),
+                }
+            })
+            .collect()
+    }
+
+    fn fixtures() -> Vec<PathBuf> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURES);
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap_or_else(|e| panic!("cannot read {}: {}", dir.display(), e))
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "yaml"))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_pnl_scenarios_match_fixtures() {
+        let update = std::env::var(UPDATE_VAR).is_ok_and(|v| v == "1");
+        let paths = fixtures();
+        assert!(!paths.is_empty(), "no P&L fixtures under {}", FIXTURES);
+
+        let mut failures = Vec::new();
+        for path in paths {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            let text = std::fs::read_to_string(&path).unwrap();
+            let mut scenario: Scenario =
+                serde_yaml::from_str(&text).unwrap_or_else(|e| panic!("{}: invalid fixture: {}", name, e));
+            let actual = replay(&scenario);
+
+            if update {
+                for (step, actual) in scenario.steps.iter_mut().zip(actual) {
+                    step.expect = actual;
+                }
+                std::fs::write(&path, serde_yaml::to_string(&scenario).unwrap()).unwrap();
+                continue;
+            }
+
+            for (i, (step, actual)) in scenario.steps.iter().zip(&actual).enumerate() {
+                if &step.expect != actual {
+                    failures.push(format!(
+                        "{} step {} ({:?}):\n  expected {:?}\n  actual   {:?}",
+                        name,
+                        i + 1,
+                        step.movement,
+                        step.expect,
+                        actual
+                    ));
+                }
+            }
+        }
+
+        assert!(
+            failures.is_empty(),
+            "{} P&L scenario step(s) differ from their fixtures; if the change is intended, rerun \
+             with {}=1 and review the fixture diff\n\n{}",
+            failures.len(),
+            UPDATE_VAR,
+            failures.join("\n\n")
+        );
+    }
+
+    #[test]
+    fn test_fixtures_round_trip() {
+        // Rewriting a fixture from its own expectations changes nothing but formatting
+        for path in fixtures() {
+            let scenario: Scenario = serde_yaml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            let reread: Scenario = serde_yaml::from_str(&serde_yaml::to_string(&scenario).unwrap()).unwrap();
+            assert_eq!(scenario.steps.len(), reread.steps.len());
+            for (a, b) in scenario.steps.iter().zip(&reread.steps) {
+                assert_eq!(a.expect, b.expect, "{}", path.display());
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Unit Tests for Post-Only Orders
+//! Which post-only orders are accepted and when their price would take liquidity
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::order::{crosses, post_only_contra, validate_post_only};
+use enthropic_domain::{NewOrderRequest, Order, OrderBook};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod post_only_tests {
+    use super::*;
+
+    fn request(side: &str, order_type: &str, time_in_force: &str) -> NewOrderRequest {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "AAPL",
+            "side": side,
+            "orderType": order_type,
+            "timeInForce": time_in_force,
+            "quantity": "100",
+            "price": "100",
+            "postOnly": true,
+        }))
+            .unwrap()
+    }
+
+    fn resting(account: u128, side: &str, price: Decimal) -> Order {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        Order {
+            id: Uuid::new_v4(),
+            account_id: Uuid::from_u128(account),
+            client_order_id: format!("resting-{}", price),
+            symbol: "AAPL".into(),
+            side: side.into(),
+            order_type: "limit".into(),
+            quantity: dec!(10),
+            price: Some(price),
+            stop_price: None,
+            trail_amount: None,
+            trail_percent: None,
+            expires_at: None,
+            filled_quantity: Decimal::ZERO,
+            avg_fill_price: None,
+            status: "pending".into(),
+            created_at: at,
+            updated_at: at,
+            strategy_id: None,
+            metadata: None,
+            replaces_order_id: None,
+            group_id: None,
+            parent_order_id: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+        }
+    }
+
+    #[test]
+    fn test_crosses() {
+        assert!(crosses("buy", dec!(100), dec!(99.5)));
+        assert!(crosses("buy", dec!(100), dec!(100)));
+        assert!(!crosses("buy", dec!(100), dec!(100.5)));
+        assert!(crosses("sell", dec!(100), dec!(100.5)));
+        assert!(crosses("sell", dec!(100), dec!(100)));
+        assert!(!crosses("sell", dec!(100), dec!(99.5)));
+    }
+
+    #[test]
+    fn test_resting_price_is_accepted() {
+        assert!(validate_post_only(&request("buy", "limit", "gtc"), Some(dec!(101))).is_ok());
+        assert!(validate_post_only(&request("sell", "limit", "day"), Some(dec!(99))).is_ok());
+        // Nothing has traded yet, so there is nothing to take
+        assert!(validate_post_only(&request("buy", "limit", "gtc"), None).is_ok());
+    }
+
+    #[test]
+    fn test_crossing_price_would_take() {
+        let (code, _) = validate_post_only(&request("buy", "limit", "gtc"), Some(dec!(100))).unwrap_err();
+        assert_eq!(code, "POST_ONLY_WOULD_TAKE");
+        let (code, _) = validate_post_only(&request("sell", "limit", "gtc"), Some(dec!(100.25))).unwrap_err();
+        assert_eq!(code, "POST_ONLY_WOULD_TAKE");
+    }
+
+    #[test]
+    fn test_only_resting_limit_orders_can_be_post_only() {
+        for req in [request("buy", "market", "gtc"), request("buy", "limit", "ioc"), request("buy", "limit", "fok")] {
+            let (code, _) = validate_post_only(&req, Some(dec!(101))).unwrap_err();
+            assert_eq!(code, "INVALID_POST_ONLY");
+        }
+    }
+
+    #[test]
+    fn test_without_flag_nothing_is_checked() {
+        let mut req = request("buy", "market", "ioc");
+        req.post_only = false;
+        assert!(validate_post_only(&req, Some(dec!(1))).is_ok());
+    }
+
+    #[test]
+    fn test_resting_order_of_another_account_would_be_taken() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 13, 0, 0).unwrap();
+        let mut book: OrderBook = OrderBook::new();
+        let offer = resting(2, "sell", dec!(100));
+        book.insert(offer.clone());
+        book.insert(resting(1, "sell", dec!(99)));
+
+        // A bid at 100.5 reaches the other account's offer at 100, so resting it would cross the book
+        let buyer = Uuid::from_u128(1);
+        let contra = post_only_contra(buyer, "buy", dec!(100.5), now, book.crossing("AAPL", "buy", dec!(100.5)));
+        assert_eq!(contra.map(|o| o.id), Some(offer.id));
+
+        // Its own offer at 99 is never crossed, and a bid below 100 reaches no one else's
+        assert!(post_only_contra(buyer, "buy", dec!(99.5), now, book.crossing("AAPL", "buy", dec!(99.5))).is_none());
+
+        // Nor is an offer that has expired or that only reduces a position
+        let mut expired = resting(3, "sell", dec!(98));
+        expired.expires_at = Some(now);
+        let mut reducing = resting(4, "sell", dec!(98));
+        reducing.reduce_only = true;
+        let mut stale: OrderBook = OrderBook::new();
+        stale.insert(expired);
+        stale.insert(reducing);
+        assert!(post_only_contra(buyer, "buy", dec!(100), now, stale.crossing("AAPL", "buy", dec!(100))).is_none());
+    }
+}
@@ -0,0 +1,67 @@
+//! Unit Tests for Rebates
+//! Maker classification, rebate amounts, volume tiers and monthly periods
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use enthropic_domain::rebate::{is_maker, last_closed_period, period_bounds, period_of, rebate_amount, tier_for, RebateTier};
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod rebate_tests {
+    use super::*;
+
+    fn tiers() -> Vec<RebateTier> {
+        vec![
+            RebateTier { min_volume: dec!(10_000_000), bps: dec!(1) },
+            RebateTier { min_volume: dec!(1_000_000), bps: dec!(0.5) },
+        ]
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_limit_orders_make() {
+        assert!(is_maker("limit"));
+        assert!(!is_maker("market"));
+        assert!(!is_maker("stop"));
+    }
+
+    #[test]
+    fn test_rebate_amount() {
+        assert_eq!(rebate_amount(dec!(10_000), dec!(2)), dec!(2));
+        assert_eq!(rebate_amount(dec!(-10_000), dec!(2)), dec!(2));
+    }
+
+    #[test]
+    fn test_rebate_amount_truncates() {
+        // 0.000000015 truncates rather than rounding up
+        assert_eq!(rebate_amount(dec!(0.0003), dec!(0.5)), dec!(0.00000001));
+    }
+
+    #[test]
+    fn test_highest_tier_reached_applies() {
+        assert_eq!(tier_for(&tiers(), dec!(999_999)), None);
+        assert_eq!(tier_for(&tiers(), dec!(1_000_000)).map(|t| t.bps), Some(dec!(0.5)));
+        assert_eq!(tier_for(&tiers(), dec!(25_000_000)).map(|t| t.bps), Some(dec!(1)));
+        assert_eq!(tier_for(&[], dec!(25_000_000)), None);
+    }
+
+    #[test]
+    fn test_periods_are_calendar_months() {
+        assert_eq!(period_of(date(2024, 2, 29)), date(2024, 2, 1));
+
+        let (start, end) = period_bounds(date(2024, 2, 14));
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_last_closed_period() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 5, 0).unwrap();
+        assert_eq!(last_closed_period(now), date(2023, 12, 1));
+
+        let now = Utc.with_ymd_and_hms(2024, 3, 31, 23, 59, 0).unwrap();
+        assert_eq!(last_closed_period(now), date(2024, 2, 1));
+    }
+}
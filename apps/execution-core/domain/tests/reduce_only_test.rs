@@ -0,0 +1,54 @@
+//! Unit Tests for Reduce-Only Orders
+//! How much of an order reduces a position and which reduce-only orders are accepted
+
+use enthropic_domain::order::{reducible_quantity, validate_reduce_only};
+use enthropic_domain::NewOrderRequest;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod reduce_only_tests {
+    use super::*;
+
+    fn request(bracket: Option<serde_json::Value>) -> NewOrderRequest {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "AAPL",
+            "side": "sell",
+            "orderType": "limit",
+            "quantity": "100",
+            "price": "100",
+            "reduceOnly": true,
+            "bracket": bracket,
+        }))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sell_reduces_long() {
+        assert_eq!(reducible_quantity("sell", dec!(40), dec!(100)), dec!(40));
+        // Capped at the position rather than flipping it short
+        assert_eq!(reducible_quantity("sell", dec!(150), dec!(100)), dec!(100));
+    }
+
+    #[test]
+    fn test_buy_reduces_short() {
+        assert_eq!(reducible_quantity("buy", dec!(40), dec!(-100)), dec!(40));
+        assert_eq!(reducible_quantity("buy", dec!(150), dec!(-100)), dec!(100));
+    }
+
+    #[test]
+    fn test_nothing_reduces_flat_or_same_side_position() {
+        assert_eq!(reducible_quantity("sell", dec!(10), dec!(0)), dec!(0));
+        assert_eq!(reducible_quantity("buy", dec!(10), dec!(0)), dec!(0));
+        assert_eq!(reducible_quantity("buy", dec!(10), dec!(100)), dec!(0));
+        assert_eq!(reducible_quantity("sell", dec!(10), dec!(-100)), dec!(0));
+    }
+
+    #[test]
+    fn test_bracket_is_invalid() {
+        assert!(validate_reduce_only(&request(None)).is_ok());
+
+        let bracket = serde_json::json!({ "takeProfit": "90" });
+        let (code, _) = validate_reduce_only(&request(Some(bracket))).unwrap_err();
+        assert_eq!(code, "INVALID_REDUCE_ONLY");
+    }
+}
@@ -0,0 +1,175 @@
+//! Unit Tests for Resubmitted Orders
+//! Which terms of a request differ from the order its client_order_id already names
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::order::{changed_terms, StatedTerms};
+use enthropic_domain::{NewOrderRequest, Order};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod resubmit_tests {
+    use super::*;
+
+    fn order(order_type: &str, quantity: Decimal, price: Option<Decimal>) -> Order {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        Order {
+            id: Uuid::new_v4(),
+            account_id: Uuid::new_v4(),
+            client_order_id: "c-1".into(),
+            symbol: "AAPL".into(),
+            side: "buy".into(),
+            order_type: order_type.into(),
+            quantity,
+            price,
+            stop_price: None,
+            trail_amount: None,
+            trail_percent: None,
+            expires_at: None,
+            filled_quantity: Decimal::ZERO,
+            avg_fill_price: None,
+            status: "pending".into(),
+            created_at: at,
+            updated_at: at,
+            strategy_id: None,
+            metadata: None,
+            replaces_order_id: None,
+            group_id: None,
+            parent_order_id: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+        }
+    }
+
+    fn request(fields: serde_json::Value) -> NewOrderRequest {
+        let mut req = serde_json::json!({ "clientOrderId": "c-1", "symbol": "AAPL", "side": "buy" });
+        req.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(req).unwrap()
+    }
+
+    #[test]
+    fn test_same_order_again_changes_nothing() {
+        let limit = order("limit", dec!(10), Some(dec!(100)));
+        let retry = request(serde_json::json!({ "orderType": "limit", "quantity": "10.00", "price": "100" }));
+        assert!(changed_terms(&limit, &StatedTerms::default(), &retry).is_empty());
+
+        // Case of the side and type is not a difference
+        let shouted = request(serde_json::json!({ "side": "BUY", "orderType": "LIMIT", "quantity": "10", "price": "100" }));
+        assert!(changed_terms(&limit, &StatedTerms::default(), &shouted).is_empty());
+    }
+
+    #[test]
+    fn test_different_terms_are_named() {
+        let limit = order("limit", dec!(10), Some(dec!(100)));
+        let changed = request(serde_json::json!({ "side": "sell", "orderType": "limit", "quantity": "20", "price": "101" }));
+        assert_eq!(changed_terms(&limit, &StatedTerms::default(), &changed), vec!["side", "quantity", "price"]);
+
+        let other_symbol = request(serde_json::json!({ "symbol": "MSFT", "quantity": "10", "price": "100" }));
+        assert_eq!(changed_terms(&limit, &StatedTerms::default(), &other_symbol), vec!["symbol"]);
+
+        let flags = request(serde_json::json!({ "quantity": "10", "price": "100", "postOnly": true, "displayQuantity": "2" }));
+        assert_eq!(changed_terms(&limit, &StatedTerms::default(), &flags), vec!["displayQuantity", "postOnly"]);
+    }
+
+    #[test]
+    fn test_defaulted_and_derived_terms_are_not_compared() {
+        // Type left to the account default
+        let limit = order("limit", dec!(10), Some(dec!(100)));
+        assert!(changed_terms(&limit, &StatedTerms::default(), &request(serde_json::json!({ "quantity": "10", "price": "100" }))).is_empty());
+
+        // Quantity resolved from a notional
+        let notional = request(serde_json::json!({ "orderType": "limit", "notional": "1000", "price": "100" }));
+        let from_notional = StatedTerms { notional: Some(dec!(1000)), ..StatedTerms::default() };
+        assert!(changed_terms(&limit, &from_notional, &notional).is_empty());
+
+        // A market order given a slippage limit by the account default
+        let market = order("market", dec!(10), Some(dec!(100.5)));
+        assert!(changed_terms(&market, &StatedTerms::default(), &request(serde_json::json!({ "orderType": "market", "quantity": "10" }))).is_empty());
+        let limited = request(serde_json::json!({ "orderType": "market", "quantity": "10", "price": "99" }));
+        assert_eq!(changed_terms(&market, &StatedTerms::default(), &limited), vec!["price"]);
+    }
+
+    #[test]
+    fn test_reduce_only_quantity_is_cut_down() {
+        let mut reduce = order("limit", dec!(4), Some(dec!(100)));
+        reduce.reduce_only = true;
+        let req = request(serde_json::json!({ "quantity": "10", "price": "100", "reduceOnly": true }));
+        assert!(changed_terms(&reduce, &StatedTerms::default(), &req).is_empty());
+
+        let plain = request(serde_json::json!({ "quantity": "10", "price": "100" }));
+        assert_eq!(changed_terms(&reduce, &StatedTerms::default(), &plain), vec!["quantity", "reduceOnly"]);
+    }
+
+    #[test]
+    fn test_triggered_stops_keep_matching() {
+        let mut triggered = order("market", dec!(10), None);
+        triggered.stop_price = Some(dec!(95));
+        let stop = request(serde_json::json!({ "orderType": "stop", "quantity": "10", "stopPrice": "95" }));
+        assert!(changed_terms(&triggered, &StatedTerms::default(), &stop).is_empty());
+
+        let moved = request(serde_json::json!({ "orderType": "stop", "quantity": "10", "stopPrice": "94" }));
+        assert_eq!(changed_terms(&triggered, &StatedTerms::default(), &moved), vec!["stopPrice"]);
+
+        // A trailing stop's stop price follows the market after submission
+        let mut trailing = order("trailing_stop", dec!(10), None);
+        trailing.trail_amount = Some(dec!(2));
+        trailing.stop_price = Some(dec!(103));
+        let trail = request(serde_json::json!({ "orderType": "trailing_stop", "quantity": "10", "trailAmount": "2", "stopPrice": "98" }));
+        assert!(changed_terms(&trailing, &StatedTerms::default(), &trail).is_empty());
+    }
+
+    #[test]
+    fn test_time_in_force_and_expiry_are_compared() {
+        let expires = Utc.with_ymd_and_hms(2024, 3, 8, 16, 0, 0).unwrap();
+        let mut gtd = order("limit", dec!(10), Some(dec!(100)));
+        gtd.expires_at = Some(expires);
+        let stored = StatedTerms { time_in_force: Some("gtd".into()), ..StatedTerms::default() };
+
+        let retry = request(serde_json::json!({ "quantity": "10", "price": "100", "timeInForce": "GTD", "expiresAt": expires }));
+        assert!(changed_terms(&gtd, &stored, &retry).is_empty());
+        // Left out, the time in force defaults to gtd from the expiry again
+        let implied = request(serde_json::json!({ "quantity": "10", "price": "100", "expiresAt": expires }));
+        assert!(changed_terms(&gtd, &stored, &implied).is_empty());
+
+        let later = request(serde_json::json!({ "quantity": "10", "price": "100", "expiresAt": expires + chrono::Duration::hours(1) }));
+        assert_eq!(changed_terms(&gtd, &stored, &later), vec!["expiresAt"]);
+        let day = request(serde_json::json!({ "quantity": "10", "price": "100", "timeInForce": "day" }));
+        assert_eq!(changed_terms(&gtd, &stored, &day), vec!["timeInForce", "expiresAt"]);
+
+        // A gtc order resubmitted as ioc
+        let gtc = order("limit", dec!(10), Some(dec!(100)));
+        let stored = StatedTerms { time_in_force: Some("gtc".into()), ..StatedTerms::default() };
+        let ioc = request(serde_json::json!({ "quantity": "10", "price": "100", "timeInForce": "ioc" }));
+        assert_eq!(changed_terms(&gtc, &stored, &ioc), vec!["timeInForce"]);
+    }
+
+    #[test]
+    fn test_stated_terms_outside_the_order_are_compared() {
+        let limit = order("limit", dec!(10), Some(dec!(100)));
+        let sibling = Uuid::new_v4();
+        let original = request(serde_json::json!({
+            "quantity": "10",
+            "price": "100",
+            "ocoWith": sibling,
+            "bracket": { "takeProfit": "110", "stopLoss": "95" },
+            "trigger": { "type": "price_vwap", "direction": "above" },
+        }));
+        let stated = StatedTerms::of(&original);
+        assert!(changed_terms(&limit, &stated, &original).is_empty());
+
+        // Stored and read back as JSON, they still compare equal
+        let stored: StatedTerms = serde_json::from_value(serde_json::to_value(&stated).unwrap()).unwrap();
+        assert!(changed_terms(&limit, &stored, &original).is_empty());
+
+        let changed = request(serde_json::json!({
+            "quantity": "10",
+            "price": "100",
+            "notional": "1000",
+            "bracket": { "takeProfit": "111", "stopLoss": "95" },
+            "trigger": { "type": "price_vwap", "direction": "below" },
+        }));
+        assert_eq!(changed_terms(&limit, &stated, &changed), vec!["notional", "trigger", "bracket", "ocoWith"]);
+    }
+}
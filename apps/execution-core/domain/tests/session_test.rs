@@ -0,0 +1,46 @@
+//! Unit Tests for Trading Sessions
+//! Where session closes fall and when a day order's session is over
+
+use chrono::{TimeZone, Utc};
+use enthropic_domain::TradingSession;
+
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+
+    fn session() -> TradingSession {
+        TradingSession::parse("21:00").unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(TradingSession::parse("21:00"), TradingSession::parse("21:00:00"));
+        assert!(TradingSession::parse("").is_none());
+        assert!(TradingSession::parse("25:00").is_none());
+        assert!(TradingSession::parse("close").is_none());
+    }
+
+    #[test]
+    fn test_closes_around_an_instant() {
+        let morning = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        assert_eq!(session().last_close(morning), Utc.with_ymd_and_hms(2024, 2, 29, 21, 0, 0).unwrap());
+        assert_eq!(session().next_close(morning), Utc.with_ymd_and_hms(2024, 3, 1, 21, 0, 0).unwrap());
+
+        // At the close itself the session has closed
+        let close = Utc.with_ymd_and_hms(2024, 3, 1, 21, 0, 0).unwrap();
+        assert_eq!(session().last_close(close), close);
+        assert_eq!(session().next_close(close), Utc.with_ymd_and_hms(2024, 3, 2, 21, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_day_order_lives_until_next_close() {
+        let placed = Utc.with_ymd_and_hms(2024, 3, 1, 14, 30, 0).unwrap();
+        assert!(!session().day_order_closed(placed, Utc.with_ymd_and_hms(2024, 3, 1, 20, 59, 59).unwrap()));
+        assert!(session().day_order_closed(placed, Utc.with_ymd_and_hms(2024, 3, 1, 21, 0, 0).unwrap()));
+
+        // Placed after the close: the next session's order
+        let late = Utc.with_ymd_and_hms(2024, 3, 1, 22, 0, 0).unwrap();
+        assert!(!session().day_order_closed(late, Utc.with_ymd_and_hms(2024, 3, 2, 9, 0, 0).unwrap()));
+        assert!(session().day_order_closed(late, Utc.with_ymd_and_hms(2024, 3, 2, 21, 0, 0).unwrap()));
+    }
+}
@@ -0,0 +1,92 @@
+//! Unit Tests for TWAP Scheduling
+//! How a parent quantity is sliced, when each slice is due and which requests are accepted
+
+use chrono::{Duration, TimeZone, Utc};
+use enthropic_domain::twap::{slice_client_id, slice_due_at, slice_quantities, validate_twap, MAX_TWAP_SLICES};
+use enthropic_domain::{ExecutionStrategy, TwapRequest};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod twap_tests {
+    use super::*;
+
+    fn request(quantity: Decimal, duration_secs: u32, slices: u32) -> TwapRequest {
+        TwapRequest {
+            client_twap_id: "twap-1".into(),
+            symbol: "AAPL".into(),
+            side: "buy".into(),
+            quantity,
+            price: None,
+            duration_secs,
+            slices,
+            strategy: ExecutionStrategy::Twap,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_even_split() {
+        assert_eq!(slice_quantities(dec!(100), 4), vec![dec!(25); 4]);
+    }
+
+    #[test]
+    fn test_last_slice_takes_remainder() {
+        let slices = slice_quantities(dec!(10), 3);
+        assert_eq!(slices[..2], [dec!(3.33333333), dec!(3.33333333)]);
+        assert_eq!(slices[2], dec!(3.33333334));
+        assert_eq!(slices.iter().sum::<Decimal>(), dec!(10));
+    }
+
+    #[test]
+    fn test_single_slice_is_whole_quantity() {
+        assert_eq!(slice_quantities(dec!(7.5), 1), vec![dec!(7.5)]);
+        assert!(slice_quantities(dec!(7.5), 0).is_empty());
+    }
+
+    #[test]
+    fn test_slices_spread_over_duration() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 14, 0, 0).unwrap();
+        assert_eq!(slice_due_at(start, 600, 4, 0), start);
+        assert_eq!(slice_due_at(start, 600, 4, 1), start + Duration::seconds(150));
+        assert_eq!(slice_due_at(start, 600, 4, 3), start + Duration::seconds(450));
+        // Past the last slice: the end of the window
+        assert_eq!(slice_due_at(start, 600, 4, 4), start + Duration::seconds(600));
+        assert_eq!(slice_due_at(start, 10, 3, 1), start + Duration::milliseconds(3_333));
+    }
+
+    #[test]
+    fn test_slice_client_ids_are_one_based() {
+        assert_eq!(slice_client_id("twap-1", 0), "twap-1:1");
+        assert_eq!(slice_client_id("twap-1", 9), "twap-1:10");
+    }
+
+    #[test]
+    fn test_valid_request() {
+        assert!(validate_twap(&request(dec!(100), 600, 10)).is_ok());
+        assert!(validate_twap(&request(dec!(100), 10, 10)).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        assert!(validate_twap(&request(dec!(0), 600, 10)).is_err());
+        assert!(validate_twap(&request(dec!(100), 600, 0)).is_err());
+        assert!(validate_twap(&request(dec!(100), 5_000, MAX_TWAP_SLICES + 1)).is_err());
+        assert!(validate_twap(&request(dec!(100), 9, 10)).is_err());
+        assert!(validate_twap(&request(dec!(100), 8 * 86_400, 10)).is_err());
+        // Too small to give every slice a quantity at the orders' precision
+        assert!(validate_twap(&request(dec!(0.00000001), 600, 2)).is_err());
+
+        let mut sell_short = request(dec!(100), 600, 10);
+        sell_short.side = "short".into();
+        assert!(validate_twap(&sell_short).is_err());
+
+        let mut free = request(dec!(100), 600, 10);
+        free.price = Some(dec!(0));
+        assert!(validate_twap(&free).is_err());
+
+        let mut unnamed = request(dec!(100), 600, 10);
+        unnamed.client_twap_id = String::new();
+        assert!(validate_twap(&unnamed).is_err());
+    }
+}
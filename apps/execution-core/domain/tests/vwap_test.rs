@@ -0,0 +1,76 @@
+//! Unit Tests for VWAP Scheduling
+//! How historical volume sizes slices and how executions compare to the market VWAP
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use enthropic_domain::vwap::{benchmark_vwap, slippage_bps, volume_profile, weighted_quantities, VolumeBar};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod vwap_tests {
+    use super::*;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 5, 14, 0, 0).unwrap()
+    }
+
+    fn bar(start: DateTime<Utc>, volume: Decimal, price: Decimal) -> VolumeBar {
+        VolumeBar { start, volume, notional: volume * price }
+    }
+
+    #[test]
+    fn test_profile_from_previous_days() {
+        let bars = vec![
+            // Yesterday: first and second half of the window
+            bar(start() - Duration::days(1), dec!(100), dec!(10)),
+            bar(start() - Duration::days(1) + Duration::minutes(45), dec!(300), dec!(10)),
+            // Two days ago: first half again
+            bar(start() - Duration::days(2) + Duration::minutes(10), dec!(50), dec!(10)),
+            // Outside the window on every day
+            bar(start() - Duration::days(1) + Duration::hours(2), dec!(999), dec!(10)),
+        ];
+
+        let profile = volume_profile(&bars, start(), 3_600, 2, 20);
+        assert_eq!(profile, vec![dec!(150), dec!(300)]);
+    }
+
+    #[test]
+    fn test_profile_ignores_days_past_lookback() {
+        let bars = vec![bar(start() - Duration::days(3), dec!(100), dec!(10))];
+        assert_eq!(volume_profile(&bars, start(), 3_600, 2, 2), vec![Decimal::ZERO; 2]);
+        assert_eq!(volume_profile(&bars, start(), 3_600, 2, 3), vec![dec!(100), Decimal::ZERO]);
+    }
+
+    #[test]
+    fn test_quantities_follow_weights() {
+        let quantities = weighted_quantities(dec!(100), &[dec!(1), dec!(3)]);
+        assert_eq!(quantities, vec![dec!(25), dec!(75)]);
+    }
+
+    #[test]
+    fn test_last_weighted_slice_takes_remainder() {
+        let quantities = weighted_quantities(dec!(10), &[dec!(1), dec!(1), dec!(1), Decimal::ZERO]);
+        assert_eq!(quantities, vec![dec!(3.33333333), dec!(3.33333333), dec!(3.33333334), Decimal::ZERO]);
+        assert_eq!(quantities.iter().sum::<Decimal>(), dec!(10));
+    }
+
+    #[test]
+    fn test_no_history_splits_evenly() {
+        assert_eq!(weighted_quantities(dec!(90), &[Decimal::ZERO; 3]), vec![dec!(30); 3]);
+    }
+
+    #[test]
+    fn test_benchmark_is_volume_weighted() {
+        let bars = vec![bar(start(), dec!(100), dec!(10)), bar(start(), dec!(300), dec!(12))];
+        assert_eq!(benchmark_vwap(&bars), Some(dec!(11.5)));
+        assert_eq!(benchmark_vwap(&[]), None);
+    }
+
+    #[test]
+    fn test_slippage_positive_when_worse() {
+        assert_eq!(slippage_bps("buy", dec!(101), dec!(100)), Some(dec!(100)));
+        assert_eq!(slippage_bps("sell", dec!(101), dec!(100)), Some(dec!(-100)));
+        assert_eq!(slippage_bps("sell", dec!(99.95), dec!(100)), Some(dec!(5)));
+        assert_eq!(slippage_bps("buy", dec!(100), Decimal::ZERO), None);
+    }
+}
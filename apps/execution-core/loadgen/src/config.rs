@@ -0,0 +1,111 @@
+//! Load Test Configuration
+//! Read from the environment, like the engine's own configuration
+
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct LoadConfig {
+    pub nats_url: String,
+    /// Funded accounts the orders are placed for
+    pub accounts: Vec<Uuid>,
+    pub symbols: Vec<String>,
+    pub duration: Duration,
+    /// Order submits per second
+    pub order_rate: f64,
+    /// Market ticks per second, across all symbols
+    pub tick_rate: f64,
+    /// Share of submits that are market orders; the rest are limits around the last price
+    pub market_ratio: f64,
+    /// Share of accepted limit orders cancelled after a while
+    pub cancel_ratio: f64,
+    pub start_price: f64,
+    /// `Enthropic-Ack-Mode` of every submit: `sync` or `fast`
+    pub ack_mode: String,
+    /// Route order flow as `{subject}.{symbol}` for a sharded deployment
+    pub sharded: bool,
+    /// Requests unanswered this long count as timeouts
+    pub timeout: Duration,
+    /// Write the report as JSON here as well as printing it
+    pub report_path: Option<String>,
+    /// Fail the run when the submit ack p99 is above this
+    pub max_ack_p99: Option<Duration>,
+    pub seed: Option<u64>,
+}
+
+impl LoadConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let accounts = env::var("LOADGEN_ACCOUNTS")
+            .map_err(|_| anyhow::anyhow!("LOADGEN_ACCOUNTS must list the account ids to trade for"))?
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(Uuid::parse_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if accounts.is_empty() {
+            anyhow::bail!("LOADGEN_ACCOUNTS lists no accounts");
+        }
+
+        let symbols: Vec<String> = env::var("LOADGEN_SYMBOLS")
+            .unwrap_or_else(|_| "BTC-USD,ETH-USD".to_string())
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+        if symbols.is_empty() {
+            anyhow::bail!("LOADGEN_SYMBOLS lists no symbols");
+        }
+
+        let ack_mode = env::var("LOADGEN_ACK_MODE").unwrap_or_else(|_| "sync".to_string());
+        if !matches!(ack_mode.as_str(), "sync" | "fast") {
+            anyhow::bail!("Invalid LOADGEN_ACK_MODE {:?}: expected sync or fast", ack_mode);
+        }
+
+        let config = Self {
+            nats_url: env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string()),
+            accounts,
+            symbols,
+            duration: Duration::from_secs(number("LOADGEN_DURATION_SECS", 60)),
+            order_rate: number("LOADGEN_ORDER_RATE", 100.0),
+            tick_rate: number("LOADGEN_TICK_RATE", 20.0),
+            market_ratio: number("LOADGEN_MARKET_RATIO", 0.2),
+            cancel_ratio: number("LOADGEN_CANCEL_RATIO", 0.3),
+            start_price: number("LOADGEN_START_PRICE", 100.0),
+            ack_mode,
+            sharded: env::var("LOADGEN_SHARDED").is_ok_and(|v| v == "true" || v == "1"),
+            timeout: Duration::from_millis(number("LOADGEN_TIMEOUT_MS", 5000)),
+            report_path: env::var("LOADGEN_REPORT").ok().filter(|p| !p.is_empty()),
+            max_ack_p99: env::var("LOADGEN_MAX_ACK_P99_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis),
+            seed: env::var("LOADGEN_SEED").ok().and_then(|v| v.parse().ok()),
+        };
+
+        for (name, ratio) in [("LOADGEN_MARKET_RATIO", config.market_ratio), ("LOADGEN_CANCEL_RATIO", config.cancel_ratio)] {
+            if !(0.0..=1.0).contains(&ratio) {
+                anyhow::bail!("{} must be between 0 and 1", name);
+            }
+        }
+        if config.order_rate <= 0.0 || config.tick_rate < 0.0 || config.start_price <= 0.0 {
+            anyhow::bail!("LOADGEN_ORDER_RATE and LOADGEN_START_PRICE must be positive, LOADGEN_TICK_RATE not negative");
+        }
+        Ok(config)
+    }
+
+    /// Subject of an order request for `symbol`
+    pub fn order_subject(&self, subject: &str, symbol: &str) -> String {
+        if self.sharded {
+            format!("{}.{}", subject, symbol)
+        } else {
+            subject.to_string()
+        }
+    }
+}
+
+fn number<T: FromStr>(name: &str, default: T) -> T {
+    env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
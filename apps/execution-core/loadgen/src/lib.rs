@@ -0,0 +1,7 @@
+//! Enthropic Load Generator
+//! Drives a running execution core over NATS with a configurable mix of order submits, cancels
+//! and market ticks, and reports ack and fill latency percentiles
+
+pub mod config;
+pub mod report;
+pub mod workload;
@@ -0,0 +1,230 @@
+//! Load Test Harness
+//! Sends order submits and ticks at fixed rates, cancels a share of the accepted orders, and
+//! reports ack and fill latency percentiles once the run is over
+
+use async_nats::HeaderMap;
+use enthropic_loadgen::config::LoadConfig;
+use enthropic_loadgen::report::Recorder;
+use enthropic_loadgen::workload::{cancel_payload, OrderSpec, Workload};
+use futures::StreamExt;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{interval, sleep, Instant, MissedTickBehavior};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+/// Longest an order rests before it is cancelled
+const MAX_CANCEL_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Clone)]
+struct Run {
+    client: async_nats::Client,
+    config: Arc<LoadConfig>,
+    workload: Arc<Mutex<Workload<StdRng>>>,
+    recorder: Arc<Mutex<Recorder>>,
+    /// Sent orders not yet filled, by client order id, with when each was due to be sent
+    unfilled: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    success: bool,
+    order_id: Option<String>,
+    code: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecutionReport {
+    client_order_id: String,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
+    let config = Arc::new(LoadConfig::from_env()?);
+    let client = async_nats::connect(&config.nats_url).await?;
+    info!(
+        nats = %config.nats_url,
+        accounts = config.accounts.len(),
+        symbols = ?config.symbols,
+        order_rate = config.order_rate,
+        tick_rate = config.tick_rate,
+        ack_mode = %config.ack_mode,
+        "Starting load run for {:?}",
+        config.duration
+    );
+
+    let rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let run = Run {
+        client,
+        workload: Arc::new(Mutex::new(Workload::new(
+            rng,
+            config.accounts.clone(),
+            config.symbols.clone(),
+            config.start_price,
+            config.market_ratio,
+            config.cancel_ratio,
+        ))),
+        config,
+        recorder: Arc::default(),
+        unfilled: Arc::default(),
+    };
+
+    for account_id in &run.config.accounts {
+        let mut executions = run.client.subscribe(format!("executions.{}", account_id)).await?;
+        let run = run.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = executions.next().await {
+                let Ok(report) = serde_json::from_slice::<ExecutionReport>(&msg.payload) else {
+                    continue;
+                };
+                // Only an order's first fill is timed
+                if let Some(due) = run.unfilled.lock().unwrap().remove(&report.client_order_id) {
+                    run.recorder.lock().unwrap().latency("fill", due.elapsed());
+                }
+            }
+        });
+    }
+
+    let started = Instant::now();
+    let deadline = started + run.config.duration;
+    if run.config.tick_rate > 0.0 {
+        tokio::spawn(send_ticks(run.clone(), deadline));
+    }
+
+    let mut orders = interval(Duration::from_secs_f64(1.0 / run.config.order_rate));
+    // An open loop: requests fall due on schedule however slowly the engine answers, and are
+    // timed from when they were due, so a stall shows up as latency rather than a lower rate
+    orders.set_missed_tick_behavior(MissedTickBehavior::Burst);
+    loop {
+        let due = orders.tick().await;
+        if due >= deadline {
+            break;
+        }
+        let order = run.workload.lock().unwrap().next_order();
+        run.recorder.lock().unwrap().sent("submit");
+        tokio::spawn(submit(run.clone(), order, due));
+    }
+
+    // Let the last answers and fills come in before reporting
+    sleep(run.config.timeout * 2 + MAX_CANCEL_DELAY).await;
+    let report = run.recorder.lock().unwrap().report(started.elapsed(), &run.config.ack_mode);
+    println!("{}", report.render());
+    if let Some(path) = &run.config.report_path {
+        std::fs::write(path, serde_json::to_vec_pretty(&report)?)?;
+        info!(path = %path, "Wrote load report");
+    }
+
+    if let Some(limit) = run.config.max_ack_p99 {
+        let p99 = report.latencies.get("submit_ack").map(|s| s.p99_us).unwrap_or_default();
+        if Duration::from_micros(p99) > limit {
+            anyhow::bail!("Submit ack p99 of {}us is over the {}us limit", p99, limit.as_micros());
+        }
+    }
+    Ok(())
+}
+
+async fn send_ticks(run: Run, deadline: Instant) {
+    let mut ticks = interval(Duration::from_secs_f64(1.0 / run.config.tick_rate));
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Burst);
+    loop {
+        if ticks.tick().await >= deadline {
+            return;
+        }
+        let tick = run.workload.lock().unwrap().next_tick();
+        let payload = serde_json::to_vec(&tick).expect("tick serializes");
+        match run.client.publish(format!("market.tick.{}", tick.symbol), payload.into()).await {
+            Ok(()) => run.recorder.lock().unwrap().sent("tick"),
+            Err(e) => warn!(error = %e, "Failed to publish tick"),
+        }
+    }
+}
+
+/// Submits `order`, then cancels it if the workload picks it for cancelling
+async fn submit(run: Run, order: OrderSpec, due: Instant) {
+    let subject = run.config.order_subject("orders.submit", &order.symbol);
+    let mut submit_headers = headers();
+    submit_headers.insert("Enthropic-Ack-Mode", run.config.ack_mode.as_str());
+
+    run.unfilled.lock().unwrap().insert(order.client_order_id.clone(), due);
+    let Some(reply) = request(&run, "submit", subject, submit_headers, &order.payload(), due).await else {
+        run.unfilled.lock().unwrap().remove(&order.client_order_id);
+        return;
+    };
+    if !reply.success {
+        run.unfilled.lock().unwrap().remove(&order.client_order_id);
+    }
+
+    // Fast acks come back before the order exists, so only synchronously acked ones are cancelled
+    let Some(order_id) = reply.order_id.filter(|_| reply.success) else {
+        return;
+    };
+    let Some(delay) = run.workload.lock().unwrap().cancel_after(&order, MAX_CANCEL_DELAY) else {
+        return;
+    };
+    sleep(delay).await;
+
+    let subject = run.config.order_subject("orders.cancel", &order.symbol);
+    run.recorder.lock().unwrap().sent("cancel");
+    let sent = Instant::now();
+    request(&run, "cancel", subject, headers(), &cancel_payload(order.account_id, &order_id), sent).await;
+}
+
+/// Sends one request and records its ack latency and outcome; `None` when it went unanswered
+async fn request(
+    run: &Run,
+    operation: &'static str,
+    subject: String,
+    headers: HeaderMap,
+    payload: &serde_json::Value,
+    due: Instant,
+) -> Option<Reply> {
+    let payload = serde_json::to_vec(payload).expect("request serializes");
+    let sent = tokio::time::timeout(run.config.timeout, run.client.request_with_headers(subject, headers, payload.into())).await;
+
+    let latency = due.elapsed();
+    let mut recorder = run.recorder.lock().unwrap();
+    let msg = match sent {
+        Ok(Ok(msg)) => msg,
+        Ok(Err(e)) => {
+            warn!(operation, error = %e, "Request failed");
+            recorder.outcome(operation, "error");
+            return None;
+        }
+        Err(_) => {
+            recorder.outcome(operation, "timeout");
+            return None;
+        }
+    };
+
+    let Ok(reply) = serde_json::from_slice::<Reply>(&msg.payload) else {
+        recorder.outcome(operation, "error");
+        return None;
+    };
+    recorder.latency(if operation == "submit" { "submit_ack" } else { "cancel_ack" }, latency);
+    if reply.success {
+        recorder.outcome(operation, "accepted");
+    } else {
+        recorder.outcome(operation, &format!("rejected.{}", reply.code.as_deref().unwrap_or("unknown")));
+    }
+    Some(reply)
+}
+
+/// Headers every request carries: when it was sent, for the engine's own skew and age checks
+fn headers() -> HeaderMap {
+    let sent_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    let mut headers = HeaderMap::new();
+    headers.insert("Enthropic-Sent-At", sent_ms.to_string().as_str());
+    headers
+}
@@ -0,0 +1,127 @@
+//! Load Test Report
+//! Latency samples per operation and request outcomes, summarized as percentiles
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Latencies of one operation, in microseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub p999_us: u64,
+    pub max_us: u64,
+}
+
+impl LatencySummary {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut micros: Vec<u64> = samples.iter().map(|d| d.as_micros() as u64).collect();
+        micros.sort_unstable();
+
+        Self {
+            count: micros.len(),
+            mean_us: micros.iter().sum::<u64>() / micros.len() as u64,
+            p50_us: percentile(&micros, 50.0),
+            p90_us: percentile(&micros, 90.0),
+            p99_us: percentile(&micros, 99.0),
+            p999_us: percentile(&micros, 99.9),
+            max_us: micros[micros.len() - 1],
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+pub fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Samples and counts gathered while the load runs
+#[derive(Debug, Default)]
+pub struct Recorder {
+    latencies: BTreeMap<&'static str, Vec<Duration>>,
+    /// `{operation}.{outcome}`: `accepted`, `rejected.{code}`, `timeout`, `error`
+    outcomes: BTreeMap<String, u64>,
+    sent: BTreeMap<&'static str, u64>,
+}
+
+impl Recorder {
+    pub fn sent(&mut self, operation: &'static str) {
+        *self.sent.entry(operation).or_default() += 1;
+    }
+
+    pub fn latency(&mut self, operation: &'static str, latency: Duration) {
+        self.latencies.entry(operation).or_default().push(latency);
+    }
+
+    pub fn outcome(&mut self, operation: &str, outcome: &str) {
+        *self.outcomes.entry(format!("{}.{}", operation, outcome)).or_default() += 1;
+    }
+
+    pub fn report(&self, elapsed: Duration, ack_mode: &str) -> Report {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        Report {
+            elapsed_secs: elapsed.as_secs_f64(),
+            ack_mode: ack_mode.to_string(),
+            sent: self.sent.iter().map(|(op, n)| (op.to_string(), *n)).collect(),
+            rates: self.sent.iter().map(|(op, n)| (op.to_string(), *n as f64 / secs)).collect(),
+            outcomes: self.outcomes.clone(),
+            latencies: self
+                .latencies
+                .iter()
+                .map(|(op, samples)| (op.to_string(), LatencySummary::from_samples(samples)))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub elapsed_secs: f64,
+    pub ack_mode: String,
+    pub sent: BTreeMap<String, u64>,
+    /// Achieved per second, over the whole run
+    pub rates: BTreeMap<String, f64>,
+    pub outcomes: BTreeMap<String, u64>,
+    /// `submit_ack`, `cancel_ack` and `fill`, from when each request was due to be sent
+    pub latencies: BTreeMap<String, LatencySummary>,
+}
+
+impl Report {
+    /// The report as a plain-text table
+    pub fn render(&self) -> String {
+        let mut out = format!("Load test: {:.1}s, ack mode {}\n\n", self.elapsed_secs, self.ack_mode);
+        out.push_str(&format!("{:<12} {:>10} {:>10}\n", "operation", "sent", "per sec"));
+        for (op, sent) in &self.sent {
+            out.push_str(&format!("{:<12} {:>10} {:>10.1}\n", op, sent, self.rates[op]));
+        }
+
+        out.push_str(&format!("\n{:<24} {:>10}\n", "outcome", "count"));
+        for (outcome, count) in &self.outcomes {
+            out.push_str(&format!("{:<24} {:>10}\n", outcome, count));
+        }
+
+        out.push_str(&format!(
+            "\n{:<12} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}\n",
+            "latency", "count", "mean ms", "p50 ms", "p90 ms", "p99 ms", "p99.9 ms", "max ms"
+        ));
+        let ms = |us: u64| us as f64 / 1000.0;
+        for (op, s) in &self.latencies {
+            out.push_str(&format!(
+                "{:<12} {:>8} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2}\n",
+                op, s.count, ms(s.mean_us), ms(s.p50_us), ms(s.p90_us), ms(s.p99_us), ms(s.p999_us), ms(s.max_us)
+            ));
+        }
+        out
+    }
+}
@@ -0,0 +1,148 @@
+//! Workload Generation
+//! The orders and ticks a load run sends: limit orders spread around a random-walking last
+//! price, with a share of market orders, so ticks keep crossing some of the resting book
+
+use rand::Rng;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Largest step of a tick's random walk, as a fraction of the price
+const TICK_STEP: f64 = 0.002;
+/// Limit prices are placed up to this fraction away from the last price
+const LIMIT_SPREAD: f64 = 0.005;
+const MAX_QUANTITY: u32 = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderSpec {
+    #[serde(skip)]
+    pub account_id: Uuid,
+    pub client_order_id: String,
+    pub symbol: String,
+    pub side: &'static str,
+    pub order_type: &'static str,
+    pub quantity: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<Decimal>,
+}
+
+impl OrderSpec {
+    /// The `orders.submit` payload, authenticated as the order's account
+    pub fn payload(&self) -> serde_json::Value {
+        let mut payload = serde_json::to_value(self).expect("order spec serializes");
+        payload["auth"] = auth(self.account_id);
+        payload
+    }
+}
+
+/// The `orders.cancel` payload for an order of `account_id`
+pub fn cancel_payload(account_id: Uuid, order_id: &str) -> serde_json::Value {
+    json!({ "auth": auth(account_id), "order_id": order_id })
+}
+
+fn auth(account_id: Uuid) -> serde_json::Value {
+    json!({
+        "account_id": account_id.to_string(),
+        "username": format!("loadgen-{}", account_id.simple()),
+        "role": "trader",
+        "permissions": ["orders:create", "orders:cancel"],
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Tick {
+    pub symbol: String,
+    #[serde(rename = "lastPrice")]
+    pub last_price: String,
+    #[serde(rename = "lastSize")]
+    pub last_size: String,
+}
+
+/// Generates the run's orders and ticks, tracking each symbol's last price
+pub struct Workload<R> {
+    rng: R,
+    accounts: Vec<Uuid>,
+    symbols: Vec<String>,
+    prices: Vec<f64>,
+    market_ratio: f64,
+    cancel_ratio: f64,
+    sequence: u64,
+    run_id: String,
+}
+
+impl<R: Rng> Workload<R> {
+    pub fn new(rng: R, accounts: Vec<Uuid>, symbols: Vec<String>, start_price: f64, market_ratio: f64, cancel_ratio: f64) -> Self {
+        let prices = vec![start_price; symbols.len()];
+        Self {
+            rng,
+            accounts,
+            symbols,
+            prices,
+            market_ratio,
+            cancel_ratio,
+            sequence: 0,
+            run_id: Uuid::new_v4().simple().to_string()[..8].to_string(),
+        }
+    }
+
+    /// The next order, for a random account and symbol
+    pub fn next_order(&mut self) -> OrderSpec {
+        let account_id = self.accounts[self.rng.gen_range(0..self.accounts.len())];
+        let index = self.rng.gen_range(0..self.symbols.len());
+        let side = if self.rng.gen_bool(0.5) { "buy" } else { "sell" };
+        let quantity = Decimal::from(self.rng.gen_range(1..=MAX_QUANTITY));
+        self.sequence += 1;
+
+        let (order_type, price) = if self.rng.gen_bool(self.market_ratio) {
+            ("market", None)
+        } else {
+            // Half the limits cross the last price and fill on the next tick, half rest
+            let offset = self.rng.gen_range(-LIMIT_SPREAD..=LIMIT_SPREAD);
+            ("limit", Some(price(self.prices[index] * (1.0 + offset))))
+        };
+
+        OrderSpec {
+            account_id,
+            client_order_id: format!("loadgen-{}-{}", self.run_id, self.sequence),
+            symbol: self.symbols[index].clone(),
+            side,
+            order_type,
+            quantity,
+            price,
+        }
+    }
+
+    /// How long to leave an accepted order before cancelling it, or `None` to leave it be.
+    /// Market orders fill or are refused at once, so only limits are cancelled
+    pub fn cancel_after(&mut self, order: &OrderSpec, max_delay: Duration) -> Option<Duration> {
+        if order.order_type != "limit" || !self.rng.gen_bool(self.cancel_ratio) {
+            return None;
+        }
+        Some(max_delay.mul_f64(self.rng.gen_range(0.0..1.0)))
+    }
+
+    /// The next tick, moving a random symbol's last price by a small step
+    pub fn next_tick(&mut self) -> Tick {
+        let index = self.rng.gen_range(0..self.symbols.len());
+        let step = self.rng.gen_range(-TICK_STEP..=TICK_STEP);
+        self.prices[index] = (self.prices[index] * (1.0 + step)).max(0.01);
+
+        Tick {
+            symbol: self.symbols[index].clone(),
+            last_price: price(self.prices[index]).to_string(),
+            last_size: self.rng.gen_range(100..=1000).to_string(),
+        }
+    }
+
+    pub fn last_price(&self, symbol: &str) -> Option<f64> {
+        self.symbols.iter().position(|s| s == symbol).map(|i| self.prices[i])
+    }
+}
+
+fn price(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(2)
+}
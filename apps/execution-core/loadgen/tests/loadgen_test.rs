@@ -0,0 +1,128 @@
+//! Unit Tests for the Load Generator
+//! Percentile summaries of the latency samples, and the orders and ticks a workload sends
+
+use enthropic_loadgen::report::{percentile, LatencySummary, Recorder};
+use enthropic_loadgen::workload::Workload;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_decimal_macros::dec;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod loadgen_tests {
+    use super::*;
+
+    fn workload(seed: u64, market_ratio: f64, cancel_ratio: f64) -> Workload<StdRng> {
+        Workload::new(
+            StdRng::seed_from_u64(seed),
+            vec![Uuid::from_u128(1), Uuid::from_u128(2)],
+            vec!["BTC-USD".into(), "ETH-USD".into()],
+            100.0,
+            market_ratio,
+            cancel_ratio,
+        )
+    }
+
+    #[test]
+    fn test_nearest_rank_percentile() {
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&samples, 50.0), 50);
+        assert_eq!(percentile(&samples, 99.0), 99);
+        assert_eq!(percentile(&samples, 99.9), 100);
+        assert_eq!(percentile(&samples, 0.0), 1);
+        assert_eq!(percentile(&[7], 99.0), 7);
+        assert_eq!(percentile(&[], 50.0), 0);
+    }
+
+    #[test]
+    fn test_summary_of_unsorted_samples() {
+        let samples: Vec<Duration> = [30, 10, 20, 40].into_iter().map(Duration::from_millis).collect();
+        let summary = LatencySummary::from_samples(&samples);
+
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.mean_us, 25_000);
+        assert_eq!(summary.p50_us, 20_000);
+        assert_eq!(summary.p99_us, 40_000);
+        assert_eq!(summary.max_us, 40_000);
+        assert_eq!(LatencySummary::from_samples(&[]), LatencySummary::default());
+    }
+
+    #[test]
+    fn test_report_rates_and_outcomes() {
+        let mut recorder = Recorder::default();
+        for _ in 0..20 {
+            recorder.sent("submit");
+        }
+        recorder.outcome("submit", "accepted");
+        recorder.outcome("submit", "rejected.INSUFFICIENT_FUNDS");
+        recorder.outcome("submit", "accepted");
+        recorder.latency("submit_ack", Duration::from_millis(2));
+
+        let report = recorder.report(Duration::from_secs(10), "fast");
+        assert_eq!(report.sent["submit"], 20);
+        assert_eq!(report.rates["submit"], 2.0);
+        assert_eq!(report.outcomes["submit.accepted"], 2);
+        assert_eq!(report.outcomes["submit.rejected.INSUFFICIENT_FUNDS"], 1);
+        assert_eq!(report.latencies["submit_ack"].count, 1);
+        assert!(report.render().contains("submit_ack"));
+    }
+
+    #[test]
+    fn test_limits_stay_near_the_last_price() {
+        let mut workload = workload(7, 0.0, 0.0);
+        for _ in 0..500 {
+            let order = workload.next_order();
+            assert_eq!(order.order_type, "limit");
+            let price = order.price.expect("limits are priced");
+            assert!(price >= dec!(99.5) && price <= dec!(100.5), "{} is off the last price", price);
+            assert!(order.quantity >= dec!(1) && order.quantity <= dec!(10));
+        }
+    }
+
+    #[test]
+    fn test_market_ratio_and_unique_client_ids() {
+        let mut workload = workload(11, 1.0, 0.0);
+        let first = workload.next_order();
+        let second = workload.next_order();
+
+        assert_eq!(first.order_type, "market");
+        assert_eq!(first.price, None);
+        assert_ne!(first.client_order_id, second.client_order_id);
+        // Market orders carry no price on the wire
+        assert!(first.payload().get("price").is_none());
+        assert_eq!(first.payload()["auth"]["account_id"], first.account_id.to_string());
+    }
+
+    #[test]
+    fn test_only_limits_are_cancelled() {
+        let max = Duration::from_millis(500);
+        let mut limits = workload(3, 0.0, 1.0);
+        let limit = limits.next_order();
+        assert!(limits.cancel_after(&limit, max).is_some_and(|d| d < max));
+
+        let mut markets = workload(3, 1.0, 1.0);
+        let market = markets.next_order();
+        assert_eq!(markets.cancel_after(&market, max), None);
+    }
+
+    #[test]
+    fn test_ticks_walk_the_price() {
+        let mut workload = workload(5, 0.0, 0.0);
+        for _ in 0..1000 {
+            let tick = workload.next_tick();
+            let last = workload.last_price(&tick.symbol).unwrap();
+            assert!(last > 0.0);
+            assert_eq!(tick.last_price.parse::<f64>().unwrap(), (last * 100.0).round() / 100.0);
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_orders() {
+        let (mut a, mut b) = (workload(42, 0.3, 0.0), workload(42, 0.3, 0.0));
+        for _ in 0..50 {
+            let (x, y) = (a.next_order(), b.next_order());
+            assert_eq!((x.account_id, &x.symbol, x.side, x.price, x.quantity), (y.account_id, &y.symbol, y.side, y.price, y.quantity));
+        }
+    }
+}
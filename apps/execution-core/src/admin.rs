@@ -0,0 +1,52 @@
+//! Admin Commands
+//! One-off maintenance run as `execution-core <command>`: connects to the database, runs, exits
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::engine::PositionKeeper;
+use crate::storage::ReadPool;
+
+pub const USAGE: &str = "usage: execution-core [rebuild-positions [--account <uuid>]]";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminCommand {
+    /// Replace stored positions with positions replayed from the trades history
+    RebuildPositions { account_id: Option<Uuid> },
+}
+
+impl AdminCommand {
+    /// The command named by the process arguments (program name excluded); None serves the engine
+    pub fn parse(args: &[String]) -> anyhow::Result<Option<Self>> {
+        let Some((command, rest)) = args.split_first() else {
+            return Ok(None);
+        };
+
+        match command.as_str() {
+            "rebuild-positions" => {
+                let account_id = match rest {
+                    [] => None,
+                    [flag, account] if flag == "--account" => Some(
+                        account
+                            .parse()
+                            .map_err(|e| anyhow::anyhow!("invalid account id {:?}: {}\n{}", account, e, USAGE))?,
+                    ),
+                    _ => anyhow::bail!("unexpected arguments {:?}\n{}", rest, USAGE),
+                };
+                Ok(Some(Self::RebuildPositions { account_id }))
+            }
+            other => anyhow::bail!("unknown command {:?}\n{}", other, USAGE),
+        }
+    }
+
+    pub async fn run(self, pool: PgPool, reads: ReadPool) -> anyhow::Result<()> {
+        match self {
+            Self::RebuildPositions { account_id } => {
+                let keeper = PositionKeeper::new(pool, reads);
+                let rebuilt = keeper.rebuild_all(account_id).await?;
+                tracing::info!(account_id = ?account_id, rebuilt, "Position rebuild complete");
+            }
+        }
+        Ok(())
+    }
+}
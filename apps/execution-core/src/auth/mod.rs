@@ -8,6 +8,9 @@ use std::collections::HashSet;
 use thiserror::Error;
 use uuid::Uuid;
 
+/// Token validation is performed by the gateway; the claims and validation API below are
+/// kept for clients that connect directly
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,
@@ -26,6 +29,8 @@ pub struct AuthContext {
     pub role: String,
     pub permissions: HashSet<String>,
     pub token_jti: String,
+    /// Set when a support agent is acting as the account through an impersonation session
+    pub impersonation: Option<Uuid>,
 }
 
 impl AuthContext {
@@ -40,6 +45,7 @@ impl AuthContext {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Error, Debug)]
 pub enum AuthError {
     #[error("Invalid token: {0}")]
@@ -48,14 +54,6 @@ pub enum AuthError {
     TokenExpired,
     #[error("Token revoked")]
     TokenRevoked,
-    #[error("Insufficient permissions: {0}")]
-    InsufficientPermissions(String),
-    #[error("Account not found")]
-    AccountNotFound,
-    #[error("Account disabled")]
-    AccountDisabled,
-    #[error("Database error: {0}")]
-    DatabaseError(String),
     #[error("Redis error: {0}")]
     RedisError(#[from] redis::RedisError),
     #[error("JWT error: {0}")]
@@ -63,6 +61,7 @@ pub enum AuthError {
 }
 
 pub struct AuthService {
+    #[allow(dead_code)]
     decoding_key: DecodingKey,
 }
 
@@ -74,6 +73,7 @@ impl AuthService {
     }
 
     /// Validate token claims only (without database/redis check)
+    #[allow(dead_code)]
     pub fn validate_token_claims(&self, token: &str) -> Result<Claims, AuthError> {
         let mut validation = Validation::new(Algorithm::HS256);
         validation.validate_exp = true;
@@ -88,6 +88,7 @@ impl AuthService {
     }
 
     /// Check if token is blacklisted
+    #[allow(dead_code)]
     pub async fn check_token_blacklist(
         &self,
         jti: &str,
@@ -99,6 +100,7 @@ impl AuthService {
     }
 
     /// Convert claims to auth context
+    #[allow(dead_code)]
     pub fn claims_to_context(&self, claims: Claims) -> Result<AuthContext, AuthError> {
         let account_id = Uuid::parse_str(&claims.sub)
             .map_err(|_| AuthError::InvalidToken("Invalid UUID in subject".into()))?;
@@ -109,6 +111,7 @@ impl AuthService {
             role: claims.role,
             permissions: claims.permissions.into_iter().collect(),
             token_jti: claims.jti,
+            impersonation: None,
         })
     }
 }
@@ -119,5 +122,11 @@ pub mod permissions {
     pub const ORDERS_CANCEL: &str = "orders:cancel";
     pub const POSITIONS_READ: &str = "positions:read";
     pub const MARKET_READ: &str = "market:read";
+    pub const SANDBOX_MANAGE: &str = "sandbox:manage";
+    pub const ACCOUNTS_PRIVACY: &str = "accounts:privacy";
+    /// Orders breaching a concentration limit are accepted and flagged instead of rejected
+    pub const RISK_OVERRIDE: &str = "risk:override";
+    /// Query as a consenting customer through a time-limited, audited session
+    pub const SUPPORT_IMPERSONATE: &str = "support:impersonate";
     pub const ADMIN_FULL: &str = "admin:full";
 }
\ No newline at end of file
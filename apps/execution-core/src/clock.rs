@@ -0,0 +1,108 @@
+//! Engine Clock
+//! Single source of time for the engine; tests and simulations inject virtual time
+
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub type SharedClock = Arc<dyn Clock>;
+
+pub trait Clock: Send + Sync {
+    /// Wall-clock time for timestamps that are persisted or sent to clients.
+    /// Never goes backwards between calls.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Monotonic time since the clock was created, for timeouts and intervals
+    fn elapsed(&self) -> Duration;
+}
+
+// =====================================================
+// SYSTEM CLOCK
+// =====================================================
+
+/// Production clock: wall time from the OS, clamped so a step back (NTP) never
+/// reorders timestamps, and intervals measured on `Instant`.
+pub struct SystemClock {
+    origin: Instant,
+    last_wall_nanos: AtomicI64,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            last_wall_nanos: AtomicI64::new(i64::MIN),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        let wall = Utc::now();
+        let Some(nanos) = wall.timestamp_nanos_opt() else {
+            return wall;
+        };
+
+        let previous = self.last_wall_nanos.fetch_max(nanos, Ordering::AcqRel);
+        if previous > nanos {
+            DateTime::from_timestamp_nanos(previous)
+        } else {
+            wall
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+// =====================================================
+// MANUAL CLOCK
+// =====================================================
+
+/// Virtual clock that only moves when advanced, for deterministic replays
+#[allow(dead_code)]
+pub struct ManualClock {
+    start: DateTime<Utc>,
+    current: Mutex<DateTime<Utc>>,
+}
+
+#[allow(dead_code)]
+impl ManualClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            current: Mutex::new(start),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        *current += chrono::Duration::from_std(by).unwrap_or(chrono::Duration::MAX);
+    }
+
+    /// Jump to `to`; earlier times are ignored so the clock stays monotonic
+    pub fn set(&self, to: DateTime<Utc>) {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        if to > *current {
+            *current = to;
+        }
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.current.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn elapsed(&self) -> Duration {
+        (self.now() - self.start).to_std().unwrap_or_default()
+    }
+}
@@ -0,0 +1,178 @@
+//! Account Order Settings
+//! Per-account defaults filled into order submissions that leave a field out
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Time-in-force values the orders table accepts
+pub const TIME_IN_FORCE: &[&str] = &["gtc", "ioc", "fok", "day", "gtd"];
+
+/// Time-in-force values an account may default to; gtd needs an expiry per order
+const DEFAULT_TIME_IN_FORCE: &[&str] = &["gtc", "ioc", "fok", "day"];
+
+/// Order types an account may default to
+const DEFAULT_ORDER_TYPES: &[&str] = &["market", "limit"];
+
+/// Largest slippage allowance, as a default or on an order (10%)
+pub const MAX_SLIPPAGE_BPS: Decimal = dec!(1000);
+
+const MAX_STRATEGY_TAG_LEN: usize = 64;
+
+/// Metadata key the strategy tag is stored under
+pub const STRATEGY_TAG_KEY: &str = "strategy_tag";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct OrderDefaults {
+    #[serde(alias = "timeInForce", default)]
+    #[sqlx(rename = "default_time_in_force")]
+    pub time_in_force: Option<String>,
+    #[serde(alias = "orderType", default)]
+    #[sqlx(rename = "default_order_type")]
+    pub order_type: Option<String>,
+    /// Market orders without a price get a protective limit this far from the last trade
+    #[serde(alias = "maxSlippageBps", default)]
+    pub max_slippage_bps: Option<Decimal>,
+    /// Stored in the order's metadata unless the order sets it
+    #[serde(alias = "strategyTag", default)]
+    pub strategy_tag: Option<String>,
+}
+
+impl OrderDefaults {
+    /// Lowercase the enumerated fields and check every one is usable
+    fn validate(self) -> Result<Self, String> {
+        let time_in_force = self.time_in_force.map(|tif| tif.to_lowercase());
+        if let Some(tif) = &time_in_force {
+            if !DEFAULT_TIME_IN_FORCE.contains(&tif.as_str()) {
+                return Err(format!("time_in_force must be one of {}", DEFAULT_TIME_IN_FORCE.join(", ")));
+            }
+        }
+
+        let order_type = self.order_type.map(|t| t.to_lowercase());
+        if let Some(order_type) = &order_type {
+            if !DEFAULT_ORDER_TYPES.contains(&order_type.as_str()) {
+                return Err(format!("order_type must be one of {}", DEFAULT_ORDER_TYPES.join(", ")));
+            }
+        }
+
+        if let Some(bps) = self.max_slippage_bps {
+            validate_slippage(bps)?;
+        }
+
+        let strategy_tag = self.strategy_tag.filter(|tag| !tag.trim().is_empty());
+        if strategy_tag.as_ref().is_some_and(|tag| tag.len() > MAX_STRATEGY_TAG_LEN) {
+            return Err(format!("strategy_tag must be at most {} bytes", MAX_STRATEGY_TAG_LEN));
+        }
+
+        Ok(Self { time_in_force, order_type, max_slippage_bps: self.max_slippage_bps, strategy_tag })
+    }
+}
+
+pub fn validate_slippage(bps: Decimal) -> Result<(), String> {
+    if bps < Decimal::ZERO || bps > MAX_SLIPPAGE_BPS {
+        return Err(format!("max_slippage_bps must be between 0 and {}", MAX_SLIPPAGE_BPS));
+    }
+    Ok(())
+}
+
+// =====================================================
+// ACCOUNT SETTINGS
+// =====================================================
+
+pub struct AccountSettings {
+    pool: PgPool,
+    /// Defaults by account, including accounts that have none
+    cache: RwLock<HashMap<Uuid, OrderDefaults>>,
+}
+
+impl AccountSettings {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The caller's order defaults
+    pub async fn get(&self, auth: &AuthContext) -> Result<OrderDefaults, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_READ) {
+            return Err(EngineError::Auth(
+                "orders:read required".into()
+            ));
+        }
+
+        self.defaults(auth.account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    /// Replace the caller's order defaults; omitted fields are cleared
+    pub async fn update(
+        &self,
+        auth: &AuthContext,
+        defaults: OrderDefaults,
+    ) -> Result<OrderDefaults, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CREATE) {
+            return Err(EngineError::Auth(
+                "orders:create required".into()
+            ));
+        }
+
+        let defaults = defaults.validate().map_err(EngineError::Validation)?;
+
+        let stored: OrderDefaults = sqlx::query_as(
+            r#"INSERT INTO account_order_settings (account_id, default_time_in_force, default_order_type,
+                                                   max_slippage_bps, strategy_tag)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT (account_id) DO UPDATE SET
+                   default_time_in_force = $2,
+                   default_order_type = $3,
+                   max_slippage_bps = $4,
+                   strategy_tag = $5,
+                   updated_at = NOW()
+               RETURNING default_time_in_force, default_order_type, max_slippage_bps, strategy_tag"#
+        )
+            .bind(auth.account_id)
+            .bind(&defaults.time_in_force)
+            .bind(&defaults.order_type)
+            .bind(defaults.max_slippage_bps)
+            .bind(&defaults.strategy_tag)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        self.cache.write().await.insert(auth.account_id, stored.clone());
+        tracing::info!(account_id = %auth.account_id, "Account order defaults updated");
+        Ok(stored)
+    }
+
+    /// Defaults applied to the account's orders, loaded once and then served from memory
+    pub async fn defaults(&self, account_id: Uuid) -> Result<OrderDefaults, sqlx::Error> {
+        if let Some(defaults) = self.cache.read().await.get(&account_id) {
+            return Ok(defaults.clone());
+        }
+
+        let stored: Option<OrderDefaults> = sqlx::query_as(
+            r#"SELECT default_time_in_force, default_order_type, max_slippage_bps, strategy_tag
+               FROM account_order_settings WHERE account_id = $1"#
+        )
+            .bind(account_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let defaults = stored.unwrap_or_default();
+        self.cache.write().await.insert(account_id, defaults.clone());
+        Ok(defaults)
+    }
+
+    pub async fn evict(&self, account_id: Uuid) {
+        self.cache.write().await.remove(&account_id);
+    }
+}
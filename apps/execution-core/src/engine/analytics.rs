@@ -0,0 +1,273 @@
+//! Analytics Export
+//! Each finished UTC day of trades and market ticks copied to object storage as date-partitioned
+//! Parquet, read from the replica, so history can be analysed without querying the database
+
+use crate::clock::SharedClock;
+use crate::engine::exports::{to_parquet, Cell, ColumnType, ExportTable};
+use crate::observability::metrics::get_metrics;
+use crate::storage::{ObjectStorage, ReadPool};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// A day is exported once it has been over this long, leaving time for late writes
+const SETTLE_SECS: i64 = 3_600;
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone)]
+pub struct AnalyticsConfig {
+    /// Days exported on the first run, before which nothing is copied
+    pub backfill_days: i64,
+    /// Rows per Parquet file; a day with more is split into several parts
+    pub rows_per_file: i64,
+    /// Days exported per dataset per run, so a long backlog is caught up gradually
+    pub max_days_per_run: i64,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            backfill_days: 7,
+            rows_per_file: 500_000,
+            max_days_per_run: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dataset {
+    Trades,
+    Ticks,
+}
+
+impl Dataset {
+    pub const ALL: [Dataset; 2] = [Dataset::Trades, Dataset::Ticks];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Trades => "trades",
+            Self::Ticks => "ticks",
+        }
+    }
+
+    /// Exported columns; the first two are the time and the key that page through a day
+    fn columns(&self) -> &'static [(&'static str, ColumnType)] {
+        match self {
+            Self::Trades => &TRADE_COLUMNS,
+            Self::Ticks => &TICK_COLUMNS,
+        }
+    }
+
+    /// A page of the day from `$1` to `$2`, after the row at time `$3` and key `$4`, at most
+    /// `$5` rows
+    fn query(&self) -> &'static str {
+        match self {
+            Self::Trades => r#"SELECT executed_at, id::text, order_id::text, account_id::text, symbol, side,
+                                      quantity, price, contra_order_id::text
+                               FROM trades
+                               WHERE executed_at >= $1 AND executed_at < $2
+                                 AND (executed_at > $3 OR (executed_at = $3 AND id::text > $4))
+                               ORDER BY executed_at, id::text
+                               LIMIT $5"#,
+            Self::Ticks => r#"SELECT timestamp, symbol, bid_price, ask_price, bid_size, ask_size,
+                                     last_price, last_size, volume, source
+                              FROM market_ticks
+                              WHERE timestamp >= $1 AND timestamp < $2
+                                AND (timestamp > $3 OR (timestamp = $3 AND symbol > $4))
+                              ORDER BY timestamp, symbol
+                              LIMIT $5"#,
+        }
+    }
+}
+
+const TRADE_COLUMNS: [(&str, ColumnType); 9] = [
+    ("executed_at", ColumnType::Timestamp),
+    ("id", ColumnType::Text),
+    ("order_id", ColumnType::Text),
+    ("account_id", ColumnType::Text),
+    ("symbol", ColumnType::Text),
+    ("side", ColumnType::Text),
+    ("quantity", ColumnType::Decimal),
+    ("price", ColumnType::Decimal),
+    ("contra_order_id", ColumnType::Text),
+];
+
+const TICK_COLUMNS: [(&str, ColumnType); 10] = [
+    ("timestamp", ColumnType::Timestamp),
+    ("symbol", ColumnType::Text),
+    ("bid_price", ColumnType::Decimal),
+    ("ask_price", ColumnType::Decimal),
+    ("bid_size", ColumnType::Decimal),
+    ("ask_size", ColumnType::Decimal),
+    ("last_price", ColumnType::Decimal),
+    ("last_size", ColumnType::Decimal),
+    ("volume", ColumnType::Decimal),
+    ("source", ColumnType::Text),
+];
+
+/// One day of one dataset, written
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Partition {
+    pub dataset: Dataset,
+    pub day: NaiveDate,
+    pub files: i32,
+    pub rows: i64,
+}
+
+/// Object key of part `part` of a day, Hive-style so query engines prune by date
+pub fn partition_key(dataset: Dataset, day: NaiveDate, part: i32) -> String {
+    format!("{}/date={}/part-{:05}.parquet", dataset.as_str(), day.format("%Y-%m-%d"), part)
+}
+
+/// Days to export after `last` (the latest exported, if any) up to the last settled day
+/// at `now`: from `backfill_days` back on the first run, at most `max_days` at once
+pub fn due_days(last: Option<NaiveDate>, now: DateTime<Utc>, backfill_days: i64, max_days: i64) -> Vec<NaiveDate> {
+    let settled = (now - Duration::seconds(SETTLE_SECS)).date_naive() - Duration::days(1);
+    let first = match last {
+        Some(last) => last + Duration::days(1),
+        None => settled - Duration::days(backfill_days - 1),
+    };
+
+    first
+        .iter_days()
+        .take_while(|day| *day <= settled)
+        .take(max_days.max(0) as usize)
+        .collect()
+}
+
+// =====================================================
+// ANALYTICS EXPORTER
+// =====================================================
+
+pub struct AnalyticsExporter {
+    pool: PgPool,
+    reads: ReadPool,
+    storage: Arc<ObjectStorage>,
+    config: AnalyticsConfig,
+    clock: SharedClock,
+}
+
+impl AnalyticsExporter {
+    pub fn new(pool: PgPool, reads: ReadPool, storage: Arc<ObjectStorage>, config: AnalyticsConfig, clock: SharedClock) -> Self {
+        Self { pool, reads, storage, config, clock }
+    }
+
+    /// Export every due day of every dataset, oldest first. A failing dataset is logged and
+    /// retried from the same day next run, while the others carry on.
+    pub async fn run(&self) -> Vec<Partition> {
+        let mut written = Vec::new();
+
+        for dataset in Dataset::ALL {
+            let result = async {
+                let last: Option<NaiveDate> = sqlx::query_scalar(
+                    "SELECT MAX(day) FROM analytics_partitions WHERE dataset = $1"
+                )
+                    .bind(dataset.as_str())
+                    .fetch_one(&self.pool)
+                    .await?;
+
+                let days = due_days(last, self.clock.now(), self.config.backfill_days, self.config.max_days_per_run);
+                for day in days {
+                    written.push(self.export_day(dataset, day).await?);
+                }
+                Ok::<_, anyhow::Error>(())
+            }
+            .await;
+
+            if let Err(e) = result {
+                tracing::error!(dataset = dataset.as_str(), error = %e, "Analytics export failed");
+            }
+        }
+
+        written
+    }
+
+    /// Write one day in parts of `rows_per_file` and record it. A day with no rows still gets
+    /// an empty part, so readers can tell an empty day from a missing one.
+    async fn export_day(&self, dataset: Dataset, day: NaiveDate) -> anyhow::Result<Partition> {
+        let start = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        let end = start + Duration::days(1);
+        let (mut after_at, mut after_key) = (start, String::new());
+        let (mut files, mut rows) = (0, 0);
+        let limit = self.config.rows_per_file.max(1);
+
+        loop {
+            let page: Vec<PgRow> = self.reads.run("analytics.export_page", |pool| {
+                let after_key = after_key.clone();
+                async move {
+                    sqlx::query(dataset.query())
+                        .bind(start)
+                        .bind(end)
+                        .bind(after_at)
+                        .bind(after_key)
+                        .bind(limit)
+                        .fetch_all(&pool)
+                        .await
+                }
+            }).await?;
+
+            let table = ExportTable::decode(dataset.columns(), &page)?;
+            if !table.rows.is_empty() || files == 0 {
+                self.storage.put(&partition_key(dataset, day, files), to_parquet(&table)?).await?;
+                files += 1;
+                rows += table.rows.len() as i64;
+            }
+
+            match table.rows.last().map(|row| (&row[0], &row[1])) {
+                Some((Cell::Timestamp(Some(at)), Cell::Text(Some(key))))
+                    if table.rows.len() as i64 == limit =>
+                {
+                    (after_at, after_key) = (*at, key.clone());
+                }
+                _ => break,
+            }
+        }
+
+        sqlx::query(
+            r#"INSERT INTO analytics_partitions (dataset, day, files, rows, exported_at)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT (dataset, day) DO UPDATE
+               SET files = EXCLUDED.files, rows = EXCLUDED.rows, exported_at = EXCLUDED.exported_at"#
+        )
+            .bind(dataset.as_str())
+            .bind(day)
+            .bind(files)
+            .bind(rows)
+            .bind(self.clock.now())
+            .execute(&self.pool)
+            .await?;
+
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.analytics_rows_exported_total.with_label_values(&[dataset.as_str()]).inc_by(rows as f64);
+        }
+
+        Ok(Partition { dataset, day, files, rows })
+    }
+}
+
+/// Export due days every `interval`
+pub async fn run_analytics_export(exporter: AnalyticsExporter, interval: std::time::Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        for partition in exporter.run().await {
+            tracing::info!(
+                dataset = partition.dataset.as_str(),
+                day = %partition.day,
+                files = partition.files,
+                rows = partition.rows,
+                "Analytics partition exported"
+            );
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Cancel-on-Disconnect
+//! A gateway registers a session with a heartbeat timeout; if its heartbeats on
+//! `sessions.heartbeat` stop for longer than that, the account's open orders are cancelled
+
+use crate::auth::{AuthContext, permissions};
+use crate::clock::SharedClock;
+use crate::engine::error::EngineError;
+use crate::engine::order_processor::{CancelAllRequest, CancelAllSummary};
+use crate::engine::OrderProcessor;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Reason recorded on the orders a missed heartbeat cancels
+const CANCEL_REASON: &str = "cancel_on_disconnect";
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone)]
+pub struct DisconnectConfig {
+    /// Longest heartbeat timeout a session may declare
+    pub max_timeout_secs: i64,
+}
+
+impl Default for DisconnectConfig {
+    fn default() -> Self {
+        Self { max_timeout_secs: 300 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DisconnectSession {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub timeout_secs: i32,
+    pub status: String,
+    pub registered_at: DateTime<Utc>,
+    pub last_heartbeat_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub cancelled_orders: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterSession {
+    #[serde(alias = "timeout_secs")]
+    pub timeout_secs: i64,
+}
+
+/// The session a heartbeat or close is for
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRef {
+    #[serde(alias = "session_id")]
+    pub session_id: Uuid,
+}
+
+/// Published on `sessions.disconnected` when missed heartbeats cancelled a session's orders
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDisconnected {
+    pub session: DisconnectSession,
+    pub summary: CancelAllSummary,
+}
+
+/// Whether a session last heard from at `last_heartbeat_at` has timed out at `now`. Time
+/// the engine was down does not count against it: the gateway gets a full timeout from
+/// `started_at` to heartbeat again.
+pub fn timed_out(
+    last_heartbeat_at: DateTime<Utc>,
+    timeout_secs: i32,
+    started_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> bool {
+    last_heartbeat_at.max(started_at) + Duration::seconds(timeout_secs as i64) < now
+}
+
+/// Sessions cancel orders on the account's behalf, so only callers that may cancel run them
+fn authorize(auth: &AuthContext) -> Result<(), EngineError> {
+    if !auth.has_permission(permissions::ORDERS_CANCEL) {
+        return Err(EngineError::Auth("orders:cancel required".into()));
+    }
+    Ok(())
+}
+
+// =====================================================
+// CANCEL-ON-DISCONNECT
+// =====================================================
+
+pub struct CancelOnDisconnect {
+    pool: PgPool,
+    order_processor: Arc<OrderProcessor>,
+    config: DisconnectConfig,
+    clock: SharedClock,
+    /// When this engine started watching; heartbeats missed before it are forgiven
+    started_at: DateTime<Utc>,
+}
+
+impl CancelOnDisconnect {
+    pub fn new(pool: PgPool, order_processor: Arc<OrderProcessor>, config: DisconnectConfig, clock: SharedClock) -> Self {
+        let started_at = clock.now();
+        Self { pool, order_processor, config, clock, started_at }
+    }
+
+    /// Start a session for the caller's account, counting as its first heartbeat
+    pub async fn register(&self, auth: &AuthContext, req: &RegisterSession) -> Result<DisconnectSession, EngineError> {
+        authorize(auth)?;
+        if req.timeout_secs <= 0 || req.timeout_secs > self.config.max_timeout_secs {
+            return Err(EngineError::Validation(format!(
+                "timeoutSecs must be between 1 and {}",
+                self.config.max_timeout_secs
+            )));
+        }
+
+        let now = self.clock.now();
+        let session = sqlx::query_as(
+            r#"INSERT INTO disconnect_sessions (id, account_id, timeout_secs, registered_at, last_heartbeat_at)
+               VALUES ($1, $2, $3, $4, $4)
+               RETURNING *"#
+        )
+            .bind(Uuid::new_v4())
+            .bind(auth.account_id)
+            .bind(req.timeout_secs as i32)
+            .bind(now)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+
+    /// Keep an active session alive
+    pub async fn heartbeat(&self, auth: &AuthContext, session_id: Uuid) -> Result<DisconnectSession, EngineError> {
+        authorize(auth)?;
+        let session: Option<DisconnectSession> = sqlx::query_as(
+            r#"UPDATE disconnect_sessions SET last_heartbeat_at = $3
+               WHERE id = $1 AND account_id = $2 AND status = 'active'
+               RETURNING *"#
+        )
+            .bind(session_id)
+            .bind(auth.account_id)
+            .bind(self.clock.now())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match session {
+            Some(session) => Ok(session),
+            None => Err(self.not_active(auth, session_id).await?),
+        }
+    }
+
+    /// End a session without cancelling anything, as a gateway does on a clean shutdown
+    pub async fn close(&self, auth: &AuthContext, session_id: Uuid) -> Result<DisconnectSession, EngineError> {
+        authorize(auth)?;
+        let session: Option<DisconnectSession> = sqlx::query_as(
+            r#"UPDATE disconnect_sessions SET status = 'closed', ended_at = $3
+               WHERE id = $1 AND account_id = $2 AND status = 'active'
+               RETURNING *"#
+        )
+            .bind(session_id)
+            .bind(auth.account_id)
+            .bind(self.clock.now())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match session {
+            Some(session) => Ok(session),
+            None => Err(self.not_active(auth, session_id).await?),
+        }
+    }
+
+    /// Why a heartbeat or close found no active session
+    async fn not_active(&self, auth: &AuthContext, session_id: Uuid) -> Result<EngineError, EngineError> {
+        let status: Option<String> = sqlx::query_scalar(
+            "SELECT status FROM disconnect_sessions WHERE id = $1 AND account_id = $2"
+        )
+            .bind(session_id)
+            .bind(auth.account_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(match status.as_deref() {
+            Some("triggered") => EngineError::Conflict(
+                "Session timed out and its orders were cancelled; register a new one".into()
+            ),
+            Some(_) => EngineError::Conflict("Session is closed".into()),
+            None => EngineError::Validation("Unknown session".into()),
+        })
+    }
+
+    /// Cancel the open orders of every session past its timeout and end the session. A
+    /// session whose cancel fails stays active and is retried next sweep.
+    pub async fn sweep(&self) -> anyhow::Result<Vec<SessionDisconnected>> {
+        let now = self.clock.now();
+        let active: Vec<DisconnectSession> = sqlx::query_as(
+            "SELECT * FROM disconnect_sessions WHERE status = 'active'"
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut disconnected = Vec::new();
+        for session in active {
+            if !timed_out(session.last_heartbeat_at, session.timeout_secs, self.started_at, now) {
+                continue;
+            }
+
+            // Claim it first, so a heartbeat racing the sweep either lands before and keeps
+            // the session, or finds it triggered
+            let claimed: Option<DisconnectSession> = sqlx::query_as(
+                r#"UPDATE disconnect_sessions SET status = 'triggered', ended_at = $2
+                   WHERE id = $1 AND status = 'active' AND last_heartbeat_at = $3
+                   RETURNING *"#
+            )
+                .bind(session.id)
+                .bind(now)
+                .bind(session.last_heartbeat_at)
+                .fetch_optional(&self.pool)
+                .await?;
+            let Some(session) = claimed else { continue };
+
+            let summary = match self
+                .order_processor
+                .cancel_account_orders(session.account_id, &CancelAllRequest::default(), CANCEL_REASON)
+                .await
+            {
+                Ok(summary) => summary,
+                Err(e) => {
+                    sqlx::query(
+                        "UPDATE disconnect_sessions SET status = 'active', ended_at = NULL WHERE id = $1"
+                    )
+                        .bind(session.id)
+                        .execute(&self.pool)
+                        .await?;
+                    tracing::error!(session_id = %session.id, error = %e, "Cancel-on-disconnect failed, will retry");
+                    continue;
+                }
+            };
+
+            let session: DisconnectSession = sqlx::query_as(
+                "UPDATE disconnect_sessions SET cancelled_orders = $2 WHERE id = $1 RETURNING *"
+            )
+                .bind(session.id)
+                .bind(summary.cancelled as i32)
+                .fetch_one(&self.pool)
+                .await?;
+            disconnected.push(SessionDisconnected { session, summary });
+        }
+
+        Ok(disconnected)
+    }
+}
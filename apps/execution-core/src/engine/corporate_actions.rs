@@ -0,0 +1,522 @@
+//! Corporate Action Processing
+//! Stock splits adjust positions, open orders and balances; cash dividends are paid to ledgers
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+use crate::clock::SharedClock;
+use crate::engine::ledger::{spot_pair, Posting, HOUSE_ACCOUNT};
+use crate::engine::{Ledger, OrderProcessor, PositionKeeper};
+use crate::storage::ReadPool;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use uuid::Uuid;
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionType {
+    Split,
+    CashDividend,
+}
+
+impl ActionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionType::Split => "split",
+            ActionType::CashDividend => "cash_dividend",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnounceRequest {
+    pub symbol: String,
+    #[serde(alias = "action_type")]
+    pub action_type: ActionType,
+    /// New shares per old share (2 = 2-for-1)
+    #[serde(alias = "split_ratio", default)]
+    pub split_ratio: Option<Decimal>,
+    #[serde(alias = "dividend_per_share", default)]
+    pub dividend_per_share: Option<Decimal>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Defaults to now, i.e. the action is processed immediately
+    #[serde(alias = "ex_date", default)]
+    pub ex_date: Option<DateTime<Utc>>,
+    #[serde(alias = "pay_date", default)]
+    pub pay_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CorporateAction {
+    pub id: Uuid,
+    pub symbol: String,
+    pub action_type: String,
+    pub split_ratio: Option<Decimal>,
+    pub dividend_per_share: Option<Decimal>,
+    pub currency: Option<String>,
+    pub ex_date: DateTime<Utc>,
+    pub pay_date: Option<DateTime<Utc>>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+const ACTION_COLUMNS: &str = "id, symbol, action_type, split_ratio, dividend_per_share, currency, \
+                              ex_date, pay_date, status, created_at, processed_at";
+
+// =====================================================
+// CORPORATE ACTION PROCESSOR
+// =====================================================
+
+pub struct CorporateActionProcessor {
+    pool: PgPool,
+    reads: ReadPool,
+    ledger: Arc<Ledger>,
+    order_processor: Arc<OrderProcessor>,
+    position_keeper: Arc<PositionKeeper>,
+    clock: SharedClock,
+}
+
+impl CorporateActionProcessor {
+    pub fn new(
+        pool: PgPool,
+        reads: ReadPool,
+        ledger: Arc<Ledger>,
+        order_processor: Arc<OrderProcessor>,
+        position_keeper: Arc<PositionKeeper>,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            pool,
+            reads,
+            ledger,
+            order_processor,
+            position_keeper,
+            clock,
+        }
+    }
+
+    /// Record a split or dividend; it is processed once its ex-date is reached
+    pub async fn announce(
+        &self,
+        auth: &AuthContext,
+        req: AnnounceRequest,
+    ) -> Result<CorporateAction, EngineError> {
+        if !auth.has_permission(permissions::ADMIN_FULL) {
+            return Err(EngineError::Auth(
+                "admin:full required".into()
+            ));
+        }
+
+        let symbol = req.symbol.trim().to_uppercase();
+        if symbol.is_empty() {
+            return Err(EngineError::Validation("symbol is required".into()));
+        }
+
+        let ex_date = req.ex_date.unwrap_or_else(|| self.clock.now());
+
+        let (split_ratio, dividend_per_share, currency, pay_date) = match req.action_type {
+            ActionType::Split => match req.split_ratio {
+                Some(r) if r > dec!(0) && r != dec!(1) => (Some(r), None, None, None),
+                _ => {
+                    return Err(EngineError::Validation(
+                        "split_ratio must be positive and not 1".into()
+                    ));
+                }
+            },
+            ActionType::CashDividend => {
+                let dps = req.dividend_per_share
+                    .filter(|d| *d > dec!(0))
+                    .ok_or_else(|| EngineError::Validation("dividend_per_share must be positive".into()))?;
+                let pay_date = req.pay_date.unwrap_or(ex_date);
+                if pay_date < ex_date {
+                    return Err(EngineError::Validation("pay_date must not be before ex_date".into()));
+                }
+                let currency = req.currency
+                    .map(|c| c.to_uppercase())
+                    .unwrap_or_else(|| self.ledger.default_quote().to_string());
+                (None, Some(dps), Some(currency), Some(pay_date))
+            }
+        };
+
+        let action: CorporateAction = sqlx::query_as(&format!(
+            r#"INSERT INTO corporate_actions (symbol, action_type, split_ratio, dividend_per_share,
+                                              currency, ex_date, pay_date, announced_by)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               RETURNING {}"#,
+            ACTION_COLUMNS
+        ))
+            .bind(&symbol)
+            .bind(req.action_type.as_str())
+            .bind(split_ratio)
+            .bind(dividend_per_share)
+            .bind(&currency)
+            .bind(ex_date)
+            .bind(pay_date)
+            .bind(auth.account_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        tracing::info!(
+            action_id = %action.id,
+            symbol = %action.symbol,
+            action_type = %action.action_type,
+            "Corporate action announced"
+        );
+
+        Ok(action)
+    }
+
+    /// Withdraw an action that has not been processed yet
+    pub async fn cancel(&self, auth: &AuthContext, action_id: Uuid) -> Result<bool, EngineError> {
+        if !auth.has_permission(permissions::ADMIN_FULL) {
+            return Err(EngineError::Auth(
+                "admin:full required".into()
+            ));
+        }
+
+        let result = sqlx::query(
+            r#"UPDATE corporate_actions SET status = 'cancelled', processed_at = $2
+               WHERE id = $1 AND status = 'announced'"#
+        )
+            .bind(action_id)
+            .bind(self.clock.now())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list(
+        &self,
+        auth: &AuthContext,
+        symbol: Option<&str>,
+    ) -> Result<Vec<CorporateAction>, EngineError> {
+        if !auth.has_permission(permissions::MARKET_READ) {
+            return Err(EngineError::Auth(
+                "market:read required".into()
+            ));
+        }
+
+        let sql = format!(
+            r#"SELECT {} FROM corporate_actions
+               WHERE $1::text IS NULL OR symbol = $1
+               ORDER BY ex_date DESC
+               LIMIT 100"#,
+            ACTION_COLUMNS
+        );
+        let symbol = symbol.map(str::to_uppercase);
+
+        self.reads.run("corporate_actions.list", |pool| {
+            let (sql, symbol) = (sql.clone(), symbol.clone());
+            async move {
+                sqlx::query_as(&sql)
+                    .bind(symbol)
+                    .fetch_all(&pool)
+                    .await
+            }
+        })
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    // =====================================================
+    // PROCESSING
+    // =====================================================
+
+    /// Apply splits and fix dividend entitlements at ex-date, pay dividends at pay date
+    pub async fn process_due(&self) -> anyhow::Result<usize> {
+        let due: Vec<CorporateAction> = sqlx::query_as(&format!(
+            r#"SELECT {} FROM corporate_actions
+               WHERE (status = 'announced' AND ex_date <= $1)
+                  OR (status = 'entitled' AND pay_date <= $1)
+               ORDER BY ex_date, created_at"#,
+            ACTION_COLUMNS
+        ))
+            .bind(self.clock.now())
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut processed = 0;
+        // A split rewrites the symbol's open orders, so each instance processes the symbols it trades
+        for action in due.iter().filter(|a| self.order_processor.owns(&a.symbol)) {
+            let result = match (action.action_type.as_str(), action.status.as_str()) {
+                ("split", "announced") => self.apply_split(action).await,
+                ("cash_dividend", "announced") => self.fix_entitlements(action).await,
+                ("cash_dividend", "entitled") => self.pay_dividend(action).await,
+                _ => Ok(false),
+            };
+
+            match result {
+                Ok(true) => processed += 1,
+                Ok(false) => {}
+                Err(e) => tracing::error!(action_id = %action.id, "Corporate action failed: {}", e),
+            }
+        }
+
+        Ok(processed)
+    }
+
+    /// Multiply quantities and divide prices by the ratio for positions, open orders and balances
+    async fn apply_split(&self, action: &CorporateAction) -> anyhow::Result<bool> {
+        let ratio = action.split_ratio.unwrap_or(dec!(1));
+        let mut tx = self.pool.begin().await?;
+
+        // Claiming the action first makes processing idempotent across instances
+        let claimed = sqlx::query(
+            r#"UPDATE corporate_actions SET status = 'applied', processed_at = $2
+               WHERE id = $1 AND status = 'announced'"#
+        )
+            .bind(action.id)
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        if claimed == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"INSERT INTO corporate_action_adjustments (action_id, account_id, target,
+                                                         quantity_before, quantity_after, price_before, price_after)
+               SELECT $1, account_id, 'position', net_quantity, ROUND(net_quantity * $3, 8),
+                      avg_price, ROUND(avg_price / $3, 8)
+               FROM positions WHERE symbol = $2 AND net_quantity != 0"#
+        )
+            .bind(action.id)
+            .bind(&action.symbol)
+            .bind(ratio)
+            .execute(&mut *tx)
+            .await?;
+
+        let positions = sqlx::query(
+            r#"UPDATE positions
+               SET net_quantity = net_quantity * $2, avg_price = avg_price / $2,
+                   sequence = sequence + 1, updated_at = NOW()
+               WHERE symbol = $1 AND net_quantity != 0"#
+        )
+            .bind(&action.symbol)
+            .bind(ratio)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        sqlx::query(
+            r#"INSERT INTO corporate_action_adjustments (action_id, account_id, target, order_id,
+                                                         quantity_before, quantity_after, price_before, price_after)
+               SELECT $1, account_id, 'order', id, quantity, ROUND(quantity * $3, 8),
+                      price, ROUND(price / $3, 8)
+               FROM orders WHERE symbol = $2 AND status IN ('waiting', 'pending', 'partially_filled')"#
+        )
+            .bind(action.id)
+            .bind(&action.symbol)
+            .bind(ratio)
+            .execute(&mut *tx)
+            .await?;
+
+        let orders = sqlx::query(
+            r#"UPDATE orders
+               SET quantity = quantity * $2,
+                   filled_quantity = filled_quantity * $2,
+                   price = price / $2,
+                   stop_price = stop_price / $2,
+                   trail_amount = trail_amount / $2,
+                   avg_fill_price = avg_fill_price / $2,
+                   updated_at = NOW()
+               WHERE symbol = $1 AND status IN ('waiting', 'pending', 'partially_filled')"#
+        )
+            .bind(&action.symbol)
+            .bind(ratio)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        // Fill keys are quantities of the split symbol too, so a fill after the split never
+        // meets the key of one before it
+        sqlx::query(
+            r#"UPDATE fill_dedup SET filled_before = filled_before * $2
+               WHERE order_id IN (SELECT id FROM orders
+                                  WHERE symbol = $1 AND status IN ('waiting', 'pending', 'partially_filled'))"#
+        )
+            .bind(&action.symbol)
+            .bind(ratio)
+            .execute(&mut *tx)
+            .await?;
+
+        // Parent-fill thresholds are quantities of the split symbol too
+        sqlx::query(
+            r#"UPDATE order_triggers SET fill_threshold = fill_threshold * $2
+               WHERE status = 'armed'
+                 AND parent_order_id IN (SELECT id FROM orders WHERE symbol = $1)"#
+        )
+            .bind(&action.symbol)
+            .bind(ratio)
+            .execute(&mut *tx)
+            .await?;
+
+        let mut balances = 0;
+        if let Some((base, _)) = spot_pair(&action.symbol, self.ledger.default_quote()) {
+            let changes = self.ledger.apply_split(&mut tx, &base, ratio, action.id).await?;
+            balances = changes.len();
+
+            for (account_id, before, after) in changes {
+                sqlx::query(
+                    r#"INSERT INTO corporate_action_adjustments (action_id, account_id, target,
+                                                                 quantity_before, quantity_after)
+                       VALUES ($1, $2, 'balance', $3, $4)"#
+                )
+                    .bind(action.id)
+                    .bind(account_id)
+                    .bind(before)
+                    .bind(after)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        self.order_processor.apply_split(&action.symbol, ratio).await?;
+        self.position_keeper.reload_symbol(&action.symbol).await?;
+
+        tracing::info!(
+            action_id = %action.id,
+            symbol = %action.symbol,
+            ratio = %ratio,
+            positions,
+            orders,
+            balances,
+            "Split applied"
+        );
+
+        Ok(true)
+    }
+
+    /// Fix who is owed the dividend from long positions held at ex-date
+    async fn fix_entitlements(&self, action: &CorporateAction) -> anyhow::Result<bool> {
+        let dps = action.dividend_per_share.unwrap_or_default();
+        let mut tx = self.pool.begin().await?;
+
+        let claimed = sqlx::query(
+            "UPDATE corporate_actions SET status = 'entitled' WHERE id = $1 AND status = 'announced'"
+        )
+            .bind(action.id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        if claimed == 0 {
+            return Ok(false);
+        }
+
+        // Paper shorts carry no borrow, so they are not charged the dividend
+        let entitled = sqlx::query(
+            r#"INSERT INTO dividend_entitlements (action_id, account_id, quantity, amount)
+               SELECT $1, account_id, net_quantity, ROUND(net_quantity * $3, 8)
+               FROM positions WHERE symbol = $2 AND net_quantity > 0"#
+        )
+            .bind(action.id)
+            .bind(&action.symbol)
+            .bind(dps)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+
+        tracing::info!(action_id = %action.id, symbol = %action.symbol, entitled, "Dividend entitlements fixed");
+
+        // Same-day pay dates are paid in the same pass
+        if action.pay_date.is_some_and(|d| d <= self.clock.now()) {
+            self.pay_dividend(action).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Credit every entitlement in one journal against the house account
+    async fn pay_dividend(&self, action: &CorporateAction) -> anyhow::Result<bool> {
+        let currency = action.currency.clone()
+            .unwrap_or_else(|| self.ledger.default_quote().to_string());
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let claimed = sqlx::query(
+            "UPDATE corporate_actions SET status = 'paid', processed_at = $2 WHERE id = $1 AND status = 'entitled'"
+        )
+            .bind(action.id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        if claimed == 0 {
+            return Ok(false);
+        }
+
+        let entitlements: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            r#"SELECT account_id, amount FROM dividend_entitlements
+               WHERE action_id = $1 AND paid_at IS NULL AND amount != 0"#
+        )
+            .bind(action.id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut postings: Vec<Posting> = entitlements
+            .iter()
+            .map(|(account_id, amount)| Posting {
+                account_id: *account_id,
+                asset: currency.clone(),
+                amount: *amount,
+            })
+            .collect();
+
+        let total: Decimal = postings.iter().map(|p| p.amount).sum();
+        if !postings.is_empty() {
+            postings.push(Posting { account_id: HOUSE_ACCOUNT, asset: currency.clone(), amount: -total });
+            self.ledger.post(&mut tx, "dividend", Some(action.id), &postings).await?;
+        }
+
+        sqlx::query(
+            r#"INSERT INTO corporate_action_adjustments (action_id, account_id, target,
+                                                         quantity_before, cash_amount)
+               SELECT action_id, account_id, 'dividend', quantity, amount
+               FROM dividend_entitlements WHERE action_id = $1 AND paid_at IS NULL"#
+        )
+            .bind(action.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE dividend_entitlements SET paid_at = $2 WHERE action_id = $1 AND paid_at IS NULL")
+            .bind(action.id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            action_id = %action.id,
+            symbol = %action.symbol,
+            accounts = entitlements.len(),
+            total = %total,
+            currency = %currency,
+            "Dividend paid"
+        );
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,384 @@
+//! Dust Sweeping
+//! Positions and balances worth less than a threshold are flagged, or closed against the
+//! house account at the last trade, so leftovers from trading do not clutter accounts
+
+use crate::clock::SharedClock;
+use crate::engine::ledger::{spot_pair, Posting, HOUSE_ACCOUNT};
+use crate::engine::{Ledger, OrderProcessor, PositionKeeper};
+use crate::ids::SharedIdGenerator;
+use crate::observability::metrics::get_metrics;
+
+use enthropic_domain::dust::{closing_side, dust_value, is_dust};
+use enthropic_domain::{Fill, Position};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DustAction {
+    /// Mark dust positions with `dust_flagged_at` and leave them open
+    Flag,
+    /// Close dust positions and sweep dust balances into the default quote asset
+    Close,
+}
+
+impl DustAction {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "flag" => Some(DustAction::Flag),
+            "close" => Some(DustAction::Close),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DustAction::Flag => "flagged",
+            DustAction::Close => "closed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DustConfig {
+    /// Value in the quote asset below which a holding is dust; zero disables the sweep
+    pub threshold: Decimal,
+    pub action: DustAction,
+}
+
+/// What one sweep did
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DustSummary {
+    pub positions_flagged: u64,
+    pub positions_closed: u64,
+    pub balances_swept: u64,
+}
+
+// =====================================================
+// DUST SWEEPER
+// =====================================================
+
+pub struct DustSweeper {
+    pool: PgPool,
+    ledger: Arc<Ledger>,
+    order_processor: Arc<OrderProcessor>,
+    position_keeper: Arc<PositionKeeper>,
+    config: DustConfig,
+    clock: SharedClock,
+    ids: SharedIdGenerator,
+}
+
+impl DustSweeper {
+    pub fn new(
+        pool: PgPool,
+        ledger: Arc<Ledger>,
+        order_processor: Arc<OrderProcessor>,
+        position_keeper: Arc<PositionKeeper>,
+        config: DustConfig,
+        clock: SharedClock,
+        ids: SharedIdGenerator,
+    ) -> Self {
+        Self { pool, ledger, order_processor, position_keeper, config, clock, ids }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.threshold > Decimal::ZERO
+    }
+
+    /// Flag or close every dust position, then sweep dust balances when closing
+    pub async fn sweep(&self) -> anyhow::Result<DustSummary> {
+        let mut summary = DustSummary::default();
+
+        let mut open = Vec::new();
+        let mut dust = Vec::new();
+        for position in self.position_keeper.open_positions().await {
+            // Marked like margin: the last trade, or the average price before the symbol trades
+            let mark = self.order_processor
+                .last_price(&position.symbol)
+                .await
+                .unwrap_or(position.avg_price);
+            if is_dust(position.net_quantity, mark, self.config.threshold) {
+                dust.push((position, mark));
+            } else {
+                open.push(position);
+            }
+        }
+
+        match self.config.action {
+            DustAction::Flag => summary.positions_flagged = self.flag_positions(&dust).await?,
+            DustAction::Close => {
+                for (position, mark) in dust {
+                    match self.close_position(&position, mark).await {
+                        Ok(true) => summary.positions_closed += 1,
+                        Ok(false) => open.push(position),
+                        Err(e) => {
+                            tracing::error!(
+                                account_id = %position.account_id,
+                                symbol = %position.symbol,
+                                "Failed to close dust position: {}", e
+                            );
+                            open.push(position);
+                        }
+                    }
+                }
+                if self.ledger.enabled() {
+                    summary.balances_swept = self.sweep_balances(&open).await?;
+                }
+            }
+        }
+
+        if let Some(ref metrics) = *get_metrics() {
+            let action = self.config.action.as_str();
+            metrics.dust_swept_total.with_label_values(&["position", action])
+                .inc_by((summary.positions_flagged + summary.positions_closed) as f64);
+            metrics.dust_swept_total.with_label_values(&["balance", action])
+                .inc_by(summary.balances_swept as f64);
+        }
+        Ok(summary)
+    }
+
+    /// Flag the dust positions not flagged yet and clear the flag of every other position,
+    /// which has traded out of dust or been closed since. Returns how many were newly flagged.
+    async fn flag_positions(&self, dust: &[(Position, Decimal)]) -> Result<u64, sqlx::Error> {
+        let accounts: Vec<Uuid> = dust.iter().map(|(p, _)| p.account_id).collect();
+        let symbols: Vec<String> = dust.iter().map(|(p, _)| p.symbol.clone()).collect();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"UPDATE positions p SET dust_flagged_at = NULL
+               WHERE dust_flagged_at IS NOT NULL
+                 AND NOT EXISTS (SELECT 1 FROM UNNEST($1::uuid[], $2::text[]) d(account_id, symbol)
+                                 WHERE d.account_id = p.account_id AND d.symbol = p.symbol)"#
+        )
+            .bind(&accounts)
+            .bind(&symbols)
+            .execute(&mut *tx)
+            .await?;
+
+        let flagged = sqlx::query(
+            r#"UPDATE positions p SET dust_flagged_at = $3
+               FROM UNNEST($1::uuid[], $2::text[]) d(account_id, symbol)
+               WHERE p.account_id = d.account_id AND p.symbol = d.symbol AND p.dust_flagged_at IS NULL"#
+        )
+            .bind(&accounts)
+            .bind(&symbols)
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+        Ok(flagged)
+    }
+
+    /// Close a position against the house account at `mark`, settling the trade in the
+    /// ledger for spot symbols. False when the position has open orders, which would be
+    /// left holding funds the close moved, or changed since it was read.
+    async fn close_position(&self, position: &Position, mark: Decimal) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let (busy,): (bool,) = sqlx::query_as(
+            r#"SELECT EXISTS (SELECT 1 FROM orders
+                              WHERE account_id = $1 AND symbol = $2
+                                AND status IN ('waiting', 'pending', 'partially_filled'))"#
+        )
+            .bind(position.account_id)
+            .bind(&position.symbol)
+            .fetch_one(&mut *tx)
+            .await?;
+        if busy {
+            return Ok(false);
+        }
+
+        let id = self.ids.next_id();
+        let side = closing_side(position.net_quantity);
+        let quantity = position.net_quantity.abs();
+        let value = dust_value(quantity, mark);
+
+        sqlx::query(
+            r#"INSERT INTO dust_sweeps (id, account_id, target, symbol, side, quantity, price, value, swept_at)
+               VALUES ($1, $2, 'position', $3, $4, $5, $6, $7, $8)"#
+        )
+            .bind(id)
+            .bind(position.account_id)
+            .bind(&position.symbol)
+            .bind(side)
+            .bind(quantity)
+            .bind(mark)
+            .bind(value)
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await?;
+
+        let fill = Fill {
+            account_id: position.account_id,
+            symbol: position.symbol.clone(),
+            side: side.to_string(),
+            quantity,
+            price: mark,
+            key: None,
+        };
+        let closed = self.position_keeper.apply_fills_in(&mut tx, std::slice::from_ref(&fill)).await?;
+        // A fill landed in between: the close no longer flattens it, so leave it for the next sweep
+        if closed.iter().any(|p| !p.net_quantity.is_zero()) {
+            return Ok(false);
+        }
+
+        if let Some((base, quote)) = spot_pair(&position.symbol, self.ledger.default_quote())
+            .filter(|_| self.ledger.enabled())
+        {
+            // A long sells its base for the quote, a short buys it back
+            let (base_amount, quote_amount) = if position.net_quantity > Decimal::ZERO {
+                (-quantity, value)
+            } else {
+                (quantity, -value)
+            };
+            self.ledger
+                .post(
+                    &mut tx,
+                    "dust_sweep",
+                    Some(id),
+                    &[
+                        Posting { account_id: position.account_id, asset: base.clone(), amount: base_amount },
+                        Posting { account_id: HOUSE_ACCOUNT, asset: base, amount: -base_amount },
+                        Posting { account_id: position.account_id, asset: quote.clone(), amount: quote_amount },
+                        Posting { account_id: HOUSE_ACCOUNT, asset: quote, amount: -quote_amount },
+                    ],
+                )
+                .await?;
+        }
+
+        tx.commit().await?;
+        self.position_keeper.commit_fills(std::slice::from_ref(&fill), &closed).await;
+
+        tracing::info!(
+            account_id = %position.account_id,
+            symbol = %position.symbol,
+            quantity = %position.net_quantity,
+            price = %mark,
+            "Dust position closed"
+        );
+        Ok(true)
+    }
+
+    /// Move dust balances of base assets to the house account, crediting their value in the
+    /// default quote asset. Balances held by orders, quote assets and the bases of
+    /// positions still open are left alone.
+    async fn sweep_balances(&self, open: &[Position]) -> anyhow::Result<u64> {
+        let default_quote = self.ledger.default_quote().to_uppercase();
+        let open_bases: HashSet<(Uuid, String)> = open
+            .iter()
+            .filter_map(|p| spot_pair(&p.symbol, &default_quote).map(|(base, _)| (p.account_id, base)))
+            .collect();
+
+        let candidates: Vec<(Uuid, String, Decimal)> = sqlx::query_as(
+            r#"SELECT account_id, asset, total FROM account_balances
+               WHERE account_id <> $1 AND total > 0 AND held = 0 AND UPPER(asset) <> $2"#
+        )
+            .bind(HOUSE_ACCOUNT)
+            .bind(&default_quote)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut swept = 0;
+        for (account_id, asset, total) in candidates {
+            if open_bases.contains(&(account_id, asset.to_uppercase())) {
+                continue;
+            }
+            let Some(price) = self.asset_price(&asset, &default_quote).await else {
+                continue;
+            };
+            if !is_dust(total, price, self.config.threshold) {
+                continue;
+            }
+
+            match self.sweep_balance(account_id, &asset, total, price, &default_quote).await {
+                Ok(true) => swept += 1,
+                Ok(false) => {}
+                Err(e) => tracing::error!(account_id = %account_id, asset = %asset, "Failed to sweep dust balance: {}", e),
+            }
+        }
+        Ok(swept)
+    }
+
+    async fn sweep_balance(
+        &self,
+        account_id: Uuid,
+        asset: &str,
+        total: Decimal,
+        price: Decimal,
+        quote: &str,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        // Unchanged and still unheld, or an order or fill got there first
+        let locked: Option<(Decimal,)> = sqlx::query_as(
+            r#"SELECT total FROM account_balances
+               WHERE account_id = $1 AND asset = $2 AND total = $3 AND held = 0
+               FOR UPDATE"#
+        )
+            .bind(account_id)
+            .bind(asset)
+            .bind(total)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if locked.is_none() {
+            return Ok(false);
+        }
+
+        let id = self.ids.next_id();
+        let value = dust_value(total, price);
+        sqlx::query(
+            r#"INSERT INTO dust_sweeps (id, account_id, target, symbol, quantity, price, value, swept_at)
+               VALUES ($1, $2, 'balance', $3, $4, $5, $6, $7)"#
+        )
+            .bind(id)
+            .bind(account_id)
+            .bind(asset)
+            .bind(total)
+            .bind(price)
+            .bind(value)
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await?;
+
+        let mut postings = vec![
+            Posting { account_id, asset: asset.to_string(), amount: -total },
+            Posting { account_id: HOUSE_ACCOUNT, asset: asset.to_string(), amount: total },
+        ];
+        if !value.is_zero() {
+            postings.push(Posting { account_id, asset: quote.to_string(), amount: value });
+            postings.push(Posting { account_id: HOUSE_ACCOUNT, asset: quote.to_string(), amount: -value });
+        }
+        self.ledger.post(&mut tx, "dust_sweep", Some(id), &postings).await?;
+        tx.commit().await?;
+
+        tracing::info!(account_id = %account_id, asset = %asset, amount = %total, value = %value, "Dust balance swept");
+        Ok(true)
+    }
+
+    /// Last trade of the asset against the default quote, under any of the symbol forms
+    /// `spot_pair` reads
+    async fn asset_price(&self, asset: &str, default_quote: &str) -> Option<Decimal> {
+        let asset = asset.to_uppercase();
+        let symbols = [
+            asset.clone(),
+            format!("{}-{}", asset, default_quote),
+            format!("{}/{}", asset, default_quote),
+        ];
+        for symbol in symbols {
+            if let Some(price) = self.order_processor.last_price(&symbol).await {
+                return Some(price);
+            }
+        }
+        None
+    }
+}
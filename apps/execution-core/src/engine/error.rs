@@ -0,0 +1,122 @@
+//! Engine Errors
+//! Failures of engine operations by class, each with the code it is reported under
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EngineError {
+    /// The caller lacks the permission or account access the operation needs
+    #[error("Insufficient permissions: {0}")]
+    Auth(String),
+    /// The request is malformed or out of range, whatever the state of the book
+    #[error("Invalid request: {0}")]
+    Validation(String),
+    /// Refused by a risk control; `code` names the control
+    #[error("{reason}")]
+    Risk { code: &'static str, reason: String },
+    /// Valid, but the target's current state does not allow it (already open, no longer
+    /// pending, already exists); retrying the same request will not succeed
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    /// The database or another store failed; the operation may succeed on retry
+    #[error("Database error: {0}")]
+    Storage(String),
+}
+
+impl EngineError {
+    /// Code carried by the error reply on the wire
+    pub fn code(&self) -> &'static str {
+        match self {
+            EngineError::Auth(_) => "FORBIDDEN",
+            EngineError::Validation(_) => "INVALID_REQUEST",
+            EngineError::Risk { code, .. } => code,
+            EngineError::Conflict(_) => "CONFLICT",
+            EngineError::Storage(_) => "STORAGE_ERROR",
+        }
+    }
+
+    /// Failure class, the label errors are counted under
+    pub fn class(&self) -> &'static str {
+        match self {
+            EngineError::Auth(_) => "auth",
+            EngineError::Validation(_) => "validation",
+            EngineError::Risk { .. } => "risk",
+            EngineError::Conflict(_) => "conflict",
+            EngineError::Storage(_) => "storage",
+        }
+    }
+
+    /// Whether the failure is the engine's rather than the caller's
+    pub fn is_unexpected(&self) -> bool {
+        matches!(self, EngineError::Storage(_))
+    }
+}
+
+impl From<sqlx::Error> for EngineError {
+    fn from(e: sqlx::Error) -> Self {
+        EngineError::Storage(e.to_string())
+    }
+}
+
+/// Why an order was turned away, by class; each carries the code of the check that refused it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// Trading is paused for the account, or market-maker protection or a strategy halt blocks it
+    Halted(&'static str),
+    /// The instrument does not trade
+    InvalidSymbol(&'static str),
+    /// Off the tick grid, or a price the order cannot trade at
+    BadPrice(&'static str),
+    /// Off the lot grid, outside the quantity bounds or too small to be worth trading
+    BadQuantity(&'static str),
+    /// Refused by a risk control or for lack of funds
+    RiskBreach(&'static str),
+    /// Terms the order cannot carry
+    InvalidOrder(&'static str),
+}
+
+impl RejectReason {
+    /// Class of a code returned by the domain order validators
+    pub fn of(code: &'static str) -> Self {
+        match code {
+            "INSTRUMENT_INACTIVE" => RejectReason::InvalidSymbol(code),
+            "INVALID_TICK_SIZE" | "INVALID_STOP" | "INVALID_BRACKET" | "POST_ONLY_WOULD_TAKE"
+            | "NO_REFERENCE_PRICE" => RejectReason::BadPrice(code),
+            "INVALID_QUANTITY" | "INVALID_NOTIONAL" | "NOTIONAL_BELOW_MIN_QUANTITY" | "INVALID_LOT_SIZE"
+            | "BELOW_MIN_QUANTITY" | "ABOVE_MAX_QUANTITY" | "BELOW_MIN_NOTIONAL" | "INVALID_DISPLAY" => {
+                RejectReason::BadQuantity(code)
+            }
+            _ => RejectReason::InvalidOrder(code),
+        }
+    }
+
+    /// Code carried by the rejection reply on the wire
+    pub fn code(&self) -> &'static str {
+        match self {
+            RejectReason::Halted(code)
+            | RejectReason::InvalidSymbol(code)
+            | RejectReason::BadPrice(code)
+            | RejectReason::BadQuantity(code)
+            | RejectReason::RiskBreach(code)
+            | RejectReason::InvalidOrder(code) => code,
+        }
+    }
+
+    /// Rejection class, the label rejections are counted under
+    pub fn class(&self) -> &'static str {
+        match self {
+            RejectReason::Halted(_) => "halted",
+            RejectReason::InvalidSymbol(_) => "invalid_symbol",
+            RejectReason::BadPrice(_) => "bad_price",
+            RejectReason::BadQuantity(_) => "bad_quantity",
+            RejectReason::RiskBreach(_) => "risk_breach",
+            RejectReason::InvalidOrder(_) => "invalid_order",
+        }
+    }
+}
+
+impl std::fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
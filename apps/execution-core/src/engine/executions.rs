@@ -0,0 +1,142 @@
+//! Execution Reports
+//! Every committed fill and reduction published to `executions.{account_id}` as it lands, so
+//! downstream services and UIs follow orders without polling. Best effort: consumers that must not miss
+//! a fill register with fill delivery, which redelivers until acknowledged.
+
+use crate::engine::order_processor::Order;
+use crate::observability::metrics::get_metrics;
+
+use chrono::{DateTime, Utc};
+use enthropic_domain::rebate::is_maker;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Reports waiting to be published before new ones are dropped
+const REPORT_QUEUE_CAPACITY: usize = 16_384;
+
+/// The order rested in the book and a trade came to it
+pub const MAKER: &str = "maker";
+/// The order traded against what was already there
+pub const TAKER: &str = "taker";
+
+/// Liquidity an order took or added when a market tick filled it
+pub fn tick_liquidity(order_type: &str) -> &'static str {
+    if is_maker(order_type) { MAKER } else { TAKER }
+}
+
+/// A fill
+pub const TRADE: &str = "trade";
+/// A partial cancel that lowered the order's quantity
+pub const REDUCED: &str = "reduced";
+
+/// Published on `executions.{account_id}` once per fill or reduction
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionReport {
+    /// `trade` or `reduced`
+    pub exec_type: &'static str,
+    /// Set on fills only, like the fill price and liquidity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trade_id: Option<Uuid>,
+    pub order_id: Uuid,
+    pub client_order_id: String,
+    pub account_id: Uuid,
+    pub symbol: String,
+    pub side: String,
+    /// Zero for a reduction
+    pub fill_quantity: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fill_price: Option<Decimal>,
+    /// Filled so far including this fill
+    pub cumulative_quantity: Decimal,
+    /// Still open after this fill or reduction
+    pub leaves_quantity: Decimal,
+    pub avg_fill_price: Option<Decimal>,
+    /// Order status after this fill or reduction
+    pub status: String,
+    /// `maker` or `taker`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liquidity: Option<&'static str>,
+    pub order_created_at: DateTime<Utc>,
+    pub executed_at: DateTime<Utc>,
+}
+
+impl ExecutionReport {
+    /// Report of a fill of `quantity` at `price`, from the order as the fill left it
+    pub fn new(
+        filled: &Order,
+        trade_id: Uuid,
+        quantity: Decimal,
+        price: Decimal,
+        liquidity: &'static str,
+        executed_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            exec_type: TRADE,
+            trade_id: Some(trade_id),
+            fill_quantity: quantity,
+            fill_price: Some(price),
+            liquidity: Some(liquidity),
+            ..Self::of(filled, executed_at)
+        }
+    }
+
+    /// Report of an order reduced to the quantity it now has
+    pub fn reduced(reduced: &Order, executed_at: DateTime<Utc>) -> Self {
+        Self { exec_type: REDUCED, ..Self::of(reduced, executed_at) }
+    }
+
+    /// The order's state, with nothing filled
+    fn of(order: &Order, executed_at: DateTime<Utc>) -> Self {
+        Self {
+            exec_type: TRADE,
+            trade_id: None,
+            order_id: order.id,
+            client_order_id: order.client_order_id.clone(),
+            account_id: order.account_id,
+            symbol: order.symbol.clone(),
+            side: order.side.clone(),
+            fill_quantity: Decimal::ZERO,
+            fill_price: None,
+            cumulative_quantity: order.filled_quantity,
+            leaves_quantity: (order.quantity - order.filled_quantity).max(Decimal::ZERO),
+            avg_fill_price: order.avg_fill_price,
+            status: order.status.clone(),
+            liquidity: None,
+            order_created_at: order.created_at,
+            executed_at,
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        format!("executions.{}", self.account_id)
+    }
+}
+
+// =====================================================
+// EXECUTION REPORTS
+// =====================================================
+
+pub struct ExecutionReports {
+    reports: mpsc::Sender<ExecutionReport>,
+}
+
+impl ExecutionReports {
+    /// The receiver yields every report, to be published
+    pub fn new() -> (Self, mpsc::Receiver<ExecutionReport>) {
+        let (reports, receiver) = mpsc::channel(REPORT_QUEUE_CAPACITY);
+        (Self { reports }, receiver)
+    }
+
+    /// Queue a committed fill's or reduction's report for publishing
+    pub fn notify(&self, report: ExecutionReport) {
+        if self.reports.try_send(report).is_err() {
+            tracing::warn!("Execution report queue full, report dropped");
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.execution_reports_dropped_total.inc();
+            }
+        }
+    }
+}
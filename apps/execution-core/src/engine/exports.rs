@@ -0,0 +1,510 @@
+//! Account Exports
+//! An account's orders, trades or position history over a date range, generated in the
+//! background as CSV or Parquet and written to object storage instead of returned in a reply
+
+use crate::auth::{AuthContext, permissions};
+use crate::clock::SharedClock;
+use crate::engine::error::EngineError;
+use crate::observability::metrics::get_metrics;
+use crate::storage::ObjectStorage;
+
+use arrow_array::{ArrayRef, Decimal128Array, RecordBatch, StringArray, TimestampMicrosecondArray};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Scale of every decimal column in Parquet exports, that of the NUMERIC(20, 8) columns
+const DECIMAL_SCALE: u32 = 8;
+
+/// A job left running this long was lost with the engine that claimed it and is claimed again
+const STALE_JOB_SECS: i64 = 3_600;
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone)]
+pub struct ExportConfig {
+    /// Longest date range one export may cover
+    pub max_range_days: i64,
+    /// Jobs generated per run
+    pub batch_size: i64,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            max_range_days: 366,
+            batch_size: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportKind {
+    Orders,
+    Trades,
+    /// Position snapshots taken within the range
+    Positions,
+}
+
+impl ExportKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "orders" => Some(Self::Orders),
+            "trades" => Some(Self::Trades),
+            "positions" => Some(Self::Positions),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Orders => "orders",
+            Self::Trades => "trades",
+            Self::Positions => "positions",
+        }
+    }
+
+    fn permission(&self) -> &'static str {
+        match self {
+            Self::Orders | Self::Trades => permissions::ORDERS_READ,
+            Self::Positions => permissions::POSITIONS_READ,
+        }
+    }
+
+    /// The exported columns, in the order the query selects them
+    pub fn columns(&self) -> &'static [(&'static str, ColumnType)] {
+        match self {
+            Self::Orders => &ORDER_COLUMNS,
+            Self::Trades => &TRADE_COLUMNS,
+            Self::Positions => &POSITION_COLUMNS,
+        }
+    }
+
+    /// Rows of account `$1` from `$2` up to `$3`, oldest first
+    fn query(&self) -> &'static str {
+        match self {
+            Self::Orders => r#"SELECT id::text, client_order_id, symbol, side, order_type, time_in_force,
+                                      quantity, price, stop_price, filled_quantity, avg_fill_price, status,
+                                      created_at, updated_at
+                               FROM orders
+                               WHERE account_id = $1 AND created_at >= $2 AND created_at < $3
+                               ORDER BY created_at, id"#,
+            Self::Trades => r#"SELECT id::text, order_id::text, symbol, side, quantity, price,
+                                      contra_order_id::text, executed_at
+                               FROM trades
+                               WHERE account_id = $1 AND executed_at >= $2 AND executed_at < $3
+                               ORDER BY executed_at, id"#,
+            Self::Positions => r#"SELECT symbol, snapshot_at, net_quantity, avg_price, cost_basis,
+                                         realized_pnl, unrealized_pnl
+                                  FROM position_snapshots
+                                  WHERE account_id = $1 AND snapshot_at >= $2 AND snapshot_at < $3
+                                  ORDER BY snapshot_at, symbol"#,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "csv" => Some(Self::Csv),
+            "parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Text,
+    Decimal,
+    Timestamp,
+}
+
+const ORDER_COLUMNS: [(&str, ColumnType); 14] = [
+    ("id", ColumnType::Text),
+    ("client_order_id", ColumnType::Text),
+    ("symbol", ColumnType::Text),
+    ("side", ColumnType::Text),
+    ("order_type", ColumnType::Text),
+    ("time_in_force", ColumnType::Text),
+    ("quantity", ColumnType::Decimal),
+    ("price", ColumnType::Decimal),
+    ("stop_price", ColumnType::Decimal),
+    ("filled_quantity", ColumnType::Decimal),
+    ("avg_fill_price", ColumnType::Decimal),
+    ("status", ColumnType::Text),
+    ("created_at", ColumnType::Timestamp),
+    ("updated_at", ColumnType::Timestamp),
+];
+
+const TRADE_COLUMNS: [(&str, ColumnType); 8] = [
+    ("id", ColumnType::Text),
+    ("order_id", ColumnType::Text),
+    ("symbol", ColumnType::Text),
+    ("side", ColumnType::Text),
+    ("quantity", ColumnType::Decimal),
+    ("price", ColumnType::Decimal),
+    ("contra_order_id", ColumnType::Text),
+    ("executed_at", ColumnType::Timestamp),
+];
+
+const POSITION_COLUMNS: [(&str, ColumnType); 7] = [
+    ("symbol", ColumnType::Text),
+    ("snapshot_at", ColumnType::Timestamp),
+    ("net_quantity", ColumnType::Decimal),
+    ("avg_price", ColumnType::Decimal),
+    ("cost_basis", ColumnType::Decimal),
+    ("realized_pnl", ColumnType::Decimal),
+    ("unrealized_pnl", ColumnType::Decimal),
+];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRequest {
+    pub kind: ExportKind,
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// First day exported, UTC
+    pub from: NaiveDate,
+    /// Last day exported, UTC, inclusive
+    pub to: NaiveDate,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportJob {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub kind: String,
+    pub format: String,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    /// `pending`, `running`, `ready` or `failed`
+    pub status: String,
+    /// URL of the generated file once ready
+    pub location: Option<String>,
+    pub row_count: Option<i64>,
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// One exported value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(Option<String>),
+    Decimal(Option<Decimal>),
+    Timestamp(Option<DateTime<Utc>>),
+}
+
+/// Rows of one export, every row holding a cell per column
+#[derive(Debug, Clone)]
+pub struct ExportTable {
+    pub columns: &'static [(&'static str, ColumnType)],
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl ExportTable {
+    /// Rows whose columns were selected in the order of `columns`
+    pub fn decode(columns: &'static [(&'static str, ColumnType)], rows: &[PgRow]) -> Result<Self, sqlx::Error> {
+        let rows = rows
+            .iter()
+            .map(|row| {
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, ty))| {
+                        Ok(match ty {
+                            ColumnType::Text => Cell::Text(row.try_get(i)?),
+                            ColumnType::Decimal => Cell::Decimal(row.try_get(i)?),
+                            ColumnType::Timestamp => Cell::Timestamp(row.try_get(i)?),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, sqlx::Error>>()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { columns, rows })
+    }
+}
+
+/// A header line, then a line per row; missing values are left empty and timestamps are
+/// RFC 3339 in UTC
+pub fn to_csv(table: &ExportTable) -> Vec<u8> {
+    let mut out = table.columns.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(",");
+    out.push('\n');
+
+    for row in &table.rows {
+        let line: Vec<String> = row
+            .iter()
+            .map(|cell| match cell {
+                Cell::Text(v) => v.as_deref().map(csv_field).unwrap_or_default(),
+                Cell::Decimal(v) => v.map(|d| d.to_string()).unwrap_or_default(),
+                Cell::Timestamp(v) => v.map(|t| t.to_rfc3339_opts(SecondsFormat::Micros, true)).unwrap_or_default(),
+            })
+            .collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+
+    out.into_bytes()
+}
+
+/// Quote a field holding a separator, quote or line break, doubling its quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One Snappy-compressed row group. Decimals are Decimal128 at scale 8 and timestamps are
+/// UTC microseconds.
+pub fn to_parquet(table: &ExportTable) -> Result<Vec<u8>, ParquetError> {
+    let fields: Vec<Field> = table
+        .columns
+        .iter()
+        .map(|(name, ty)| {
+            let data_type = match ty {
+                ColumnType::Text => DataType::Utf8,
+                ColumnType::Decimal => DataType::Decimal128(38, DECIMAL_SCALE as i8),
+                ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            };
+            Field::new(*name, data_type, true)
+        })
+        .collect();
+
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(fields.len());
+    for (i, (_, ty)) in table.columns.iter().enumerate() {
+        let cells = table.rows.iter().map(|row| &row[i]);
+        let array: ArrayRef = match ty {
+            ColumnType::Text => Arc::new(StringArray::from_iter(cells.map(|c| match c {
+                Cell::Text(v) => v.clone(),
+                _ => None,
+            }))),
+            ColumnType::Decimal => Arc::new(
+                Decimal128Array::from_iter(cells.map(|c| match c {
+                    Cell::Decimal(Some(d)) => {
+                        let mut d = *d;
+                        d.rescale(DECIMAL_SCALE);
+                        Some(d.mantissa())
+                    }
+                    _ => None,
+                }))
+                .with_precision_and_scale(38, DECIMAL_SCALE as i8)?,
+            ),
+            ColumnType::Timestamp => Arc::new(
+                TimestampMicrosecondArray::from_iter(cells.map(|c| match c {
+                    Cell::Timestamp(v) => v.map(|t| t.timestamp_micros()),
+                    _ => None,
+                }))
+                .with_timezone("UTC"),
+            ),
+        };
+        arrays.push(array);
+    }
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+    let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    Ok(buffer)
+}
+
+// =====================================================
+// EXPORTS
+// =====================================================
+
+pub struct Exports {
+    pool: PgPool,
+    /// `None` when no store is configured; requests are refused
+    storage: Option<Arc<ObjectStorage>>,
+    config: ExportConfig,
+    clock: SharedClock,
+}
+
+impl Exports {
+    pub fn new(pool: PgPool, storage: Option<Arc<ObjectStorage>>, config: ExportConfig, clock: SharedClock) -> Self {
+        Self { pool, storage, config, clock }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.storage.is_some()
+    }
+
+    /// Queue an export of the caller's account; the file is generated by [`Exports::run`]
+    pub async fn request(&self, auth: &AuthContext, req: ExportRequest) -> Result<ExportJob, EngineError> {
+        if !self.enabled() {
+            return Err(EngineError::Conflict("Exports are not enabled".into()));
+        }
+        if !auth.has_permission(req.kind.permission()) {
+            return Err(EngineError::Auth(format!("{} required", req.kind.permission())));
+        }
+        if auth.impersonation.is_some() {
+            return Err(EngineError::Auth("Exports cannot be requested from an impersonation session".into()));
+        }
+        let days = (req.to - req.from).num_days() + 1;
+        if days <= 0 || days > self.config.max_range_days {
+            return Err(EngineError::Validation(format!(
+                "Export range must be between 1 and {} days",
+                self.config.max_range_days
+            )));
+        }
+
+        let job: ExportJob = sqlx::query_as(
+            r#"INSERT INTO export_jobs (id, account_id, kind, format, from_date, to_date, status, requested_at)
+               VALUES ($1, $2, $3, $4, $5, $6, 'pending', $7)
+               RETURNING *"#
+        )
+            .bind(Uuid::new_v4())
+            .bind(auth.account_id)
+            .bind(req.kind.as_str())
+            .bind(req.format.as_str())
+            .bind(req.from)
+            .bind(req.to)
+            .bind(self.clock.now())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(job)
+    }
+
+    /// One of the caller's jobs
+    pub async fn job(&self, auth: &AuthContext, job_id: Uuid) -> Result<Option<ExportJob>, EngineError> {
+        let job: Option<ExportJob> = sqlx::query_as("SELECT * FROM export_jobs WHERE id = $1")
+            .bind(job_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match job {
+            Some(job) if !auth.can_access_account(&job.account_id) => Err(EngineError::Auth(
+                "Cannot read others' exports".into()
+            )),
+            job => Ok(job),
+        }
+    }
+
+    /// Generate up to `batch_size` pending jobs, oldest first; returns the jobs finished,
+    /// ready or failed
+    pub async fn run(&self) -> Result<Vec<ExportJob>, sqlx::Error> {
+        let Some(storage) = &self.storage else {
+            return Ok(Vec::new());
+        };
+
+        let now = self.clock.now();
+        let claimed: Vec<ExportJob> = sqlx::query_as(
+            r#"UPDATE export_jobs SET status = 'running', started_at = $1
+               WHERE id IN (
+                   SELECT id FROM export_jobs
+                   WHERE status = 'pending' OR (status = 'running' AND started_at < $2)
+                   ORDER BY requested_at
+                   LIMIT $3
+                   FOR UPDATE SKIP LOCKED
+               )
+               RETURNING *"#
+        )
+            .bind(now)
+            .bind(now - Duration::seconds(STALE_JOB_SECS))
+            .bind(self.config.batch_size)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut finished = Vec::with_capacity(claimed.len());
+        for job in claimed {
+            let job: ExportJob = match self.generate(storage, &job).await {
+                Ok((location, row_count, size_bytes)) => sqlx::query_as(
+                    r#"UPDATE export_jobs
+                       SET status = 'ready', location = $2, row_count = $3, size_bytes = $4, completed_at = $5
+                       WHERE id = $1
+                       RETURNING *"#
+                )
+                    .bind(job.id)
+                    .bind(location)
+                    .bind(row_count)
+                    .bind(size_bytes)
+                    .bind(self.clock.now())
+                    .fetch_one(&self.pool)
+                    .await?,
+                Err(e) => {
+                    tracing::warn!(job_id = %job.id, error = %e, "Export failed");
+                    sqlx::query_as(
+                        r#"UPDATE export_jobs SET status = 'failed', error = $2, completed_at = $3
+                           WHERE id = $1
+                           RETURNING *"#
+                    )
+                        .bind(job.id)
+                        .bind(e)
+                        .bind(self.clock.now())
+                        .fetch_one(&self.pool)
+                        .await?
+                }
+            };
+
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.exports_total.with_label_values(&[&job.kind, &job.status]).inc();
+            }
+            finished.push(job);
+        }
+
+        Ok(finished)
+    }
+
+    /// Query, encode and store one job's file: its location, rows and size
+    async fn generate(&self, storage: &ObjectStorage, job: &ExportJob) -> Result<(String, i64, i64), String> {
+        let kind = ExportKind::parse(&job.kind).ok_or_else(|| format!("unknown export kind '{}'", job.kind))?;
+        let format = ExportFormat::parse(&job.format).ok_or_else(|| format!("unknown export format '{}'", job.format))?;
+        let start = job.from_date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        let end = start + Duration::days((job.to_date - job.from_date).num_days() + 1);
+
+        let rows = sqlx::query(kind.query())
+            .bind(job.account_id)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        let table = ExportTable::decode(kind.columns(), &rows).map_err(|e| e.to_string())?;
+
+        let bytes = match format {
+            ExportFormat::Csv => to_csv(&table),
+            ExportFormat::Parquet => to_parquet(&table).map_err(|e| e.to_string())?,
+        };
+        let size_bytes = bytes.len() as i64;
+        let key = format!("exports/{}/{}.{}", job.account_id, job.id, format.as_str());
+        let location = storage.put(&key, bytes).await.map_err(|e| e.to_string())?;
+
+        Ok((location, table.rows.len() as i64, size_bytes))
+    }
+}
@@ -0,0 +1,284 @@
+//! Position Exposure
+//! Time-weighted exposure of every position per session, accrued on each position change
+//! and carried across session boundaries, for rebate programs and average-exposure risk reports
+
+use crate::auth::{AuthContext, permissions};
+use crate::clock::SharedClock;
+use crate::engine::error::EngineError;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use enthropic_domain::exposure::{accrue, average_quantity, session_of, session_start, ExposureAccrual};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone, FromRow)]
+struct ExposureRow {
+    symbol: String,
+    session_date: NaiveDate,
+    gross_quantity_seconds: Decimal,
+    net_quantity_seconds: Decimal,
+    held_seconds: Decimal,
+    max_quantity: Decimal,
+    closing_quantity: Decimal,
+    accrued_until: DateTime<Utc>,
+}
+
+/// A position's exposure over one session, up to now for the session in progress
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionExposure {
+    pub symbol: String,
+    pub session_date: NaiveDate,
+    /// Time-weighted average of the absolute quantity
+    pub average_gross_quantity: Decimal,
+    /// Time-weighted average of the signed quantity
+    pub average_net_quantity: Decimal,
+    pub held_seconds: Decimal,
+    pub max_quantity: Decimal,
+    /// Quantity held at the end of the session, or now
+    pub closing_quantity: Decimal,
+    /// False while the session is still in progress
+    pub complete: bool,
+}
+
+// =====================================================
+// ACCRUAL
+// =====================================================
+
+/// Accrue the quantity a position held up to `at` and start holding `after` from there.
+/// Runs in the transaction that changed the position.
+pub async fn record_change(
+    conn: &mut PgConnection,
+    account_id: Uuid,
+    symbol: &str,
+    before: Decimal,
+    after: Decimal,
+    at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    accrue_to(&mut *conn, account_id, symbol, before, at).await?;
+
+    let change = ExposureAccrual {
+        session: session_of(at),
+        gross_quantity_seconds: Decimal::ZERO,
+        net_quantity_seconds: Decimal::ZERO,
+        held_seconds: Decimal::ZERO,
+    };
+    add_accrual(conn, account_id, symbol, &change, after, before.abs().max(after.abs()), at).await
+}
+
+/// Accrue `quantity` from where the position was last accrued to `until`
+async fn accrue_to(
+    conn: &mut PgConnection,
+    account_id: Uuid,
+    symbol: &str,
+    quantity: Decimal,
+    until: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let last: Option<(DateTime<Utc>,)> = sqlx::query_as(
+        r#"SELECT accrued_until FROM position_exposure
+           WHERE account_id = $1 AND symbol = $2
+           ORDER BY session_date DESC LIMIT 1
+           FOR UPDATE"#
+    )
+        .bind(account_id)
+        .bind(symbol)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    // A position seen for the first time starts accruing now; a flat one accrues nothing
+    let Some((from,)) = last.filter(|_| !quantity.is_zero()) else {
+        return Ok(());
+    };
+
+    for accrual in accrue(quantity, from, until) {
+        let end = until.min(session_start(accrual.session) + Duration::days(1));
+        add_accrual(&mut *conn, account_id, symbol, &accrual, quantity, quantity.abs(), end).await?;
+    }
+    Ok(())
+}
+
+async fn add_accrual(
+    conn: &mut PgConnection,
+    account_id: Uuid,
+    symbol: &str,
+    accrual: &ExposureAccrual,
+    closing_quantity: Decimal,
+    max_quantity: Decimal,
+    accrued_until: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO position_exposure (account_id, symbol, session_date, gross_quantity_seconds,
+                                          net_quantity_seconds, held_seconds, max_quantity,
+                                          closing_quantity, accrued_until)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+           ON CONFLICT (account_id, symbol, session_date) DO UPDATE SET
+               gross_quantity_seconds = position_exposure.gross_quantity_seconds + EXCLUDED.gross_quantity_seconds,
+               net_quantity_seconds = position_exposure.net_quantity_seconds + EXCLUDED.net_quantity_seconds,
+               held_seconds = position_exposure.held_seconds + EXCLUDED.held_seconds,
+               max_quantity = GREATEST(position_exposure.max_quantity, EXCLUDED.max_quantity),
+               closing_quantity = EXCLUDED.closing_quantity,
+               accrued_until = GREATEST(position_exposure.accrued_until, EXCLUDED.accrued_until)"#
+    )
+        .bind(account_id)
+        .bind(symbol)
+        .bind(accrual.session)
+        .bind(accrual.gross_quantity_seconds)
+        .bind(accrual.net_quantity_seconds)
+        .bind(accrual.held_seconds)
+        .bind(max_quantity)
+        .bind(closing_quantity)
+        .bind(accrued_until)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// =====================================================
+// EXPOSURE TRACKER
+// =====================================================
+
+pub struct ExposureTracker {
+    pool: PgPool,
+    clock: SharedClock,
+}
+
+impl ExposureTracker {
+    pub fn new(pool: PgPool, clock: SharedClock) -> Self {
+        Self { pool, clock }
+    }
+
+    /// Exposure of every position the account held during `session` (today by default)
+    pub async fn report(
+        &self,
+        auth: &AuthContext,
+        account_id: Option<Uuid>,
+        session: Option<NaiveDate>,
+    ) -> Result<Vec<SessionExposure>, EngineError> {
+        if !auth.has_permission(permissions::POSITIONS_READ) {
+            return Err(EngineError::Auth(
+                "positions:read required".into()
+            ));
+        }
+
+        let target = account_id.unwrap_or(auth.account_id);
+        if target != auth.account_id && !auth.has_permission("positions:read_all") {
+            return Err(EngineError::Auth(
+                "Cannot view others' positions".into()
+            ));
+        }
+
+        let now = self.clock.now();
+        let session = session.unwrap_or_else(|| session_of(now));
+        if session > session_of(now) {
+            return Err(EngineError::Validation("Session has not started".into()));
+        }
+
+        // The session's own row, or the last one before it for positions held into the
+        // session without changing since
+        let rows: Vec<ExposureRow> = sqlx::query_as(
+            r#"SELECT DISTINCT ON (symbol)
+                      symbol, session_date, gross_quantity_seconds, net_quantity_seconds,
+                      held_seconds, max_quantity, closing_quantity, accrued_until
+               FROM position_exposure
+               WHERE account_id = $1 AND session_date <= $2
+               ORDER BY symbol, session_date DESC"#
+        )
+            .bind(target)
+            .bind(session)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| row.session_date == session || !row.closing_quantity.is_zero())
+            .map(|row| session_exposure(row, session, now))
+            .collect())
+    }
+
+    /// Carry open positions into the current session so every session they were held in
+    /// has its row, and start tracking open positions that have none yet. Returns how many
+    /// positions were carried.
+    pub async fn close_sessions(&self) -> Result<u64, sqlx::Error> {
+        let now = self.clock.now();
+        let current = session_start(session_of(now));
+
+        sqlx::query(
+            r#"INSERT INTO position_exposure (account_id, symbol, session_date, max_quantity,
+                                              closing_quantity, accrued_until)
+               SELECT p.account_id, p.symbol, $1, ABS(p.net_quantity), p.net_quantity, $2
+               FROM positions p
+               WHERE p.net_quantity <> 0
+                 AND NOT EXISTS (SELECT 1 FROM position_exposure e
+                                 WHERE e.account_id = p.account_id AND e.symbol = p.symbol)
+               ON CONFLICT DO NOTHING"#
+        )
+            .bind(session_of(now))
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        // Flattened out of band (a sandbox reset) when the position row is gone
+        let open: Vec<(Uuid, String, Decimal)> = sqlx::query_as(
+            r#"SELECT e.account_id, e.symbol, e.closing_quantity
+               FROM (SELECT DISTINCT ON (account_id, symbol) account_id, symbol, closing_quantity, accrued_until
+                     FROM position_exposure
+                     ORDER BY account_id, symbol, session_date DESC) e
+               JOIN positions p ON p.account_id = e.account_id AND p.symbol = e.symbol
+               WHERE e.closing_quantity <> 0 AND e.accrued_until < $1"#
+        )
+            .bind(current)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut carried = 0;
+        for (account_id, symbol, quantity) in open {
+            let mut tx = self.pool.begin().await?;
+            accrue_to(&mut tx, account_id, &symbol, quantity, current).await?;
+            tx.commit().await?;
+            carried += 1;
+        }
+
+        Ok(carried)
+    }
+}
+
+/// Averages over the part of `session` that has passed by `now`, adding what the row's
+/// closing quantity has accrued since it was last written
+fn session_exposure(row: ExposureRow, session: NaiveDate, now: DateTime<Utc>) -> SessionExposure {
+    let start = session_start(session);
+    let end = start + Duration::days(1);
+    let as_of = now.min(end);
+
+    let (mut gross, mut net, mut held, mut max) = if row.session_date == session {
+        (row.gross_quantity_seconds, row.net_quantity_seconds, row.held_seconds, row.max_quantity)
+    } else {
+        (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, row.closing_quantity.abs())
+    };
+
+    let pending = accrue(row.closing_quantity, row.accrued_until.max(start), as_of);
+    for accrual in pending.iter().filter(|a| a.session == session) {
+        gross += accrual.gross_quantity_seconds;
+        net += accrual.net_quantity_seconds;
+        held += accrual.held_seconds;
+        max = max.max(row.closing_quantity.abs());
+    }
+
+    let elapsed = Decimal::new((as_of - start).num_milliseconds(), 3);
+    SessionExposure {
+        symbol: row.symbol,
+        session_date: session,
+        average_gross_quantity: average_quantity(gross, elapsed),
+        average_net_quantity: average_quantity(net, elapsed),
+        held_seconds: held,
+        max_quantity: max,
+        closing_quantity: row.closing_quantity,
+        complete: now >= end,
+    }
+}
@@ -0,0 +1,340 @@
+//! Fill Delivery
+//! Execution reports fanned out to every registered consumer and redelivered until each
+//! acknowledges them; consumers that stop acknowledging are quarantined
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+use crate::clock::SharedClock;
+use crate::observability::metrics::get_metrics;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+pub const CONSUMER_KINDS: &[&str] = &["webhook", "websocket", "drop_copy"];
+
+/// Upper bound on the wait between redeliveries, which otherwise doubles per attempt
+const MAX_BACKOFF_SECS: f64 = 300.0;
+
+/// Deliveries sent per round; the rest wait for the next one
+const BATCH_SIZE: i64 = 500;
+
+const CONSUMER_COLUMNS: &str = r#"c.id, c.name, c.kind, c.subject, c.account_id, c.status, c.quarantined_at,
+       (SELECT COUNT(*) FROM fill_deliveries d WHERE d.consumer_id = c.id AND d.status = 'pending') AS pending,
+       (SELECT MIN(d.created_at) FROM fill_deliveries d WHERE d.consumer_id = c.id AND d.status = 'pending') AS oldest_pending_at"#;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FillConsumer {
+    pub id: Uuid,
+    pub name: String,
+    pub kind: String,
+    pub subject: String,
+    pub account_id: Option<Uuid>,
+    pub status: String,
+    pub quarantined_at: Option<DateTime<Utc>>,
+    /// Fills not acknowledged yet; zero means the consumer has received every fill
+    pub pending: i64,
+    pub oldest_pending_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegisterConsumer {
+    pub name: String,
+    pub kind: String,
+    /// Subject the consumer's copy of each execution report is published on
+    pub subject: String,
+    /// Only this account's fills; every account's without it
+    #[serde(alias = "accountId", default)]
+    pub account_id: Option<Uuid>,
+}
+
+impl RegisterConsumer {
+    fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() || name.len() > 64 {
+            return Err("name must be 1 to 64 bytes".into());
+        }
+        if !CONSUMER_KINDS.contains(&self.kind.as_str()) {
+            return Err(format!("kind must be one of {}", CONSUMER_KINDS.join(", ")));
+        }
+        let subject = self.subject.trim();
+        if subject.is_empty() || subject.contains(['*', '>']) || subject.contains(char::is_whitespace) {
+            return Err("subject must be a concrete NATS subject".into());
+        }
+        Ok(())
+    }
+}
+
+/// One consumer's copy of an execution report
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct FillReport {
+    #[serde(skip)]
+    pub subject: String,
+    pub consumer: String,
+    /// 1 on first delivery; higher values are redeliveries of the same fill
+    pub attempt: i32,
+    pub trade_id: Uuid,
+    pub order_id: Uuid,
+    pub account_id: Uuid,
+    pub symbol: String,
+    pub side: String,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// What one delivery round has to publish
+#[derive(Debug, Default)]
+pub struct DeliveryRound {
+    pub reports: Vec<FillReport>,
+    /// Consumers quarantined this round
+    pub quarantined: Vec<String>,
+}
+
+/// Queue a fill for every consumer that receives the account's fills, in the fill's transaction
+pub async fn enqueue_fill(
+    conn: &mut PgConnection,
+    trade_id: Uuid,
+    account_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO fill_deliveries (trade_id, consumer_id, next_attempt_at, created_at)
+           SELECT $1, id, $3, $3 FROM fill_consumers WHERE account_id IS NULL OR account_id = $2"#
+    )
+        .bind(trade_id)
+        .bind(account_id)
+        .bind(now)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// `enqueue_fill` for many fills in one statement, given as parallel trade and account ids
+pub async fn enqueue_fills(
+    conn: &mut PgConnection,
+    trade_ids: &[Uuid],
+    account_ids: &[Uuid],
+    now: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO fill_deliveries (trade_id, consumer_id, next_attempt_at, created_at)
+           SELECT t.trade_id, c.id, $3, $3
+           FROM UNNEST($1::uuid[], $2::uuid[]) AS t(trade_id, account_id)
+           JOIN fill_consumers c ON c.account_id IS NULL OR c.account_id = t.account_id"#
+    )
+        .bind(trade_ids)
+        .bind(account_ids)
+        .bind(now)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+fn record(consumer: &str, outcome: &str) {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.fill_deliveries_total
+            .with_label_values(&[consumer, outcome])
+            .inc();
+    }
+}
+
+// =====================================================
+// FILL DELIVERY
+// =====================================================
+
+pub struct FillDelivery {
+    pool: PgPool,
+    clock: SharedClock,
+    /// Unacknowledged sends of one fill before its consumer is quarantined
+    max_attempts: i32,
+}
+
+impl FillDelivery {
+    pub fn new(pool: PgPool, clock: SharedClock, max_attempts: i32) -> Self {
+        Self {
+            pool,
+            clock,
+            max_attempts: max_attempts.max(1),
+        }
+    }
+
+    /// Every consumer with its delivery backlog
+    pub async fn list(&self, auth: &AuthContext) -> Result<Vec<FillConsumer>, EngineError> {
+        if !auth.has_permission(permissions::ADMIN_FULL) {
+            return Err(EngineError::Auth(
+                "admin:full required".into()
+            ));
+        }
+
+        sqlx::query_as(&format!("SELECT {} FROM fill_consumers c ORDER BY c.name", CONSUMER_COLUMNS))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    /// Register a consumer; it receives the fills made from now on
+    pub async fn register(&self, auth: &AuthContext, req: RegisterConsumer) -> Result<FillConsumer, EngineError> {
+        if !auth.has_permission(permissions::ADMIN_FULL) {
+            return Err(EngineError::Auth(
+                "admin:full required".into()
+            ));
+        }
+
+        req.validate().map_err(EngineError::Validation)?;
+
+        let inserted = sqlx::query(
+            r#"INSERT INTO fill_consumers (name, kind, subject, account_id)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (name) DO NOTHING"#
+        )
+            .bind(req.name.trim())
+            .bind(&req.kind)
+            .bind(req.subject.trim())
+            .bind(req.account_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?
+            .rows_affected();
+
+        if inserted == 0 {
+            return Err(EngineError::Conflict(format!("Consumer {} already exists", req.name.trim())));
+        }
+
+        tracing::info!(consumer = %req.name.trim(), kind = %req.kind, "Fill consumer registered");
+        self.consumer(req.name.trim()).await
+    }
+
+    /// Return a quarantined consumer to delivery; its backlog is sent again from the start
+    pub async fn release(&self, auth: &AuthContext, name: &str) -> Result<FillConsumer, EngineError> {
+        if !auth.has_permission(permissions::ADMIN_FULL) {
+            return Err(EngineError::Auth(
+                "admin:full required".into()
+            ));
+        }
+
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let released: Option<(Uuid,)> = sqlx::query_as(
+            r#"UPDATE fill_consumers SET status = 'active', quarantined_at = NULL, updated_at = $2
+               WHERE name = $1 AND status = 'quarantined'
+               RETURNING id"#
+        )
+            .bind(name)
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let Some((consumer_id,)) = released else {
+            return Err(EngineError::Conflict(format!("Consumer {} is not quarantined", name)));
+        };
+
+        sqlx::query(
+            r#"UPDATE fill_deliveries SET attempts = 0, next_attempt_at = $2
+               WHERE consumer_id = $1 AND status = 'pending'"#
+        )
+            .bind(consumer_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        tracing::info!(consumer = name, "Fill consumer released from quarantine");
+        self.consumer(name).await
+    }
+
+    async fn consumer(&self, name: &str) -> Result<FillConsumer, EngineError> {
+        sqlx::query_as(&format!("SELECT {} FROM fill_consumers c WHERE c.name = $1", CONSUMER_COLUMNS))
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    /// Mark a consumer's copy of a fill delivered; false if it already was or is unknown
+    pub async fn ack(&self, consumer: &str, trade_id: Uuid) -> Result<bool, sqlx::Error> {
+        let acked = sqlx::query(
+            r#"UPDATE fill_deliveries d SET status = 'delivered', delivered_at = $3
+               FROM fill_consumers c
+               WHERE c.id = d.consumer_id AND c.name = $1 AND d.trade_id = $2 AND d.status = 'pending'"#
+        )
+            .bind(consumer)
+            .bind(trade_id)
+            .bind(self.clock.now())
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        if acked > 0 {
+            record(consumer, "acked");
+        }
+        Ok(acked > 0)
+    }
+
+    /// Quarantine consumers that let a fill run out of attempts, then claim the deliveries
+    /// that are due. Each claimed delivery counts as an attempt whether or not it is published.
+    pub async fn poll(&self) -> anyhow::Result<DeliveryRound> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let quarantined: Vec<String> = sqlx::query_scalar(
+            r#"UPDATE fill_consumers c SET status = 'quarantined', quarantined_at = $1, updated_at = $1
+               WHERE c.status = 'active' AND EXISTS (
+                   SELECT 1 FROM fill_deliveries d
+                   WHERE d.consumer_id = c.id AND d.status = 'pending'
+                     AND d.attempts >= $2 AND d.next_attempt_at <= $1
+               )
+               RETURNING c.name"#
+        )
+            .bind(now)
+            .bind(self.max_attempts)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let reports: Vec<FillReport> = sqlx::query_as(
+            r#"WITH due AS (
+                   SELECT d.trade_id, d.consumer_id FROM fill_deliveries d
+                   JOIN fill_consumers c ON c.id = d.consumer_id
+                   WHERE d.status = 'pending' AND d.next_attempt_at <= $1 AND c.status = 'active'
+                   ORDER BY d.next_attempt_at
+                   LIMIT $2
+                   FOR UPDATE OF d SKIP LOCKED
+               )
+               UPDATE fill_deliveries d
+               SET attempts = d.attempts + 1,
+                   last_attempt_at = $1,
+                   next_attempt_at = $1 + make_interval(secs => LEAST(power(2, d.attempts), $3))
+               FROM due, fill_consumers c, trades t
+               WHERE d.trade_id = due.trade_id AND d.consumer_id = due.consumer_id
+                 AND c.id = d.consumer_id AND t.id = d.trade_id
+               RETURNING c.subject, c.name AS consumer, d.attempts AS attempt, t.id AS trade_id,
+                         t.order_id, t.account_id, t.symbol, t.side, t.quantity, t.price, t.executed_at"#
+        )
+            .bind(now)
+            .bind(BATCH_SIZE)
+            .bind(MAX_BACKOFF_SECS)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        for name in &quarantined {
+            tracing::warn!(consumer = %name, max_attempts = self.max_attempts, "Fill consumer quarantined");
+            record(name, "quarantined");
+        }
+        for report in &reports {
+            record(&report.consumer, if report.attempt > 1 { "redelivered" } else { "sent" });
+        }
+
+        Ok(DeliveryRound { reports, quarantined })
+    }
+}
@@ -0,0 +1,104 @@
+//! Fill Topic
+//! In-process pub/sub of committed fills, so post-trade consumers such as the risk limits
+//! follow every fill as it lands instead of through a NATS round trip
+
+use crate::engine::position_keeper::{Fill, Position};
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Accounts are spread over this many locks; one account's fills always take the same one
+const SHARDS: usize = 16;
+
+/// A fill once committed, with the position it left behind
+#[derive(Debug, Clone)]
+pub struct FillEvent {
+    pub account_id: Uuid,
+    pub symbol: String,
+    pub side: String,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    /// The filled order; `None` for fills the engine made itself, such as dust closes
+    pub order_id: Option<Uuid>,
+    pub position: Position,
+    /// Number of the event among its account's, from 1 since the engine started
+    pub sequence: u64,
+}
+
+#[async_trait]
+pub trait FillSubscriber: Send + Sync {
+    /// Called once per fill, before the fill path returns. An account's fills arrive one
+    /// at a time in sequence order; other accounts' may arrive concurrently.
+    async fn on_fill(&self, event: &FillEvent);
+}
+
+/// Shard of an account's fills
+pub fn shard_of(account_id: Uuid) -> usize {
+    (account_id.as_u128() % SHARDS as u128) as usize
+}
+
+pub struct FillTopic {
+    subscribers: RwLock<Vec<Arc<dyn FillSubscriber>>>,
+    /// Last sequence handed out per account, behind the lock that orders its delivery
+    shards: Vec<Mutex<HashMap<Uuid, u64>>>,
+}
+
+impl Default for FillTopic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FillTopic {
+    pub fn new() -> Self {
+        Self {
+            subscribers: RwLock::new(Vec::new()),
+            shards: (0..SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+        }
+    }
+
+    /// Deliver every fill published from now on to `subscriber`, after those subscribed before it
+    pub fn subscribe(&self, subscriber: Arc<dyn FillSubscriber>) {
+        self.subscribers.write().unwrap_or_else(|e| e.into_inner()).push(subscriber);
+    }
+
+    /// Hand committed fills to every subscriber, in order. Fills whose position did not move
+    /// (already applied) are not published.
+    pub async fn publish(&self, fills: &[Fill], positions: &[Position]) {
+        let subscribers = self.subscribers.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if subscribers.is_empty() {
+            return;
+        }
+
+        for fill in fills {
+            let Some(position) = positions
+                .iter()
+                .find(|p| p.account_id == fill.account_id && p.symbol == fill.symbol)
+            else {
+                continue;
+            };
+
+            let mut sequences = self.shards[shard_of(fill.account_id)].lock().await;
+            let sequence = sequences.entry(fill.account_id).or_insert(0);
+            *sequence += 1;
+
+            let event = FillEvent {
+                account_id: fill.account_id,
+                symbol: fill.symbol.clone(),
+                side: fill.side.clone(),
+                quantity: fill.quantity,
+                price: fill.price,
+                order_id: fill.key.map(|key| key.order_id),
+                position: position.clone(),
+                sequence: *sequence,
+            };
+            for subscriber in &subscribers {
+                subscriber.on_fill(&event).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! State Handoff
+//! Hot in-memory state a draining instance hands to the instance taking over, so the
+//! successor prices and protects orders from its first message instead of rebuilding its
+//! caches from scratch. The database stays the source of truth for orders: the successor
+//! loads them itself and only checks its index against the one handed over.
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+pub const HANDOFF_SUBJECT: &str = "system.handoff";
+
+/// Snapshots older than this are ignored; what they carry has moved on
+const MAX_SNAPSHOT_AGE_SECS: i64 = 60;
+
+/// Open orders of one symbol, as a count and a digest of their sorted ids
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexDigest {
+    pub count: usize,
+    pub digest: String,
+}
+
+/// Summarize an open-order index by symbol
+pub fn index_digest(orders: impl IntoIterator<Item = (String, Uuid)>) -> BTreeMap<String, IndexDigest> {
+    let mut by_symbol: BTreeMap<String, Vec<Uuid>> = BTreeMap::new();
+    for (symbol, id) in orders {
+        by_symbol.entry(symbol).or_default().push(id);
+    }
+
+    by_symbol
+        .into_iter()
+        .map(|(symbol, mut ids)| {
+            ids.sort_unstable();
+            let mut hasher = Sha256::new();
+            for id in &ids {
+                hasher.update(id.as_bytes());
+            }
+            let digest = IndexDigest { count: ids.len(), digest: hex::encode(hasher.finalize()) };
+            (symbol, digest)
+        })
+        .collect()
+}
+
+/// Recent fills of a market-maker protection window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MmpWindow {
+    pub account_id: Uuid,
+    pub symbol: String,
+    /// Time and quantity, oldest first
+    pub fills: Vec<(DateTime<Utc>, Decimal)>,
+}
+
+/// Published on `system.handoff` by an instance as it drains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotState {
+    pub instance_id: String,
+    pub taken_at: DateTime<Utc>,
+    /// Last traded price per symbol
+    pub last_prices: HashMap<String, Decimal>,
+    pub open_orders: BTreeMap<String, IndexDigest>,
+    pub mmp_windows: Vec<MmpWindow>,
+    /// Symbols the draining instance traded when sharded; only an instance trading the same
+    /// ones takes its state over
+    #[serde(default)]
+    pub shard: Option<Vec<String>>,
+}
+
+impl HotState {
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        now - self.taken_at > Duration::seconds(MAX_SNAPSHOT_AGE_SECS)
+    }
+
+    /// Symbols whose open orders differ between the handed-over index and `local`
+    pub fn diverging(&self, local: &BTreeMap<String, IndexDigest>) -> Vec<String> {
+        let mut symbols: Vec<String> = self
+            .open_orders
+            .keys()
+            .chain(local.keys())
+            .filter(|symbol| self.open_orders.get(*symbol) != local.get(*symbol))
+            .cloned()
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+}
+
+/// What the successor took from a snapshot
+#[derive(Debug, Default, Serialize)]
+pub struct HandoffOutcome {
+    /// Symbols priced from the snapshot because no tick had arrived for them yet
+    pub prices_seeded: usize,
+    pub windows_seeded: usize,
+    /// Symbols whose open orders in the database differ from the draining instance's index
+    pub diverging_symbols: Vec<String>,
+}
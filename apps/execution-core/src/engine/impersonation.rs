@@ -0,0 +1,348 @@
+//! Support Impersonation
+//! Customers consent to support access for a window; an agent holding `support:impersonate`
+//! then opens a session to query as the customer. Sessions are read-only and every request
+//! made through one is audited.
+
+use crate::auth::{AuthContext, permissions};
+use crate::clock::SharedClock;
+use crate::engine::error::EngineError;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+/// All an impersonating agent may do as the customer
+const IMPERSONATED_PERMISSIONS: [&str; 3] = [
+    permissions::ORDERS_READ,
+    permissions::POSITIONS_READ,
+    permissions::MARKET_READ,
+];
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone)]
+pub struct ImpersonationConfig {
+    /// Longest consent a customer can give at once
+    pub max_consent_secs: i64,
+    /// Longest session an agent can open
+    pub max_session_secs: i64,
+}
+
+impl Default for ImpersonationConfig {
+    fn default() -> Self {
+        Self {
+            max_consent_secs: 86_400,
+            max_session_secs: 3_600,
+        }
+    }
+}
+
+/// A customer's permission for support to look at their account
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportConsent {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub granted_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationSession {
+    pub id: Uuid,
+    pub agent_account_id: Uuid,
+    pub agent_username: String,
+    pub customer_account_id: Uuid,
+    pub consent_id: Uuid,
+    pub reason: String,
+    /// The customer is told when the session starts and ends
+    pub notify_customer: bool,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartImpersonation {
+    #[serde(alias = "account_id")]
+    pub account_id: Uuid,
+    pub reason: String,
+    #[serde(alias = "window_secs", default)]
+    pub window_secs: Option<i64>,
+    #[serde(alias = "notify_customer", default = "notify_by_default")]
+    pub notify_customer: bool,
+}
+
+fn notify_by_default() -> bool {
+    true
+}
+
+// =====================================================
+// IMPERSONATION
+// =====================================================
+
+pub struct Impersonation {
+    pool: PgPool,
+    config: ImpersonationConfig,
+    clock: SharedClock,
+}
+
+impl Impersonation {
+    pub fn new(pool: PgPool, config: ImpersonationConfig, clock: SharedClock) -> Self {
+        Self { pool, config, clock }
+    }
+
+    /// The caller's consent in force, if any
+    pub async fn consent(&self, auth: &AuthContext) -> Result<Option<SupportConsent>, EngineError> {
+        let mut conn = self.pool.acquire().await?;
+        Ok(active_consent(&mut conn, auth.account_id, self.clock.now()).await?)
+    }
+
+    /// Let support access the caller's account for `window_secs` (the longest allowed by
+    /// default), replacing any consent in force
+    pub async fn grant_consent(&self, auth: &AuthContext, window_secs: Option<i64>) -> Result<SupportConsent, EngineError> {
+        let window = window_secs.unwrap_or(self.config.max_consent_secs);
+        if window <= 0 || window > self.config.max_consent_secs {
+            return Err(EngineError::Validation(format!(
+                "Consent window must be between 1 and {} seconds",
+                self.config.max_consent_secs
+            )));
+        }
+
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+        revoke(&mut tx, auth.account_id, now).await?;
+
+        let consent: SupportConsent = sqlx::query_as(
+            r#"INSERT INTO support_consents (id, account_id, granted_at, expires_at)
+               VALUES ($1, $2, $3, $4)
+               RETURNING *"#
+        )
+            .bind(Uuid::new_v4())
+            .bind(auth.account_id)
+            .bind(now)
+            .bind(now + Duration::seconds(window))
+            .fetch_one(&mut *tx)
+            .await?;
+
+        audit(&mut tx, auth.account_id, "support_consent_granted", serde_json::json!({
+            "consentId": consent.id,
+            "expiresAt": consent.expires_at,
+        }), true).await?;
+        tx.commit().await?;
+
+        Ok(consent)
+    }
+
+    /// Withdraw the caller's consent, ending every session opened under it. Returns the
+    /// sessions ended.
+    pub async fn revoke_consent(&self, auth: &AuthContext) -> Result<Vec<ImpersonationSession>, EngineError> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+        let ended = revoke(&mut tx, auth.account_id, now).await?;
+
+        audit(&mut tx, auth.account_id, "support_consent_revoked", serde_json::json!({
+            "endedSessions": ended.iter().map(|s| s.id).collect::<Vec<_>>(),
+        }), true).await?;
+        tx.commit().await?;
+
+        Ok(ended)
+    }
+
+    /// Open a session on a customer who has consented, for no longer than their consent
+    /// or the longest session allowed
+    pub async fn start(&self, auth: &AuthContext, req: StartImpersonation) -> Result<ImpersonationSession, EngineError> {
+        if !auth.has_permission(permissions::SUPPORT_IMPERSONATE) {
+            return Err(EngineError::Auth("support:impersonate required".into()));
+        }
+        if auth.impersonation.is_some() {
+            return Err(EngineError::Auth("Cannot impersonate from an impersonation session".into()));
+        }
+        if req.reason.trim().is_empty() {
+            return Err(EngineError::Validation("A reason is required".into()));
+        }
+        if req.account_id == auth.account_id {
+            return Err(EngineError::Validation("Cannot impersonate your own account".into()));
+        }
+        let window = req.window_secs.unwrap_or(self.config.max_session_secs);
+        if window <= 0 || window > self.config.max_session_secs {
+            return Err(EngineError::Validation(format!(
+                "Session window must be between 1 and {} seconds",
+                self.config.max_session_secs
+            )));
+        }
+
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let Some(consent) = active_consent(&mut tx, req.account_id, now).await? else {
+            audit(&mut tx, req.account_id, "impersonation_refused", serde_json::json!({
+                "agentAccountId": auth.account_id,
+                "agent": auth.username,
+                "reason": req.reason,
+            }), false).await?;
+            tx.commit().await?;
+            return Err(EngineError::Conflict("Customer has not consented to support access".into()));
+        };
+
+        let session: ImpersonationSession = sqlx::query_as(
+            r#"INSERT INTO impersonation_sessions (id, agent_account_id, agent_username, customer_account_id,
+                                                   consent_id, reason, notify_customer, started_at, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING *"#
+        )
+            .bind(Uuid::new_v4())
+            .bind(auth.account_id)
+            .bind(&auth.username)
+            .bind(req.account_id)
+            .bind(consent.id)
+            .bind(req.reason.trim())
+            .bind(req.notify_customer)
+            .bind(now)
+            .bind((now + Duration::seconds(window)).min(consent.expires_at))
+            .fetch_one(&mut *tx)
+            .await?;
+
+        audit(&mut tx, req.account_id, "impersonation_started", serde_json::json!({
+            "sessionId": session.id,
+            "agentAccountId": auth.account_id,
+            "agent": auth.username,
+            "reason": session.reason,
+            "expiresAt": session.expires_at,
+        }), true).await?;
+        tx.commit().await?;
+
+        tracing::info!(session_id = %session.id, agent = %auth.username, customer = %req.account_id, "Impersonation started");
+        Ok(session)
+    }
+
+    /// End one of the caller's sessions early
+    pub async fn end(&self, auth: &AuthContext, session_id: Uuid) -> Result<Option<ImpersonationSession>, EngineError> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let ended: Option<ImpersonationSession> = sqlx::query_as(
+            r#"UPDATE impersonation_sessions SET ended_at = $3
+               WHERE id = $1 AND agent_account_id = $2 AND ended_at IS NULL AND expires_at > $3
+               RETURNING *"#
+        )
+            .bind(session_id)
+            .bind(auth.account_id)
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        if let Some(session) = &ended {
+            audit(&mut tx, session.customer_account_id, "impersonation_ended", serde_json::json!({
+                "sessionId": session.id,
+                "agent": session.agent_username,
+            }), true).await?;
+        }
+        tx.commit().await?;
+
+        Ok(ended)
+    }
+
+    /// The customer's read-only context for a request the agent makes through `session_id`,
+    /// audited under the request's subject. Refused (and audited as such) once the session
+    /// or the consent behind it is over.
+    pub async fn assume(&self, agent: &AuthContext, session_id: Uuid, subject: &str) -> Result<AuthContext, EngineError> {
+        let now = self.clock.now();
+        let session: Option<ImpersonationSession> = sqlx::query_as(
+            r#"SELECT s.* FROM impersonation_sessions s
+               JOIN support_consents c ON c.id = s.consent_id
+               WHERE s.id = $1 AND s.agent_account_id = $2
+                 AND s.ended_at IS NULL AND s.expires_at > $3
+                 AND c.revoked_at IS NULL AND c.expires_at > $3"#
+        )
+            .bind(session_id)
+            .bind(agent.account_id)
+            .bind(now)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let allowed = session.filter(|_| agent.has_permission(permissions::SUPPORT_IMPERSONATE));
+        let customer = allowed.as_ref().map_or(agent.account_id, |s| s.customer_account_id);
+
+        let mut conn = self.pool.acquire().await?;
+        audit(&mut conn, customer, "impersonated_request", serde_json::json!({
+            "sessionId": session_id,
+            "agentAccountId": agent.account_id,
+            "agent": agent.username,
+            "subject": subject,
+        }), allowed.is_some()).await?;
+
+        let Some(session) = allowed else {
+            return Err(EngineError::Auth("Impersonation session is not active".into()));
+        };
+
+        Ok(AuthContext {
+            account_id: session.customer_account_id,
+            username: format!("{} (support)", agent.username),
+            role: "support".into(),
+            permissions: IMPERSONATED_PERMISSIONS.iter().map(|p| p.to_string()).collect(),
+            token_jti: agent.token_jti.clone(),
+            impersonation: Some(session.id),
+        })
+    }
+}
+
+async fn active_consent(
+    conn: &mut PgConnection,
+    account_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Option<SupportConsent>, sqlx::Error> {
+    sqlx::query_as(
+        r#"SELECT * FROM support_consents
+           WHERE account_id = $1 AND revoked_at IS NULL AND expires_at > $2
+           ORDER BY granted_at DESC LIMIT 1"#
+    )
+        .bind(account_id)
+        .bind(now)
+        .fetch_optional(conn)
+        .await
+}
+
+/// Revoke the account's consents in force and end the sessions opened under them
+async fn revoke(conn: &mut PgConnection, account_id: Uuid, now: DateTime<Utc>) -> Result<Vec<ImpersonationSession>, sqlx::Error> {
+    sqlx::query_as(
+        r#"WITH revoked AS (
+               UPDATE support_consents SET revoked_at = $2
+               WHERE account_id = $1 AND revoked_at IS NULL AND expires_at > $2
+               RETURNING id
+           )
+           UPDATE impersonation_sessions SET ended_at = $2
+           WHERE consent_id IN (SELECT id FROM revoked) AND ended_at IS NULL AND expires_at > $2
+           RETURNING *"#
+    )
+        .bind(account_id)
+        .bind(now)
+        .fetch_all(conn)
+        .await
+}
+
+async fn audit(
+    conn: &mut PgConnection,
+    account_id: Uuid,
+    event_type: &str,
+    data: serde_json::Value,
+    success: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_log (account_id, event_type, event_data, success) VALUES ($1, $2, $3::jsonb, $4)"
+    )
+        .bind(account_id)
+        .bind(event_type)
+        .bind(data.to_string())
+        .bind(success)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! Instrument Registry
+//! Tick size, lot size, quantity bounds and minimum notional of every listed symbol, held
+//! in memory so order entry never waits on the `instruments` table
+
+use enthropic_domain::order::{InstrumentRules, LotSize};
+
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+type InstrumentRow = (String, Decimal, Decimal, Option<Decimal>, Option<Decimal>, Option<Decimal>, bool);
+
+fn from_row((symbol, tick_size, lot_size, min_quantity, max_quantity, min_notional, active): InstrumentRow) -> InstrumentRules {
+    InstrumentRules {
+        symbol,
+        tick_size,
+        lot: LotSize { size: lot_size, min_quantity: min_quantity.unwrap_or_default() },
+        max_quantity,
+        min_notional,
+        active,
+    }
+}
+
+// =====================================================
+// INSTRUMENTS
+// =====================================================
+
+pub struct Instruments {
+    pool: PgPool,
+    /// Rules by symbol; symbols without a row are not restricted
+    rules: RwLock<HashMap<String, InstrumentRules>>,
+}
+
+impl Instruments {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            rules: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Replace the registry with the current `instruments` rows; returns how many
+    pub async fn load(&self) -> Result<usize, sqlx::Error> {
+        let rows: Vec<InstrumentRow> = sqlx::query_as(
+            r#"SELECT symbol, tick_size, lot_size, min_quantity, max_quantity, min_notional, is_active
+               FROM instruments"#
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        let rules: HashMap<_, _> = rows
+            .into_iter()
+            .map(from_row)
+            .map(|rules| (rules.symbol.clone(), rules))
+            .collect();
+        let count = rules.len();
+        *self.rules.write().await = rules;
+        Ok(count)
+    }
+
+    /// Rules of a symbol, if it is listed
+    pub async fn get(&self, symbol: &str) -> Option<InstrumentRules> {
+        self.rules.read().await.get(symbol).cloned()
+    }
+}
@@ -0,0 +1,178 @@
+//! Ledger Integrity Checks
+//! Scheduled verification of double-entry invariants, with results kept for auditors
+
+use crate::clock::SharedClock;
+use crate::observability::metrics::get_metrics;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Violating rows kept per check; the count always covers all of them
+const SAMPLE_LIMIT: i64 = 50;
+
+/// Each query returns one row per violation
+const CHECKS: &[(&str, &str)] = &[
+    // Every journal nets to zero per asset
+    (
+        "journal_balanced",
+        r#"SELECT journal_id, asset, SUM(amount) AS net
+           FROM ledger_entries
+           GROUP BY journal_id, asset
+           HAVING SUM(amount) != 0"#,
+    ),
+    // Debits equal credits per currency across the whole ledger
+    (
+        "currency_balanced",
+        r#"SELECT asset, SUM(amount) AS net
+           FROM ledger_entries
+           GROUP BY asset
+           HAVING SUM(amount) != 0"#,
+    ),
+    // Materialized balances equal the sum of their entries
+    (
+        "balances_match_entries",
+        r#"SELECT COALESCE(b.account_id, e.account_id) AS account_id,
+                  COALESCE(b.asset, e.asset) AS asset,
+                  b.total AS balance, e.total AS entries
+           FROM account_balances b
+           FULL OUTER JOIN (
+               SELECT account_id, asset, SUM(amount) AS total
+               FROM ledger_entries GROUP BY account_id, asset
+           ) e ON e.account_id = b.account_id AND e.asset = b.asset
+           WHERE COALESCE(b.total, 0) != COALESCE(e.total, 0)"#,
+    ),
+    // Held amounts equal the open holds behind them
+    (
+        "holds_match_balances",
+        r#"SELECT COALESCE(b.account_id, h.account_id) AS account_id,
+                  COALESCE(b.asset, h.asset) AS asset,
+                  b.held AS held, h.remaining AS open_holds
+           FROM account_balances b
+           FULL OUTER JOIN (
+               SELECT account_id, asset, SUM(remaining) AS remaining
+               FROM balance_holds WHERE released_at IS NULL
+               GROUP BY account_id, asset
+           ) h ON h.account_id = b.account_id AND h.asset = b.asset
+           WHERE COALESCE(b.held, 0) != COALESCE(h.remaining, 0)"#,
+    ),
+    // Positions equal their signed fills plus split adjustments (since the last sandbox reset)
+    (
+        "positions_match_trades",
+        r#"SELECT COALESCE(p.account_id, f.account_id) AS account_id,
+                  COALESCE(p.symbol, f.symbol) AS symbol,
+                  p.net_quantity AS position, f.quantity AS from_trades
+           FROM positions p
+           FULL OUTER JOIN (
+               SELECT m.account_id, m.symbol, SUM(m.quantity) AS quantity FROM (
+                   SELECT account_id, symbol, executed_at AS at,
+                          CASE WHEN side = 'buy' THEN quantity ELSE -quantity END AS quantity
+                   FROM trades
+                   UNION ALL
+                   SELECT a.account_id, c.symbol, a.created_at, a.quantity_after - a.quantity_before
+                   FROM corporate_action_adjustments a
+                   JOIN corporate_actions c ON c.id = a.action_id
+                   WHERE a.target = 'position'
+               ) m
+               LEFT JOIN sandbox_accounts s ON s.account_id = m.account_id
+               WHERE s.account_id IS NULL OR m.at > s.last_reset_at
+               GROUP BY m.account_id, m.symbol
+           ) f ON f.account_id = p.account_id AND f.symbol = p.symbol
+           WHERE COALESCE(p.net_quantity, 0) != COALESCE(f.quantity, 0)"#,
+    ),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub check: &'static str,
+    pub violations: i64,
+    /// Up to `SAMPLE_LIMIT` violating rows
+    pub sample: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityRun {
+    pub run_id: Uuid,
+    pub checked_at: DateTime<Utc>,
+    pub results: Vec<CheckResult>,
+}
+
+impl IntegrityRun {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.violations == 0)
+    }
+}
+
+// =====================================================
+// CHECKER
+// =====================================================
+
+pub struct IntegrityChecker {
+    pool: PgPool,
+    clock: SharedClock,
+}
+
+impl IntegrityChecker {
+    pub fn new(pool: PgPool, clock: SharedClock) -> Self {
+        Self { pool, clock }
+    }
+
+    /// Run every check against one snapshot and record the results
+    pub async fn run(&self) -> anyhow::Result<IntegrityRun> {
+        let run_id = Uuid::new_v4();
+        let checked_at = self.clock.now();
+
+        // Always the primary: replica lag would show up as violations
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+
+        let mut results = Vec::with_capacity(CHECKS.len());
+        for (check, query) in CHECKS {
+            let (violations, sample): (i64, serde_json::Value) = sqlx::query_as(&format!(
+                r#"SELECT COUNT(*), COALESCE(jsonb_agg(to_jsonb(v) - 'rn') FILTER (WHERE v.rn <= $1), '[]'::jsonb)
+                   FROM (SELECT q.*, row_number() OVER () AS rn FROM ({}) q) v"#,
+                query
+            ))
+                .bind(SAMPLE_LIMIT)
+                .fetch_one(&mut *tx)
+                .await?;
+
+            results.push(CheckResult { check, violations, sample });
+        }
+        tx.commit().await?;
+
+        let run = IntegrityRun { run_id, checked_at, results };
+        self.record(&run).await?;
+        Ok(run)
+    }
+
+    async fn record(&self, run: &IntegrityRun) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        for result in &run.results {
+            sqlx::query(
+                r#"INSERT INTO ledger_integrity_checks (run_id, check_name, passed, violations, sample, checked_at)
+                   VALUES ($1, $2, $3, $4, $5, $6)"#
+            )
+                .bind(run.run_id)
+                .bind(result.check)
+                .bind(result.violations == 0)
+                .bind(result.violations)
+                .bind(&result.sample)
+                .bind(run.checked_at)
+                .execute(&mut *tx)
+                .await?;
+
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.ledger_integrity_violations
+                    .with_label_values(&[result.check])
+                    .set(result.violations as f64);
+            }
+        }
+
+        tx.commit().await
+    }
+}
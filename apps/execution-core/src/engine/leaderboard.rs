@@ -0,0 +1,381 @@
+//! Paper-Trading Leaderboard
+//! Opt-in ranking of accounts by period PnL computed from position snapshots
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+use crate::clock::SharedClock;
+use crate::storage::ReadPool;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+const DISPLAY_NAME_MIN_LEN: usize = 3;
+const DISPLAY_NAME_MAX_LEN: usize = 32;
+
+// =====================================================
+// PERIODS
+// =====================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardPeriod {
+    Day,
+    Week,
+    Month,
+    AllTime,
+}
+
+impl LeaderboardPeriod {
+    pub const ALL: [LeaderboardPeriod; 4] = [
+        LeaderboardPeriod::Day,
+        LeaderboardPeriod::Week,
+        LeaderboardPeriod::Month,
+        LeaderboardPeriod::AllTime,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderboardPeriod::Day => "day",
+            LeaderboardPeriod::Week => "week",
+            LeaderboardPeriod::Month => "month",
+            LeaderboardPeriod::AllTime => "all_time",
+        }
+    }
+
+    /// Start of the current period in UTC (`None` for all-time)
+    pub fn start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let midnight = Utc
+            .with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
+            .single()?;
+
+        match self {
+            LeaderboardPeriod::Day => Some(midnight),
+            LeaderboardPeriod::Week => {
+                let days_since_monday = now.weekday().num_days_from_monday() as i64;
+                Some(midnight - Duration::days(days_since_monday))
+            }
+            LeaderboardPeriod::Month => Utc
+                .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+                .single(),
+            LeaderboardPeriod::AllTime => None,
+        }
+    }
+}
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone)]
+pub struct LeaderboardConfig {
+    /// Paper capital every account is measured against for return %
+    pub reference_capital: Decimal,
+    /// Maximum number of entries returned or published
+    pub max_entries: usize,
+}
+
+impl Default for LeaderboardConfig {
+    fn default() -> Self {
+        Self {
+            reference_capital: dec!(100000),
+            max_entries: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OptInRequest {
+    #[serde(alias = "displayName", default)]
+    pub display_name: Option<String>,
+    #[serde(alias = "showPnl", default)]
+    pub show_pnl: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LeaderboardParticipant {
+    pub account_id: Uuid,
+    pub display_name: String,
+    pub show_pnl: bool,
+    pub is_active: bool,
+    pub opted_in_at: DateTime<Utc>,
+}
+
+/// Public leaderboard row - never carries the account id or username
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub display_name: String,
+    pub return_pct: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pnl: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardSnapshot {
+    pub period: LeaderboardPeriod,
+    pub generated_at: DateTime<Utc>,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Debug, FromRow)]
+struct StandingRow {
+    display_name: String,
+    show_pnl: bool,
+    current_pnl: Option<Decimal>,
+    baseline_pnl: Option<Decimal>,
+}
+
+// =====================================================
+// LEADERBOARD
+// =====================================================
+
+pub struct Leaderboard {
+    pool: PgPool,
+    reads: ReadPool,
+    config: LeaderboardConfig,
+    clock: SharedClock,
+}
+
+impl Leaderboard {
+    pub fn new(pool: PgPool, reads: ReadPool, config: LeaderboardConfig, clock: SharedClock) -> Self {
+        Self { pool, reads, config, clock }
+    }
+
+    /// Opt the caller's account in (or update its privacy settings)
+    pub async fn opt_in(
+        &self,
+        auth: &AuthContext,
+        req: OptInRequest,
+    ) -> Result<LeaderboardParticipant, EngineError> {
+        if !auth.has_permission(permissions::POSITIONS_READ) {
+            return Err(EngineError::Auth(
+                "positions:read required".into()
+            ));
+        }
+
+        let display_name = match req.display_name {
+            Some(name) => validate_display_name(&name)
+                .map_err(EngineError::Validation)?,
+            None => default_display_name(&auth.account_id),
+        };
+
+        let participant: LeaderboardParticipant = sqlx::query_as(
+            r#"INSERT INTO leaderboard_participants (account_id, display_name, show_pnl, is_active)
+               VALUES ($1, $2, $3, true)
+               ON CONFLICT (account_id) DO UPDATE SET
+                   display_name = $2,
+                   show_pnl = $3,
+                   is_active = true,
+                   updated_at = NOW()
+               RETURNING account_id, display_name, show_pnl, is_active, opted_in_at"#
+        )
+            .bind(auth.account_id)
+            .bind(&display_name)
+            .bind(req.show_pnl)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        tracing::info!(account_id = %auth.account_id, "Account opted in to leaderboard");
+        Ok(participant)
+    }
+
+    /// Remove the caller's account from all future rankings
+    pub async fn opt_out(&self, auth: &AuthContext) -> Result<bool, EngineError> {
+        let result = sqlx::query(
+            r#"UPDATE leaderboard_participants
+               SET is_active = false, updated_at = NOW()
+               WHERE account_id = $1 AND is_active"#
+        )
+            .bind(auth.account_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Rankings for a client query (leaderboards are visible to anyone with market data access)
+    pub async fn query(
+        &self,
+        auth: &AuthContext,
+        period: LeaderboardPeriod,
+        limit: Option<usize>,
+    ) -> Result<LeaderboardSnapshot, EngineError> {
+        if !auth.has_permission(permissions::MARKET_READ) {
+            return Err(EngineError::Auth(
+                "market:read required".into()
+            ));
+        }
+
+        self.rankings(period, limit)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    /// Copy current positions of all active participants into position_snapshots
+    pub async fn snapshot_positions(&self) -> anyhow::Result<u64> {
+        let result = sqlx::query(
+            r#"INSERT INTO position_snapshots (account_id, symbol, snapshot_at, net_quantity,
+                                               avg_price, realized_pnl, unrealized_pnl, cost_basis)
+               SELECT p.account_id, p.symbol, $1, p.net_quantity,
+                      p.avg_price, p.realized_pnl, p.unrealized_pnl, p.cost_basis
+               FROM positions p
+               JOIN leaderboard_participants lp ON lp.account_id = p.account_id
+               WHERE lp.is_active
+               ON CONFLICT (account_id, symbol, snapshot_at) DO NOTHING"#
+        )
+            .bind(self.clock.now())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Rank active participants by PnL earned since the start of `period`
+    pub async fn rankings(
+        &self,
+        period: LeaderboardPeriod,
+        limit: Option<usize>,
+    ) -> anyhow::Result<LeaderboardSnapshot> {
+        let now = self.clock.now();
+        let start = period.start(now);
+
+        // Baseline is the last snapshot taken before the period began, or the
+        // first one inside it for accounts that joined mid-period.
+        let rows: Vec<StandingRow> = self.reads.run("leaderboard.standings", |pool| async move {
+            sqlx::query_as(
+                r#"SELECT lp.display_name, lp.show_pnl,
+                          cur.pnl AS current_pnl,
+                          base.pnl AS baseline_pnl
+                   FROM leaderboard_participants lp
+                   LEFT JOIN LATERAL (
+                       SELECT snapshot_at FROM position_snapshots s
+                       WHERE s.account_id = lp.account_id
+                       ORDER BY s.snapshot_at DESC LIMIT 1
+                   ) latest ON true
+                   LEFT JOIN LATERAL (
+                       SELECT c.snapshot_at FROM (
+                           (SELECT snapshot_at FROM position_snapshots s
+                            WHERE s.account_id = lp.account_id AND s.snapshot_at <= $1
+                            ORDER BY s.snapshot_at DESC LIMIT 1)
+                           UNION ALL
+                           (SELECT snapshot_at FROM position_snapshots s
+                            WHERE s.account_id = lp.account_id AND s.snapshot_at > $1
+                            ORDER BY s.snapshot_at ASC LIMIT 1)
+                       ) c
+                       ORDER BY (c.snapshot_at <= $1) DESC LIMIT 1
+                   ) baseline ON $1 IS NOT NULL
+                   LEFT JOIN LATERAL (
+                       SELECT SUM(realized_pnl + unrealized_pnl) AS pnl FROM position_snapshots s
+                       WHERE s.account_id = lp.account_id AND s.snapshot_at = latest.snapshot_at
+                   ) cur ON true
+                   LEFT JOIN LATERAL (
+                       SELECT SUM(realized_pnl + unrealized_pnl) AS pnl FROM position_snapshots s
+                       WHERE s.account_id = lp.account_id AND s.snapshot_at = baseline.snapshot_at
+                   ) base ON true
+                   WHERE lp.is_active"#
+            )
+                .bind(start)
+                .fetch_all(&pool)
+                .await
+        })
+            .await?;
+
+        let standings = rows
+            .into_iter()
+            .map(|row| Standing {
+                display_name: row.display_name,
+                show_pnl: row.show_pnl,
+                pnl: row.current_pnl.unwrap_or_default() - row.baseline_pnl.unwrap_or_default(),
+            })
+            .collect();
+
+        let limit = limit
+            .unwrap_or(self.config.max_entries)
+            .min(self.config.max_entries);
+
+        Ok(LeaderboardSnapshot {
+            period,
+            generated_at: now,
+            entries: rank_standings(standings, self.config.reference_capital, limit),
+        })
+    }
+}
+
+// =====================================================
+// RANKING
+// =====================================================
+
+#[derive(Debug, Clone)]
+struct Standing {
+    display_name: String,
+    show_pnl: bool,
+    pnl: Decimal,
+}
+
+/// Sort by PnL descending with competition ranking (ties share a rank)
+fn rank_standings(
+    mut standings: Vec<Standing>,
+    reference_capital: Decimal,
+    limit: usize,
+) -> Vec<LeaderboardEntry> {
+    standings.sort_by(|a, b| {
+        b.pnl.cmp(&a.pnl).then_with(|| a.display_name.cmp(&b.display_name))
+    });
+
+    let mut entries = Vec::with_capacity(standings.len().min(limit));
+    let mut rank = 0u32;
+    let mut previous_pnl: Option<Decimal> = None;
+
+    for (index, standing) in standings.into_iter().take(limit).enumerate() {
+        if previous_pnl != Some(standing.pnl) {
+            rank = index as u32 + 1;
+            previous_pnl = Some(standing.pnl);
+        }
+
+        let return_pct = if reference_capital > dec!(0) {
+            (standing.pnl / reference_capital * dec!(100)).round_dp(4)
+        } else {
+            dec!(0)
+        };
+
+        entries.push(LeaderboardEntry {
+            rank,
+            display_name: standing.display_name,
+            return_pct,
+            pnl: standing.show_pnl.then_some(standing.pnl),
+        });
+    }
+
+    entries
+}
+
+fn validate_display_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let len = name.chars().count();
+
+    if !(DISPLAY_NAME_MIN_LEN..=DISPLAY_NAME_MAX_LEN).contains(&len) {
+        return Err(format!(
+            "display_name must be {}-{} characters",
+            DISPLAY_NAME_MIN_LEN, DISPLAY_NAME_MAX_LEN
+        ));
+    }
+
+    if !name.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_')) {
+        return Err("display_name may only contain letters, digits, spaces, '-' and '_'".into());
+    }
+
+    Ok(name.to_string())
+}
+
+/// Stable alias derived from a hash so the account id is never exposed
+fn default_display_name(account_id: &Uuid) -> String {
+    let digest = Sha256::digest(account_id.as_bytes());
+    format!("trader-{}", &hex::encode(digest)[..8])
+}
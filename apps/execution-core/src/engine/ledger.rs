@@ -0,0 +1,548 @@
+//! Asset Ledger with Order Holds
+//! Double-entry balances per asset; orders reserve funds before acceptance
+
+use crate::engine::order_processor::Order;
+use crate::ids::SharedIdGenerator;
+use crate::storage::ReadPool;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use sqlx::{FromRow, PgConnection};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Counterparty for every posting against the market (paper trading has no real venue)
+pub const HOUSE_ACCOUNT: Uuid = Uuid::nil();
+
+#[derive(Error, Debug)]
+pub enum LedgerError {
+    #[error("Insufficient {asset} balance: required {required}, available {available}")]
+    InsufficientFunds {
+        asset: String,
+        required: Decimal,
+        available: Decimal,
+    },
+    #[error("No reference price for {0}")]
+    NoReferencePrice(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct LedgerConfig {
+    pub enforce_balances: bool,
+    /// Quote asset assumed for symbols without an explicit pair (e.g. "AAPL")
+    pub default_quote: String,
+    /// Extra fraction reserved for market buys to absorb price movement
+    pub market_hold_buffer: Decimal,
+}
+
+impl Default for LedgerConfig {
+    fn default() -> Self {
+        Self {
+            enforce_balances: true,
+            default_quote: "USD".to_string(),
+            market_hold_buffer: dec!(0.05),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AssetBalance {
+    pub account_id: Uuid,
+    pub asset: String,
+    pub total: Decimal,
+    pub held: Decimal,
+}
+
+/// A signed posting; the postings of one journal must sum to zero per asset
+#[derive(Debug, Clone)]
+pub struct Posting {
+    pub account_id: Uuid,
+    pub asset: String,
+    pub amount: Decimal,
+}
+
+/// Base and quote asset of a spot symbol ("BTC/USD", "BTC-USD", "AAPL").
+/// Returns `None` for derivatives, which are margined rather than pre-funded.
+pub fn spot_pair(symbol: &str, default_quote: &str) -> Option<(String, String)> {
+    let upper = symbol.to_uppercase();
+    if upper.ends_with("-PERP") || upper.ends_with("PERP") || upper.contains("-FUT") {
+        return None;
+    }
+
+    match upper.split_once(['/', '-']) {
+        Some((base, quote)) if !base.is_empty() && !quote.is_empty() => {
+            Some((base.to_string(), quote.to_string()))
+        }
+        Some(_) => None,
+        None => Some((upper, default_quote.to_uppercase())),
+    }
+}
+
+// =====================================================
+// LEDGER
+// =====================================================
+
+pub struct Ledger {
+    reads: ReadPool,
+    config: LedgerConfig,
+    /// Journal ids
+    ids: SharedIdGenerator,
+}
+
+impl Ledger {
+    pub fn new(reads: ReadPool, config: LedgerConfig, ids: SharedIdGenerator) -> Self {
+        Self { reads, config, ids }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enforce_balances
+    }
+
+    pub fn default_quote(&self) -> &str {
+        &self.config.default_quote
+    }
+
+    /// Asset and amount an order must reserve (`None` if the symbol is not pre-funded)
+    pub fn required_hold(
+        &self,
+        order: &Order,
+        reference_price: Option<Decimal>,
+    ) -> Result<Option<(String, Decimal)>, LedgerError> {
+        let Some((base, quote)) = spot_pair(&order.symbol, &self.config.default_quote) else {
+            return Ok(None);
+        };
+
+        if order.side == "sell" {
+            return Ok(Some((base, order.quantity)));
+        }
+
+        // A buy stop executes no lower than its stop price
+        let price = match order.price {
+            Some(limit) => limit,
+            None => reference_price
+                .max(order.stop_price)
+                .map(|p| p * (dec!(1) + self.config.market_hold_buffer))
+                .ok_or_else(|| LedgerError::NoReferencePrice(order.symbol.clone()))?,
+        };
+
+        Ok(Some((quote, order.quantity * price)))
+    }
+
+    /// Reserve funds for a newly accepted order inside the caller's transaction
+    pub async fn reserve(
+        &self,
+        conn: &mut PgConnection,
+        order: &Order,
+        reference_price: Option<Decimal>,
+    ) -> Result<(), LedgerError> {
+        if !self.enabled() {
+            return Ok(());
+        }
+
+        let Some((asset, amount)) = self.required_hold(order, reference_price)? else {
+            return Ok(());
+        };
+
+        let reserved = sqlx::query(
+            r#"UPDATE account_balances
+               SET held = held + $3, updated_at = NOW()
+               WHERE account_id = $1 AND asset = $2 AND total - held >= $3"#
+        )
+            .bind(order.account_id)
+            .bind(&asset)
+            .bind(amount)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+
+        if reserved == 0 {
+            let available: Option<(Decimal,)> = sqlx::query_as(
+                "SELECT total - held FROM account_balances WHERE account_id = $1 AND asset = $2"
+            )
+                .bind(order.account_id)
+                .bind(&asset)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+            return Err(LedgerError::InsufficientFunds {
+                asset,
+                required: amount,
+                available: available.map(|(a,)| a).unwrap_or_default(),
+            });
+        }
+
+        sqlx::query(
+            r#"INSERT INTO balance_holds (order_id, account_id, asset, amount, remaining)
+               VALUES ($1, $2, $3, $4, $4)"#
+        )
+            .bind(order.id)
+            .bind(order.account_id)
+            .bind(&asset)
+            .bind(amount)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Release whatever is still held for an order (cancel / expiry)
+    pub async fn release(&self, conn: &mut PgConnection, order_id: Uuid) -> Result<Decimal, sqlx::Error> {
+        let hold: Option<(Uuid, String, Decimal)> = sqlx::query_as(
+            r#"SELECT account_id, asset, remaining FROM balance_holds
+               WHERE order_id = $1 AND released_at IS NULL
+               FOR UPDATE"#
+        )
+            .bind(order_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        let Some((account_id, asset, remaining)) = hold else {
+            return Ok(dec!(0));
+        };
+
+        sqlx::query("UPDATE balance_holds SET released_at = NOW(), remaining = 0 WHERE order_id = $1")
+            .bind(order_id)
+            .execute(&mut *conn)
+            .await?;
+
+        sqlx::query(
+            r#"UPDATE account_balances SET held = held - $3, updated_at = NOW()
+               WHERE account_id = $1 AND asset = $2"#
+        )
+            .bind(account_id)
+            .bind(&asset)
+            .bind(remaining)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(remaining)
+    }
+
+    /// Shrink an order's hold in proportion to a reduced open quantity; returns the amount released
+    pub async fn reduce_hold(
+        &self,
+        conn: &mut PgConnection,
+        order_id: Uuid,
+        open_before: Decimal,
+        open_after: Decimal,
+    ) -> Result<Decimal, sqlx::Error> {
+        let hold: Option<(Uuid, String, Decimal)> = sqlx::query_as(
+            r#"SELECT account_id, asset, remaining FROM balance_holds
+               WHERE order_id = $1 AND released_at IS NULL
+               FOR UPDATE"#
+        )
+            .bind(order_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        let Some((account_id, asset, remaining)) = hold else {
+            return Ok(dec!(0));
+        };
+        if open_before <= dec!(0) {
+            return Ok(dec!(0));
+        }
+
+        let released = remaining - (remaining * open_after / open_before)
+            .round_dp_with_strategy(8, RoundingStrategy::AwayFromZero);
+        if released <= dec!(0) {
+            return Ok(dec!(0));
+        }
+
+        sqlx::query("UPDATE balance_holds SET remaining = remaining - $2 WHERE order_id = $1")
+            .bind(order_id)
+            .bind(released)
+            .execute(&mut *conn)
+            .await?;
+
+        sqlx::query(
+            r#"UPDATE account_balances SET held = held - $3, updated_at = NOW()
+               WHERE account_id = $1 AND asset = $2"#
+        )
+            .bind(account_id)
+            .bind(&asset)
+            .bind(released)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(released)
+    }
+
+    /// Release every open hold of an account (mass cancel, sandbox reset/expiry)
+    pub async fn release_account(&self, conn: &mut PgConnection, account_id: Uuid) -> Result<u64, sqlx::Error> {
+        let order_ids: Vec<(Uuid,)> = sqlx::query_as(
+            "SELECT order_id FROM balance_holds WHERE account_id = $1 AND released_at IS NULL"
+        )
+            .bind(account_id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        for (order_id,) in &order_ids {
+            self.release(conn, *order_id).await?;
+        }
+
+        Ok(order_ids.len() as u64)
+    }
+
+    /// Book a fill: consume the order's hold, debit what was paid and credit what was received
+    pub async fn settle_fill(
+        &self,
+        conn: &mut PgConnection,
+        order: &Order,
+        quantity: Decimal,
+        price: Decimal,
+        completes_order: bool,
+    ) -> Result<(), sqlx::Error> {
+        if !self.enabled() {
+            return Ok(());
+        }
+
+        let Some((base, quote)) = spot_pair(&order.symbol, &self.config.default_quote) else {
+            return Ok(());
+        };
+
+        let notional = quantity * price;
+        let (pay_asset, pay_amount, recv_asset, recv_amount) = if order.side == "buy" {
+            (quote, notional, base, quantity)
+        } else {
+            (base, quantity, quote, notional)
+        };
+
+        // Shrink the hold first so that held <= total holds after the debit
+        let remaining: Option<(Decimal,)> = sqlx::query_as(
+            "SELECT remaining FROM balance_holds WHERE order_id = $1 AND released_at IS NULL FOR UPDATE"
+        )
+            .bind(order.id)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        if let Some((remaining,)) = remaining {
+            let consumed = if completes_order { remaining } else { remaining.min(pay_amount) };
+
+            sqlx::query(
+                r#"UPDATE balance_holds
+                   SET remaining = remaining - $2,
+                       released_at = CASE WHEN $3 THEN NOW() ELSE NULL END
+                   WHERE order_id = $1"#
+            )
+                .bind(order.id)
+                .bind(consumed)
+                .bind(completes_order)
+                .execute(&mut *conn)
+                .await?;
+
+            sqlx::query(
+                r#"UPDATE account_balances SET held = held - $3, updated_at = NOW()
+                   WHERE account_id = $1 AND asset = $2"#
+            )
+                .bind(order.account_id)
+                .bind(&pay_asset)
+                .bind(consumed)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        self.post(
+            conn,
+            "trade",
+            Some(order.id),
+            &[
+                Posting { account_id: order.account_id, asset: pay_asset.clone(), amount: -pay_amount },
+                Posting { account_id: HOUSE_ACCOUNT, asset: pay_asset, amount: pay_amount },
+                Posting { account_id: order.account_id, asset: recv_asset.clone(), amount: recv_amount },
+                Posting { account_id: HOUSE_ACCOUNT, asset: recv_asset, amount: -recv_amount },
+            ],
+        )
+            .await
+    }
+
+    /// Credit an account from the house account (funding, sandbox balances)
+    pub async fn deposit(
+        &self,
+        conn: &mut PgConnection,
+        account_id: Uuid,
+        asset: &str,
+        amount: Decimal,
+        entry_type: &str,
+    ) -> Result<(), sqlx::Error> {
+        self.post(
+            conn,
+            entry_type,
+            None,
+            &[
+                Posting { account_id, asset: asset.to_string(), amount },
+                Posting { account_id: HOUSE_ACCOUNT, asset: asset.to_string(), amount: -amount },
+            ],
+        )
+            .await
+    }
+
+    /// Transfer every balance of an account back to the house account (holds must be released first)
+    pub async fn zero_account(&self, conn: &mut PgConnection, account_id: Uuid) -> Result<(), sqlx::Error> {
+        let balances: Vec<(String, Decimal)> = sqlx::query_as(
+            "SELECT asset, total FROM account_balances WHERE account_id = $1 AND total != 0"
+        )
+            .bind(account_id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        let postings: Vec<Posting> = balances
+            .into_iter()
+            .flat_map(|(asset, total)| {
+                [
+                    Posting { account_id, asset: asset.clone(), amount: -total },
+                    Posting { account_id: HOUSE_ACCOUNT, asset, amount: total },
+                ]
+            })
+            .collect();
+
+        if postings.is_empty() {
+            return Ok(());
+        }
+
+        self.post(conn, "reset", None, &postings).await
+    }
+
+    /// Scale every holding of an asset (and its open holds) by a split ratio.
+    /// Returns `(account_id, total_before, total_after)` for each adjusted account.
+    pub async fn apply_split(
+        &self,
+        conn: &mut PgConnection,
+        asset: &str,
+        ratio: Decimal,
+        reference_id: Uuid,
+    ) -> Result<Vec<(Uuid, Decimal, Decimal)>, sqlx::Error> {
+        let balances: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            r#"SELECT account_id, total FROM account_balances
+               WHERE asset = $1 AND account_id != $2 AND total != 0
+               FOR UPDATE"#
+        )
+            .bind(asset)
+            .bind(HOUSE_ACCOUNT)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        let changes: Vec<(Uuid, Decimal, Decimal)> = balances
+            .into_iter()
+            .map(|(account_id, total)| (account_id, total, (total * ratio).round_dp_with_strategy(8, RoundingStrategy::MidpointAwayFromZero)))
+            .filter(|(_, before, after)| before != after)
+            .collect();
+
+        // Rounded like Postgres ROUND(); holds shrink before totals and grow after them so held <= total is never violated
+        if ratio < dec!(1) {
+            self.scale_holds(conn, asset, ratio).await?;
+        }
+
+        let mut postings: Vec<Posting> = changes
+            .iter()
+            .map(|(account_id, before, after)| Posting {
+                account_id: *account_id,
+                asset: asset.to_string(),
+                amount: after - before,
+            })
+            .collect();
+
+        if !postings.is_empty() {
+            let net: Decimal = postings.iter().map(|p| p.amount).sum();
+            postings.push(Posting { account_id: HOUSE_ACCOUNT, asset: asset.to_string(), amount: -net });
+            self.post(conn, "split", Some(reference_id), &postings).await?;
+        }
+
+        if ratio > dec!(1) {
+            self.scale_holds(conn, asset, ratio).await?;
+        }
+
+        Ok(changes)
+    }
+
+    /// Truncating each hold keeps the recomputed `held` at or below the rounded total
+    async fn scale_holds(&self, conn: &mut PgConnection, asset: &str, ratio: Decimal) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE balance_holds
+               SET amount = TRUNC(amount * $2, 8), remaining = TRUNC(remaining * $2, 8)
+               WHERE asset = $1 AND released_at IS NULL"#
+        )
+            .bind(asset)
+            .bind(ratio)
+            .execute(&mut *conn)
+            .await?;
+
+        sqlx::query(
+            r#"UPDATE account_balances ab
+               SET held = COALESCE((SELECT SUM(h.remaining) FROM balance_holds h
+                                    WHERE h.account_id = ab.account_id AND h.asset = ab.asset
+                                      AND h.released_at IS NULL), 0),
+                   updated_at = NOW()
+               WHERE ab.asset = $1 AND ab.held != 0"#
+        )
+            .bind(asset)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Write a balanced journal and apply it to account_balances
+    pub async fn post(
+        &self,
+        conn: &mut PgConnection,
+        entry_type: &str,
+        reference_id: Option<Uuid>,
+        postings: &[Posting],
+    ) -> Result<(), sqlx::Error> {
+        let journal_id = self.ids.next_id();
+        debug_assert!(is_balanced(postings), "unbalanced journal {}", journal_id);
+
+        for posting in postings {
+            sqlx::query(
+                r#"INSERT INTO ledger_entries (journal_id, account_id, asset, amount, entry_type, reference_id)
+                   VALUES ($1, $2, $3, $4, $5, $6)"#
+            )
+                .bind(journal_id)
+                .bind(posting.account_id)
+                .bind(&posting.asset)
+                .bind(posting.amount)
+                .bind(entry_type)
+                .bind(reference_id)
+                .execute(&mut *conn)
+                .await?;
+
+            sqlx::query(
+                r#"INSERT INTO account_balances (account_id, asset, total)
+                   VALUES ($1, $2, $3)
+                   ON CONFLICT (account_id, asset) DO UPDATE SET
+                       total = account_balances.total + $3,
+                       updated_at = NOW()"#
+            )
+                .bind(posting.account_id)
+                .bind(&posting.asset)
+                .bind(posting.amount)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn balances(&self, account_id: Uuid) -> Result<Vec<AssetBalance>, sqlx::Error> {
+        self.reads.run("balances.get", |pool| async move {
+            sqlx::query_as(
+                "SELECT account_id, asset, total, held FROM account_balances WHERE account_id = $1 ORDER BY asset"
+            )
+                .bind(account_id)
+                .fetch_all(&pool)
+                .await
+        })
+            .await
+    }
+}
+
+fn is_balanced(postings: &[Posting]) -> bool {
+    let mut sums: std::collections::HashMap<&str, Decimal> = std::collections::HashMap::new();
+    for p in postings {
+        *sums.entry(p.asset.as_str()).or_default() += p.amount;
+    }
+    sums.values().all(|s| s.is_zero())
+}
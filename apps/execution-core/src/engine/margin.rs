@@ -0,0 +1,78 @@
+//! Margin Calculator
+//! Margin an account's derivative positions require, after netting related instruments
+
+use crate::auth::AuthContext;
+use crate::engine::error::EngineError;
+use crate::engine::netting::{classify, Exposure, NettingEngine, NettingSet};
+use crate::engine::{OrderProcessor, PositionKeeper};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarginReport {
+    pub sets: Vec<NettingSet>,
+    /// Derivative notional before netting
+    pub gross_exposure: Decimal,
+    /// Derivative notional after netting
+    pub net_exposure: Decimal,
+    pub margin_rate: Decimal,
+    pub required_margin: Decimal,
+}
+
+pub struct MarginCalculator {
+    netting: NettingEngine,
+    rate: Decimal,
+    order_processor: Arc<OrderProcessor>,
+    position_keeper: Arc<PositionKeeper>,
+}
+
+impl MarginCalculator {
+    pub fn new(
+        netting: NettingEngine,
+        rate: Decimal,
+        order_processor: Arc<OrderProcessor>,
+        position_keeper: Arc<PositionKeeper>,
+    ) -> Self {
+        Self { netting, rate, order_processor, position_keeper }
+    }
+
+    /// Margin of the caller's positions, marked at the last trade (or their average price
+    /// before the symbol has traded)
+    pub async fn account_margin(&self, auth: &AuthContext) -> Result<MarginReport, EngineError> {
+        let positions = self.position_keeper.get_account_positions(auth, None).await?;
+
+        let mut exposures = Vec::with_capacity(positions.len());
+        for position in positions {
+            let mark = self.order_processor
+                .last_price(&position.symbol)
+                .await
+                .unwrap_or(position.avg_price);
+            exposures.push(Exposure {
+                notional: position.net_quantity * mark,
+                symbol: position.symbol,
+            });
+        }
+
+        Ok(self.report(&exposures))
+    }
+
+    fn report(&self, exposures: &[Exposure]) -> MarginReport {
+        let sets = self.netting.netting_sets(exposures);
+        let gross_exposure = exposures
+            .iter()
+            .filter(|e| classify(&e.symbol).0.is_derivative())
+            .map(|e| e.notional.abs())
+            .sum();
+        let net_exposure: Decimal = sets.iter().map(|s| s.exposure).sum();
+
+        MarginReport {
+            sets,
+            gross_exposure,
+            net_exposure,
+            margin_rate: self.rate,
+            required_margin: (net_exposure * self.rate).round_dp(8),
+        }
+    }
+}
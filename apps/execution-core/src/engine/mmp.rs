@@ -0,0 +1,364 @@
+//! Market-Maker Protection
+//! Pulls a quoting account's orders on a symbol when it is filled too often or too much
+//! within a rolling window, and blocks new ones until the protection resets
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+use crate::engine::handoff::MmpWindow;
+use crate::clock::SharedClock;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::{mpsc, RwLock};
+use uuid::Uuid;
+
+/// Trips waiting to be published; more than this and the oldest notifications are dropped
+const TRIP_QUEUE_CAPACITY: usize = 1024;
+
+/// Longest rolling window (one hour)
+const MAX_WINDOW_SECS: i32 = 3_600;
+
+/// Time and quantity of the fills within a rolling window, oldest first
+pub type FillWindow = VecDeque<(DateTime<Utc>, Decimal)>;
+
+/// Protection of one symbol, with when it last triggered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct MmpSettings {
+    pub symbol: String,
+    /// Trigger after more than this many fills within the window
+    #[serde(alias = "fillLimit", default)]
+    pub fill_limit: Option<i32>,
+    /// Trigger once more than this quantity filled within the window
+    #[serde(alias = "quantityLimit", default)]
+    pub quantity_limit: Option<Decimal>,
+    #[serde(alias = "windowSecs")]
+    pub window_secs: i32,
+    /// New orders are blocked this long after triggering; without it until reset
+    #[serde(alias = "freezeSecs", default)]
+    pub freeze_secs: Option<i32>,
+    #[serde(skip_deserializing)]
+    pub triggered_at: Option<DateTime<Utc>>,
+}
+
+impl MmpSettings {
+    fn validate(&self) -> Result<(), String> {
+        if self.symbol.trim().is_empty() {
+            return Err("symbol is required".into());
+        }
+        if self.fill_limit.is_none() && self.quantity_limit.is_none() {
+            return Err("fill_limit or quantity_limit is required".into());
+        }
+        if self.fill_limit.is_some_and(|limit| limit <= 0) {
+            return Err("fill_limit must be positive".into());
+        }
+        if self.quantity_limit.is_some_and(|limit| limit <= Decimal::ZERO) {
+            return Err("quantity_limit must be positive".into());
+        }
+        if self.window_secs <= 0 || self.window_secs > MAX_WINDOW_SECS {
+            return Err(format!("window_secs must be between 1 and {}", MAX_WINDOW_SECS));
+        }
+        if self.freeze_secs.is_some_and(|secs| secs <= 0) {
+            return Err("freeze_secs must be positive".into());
+        }
+        Ok(())
+    }
+
+    /// When a trigger at `triggered_at` stops blocking orders on its own, if it does
+    pub fn resets_at(&self) -> Option<DateTime<Utc>> {
+        let freeze = self.freeze_secs?;
+        Some(self.triggered_at? + Duration::seconds(freeze.into()))
+    }
+
+    /// Whether the protection still blocks new orders at `now`
+    pub fn blocks(&self, now: DateTime<Utc>) -> bool {
+        self.triggered_at.is_some() && self.resets_at().is_none_or(|at| at > now)
+    }
+
+    /// Why the fills in the window trip the protection, if they do
+    pub fn breached(&self, fills: &FillWindow) -> Option<String> {
+        let count = fills.len();
+        if let Some(limit) = self.fill_limit.filter(|limit| count > *limit as usize) {
+            return Some(format!(
+                "{} fills on {} within {}s, limit is {}",
+                count, self.symbol, self.window_secs, limit
+            ));
+        }
+
+        let quantity: Decimal = fills.iter().map(|(_, quantity)| *quantity).sum();
+        if let Some(limit) = self.quantity_limit.filter(|limit| quantity > *limit) {
+            return Some(format!(
+                "{} filled on {} within {}s, limit is {}",
+                quantity, self.symbol, self.window_secs, limit
+            ));
+        }
+        None
+    }
+}
+
+/// Drop the fills that fell out of a `window_secs` window ending at `now`
+pub fn prune(fills: &mut FillWindow, window_secs: i32, now: DateTime<Utc>) {
+    let start = now - Duration::seconds(window_secs.into());
+    while fills.front().is_some_and(|(at, _)| *at <= start) {
+        fills.pop_front();
+    }
+}
+
+/// Published on `mmp.triggered` when a protection pulls an account's orders
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MmpTrip {
+    pub account_id: Uuid,
+    pub symbol: String,
+    pub reason: String,
+    pub triggered_at: DateTime<Utc>,
+    pub resets_at: Option<DateTime<Utc>>,
+    pub cancelled_orders: Vec<Uuid>,
+}
+
+// =====================================================
+// MARKET-MAKER PROTECTION
+// =====================================================
+
+pub struct MarketMakerProtection {
+    pool: PgPool,
+    clock: SharedClock,
+    /// Settings by account, including accounts that have none
+    settings: RwLock<HashMap<Uuid, Vec<MmpSettings>>>,
+    /// Recent fills by account and symbol, for protected symbols only. Kept in memory, so a
+    /// restart starts every window empty.
+    fills: RwLock<HashMap<(Uuid, String), FillWindow>>,
+    trips: mpsc::Sender<MmpTrip>,
+}
+
+impl MarketMakerProtection {
+    /// The receiver yields every trip, to be published
+    pub fn new(pool: PgPool, clock: SharedClock) -> (Self, mpsc::Receiver<MmpTrip>) {
+        let (trips, receiver) = mpsc::channel(TRIP_QUEUE_CAPACITY);
+        let protection = Self {
+            pool,
+            clock,
+            settings: RwLock::new(HashMap::new()),
+            fills: RwLock::new(HashMap::new()),
+            trips,
+        };
+        (protection, receiver)
+    }
+
+    /// The caller's protections
+    pub async fn get(&self, auth: &AuthContext) -> Result<Vec<MmpSettings>, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_READ) {
+            return Err(EngineError::Auth(
+                "orders:read required".into()
+            ));
+        }
+
+        self.account_settings(auth.account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    /// Protect a symbol, or change its protection; a running trigger stays in place
+    pub async fn update(&self, auth: &AuthContext, mut req: MmpSettings) -> Result<MmpSettings, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CREATE) {
+            return Err(EngineError::Auth(
+                "orders:create required".into()
+            ));
+        }
+
+        req.validate().map_err(EngineError::Validation)?;
+        req.symbol = req.symbol.trim().to_string();
+
+        let stored: MmpSettings = sqlx::query_as(
+            r#"INSERT INTO account_mmp_settings (account_id, symbol, fill_limit, quantity_limit,
+                                                 window_secs, freeze_secs)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               ON CONFLICT (account_id, symbol) DO UPDATE SET
+                   fill_limit = $3,
+                   quantity_limit = $4,
+                   window_secs = $5,
+                   freeze_secs = $6,
+                   updated_at = NOW()
+               RETURNING symbol, fill_limit, quantity_limit, window_secs, freeze_secs, triggered_at"#
+        )
+            .bind(auth.account_id)
+            .bind(&req.symbol)
+            .bind(req.fill_limit)
+            .bind(req.quantity_limit)
+            .bind(req.window_secs)
+            .bind(req.freeze_secs)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        self.store(auth.account_id, stored.clone()).await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        Ok(stored)
+    }
+
+    /// Lift a trigger so the account can quote the symbol again
+    pub async fn reset(&self, auth: &AuthContext, symbol: &str) -> Result<MmpSettings, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CREATE) {
+            return Err(EngineError::Auth(
+                "orders:create required".into()
+            ));
+        }
+
+        let stored: Option<MmpSettings> = sqlx::query_as(
+            r#"UPDATE account_mmp_settings SET triggered_at = NULL, updated_at = NOW()
+               WHERE account_id = $1 AND symbol = $2
+               RETURNING symbol, fill_limit, quantity_limit, window_secs, freeze_secs, triggered_at"#
+        )
+            .bind(auth.account_id)
+            .bind(symbol)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        let stored = stored.ok_or_else(|| {
+            EngineError::Validation(format!("No market-maker protection on {}", symbol))
+        })?;
+
+        self.fills.write().await.remove(&(auth.account_id, stored.symbol.clone()));
+        self.store(auth.account_id, stored.clone()).await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        tracing::info!(account_id = %auth.account_id, symbol, "Market-maker protection reset");
+        Ok(stored)
+    }
+
+    /// Why the account may not place orders on the symbol right now, if it may not
+    pub async fn check(&self, account_id: Uuid, symbol: &str) -> Result<Option<String>, sqlx::Error> {
+        let Some(settings) = self.symbol_settings(account_id, symbol).await? else {
+            return Ok(None);
+        };
+
+        let now = self.clock.now();
+        if !settings.blocks(now) {
+            return Ok(None);
+        }
+        Ok(Some(match settings.resets_at() {
+            Some(at) => format!("Market-maker protection on {} triggered, resets at {}", symbol, at.to_rfc3339()),
+            None => format!("Market-maker protection on {} triggered, reset required", symbol),
+        }))
+    }
+
+    /// Count a committed fill. Returns the settings and reason when it trips the protection;
+    /// the trigger is recorded, and the caller pulls the account's orders and calls `notify`.
+    pub async fn record_fill(
+        &self,
+        account_id: Uuid,
+        symbol: &str,
+        quantity: Decimal,
+    ) -> Result<Option<(MmpSettings, String)>, sqlx::Error> {
+        let Some(settings) = self.symbol_settings(account_id, symbol).await? else {
+            return Ok(None);
+        };
+        let now = self.clock.now();
+        if settings.blocks(now) {
+            return Ok(None);
+        }
+
+        let reason = {
+            let mut fills = self.fills.write().await;
+            let window = fills.entry((account_id, symbol.to_string())).or_default();
+            window.push_back((now, quantity.abs()));
+            prune(window, settings.window_secs, now);
+            let reason = settings.breached(window);
+            if reason.is_some() {
+                window.clear();
+            }
+            reason
+        };
+        let Some(reason) = reason else {
+            return Ok(None);
+        };
+
+        let triggered: MmpSettings = sqlx::query_as(
+            r#"UPDATE account_mmp_settings SET triggered_at = $3, updated_at = NOW()
+               WHERE account_id = $1 AND symbol = $2
+               RETURNING symbol, fill_limit, quantity_limit, window_secs, freeze_secs, triggered_at"#
+        )
+            .bind(account_id)
+            .bind(symbol)
+            .bind(now)
+            .fetch_one(&self.pool)
+            .await?;
+        self.store(account_id, triggered.clone()).await?;
+
+        tracing::warn!(%account_id, symbol, "Market-maker protection triggered: {}", reason);
+        Ok(Some((triggered, reason)))
+    }
+
+    /// Every fill window, for a state handoff
+    pub async fn windows(&self) -> Vec<MmpWindow> {
+        self.fills
+            .read()
+            .await
+            .iter()
+            .filter(|(_, fills)| !fills.is_empty())
+            .map(|((account_id, symbol), fills)| MmpWindow {
+                account_id: *account_id,
+                symbol: symbol.clone(),
+                fills: fills.iter().copied().collect(),
+            })
+            .collect()
+    }
+
+    /// Take over fill windows from a handoff where no fill was counted here yet; returns how many
+    pub async fn seed_windows(&self, windows: Vec<MmpWindow>) -> usize {
+        let mut fills = self.fills.write().await;
+        let mut seeded = 0;
+        for window in windows {
+            let current = fills.entry((window.account_id, window.symbol)).or_default();
+            if current.is_empty() {
+                current.extend(window.fills);
+                seeded += 1;
+            }
+        }
+        seeded
+    }
+
+    /// Queue a trip for publishing
+    pub fn notify(&self, trip: MmpTrip) {
+        if self.trips.try_send(trip).is_err() {
+            tracing::warn!("Market-maker protection queue full, trip notification dropped");
+        }
+    }
+
+    async fn symbol_settings(&self, account_id: Uuid, symbol: &str) -> Result<Option<MmpSettings>, sqlx::Error> {
+        let settings = self.account_settings(account_id).await?;
+        Ok(settings.into_iter().find(|s| s.symbol == symbol))
+    }
+
+    async fn store(&self, account_id: Uuid, stored: MmpSettings) -> Result<(), sqlx::Error> {
+        let mut settings = self.account_settings(account_id).await?;
+        settings.retain(|s| s.symbol != stored.symbol);
+        settings.push(stored);
+        self.settings.write().await.insert(account_id, settings);
+        Ok(())
+    }
+
+    /// Settings of the account, loaded once and then served from memory
+    async fn account_settings(&self, account_id: Uuid) -> Result<Vec<MmpSettings>, sqlx::Error> {
+        if let Some(settings) = self.settings.read().await.get(&account_id) {
+            return Ok(settings.clone());
+        }
+
+        let settings: Vec<MmpSettings> = sqlx::query_as(
+            r#"SELECT symbol, fill_limit, quantity_limit, window_secs, freeze_secs, triggered_at
+               FROM account_mmp_settings WHERE account_id = $1 ORDER BY symbol"#
+        )
+            .bind(account_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        self.settings.write().await.insert(account_id, settings.clone());
+        Ok(settings)
+    }
+
+    pub async fn evict(&self, account_id: Uuid) {
+        self.settings.write().await.remove(&account_id);
+        self.fills.write().await.retain(|(account, _), _| *account != account_id);
+    }
+}
@@ -1,8 +1,64 @@
 //! Trading Engine Module
 //! Contains order processing and position management
 
+pub mod account_settings;
+pub mod analytics;
+pub mod cancel_on_disconnect;
+pub mod corporate_actions;
+pub mod dust;
+pub mod error;
+pub mod executions;
+pub mod exports;
+pub mod exposure;
+pub mod fill_delivery;
+pub mod fill_topic;
+pub mod handoff;
+pub mod impersonation;
+pub mod instruments;
+pub mod integrity;
+pub mod leaderboard;
+pub mod ledger;
+pub mod margin;
+pub mod mmp;
+pub mod netting;
+pub mod order_history;
+pub mod order_journal;
 pub mod order_processor;
 pub mod position_keeper;
+pub mod privacy;
+pub mod rebalance;
+pub mod rebates;
+pub mod risk;
+pub mod sandbox;
+pub mod shard;
+pub mod slippage;
+pub mod strategy_limits;
+pub mod trading_pauses;
+pub mod triggers;
+pub mod twap;
 
+pub use account_settings::AccountSettings;
+pub use cancel_on_disconnect::CancelOnDisconnect;
+pub use corporate_actions::CorporateActionProcessor;
+pub use dust::DustSweeper;
+pub use error::{EngineError, RejectReason};
+pub use executions::ExecutionReports;
+pub use exports::Exports;
+pub use exposure::ExposureTracker;
+pub use fill_delivery::FillDelivery;
+pub use impersonation::Impersonation;
+pub use instruments::Instruments;
+pub use integrity::IntegrityChecker;
+pub use leaderboard::Leaderboard;
+pub use ledger::Ledger;
+pub use margin::MarginCalculator;
+pub use mmp::MarketMakerProtection;
+pub use order_history::OrderHistory;
 pub use order_processor::OrderProcessor;
-pub use position_keeper::PositionKeeper;
\ No newline at end of file
+pub use position_keeper::PositionKeeper;
+pub use privacy::PrivacyManager;
+pub use rebates::Rebates;
+pub use sandbox::SandboxManager;
+pub use strategy_limits::StrategyLimits;
+pub use trading_pauses::TradingPauses;
+pub use twap::TwapScheduler;
\ No newline at end of file
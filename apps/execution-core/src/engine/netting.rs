@@ -0,0 +1,219 @@
+//! Position Netting
+//! Groups positions by underlying and asset class, netting the classes the rules relate
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// Instrument families told apart by symbol, like `spot_pair` does for pre-funding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetClass {
+    Spot,
+    Future,
+    Perpetual,
+}
+
+impl AssetClass {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "spot" => Some(Self::Spot),
+            "future" | "futures" => Some(Self::Future),
+            "perpetual" | "perp" => Some(Self::Perpetual),
+            _ => None,
+        }
+    }
+
+    /// Derivatives are margined; spot is pre-funded and only ever offsets them
+    pub fn is_derivative(&self) -> bool {
+        !matches!(self, Self::Spot)
+    }
+}
+
+/// Asset class and underlying of a symbol: "BTC-PERP" and "BTCPERP" are perpetuals on BTC,
+/// "BTC-FUT-DEC24" a future on BTC, "BTC/USD" and "BTC" spot BTC
+pub fn classify(symbol: &str) -> (AssetClass, String) {
+    let upper = symbol.to_uppercase();
+
+    if let Some((underlying, _)) = upper.split_once("-FUT") {
+        return (AssetClass::Future, underlying.to_string());
+    }
+    if let Some(underlying) = upper.strip_suffix("-PERP").or_else(|| upper.strip_suffix("PERP")) {
+        return (AssetClass::Perpetual, underlying.to_string());
+    }
+
+    let base = upper.split(['/', '-']).next().unwrap_or_default().to_string();
+    (AssetClass::Spot, base)
+}
+
+/// Two asset classes whose positions in the same underlying net against each other.
+/// A class paired with itself nets its own instruments, e.g. futures of different expiries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NettingRule(pub AssetClass, pub AssetClass);
+
+/// Parse `class+class[,class+class...]`, e.g. `future+perpetual,spot+future`
+pub fn parse_netting_rules(spec: &str) -> anyhow::Result<Vec<NettingRule>> {
+    let mut rules: Vec<NettingRule> = Vec::new();
+
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (a, b) = entry
+            .split_once('+')
+            .ok_or_else(|| anyhow::anyhow!("netting rule '{}' is not class+class", entry))?;
+        let class = |name: &str| {
+            AssetClass::parse(name.trim())
+                .ok_or_else(|| anyhow::anyhow!("unknown asset class '{}'", name.trim()))
+        };
+        let rule = NettingRule(class(a)?, class(b)?);
+
+        if rules.iter().any(|r| r.relates(rule.0, rule.1)) {
+            anyhow::bail!("duplicate netting rule {}", entry);
+        }
+        rules.push(rule);
+    }
+
+    Ok(rules)
+}
+
+impl NettingRule {
+    fn relates(&self, a: AssetClass, b: AssetClass) -> bool {
+        (self.0 == a && self.1 == b) || (self.0 == b && self.1 == a)
+    }
+}
+
+/// Signed notional of one position: positive long, negative short
+#[derive(Debug, Clone)]
+pub struct Exposure {
+    pub symbol: String,
+    pub notional: Decimal,
+}
+
+/// Positions margined as one: a single position, or every position the rules net together
+#[derive(Debug, Clone, Serialize)]
+pub struct NettingSet {
+    pub underlying: String,
+    pub classes: Vec<AssetClass>,
+    pub symbols: Vec<String>,
+    pub long_notional: Decimal,
+    pub short_notional: Decimal,
+    /// Net derivative notional, less any spot position on the opposite side.
+    /// Spot offsets derivatives but never adds margin of its own.
+    pub exposure: Decimal,
+}
+
+// =====================================================
+// NETTING ENGINE
+// =====================================================
+
+#[derive(Debug, Clone, Default)]
+pub struct NettingEngine {
+    rules: Vec<NettingRule>,
+}
+
+impl NettingEngine {
+    pub fn new(rules: Vec<NettingRule>) -> Self {
+        Self { rules }
+    }
+
+    /// Whether positions of the two classes net when they share an underlying
+    pub fn nets(&self, a: AssetClass, b: AssetClass) -> bool {
+        self.rules.iter().any(|r| r.relates(a, b))
+    }
+
+    /// Split an account's exposures into netting sets. Classes linked by rules, directly or
+    /// through another class, share a set per underlying; every other position stands alone.
+    pub fn netting_sets(&self, exposures: &[Exposure]) -> Vec<NettingSet> {
+        let mut sets: Vec<(NettingSet, Offsets, Option<Vec<AssetClass>>)> = Vec::new();
+
+        for exposure in exposures.iter().filter(|e| !e.notional.is_zero()) {
+            let (class, underlying) = classify(&exposure.symbol);
+            let group = self.group_of(class);
+            let key = self.nets_within(&group).then_some(group);
+
+            let existing = key.as_ref().and_then(|key| {
+                sets.iter().position(|(set, _, k)| set.underlying == underlying && k.as_ref() == Some(key))
+            });
+            let index = existing.unwrap_or_else(|| {
+                sets.push((empty_set(&underlying), Offsets::default(), key));
+                sets.len() - 1
+            });
+
+            let (set, offsets, _) = &mut sets[index];
+            if !set.classes.contains(&class) {
+                set.classes.push(class);
+                set.classes.sort();
+            }
+            set.symbols.push(exposure.symbol.clone());
+            if exposure.notional > Decimal::ZERO {
+                set.long_notional += exposure.notional;
+            } else {
+                set.short_notional -= exposure.notional;
+            }
+            if class.is_derivative() {
+                offsets.derivatives += exposure.notional;
+                offsets.has_derivatives = true;
+            } else {
+                offsets.spot += exposure.notional;
+            }
+        }
+
+        sets.into_iter()
+            .filter(|(_, offsets, _)| offsets.has_derivatives)
+            .map(|(mut set, offsets, _)| {
+                set.exposure = offsets.exposure();
+                set
+            })
+            .collect()
+    }
+
+    /// Every class reachable from `class` through the rules, itself included
+    fn group_of(&self, class: AssetClass) -> Vec<AssetClass> {
+        let mut group = vec![class];
+        let mut i = 0;
+        while i < group.len() {
+            for rule in &self.rules {
+                for (from, to) in [(rule.0, rule.1), (rule.1, rule.0)] {
+                    if from == group[i] && !group.contains(&to) {
+                        group.push(to);
+                    }
+                }
+            }
+            i += 1;
+        }
+        group.sort();
+        group
+    }
+
+    /// A group nets when it links two classes, or a class to itself
+    fn nets_within(&self, group: &[AssetClass]) -> bool {
+        group.len() > 1 || self.nets(group[0], group[0])
+    }
+}
+
+/// Signed net notional of a set's derivatives and of its spot positions
+#[derive(Debug, Default)]
+struct Offsets {
+    derivatives: Decimal,
+    spot: Decimal,
+    has_derivatives: bool,
+}
+
+impl Offsets {
+    fn exposure(&self) -> Decimal {
+        let hedged = self.spot.is_sign_negative() != self.derivatives.is_sign_negative();
+        if hedged {
+            (self.derivatives.abs() - self.spot.abs()).max(Decimal::ZERO)
+        } else {
+            self.derivatives.abs()
+        }
+    }
+}
+
+fn empty_set(underlying: &str) -> NettingSet {
+    NettingSet {
+        underlying: underlying.to_string(),
+        classes: Vec::new(),
+        symbols: Vec::new(),
+        long_notional: Decimal::ZERO,
+        short_notional: Decimal::ZERO,
+        exposure: Decimal::ZERO,
+    }
+}
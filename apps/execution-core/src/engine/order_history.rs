@@ -0,0 +1,654 @@
+//! Order History & Event Compaction
+//! Folds the event streams of settled orders into snapshots and archives the raw events;
+//! serves either view per order, a timeline of the order's whole life, and pages of an
+//! account's orders for blotters
+
+use crate::auth::{AuthContext, permissions};
+use crate::clock::SharedClock;
+use crate::engine::error::EngineError;
+use crate::observability::metrics::get_metrics;
+use crate::storage::ReadPool;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Order statuses that take no further events
+pub const SETTLED_STATUSES: [&str; 4] = ["filled", "cancelled", "rejected", "expired"];
+
+/// Every status an order row can hold, for validating list filters
+pub const ORDER_STATUSES: [&str; 8] =
+    ["waiting", "pending", "accepted", "partially_filled", "filled", "cancelled", "rejected", "expired"];
+
+/// Orders per page of a list query when the request does not say, and at most
+pub const DEFAULT_PAGE_SIZE: i64 = 100;
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone)]
+pub struct CompactionConfig {
+    /// Time an order must have been settled before its events are compacted
+    pub min_age_secs: i64,
+    /// Orders compacted per transaction
+    pub batch_size: i64,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            min_age_secs: 7 * 86_400,
+            batch_size: 500,
+        }
+    }
+}
+
+/// One order event, live or archived
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderEvent {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub event_type: String,
+    pub event_data: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    /// Set once compaction has moved the event to the archive
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+/// Events of one type within a stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSummary {
+    pub count: i64,
+    pub first_at: DateTime<Utc>,
+    pub last_at: DateTime<Utc>,
+    pub last_data: Option<serde_json::Value>,
+}
+
+/// An order's event stream folded into one row
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderSnapshot {
+    pub order_id: Uuid,
+    pub account_id: Uuid,
+    pub status: String,
+    pub event_count: i64,
+    pub events: Json<BTreeMap<String, EventSummary>>,
+    pub first_event_at: Option<DateTime<Utc>>,
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// `None` while the events are still live and the snapshot is folded on read
+    pub compacted_at: Option<DateTime<Utc>>,
+}
+
+impl OrderSnapshot {
+    fn empty(order_id: Uuid, account_id: Uuid, status: String) -> Self {
+        Self {
+            order_id,
+            account_id,
+            status,
+            event_count: 0,
+            events: Json(BTreeMap::new()),
+            first_event_at: None,
+            last_event_at: None,
+            compacted_at: None,
+        }
+    }
+
+    /// Fold events, oldest first, into the snapshot
+    pub fn fold<'a>(&mut self, stream: impl IntoIterator<Item = &'a OrderEvent>) {
+        for event in stream {
+            self.event_count += 1;
+            self.first_event_at = Some(self.first_event_at.map_or(event.created_at, |t| t.min(event.created_at)));
+            self.last_event_at = Some(self.last_event_at.map_or(event.created_at, |t| t.max(event.created_at)));
+
+            let summary = self.events.0.entry(event.event_type.clone()).or_insert(EventSummary {
+                count: 0,
+                first_at: event.created_at,
+                last_at: event.created_at,
+                last_data: None,
+            });
+            summary.count += 1;
+            summary.first_at = summary.first_at.min(event.created_at);
+            if event.created_at >= summary.last_at {
+                summary.last_at = event.created_at;
+                summary.last_data = event.event_data.clone();
+            }
+        }
+    }
+}
+
+/// How much of an order's history to return
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryView {
+    #[default]
+    Snapshot,
+    Full,
+}
+
+/// The order row a timeline starts from
+#[derive(Debug, Clone, FromRow)]
+pub struct TimelineOrder {
+    pub symbol: String,
+    pub side: String,
+    pub order_type: String,
+    pub quantity: Decimal,
+    pub price: Option<Decimal>,
+    pub stop_price: Option<Decimal>,
+    pub status: String,
+    pub replaces_order_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One trade of the order
+#[derive(Debug, Clone, FromRow)]
+pub struct TimelineFill {
+    pub id: Uuid,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub executed_at: DateTime<Utc>,
+    pub contra_order_id: Option<Uuid>,
+}
+
+/// One step in the life of an order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+    pub at: DateTime<Utc>,
+    /// `created`, `risk_checked`, `filled`, a final status, or the type of a stored event
+    pub kind: String,
+    pub data: Option<serde_json::Value>,
+}
+
+impl TimelineEntry {
+    fn new(at: DateTime<Utc>, kind: &str, data: serde_json::Value) -> Self {
+        Self { at, kind: kind.to_string(), data: Some(data) }
+    }
+}
+
+/// Assemble an order's timeline: its creation and risk check, every stored event (amends,
+/// triggers, cancellations of siblings...), each fill with its price and the running filled
+/// quantity, and the final status when no event already records it. Oldest first; steps at
+/// the same instant keep that order.
+pub fn assemble_timeline(order: &TimelineOrder, events: &[OrderEvent], fills: &[TimelineFill]) -> Vec<TimelineEntry> {
+    let mut timeline = vec![
+        TimelineEntry::new(order.created_at, "created", serde_json::json!({
+            "symbol": order.symbol,
+            "side": order.side,
+            "orderType": order.order_type,
+            "quantity": order.quantity,
+            "price": order.price,
+            "stopPrice": order.stop_price,
+            "replacesOrderId": order.replaces_order_id,
+        })),
+        // Orders are only stored once their risk, funds and limit checks pass
+        TimelineEntry::new(order.created_at, "risk_checked", serde_json::json!({
+            "passed": order.status != "rejected",
+        })),
+    ];
+
+    timeline.extend(events.iter().map(|event| TimelineEntry {
+        at: event.created_at,
+        kind: event.event_type.clone(),
+        data: event.event_data.clone(),
+    }));
+
+    let mut filled = Decimal::ZERO;
+    for fill in fills {
+        filled += fill.quantity;
+        timeline.push(TimelineEntry::new(fill.executed_at, "filled", serde_json::json!({
+            "tradeId": fill.id,
+            "quantity": fill.quantity,
+            "price": fill.price,
+            "filledQuantity": filled,
+            "complete": filled >= order.quantity,
+            "contraOrderId": fill.contra_order_id,
+        })));
+    }
+
+    let closed = matches!(order.status.as_str(), "cancelled" | "rejected" | "expired")
+        && !events.iter().any(|event| event.event_type == order.status);
+    if closed {
+        timeline.push(TimelineEntry::new(order.updated_at, &order.status, serde_json::json!({
+            "filledQuantity": filled,
+        })));
+    }
+
+    // Stable, so the creation steps stay first and fills stay in trade order
+    timeline.sort_by_key(|entry| entry.at);
+    timeline
+}
+
+/// Which way a list query walks creation time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Newest first
+    #[default]
+    Desc,
+    Asc,
+}
+
+/// Filters and position of a page of an account's orders
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderQuery {
+    /// The caller's own account when absent
+    #[serde(alias = "account_id", default)]
+    pub account_id: Option<Uuid>,
+    /// Any of these statuses; every status when empty
+    #[serde(default)]
+    pub statuses: Vec<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// Created at or after
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// Created before
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub sort: SortOrder,
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// `nextCursor` of the previous page, with the same filters and sort
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+impl OrderQuery {
+    /// Rows to ask for: the page, and one more to tell whether another follows
+    pub fn page_size(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    fn validate(&self) -> Result<(), EngineError> {
+        if let Some(status) = self.statuses.iter().find(|s| !ORDER_STATUSES.contains(&s.as_str())) {
+            return Err(EngineError::Validation(format!("unknown order status '{}'", status)));
+        }
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from >= to {
+                return Err(EngineError::Validation("from must be before to".into()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where a page ended: creation time and id of its last order, which orders the rows
+/// uniquely. Opaque to clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl OrderCursor {
+    pub fn encode(&self) -> String {
+        BASE64.encode(format!("{}:{}", self.created_at.timestamp_micros(), self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let raw = String::from_utf8(BASE64.decode(cursor).ok()?).ok()?;
+        let (micros, id) = raw.split_once(':')?;
+        Some(Self {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// One order of a blotter page
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderListing {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub client_order_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub order_type: String,
+    pub quantity: Decimal,
+    pub price: Option<Decimal>,
+    pub stop_price: Option<Decimal>,
+    pub filled_quantity: Decimal,
+    pub avg_fill_price: Option<Decimal>,
+    pub status: String,
+    pub replaces_order_id: Option<Uuid>,
+    pub strategy_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderPage {
+    pub orders: Vec<OrderListing>,
+    /// Cursor of the next page; `None` on the last
+    pub next_cursor: Option<String>,
+}
+
+impl OrderPage {
+    /// Cut the rows fetched for a page of `size` down to it, with a cursor when more follow
+    pub fn from_rows(mut rows: Vec<OrderListing>, size: i64) -> Self {
+        let more = rows.len() as i64 > size;
+        rows.truncate(size as usize);
+        let next_cursor = rows
+            .last()
+            .filter(|_| more)
+            .map(|last| OrderCursor { created_at: last.created_at, id: last.id }.encode());
+        Self { orders: rows, next_cursor }
+    }
+}
+
+// =====================================================
+// ORDER HISTORY
+// =====================================================
+
+pub struct OrderHistory {
+    pool: PgPool,
+    /// Blotter pages; a replica's lag only delays the newest orders
+    reads: ReadPool,
+    config: CompactionConfig,
+    clock: SharedClock,
+}
+
+impl OrderHistory {
+    pub fn new(pool: PgPool, reads: ReadPool, config: CompactionConfig, clock: SharedClock) -> Self {
+        Self {
+            pool,
+            reads,
+            config: CompactionConfig {
+                batch_size: config.batch_size.max(1),
+                ..config
+            },
+            clock,
+        }
+    }
+
+    /// The order's snapshot; folded from its live events when it has not been compacted yet
+    pub async fn snapshot(&self, auth: &AuthContext, order_id: Uuid) -> Result<Option<OrderSnapshot>, EngineError> {
+        let Some((account_id, status)) = self.authorize(auth, order_id).await? else {
+            return Ok(None);
+        };
+
+        let stored: Option<OrderSnapshot> = sqlx::query_as(
+            "SELECT * FROM order_snapshots WHERE order_id = $1"
+        )
+            .bind(order_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let live = live_events(&self.pool, order_id).await?;
+        let mut snapshot = stored.unwrap_or_else(|| OrderSnapshot::empty(order_id, account_id, status));
+        snapshot.fold(&live);
+        Ok(Some(snapshot))
+    }
+
+    /// Every event of the order, archived and live, oldest first
+    pub async fn history(&self, auth: &AuthContext, order_id: Uuid) -> Result<Option<Vec<OrderEvent>>, EngineError> {
+        if self.authorize(auth, order_id).await?.is_none() {
+            return Ok(None);
+        }
+
+        let events: Vec<OrderEvent> = sqlx::query_as(
+            r#"SELECT id, order_id, event_type, event_data, created_at, archived_at
+               FROM order_events_archive WHERE order_id = $1
+               UNION ALL
+               SELECT id, order_id, event_type, event_data, created_at, NULL::timestamptz
+               FROM order_events WHERE order_id = $1
+               ORDER BY created_at, id"#
+        )
+            .bind(order_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Some(events))
+    }
+
+    /// The order's whole life, oldest first: see [`assemble_timeline`]
+    pub async fn timeline(&self, auth: &AuthContext, order_id: Uuid) -> Result<Option<Vec<TimelineEntry>>, EngineError> {
+        let Some(events) = self.history(auth, order_id).await? else {
+            return Ok(None);
+        };
+
+        let order: Option<TimelineOrder> = sqlx::query_as(
+            r#"SELECT symbol, side, order_type, quantity, price, stop_price, status,
+                      replaces_order_id, created_at, updated_at
+               FROM orders WHERE id = $1"#
+        )
+            .bind(order_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(order) = order else {
+            return Ok(None);
+        };
+
+        let fills: Vec<TimelineFill> = sqlx::query_as(
+            r#"SELECT id, quantity, price, executed_at, contra_order_id
+               FROM trades WHERE order_id = $1
+               ORDER BY executed_at, id"#
+        )
+            .bind(order_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Some(assemble_timeline(&order, &events, &fills)))
+    }
+
+    /// A page of an account's orders, live and settled, by creation time. Each page resumes
+    /// strictly after the cursor, so orders created while a client pages never repeat or
+    /// shift a page.
+    pub async fn list(&self, auth: &AuthContext, query: &OrderQuery) -> Result<OrderPage, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_READ) {
+            return Err(EngineError::Auth(
+                "orders:read required".into()
+            ));
+        }
+        let account_id = query.account_id.unwrap_or(auth.account_id);
+        if !auth.can_access_account(&account_id) {
+            return Err(EngineError::Auth(
+                "Cannot read others' orders".into()
+            ));
+        }
+        query.validate()?;
+        let after = match query.cursor.as_deref() {
+            Some(cursor) => Some(OrderCursor::decode(cursor)
+                .ok_or_else(|| EngineError::Validation("invalid cursor".into()))?),
+            None => None,
+        };
+
+        let (direction, past) = match query.sort {
+            SortOrder::Desc => ("DESC", "<"),
+            SortOrder::Asc => ("ASC", ">"),
+        };
+        let sql = format!(
+            r#"SELECT id, account_id, client_order_id, symbol, side, order_type, quantity, price,
+                      stop_price, filled_quantity, avg_fill_price, status, replaces_order_id,
+                      strategy_id, created_at, updated_at
+               FROM orders
+               WHERE account_id = $1
+                 AND (cardinality($2::text[]) = 0 OR status = ANY($2))
+                 AND ($3::text IS NULL OR symbol = $3)
+                 AND ($4::timestamptz IS NULL OR created_at >= $4)
+                 AND ($5::timestamptz IS NULL OR created_at < $5)
+                 AND ($6::timestamptz IS NULL OR (created_at, id) {past} ($6, $7))
+               ORDER BY created_at {direction}, id {direction}
+               LIMIT $8"#
+        );
+        let size = query.page_size();
+        let (sql, query, after) = (&sql, query, &after);
+
+        let rows: Vec<OrderListing> = self.reads.run("orders.list", move |pool| async move {
+            sqlx::query_as(sql)
+                .bind(account_id)
+                .bind(&query.statuses)
+                .bind(&query.symbol)
+                .bind(query.from)
+                .bind(query.to)
+                .bind(after.map(|c| c.created_at))
+                .bind(after.map(|c| c.id))
+                .bind(size + 1)
+                .fetch_all(&pool)
+                .await
+        })
+            .await?;
+
+        Ok(OrderPage::from_rows(rows, size))
+    }
+
+    /// The order's account and status, once the caller may read it
+    async fn authorize(&self, auth: &AuthContext, order_id: Uuid) -> Result<Option<(Uuid, String)>, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_READ) {
+            return Err(EngineError::Auth(
+                "orders:read required".into()
+            ));
+        }
+
+        let order: Option<(Uuid, String)> = sqlx::query_as(
+            "SELECT account_id, status FROM orders WHERE id = $1"
+        )
+            .bind(order_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match order {
+            Some((account_id, _)) if !auth.can_access_account(&account_id) => Err(EngineError::Auth(
+                "Cannot read others' orders".into()
+            )),
+            order => Ok(order),
+        }
+    }
+
+    /// Compact every order settled longer than `min_age_secs`, a batch per transaction;
+    /// returns how many orders were compacted
+    pub async fn compact(&self) -> Result<u64, sqlx::Error> {
+        let cutoff = self.clock.now() - Duration::seconds(self.config.min_age_secs);
+        let mut compacted = 0;
+
+        loop {
+            let batch = self.compact_batch(cutoff).await?;
+            compacted += batch;
+            if batch < self.config.batch_size as u64 {
+                return Ok(compacted);
+            }
+        }
+    }
+
+    async fn compact_batch(&self, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let now = self.clock.now();
+
+        // Skip-locked so replicas compacting at the same time take different orders
+        let orders: Vec<(Uuid, Uuid, String)> = sqlx::query_as(
+            r#"SELECT o.id, o.account_id, o.status FROM orders o
+               WHERE o.status = ANY($1) AND o.updated_at < $2
+                 AND EXISTS (SELECT 1 FROM order_events e WHERE e.order_id = o.id)
+               ORDER BY o.updated_at
+               LIMIT $3
+               FOR UPDATE OF o SKIP LOCKED"#
+        )
+            .bind(&SETTLED_STATUSES[..])
+            .bind(cutoff)
+            .bind(self.config.batch_size)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        if orders.is_empty() {
+            return Ok(0);
+        }
+
+        let ids: Vec<Uuid> = orders.iter().map(|(id, _, _)| *id).collect();
+        let moved = archive_events(&mut tx, &ids, now).await?;
+
+        let mut snapshots: BTreeMap<Uuid, OrderSnapshot> = sqlx::query_as::<_, OrderSnapshot>(
+            "SELECT * FROM order_snapshots WHERE order_id = ANY($1)"
+        )
+            .bind(&ids)
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .map(|s| (s.order_id, s))
+            .collect();
+
+        for (order_id, account_id, status) in orders {
+            let snapshot = snapshots
+                .entry(order_id)
+                .or_insert_with(|| OrderSnapshot::empty(order_id, account_id, status.clone()));
+            snapshot.status = status;
+            snapshot.fold(moved.iter().filter(|e| e.order_id == order_id));
+            store_snapshot(&mut tx, snapshot, now).await?;
+        }
+
+        tx.commit().await?;
+
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.order_events_compacted_total.inc_by(moved.len() as f64);
+        }
+
+        Ok(ids.len() as u64)
+    }
+}
+
+async fn live_events(pool: &PgPool, order_id: Uuid) -> Result<Vec<OrderEvent>, sqlx::Error> {
+    sqlx::query_as(
+        r#"SELECT id, order_id, event_type, event_data, created_at, NULL::timestamptz AS archived_at
+           FROM order_events WHERE order_id = $1 ORDER BY created_at, id"#
+    )
+        .bind(order_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Move the orders' live events to the archive, returning them oldest first per order
+async fn archive_events(conn: &mut PgConnection, order_ids: &[Uuid], now: DateTime<Utc>) -> Result<Vec<OrderEvent>, sqlx::Error> {
+    sqlx::query_as(
+        r#"WITH moved AS (
+               DELETE FROM order_events WHERE order_id = ANY($1)
+               RETURNING id, order_id, event_type, event_data, created_at
+           ), archived AS (
+               INSERT INTO order_events_archive (id, order_id, event_type, event_data, created_at, archived_at)
+               SELECT id, order_id, event_type, event_data, created_at, $2 FROM moved
+           )
+           SELECT id, order_id, event_type, event_data, created_at, $2 AS archived_at
+           FROM moved ORDER BY order_id, created_at, id"#
+    )
+        .bind(order_ids)
+        .bind(now)
+        .fetch_all(conn)
+        .await
+}
+
+async fn store_snapshot(conn: &mut PgConnection, snapshot: &OrderSnapshot, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO order_snapshots (order_id, account_id, status, event_count, events,
+                                        first_event_at, last_event_at, compacted_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           ON CONFLICT (order_id) DO UPDATE SET
+               status = EXCLUDED.status, event_count = EXCLUDED.event_count, events = EXCLUDED.events,
+               first_event_at = EXCLUDED.first_event_at, last_event_at = EXCLUDED.last_event_at,
+               compacted_at = EXCLUDED.compacted_at"#
+    )
+        .bind(snapshot.order_id)
+        .bind(snapshot.account_id)
+        .bind(&snapshot.status)
+        .bind(snapshot.event_count)
+        .bind(&snapshot.events)
+        .bind(snapshot.first_event_at)
+        .bind(snapshot.last_event_at)
+        .bind(now)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
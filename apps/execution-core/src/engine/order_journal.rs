@@ -0,0 +1,194 @@
+//! Order Journal
+//! Order submits in fast-ack mode are appended here and answered before they are processed.
+//! Each entry is completed with its outcome once processed; entries still pending at startup
+//! were answered by an instance that stopped before processing them and are replayed, which the
+//! client order id makes idempotent.
+
+use crate::auth::AuthContext;
+use crate::engine::order_processor::NewOrderRequest;
+use crate::ids::SharedIdGenerator;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Producers choose the ack mode of an order submit here; absent means `sync`
+pub const ACK_MODE_HEADER: &str = "Enthropic-Ack-Mode";
+
+/// When an order submit is answered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AckMode {
+    /// Once the order is committed or rejected
+    #[default]
+    Sync,
+    /// Once the request is journaled; the outcome follows on `orders.results.{account_id}`
+    Fast,
+}
+
+impl AckMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "sync" => Some(Self::Sync),
+            "fast" => Some(Self::Fast),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sync => "sync",
+            Self::Fast => "fast",
+        }
+    }
+}
+
+/// What a journaled request came to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalOutcome {
+    /// The order was accepted, or was already there
+    Committed,
+    Rejected,
+    /// Processing failed unexpectedly; the client has to resubmit
+    Failed,
+}
+
+impl JournalOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Committed => "committed",
+            Self::Rejected => "rejected",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// The caller a journaled request is replayed as
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalAuth {
+    pub account_id: Uuid,
+    pub username: String,
+    pub role: String,
+    pub permissions: Vec<String>,
+}
+
+impl From<&AuthContext> for JournalAuth {
+    fn from(auth: &AuthContext) -> Self {
+        Self {
+            account_id: auth.account_id,
+            username: auth.username.clone(),
+            role: auth.role.clone(),
+            permissions: auth.permissions.iter().cloned().collect(),
+        }
+    }
+}
+
+impl From<JournalAuth> for AuthContext {
+    fn from(auth: JournalAuth) -> Self {
+        AuthContext {
+            account_id: auth.account_id,
+            username: auth.username,
+            role: auth.role,
+            permissions: auth.permissions.into_iter().collect::<HashSet<String>>(),
+            token_jti: String::new(),
+            impersonation: None,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct JournalEntry {
+    pub id: Uuid,
+    pub auth: Json<JournalAuth>,
+    pub request: Json<NewOrderRequest>,
+    pub appended_at: DateTime<Utc>,
+}
+
+pub struct OrderJournal {
+    pool: PgPool,
+    /// Entry ids
+    ids: SharedIdGenerator,
+}
+
+impl OrderJournal {
+    pub fn new(pool: PgPool, ids: SharedIdGenerator) -> Self {
+        Self { pool, ids }
+    }
+
+    /// Append a request to process after answering it. Returns its entry, and false when
+    /// the account had already journaled its client order id, giving that entry instead.
+    pub async fn append(
+        &self,
+        auth: &AuthContext,
+        req: &NewOrderRequest,
+        at: DateTime<Utc>,
+    ) -> Result<(Uuid, bool), sqlx::Error> {
+        let id = self.ids.next_id();
+        let appended: Option<Uuid> = sqlx::query_scalar(
+            r#"INSERT INTO order_journal (id, account_id, client_order_id, symbol, auth, request, appended_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               ON CONFLICT (account_id, client_order_id) DO NOTHING
+               RETURNING id"#
+        )
+            .bind(id)
+            .bind(auth.account_id)
+            .bind(&req.client_order_id)
+            .bind(&req.symbol)
+            .bind(Json(JournalAuth::from(auth)))
+            .bind(Json(req))
+            .bind(at)
+            .fetch_optional(&self.pool)
+            .await?;
+        if appended.is_some() {
+            return Ok((id, true));
+        }
+
+        let existing: Uuid = sqlx::query_scalar(
+            "SELECT id FROM order_journal WHERE account_id = $1 AND client_order_id = $2"
+        )
+            .bind(auth.account_id)
+            .bind(&req.client_order_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok((existing, false))
+    }
+
+    /// Record what a pending entry came to
+    pub async fn complete(
+        &self,
+        id: Uuid,
+        outcome: JournalOutcome,
+        order_id: Option<Uuid>,
+        error: Option<&str>,
+        at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE order_journal
+               SET status = $2, order_id = $3, error = $4, completed_at = $5, request = NULL
+               WHERE id = $1 AND status = 'pending'"#
+        )
+            .bind(id)
+            .bind(outcome.as_str())
+            .bind(order_id)
+            .bind(error)
+            .bind(at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Entries answered and never processed, oldest first, limited to `symbols` when sharded
+    pub async fn pending(&self, symbols: Option<Vec<String>>) -> Result<Vec<JournalEntry>, sqlx::Error> {
+        sqlx::query_as(
+            r#"SELECT id, auth, request, appended_at FROM order_journal
+               WHERE status = 'pending' AND ($1::text[] IS NULL OR symbol = ANY($1))
+               ORDER BY appended_at, id"#
+        )
+            .bind(symbols)
+            .fetch_all(&self.pool)
+            .await
+    }
+}
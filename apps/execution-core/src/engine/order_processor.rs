@@ -2,16 +2,53 @@
 //! Phase 1: Persistence + Phase 2: Auth checks
 //! Phase 3: Market execution via MarketTick
 
-use crate::auth::{AuthContext, AuthError, permissions};
-use crate::engine::position_keeper::{PositionKeeper, Fill};
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::{EngineError, RejectReason};
+use crate::clock::SharedClock;
+use crate::ids::SharedIdGenerator;
+use crate::engine::account_settings::{validate_slippage, AccountSettings, OrderDefaults, STRATEGY_TAG_KEY, TIME_IN_FORCE};
+use crate::engine::ledger::{Ledger, LedgerError};
+use crate::engine::rebates::Rebates;
+use crate::engine::shard::{Evictions, Shard, WRONG_SHARD};
+use crate::engine::slippage::SlippageModel;
+use crate::engine::executions::{tick_liquidity, ExecutionReport, ExecutionReports, MAKER, TAKER};
+use crate::engine::fill_delivery::{enqueue_fill, enqueue_fills};
+use crate::engine::handoff::{index_digest, IndexDigest};
+use crate::engine::instruments::Instruments;
+use crate::engine::mmp::{MarketMakerProtection, MmpTrip};
+use crate::engine::strategy_limits::{strategy_tag, StrategyLimits};
+use crate::engine::risk::{BreachAction, ConcentrationBreach, RiskLimits};
+use crate::engine::trading_pauses::TradingPauses;
+use crate::engine::position_keeper::{claim_fill, claim_fills, Fill, FillKey, PositionKeeper};
+use crate::market_data::MarketData;
+use crate::observability::business;
+use crate::observability::metrics::get_metrics;
+use crate::observability::slo;
+use crate::observability::stages::{Stage, StageTimer};
+use crate::observability::slow_ops::slow_query;
+use crate::resilience::AdaptiveLimiter;
+use crate::storage::{retry_transient, EncryptedJson, OrderRepository};
+use crate::storage::encryption::SEALED_KEY;
+use crate::engine::triggers::{OrderTrigger, TriggerAction, TriggerBook, TriggerCondition, TriggerEvent, TriggerSpec};
+
+pub use enthropic_domain::order::{AmendOrderRequest, BracketSpec, NewOrderRequest};
+use enthropic_domain::order::{
+    allocate_volume, amended_terms, batch_fills, average_fill_price, changed_terms, crosses, executes_immediately, generate_order_id, ratchet,
+    plan_crosses, post_only_contra, reducible_quantity, StatedTerms, resolve_notional, stop_triggered, trail_stop, triggered_type, validate_bracket, validate_display, validate_expiry,
+    validate_instrument, validate_post_only, validate_reduce_only, validate_stop, validate_time_in_force,
+};
+use enthropic_domain::consistency::{compare_orders, Mismatch};
+use enthropic_domain::{Depth, OrderBook};
 
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgPool};
 
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
@@ -26,57 +63,239 @@ pub struct MarketTick {
 
     #[serde(rename = "lastPrice")]
     pub last_price: String,
+
+    #[serde(rename = "lastSize", default)]
+    pub last_size: Option<String>,
+
+    /// Cumulative session volume
+    #[serde(default)]
+    pub volume: Option<String>,
+
+    /// Quantity the tick can fill across the symbol's resting orders, oldest first. Without
+    /// it every matched order fills in full.
+    #[serde(rename = "availableVolume", default)]
+    pub available_volume: Option<String>,
 }
 
 // =====================================================
 // ORDER MODEL
 // =====================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Order {
-    pub id: Uuid,
+/// Orders as the engine holds them, with metadata encrypted at rest
+pub type Order = enthropic_domain::Order<EncryptedJson>;
+
+/// Size limit of the serialized `metadata` object
+pub const MAX_ORDER_METADATA_BYTES: usize = 4096;
+
+/// Fewest fills of a tick worth a shared transaction; fewer fill one at a time
+const MIN_FILL_BATCH: usize = 2;
+
+/// Unique index over an account's client order ids. An insert that hits it lost a race
+/// with a concurrent submit of the same client order id.
+const CLIENT_ORDER_ID_KEY: &str = "orders_client_order_id_key";
+
+pub(crate) fn validate_metadata(metadata: Option<&serde_json::Value>) -> Result<(), String> {
+    let Some(value) = metadata else {
+        return Ok(());
+    };
+    if !value.is_object() {
+        return Err("metadata must be a JSON object".into());
+    }
+    // Reserved for values sealed at rest
+    if value.get(SEALED_KEY).is_some() {
+        return Err(format!("metadata key '{}' is reserved", SEALED_KEY));
+    }
+
+    let size = value.to_string().len();
+    if size > MAX_ORDER_METADATA_BYTES {
+        return Err(format!(
+            "metadata is {} bytes, limit is {}",
+            size, MAX_ORDER_METADATA_BYTES
+        ));
+    }
+    Ok(())
+}
+
+/// Fill the fields a submission left out from the account's defaults, rejecting
+/// values the orders table would not accept: `(code, reason)`
+fn apply_defaults(
+    req: &mut NewOrderRequest,
+    defaults: &OrderDefaults,
+    reference_price: Option<Decimal>,
+) -> Result<(), (&'static str, String)> {
+    let order_type = req
+        .order_type
+        .take()
+        // A trail or stop price only makes sense on a stop order, whatever the account defaults to
+        .or_else(|| (req.trail_amount.is_some() || req.trail_percent.is_some()).then(|| "trailing_stop".to_string()))
+        .or_else(|| req.stop_price.map(|_| if req.price.is_some() { "stop_limit" } else { "stop" }.to_string()))
+        .or_else(|| defaults.order_type.clone())
+        .unwrap_or_else(|| if req.price.is_some() { "limit" } else { "market" }.to_string());
+    req.order_type = Some(order_type.to_lowercase());
+
+    let time_in_force = req
+        .time_in_force
+        .take()
+        .or_else(|| req.expires_at.map(|_| "gtd".to_string()))
+        .or_else(|| defaults.time_in_force.clone())
+        .map(|tif| tif.to_lowercase());
+    if let Some(tif) = &time_in_force {
+        if !TIME_IN_FORCE.contains(&tif.as_str()) {
+            return Err((
+                "INVALID_TIME_IN_FORCE",
+                format!("time_in_force must be one of {}", TIME_IN_FORCE.join(", ")),
+            ));
+        }
+    }
+    req.time_in_force = time_in_force;
+
+    let slippage = req.max_slippage_bps.or(defaults.max_slippage_bps);
+    if let Some(bps) = slippage {
+        validate_slippage(bps).map_err(|reason| ("INVALID_SLIPPAGE", reason))?;
+    }
+    if req.order_type.as_deref() == Some("market") && req.price.is_none() {
+        if let (Some(bps), Some(reference)) = (slippage, reference_price) {
+            let allowance = reference * bps / Decimal::from(10_000);
+            let limit = if req.side == "buy" { reference + allowance } else { reference - allowance };
+            req.price = Some(limit.round_dp(8));
+        }
+    }
+
+    if let Some(tag) = &defaults.strategy_tag {
+        match &mut req.metadata {
+            None => req.metadata = Some(serde_json::json!({ STRATEGY_TAG_KEY: tag })),
+            Some(serde_json::Value::Object(fields)) => {
+                fields.entry(STRATEGY_TAG_KEY).or_insert_with(|| tag.clone().into());
+            }
+            // Rejected by validate_metadata
+            Some(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Price `order` executes at on a tick at `tick_price` that traded `traded`, if it executes.
+/// Limit orders fill at the tick price once it crosses their limit; market orders at the
+/// slipped price, unless that breaches the protective limit they carry. Stop orders wait to
+/// be triggered.
+fn execution_price(order: &Order, tick_price: Decimal, traded: Option<Decimal>, slippage: &SlippageModel) -> Option<Decimal> {
+    if triggered_type(&order.order_type).is_some() {
+        return None;
+    }
+
+    let fill = if order.order_type == "market" {
+        slippage.fill_price(&order.side, order.quantity - order.filled_quantity, tick_price, traded)
+    } else {
+        tick_price
+    };
+
+    match (order.side.as_str(), order.price) {
+        ("buy", Some(limit)) => (fill <= limit).then_some(fill),
+        ("sell", Some(limit)) => (fill >= limit).then_some(fill),
+        (_, None) if order.order_type == "market" => Some(fill),
+        _ => None,
+    }
+}
+
+/// Published on `orders.expired` for every good-till-date order the sweeper expires
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderExpired {
+    pub order_id: Uuid,
     pub account_id: Uuid,
     pub client_order_id: String,
     pub symbol: String,
-    pub side: String,
-    pub order_type: String,
-    pub quantity: Decimal,
-    pub price: Option<Decimal>,
     pub filled_quantity: Decimal,
-    pub avg_fill_price: Option<Decimal>,
-    pub status: String,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
-// =====================================================
-// NEW ORDER REQUEST
-// =====================================================
+impl From<&Order> for OrderExpired {
+    fn from(order: &Order) -> Self {
+        Self {
+            order_id: order.id,
+            account_id: order.account_id,
+            client_order_id: order.client_order_id.clone(),
+            symbol: order.symbol.clone(),
+            filled_quantity: order.filled_quantity,
+            expires_at: order.expires_at,
+        }
+    }
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Published on `orders.day_cancelled` for every day order cancelled at the session close
+#[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct NewOrderRequest {
-    #[serde(alias = "client_order_id", default = "generate_order_id")]
+pub struct DayOrderCancelled {
+    pub order_id: Uuid,
+    pub account_id: Uuid,
     pub client_order_id: String,
+    pub symbol: String,
+    pub filled_quantity: Decimal,
+    pub session_close: DateTime<Utc>,
+}
 
-    #[serde(alias = "accountId")]
-    pub account_id: Option<String>,
+impl DayOrderCancelled {
+    pub fn new(order: &Order, session_close: DateTime<Utc>) -> Self {
+        Self {
+            order_id: order.id,
+            account_id: order.account_id,
+            client_order_id: order.client_order_id.clone(),
+            symbol: order.symbol.clone(),
+            filled_quantity: order.filled_quantity,
+            session_close,
+        }
+    }
+}
 
-    pub symbol: String,
-    pub side: String,
+/// Filters for `orders.cancel_all`; an empty request cancels every open order
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CancelAllRequest {
+    pub symbol: Option<String>,
+    pub side: Option<String>,
+}
 
-    #[serde(alias = "order_type")]
-    pub order_type: String,
+/// Reply to `orders.cancel_all`: what was cancelled, counted per symbol
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelAllSummary {
+    pub cancelled: usize,
+    pub order_ids: Vec<Uuid>,
+    pub by_symbol: BTreeMap<String, usize>,
+    /// Dependent triggers cancelled with the orders
+    #[serde(skip)]
+    pub disarmed: Vec<Uuid>,
+}
 
-    pub quantity: Decimal,
-    pub price: Option<Decimal>,
+impl CancelAllSummary {
+    pub fn new(orders: &[Order]) -> Self {
+        let mut by_symbol = BTreeMap::new();
+        for order in orders {
+            *by_symbol.entry(order.symbol.clone()).or_insert(0) += 1;
+        }
+        Self {
+            cancelled: orders.len(),
+            order_ids: orders.iter().map(|o| o.id).collect(),
+            by_symbol,
+            disarmed: Vec::new(),
+        }
+    }
 
-    #[serde(alias = "time_in_force", default)]
-    pub time_in_force: Option<String>,
+    /// What the instances owning the cancelled orders' symbols must drop
+    pub fn evictions(&self) -> Evictions {
+        Evictions { order_ids: self.order_ids.clone(), disarmed: self.disarmed.clone(), strategy_ids: Vec::new() }
+    }
 }
 
-fn generate_order_id() -> String {
-    Uuid::new_v4().to_string()
+/// One order's fill as recorded in a transaction it shares: one half of an internal cross,
+/// or one of a tick's batched fills
+struct RecordedFill {
+    updated: Order,
+    fill: Fill,
+    trade_id: Uuid,
+    oco_cancelled: Vec<Uuid>,
+    oco_disarmed: Vec<Uuid>,
 }
 
 // =====================================================
@@ -86,25 +305,291 @@ fn generate_order_id() -> String {
 #[derive(Debug)]
 pub enum OrderResult {
     Accepted(Order),
-    Rejected { reason: String, code: String },
+    Rejected { reason: String, code: RejectReason },
     Duplicate(Order),
 }
 
+fn reject_code(e: &LedgerError) -> RejectReason {
+    match e {
+        LedgerError::NoReferencePrice(_) => RejectReason::BadPrice("NO_REFERENCE_PRICE"),
+        _ => RejectReason::RiskBreach("INSUFFICIENT_FUNDS"),
+    }
+}
+
+/// What a submit comes to when its client order id already names `existing`: a retry of
+/// the same order is a duplicate, while different terms are refused rather than answered
+/// with an order that does not describe them
+fn resubmitted(existing: Order, stated: &StatedTerms, req: &NewOrderRequest) -> OrderResult {
+    let changed = changed_terms(&existing, stated, req);
+    if changed.is_empty() {
+        return OrderResult::Duplicate(existing);
+    }
+    OrderResult::Rejected {
+        reason: format!(
+            "clientOrderId {} was already used for an order with a different {}",
+            req.client_order_id,
+            changed.join(", ")
+        ),
+        code: RejectReason::InvalidOrder("CLIENT_ORDER_ID_REUSED"),
+    }
+}
+
+/// Whether `e` is the insert of a client order id the account already used
+fn is_client_id_conflict(e: &sqlx::Error) -> bool {
+    e.as_database_error().and_then(|e| e.constraint()) == Some(CLIENT_ORDER_ID_KEY)
+}
+
+/// Refusal of an existing order whose symbol another instance trades
+fn wrong_shard(symbol: &str) -> EngineError {
+    EngineError::Validation(format!("{} is traded by another instance", symbol))
+}
+
+// =====================================================
+// MULTI-LEG STRATEGY
+// =====================================================
+
+pub const MAX_STRATEGY_LEGS: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrategyLegRequest {
+    pub symbol: String,
+    pub side: String,
+    /// Leg quantity per strategy unit
+    #[serde(default = "default_leg_ratio")]
+    pub ratio: Decimal,
+    #[serde(default)]
+    pub price: Option<Decimal>,
+}
+
+fn default_leg_ratio() -> Decimal {
+    Decimal::ONE
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewStrategyRequest {
+    #[serde(alias = "client_strategy_id", default = "generate_order_id")]
+    pub client_strategy_id: String,
+    pub quantity: Decimal,
+    /// Limit on the net debit per unit (negative = minimum net credit)
+    #[serde(alias = "net_price", default)]
+    pub net_price: Option<Decimal>,
+    pub legs: Vec<StrategyLegRequest>,
+    /// Copied onto every leg
+    #[serde(alias = "tags", default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Strategy {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub client_strategy_id: String,
+    pub quantity: Decimal,
+    pub net_price: Option<Decimal>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    #[sqlx(skip)]
+    pub legs: Vec<Order>,
+}
+
+#[derive(Debug)]
+pub enum StrategyResult {
+    Accepted(Strategy),
+    Rejected { reason: String, code: RejectReason },
+    Duplicate(Strategy),
+}
+
+/// Whether a strategy can fill at the given leg prices (in leg order).
+/// With a net price the whole package is priced; otherwise every priced leg must be marketable.
+pub fn strategy_marketable(strategy: &Strategy, prices: &[Decimal]) -> bool {
+    let mut legs = strategy.legs.iter().zip(prices);
+
+    match strategy.net_price {
+        Some(limit) => {
+            let net_debit: Decimal = legs
+                .map(|(leg, price)| {
+                    let per_unit = leg.quantity / strategy.quantity * price;
+                    if leg.side == "buy" { per_unit } else { -per_unit }
+                })
+                .sum();
+            net_debit <= limit
+        }
+        None => legs.all(|(leg, price)| match (leg.side.as_str(), leg.price) {
+            ("buy", Some(limit)) => *price <= limit,
+            ("sell", Some(limit)) => *price >= limit,
+            _ => true,
+        }),
+    }
+}
+
+fn validate_strategy(req: &NewStrategyRequest) -> Result<(), String> {
+    if req.legs.len() < 2 || req.legs.len() > MAX_STRATEGY_LEGS {
+        return Err(format!("A strategy needs 2 to {} legs", MAX_STRATEGY_LEGS));
+    }
+    if req.quantity <= Decimal::ZERO {
+        return Err("quantity must be positive".into());
+    }
+
+    let mut symbols = std::collections::HashSet::new();
+    for leg in &req.legs {
+        if !symbols.insert(leg.symbol.as_str()) {
+            return Err(format!("Duplicate leg symbol {}", leg.symbol));
+        }
+        if leg.side != "buy" && leg.side != "sell" {
+            return Err(format!("Invalid side for leg {}", leg.symbol));
+        }
+        if leg.ratio <= Decimal::ZERO {
+            return Err(format!("ratio must be positive for leg {}", leg.symbol));
+        }
+        if matches!(leg.price, Some(p) if p <= Decimal::ZERO) {
+            return Err(format!("price must be positive for leg {}", leg.symbol));
+        }
+    }
+
+    let priced = req.legs.iter().filter(|l| l.price.is_some()).count();
+    if req.net_price.is_some() && priced > 0 {
+        return Err("Use either a net price or leg prices, not both".into());
+    }
+    if priced != 0 && priced != req.legs.len() {
+        return Err("Either all legs or no legs must have a price".into());
+    }
+
+    validate_metadata(req.metadata.as_ref())
+}
+
 // =====================================================
 // ORDER PROCESSOR
 // =====================================================
 
 pub struct OrderProcessor {
     pool: PgPool,
-    orders: Arc<RwLock<HashMap<Uuid, Order>>>,
+    /// Client-initiated writes share the primary's adaptive concurrency limit
+    db_limiter: Arc<AdaptiveLimiter>,
+    repo: Arc<dyn OrderRepository>,
+    ledger: Arc<Ledger>,
+    rebates: Arc<Rebates>,
+    settings: Arc<AccountSettings>,
+    instruments: Arc<Instruments>,
+    pauses: Arc<TradingPauses>,
+    risk: Arc<RiskLimits>,
+    strategy_limits: Arc<StrategyLimits>,
+    mmp: Arc<MarketMakerProtection>,
+    executions: ExecutionReports,
+    slippage: SlippageModel,
+    /// Cross new limit orders with resting orders of other accounts before waiting for ticks
+    internal_crossing: bool,
+    orders: Arc<RwLock<OrderBook<EncryptedJson>>>,
+    strategies: Arc<RwLock<HashMap<Uuid, Strategy>>>,
+    triggers: TriggerBook,
+    market_data: Arc<MarketData>,
+    clock: SharedClock,
+    ids: SharedIdGenerator,
+    last_prices: Arc<RwLock<HashMap<String, Decimal>>>,
+    /// Symbols this instance trades when several active instances split them; all when `None`
+    shard: Option<Arc<Shard>>,
 }
 
 impl OrderProcessor {
-    pub fn new(pool: PgPool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pool: PgPool,
+        db_limiter: Arc<AdaptiveLimiter>,
+        repo: Arc<dyn OrderRepository>,
+        ledger: Arc<Ledger>,
+        rebates: Arc<Rebates>,
+        settings: Arc<AccountSettings>,
+        instruments: Arc<Instruments>,
+        pauses: Arc<TradingPauses>,
+        risk: Arc<RiskLimits>,
+        strategy_limits: Arc<StrategyLimits>,
+        mmp: Arc<MarketMakerProtection>,
+        executions: ExecutionReports,
+        slippage: SlippageModel,
+        internal_crossing: bool,
+        market_data: Arc<MarketData>,
+        clock: SharedClock,
+        ids: SharedIdGenerator,
+        shard: Option<Arc<Shard>>,
+    ) -> Self {
         Self {
             pool,
-            orders: Arc::new(RwLock::new(HashMap::new())),
+            db_limiter,
+            repo,
+            ledger,
+            rebates,
+            settings,
+            instruments,
+            pauses,
+            risk,
+            strategy_limits,
+            mmp,
+            executions,
+            slippage,
+            internal_crossing,
+            market_data,
+            clock,
+            ids,
+            orders: Arc::new(RwLock::new(OrderBook::new())),
+            strategies: Arc::new(RwLock::new(HashMap::new())),
+            triggers: TriggerBook::default(),
+            last_prices: Arc::new(RwLock::new(HashMap::new())),
+            shard,
+        }
+    }
+
+    /// Whether this instance trades `symbol`: every symbol unless sharded
+    pub fn owns(&self, symbol: &str) -> bool {
+        self.shard.as_ref().is_none_or(|shard| shard.owns(symbol))
+    }
+
+    /// Owned symbols for a query filter, `None` unless sharded
+    pub fn owned_symbols(&self) -> Option<Vec<String>> {
+        self.shard.as_ref().map(|shard| shard.symbols())
+    }
+
+    /// Last traded price seen on the market feed for a symbol
+    pub async fn last_price(&self, symbol: &str) -> Option<Decimal> {
+        self.last_prices.read().await.get(symbol).copied()
+    }
+
+    /// Every last traded price seen, for a state handoff
+    pub async fn price_cache(&self) -> HashMap<String, Decimal> {
+        self.last_prices.read().await.clone()
+    }
+
+    /// Take over prices from a handoff for symbols no tick has priced here yet; returns how many
+    pub async fn seed_prices(&self, prices: HashMap<String, Decimal>) -> usize {
+        let mut cache = self.last_prices.write().await;
+        let mut seeded = 0;
+        for (symbol, price) in prices {
+            if let Entry::Vacant(slot) = cache.entry(symbol) {
+                slot.insert(price);
+                seeded += 1;
+            }
         }
+        seeded
+    }
+
+    /// Open orders in the book by symbol, summarized for a state handoff
+    pub async fn open_order_index(&self) -> BTreeMap<String, IndexDigest> {
+        let orders = self.orders.read().await;
+        index_digest(orders.values().map(|order| (order.symbol.clone(), order.id)))
+    }
+
+    /// Price levels of a symbol's resting limit orders
+    pub async fn depth(&self, symbol: &str, levels: usize) -> Depth {
+        self.orders.read().await.depth(symbol, levels)
+    }
+
+    /// Price levels of every symbol with open orders
+    pub async fn depths(&self, levels: usize) -> HashMap<String, Depth> {
+        let orders = self.orders.read().await;
+        orders
+            .symbols()
+            .map(|symbol| (symbol.to_string(), orders.depth(symbol, levels)))
+            .collect()
     }
 
     // =====================================================
@@ -112,23 +597,136 @@ impl OrderProcessor {
     // =====================================================
 
     pub async fn load_open_orders(&self) -> anyhow::Result<usize> {
-        let rows: Vec<Order> = sqlx::query_as(
-            r#"SELECT id, account_id, client_order_id, symbol, side, order_type,
-                      quantity, price, filled_quantity, avg_fill_price, status,
-                      created_at, updated_at
-               FROM orders
-               WHERE status IN ('pending', 'partially_filled')"#
-        )
-            .fetch_all(&self.pool)
-            .await?;
+        let mut rows = self.repo.open_orders(None).await?;
+        rows.retain(|o| self.owns(&o.symbol));
+
+        let count = rows.len();
+        {
+            let mut orders = self.orders.write().await;
+            for order in rows {
+                orders.insert(order);
+            }
+        }
+
+        let strategies = self.load_open_strategies().await?;
+        let triggers = self.triggers.load(&self.pool).await?;
+
+        tracing::info!(
+            "Loaded {} open orders, {} strategies and {} armed triggers",
+            count,
+            strategies,
+            triggers
+        );
+        Ok(count)
+    }
+
+    /// Differences between the cached open orders and their rows on the primary, empty when
+    /// they agree. Only meaningful with no order in flight, as between the operations of a test.
+    pub async fn check_consistency(&self) -> anyhow::Result<Vec<Mismatch>> {
+        let mut rows = self.repo.open_orders(None).await?;
+        rows.retain(|o| self.owns(&o.symbol));
+        let orders = self.orders.read().await;
+        Ok(compare_orders(orders.values(), &rows))
+    }
+
+    /// Replace the strategy cache with all pending strategies and their legs
+    async fn load_open_strategies(&self) -> anyhow::Result<usize> {
+        let by_id: HashMap<Uuid, Strategy> = self.repo
+            .open_strategies()
+            .await?
+            .into_iter()
+            .filter(|s| s.legs.iter().all(|leg| self.owns(&leg.symbol)))
+            .map(|s| (s.id, s))
+            .collect();
+
+        let count = by_id.len();
+        *self.strategies.write().await = by_id;
+        Ok(count)
+    }
+
+    /// Stop trading `symbol` as a rebalance moves it away: drop its open orders and the
+    /// strategies with a leg in it from memory. Returns its last price and how many orders
+    /// were dropped; `None` if this instance does not trade it.
+    pub async fn release_symbol(&self, symbol: &str) -> Option<(Option<Decimal>, usize)> {
+        let shard = self.shard.as_ref()?;
+        if !shard.release(symbol) {
+            return None;
+        }
+
+        let mut orders = self.orders.write().await;
+        let before = orders.len();
+        orders.retain(|o| o.symbol != symbol);
+        let dropped = before - orders.len();
+        drop(orders);
+        self.strategies.write().await.retain(|_, s| s.legs.iter().all(|leg| leg.symbol != symbol));
+
+        Some((self.last_price(symbol).await, dropped))
+    }
+
+    /// Start trading `symbol` as a rebalance moves it here: load its open orders and pending
+    /// strategies, and price it at `last_price` unless a tick already has. Returns how many
+    /// orders were loaded.
+    pub async fn acquire_symbol(&self, symbol: &str, last_price: Option<Decimal>) -> anyhow::Result<usize> {
+        let Some(shard) = &self.shard else {
+            anyhow::bail!("not sharded");
+        };
+        let rows = self.repo.open_orders(Some(symbol)).await?;
 
+        shard.acquire(symbol);
         let count = rows.len();
+        {
+            let mut orders = self.orders.write().await;
+            orders.retain(|o| o.symbol != symbol);
+            for order in rows {
+                orders.insert(order);
+            }
+        }
+        if let Err(e) = self.load_open_strategies().await {
+            self.release_symbol(symbol).await;
+            return Err(e);
+        }
+
+        if let Some(price) = last_price {
+            self.seed_prices(HashMap::from([(symbol.to_string(), price)])).await;
+        }
+        Ok(count)
+    }
+
+    /// Drop all cached open orders of an account (after a bulk DB cancel)
+    pub async fn evict_account(&self, account_id: Uuid) -> usize {
         let mut orders = self.orders.write().await;
-        for order in rows {
-            orders.insert(order.id, order);
+        let before = orders.len();
+        orders.retain(|o| o.account_id != account_id);
+        self.strategies.write().await.retain(|_, s| s.account_id != account_id);
+        self.triggers.disarm_account(account_id).await;
+        self.settings.evict(account_id).await;
+        self.pauses.evict(account_id).await;
+        self.strategy_limits.evict(account_id).await;
+        self.mmp.evict(account_id).await;
+        before - orders.len()
+    }
+
+    /// Re-read a symbol's open orders after a split rewrote them in the DB and rescale its prices
+    pub async fn apply_split(&self, symbol: &str, ratio: Decimal) -> anyhow::Result<usize> {
+        let rows = self.repo.open_orders(Some(symbol)).await?;
+
+        let count = rows.len();
+        {
+            let mut orders = self.orders.write().await;
+            orders.retain(|o| o.symbol != symbol);
+            for order in rows {
+                orders.insert(order);
+            }
+        }
+
+        self.load_open_strategies().await?;
+        self.triggers.load(&self.pool).await?;
+
+        if let Some(price) = self.last_prices.write().await.get_mut(symbol) {
+            *price /= ratio;
         }
+        self.market_data.apply_split(symbol, ratio).await;
 
-        tracing::info!("Loaded {} open orders", count);
         Ok(count)
     }
 
@@ -136,6 +734,7 @@ impl OrderProcessor {
     // MARKET EXECUTION (INILAH YANG HILANG)
     // =====================================================
 
+    #[tracing::instrument(skip_all, fields(symbol = %tick.symbol, price = %tick.last_price))]
     pub async fn process_market_tick(
         &self,
         tick: &MarketTick,
@@ -149,181 +748,2804 @@ impl OrderProcessor {
             }
         };
 
-        let orders = self.orders.read().await;
+        let received = self.clock.elapsed();
+        self.last_prices.write().await.insert(tick.symbol.clone(), price);
 
-        let matched: Vec<Order> = orders
-            .values()
-            .filter(|o| {
-                o.symbol == tick.symbol
-                    && o.status == "pending"
-                    && match (o.side.as_str(), o.price) {
-                    ("buy", Some(limit)) => price <= limit,
-                    ("sell", Some(limit)) => price >= limit,
-                    _ => false,
-                }
-            })
+        let triggered: Vec<Order> = self.orders
+            .read()
+            .await
+            .unpriced(&tick.symbol)
+            .filter(|o| o.status == "pending" && stop_triggered(o, price))
             .cloned()
             .collect();
 
+        // Triggered stops join the book as market or limit orders and can fill on this tick
+        for order in triggered {
+            if let Err(e) = self.trigger_stop(&order, price).await {
+                tracing::error!(order_id = %order.id, "Failed to trigger stop order: {}", e);
+            }
+        }
+
+        // Trailing stops that did not trigger follow the price if it moved their way
+        if let Err(e) = self.ratchet_trailing_stops(&tick.symbol, price).await {
+            tracing::error!("Failed to move trailing stops: {}", e);
+        }
+
+        let orders = self.orders.read().await;
+        let now = self.clock.now();
+
+        // Expired orders wait for the sweeper rather than filling in between, and orders still
+        // on their way to the simulated market wait for a later tick
+        let traded = tick.last_size.as_deref().and_then(|v| v.parse().ok());
+        let matched: Vec<(Order, Decimal)> = orders
+            .matching(&tick.symbol, price)
+            .filter(|o| matches!(o.status.as_str(), "pending" | "partially_filled"))
+            .filter(|o| o.expires_at.is_none_or(|expires_at| expires_at > now))
+            .filter(|o| self.slippage.arrived(o.created_at, now))
+            .filter_map(|o| execution_price(o, price, traded, &self.slippage).map(|fill| (o.clone(), fill)))
+            .collect();
+
         drop(orders);
 
-        for order in matched {
-            if let Err(e) = self.fill_order(order, price, position_keeper).await {
+        let available = tick.available_volume.as_deref().and_then(|v| v.parse().ok());
+        let allocated = allocate_volume(matched, available);
+        let (batch, rest) = batch_fills(allocated.clone());
+        let singles = if batch.len() < MIN_FILL_BATCH {
+            allocated
+        } else {
+            let count = batch.len();
+            match self.fill_batch(batch, position_keeper).await {
+                Ok(true) => {
+                    (0..count).for_each(|_| self.record_fill_delay(received, true));
+                    rest
+                }
+                // A fill recorded elsewhere rolled the batch back; one at a time skips just it
+                Ok(false) => allocated,
+                Err(e) => {
+                    tracing::error!(orders = count, "Failed to fill batch: {}", e);
+                    (0..count).for_each(|_| self.record_fill_delay(received, false));
+                    rest
+                }
+            }
+        };
+
+        for (order, fill_price, quantity) in singles {
+            // A market-maker protection tripped by an earlier fill may have pulled the order
+            if !self.orders.read().await.contains_key(&order.id) {
+                continue;
+            }
+            let filled = self.fill_order(order, fill_price, quantity, position_keeper).await;
+            if let Err(e) = &filled {
                 tracing::error!("Failed to fill order: {}", e);
             }
+            self.record_fill_delay(received, filled.is_ok());
+        }
+
+        let marketable: Vec<(Strategy, Vec<Decimal>)> = {
+            let strategies = self.strategies.read().await;
+            let prices = self.last_prices.read().await;
+
+            strategies
+                .values()
+                .filter(|s| s.legs.iter().any(|l| l.symbol == tick.symbol))
+                .filter_map(|s| {
+                    let leg_prices: Option<Vec<Decimal>> =
+                        s.legs.iter().map(|l| prices.get(&l.symbol).copied()).collect();
+                    leg_prices
+                        .filter(|p| strategy_marketable(s, p))
+                        .map(|p| (s.clone(), p))
+                })
+                .collect()
+        };
+
+        for (strategy, prices) in marketable {
+            let fill = retry_transient(self.repo.dialect(), "fill_strategy", || {
+                self.fill_strategy(strategy.clone(), &prices, position_keeper)
+            });
+            let filled = fill.await;
+            if let Err(e) = &filled {
+                tracing::error!("Failed to fill strategy: {}", e);
+            }
+            self.record_fill_delay(received, filled.is_ok());
+        }
+
+        if self.triggers.watches(&tick.symbol).await {
+            if let Some(series) = self.market_data.snapshot(&tick.symbol).await {
+                self.fire_triggers(TriggerEvent::MarketTick {
+                    symbol: tick.symbol.clone(),
+                    series,
+                })
+                    .await;
+            }
         }
     }
 
-    async fn fill_order(
-        &self,
-        order: Order,
-        price: Decimal,
-        position_keeper: &PositionKeeper,
-    ) -> anyhow::Result<()> {
+    /// Convert a stop order whose stop price traded into the market or limit order it stands for
+    async fn trigger_stop(&self, order: &Order, tick_price: Decimal) -> anyhow::Result<()> {
+        let Some(order_type) = triggered_type(&order.order_type) else {
+            return Ok(());
+        };
 
-        // 1. Insert trade
-        sqlx::query(
-            r#"INSERT INTO trades (order_id, account_id, symbol, side, quantity, price)
-               VALUES ($1, $2, $3, $4, $5, $6)"#
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let converted: Option<Order> = sqlx::query_as(
+            r#"UPDATE orders SET order_type = $2, updated_at = $3
+               WHERE id = $1 AND order_type = $4 AND status = 'pending'
+               RETURNING *"#
         )
             .bind(order.id)
-            .bind(order.account_id)
-            .bind(&order.symbol)
-            .bind(&order.side)
-            .bind(order.quantity)
-            .bind(price)
-            .execute(&self.pool)
+            .bind(order_type)
+            .bind(now)
+            .bind(&order.order_type)
+            .fetch_optional(&mut *tx)
             .await?;
 
-        // 2. Update order
+        let Some(converted) = converted else {
+            // Filled, cancelled or already triggered elsewhere
+            tx.commit().await?;
+            self.orders.write().await.remove(&order.id);
+            return Ok(());
+        };
+
         sqlx::query(
-            r#"UPDATE orders
-               SET status = 'filled',
-                   filled_quantity = quantity,
-                   avg_fill_price = $2,
-                   updated_at = NOW()
-               WHERE id = $1"#
+            "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'stop_triggered', $2::jsonb)"
         )
             .bind(order.id)
-            .bind(price)
-            .execute(&self.pool)
+            .bind(serde_json::json!({
+                "stopPrice": order.stop_price,
+                "triggerPrice": tick_price,
+                "orderType": order_type,
+            }).to_string())
+            .execute(&mut *tx)
             .await?;
 
-        {
-            let mut cache = self.orders.write().await;
-            cache.remove(&order.id);
+        tx.commit().await?;
+
+        tracing::info!(order_id = %order.id, order_type, "Stop order triggered");
+        self.orders.write().await.insert(converted);
+        Ok(())
+    }
+
+    /// Move the trailing stops of `symbol` after a tick, persisting the new levels in one
+    /// statement so a restart resumes from them
+    async fn ratchet_trailing_stops(&self, symbol: &str, tick_price: Decimal) -> anyhow::Result<()> {
+        let (ids, stops): (Vec<Uuid>, Vec<Decimal>) = self.orders
+            .read()
+            .await
+            .unpriced(symbol)
+            .filter(|o| o.status == "pending")
+            .filter_map(|o| ratchet(o, tick_price).map(|stop| (o.id, stop)))
+            .unzip();
+        if ids.is_empty() {
+            return Ok(());
         }
 
-        // 3. Update position
-        position_keeper
-            .apply_fill(&Fill {
-                account_id: order.account_id,
-                symbol: order.symbol,
-                side: order.side,
-                quantity: order.quantity,
-                price,
-            })
+        let now = self.clock.now();
+        let moved: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            r#"UPDATE orders o SET stop_price = t.stop_price, updated_at = $3
+               FROM UNNEST($1::uuid[], $2::numeric[]) AS t(id, stop_price)
+               WHERE o.id = t.id AND o.order_type = 'trailing_stop' AND o.status = 'pending'
+               RETURNING o.id, o.stop_price"#
+        )
+            .bind(&ids)
+            .bind(&stops)
+            .bind(now)
+            .fetch_all(&self.pool)
             .await?;
 
-        tracing::info!("Order {} filled at {}", order.id, price);
+        let mut orders = self.orders.write().await;
+        for (id, stop) in moved {
+            orders.update(&id, |order| {
+                order.stop_price = Some(stop);
+                order.updated_at = now;
+            });
+        }
         Ok(())
     }
 
-    // =====================================================
+    /// Count a fill towards the fill-delay SLO; a failed fill is a bad event
+    fn record_fill_delay(&self, received: Duration, filled: bool) {
+        if filled {
+            slo::record_latency(slo::FILL_DELAY, self.clock.elapsed().saturating_sub(received));
+        } else {
+            slo::record_outcome(slo::FILL_DELAY, false);
+        }
+    }
+
+    /// Fill every leg, settle balances and update positions in a single transaction
+    async fn fill_strategy(
+        &self,
+        strategy: Strategy,
+        prices: &[Decimal],
+        position_keeper: &PositionKeeper,
+    ) -> anyhow::Result<()> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let claimed = sqlx::query(
+            r#"UPDATE order_strategies SET status = 'filled', filled_at = $2, updated_at = $2
+               WHERE id = $1 AND status = 'pending'"#
+        )
+            .bind(strategy.id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        if claimed == 0 {
+            self.strategies.write().await.remove(&strategy.id);
+            return Ok(());
+        }
+
+        let mut fills = Vec::with_capacity(strategy.legs.len());
+        let mut trade_ids = Vec::with_capacity(strategy.legs.len());
+        for (leg, price) in strategy.legs.iter().zip(prices) {
+            let key = FillKey::full(leg.id);
+            let trade_id = self.ids.next_id();
+            if !claim_fill(&mut tx, key, trade_id).await? {
+                // The strategy claim above makes this unreachable unless a leg was filled on its own
+                tracing::warn!(strategy_id = %strategy.id, order_id = %leg.id, "Strategy leg already filled, skipping strategy");
+                self.strategies.write().await.remove(&strategy.id);
+                return Ok(());
+            }
+
+            sqlx::query(
+                r#"INSERT INTO trades (id, order_id, account_id, symbol, side, quantity, price, executed_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#
+            )
+                .bind(trade_id)
+                .bind(leg.id)
+                .bind(leg.account_id)
+                .bind(&leg.symbol)
+                .bind(&leg.side)
+                .bind(leg.quantity)
+                .bind(price)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+            enqueue_fill(&mut tx, trade_id, leg.account_id, now).await?;
+
+            sqlx::query(
+                r#"UPDATE orders
+                   SET status = 'filled',
+                       filled_quantity = quantity,
+                       avg_fill_price = $2,
+                       updated_at = $3
+                   WHERE id = $1"#
+            )
+                .bind(leg.id)
+                .bind(price)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+
+            self.ledger
+                .settle_fill(&mut tx, leg, leg.quantity, *price, true)
+                .await?;
+            self.rebates
+                .credit_maker(&mut tx, leg, trade_id, leg.quantity, *price, now)
+                .await?;
+
+            fills.push(Fill {
+                account_id: leg.account_id,
+                symbol: leg.symbol.clone(),
+                side: leg.side.clone(),
+                quantity: leg.quantity,
+                price: *price,
+                key: Some(key),
+            });
+            trade_ids.push(trade_id);
+        }
+
+        let positions = position_keeper.apply_fills_in(&mut tx, &fills).await?;
+        tx.commit().await?;
+        for fill in &fills {
+            business::record_fill(&fill.symbol, fill.quantity, fill.price);
+        }
+
+        position_keeper.commit_fills(&fills, &positions).await;
+        self.strategies.write().await.remove(&strategy.id);
+
+        for ((leg, fill), trade_id) in strategy.legs.iter().zip(&fills).zip(trade_ids) {
+            let mut filled = leg.clone();
+            filled.filled_quantity = leg.quantity;
+            filled.avg_fill_price = Some(fill.price);
+            filled.status = "filled".into();
+            self.executions.notify(ExecutionReport::new(
+                &filled, trade_id, fill.quantity, fill.price, tick_liquidity(&leg.order_type), now,
+            ));
+        }
+
+        tracing::info!("Strategy {} filled ({} legs)", strategy.id, strategy.legs.len());
+
+        for fill in &fills {
+            self.count_quote_fill(fill.account_id, &fill.symbol, fill.quantity).await;
+        }
+
+        for leg in &strategy.legs {
+            self.fire_triggers(TriggerEvent::OrderFilled {
+                order_id: leg.id,
+                filled_quantity: leg.quantity,
+                completed: true,
+            })
+                .await;
+        }
+        Ok(())
+    }
+
+    // =====================================================
+    // CONDITIONAL TRIGGERS
+    // =====================================================
+
+    /// Activate or cancel every order whose trigger is met by the event
+    async fn fire_triggers(&self, event: TriggerEvent) {
+        for trigger in self.triggers.fired(&event).await {
+            // Both claim the trigger first, so a retried attempt cannot fire it twice
+            let result = retry_transient(self.repo.dialect(), "fire_trigger", || async {
+                match trigger.action {
+                    TriggerAction::Activate => self.activate_triggered(&trigger).await,
+                    TriggerAction::Cancel => self.cancel_triggered(&trigger).await,
+                }
+            })
+                .await;
+
+            if let Err(e) = result {
+                tracing::error!(order_id = %trigger.order_id, "Failed to fire trigger: {}", e);
+            }
+        }
+    }
+
+    /// Move a waiting order into the book, reserving its hold now that it is live
+    async fn activate_triggered(&self, trigger: &OrderTrigger) -> anyhow::Result<()> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        if !claim_trigger(&mut tx, trigger.order_id, now).await? {
+            self.triggers.disarm(&[trigger.order_id]).await;
+            return Ok(());
+        }
+
+        let order: Option<Order> = sqlx::query_as(
+            r#"UPDATE orders SET status = 'pending', updated_at = $2
+               WHERE id = $1 AND status = 'waiting'
+               RETURNING *"#
+        )
+            .bind(trigger.order_id)
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let Some(order) = order else {
+            tx.commit().await?;
+            self.triggers.disarm(&[trigger.order_id]).await;
+            return Ok(());
+        };
+
+        let reference_price = self.last_price(&order.symbol).await;
+        let activated = match self.ledger.reserve(&mut tx, &order, reference_price).await {
+            Ok(()) => true,
+            Err(LedgerError::Database(e)) => return Err(e.into()),
+            Err(e) => {
+                sqlx::query(
+                    r#"UPDATE orders SET status = 'rejected', reject_reason = $2, updated_at = $3
+                       WHERE id = $1"#
+                )
+                    .bind(order.id)
+                    .bind(e.to_string())
+                    .bind(now)
+                    .execute(&mut *tx)
+                    .await?;
+                tracing::info!(order_id = %order.id, code = %reject_code(&e), "Triggered order rejected");
+                false
+            }
+        };
+
+        // A rejected order can never fill, so orders waiting on it are cancelled with it
+        let disarmed = if activated {
+            Vec::new()
+        } else {
+            cancel_dependents(&mut tx, order.id, now).await?
+        };
+
+        tx.commit().await?;
+
+        self.triggers.disarm(&[trigger.order_id]).await;
+        self.triggers.disarm(&disarmed).await;
+        if activated {
+            tracing::info!(order_id = %order.id, "Triggered order activated");
+            self.orders.write().await.insert(order);
+        }
+        Ok(())
+    }
+
+    async fn cancel_triggered(&self, trigger: &OrderTrigger) -> anyhow::Result<()> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        if !claim_trigger(&mut tx, trigger.order_id, now).await? {
+            self.triggers.disarm(&[trigger.order_id]).await;
+            return Ok(());
+        }
+
+        let cancelled = sqlx::query(
+            r#"UPDATE orders SET status = 'cancelled', updated_at = $2
+               WHERE id = $1 AND status IN ('pending', 'partially_filled')"#
+        )
+            .bind(trigger.order_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        self.ledger.release(&mut tx, trigger.order_id).await?;
+        let disarmed = cancel_dependents(&mut tx, trigger.order_id, now).await?;
+
+        tx.commit().await?;
+
+        self.triggers.disarm(&[trigger.order_id]).await;
+        self.triggers.disarm(&disarmed).await;
+        self.orders.write().await.remove(&trigger.order_id);
+
+        if cancelled > 0 {
+            tracing::info!(order_id = %trigger.order_id, "Order cancelled by trigger");
+        }
+        Ok(())
+    }
+
+    /// Fill `quantity` of an order at `price`. Fills short of what remains leave the order
+    /// partially filled in the book for later ticks.
+    #[tracing::instrument(skip_all, fields(order_id = %order.id, account_id = %order.account_id, symbol = %order.symbol))]
+    async fn fill_order(
+        &self,
+        order: Order,
+        price: Decimal,
+        quantity: Decimal,
+        position_keeper: &PositionKeeper,
+    ) -> anyhow::Result<()> {
+        // A reduce-only order fills no more than what is left of the position it closes, and
+        // whatever it cannot fill is cancelled
+        let mut exhausted = false;
+        let quantity = if order.reduce_only {
+            let position = position_keeper.net_quantity(order.account_id, &order.symbol).await;
+            let reducible = reducible_quantity(&order.side, quantity, position);
+            if reducible.is_zero() {
+                return self.cancel_reduce_only(&order, position).await;
+            }
+            exhausted = reducible < quantity;
+            reducible
+        } else {
+            quantity
+        };
+
+        let now = self.clock.now();
+        let filled_quantity = order.filled_quantity + quantity;
+        let completes = filled_quantity >= order.quantity;
+        let trade_id = self.ids.next_id();
+
+        // The order, trade, settlement and position change commit together or not at all;
+        // the caches only follow once they have
+        let mut tx = self.pool.begin().await?;
+
+        // 1. Update the order against the fill it last saw, so a fill raced by another
+        //    one, a cancel or a redelivered tick changes nothing
+        let Some(updated) = record_order_fill(&mut tx, &order, quantity, price, now).await? else {
+            tracing::warn!("Order changed since it matched, skipping fill");
+            self.orders.write().await.remove(&order.id);
+            return Ok(());
+        };
+
+        // The first fill of a grouped order cancels the rest of its group with it
+        let (oco_cancelled, oco_disarmed) = match updated.group_id {
+            Some(group_id) => self.cancel_oco_siblings(&mut tx, group_id, order.id, now).await?,
+            None => (Vec::new(), Vec::new()),
+        };
+
+        // 2. Claim the fill's key and insert the trade with the order update, so a replay
+        //    finds the key taken and records nothing
+        let key = FillKey { order_id: order.id, filled_before: order.filled_quantity };
+        if !claim_fill(&mut tx, key, trade_id).await? {
+            tracing::warn!(filled_before = %key.filled_before, "Fill already recorded, skipping duplicate");
+            self.orders.write().await.remove(&order.id);
+            return Ok(());
+        }
+
+        let insert_trade = sqlx::query(
+            r#"INSERT INTO trades (id, order_id, account_id, symbol, side, quantity, price, executed_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#
+        )
+            .bind(trade_id)
+            .bind(order.id)
+            .bind(order.account_id)
+            .bind(&order.symbol)
+            .bind(&order.side)
+            .bind(quantity)
+            .bind(price)
+            .bind(now)
+            .execute(&mut *tx);
+        slow_query("trades.insert", insert_trade).await?;
+        enqueue_fill(&mut tx, trade_id, order.account_id, now).await?;
+
+        // 3. Settle balances and consume the order's hold, all of what is left once complete,
+        //    crediting the maker rebate with them
+        self.ledger
+            .settle_fill(&mut tx, &order, quantity, price, completes)
+            .await?;
+        self.rebates
+            .credit_maker(&mut tx, &order, trade_id, quantity, price, now)
+            .await?;
+
+        // 4. Update position
+        let fill = Fill {
+            account_id: order.account_id,
+            symbol: order.symbol.clone(),
+            side: order.side.clone(),
+            quantity,
+            price,
+            key: Some(key),
+        };
+        let positions = position_keeper.apply_fills_in(&mut tx, std::slice::from_ref(&fill)).await?;
+        tx.commit().await?;
+        business::record_fill(&order.symbol, quantity, price);
+
+        position_keeper.commit_fills(std::slice::from_ref(&fill), &positions).await;
+        self.executions.notify(ExecutionReport::new(
+            &updated, trade_id, quantity, price, tick_liquidity(&order.order_type), now,
+        ));
+
+        {
+            let mut cache = self.orders.write().await;
+            if completes {
+                cache.remove(&order.id);
+            } else {
+                cache.insert(updated);
+            }
+            for id in &oco_cancelled {
+                cache.remove(id);
+            }
+        }
+        if !oco_cancelled.is_empty() {
+            self.triggers.disarm(&oco_cancelled).await;
+            self.triggers.disarm(&oco_disarmed).await;
+            tracing::info!(siblings = ?oco_cancelled, "One-cancels-other siblings cancelled");
+        }
+
+        if completes {
+            tracing::info!("Order {} filled at {}", order.id, price);
+        } else {
+            tracing::info!("Order {} partially filled, {} of {} at {}", order.id, filled_quantity, order.quantity, price);
+        }
+
+        if exhausted && !completes {
+            let position = position_keeper.net_quantity(order.account_id, &order.symbol).await;
+            self.cancel_reduce_only(&order, position).await?;
+        }
+
+        self.count_quote_fill(order.account_id, &order.symbol, quantity).await;
+
+        self.fire_triggers(TriggerEvent::OrderFilled {
+            order_id: order.id,
+            filled_quantity,
+            completed: completes,
+        })
+            .await;
+        Ok(())
+    }
+
+    /// Fill a tick's orders of distinct accounts, none of them reduce-only, in one transaction
+    /// with one statement per step for all of them. False when some fill was already recorded,
+    /// having rolled back and changed nothing.
+    #[tracing::instrument(skip_all, fields(orders = fills.len()))]
+    async fn fill_batch(
+        &self,
+        fills: Vec<(Order, Decimal, Decimal)>,
+        position_keeper: &PositionKeeper,
+    ) -> anyhow::Result<bool> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        // 1. Update every order against the fill it last saw; those that changed drop out
+        let mut moved: HashMap<Uuid, Order> = record_order_fills(&mut tx, &fills, now)
+            .await?
+            .into_iter()
+            .map(|o| (o.id, o))
+            .collect();
+        let mut changed = Vec::new();
+        let mut fills: Vec<(Order, Order, Decimal, Decimal)> = fills
+            .into_iter()
+            .filter_map(|(order, price, quantity)| match moved.remove(&order.id) {
+                Some(updated) => Some((order, updated, price, quantity)),
+                None => {
+                    changed.push(order.id);
+                    None
+                }
+            })
+            .collect();
+        if !changed.is_empty() {
+            tracing::warn!(orders = ?changed, "Orders changed since they matched, skipping their fills");
+            let mut cache = self.orders.write().await;
+            for id in &changed {
+                cache.remove(id);
+            }
+        }
+        if fills.is_empty() {
+            return Ok(true);
+        }
+
+        // 2. Claim every fill's key and insert the trades, so a replay finds the keys taken
+        let keys: Vec<FillKey> = fills
+            .iter()
+            .map(|(order, ..)| FillKey { order_id: order.id, filled_before: order.filled_quantity })
+            .collect();
+        let trade_ids: Vec<Uuid> = fills.iter().map(|_| self.ids.next_id()).collect();
+        if claim_fills(&mut tx, &keys, &trade_ids).await? < keys.len() {
+            tracing::warn!("Fill already recorded, rolling back batch");
+            return Ok(false);
+        }
+        let order_ids: Vec<Uuid> = keys.iter().map(|key| key.order_id).collect();
+        let mut account_ids = Vec::with_capacity(fills.len());
+        let mut symbols = Vec::with_capacity(fills.len());
+        let mut sides = Vec::with_capacity(fills.len());
+        let mut quantities = Vec::with_capacity(fills.len());
+        let mut prices = Vec::with_capacity(fills.len());
+        for (order, _, price, quantity) in &fills {
+            account_ids.push(order.account_id);
+            symbols.push(order.symbol.clone());
+            sides.push(order.side.clone());
+            quantities.push(*quantity);
+            prices.push(*price);
+        }
+        let insert_trades = sqlx::query(
+            r#"INSERT INTO trades (id, order_id, account_id, symbol, side, quantity, price, executed_at)
+               SELECT t.*, $8 FROM UNNEST($1::uuid[], $2::uuid[], $3::uuid[], $4::text[], $5::text[], $6::numeric[], $7::numeric[])
+                   AS t(id, order_id, account_id, symbol, side, quantity, price)"#
+        )
+            .bind(&trade_ids)
+            .bind(&order_ids)
+            .bind(&account_ids)
+            .bind(&symbols)
+            .bind(&sides)
+            .bind(&quantities)
+            .bind(&prices)
+            .bind(now)
+            .execute(&mut *tx);
+        slow_query("trades.insert_batch", insert_trades).await?;
+        enqueue_fills(&mut tx, &trade_ids, &account_ids, now).await?;
+
+        // 3. Settle each fill, cancel the rest of each filled order's group and credit makers
+        let mut recorded = Vec::with_capacity(fills.len());
+        for ((order, moved, price, quantity), (key, trade_id)) in fills.drain(..).zip(keys.into_iter().zip(trade_ids)) {
+            let completes = moved.status == "filled";
+            self.ledger
+                .settle_fill(&mut tx, &order, quantity, price, completes)
+                .await?;
+            self.rebates
+                .credit_maker(&mut tx, &order, trade_id, quantity, price, now)
+                .await?;
+            let (oco_cancelled, oco_disarmed) = match moved.group_id {
+                Some(group_id) => self.cancel_oco_siblings(&mut tx, group_id, order.id, now).await?,
+                None => (Vec::new(), Vec::new()),
+            };
+            recorded.push(RecordedFill {
+                fill: Fill {
+                    account_id: order.account_id,
+                    symbol: order.symbol.clone(),
+                    side: order.side.clone(),
+                    quantity,
+                    price,
+                    key: Some(key),
+                },
+                updated: moved,
+                trade_id,
+                oco_cancelled,
+                oco_disarmed,
+            });
+        }
+
+        // 4. Update positions, one per account
+        let fills: Vec<Fill> = recorded.iter().map(|r| r.fill.clone()).collect();
+        let positions = position_keeper.apply_fills_in(&mut tx, &fills).await?;
+        tx.commit().await?;
+
+        position_keeper.commit_fills(&fills, &positions).await;
+        {
+            let mut cache = self.orders.write().await;
+            for r in &recorded {
+                if r.updated.status == "filled" {
+                    cache.remove(&r.updated.id);
+                } else {
+                    cache.insert(r.updated.clone());
+                }
+                for id in &r.oco_cancelled {
+                    cache.remove(id);
+                }
+            }
+        }
+
+        for r in recorded {
+            let RecordedFill { fill, updated, trade_id, oco_cancelled, oco_disarmed } = r;
+            business::record_fill(&fill.symbol, fill.quantity, fill.price);
+            self.executions.notify(ExecutionReport::new(
+                &updated, trade_id, fill.quantity, fill.price, tick_liquidity(&updated.order_type), now,
+            ));
+            if !oco_cancelled.is_empty() {
+                self.triggers.disarm(&oco_cancelled).await;
+                self.triggers.disarm(&oco_disarmed).await;
+                tracing::info!(order_id = %updated.id, siblings = ?oco_cancelled, "One-cancels-other siblings cancelled");
+            }
+            tracing::info!("Order {} filled {} of {} at {}", updated.id, updated.filled_quantity, updated.quantity, fill.price);
+
+            self.count_quote_fill(fill.account_id, &fill.symbol, fill.quantity).await;
+            self.fire_triggers(TriggerEvent::OrderFilled {
+                order_id: updated.id,
+                filled_quantity: updated.filled_quantity,
+                completed: updated.status == "filled",
+            })
+                .await;
+        }
+        Ok(true)
+    }
+
+    // =====================================================
+    // INTERNAL CROSSING
+    // =====================================================
+
+    /// Rejection of a post-only order that would take a resting order of another account,
+    /// which it skips crossing and would rest against, leaving the book crossed
+    async fn post_only_takes_internally(
+        &self,
+        account_id: Uuid,
+        symbol: &str,
+        side: &str,
+        limit: Decimal,
+    ) -> Option<OrderResult> {
+        if !self.internal_crossing {
+            return None;
+        }
+        let now = self.clock.now();
+        let orders = self.orders.read().await;
+        let contra = post_only_contra(account_id, side, limit, now, orders.crossing(symbol, side, limit))?;
+        Some(OrderResult::Rejected {
+            reason: format!("Post-only order at {} would execute against a resting order at {}", limit, contra.price.unwrap_or(limit)),
+            code: RejectReason::BadPrice("POST_ONLY_WOULD_TAKE"),
+        })
+    }
+
+    /// Cross a newly resting limit order with the opposite resting orders of other accounts
+    /// it reaches, at their limit prices, before it waits for ticks. Returns the order as it
+    /// stands afterwards.
+    async fn cross_internally(&self, order: Order, position_keeper: &PositionKeeper) -> anyhow::Result<Order> {
+        // Post-only orders never take; reduce-only caps are applied one tick fill at a time
+        if !self.internal_crossing || order.post_only || order.reduce_only {
+            return Ok(order);
+        }
+        let Some(limit) = order.price else {
+            return Ok(order);
+        };
+
+        let now = self.clock.now();
+        let resting: Vec<Order> = self.orders
+            .read()
+            .await
+            .crossing(&order.symbol, &order.side, limit)
+            .filter(|o| !o.reduce_only && o.strategy_id.is_none())
+            .filter(|o| o.expires_at.is_none_or(|expires_at| expires_at > now))
+            .cloned()
+            .collect();
+
+        let mut taker = order;
+        for (maker, price, quantity) in plan_crosses(&taker, resting) {
+            let quantity = quantity.min(taker.quantity - taker.filled_quantity);
+            if quantity <= Decimal::ZERO {
+                break;
+            }
+            if let Some(updated) = self.cross(&taker, &maker, price, quantity, position_keeper).await? {
+                taker = updated;
+            }
+        }
+        Ok(taker)
+    }
+
+    /// Fill both orders of a cross, settle both and update both positions in one
+    /// transaction. `None` when either order changed since the cross was planned.
+    #[tracing::instrument(skip_all, fields(taker_id = %taker.id, maker_id = %maker.id, symbol = %taker.symbol))]
+    async fn cross(
+        &self,
+        taker: &Order,
+        maker: &Order,
+        price: Decimal,
+        quantity: Decimal,
+        position_keeper: &PositionKeeper,
+    ) -> anyhow::Result<Option<Order>> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let mut sides = Vec::with_capacity(2);
+        for (order, contra) in [(taker, maker), (maker, taker)] {
+            match self.record_cross_fill(&mut tx, order, contra.id, quantity, price, now).await? {
+                Some(side) => sides.push(side),
+                None => {
+                    tracing::warn!(order_id = %order.id, "Order changed since it crossed, skipping cross");
+                    return Ok(None);
+                }
+            }
+        }
+        // Only the resting order added the liquidity
+        self.rebates
+            .credit_maker(&mut tx, maker, sides[1].trade_id, quantity, price, now)
+            .await?;
+
+        let fills: Vec<Fill> = sides.iter().map(|side| side.fill.clone()).collect();
+        let positions = position_keeper.apply_fills_in(&mut tx, &fills).await?;
+        tx.commit().await?;
+        business::record_fill(&taker.symbol, quantity, price);
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.internal_crosses_total.inc();
+        }
+
+        position_keeper.commit_fills(&fills, &positions).await;
+        for (side, liquidity) in sides.iter().zip([TAKER, MAKER]) {
+            self.executions.notify(ExecutionReport::new(
+                &side.updated, side.trade_id, quantity, price, liquidity, now,
+            ));
+        }
+        {
+            let mut cache = self.orders.write().await;
+            for side in &sides {
+                if side.updated.status == "filled" {
+                    cache.remove(&side.updated.id);
+                } else {
+                    cache.insert(side.updated.clone());
+                }
+                for id in &side.oco_cancelled {
+                    cache.remove(id);
+                }
+            }
+        }
+        for side in &sides {
+            if !side.oco_cancelled.is_empty() {
+                self.triggers.disarm(&side.oco_cancelled).await;
+                self.triggers.disarm(&side.oco_disarmed).await;
+            }
+        }
+
+        tracing::info!("Order {} crossed order {} internally, {} at {}", taker.id, maker.id, quantity, price);
+
+        for side in &sides {
+            self.count_quote_fill(side.fill.account_id, &side.fill.symbol, quantity).await;
+            self.fire_triggers(TriggerEvent::OrderFilled {
+                order_id: side.updated.id,
+                filled_quantity: side.updated.filled_quantity,
+                completed: side.updated.status == "filled",
+            })
+                .await;
+        }
+        Ok(sides.into_iter().next().map(|side| side.updated))
+    }
+
+    /// One order's half of a cross: its fill, trade and settlement in the cross's transaction
+    async fn record_cross_fill(
+        &self,
+        tx: &mut PgConnection,
+        order: &Order,
+        contra_order_id: Uuid,
+        quantity: Decimal,
+        price: Decimal,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<Option<RecordedFill>> {
+        let Some(updated) = record_order_fill(&mut *tx, order, quantity, price, now).await? else {
+            return Ok(None);
+        };
+        let completes = updated.status == "filled";
+
+        let (oco_cancelled, oco_disarmed) = match updated.group_id {
+            Some(group_id) => self.cancel_oco_siblings(&mut *tx, group_id, order.id, now).await?,
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let trade_id = self.ids.next_id();
+        let key = FillKey { order_id: order.id, filled_before: order.filled_quantity };
+        if !claim_fill(&mut *tx, key, trade_id).await? {
+            return Ok(None);
+        }
+
+        sqlx::query(
+            r#"INSERT INTO trades (id, order_id, account_id, symbol, side, quantity, price, executed_at, contra_order_id)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#
+        )
+            .bind(trade_id)
+            .bind(order.id)
+            .bind(order.account_id)
+            .bind(&order.symbol)
+            .bind(&order.side)
+            .bind(quantity)
+            .bind(price)
+            .bind(now)
+            .bind(contra_order_id)
+            .execute(&mut *tx)
+            .await?;
+        enqueue_fill(&mut *tx, trade_id, order.account_id, now).await?;
+
+        self.ledger
+            .settle_fill(&mut *tx, order, quantity, price, completes)
+            .await?;
+
+        Ok(Some(RecordedFill {
+            fill: Fill {
+                account_id: order.account_id,
+                symbol: order.symbol.clone(),
+                side: order.side.clone(),
+                quantity,
+                price,
+                key: Some(key),
+            },
+            updated,
+            trade_id,
+            oco_cancelled,
+            oco_disarmed,
+        }))
+    }
+
+    /// Cancel the open members of an order's group other than the order itself, in the
+    /// fill's transaction, and complete the group. Returns the cancelled orders and the
+    /// triggers cancelled with them.
+    async fn cancel_oco_siblings(
+        &self,
+        tx: &mut PgConnection,
+        group_id: Uuid,
+        filled_order_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<(Vec<Uuid>, Vec<Uuid>)> {
+        let completed = sqlx::query(
+            r#"UPDATE order_groups SET status = 'completed', filled_order_id = $2, completed_at = $3
+               WHERE id = $1 AND status = 'active'"#
+        )
+            .bind(group_id)
+            .bind(filled_order_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        // A later fill of the order that completed the group has nothing left to cancel
+        if completed == 0 {
+            return Ok((Vec::new(), Vec::new()));
+        }
+
+        let cancelled: Vec<Uuid> = sqlx::query_scalar(
+            r#"UPDATE orders SET status = 'cancelled', updated_at = $3
+               WHERE group_id = $1 AND id <> $2
+                 AND status IN ('waiting', 'pending', 'partially_filled')
+               RETURNING id"#
+        )
+            .bind(group_id)
+            .bind(filled_order_id)
+            .bind(now)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut disarmed = Vec::new();
+        for order_id in &cancelled {
+            self.ledger.release(&mut *tx, *order_id).await?;
+            claim_trigger(&mut *tx, *order_id, now).await?;
+            disarmed.extend(cancel_dependents(&mut *tx, *order_id, now).await?);
+
+            sqlx::query(
+                "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'oco_cancelled', $2::jsonb)"
+            )
+                .bind(order_id)
+                .bind(serde_json::json!({ "groupId": group_id, "filledOrderId": filled_order_id }).to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        Ok((cancelled, disarmed))
+    }
+
+    // =====================================================
+    // MARKET-MAKER PROTECTION
+    // =====================================================
+
+    /// Count a fill against the account's market-maker protection on the symbol, pulling
+    /// its remaining orders there if the fill trips it
+    async fn count_quote_fill(&self, account_id: Uuid, symbol: &str, quantity: Decimal) {
+        let tripped = match self.mmp.record_fill(account_id, symbol, quantity).await {
+            Ok(tripped) => tripped,
+            Err(e) => {
+                tracing::error!(%account_id, symbol, "Failed to count fill for market-maker protection: {}", e);
+                return;
+            }
+        };
+        let Some((settings, reason)) = tripped else {
+            return;
+        };
+
+        let pulled = retry_transient(self.repo.dialect(), "cancel_quotes", || {
+            self.cancel_quotes(account_id, symbol, &reason)
+        })
+            .await;
+        // The trigger already blocks new orders, so a failed pull is still reported
+        let cancelled_orders = pulled.unwrap_or_else(|e| {
+            tracing::error!(%account_id, symbol, "Failed to pull orders after market-maker protection: {}", e);
+            Vec::new()
+        });
+
+        self.mmp.notify(MmpTrip {
+            account_id,
+            symbol: symbol.to_string(),
+            reason,
+            triggered_at: settings.triggered_at.unwrap_or_else(|| self.clock.now()),
+            resets_at: settings.resets_at(),
+            cancelled_orders,
+        });
+    }
+
+    /// Cancel every open order of the account on the symbol, releasing holds and cancelling
+    /// dependent triggers as a cancel would. Strategy legs are left to their strategy.
+    async fn cancel_quotes(&self, account_id: Uuid, symbol: &str, reason: &str) -> anyhow::Result<Vec<Uuid>> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let cancelled: Vec<Uuid> = sqlx::query_scalar(
+            r#"UPDATE orders SET status = 'cancelled', updated_at = $3
+               WHERE account_id = $1 AND symbol = $2 AND strategy_id IS NULL
+                 AND status IN ('waiting', 'pending', 'partially_filled')
+               RETURNING id"#
+        )
+            .bind(account_id)
+            .bind(symbol)
+            .bind(now)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut disarmed = Vec::new();
+        for order_id in &cancelled {
+            self.ledger.release(&mut tx, *order_id).await?;
+            claim_trigger(&mut tx, *order_id, now).await?;
+            disarmed.extend(cancel_dependents(&mut tx, *order_id, now).await?);
+
+            sqlx::query(
+                "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'mmp_cancelled', $2::jsonb)"
+            )
+                .bind(order_id)
+                .bind(serde_json::json!({ "reason": reason }).to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        if !cancelled.is_empty() {
+            let mut orders = self.orders.write().await;
+            for id in &cancelled {
+                orders.remove(id);
+            }
+            drop(orders);
+            self.triggers.disarm(&cancelled).await;
+            self.triggers.disarm(&disarmed).await;
+        }
+
+        tracing::info!(%account_id, symbol, count = cancelled.len(), "Orders pulled by market-maker protection");
+        Ok(cancelled)
+    }
+
+    // =====================================================
     // SUBMIT / CANCEL
     // =====================================================
 
-    pub async fn submit_order(
+    /// Outcome of a submit whose client order id the account already used, or `None` when
+    /// it has not
+    pub async fn resubmission(&self, account_id: Uuid, req: &NewOrderRequest) -> Result<Option<OrderResult>, EngineError> {
+        let existing = self.repo
+            .find_by_client_id(account_id, &req.client_order_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        let Some(existing) = existing else {
+            return Ok(None);
+        };
+        let stated = stated_terms(&self.pool, existing.id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        Ok(Some(resubmitted(existing, &stated, req)))
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(account_id = %auth.account_id, symbol = %req.symbol, order_id = tracing::field::Empty)
+    )]
+    pub async fn submit_order(
+        &self,
+        auth: &AuthContext,
+        mut req: NewOrderRequest,
+        position_keeper: &PositionKeeper,
+        timer: &mut StageTimer,
+    ) -> Result<OrderResult, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CREATE) {
+            return Err(EngineError::Auth(
+                "orders:create required".into()
+            ));
+        }
+        timer.lap(Stage::Auth);
+
+        if !self.owns(&req.symbol) {
+            return Ok(OrderResult::Rejected {
+                reason: format!("{} is traded by another instance", req.symbol),
+                code: RejectReason::InvalidSymbol(WRONG_SHARD),
+            });
+        }
+
+        // Held for the database work only: crossing, fills and publishing after the commit
+        // would skew the latency samples the limit adapts to
+        let permit = self.db_limiter.acquire().await;
+
+        if let Some(result) = self.resubmission(auth.account_id, &req).await? {
+            timer.lap(Stage::Db);
+            return Ok(result);
+        }
+        // Compared as stated if the insert finds the client order id taken after all
+        let stated = req.clone();
+
+        let paused = self.pauses
+            .check(auth.account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        if let Some(reason) = paused {
+            timer.lap(Stage::Db);
+            return Ok(OrderResult::Rejected { reason, code: RejectReason::Halted("TRADING_PAUSED") });
+        }
+
+        let protected = self.mmp
+            .check(auth.account_id, &req.symbol)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        if let Some(reason) = protected {
+            timer.lap(Stage::Db);
+            return Ok(OrderResult::Rejected { reason, code: RejectReason::Halted("MMP_TRIGGERED") });
+        }
+
+        let defaults = self.settings
+            .defaults(auth.account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        timer.lap(Stage::Db);
+
+        let instrument = self.instruments.get(&req.symbol).await;
+        let lot = instrument.as_ref().map(|rules| rules.lot).unwrap_or_default();
+        let reference_price = self.last_price(&req.symbol).await;
+        if let Err((code, reason)) = apply_defaults(&mut req, &defaults, reference_price)
+            .and_then(|()| resolve_notional(&mut req, reference_price, &lot))
+            .and_then(|()| match &instrument {
+                Some(rules) => validate_instrument(&req, rules, reference_price),
+                None => Ok(()),
+            })
+            .and_then(|()| validate_stop(&req))
+            .and_then(|()| validate_time_in_force(&req))
+            .and_then(|()| validate_expiry(&req, self.clock.now()))
+            .and_then(|()| validate_bracket(&req))
+            .and_then(|()| validate_display(&req))
+            .and_then(|()| validate_post_only(&req, reference_price))
+            .and_then(|()| validate_reduce_only(&req))
+        {
+            return Ok(OrderResult::Rejected { reason, code: RejectReason::of(code) });
+        }
+        if let (true, Some(limit)) = (req.post_only, req.price) {
+            if let Some(rejected) = self.post_only_takes_internally(auth.account_id, &req.symbol, &req.side, limit).await {
+                return Ok(rejected);
+            }
+        }
+
+        // A reduce-only order is cut down to the position it closes
+        if req.reduce_only {
+            let position = position_keeper.net_quantity(auth.account_id, &req.symbol).await;
+            let reducible = reducible_quantity(&req.side, req.quantity, position);
+            if reducible.is_zero() {
+                return Ok(OrderResult::Rejected {
+                    reason: format!("Reduce-only {} order would increase a position of {}", req.side, position),
+                    code: RejectReason::InvalidOrder("REDUCE_ONLY_WOULD_INCREASE"),
+                });
+            }
+            req.quantity = reducible;
+        }
+
+        if let Err(reason) = validate_metadata(req.metadata.as_ref()) {
+            return Ok(OrderResult::Rejected { reason, code: RejectReason::InvalidOrder("INVALID_METADATA") });
+        }
+
+        let tag = strategy_tag(req.metadata.as_ref()).map(str::to_string);
+        if let Some(tag) = &tag {
+            let halted = self.strategy_limits
+                .check_halted(auth.account_id, tag)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+            if let Some(reason) = halted {
+                return Ok(OrderResult::Rejected { reason, code: RejectReason::Halted("STRATEGY_HALTED") });
+            }
+        }
+
+        let breach = self.risk
+            .check_concentration(auth, &req.symbol, &req.side, req.quantity)
+            .await;
+        if let Some(ConcentrationBreach { reason, action: BreachAction::Rejected }) = breach {
+            return Ok(OrderResult::Rejected { reason, code: RejectReason::RiskBreach("CONCENTRATION_LIMIT") });
+        }
+
+        let id = self.ids.next_id();
+        tracing::Span::current().record("order_id", tracing::field::display(id));
+        let now = self.clock.now();
+
+        let trigger = match &req.trigger {
+            Some(spec) => Some(self.resolve_trigger(auth.account_id, id, &req.symbol, spec).await?),
+            None => None,
+        };
+
+        // Orders that activate on a trigger wait outside the book and reserve nothing yet
+        let waiting = matches!(trigger, Some(OrderTrigger { action: TriggerAction::Activate, .. }));
+        timer.lap(Stage::Risk);
+
+        // A trailing stop starts one trail from the last trade, or from the first tick without one
+        let stop_price = match (req.order_type.as_deref(), reference_price) {
+            (Some("trailing_stop"), Some(reference)) => {
+                trail_stop(&req.side, req.trail_amount, req.trail_percent, reference)
+            }
+            _ => req.stop_price,
+        };
+
+        let mut tx = self.pool.begin().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let inserted = insert_order(
+            &mut tx,
+            NewOrderRow {
+                id,
+                account_id: auth.account_id,
+                client_order_id: &req.client_order_id,
+                symbol: &req.symbol,
+                side: &req.side,
+                order_type: req.order_type.as_deref().unwrap_or("market"),
+                time_in_force: req.time_in_force.as_deref(),
+                stated_terms: Some(&StatedTerms::of(&stated)),
+                quantity: req.quantity,
+                price: req.price,
+                stop_price,
+                trail_amount: req.trail_amount,
+                trail_percent: req.trail_percent,
+                expires_at: req.expires_at,
+                status: if waiting { "waiting" } else { "pending" },
+                strategy_id: None,
+                parent_order_id: None,
+                display_quantity: req.display_quantity,
+                post_only: req.post_only,
+                reduce_only: req.reduce_only,
+                metadata: req.metadata.as_ref(),
+                now,
+            },
+        )
+            .await;
+        timer.lap(Stage::Db);
+        let mut order = match inserted {
+            Ok(order) => order,
+            // A concurrent submit of the same client order id committed first
+            Err(e) if is_client_id_conflict(&e) => {
+                drop(tx);
+                return self.resubmission(auth.account_id, &stated)
+                    .await?
+                    .ok_or_else(|| EngineError::Conflict(format!("clientOrderId {} is taken", stated.client_order_id)));
+            }
+            Err(e) => return Err(EngineError::Storage(e.to_string())),
+        };
+
+        if let Some(breach) = &breach {
+            record_breach(&mut tx, order.id, breach)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+        }
+
+        if let Some(tag) = &tag {
+            let notional = order.quantity * order.price.or(order.stop_price).or(reference_price).unwrap_or_default();
+            let exceeded = self.strategy_limits
+                .consume(&mut tx, auth.account_id, tag, notional)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+            if let Some(reason) = exceeded {
+                return Ok(OrderResult::Rejected { reason, code: RejectReason::RiskBreach("STRATEGY_LIMIT") });
+            }
+        }
+
+        if let Some(sibling_id) = req.oco_with {
+            match link_oco(&mut tx, &order, sibling_id, now).await {
+                Ok(Ok(group_id)) => order.group_id = Some(group_id),
+                Ok(Err(reason)) => return Ok(OrderResult::Rejected { reason, code: RejectReason::InvalidOrder("INVALID_OCO") }),
+                Err(e) => return Err(EngineError::Storage(e.to_string())),
+            }
+        }
+
+        // IOC and FOK orders match against the last trade now; there is no later tick for them
+        let immediate = executes_immediately(req.time_in_force.as_deref());
+        let fill_price = reference_price
+            .filter(|_| immediate)
+            .and_then(|reference| execution_price(&order, reference, None, &self.slippage));
+        if immediate && fill_price.is_none() {
+            if req.time_in_force.as_deref() == Some("fok") {
+                return Ok(OrderResult::Rejected {
+                    reason: "Fill-or-kill order cannot be filled at the last price".into(),
+                    code: RejectReason::BadPrice("NOT_FILLABLE"),
+                });
+            }
+
+            let cancelled: Order = sqlx::query_as(
+                "UPDATE orders SET status = 'cancelled', updated_at = $2 WHERE id = $1 RETURNING *"
+            )
+                .bind(order.id)
+                .bind(now)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+            tx.commit().await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+            timer.lap(Stage::Db);
+
+            tracing::info!(order_id = %order.id, "Immediate-or-cancel order not marketable, cancelled");
+            return Ok(OrderResult::Accepted(cancelled));
+        }
+
+        // Pre-funding: the order is only accepted if its hold can be reserved
+        if !waiting {
+            match self.ledger.reserve(&mut tx, &order, reference_price).await {
+                Ok(()) => {}
+                Err(LedgerError::Database(e)) => return Err(EngineError::Storage(e.to_string())),
+                Err(e) => {
+                    return Ok(OrderResult::Rejected {
+                        code: reject_code(&e),
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+        timer.lap(Stage::Risk);
+
+        if let Some(trigger) = &trigger {
+            TriggerBook::insert(&mut tx, trigger)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+        }
+
+        let exits = match &req.bracket {
+            Some(bracket) => self.insert_bracket(&mut tx, &order, bracket, req.metadata.as_ref(), now)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?,
+            None => Vec::new(),
+        };
+
+        tx.commit().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        drop(permit);
+        timer.lap(Stage::Db);
+
+        {
+            let mut orders = self.orders.write().await;
+            if let Some(sibling) = req.oco_with {
+                orders.update(&sibling, |sibling| sibling.group_id = order.group_id);
+            }
+            if !waiting {
+                orders.insert(order.clone());
+            }
+        }
+        if let Some(trigger) = trigger {
+            self.triggers.arm(trigger).await;
+        }
+        for exit in exits {
+            self.triggers.arm(exit).await;
+        }
+
+        if let Some(price) = fill_price {
+            if let Err(e) = self.fill_order(order.clone(), price, order.quantity, position_keeper).await {
+                // An immediate order must not rest in the book because its fill failed
+                if let Err(cancel) = self.cancel_unfilled(order.id).await {
+                    tracing::error!(order_id = %order.id, "Failed to cancel unfilled order: {}", cancel);
+                }
+                return Err(EngineError::Storage(e.to_string()));
+            }
+        }
+
+        // A resting limit order first meets the book of other accounts' orders
+        if !waiting && !immediate && fill_price.is_none() {
+            match self.cross_internally(order.clone(), position_keeper).await {
+                Ok(crossed) => return Ok(OrderResult::Accepted(crossed)),
+                Err(e) => tracing::error!(order_id = %order.id, "Failed to cross order internally: {}", e),
+            }
+        }
+        Ok(OrderResult::Accepted(order))
+    }
+
+    /// Insert the exits of a bracket as waiting orders armed to activate on the entry's
+    /// complete fill, returning their triggers to arm once committed
+    async fn insert_bracket(
+        &self,
+        tx: &mut PgConnection,
+        entry: &Order,
+        bracket: &BracketSpec,
+        metadata: Option<&serde_json::Value>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<OrderTrigger>, sqlx::Error> {
+        let side = if entry.side == "buy" { "sell" } else { "buy" };
+        let legs = [
+            ("tp", "limit", bracket.take_profit.map(|p| (Some(p), None))),
+            ("sl", "stop", bracket.stop_loss.map(|p| (None, Some(p)))),
+        ];
+
+        let mut exits = Vec::new();
+        for (suffix, order_type, prices) in legs {
+            let Some((price, stop_price)) = prices else {
+                continue;
+            };
+            let exit = insert_order(
+                &mut *tx,
+                NewOrderRow {
+                    id: self.ids.next_id(),
+                    account_id: entry.account_id,
+                    client_order_id: &format!("{}:{}", entry.client_order_id, suffix),
+                    symbol: &entry.symbol,
+                    side,
+                    order_type,
+                    time_in_force: None,
+                    stated_terms: None,
+                    quantity: entry.quantity,
+                    price,
+                    stop_price,
+                    trail_amount: None,
+                    trail_percent: None,
+                    expires_at: None,
+                    status: "waiting",
+                    strategy_id: None,
+                    parent_order_id: Some(entry.id),
+                    display_quantity: None,
+                    post_only: false,
+                    reduce_only: false,
+                    metadata,
+                    now,
+                },
+            )
+                .await?;
+
+            let trigger = OrderTrigger {
+                order_id: exit.id,
+                account_id: entry.account_id,
+                condition: TriggerCondition::ParentFill {
+                    parent_order_id: entry.id,
+                    filled_quantity: Some(entry.quantity),
+                },
+                action: TriggerAction::Activate,
+            };
+            TriggerBook::insert(&mut *tx, &trigger).await?;
+            exits.push(trigger);
+        }
+
+        if let [take_profit, stop_loss] = exits.as_slice() {
+            create_oco_group(&mut *tx, entry.account_id, &[take_profit.order_id, stop_loss.order_id], now).await?;
+        }
+        Ok(exits)
+    }
+
+    /// Cancel an order that is still pending and release its hold
+    async fn cancel_unfilled(&self, order_id: Uuid) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let cancelled = sqlx::query(
+            "UPDATE orders SET status = 'cancelled', updated_at = $2 WHERE id = $1 AND status = 'pending'"
+        )
+            .bind(order_id)
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if cancelled > 0 {
+            self.ledger.release(&mut tx, order_id).await?;
+        }
+        tx.commit().await?;
+
+        self.orders.write().await.remove(&order_id);
+        Ok(())
+    }
+
+    /// Cancel the rest of a reduce-only order once the position it closes is gone, releasing
+    /// its hold and cancelling dependent triggers as a cancel would
+    async fn cancel_reduce_only(&self, order: &Order, position: Decimal) -> anyhow::Result<()> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+        let cancelled = sqlx::query(
+            r#"UPDATE orders SET status = 'cancelled', updated_at = $2
+               WHERE id = $1 AND status IN ('pending', 'partially_filled')"#
+        )
+            .bind(order.id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        let mut disarmed = Vec::new();
+        if cancelled > 0 {
+            self.ledger.release(&mut tx, order.id).await?;
+            claim_trigger(&mut tx, order.id, now).await?;
+            disarmed = cancel_dependents(&mut tx, order.id, now).await?;
+
+            sqlx::query(
+                "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'reduce_only_cancelled', $2::jsonb)"
+            )
+                .bind(order.id)
+                .bind(serde_json::json!({ "netQuantity": position }).to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        self.orders.write().await.remove(&order.id);
+        if cancelled > 0 {
+            self.triggers.disarm(&[order.id]).await;
+            self.triggers.disarm(&disarmed).await;
+            tracing::info!(order_id = %order.id, %position, "Reduce-only order has no position left to reduce, cancelled");
+        }
+        Ok(())
+    }
+
+    /// Validate a requested trigger against its parent order and fill in defaults
+    async fn resolve_trigger(
+        &self,
+        account_id: Uuid,
+        order_id: Uuid,
+        order_symbol: &str,
+        spec: &TriggerSpec,
+    ) -> Result<OrderTrigger, EngineError> {
+        let condition = match &spec.condition {
+            TriggerCondition::ParentFill { parent_order_id, filled_quantity } => {
+                let parent = self.repo
+                    .find(*parent_order_id)
+                    .await
+                    .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+                let parent = parent
+                    .filter(|p| p.account_id == account_id)
+                    .ok_or_else(|| EngineError::Validation("Parent order not found".into()))?;
+
+                if !matches!(parent.status.as_str(), "waiting" | "pending" | "partially_filled") {
+                    return Err(EngineError::Conflict("Parent order is no longer open".into()));
+                }
+
+                let threshold = filled_quantity.unwrap_or(parent.quantity);
+                if threshold <= Decimal::ZERO || threshold > parent.quantity {
+                    return Err(EngineError::Validation(
+                        "filled_quantity must be positive and at most the parent quantity".into()
+                    ));
+                }
+
+                TriggerCondition::ParentFill {
+                    parent_order_id: parent.id,
+                    filled_quantity: Some(threshold),
+                }
+            }
+            TriggerCondition::MaCross { symbol, fast_period, slow_period, direction } => {
+                if *fast_period == 0 || fast_period >= slow_period {
+                    return Err(EngineError::Validation(
+                        "fast_period must be positive and shorter than slow_period".into()
+                    ));
+                }
+                if *slow_period > self.market_data.max_history() {
+                    return Err(EngineError::Validation(format!(
+                        "slow_period must be at most {}",
+                        self.market_data.max_history()
+                    )));
+                }
+
+                TriggerCondition::MaCross {
+                    symbol: Some(symbol.clone().unwrap_or_else(|| order_symbol.to_string())),
+                    fast_period: *fast_period,
+                    slow_period: *slow_period,
+                    direction: *direction,
+                }
+            }
+            TriggerCondition::PriceVwap { symbol, direction } => TriggerCondition::PriceVwap {
+                symbol: Some(symbol.clone().unwrap_or_else(|| order_symbol.to_string())),
+                direction: *direction,
+            },
+        };
+
+        Ok(OrderTrigger {
+            order_id,
+            account_id,
+            condition,
+            action: spec.action,
+        })
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(account_id = %auth.account_id, order_id = %order_id, symbol = tracing::field::Empty)
+    )]
+    pub async fn cancel_order(
+        &self,
+        auth: &AuthContext,
+        order_id: Uuid,
+    ) -> Result<Option<Order>, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CANCEL) {
+            return Err(EngineError::Auth(
+                "orders:cancel required".into()
+            ));
+        }
+
+        let _permit = self.db_limiter.acquire().await;
+
+        let order = self.repo
+            .find(order_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let order = match order {
+            Some(o) => o,
+            None => return Ok(None),
+        };
+
+        tracing::Span::current().record("symbol", order.symbol.as_str());
+
+        if !auth.can_access_account(&order.account_id) {
+            return Err(EngineError::Auth(
+                "Cannot cancel others' orders".into()
+            ));
+        }
+
+        if !self.owns(&order.symbol) {
+            return Err(wrong_shard(&order.symbol));
+        }
+
+        if order.strategy_id.is_some() {
+            return Err(EngineError::Validation(
+                "Strategy legs can only be cancelled with their strategy".into()
+            ));
+        }
+
+        let mut tx = self.pool.begin().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        // Only an order still open is cancelled, so a cancel that lost a race with its final
+        // fill or another cancel changes nothing and releases no hold
+        let now = self.clock.now();
+        let cancelled = sqlx::query_as::<_, Order>(
+            r#"UPDATE orders SET status='cancelled', updated_at=$2
+               WHERE id=$1 AND status IN ('waiting', 'pending', 'partially_filled')
+               RETURNING *"#
+        )
+            .bind(order_id)
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let Some(cancelled) = cancelled else {
+            return Err(EngineError::Conflict("Order is no longer open".into()));
+        };
+
+        self.ledger.release(&mut tx, order_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        claim_trigger(&mut tx, order_id, now)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        let disarmed = cancel_dependents(&mut tx, order_id, now)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        self.orders.write().await.remove(&order_id);
+        self.triggers.disarm(&[order_id]).await;
+        self.triggers.disarm(&disarmed).await;
+        Ok(Some(cancelled))
+    }
+
+    /// Cancel every open order of the account, optionally only one symbol or side, in one
+    /// statement. Strategy legs are left to their strategy's cancel.
+    #[tracing::instrument(skip_all, fields(account_id = %auth.account_id))]
+    pub async fn cancel_all(
+        &self,
+        auth: &AuthContext,
+        req: &CancelAllRequest,
+    ) -> Result<CancelAllSummary, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CANCEL) {
+            return Err(EngineError::Auth(
+                "orders:cancel required".into()
+            ));
+        }
+
+        if matches!(req.side.as_deref(), Some(side) if side != "buy" && side != "sell") {
+            return Err(EngineError::Validation("side must be buy or sell".into()));
+        }
+
+        let _permit = self.db_limiter.acquire().await;
+
+        self.cancel_account_orders(auth.account_id, req, "cancel_all")
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    /// Mass cancel for `cancel_all` and the engine's own triggers, recording `reason` in each
+    /// order's `cancelled` event
+    pub async fn cancel_account_orders(
+        &self,
+        account_id: Uuid,
+        req: &CancelAllRequest,
+        reason: &str,
+    ) -> anyhow::Result<CancelAllSummary> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let cancelled: Vec<Order> = sqlx::query_as(
+            r#"WITH cancelled AS (
+                   UPDATE orders SET status = 'cancelled', updated_at = $4
+                   WHERE account_id = $1 AND strategy_id IS NULL
+                     AND status IN ('waiting', 'pending', 'partially_filled')
+                     AND ($2::varchar IS NULL OR symbol = $2)
+                     AND ($3::varchar IS NULL OR side = $3)
+                   RETURNING *
+               ), events AS (
+                   INSERT INTO order_events (order_id, event_type, event_data)
+                   SELECT id, 'cancelled',
+                          jsonb_build_object('reason', $5::text, 'filledQuantity', filled_quantity::text)
+                   FROM cancelled
+               )
+               SELECT * FROM cancelled"#
+        )
+            .bind(account_id)
+            .bind(req.symbol.as_deref())
+            .bind(req.side.as_deref())
+            .bind(now)
+            .bind(reason)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut disarmed = Vec::new();
+        for order in &cancelled {
+            self.ledger.release(&mut tx, order.id).await?;
+            claim_trigger(&mut tx, order.id, now).await?;
+            disarmed.extend(cancel_dependents(&mut tx, order.id, now).await?);
+        }
+        tx.commit().await?;
+
+        let summary = CancelAllSummary { disarmed, ..CancelAllSummary::new(&cancelled) };
+        self.evict_orders(&summary.evictions()).await;
+        Ok(summary)
+    }
+
+    /// Drop closed orders from the book, their triggers and pending strategies. Sharded, the rest
+    /// of a cancel's orders are with other instances; they apply it from `system.evictions`.
+    pub async fn evict_orders(&self, evictions: &Evictions) {
+        if evictions.is_empty() {
+            return;
+        }
+        let mut orders = self.orders.write().await;
+        for id in &evictions.order_ids {
+            orders.remove(id);
+        }
+        drop(orders);
+        self.triggers.disarm(&evictions.order_ids).await;
+        self.triggers.disarm(&evictions.disarmed).await;
+
+        let mut strategies = self.strategies.write().await;
+        for id in &evictions.strategy_ids {
+            strategies.remove(id);
+        }
+    }
+
+    /// Expire every open good-till-date order whose expiry has passed, releasing holds and
+    /// cancelling dependent triggers as a cancel would
+    pub async fn expire_orders(&self) -> anyhow::Result<Vec<Order>> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let expired: Vec<Order> = sqlx::query_as(
+            r#"UPDATE orders SET status = 'expired', updated_at = $1
+               WHERE expires_at <= $1 AND status IN ('waiting', 'pending', 'partially_filled')
+                 AND ($2::text[] IS NULL OR symbol = ANY($2))
+               RETURNING *"#
+        )
+            .bind(now)
+            .bind(self.owned_symbols())
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut disarmed = Vec::new();
+        for order in &expired {
+            self.ledger.release(&mut tx, order.id).await?;
+            claim_trigger(&mut tx, order.id, now).await?;
+            disarmed.extend(cancel_dependents(&mut tx, order.id, now).await?);
+
+            sqlx::query(
+                "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'expired', $2::jsonb)"
+            )
+                .bind(order.id)
+                .bind(serde_json::json!({ "expiresAt": order.expires_at }).to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        if !expired.is_empty() {
+            let ids: Vec<Uuid> = expired.iter().map(|o| o.id).collect();
+            let mut orders = self.orders.write().await;
+            for id in &ids {
+                orders.remove(id);
+            }
+            drop(orders);
+            self.triggers.disarm(&ids).await;
+            self.triggers.disarm(&disarmed).await;
+        }
+        Ok(expired)
+    }
+
+    /// Cancel every open day order placed before `session_close`, releasing holds and
+    /// cancelling dependent triggers as a cancel would
+    pub async fn cancel_day_orders(&self, session_close: DateTime<Utc>) -> anyhow::Result<Vec<Order>> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let cancelled: Vec<Order> = sqlx::query_as(
+            r#"UPDATE orders SET status = 'cancelled', updated_at = $2
+               WHERE time_in_force = 'day' AND created_at < $1
+                 AND status IN ('waiting', 'pending', 'partially_filled')
+                 AND ($3::text[] IS NULL OR symbol = ANY($3))
+               RETURNING *"#
+        )
+            .bind(session_close)
+            .bind(now)
+            .bind(self.owned_symbols())
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut disarmed = Vec::new();
+        for order in &cancelled {
+            self.ledger.release(&mut tx, order.id).await?;
+            claim_trigger(&mut tx, order.id, now).await?;
+            disarmed.extend(cancel_dependents(&mut tx, order.id, now).await?);
+
+            sqlx::query(
+                "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'cancelled', $2::jsonb)"
+            )
+                .bind(order.id)
+                .bind(serde_json::json!({
+                    "reason": "session_close",
+                    "sessionClose": session_close,
+                    "filledQuantity": order.filled_quantity,
+                }).to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        if !cancelled.is_empty() {
+            let ids: Vec<Uuid> = cancelled.iter().map(|o| o.id).collect();
+            let mut orders = self.orders.write().await;
+            for id in &ids {
+                orders.remove(id);
+            }
+            drop(orders);
+            self.triggers.disarm(&ids).await;
+            self.triggers.disarm(&disarmed).await;
+        }
+        Ok(cancelled)
+    }
+
+    /// Lower the quantity of an open order in place. Price and `created_at` are
+    /// untouched, so the order keeps its place in the queue.
+    pub async fn reduce_order(
+        &self,
+        auth: &AuthContext,
+        order_id: Uuid,
+        new_quantity: Decimal,
+    ) -> Result<Option<Order>, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CANCEL) {
+            return Err(EngineError::Auth(
+                "orders:cancel required".into()
+            ));
+        }
+
+        let _permit = self.db_limiter.acquire().await;
+
+        let order = self.repo
+            .find(order_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let order = match order {
+            Some(o) => o,
+            None => return Ok(None),
+        };
+
+        if !auth.can_access_account(&order.account_id) {
+            return Err(EngineError::Auth(
+                "Cannot modify others' orders".into()
+            ));
+        }
+
+        if !self.owns(&order.symbol) {
+            return Err(wrong_shard(&order.symbol));
+        }
+
+        if order.strategy_id.is_some() {
+            return Err(EngineError::Validation(
+                "Strategy legs cannot be reduced individually".into()
+            ));
+        }
+
+        if new_quantity >= order.quantity || new_quantity <= order.filled_quantity {
+            return Err(EngineError::Validation(format!(
+                "New quantity must be below {} and above the filled {}",
+                order.quantity, order.filled_quantity
+            )));
+        }
+
+        let mut tx = self.pool.begin().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        // Children waiting on a fill the reduced order can no longer reach would never fire
+        let (stranded,): (i64,) = sqlx::query_as(
+            r#"SELECT COUNT(*) FROM order_triggers
+               WHERE parent_order_id = $1 AND status = 'armed' AND fill_threshold > $2"#
+        )
+            .bind(order_id)
+            .bind(new_quantity)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        if stranded > 0 {
+            return Err(EngineError::Conflict(
+                "Dependent orders trigger on a fill above the new quantity".into()
+            ));
+        }
+
+        let reduced: Option<Order> = sqlx::query_as(
+            r#"UPDATE orders SET quantity = $2, updated_at = $3
+               WHERE id = $1
+                 AND status IN ('waiting', 'pending', 'partially_filled')
+                 AND quantity > $2 AND filled_quantity < $2
+               RETURNING *"#
+        )
+            .bind(order_id)
+            .bind(new_quantity)
+            .bind(self.clock.now())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let Some(reduced) = reduced else {
+            return Err(EngineError::Conflict("Order is no longer open".into()));
+        };
+
+        let released = self.ledger
+            .reduce_hold(
+                &mut tx,
+                order_id,
+                order.quantity - order.filled_quantity,
+                new_quantity - order.filled_quantity,
+            )
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'reduced', $2::jsonb)"
+        )
+            .bind(order_id)
+            .bind(serde_json::json!({
+                "previousQuantity": order.quantity,
+                "quantity": new_quantity,
+                "filledQuantity": reduced.filled_quantity,
+                "releasedHold": released,
+                "actor": auth.username,
+            }).to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        self.orders.write().await.update(&order_id, |cached| {
+            cached.quantity = reduced.quantity;
+            cached.updated_at = reduced.updated_at;
+        });
+        self.executions.notify(ExecutionReport::reduced(&reduced, reduced.updated_at));
+
+        tracing::info!(
+            order_id = %order_id,
+            from = %order.quantity,
+            to = %new_quantity,
+            "Order reduced"
+        );
+        Ok(Some(reduced))
+    }
+
+    /// Replace an open order with one at a new price or quantity, atomically: either the
+    /// order is cancelled and its replacement live with its own hold, or nothing changed.
+    /// The replacement joins the back of the queue. Returns `None` if the order does not exist.
+    pub async fn amend_order(
+        &self,
+        auth: &AuthContext,
+        req: AmendOrderRequest,
+    ) -> Result<Option<OrderResult>, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CREATE) || !auth.has_permission(permissions::ORDERS_CANCEL) {
+            return Err(EngineError::Auth(
+                "orders:create and orders:cancel required".into()
+            ));
+        }
+
+        let _permit = self.db_limiter.acquire().await;
+
+        let Some(order) = self.repo
+            .find(req.order_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+
+        if !auth.can_access_account(&order.account_id) {
+            return Err(EngineError::Auth(
+                "Cannot modify others' orders".into()
+            ));
+        }
+
+        if !self.owns(&order.symbol) {
+            return Ok(Some(OrderResult::Rejected {
+                reason: format!("{} is traded by another instance", order.symbol),
+                code: RejectReason::InvalidSymbol(WRONG_SHARD),
+            }));
+        }
+
+        if order.strategy_id.is_some() {
+            return Err(EngineError::Validation(
+                "Strategy legs cannot be amended individually".into()
+            ));
+        }
+
+        let existing = self.repo
+            .find_by_client_id(order.account_id, &req.client_order_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        if let Some(existing) = existing {
+            return Ok(Some(OrderResult::Duplicate(existing)));
+        }
+
+        let (price, remaining) = amended_terms(&order, &req).map_err(EngineError::Validation)?;
+
+        // The replacement keeps to the instrument's tick and lot grid like any new order
+        if let Some(rules) = self.instruments.get(&order.symbol).await {
+            let checked = match price {
+                Some(price) => rules.check_price("Price", price),
+                None => Ok(()),
+            }
+                .and_then(|()| rules.check_quantity(remaining + order.filled_quantity));
+            if let Err((code, reason)) = checked {
+                return Ok(Some(OrderResult::Rejected { reason, code: RejectReason::of(code) }));
+            }
+        }
+
+        // A post-only order stays post-only at its new price
+        if let (true, Some(limit), Some(last)) = (order.post_only, price, self.last_price(&order.symbol).await) {
+            if crosses(&order.side, limit, last) {
+                return Ok(Some(OrderResult::Rejected {
+                    reason: format!("Post-only order at {} would execute against the last trade at {}", limit, last),
+                    code: RejectReason::BadPrice("POST_ONLY_WOULD_TAKE"),
+                }));
+            }
+        }
+        if let (true, Some(limit)) = (order.post_only, price) {
+            if let Some(rejected) = self.post_only_takes_internally(order.account_id, &order.symbol, &order.side, limit).await {
+                return Ok(Some(rejected));
+            }
+        }
+
+        // The replacement is a new order, so what blocks new orders blocks it too
+        let paused = self.pauses
+            .check(order.account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        if let Some(reason) = paused {
+            return Ok(Some(OrderResult::Rejected { reason, code: RejectReason::Halted("TRADING_PAUSED") }));
+        }
+
+        let protected = self.mmp
+            .check(order.account_id, &order.symbol)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        if let Some(reason) = protected {
+            return Ok(Some(OrderResult::Rejected { reason, code: RejectReason::Halted("MMP_TRIGGERED") }));
+        }
+
+        let breach = self.risk
+            .check_concentration(auth, &order.symbol, &order.side, remaining)
+            .await;
+        if let Some(ConcentrationBreach { reason, action: BreachAction::Rejected }) = breach {
+            return Ok(Some(OrderResult::Rejected { reason, code: RejectReason::RiskBreach("CONCENTRATION_LIMIT") }));
+        }
+
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let cancelled: Option<Order> = sqlx::query_as(
+            r#"UPDATE orders SET status = 'cancelled', updated_at = $2
+               WHERE id = $1 AND status IN ('pending', 'partially_filled')
+               RETURNING *"#
+        )
+            .bind(order.id)
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let Some(cancelled) = cancelled else {
+            return Err(EngineError::Conflict("Order is no longer open".into()));
+        };
+
+        // Triggers watch the order's own fills, which the replacement does not carry over
+        let (dependents,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM order_triggers WHERE parent_order_id = $1 AND status = 'armed'"
+        )
+            .bind(order.id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        if dependents > 0 {
+            return Err(EngineError::Conflict(
+                "Orders with dependent triggers cannot be amended".into()
+            ));
+        }
+
+        self.ledger.release(&mut tx, order.id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let inserted = sqlx::query_as::<_, Order>(
+            r#"INSERT INTO orders (id, account_id, client_order_id, symbol, side, order_type,
+                                   quantity, price, metadata, time_in_force, stop_price,
+                                   trail_amount, trail_percent, expires_at, filled_quantity,
+                                   status, replaces_order_id, group_id, display_quantity, post_only, reduce_only,
+                                   created_at, updated_at)
+               SELECT $2, account_id, $3, symbol, side, order_type,
+                      $4, $5, metadata, time_in_force, stop_price,
+                      trail_amount, trail_percent, expires_at, 0,
+                      'pending', id, group_id, LEAST(display_quantity, $4), post_only, reduce_only, $6, $6
+               FROM orders WHERE id = $1
+               RETURNING *"#
+        )
+            .bind(order.id)
+            .bind(self.ids.next_id())
+            .bind(&req.client_order_id)
+            .bind(remaining)
+            .bind(price)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await;
+        let replacement = match inserted {
+            Ok(replacement) => replacement,
+            // A concurrent submit or amend with the same client order id committed first;
+            // the original stays open as the rollback leaves it
+            Err(e) if is_client_id_conflict(&e) => {
+                drop(tx);
+                return self.repo
+                    .find_by_client_id(order.account_id, &req.client_order_id)
+                    .await
+                    .map_err(|e| EngineError::Storage(e.to_string()))?
+                    .map(|existing| Some(OrderResult::Duplicate(existing)))
+                    .ok_or_else(|| EngineError::Conflict(format!("clientOrderId {} is taken", req.client_order_id)));
+            }
+            Err(e) => return Err(EngineError::Storage(e.to_string())),
+        };
+
+        let reference_price = self.last_price(&order.symbol).await;
+        match self.ledger.reserve(&mut tx, &replacement, reference_price).await {
+            Ok(()) => {}
+            Err(LedgerError::Database(e)) => return Err(EngineError::Storage(e.to_string())),
+            Err(e) => {
+                return Ok(Some(OrderResult::Rejected {
+                    code: reject_code(&e),
+                    reason: e.to_string(),
+                }));
+            }
+        }
+
+        if let Some(breach) = &breach {
+            record_breach(&mut tx, replacement.id, breach)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+        }
+
+        sqlx::query(
+            "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'replaced', $2::jsonb)"
+        )
+            .bind(order.id)
+            .bind(serde_json::json!({
+                "replacedBy": replacement.id,
+                "clientOrderId": replacement.client_order_id,
+                "price": replacement.price,
+                "quantity": replacement.quantity,
+                "filledQuantity": cancelled.filled_quantity,
+                "actor": auth.username,
+            }).to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        {
+            let mut orders = self.orders.write().await;
+            orders.remove(&order.id);
+            orders.insert(replacement.clone());
+        }
+
+        tracing::info!(
+            order_id = %order.id,
+            replaced_by = %replacement.id,
+            "Order amended"
+        );
+        Ok(Some(OrderResult::Accepted(replacement)))
+    }
+
+    // =====================================================
+    // MULTI-LEG SUBMIT / CANCEL
+    // =====================================================
+
+    /// Accept a strategy only if every leg's hold can be reserved
+    pub async fn submit_strategy(
         &self,
         auth: &AuthContext,
-        req: NewOrderRequest,
-    ) -> Result<OrderResult, AuthError> {
+        req: NewStrategyRequest,
+    ) -> Result<StrategyResult, EngineError> {
         if !auth.has_permission(permissions::ORDERS_CREATE) {
-            return Err(AuthError::InsufficientPermissions(
+            return Err(EngineError::Auth(
                 "orders:create required".into()
             ));
         }
 
-        let existing: Option<Order> = sqlx::query_as(
-            "SELECT * FROM orders WHERE account_id = $1 AND client_order_id = $2"
-        )
-            .bind(auth.account_id)
-            .bind(&req.client_order_id)
-            .fetch_optional(&self.pool)
+        validate_strategy(&req).map_err(EngineError::Validation)?;
+
+        // Routed by its first leg, a strategy fills atomically only where every leg trades
+        if let Some(leg) = req.legs.iter().find(|leg| !self.owns(&leg.symbol)) {
+            return Ok(StrategyResult::Rejected {
+                reason: format!("{} is traded by another instance", leg.symbol),
+                code: RejectReason::InvalidSymbol(WRONG_SHARD),
+            });
+        }
+
+        let _permit = self.db_limiter.acquire().await;
+
+        let existing = self.repo
+            .find_strategy(auth.account_id, &req.client_strategy_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        if let Some(existing) = existing {
+            return Ok(StrategyResult::Duplicate(existing));
+        }
+
+        let paused = self.pauses
+            .check(auth.account_id)
             .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        if let Some(reason) = paused {
+            return Ok(StrategyResult::Rejected { reason, code: RejectReason::Halted("TRADING_PAUSED") });
+        }
+
+        for leg in &req.legs {
+            let protected = self.mmp
+                .check(auth.account_id, &leg.symbol)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+            if let Some(reason) = protected {
+                return Ok(StrategyResult::Rejected { reason, code: RejectReason::Halted("MMP_TRIGGERED") });
+            }
+        }
 
-        if let Some(order) = existing {
-            return Ok(OrderResult::Duplicate(order));
+        let tag = strategy_tag(req.metadata.as_ref());
+        if let Some(tag) = tag {
+            let halted = self.strategy_limits
+                .check_halted(auth.account_id, tag)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+            if let Some(reason) = halted {
+                return Ok(StrategyResult::Rejected { reason, code: RejectReason::Halted("STRATEGY_HALTED") });
+            }
         }
 
-        let id = Uuid::new_v4();
-        let now = Utc::now();
+        let mut breaches = Vec::with_capacity(req.legs.len());
+        for leg in &req.legs {
+            let breach = self.risk
+                .check_concentration(auth, &leg.symbol, &leg.side, leg.ratio * req.quantity)
+                .await;
+            if let Some(ConcentrationBreach { reason, action: BreachAction::Rejected }) = breach {
+                return Ok(StrategyResult::Rejected { reason, code: RejectReason::RiskBreach("CONCENTRATION_LIMIT") });
+            }
+            breaches.push(breach);
+        }
 
-        let order: Order = sqlx::query_as(
-            r#"INSERT INTO orders (id, account_id, client_order_id, symbol, side,
-                                   order_type, quantity, price,
-                                   filled_quantity, status, created_at, updated_at)
-               VALUES ($1,$2,$3,$4,$5,$6,$7,$8,0,'pending',$9,$9)
-               RETURNING *"#
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let mut strategy: Strategy = sqlx::query_as(
+            r#"INSERT INTO order_strategies (id, account_id, client_strategy_id, quantity, net_price,
+                                             created_at, updated_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $6)
+               RETURNING id, account_id, client_strategy_id, quantity, net_price, status, created_at"#
         )
-            .bind(id)
+            .bind(self.ids.next_id())
             .bind(auth.account_id)
-            .bind(&req.client_order_id)
-            .bind(&req.symbol)
-            .bind(&req.side)
-            .bind(&req.order_type)
+            .bind(&req.client_strategy_id)
             .bind(req.quantity)
-            .bind(req.price)
+            .bind(req.net_price)
             .bind(now)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
 
-        self.orders.write().await.insert(order.id, order.clone());
-        Ok(OrderResult::Accepted(order))
+        for (i, leg) in req.legs.iter().enumerate() {
+            let order = insert_order(
+                &mut tx,
+                NewOrderRow {
+                    id: self.ids.next_id(),
+                    account_id: auth.account_id,
+                    client_order_id: &format!("{}/leg{}", req.client_strategy_id, i + 1),
+                    symbol: &leg.symbol,
+                    side: &leg.side,
+                    order_type: if leg.price.is_some() { "limit" } else { "market" },
+                    time_in_force: None,
+                    stated_terms: None,
+                    quantity: leg.ratio * req.quantity,
+                    price: leg.price,
+                    stop_price: None,
+                    trail_amount: None,
+                    trail_percent: None,
+                    expires_at: None,
+                    status: "pending",
+                    strategy_id: Some(strategy.id),
+                    parent_order_id: None,
+                    display_quantity: None,
+                    post_only: false,
+                    reduce_only: false,
+                    metadata: req.metadata.as_ref(),
+                    now,
+                },
+            )
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+            if let Some(breach) = &breaches[i] {
+                record_breach(&mut tx, order.id, breach)
+                    .await
+                    .map_err(|e| EngineError::Storage(e.to_string()))?;
+            }
+
+            let reference_price = self.last_price(&leg.symbol).await;
+            if let Some(tag) = tag {
+                let notional = order.quantity * order.price.or(reference_price).unwrap_or_default();
+                let exceeded = self.strategy_limits
+                    .consume(&mut tx, auth.account_id, tag, notional)
+                    .await
+                    .map_err(|e| EngineError::Storage(e.to_string()))?;
+                if let Some(reason) = exceeded {
+                    return Ok(StrategyResult::Rejected { reason, code: RejectReason::RiskBreach("STRATEGY_LIMIT") });
+                }
+            }
+
+            match self.ledger.reserve(&mut tx, &order, reference_price).await {
+                Ok(()) => {}
+                Err(LedgerError::Database(e)) => return Err(EngineError::Storage(e.to_string())),
+                Err(e) => {
+                    return Ok(StrategyResult::Rejected {
+                        code: reject_code(&e),
+                        reason: format!("Leg {}: {}", leg.symbol, e),
+                    });
+                }
+            }
+
+            strategy.legs.push(order);
+        }
+
+        tx.commit().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        self.strategies.write().await.insert(strategy.id, strategy.clone());
+        Ok(StrategyResult::Accepted(strategy))
     }
 
-    pub async fn cancel_order(
+    pub async fn cancel_strategy(
         &self,
         auth: &AuthContext,
-        order_id: Uuid,
-    ) -> Result<Option<Order>, AuthError> {
+        strategy_id: Uuid,
+    ) -> Result<Option<Strategy>, EngineError> {
         if !auth.has_permission(permissions::ORDERS_CANCEL) {
-            return Err(AuthError::InsufficientPermissions(
+            return Err(EngineError::Auth(
                 "orders:cancel required".into()
             ));
         }
 
-        let order: Option<Order> = sqlx::query_as(
-            "SELECT * FROM orders WHERE id = $1"
+        let _permit = self.db_limiter.acquire().await;
+
+        let account_id: Option<(Uuid,)> = sqlx::query_as(
+            "SELECT account_id FROM order_strategies WHERE id = $1"
         )
-            .bind(order_id)
+            .bind(strategy_id)
             .fetch_optional(&self.pool)
             .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
 
-        let order = match order {
-            Some(o) => o,
-            None => return Ok(None),
+        let Some((account_id,)) = account_id else {
+            return Ok(None);
         };
 
-        if !auth.can_access_account(&order.account_id) {
-            return Err(AuthError::InsufficientPermissions(
+        if !auth.can_access_account(&account_id) {
+            return Err(EngineError::Auth(
                 "Cannot cancel others' orders".into()
             ));
         }
 
-        let cancelled: Order = sqlx::query_as(
-            r#"UPDATE orders SET status='cancelled', updated_at=NOW()
-               WHERE id=$1 RETURNING *"#
+        let mut tx = self.pool.begin().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let now = self.clock.now();
+        let cancelled: Option<Strategy> = sqlx::query_as(
+            r#"UPDATE order_strategies SET status = 'cancelled', updated_at = $2
+               WHERE id = $1 AND status = 'pending'
+               RETURNING id, account_id, client_strategy_id, quantity, net_price, status, created_at"#
         )
-            .bind(order_id)
-            .fetch_one(&self.pool)
+            .bind(strategy_id)
+            .bind(now)
+            .fetch_optional(&mut *tx)
             .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
 
-        self.orders.write().await.remove(&order_id);
-        Ok(Some(cancelled))
+        let Some(mut strategy) = cancelled else {
+            return Err(EngineError::Conflict("Strategy is no longer pending".into()));
+        };
+
+        strategy.legs = sqlx::query_as(
+            r#"UPDATE orders SET status = 'cancelled', updated_at = $2
+               WHERE strategy_id = $1
+               RETURNING *"#
+        )
+            .bind(strategy_id)
+            .bind(now)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        for leg in &strategy.legs {
+            self.ledger.release(&mut tx, leg.id)
+                .await
+                .map_err(|e| EngineError::Storage(e.to_string()))?;
+        }
+
+        tx.commit().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        self.strategies.write().await.remove(&strategy_id);
+        Ok(Some(strategy))
+    }
+}
+
+// =====================================================
+// ORDER INSERT
+// =====================================================
+
+struct NewOrderRow<'a> {
+    id: Uuid,
+    account_id: Uuid,
+    client_order_id: &'a str,
+    symbol: &'a str,
+    side: &'a str,
+    order_type: &'a str,
+    /// The column default (gtc) when `None`
+    time_in_force: Option<&'a str>,
+    /// What the request stated beyond the columns, for comparing a resubmission with
+    stated_terms: Option<&'a StatedTerms>,
+    quantity: Decimal,
+    price: Option<Decimal>,
+    stop_price: Option<Decimal>,
+    trail_amount: Option<Decimal>,
+    trail_percent: Option<Decimal>,
+    expires_at: Option<DateTime<Utc>>,
+    status: &'a str,
+    strategy_id: Option<Uuid>,
+    parent_order_id: Option<Uuid>,
+    display_quantity: Option<Decimal>,
+    post_only: bool,
+    reduce_only: bool,
+    metadata: Option<&'a serde_json::Value>,
+    now: DateTime<Utc>,
+}
+
+/// Move an order's fill forward by `quantity` at `price`, against the fill it last saw, so
+/// a fill raced by another one, a cancel or a redelivered tick changes nothing (`None`).
+/// An iceberg whose slice just filled shows the next one from its hidden remainder.
+async fn record_order_fill(
+    conn: &mut PgConnection,
+    order: &Order,
+    quantity: Decimal,
+    price: Decimal,
+    now: DateTime<Utc>,
+) -> Result<Option<Order>, sqlx::Error> {
+    let filled_quantity = order.filled_quantity + quantity;
+    let completes = filled_quantity >= order.quantity;
+
+    let updated: Option<Order> = sqlx::query_as(
+        r#"UPDATE orders
+           SET status = $2,
+               filled_quantity = $3,
+               avg_fill_price = $4,
+               updated_at = $5
+           WHERE id = $1 AND filled_quantity = $6 AND status IN ('pending', 'partially_filled')
+           RETURNING *"#
+    )
+        .bind(order.id)
+        .bind(if completes { "filled" } else { "partially_filled" })
+        .bind(filled_quantity)
+        .bind(average_fill_price(order, quantity, price))
+        .bind(now)
+        .bind(order.filled_quantity)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    if let Some(updated) = &updated {
+        record_iceberg_slice(&mut *conn, updated).await?;
+    }
+    Ok(updated)
+}
+
+/// `record_order_fill` for a tick's fills in one statement, each given with its price and
+/// quantity. Returns the orders that moved; the others changed since they matched.
+async fn record_order_fills(
+    conn: &mut PgConnection,
+    fills: &[(Order, Decimal, Decimal)],
+    now: DateTime<Utc>,
+) -> Result<Vec<Order>, sqlx::Error> {
+    let mut ids = Vec::with_capacity(fills.len());
+    let mut statuses = Vec::with_capacity(fills.len());
+    let mut filled_quantities = Vec::with_capacity(fills.len());
+    let mut avg_fill_prices = Vec::with_capacity(fills.len());
+    let mut seen_quantities = Vec::with_capacity(fills.len());
+    for (order, price, quantity) in fills {
+        let filled_quantity = order.filled_quantity + quantity;
+        ids.push(order.id);
+        statuses.push(if filled_quantity >= order.quantity { "filled" } else { "partially_filled" });
+        filled_quantities.push(filled_quantity);
+        avg_fill_prices.push(average_fill_price(order, *quantity, *price));
+        seen_quantities.push(order.filled_quantity);
+    }
+
+    let updated: Vec<Order> = sqlx::query_as(
+        r#"UPDATE orders o
+           SET status = t.status,
+               filled_quantity = t.filled_quantity,
+               avg_fill_price = t.avg_fill_price,
+               updated_at = $6
+           FROM UNNEST($1::uuid[], $2::text[], $3::numeric[], $4::numeric[], $5::numeric[])
+               AS t(id, status, filled_quantity, avg_fill_price, seen_quantity)
+           WHERE o.id = t.id AND o.filled_quantity = t.seen_quantity
+             AND o.status IN ('pending', 'partially_filled')
+           RETURNING o.*"#
+    )
+        .bind(&ids)
+        .bind(&statuses)
+        .bind(&filled_quantities)
+        .bind(&avg_fill_prices)
+        .bind(now)
+        .bind(&seen_quantities)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    for order in &updated {
+        record_iceberg_slice(&mut *conn, order).await?;
+    }
+    Ok(updated)
+}
+
+/// Show an iceberg's next slice from its hidden remainder once a fill used up the last one
+async fn record_iceberg_slice(conn: &mut PgConnection, updated: &Order) -> Result<(), sqlx::Error> {
+    let Some(display) = updated.display_quantity.filter(|_| updated.status != "filled") else {
+        return Ok(());
+    };
+    if (updated.filled_quantity % display).is_zero() {
+        let remaining = updated.quantity - updated.filled_quantity;
+        sqlx::query(
+            "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'iceberg_replenished', $2::jsonb)"
+        )
+            .bind(updated.id)
+            .bind(serde_json::json!({
+                "displayQuantity": display.min(remaining),
+                "hiddenQuantity": (remaining - display).max(Decimal::ZERO),
+            }).to_string())
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn insert_order(conn: &mut PgConnection, row: NewOrderRow<'_>) -> Result<Order, sqlx::Error> {
+    let insert = sqlx::query_as(
+        r#"INSERT INTO orders (id, account_id, client_order_id, symbol, side,
+                               order_type, quantity, price, strategy_id, metadata,
+                               time_in_force, stop_price, trail_amount, trail_percent, expires_at,
+                               parent_order_id, display_quantity, post_only, reduce_only, stated_terms,
+                               filled_quantity, status, created_at, updated_at)
+           VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,COALESCE($13, 'gtc'),$14,$15,$16,$17,$18,$19,$20,$21,$22,0,$11,$12,$12)
+           RETURNING *"#
+    )
+        .bind(row.id)
+        .bind(row.account_id)
+        .bind(row.client_order_id)
+        .bind(row.symbol)
+        .bind(row.side)
+        .bind(row.order_type)
+        .bind(row.quantity)
+        .bind(row.price)
+        .bind(row.strategy_id)
+        .bind(row.metadata.cloned().map(EncryptedJson))
+        .bind(row.status)
+        .bind(row.now)
+        .bind(row.time_in_force)
+        .bind(row.stop_price)
+        .bind(row.trail_amount)
+        .bind(row.trail_percent)
+        .bind(row.expires_at)
+        .bind(row.parent_order_id)
+        .bind(row.display_quantity)
+        .bind(row.post_only)
+        .bind(row.reduce_only)
+        .bind(row.stated_terms.filter(|terms| !terms.is_empty()).map(sqlx::types::Json))
+        .fetch_one(conn);
+    slow_query("orders.insert", insert).await
+}
+
+/// The time in force and stored request terms of an order, to compare a resubmission with
+async fn stated_terms(pool: &PgPool, order_id: Uuid) -> Result<StatedTerms, sqlx::Error> {
+    let (time_in_force, stored): (String, Option<sqlx::types::Json<StatedTerms>>) =
+        sqlx::query_as("SELECT time_in_force, stated_terms FROM orders WHERE id = $1")
+            .bind(order_id)
+            .fetch_one(pool)
+            .await?;
+    let terms = stored.map(|json| json.0).unwrap_or_default();
+    Ok(StatedTerms { time_in_force: Some(time_in_force), ..terms })
+}
+
+/// Group a new order with an open sibling one-cancels-other. The sibling must be an open,
+/// ungrouped order of the same account and symbol outside any strategy.
+async fn link_oco(
+    conn: &mut PgConnection,
+    order: &Order,
+    sibling_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Result<Uuid, String>, sqlx::Error> {
+    let sibling: Option<Order> = sqlx::query_as("SELECT * FROM orders WHERE id = $1 AND account_id = $2 FOR UPDATE")
+        .bind(sibling_id)
+        .bind(order.account_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    let Some(sibling) = sibling else {
+        return Ok(Err("ocoWith order not found".into()));
+    };
+    if sibling.symbol != order.symbol {
+        return Ok(Err("ocoWith order must be on the same symbol".into()));
+    }
+    if !matches!(sibling.status.as_str(), "waiting" | "pending") {
+        return Ok(Err("ocoWith order must be open and unfilled".into()));
+    }
+    if sibling.strategy_id.is_some() {
+        return Ok(Err("Strategy legs cannot be grouped".into()));
+    }
+    if sibling.group_id.is_some() {
+        return Ok(Err("ocoWith order is already grouped".into()));
+    }
+
+    create_oco_group(conn, order.account_id, &[order.id, sibling.id], now).await.map(Ok)
+}
+
+async fn create_oco_group(
+    conn: &mut PgConnection,
+    account_id: Uuid,
+    members: &[Uuid],
+    now: DateTime<Utc>,
+) -> Result<Uuid, sqlx::Error> {
+    let (group_id,): (Uuid,) = sqlx::query_as(
+        "INSERT INTO order_groups (account_id, group_type, created_at) VALUES ($1, 'oco', $2) RETURNING id"
+    )
+        .bind(account_id)
+        .bind(now)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    sqlx::query("UPDATE orders SET group_id = $1, updated_at = $3 WHERE id = ANY($2)")
+        .bind(group_id)
+        .bind(members)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+    Ok(group_id)
+}
+
+/// Keep a concentration breach an order was accepted with in its history
+async fn record_breach(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    breach: &ConcentrationBreach,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO order_events (order_id, event_type, event_data) VALUES ($1, 'concentration_flagged', $2::jsonb)"
+    )
+        .bind(order_id)
+        .bind(serde_json::json!({
+            "reason": breach.reason,
+            "action": breach.action.as_str(),
+        }).to_string())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+// =====================================================
+// TRIGGER PERSISTENCE
+// =====================================================
+
+/// Mark an order's armed trigger as fired; false if it was already fired or cancelled
+async fn claim_trigger(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let claimed = sqlx::query(
+        r#"UPDATE order_triggers SET status = 'fired', fired_at = $2
+           WHERE order_id = $1 AND status = 'armed'"#
+    )
+        .bind(order_id)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    Ok(claimed > 0)
+}
+
+/// Disarm triggers that depend on an order that will never fill. Waiting orders that
+/// could only be activated by it are cancelled, along with their own dependents.
+async fn cancel_dependents(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let mut disarmed = Vec::new();
+    let mut parents = vec![order_id];
+
+    while let Some(parent) = parents.pop() {
+        let children: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"UPDATE order_triggers SET status = 'cancelled'
+               WHERE parent_order_id = $1 AND status = 'armed'
+               RETURNING order_id, action"#
+        )
+            .bind(parent)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        for (child, action) in children {
+            disarmed.push(child);
+            if action == TriggerAction::Activate.as_str() {
+                sqlx::query(
+                    r#"UPDATE orders SET status = 'cancelled', updated_at = $2
+                       WHERE id = $1 AND status = 'waiting'"#
+                )
+                    .bind(child)
+                    .bind(now)
+                    .execute(&mut *conn)
+                    .await?;
+                parents.push(child);
+            }
+        }
     }
+
+    Ok(disarmed)
 }
@@ -1,56 +1,154 @@
 //! Position Management with Weighted Average Price Calculation
 //! Phase 1: Persistence + Phase 2: Auth checks
 
-use crate::auth::{AuthContext, AuthError, permissions};
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+use crate::engine::exposure;
+use crate::engine::fill_topic::FillTopic;
+use crate::engine::trading_pauses::record_loss;
+use crate::observability::business;
+use crate::observability::slow_ops::slow_query;
+use crate::storage::ReadPool;
+
+pub use enthropic_domain::fill::{Fill, FillKey};
+pub use enthropic_domain::position::Position;
+use enthropic_domain::consistency::{compare_positions, Mismatch};
+use enthropic_domain::position::{
+    apply_fill, apply_split, calculate_new_position, calculate_new_position_from_zero, check_position,
+};
+
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgPool};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
-
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Position {
-    pub account_id: Uuid,
-    pub symbol: String,
-    pub net_quantity: Decimal,
-    pub avg_price: Decimal,
-    pub realized_pnl: Decimal,
-    pub unrealized_pnl: Decimal,
-    pub cost_basis: Decimal,
-    pub updated_at: DateTime<Utc>,
+use chrono::Utc;
+
+/// Record the fill before its trade is inserted, in the same transaction.
+/// False when the key is already taken: the fill was redelivered or replayed.
+pub async fn claim_fill(conn: &mut PgConnection, key: FillKey, trade_id: Uuid) -> Result<bool, sqlx::Error> {
+    let claimed = sqlx::query(
+        r#"INSERT INTO fill_dedup (order_id, filled_before, trade_id)
+           VALUES ($1, $2, $3)
+           ON CONFLICT (order_id, filled_before) DO NOTHING"#
+    )
+        .bind(key.order_id)
+        .bind(key.filled_before)
+        .bind(trade_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    Ok(claimed == 1)
+}
+
+/// `claim_fill` for many fills in one statement; returns how many were claimed, fewer than
+/// given when some key is already taken
+pub async fn claim_fills(conn: &mut PgConnection, keys: &[FillKey], trade_ids: &[Uuid]) -> Result<usize, sqlx::Error> {
+    let (order_ids, filled_before): (Vec<Uuid>, Vec<Decimal>) =
+        keys.iter().map(|key| (key.order_id, key.filled_before)).unzip();
+    let claimed = sqlx::query(
+        r#"INSERT INTO fill_dedup (order_id, filled_before, trade_id)
+           SELECT * FROM UNNEST($1::uuid[], $2::numeric[], $3::uuid[])
+           ON CONFLICT (order_id, filled_before) DO NOTHING"#
+    )
+        .bind(&order_ids)
+        .bind(&filled_before)
+        .bind(trade_ids)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    Ok(claimed as usize)
+}
+
+/// Mark a claimed fill as applied to its position; false if it already was
+async fn mark_applied(conn: &mut PgConnection, key: FillKey) -> Result<bool, sqlx::Error> {
+    let marked = sqlx::query(
+        r#"UPDATE fill_dedup SET position_applied_at = NOW()
+           WHERE order_id = $1 AND filled_before = $2 AND position_applied_at IS NULL"#
+    )
+        .bind(key.order_id)
+        .bind(key.filled_before)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    Ok(marked == 1)
 }
 
-#[derive(Debug, Clone)]
-pub struct Fill {
-    pub account_id: Uuid,
-    pub symbol: String,
-    pub side: String,
-    pub quantity: Decimal,
-    pub price: Decimal,
+/// Every fill, dust close and split adjustment of a position since its account's last
+/// sandbox reset. A position's `sequence` equals its number of rows here.
+const POSITION_MOVEMENTS: &str = r#"
+    SELECT m.* FROM (
+        SELECT account_id, symbol, executed_at AS at, 'fill' AS kind,
+               side, quantity, price, NULL::numeric AS ratio
+        FROM trades
+        UNION ALL
+        SELECT account_id, symbol, swept_at, 'fill',
+               side, quantity, price, NULL
+        FROM dust_sweeps
+        WHERE target = 'position'
+        UNION ALL
+        SELECT a.account_id, c.symbol, a.created_at, 'split',
+               NULL, NULL, NULL, c.split_ratio
+        FROM corporate_action_adjustments a
+        JOIN corporate_actions c ON c.id = a.action_id
+        WHERE a.target = 'position'
+    ) m
+    LEFT JOIN sandbox_accounts s ON s.account_id = m.account_id
+    WHERE s.account_id IS NULL OR m.at > s.last_reset_at"#;
+
+#[derive(Debug, FromRow)]
+struct Movement {
+    kind: String,
+    side: Option<String>,
+    quantity: Option<Decimal>,
+    price: Option<Decimal>,
+    ratio: Option<Decimal>,
 }
 
 pub struct PositionKeeper {
     pool: PgPool,
+    reads: ReadPool,
     positions: Arc<RwLock<HashMap<(Uuid, String), Position>>>,
+    /// Sum of long position quantities per symbol, kept in step with `positions`
+    open_interest: Arc<RwLock<HashMap<String, Decimal>>>,
+    /// Committed fills, for in-process post-trade consumers
+    fills: FillTopic,
+}
+
+/// Quantity a position adds to its symbol's open interest
+fn long_quantity(position: &Position) -> Decimal {
+    position.net_quantity.max(Decimal::ZERO)
 }
 
 impl PositionKeeper {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: PgPool, reads: ReadPool) -> Self {
         Self {
             pool,
+            reads,
             positions: Arc::new(RwLock::new(HashMap::new())),
+            open_interest: Arc::new(RwLock::new(HashMap::new())),
+            fills: FillTopic::new(),
         }
     }
 
-    /// Load positions from database on startup
+    /// Topic every committed fill is published on
+    pub fn fill_topic(&self) -> &FillTopic {
+        &self.fills
+    }
+
+    /// Load positions from database on startup, rebuilding any whose sequence
+    /// disagrees with the fills recorded for it
     pub async fn load_positions(&self) -> anyhow::Result<usize> {
+        let rebuilt = self.repair_positions().await?;
+        if rebuilt > 0 {
+            tracing::warn!(rebuilt, "Rebuilt positions from trades after sequence mismatch");
+        }
+
         let rows: Vec<Position> = sqlx::query_as(
             r#"SELECT account_id, symbol, net_quantity, avg_price,
-                      realized_pnl, unrealized_pnl, cost_basis, updated_at
+                      realized_pnl, unrealized_pnl, cost_basis, sequence, updated_at
                FROM positions WHERE net_quantity != 0"#
         )
             .fetch_all(&self.pool)
@@ -61,40 +159,159 @@ impl PositionKeeper {
         for pos in rows {
             positions.insert((pos.account_id, pos.symbol.clone()), pos);
         }
+        self.recount_open_interest(&positions).await;
         tracing::info!("Loaded {} positions from database", count);
         Ok(count)
     }
 
-    /// Apply a fill to update position (weighted average calculation)
-    pub async fn apply_fill(&self, fill: &Fill) -> anyhow::Result<Position> {
-        let key = (fill.account_id, fill.symbol.clone());
+    /// Differences between the cached positions and the open position rows on the primary,
+    /// empty when they agree. Only meaningful with no fill in flight, as between the operations
+    /// of a test, and on a single instance: fills of another move the rows but not this cache.
+    pub async fn check_consistency(&self) -> anyhow::Result<Vec<Mismatch>> {
+        let rows: Vec<Position> = sqlx::query_as(
+            r#"SELECT account_id, symbol, net_quantity, avg_price,
+                      realized_pnl, unrealized_pnl, cost_basis, sequence, updated_at
+               FROM positions WHERE net_quantity != 0"#
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        let positions = self.positions.read().await;
+        Ok(compare_positions(positions.values(), &rows))
+    }
+
+    /// Drop all cached positions of an account (after positions were reset in the DB)
+    pub async fn evict_account(&self, account_id: Uuid) {
+        let mut positions = self.positions.write().await;
+        positions.retain(|(id, _), _| *id != account_id);
+        self.recount_open_interest(&positions).await;
+    }
 
-        // Get current position
-        let current = {
-            let positions = self.positions.read().await;
-            positions.get(&key).cloned()
-        };
+    /// Reload cached positions of a symbol after they were adjusted in the DB (corporate actions)
+    pub async fn reload_symbol(&self, symbol: &str) -> anyhow::Result<usize> {
+        let rows: Vec<Position> = sqlx::query_as(
+            r#"SELECT account_id, symbol, net_quantity, avg_price,
+                      realized_pnl, unrealized_pnl, cost_basis, sequence, updated_at
+               FROM positions WHERE symbol = $1 AND net_quantity != 0"#
+        )
+            .bind(symbol)
+            .fetch_all(&self.pool)
+            .await?;
 
+        let count = rows.len();
+        let mut positions = self.positions.write().await;
+        positions.retain(|(_, s), _| s != symbol);
+        for pos in rows {
+            positions.insert((pos.account_id, pos.symbol.clone()), pos);
+        }
+        self.recount_open_interest(&positions).await;
+        Ok(count)
+    }
+
+    /// Apply fills to their positions (weighted average calculation) inside the caller's
+    /// transaction; call `commit_fills` once it commits.
+    /// Fills must be for distinct (account, symbol) pairs. Keyed fills already applied
+    /// are skipped, so only positions that moved are returned.
+    pub async fn apply_fills_in(
+        &self,
+        conn: &mut PgConnection,
+        fills: &[Fill],
+    ) -> anyhow::Result<Vec<Position>> {
+        let mut updated = Vec::with_capacity(fills.len());
+
+        for fill in fills {
+            if let Some(fill_key) = fill.key {
+                if !mark_applied(conn, fill_key).await? {
+                    tracing::warn!(
+                        order_id = %fill_key.order_id,
+                        filled_before = %fill_key.filled_before,
+                        "Fill already applied to its position, skipping"
+                    );
+                    continue;
+                }
+            }
+
+            let key = (fill.account_id, fill.symbol.clone());
+
+            // Get current position; flat positions are not cached but keep their row
+            let cached = {
+                let positions = self.positions.read().await;
+                positions.get(&key).cloned()
+            };
+            let mut current = match cached {
+                Some(pos) => Some(pos),
+                None => fetch_position(conn, fill.account_id, &fill.symbol).await?,
+            };
+
+            let mut attempts = 0;
+            let position = loop {
+                if let Some(position) = self.upsert_fill(conn, current.as_ref(), fill).await? {
+                    break position;
+                }
+
+                // The row moved past the sequence this calculation started from
+                attempts += 1;
+                let fresh = fetch_position(conn, fill.account_id, &fill.symbol).await?;
+                tracing::warn!(
+                    account_id = %fill.account_id,
+                    symbol = %fill.symbol,
+                    cached_sequence = current.as_ref().map(|p| p.sequence).unwrap_or(0),
+                    stored_sequence = fresh.as_ref().map(|p| p.sequence).unwrap_or(0),
+                    "Position cache behind database, recomputing fill"
+                );
+                if attempts >= 3 {
+                    anyhow::bail!("position {}/{} kept changing underneath fill", fill.account_id, fill.symbol);
+                }
+                current = fresh;
+            };
+
+            let held_before = current.as_ref().map(|p| p.net_quantity).unwrap_or_default();
+            exposure::record_change(conn, fill.account_id, &fill.symbol, held_before, position.net_quantity, position.updated_at)
+                .await?;
+
+            // Live fills that lower realized P&L count towards self-imposed loss limits
+            let realized_before = current.as_ref().map(|p| p.realized_pnl).unwrap_or_default();
+            if fill.key.is_some() && position.realized_pnl < realized_before {
+                record_loss(conn, fill.account_id).await?;
+            }
+
+            updated.push(position);
+        }
+
+        Ok(updated)
+    }
+
+    /// Write the position after `fill`, only if the stored sequence still matches `current`.
+    /// Returns `None` when another writer got there first.
+    async fn upsert_fill(
+        &self,
+        conn: &mut PgConnection,
+        current: Option<&Position>,
+        fill: &Fill,
+    ) -> Result<Option<Position>, sqlx::Error> {
         let (new_quantity, new_avg_price, realized_pnl) = match current {
-            Some(ref pos) => self.calculate_new_position(pos, fill),
-            None => self.calculate_new_position_from_zero(fill),
+            Some(pos) => calculate_new_position(pos, fill),
+            None => calculate_new_position_from_zero(fill),
         };
 
         let cost_basis = new_quantity.abs() * new_avg_price;
+        let expected_sequence = current.map(|p| p.sequence).unwrap_or(0);
 
         // Upsert to database atomically
-        let position: Position = sqlx::query_as(
+        let upsert = sqlx::query_as(
             r#"INSERT INTO positions (account_id, symbol, net_quantity, avg_price,
-                                      realized_pnl, cost_basis, unrealized_pnl, updated_at)
-               VALUES ($1, $2, $3, $4, $5, $6, 0, NOW())
+                                      realized_pnl, cost_basis, unrealized_pnl, sequence, updated_at)
+               VALUES ($1, $2, $3, $4, $5, $6, 0, 1, NOW())
                ON CONFLICT (account_id, symbol) DO UPDATE SET
                    net_quantity = $3,
                    avg_price = $4,
                    realized_pnl = positions.realized_pnl + $5,
                    cost_basis = $6,
+                   sequence = positions.sequence + 1,
                    updated_at = NOW()
+               WHERE positions.sequence = $7
                RETURNING account_id, symbol, net_quantity, avg_price,
-                         realized_pnl, unrealized_pnl, cost_basis, updated_at"#
+                         realized_pnl, unrealized_pnl, cost_basis, sequence, updated_at"#
         )
             .bind(fill.account_id)
             .bind(&fill.symbol)
@@ -102,97 +319,306 @@ impl PositionKeeper {
             .bind(new_avg_price)
             .bind(realized_pnl)
             .bind(cost_basis)
-            .fetch_one(&self.pool)
-            .await?;
+            .bind(expected_sequence)
+            .fetch_optional(&mut *conn);
+        slow_query("positions.upsert", upsert).await
+    }
 
-        // Update cache
-        {
-            let mut positions = self.positions.write().await;
-            if new_quantity == dec!(0) {
-                positions.remove(&key);
+    /// Cache the positions committed fills left behind, then publish the fills on the fill topic
+    pub async fn commit_fills(&self, fills: &[Fill], updated: &[Position]) {
+        self.cache_positions(updated).await;
+        self.fills.publish(fills, updated).await;
+    }
+
+    /// Publish committed positions to the cache, moving open interest by the change in each
+    async fn cache_positions(&self, updated: &[Position]) {
+        let mut positions = self.positions.write().await;
+        let mut open_interest = self.open_interest.write().await;
+        for position in updated {
+            debug_assert_eq!(check_position(position), Ok(()), "position {} {} entering the cache", position.account_id, position.symbol);
+            let key = (position.account_id, position.symbol.clone());
+            let previous = if position.net_quantity == dec!(0) {
+                positions.remove(&key)
             } else {
-                positions.insert(key, position.clone());
+                positions.insert(key, position.clone())
+            };
+
+            let change = long_quantity(position) - previous.as_ref().map(long_quantity).unwrap_or_default();
+            if !change.is_zero() {
+                let total = open_interest.entry(position.symbol.clone()).or_default();
+                *total = (*total + change).max(Decimal::ZERO);
+                business::record_open_interest(&position.symbol, *total);
             }
         }
+    }
 
-        Ok(position)
+    /// Recompute open interest from the cache after positions changed outside the fill path
+    async fn recount_open_interest(&self, positions: &HashMap<(Uuid, String), Position>) {
+        let mut open_interest = self.open_interest.write().await;
+        let symbols: Vec<String> = open_interest.keys().cloned().collect();
+
+        open_interest.clear();
+        for position in positions.values() {
+            *open_interest.entry(position.symbol.clone()).or_default() += long_quantity(position);
+        }
+        for symbol in symbols.iter().chain(open_interest.keys()) {
+            business::record_open_interest(symbol, open_interest.get(symbol).copied().unwrap_or_default());
+        }
     }
 
-    /// Calculate new position after fill using weighted average rules
-    fn calculate_new_position(&self, pos: &Position, fill: &Fill) -> (Decimal, Decimal, Decimal) {
-        let fill_qty_signed = if fill.side == "buy" {
-            fill.quantity
-        } else {
-            -fill.quantity
-        };
+    /// Open interest of a symbol: the sum of every account's long position
+    pub async fn open_interest(&self, symbol: &str) -> Decimal {
+        self.open_interest.read().await.get(symbol).copied().unwrap_or_default()
+    }
 
-        let new_quantity = pos.net_quantity + fill_qty_signed;
+    /// Every cached open position
+    pub async fn open_positions(&self) -> Vec<Position> {
+        self.positions.read().await.values().cloned().collect()
+    }
 
-        // Helper: get sign multiplier for a decimal
-        let sign_multiplier = |d: Decimal| -> Decimal {
-            if d > dec!(0) { dec!(1) } else { dec!(-1) }
-        };
+    /// Cached net quantity of an account's position, zero when flat
+    pub async fn net_quantity(&self, account_id: Uuid, symbol: &str) -> Decimal {
+        self.positions
+            .read()
+            .await
+            .get(&(account_id, symbol.to_string()))
+            .map(|p| p.net_quantity)
+            .unwrap_or_default()
+    }
 
-        // Helper: check if same direction
-        let same_direction = (pos.net_quantity > dec!(0) && fill_qty_signed > dec!(0)) ||
-            (pos.net_quantity < dec!(0) && fill_qty_signed < dec!(0));
+    /// Open interest of one symbol, or of every symbol with any
+    pub async fn get_open_interest(
+        &self,
+        auth: &AuthContext,
+        symbol: Option<&str>,
+    ) -> Result<HashMap<String, Decimal>, EngineError> {
+        if !auth.has_permission(permissions::POSITIONS_READ) {
+            return Err(EngineError::Auth(
+                "positions:read required".into()
+            ));
+        }
+
+        let open_interest = self.open_interest.read().await;
+        Ok(match symbol {
+            Some(symbol) => HashMap::from([(symbol.to_string(), open_interest.get(symbol).copied().unwrap_or_default())]),
+            None => open_interest.iter().filter(|(_, q)| !q.is_zero()).map(|(s, q)| (s.clone(), *q)).collect(),
+        })
+    }
+
+    // =====================================================
+    // RECOVERY
+    // =====================================================
+
+    /// Rebuild every position whose sequence differs from its number of recorded movements.
+    /// A mismatch means a crash landed between a fill and its position update.
+    pub async fn repair_positions(&self) -> anyhow::Result<usize> {
+        let mismatched: Vec<(Uuid, String, Option<i64>, Option<i64>)> = sqlx::query_as(&format!(
+            r#"SELECT COALESCE(p.account_id, m.account_id), COALESCE(p.symbol, m.symbol),
+                      p.sequence, m.movements
+               FROM positions p
+               FULL OUTER JOIN (
+                   SELECT account_id, symbol, COUNT(*) AS movements FROM ({}) x
+                   GROUP BY account_id, symbol
+               ) m ON m.account_id = p.account_id AND m.symbol = p.symbol
+               WHERE COALESCE(p.sequence, 0) != COALESCE(m.movements, 0)"#,
+            POSITION_MOVEMENTS
+        ))
+            .fetch_all(&self.pool)
+            .await?;
 
-        // Rule 1: Increasing position (same direction)
-        if same_direction {
-            let total_cost = pos.net_quantity.abs() * pos.avg_price + fill.quantity * fill.price;
-            let new_avg = total_cost / new_quantity.abs();
-            return (new_quantity, new_avg, dec!(0));
+        let mut rebuilt = 0;
+        for (account_id, symbol, stored, recorded) in mismatched {
+            tracing::warn!(
+                account_id = %account_id,
+                symbol = %symbol,
+                stored_sequence = stored.unwrap_or(0),
+                recorded_movements = recorded.unwrap_or(0),
+                "Position sequence mismatch"
+            );
+
+            match self.rebuild_position(account_id, &symbol).await {
+                Ok(true) => rebuilt += 1,
+                Ok(false) => tracing::error!(
+                    account_id = %account_id,
+                    symbol = %symbol,
+                    "No fills recorded for position, leaving it for manual review"
+                ),
+                Err(e) => tracing::error!(account_id = %account_id, symbol = %symbol, "Position rebuild failed: {}", e),
+            }
         }
 
-        // Rule 2: Reducing position (opposite direction, same sign result)
-        let still_same_side = (pos.net_quantity > dec!(0) && new_quantity > dec!(0)) ||
-            (pos.net_quantity < dec!(0) && new_quantity < dec!(0));
+        Ok(rebuilt)
+    }
+
+    /// Disaster recovery: replace the stored positions, every account's or just `account_id`'s,
+    /// with positions replayed from recorded fills and splits. All or nothing.
+    pub async fn rebuild_all(&self, account_id: Option<Uuid>) -> anyhow::Result<usize> {
+        let mut tx = self.pool.begin().await?;
+
+        let removed = sqlx::query("DELETE FROM positions WHERE $1::uuid IS NULL OR account_id = $1")
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        let keys: Vec<(Uuid, String)> = sqlx::query_as(&format!(
+            r#"SELECT DISTINCT account_id, symbol FROM ({}) x
+               WHERE $1::uuid IS NULL OR account_id = $1
+               ORDER BY account_id, symbol"#,
+            POSITION_MOVEMENTS
+        ))
+            .bind(account_id)
+            .fetch_all(&mut *tx)
+            .await?;
 
-        if new_quantity != dec!(0) && still_same_side {
-            let realized = fill.quantity * (fill.price - pos.avg_price) * sign_multiplier(pos.net_quantity);
-            return (new_quantity, pos.avg_price, realized);
+        let mut rebuilt = 0;
+        for (account_id, symbol) in keys {
+            if self.rebuild_in(&mut tx, account_id, &symbol).await? {
+                rebuilt += 1;
+            }
         }
 
-        // Rule 3: Closing position exactly
-        if new_quantity == dec!(0) {
-            let realized = pos.net_quantity.abs() * (fill.price - pos.avg_price) * sign_multiplier(pos.net_quantity);
-            return (dec!(0), dec!(0), realized);
+        // Every recorded fill is part of a rebuilt position now
+        sqlx::query(
+            r#"UPDATE fill_dedup d SET position_applied_at = NOW()
+               FROM trades t
+               WHERE t.id = d.trade_id AND d.position_applied_at IS NULL
+                 AND ($1::uuid IS NULL OR t.account_id = $1)"#
+        )
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        self.positions
+            .write()
+            .await
+            .retain(|(cached_account, _), _| account_id.is_some_and(|id| id != *cached_account));
+
+        tracing::warn!(account_id = ?account_id, removed, rebuilt, "Positions rebuilt from trades");
+        Ok(rebuilt)
+    }
+
+    /// Replay fills and splits since the last sandbox reset into the stored position
+    async fn rebuild_position(&self, account_id: Uuid, symbol: &str) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT 1 FROM positions WHERE account_id = $1 AND symbol = $2 FOR UPDATE")
+            .bind(account_id)
+            .bind(symbol)
+            .execute(&mut *tx)
+            .await?;
+
+        let rebuilt = self.rebuild_in(&mut tx, account_id, symbol).await?;
+        tx.commit().await?;
+        Ok(rebuilt)
+    }
+
+    /// Upsert the replayed position inside the caller's transaction; false if nothing is recorded
+    async fn rebuild_in(&self, conn: &mut PgConnection, account_id: Uuid, symbol: &str) -> anyhow::Result<bool> {
+        let movements: Vec<Movement> = sqlx::query_as(&format!(
+            r#"SELECT kind, side, quantity, price, ratio FROM ({}) x
+               WHERE account_id = $1 AND symbol = $2
+               ORDER BY at, kind"#,
+            POSITION_MOVEMENTS
+        ))
+            .bind(account_id)
+            .bind(symbol)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        if movements.is_empty() {
+            return Ok(false);
         }
 
-        // Rule 4: Crossing zero (close old + open new)
-        let close_qty = pos.net_quantity.abs();
-        let realized = close_qty * (fill.price - pos.avg_price) * sign_multiplier(pos.net_quantity);
-        let new_avg = fill.price; // New position at fill price
-        (new_quantity, new_avg, realized)
+        let position = self.replay(account_id, symbol, &movements);
+
+        sqlx::query(
+            r#"INSERT INTO positions (account_id, symbol, net_quantity, avg_price,
+                                      realized_pnl, cost_basis, unrealized_pnl, sequence, updated_at)
+               VALUES ($1, $2, $3, $4, $5, $6, 0, $7, NOW())
+               ON CONFLICT (account_id, symbol) DO UPDATE SET
+                   net_quantity = $3,
+                   avg_price = $4,
+                   realized_pnl = $5,
+                   cost_basis = $6,
+                   sequence = $7,
+                   updated_at = NOW()"#
+        )
+            .bind(account_id)
+            .bind(symbol)
+            .bind(position.net_quantity)
+            .bind(position.avg_price)
+            .bind(position.realized_pnl)
+            .bind(position.cost_basis)
+            .bind(position.sequence)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(true)
     }
 
-    /// Calculate position from zero
-    fn calculate_new_position_from_zero(&self, fill: &Fill) -> (Decimal, Decimal, Decimal) {
-        let qty = if fill.side == "buy" { fill.quantity } else { -fill.quantity };
-        (qty, fill.price, dec!(0))
+    /// Apply movements in order, rounding each step to the column scale like the live path
+    fn replay(&self, account_id: Uuid, symbol: &str, movements: &[Movement]) -> Position {
+        let mut pos = Position {
+            account_id,
+            symbol: symbol.to_string(),
+            net_quantity: dec!(0),
+            avg_price: dec!(0),
+            realized_pnl: dec!(0),
+            unrealized_pnl: dec!(0),
+            cost_basis: dec!(0),
+            sequence: 0,
+            updated_at: Utc::now(),
+        };
+
+        for movement in movements {
+            match (movement.kind.as_str(), &movement.side, movement.quantity, movement.price, movement.ratio) {
+                ("fill", Some(side), Some(quantity), Some(price), _) => {
+                    let fill = Fill {
+                        account_id,
+                        symbol: symbol.to_string(),
+                        side: side.clone(),
+                        quantity,
+                        price,
+                        key: None,
+                    };
+                    apply_fill(&mut pos, &fill);
+                }
+                ("split", _, _, _, Some(ratio)) => apply_split(&mut pos, ratio),
+                _ => continue,
+            }
+        }
+
+        pos
     }
 
     /// Get position with auth check
+    #[allow(dead_code)]
     pub async fn get_position(
         &self,
         auth: &AuthContext,
         symbol: &str,
-    ) -> Result<Option<Position>, AuthError> {
+    ) -> Result<Option<Position>, EngineError> {
         if !auth.has_permission(permissions::POSITIONS_READ) {
-            return Err(AuthError::InsufficientPermissions(
+            return Err(EngineError::Auth(
                 "positions:read required".into()
             ));
         }
 
-        let position: Option<Position> = sqlx::query_as(
-            "SELECT account_id, symbol, net_quantity, avg_price, realized_pnl, \
-             unrealized_pnl, cost_basis, updated_at FROM positions WHERE account_id = $1 AND symbol = $2"
-        )
-            .bind(auth.account_id)
-            .bind(symbol)
-            .fetch_optional(&self.pool)
+        let position: Option<Position> = self.reads.run_hedged("positions.get", |pool| async move {
+            sqlx::query_as(
+                "SELECT account_id, symbol, net_quantity, avg_price, realized_pnl, \
+                 unrealized_pnl, cost_basis, sequence, updated_at FROM positions WHERE account_id = $1 AND symbol = $2"
+            )
+                .bind(auth.account_id)
+                .bind(symbol)
+                .fetch_optional(&pool)
+                .await
+        })
             .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
 
         Ok(position)
     }
@@ -202,9 +628,9 @@ impl PositionKeeper {
         &self,
         auth: &AuthContext,
         account_id: Option<Uuid>,
-    ) -> Result<Vec<Position>, AuthError> {
+    ) -> Result<Vec<Position>, EngineError> {
         if !auth.has_permission(permissions::POSITIONS_READ) {
-            return Err(AuthError::InsufficientPermissions(
+            return Err(EngineError::Auth(
                 "positions:read required".into()
             ));
         }
@@ -212,20 +638,39 @@ impl PositionKeeper {
         let target = account_id.unwrap_or(auth.account_id);
 
         if target != auth.account_id && !auth.has_permission("positions:read_all") {
-            return Err(AuthError::InsufficientPermissions(
+            return Err(EngineError::Auth(
                 "Cannot view others' positions".into()
             ));
         }
 
-        let positions: Vec<Position> = sqlx::query_as(
-            "SELECT account_id, symbol, net_quantity, avg_price, realized_pnl, \
-             unrealized_pnl, cost_basis, updated_at FROM positions WHERE account_id = $1"
-        )
-            .bind(target)
-            .fetch_all(&self.pool)
+        let positions: Vec<Position> = self.reads.run_hedged("positions.list", |pool| async move {
+            sqlx::query_as(
+                "SELECT account_id, symbol, net_quantity, avg_price, realized_pnl, \
+                 unrealized_pnl, cost_basis, sequence, updated_at FROM positions WHERE account_id = $1"
+            )
+                .bind(target)
+                .fetch_all(&pool)
+                .await
+        })
             .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
 
         Ok(positions)
     }
-}
\ No newline at end of file
+}
+
+async fn fetch_position(
+    conn: &mut PgConnection,
+    account_id: Uuid,
+    symbol: &str,
+) -> Result<Option<Position>, sqlx::Error> {
+    sqlx::query_as(
+        r#"SELECT account_id, symbol, net_quantity, avg_price,
+                  realized_pnl, unrealized_pnl, cost_basis, sequence, updated_at
+           FROM positions WHERE account_id = $1 AND symbol = $2"#
+    )
+        .bind(account_id)
+        .bind(symbol)
+        .fetch_optional(&mut *conn)
+        .await
+}
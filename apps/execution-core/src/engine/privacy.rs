@@ -0,0 +1,410 @@
+//! Account Data Export & Erasure
+//! Subject-access exports across all account tables, and scheduled pseudonymization
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+use crate::clock::SharedClock;
+use crate::engine::order_processor::Order;
+use crate::engine::sandbox::cancel_open_orders;
+use crate::engine::{Ledger, OrderProcessor, PositionKeeper};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Replaces the password hash of erased accounts; never matches a bcrypt hash
+const ERASED_PASSWORD_HASH: &str = "!erased";
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone)]
+pub struct PrivacyConfig {
+    /// Time between an erasure request and scrubbing personal identifiers
+    pub erasure_retention_secs: i64,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            erasure_retention_secs: 30 * 86_400,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErasureRequest {
+    #[serde(alias = "account_id")]
+    pub account_id: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ErasureRecord {
+    pub account_id: Uuid,
+    pub status: String,
+    pub requested_at: DateTime<Utc>,
+    pub erase_after: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Everything stored about one account, grouped by table
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountExport {
+    pub account_id: Uuid,
+    pub generated_at: DateTime<Utc>,
+    pub account: serde_json::Value,
+    pub orders: Vec<Order>,
+    pub order_events: serde_json::Value,
+    pub order_events_archive: serde_json::Value,
+    pub order_snapshots: serde_json::Value,
+    pub trades: serde_json::Value,
+    pub positions: serde_json::Value,
+    pub position_exposure: serde_json::Value,
+    pub fee_rebates: serde_json::Value,
+    pub dust_sweeps: serde_json::Value,
+    pub balances: serde_json::Value,
+    pub ledger_entries: serde_json::Value,
+    pub audit_log: serde_json::Value,
+    pub leaderboard: serde_json::Value,
+    pub order_settings: serde_json::Value,
+    pub trading_pauses: serde_json::Value,
+    pub strategy_limits: serde_json::Value,
+    pub mmp: serde_json::Value,
+    pub support_consents: serde_json::Value,
+    pub impersonation_sessions: serde_json::Value,
+    pub export_jobs: serde_json::Value,
+    pub erasure: Option<ErasureRecord>,
+}
+
+/// Export sections that are plain row dumps: (name, query returning a JSON array)
+const EXPORT_SECTIONS: [(&str, &str); 19] = [
+    ("order_events", r#"SELECT e.* FROM order_events e JOIN orders o ON o.id = e.order_id
+                        WHERE o.account_id = $1 ORDER BY e.created_at"#),
+    ("order_events_archive", r#"SELECT e.* FROM order_events_archive e JOIN orders o ON o.id = e.order_id
+                                WHERE o.account_id = $1 ORDER BY e.created_at"#),
+    ("order_snapshots", "SELECT * FROM order_snapshots WHERE account_id = $1 ORDER BY last_event_at"),
+    ("trades", "SELECT * FROM trades WHERE account_id = $1 ORDER BY executed_at"),
+    ("positions", "SELECT * FROM positions WHERE account_id = $1 ORDER BY symbol"),
+    ("position_exposure", "SELECT * FROM position_exposure WHERE account_id = $1 ORDER BY session_date, symbol"),
+    ("fee_rebates", "SELECT * FROM fee_rebates WHERE account_id = $1 ORDER BY created_at"),
+    ("dust_sweeps", "SELECT * FROM dust_sweeps WHERE account_id = $1 ORDER BY swept_at"),
+    ("balances", "SELECT * FROM account_balances WHERE account_id = $1 ORDER BY asset"),
+    ("ledger_entries", "SELECT * FROM ledger_entries WHERE account_id = $1 ORDER BY created_at"),
+    ("audit_log", "SELECT * FROM audit_log WHERE account_id = $1 ORDER BY created_at"),
+    ("leaderboard", "SELECT * FROM leaderboard_participants WHERE account_id = $1"),
+    ("order_settings", "SELECT * FROM account_order_settings WHERE account_id = $1"),
+    ("trading_pauses", "SELECT * FROM account_trading_pauses WHERE account_id = $1"),
+    ("strategy_limits", "SELECT * FROM strategy_tag_limits WHERE account_id = $1 ORDER BY strategy_tag"),
+    ("mmp", "SELECT * FROM account_mmp_settings WHERE account_id = $1 ORDER BY symbol"),
+    ("support_consents", "SELECT * FROM support_consents WHERE account_id = $1 ORDER BY granted_at"),
+    ("impersonation_sessions", r#"SELECT id, agent_username, reason, started_at, expires_at, ended_at
+                                  FROM impersonation_sessions WHERE customer_account_id = $1
+                                  ORDER BY started_at"#),
+    ("export_jobs", "SELECT * FROM export_jobs WHERE account_id = $1 ORDER BY requested_at"),
+];
+
+// =====================================================
+// PRIVACY MANAGER
+// =====================================================
+
+pub struct PrivacyManager {
+    pool: PgPool,
+    ledger: Arc<Ledger>,
+    order_processor: Arc<OrderProcessor>,
+    position_keeper: Arc<PositionKeeper>,
+    config: PrivacyConfig,
+    clock: SharedClock,
+}
+
+impl PrivacyManager {
+    pub fn new(
+        pool: PgPool,
+        ledger: Arc<Ledger>,
+        order_processor: Arc<OrderProcessor>,
+        position_keeper: Arc<PositionKeeper>,
+        config: PrivacyConfig,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            pool,
+            ledger,
+            order_processor,
+            position_keeper,
+            config,
+            clock,
+        }
+    }
+
+    /// Export all data for an account - the account itself or a privacy admin
+    pub async fn export(
+        &self,
+        auth: &AuthContext,
+        account_id: Uuid,
+    ) -> Result<AccountExport, EngineError> {
+        if account_id != auth.account_id && !auth.has_permission(permissions::ACCOUNTS_PRIVACY) {
+            return Err(EngineError::Auth(
+                "Cannot export another account".into()
+            ));
+        }
+
+        self.build_export(account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?
+            .ok_or_else(|| EngineError::Validation("Account not found".into()))
+    }
+
+    async fn build_export(&self, account_id: Uuid) -> Result<Option<AccountExport>, sqlx::Error> {
+        // One snapshot so sections agree with each other
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+
+        let account: Option<(serde_json::Value,)> = sqlx::query_as(
+            r#"SELECT to_jsonb(a) - 'password_hash' FROM accounts a WHERE id = $1"#
+        )
+            .bind(account_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let Some((account,)) = account else {
+            return Ok(None);
+        };
+
+        let orders: Vec<Order> = sqlx::query_as(
+            "SELECT * FROM orders WHERE account_id = $1 ORDER BY created_at"
+        )
+            .bind(account_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut sections = serde_json::Map::new();
+        for (name, query) in EXPORT_SECTIONS {
+            let (rows,): (serde_json::Value,) = sqlx::query_as(&format!(
+                "SELECT COALESCE(jsonb_agg(to_jsonb(t)), '[]'::jsonb) FROM ({}) t",
+                query
+            ))
+                .bind(account_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            sections.insert(name.to_string(), rows);
+        }
+
+        let erasure = fetch_erasure(&mut tx, account_id).await?;
+        tx.commit().await?;
+
+        let mut section = |name: &str| sections.remove(name).unwrap_or_default();
+        Ok(Some(AccountExport {
+            account_id,
+            generated_at: self.clock.now(),
+            account,
+            orders,
+            order_events: section("order_events"),
+            order_events_archive: section("order_events_archive"),
+            order_snapshots: section("order_snapshots"),
+            trades: section("trades"),
+            positions: section("positions"),
+            position_exposure: section("position_exposure"),
+            fee_rebates: section("fee_rebates"),
+            dust_sweeps: section("dust_sweeps"),
+            balances: section("balances"),
+            ledger_entries: section("ledger_entries"),
+            audit_log: section("audit_log"),
+            leaderboard: section("leaderboard"),
+            order_settings: section("order_settings"),
+            trading_pauses: section("trading_pauses"),
+            strategy_limits: section("strategy_limits"),
+            mmp: section("mmp"),
+            support_consents: section("support_consents"),
+            impersonation_sessions: section("impersonation_sessions"),
+            export_jobs: section("export_jobs"),
+            erasure,
+        }))
+    }
+
+    /// Freeze the account now and schedule its identifiers to be scrubbed after retention
+    pub async fn request_erasure(
+        &self,
+        auth: &AuthContext,
+        req: ErasureRequest,
+    ) -> Result<ErasureRecord, EngineError> {
+        if !auth.has_permission(permissions::ACCOUNTS_PRIVACY) {
+            return Err(EngineError::Auth(
+                "accounts:privacy required".into()
+            ));
+        }
+
+        let account_id = Uuid::parse_str(&req.account_id)
+            .map_err(|_| EngineError::Validation("Invalid account_id".into()))?;
+
+        self.freeze_account(auth.account_id, account_id, req.reason)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?
+            .ok_or_else(|| EngineError::Validation("Account not found".into()))
+    }
+
+    async fn freeze_account(
+        &self,
+        requested_by: Uuid,
+        account_id: Uuid,
+        reason: Option<String>,
+    ) -> anyhow::Result<Option<ErasureRecord>> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        let frozen = sqlx::query("UPDATE accounts SET is_active = false, updated_at = $2 WHERE id = $1")
+            .bind(account_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        if frozen.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let orders_cancelled = cancel_open_orders(&mut tx, account_id).await?;
+        self.ledger.release_account(&mut tx, account_id).await?;
+
+        sqlx::query(
+            r#"UPDATE refresh_tokens SET revoked_at = $2, revoked_reason = 'erasure_requested'
+               WHERE account_id = $1 AND revoked_at IS NULL"#
+        )
+            .bind(account_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        // Re-requesting keeps the original retention deadline
+        let record: ErasureRecord = sqlx::query_as(
+            r#"INSERT INTO account_erasure_requests (account_id, requested_by, reason, requested_at, erase_after)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT (account_id) DO UPDATE SET reason = COALESCE(EXCLUDED.reason, account_erasure_requests.reason)
+               RETURNING account_id, status, requested_at, erase_after, completed_at"#
+        )
+            .bind(account_id)
+            .bind(requested_by)
+            .bind(reason)
+            .bind(now)
+            .bind(now + Duration::seconds(self.config.erasure_retention_secs))
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        self.order_processor.evict_account(account_id).await;
+        self.position_keeper.evict_account(account_id).await;
+
+        tracing::info!(
+            account_id = %account_id,
+            orders_cancelled,
+            erase_after = %record.erase_after,
+            "Account frozen for erasure"
+        );
+
+        Ok(Some(record))
+    }
+
+    /// Scheduled pass: pseudonymize accounts whose retention window has ended
+    pub async fn run_scheduled(&self) -> anyhow::Result<usize> {
+        let due: Vec<(Uuid,)> = sqlx::query_as(
+            r#"SELECT account_id FROM account_erasure_requests
+               WHERE status = 'scheduled' AND erase_after <= $1
+               ORDER BY erase_after"#
+        )
+            .bind(self.clock.now())
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut erased = 0;
+        for (account_id,) in &due {
+            match self.pseudonymize(*account_id).await {
+                Ok(true) => erased += 1,
+                Ok(false) => {}
+                Err(e) => tracing::error!(account_id = %account_id, "Account erasure failed: {}", e),
+            }
+        }
+
+        Ok(erased)
+    }
+
+    /// Scrub personal identifiers; financial records stay, keyed by the now-anonymous id
+    async fn pseudonymize(&self, account_id: Uuid) -> anyhow::Result<bool> {
+        let now = self.clock.now();
+        let mut tx = self.pool.begin().await?;
+
+        // Claim first so two sweepers never both scrub the same account
+        let claimed = sqlx::query(
+            r#"UPDATE account_erasure_requests SET status = 'completed', completed_at = $2
+               WHERE account_id = $1 AND status = 'scheduled'"#
+        )
+            .bind(account_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        if claimed.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        let alias = pseudonym(account_id);
+        sqlx::query(
+            r#"UPDATE accounts
+               SET username = $2, email = $2 || '@erased.invalid', password_hash = $3,
+                   is_active = false, is_verified = false, last_login_at = NULL, updated_at = $4
+               WHERE id = $1"#
+        )
+            .bind(account_id)
+            .bind(&alias)
+            .bind(ERASED_PASSWORD_HASH)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        scrub_account_tables(&mut tx, account_id).await?;
+        tx.commit().await?;
+
+        self.order_processor.evict_account(account_id).await;
+        self.position_keeper.evict_account(account_id).await;
+
+        tracing::info!(account_id = %account_id, "Account personal data erased");
+        Ok(true)
+    }
+}
+
+/// Stable, non-reversible replacement for username and email
+fn pseudonym(account_id: Uuid) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(account_id.as_bytes());
+    format!("erased-{}", hex::encode(&digest[..6]))
+}
+
+async fn scrub_account_tables(conn: &mut PgConnection, account_id: Uuid) -> Result<(), sqlx::Error> {
+    let statements = [
+        "DELETE FROM refresh_tokens WHERE account_id = $1",
+        "DELETE FROM api_keys WHERE account_id = $1",
+        "DELETE FROM leaderboard_participants WHERE account_id = $1",
+        "UPDATE audit_log SET ip_address = NULL, user_agent = NULL WHERE account_id = $1",
+        "UPDATE sandbox_accounts SET session_id = NULL WHERE account_id = $1",
+        // Client metadata is free-form and may carry personal references
+        "UPDATE orders SET metadata = NULL WHERE account_id = $1 AND metadata IS NOT NULL",
+    ];
+
+    for sql in statements {
+        sqlx::query(sql).bind(account_id).execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+async fn fetch_erasure(conn: &mut PgConnection, account_id: Uuid) -> Result<Option<ErasureRecord>, sqlx::Error> {
+    sqlx::query_as(
+        r#"SELECT account_id, status, requested_at, erase_after, completed_at
+           FROM account_erasure_requests WHERE account_id = $1"#
+    )
+        .bind(account_id)
+        .fetch_optional(conn)
+        .await
+}
@@ -0,0 +1,168 @@
+//! Shard Rebalancing
+//! Moves a symbol from one sharded instance to another while both run. An admin asks on
+//! `admin.shard.rebalance`; every step is then an event on `system.rebalance` that the
+//! instances involved act on. The source gives the symbol up (`released`), handing over its last
+//! price, and the target records the move and loads the symbol's open orders (`acquired`). The
+//! symbol is halted in between. A move that fails or misses its deadline goes back to the
+//! source (`restored`).
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+pub const REBALANCE_SUBJECT: &str = "admin.shard.rebalance";
+pub const EVENTS_SUBJECT: &str = "system.rebalance";
+
+#[derive(Debug, Deserialize)]
+pub struct RebalanceRequest {
+    pub symbol: String,
+    /// `INSTANCE_ID` of the instance trading the symbol now
+    pub from: String,
+    /// `INSTANCE_ID` of the instance to trade it from now on
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebalancePhase {
+    Requested,
+    Released,
+    Acquired,
+    Failed,
+    Restored,
+}
+
+/// Published on `system.rebalance` at every step of a move
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebalanceEvent {
+    pub rebalance_id: Uuid,
+    pub symbol: String,
+    pub from: String,
+    pub to: String,
+    pub phase: RebalancePhase,
+    pub at: DateTime<Utc>,
+    /// The target does not take the symbol on after this; the move fails instead
+    pub deadline: DateTime<Utc>,
+    /// Last traded price handed over on `released`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_price: Option<Decimal>,
+    /// Open orders given up on `released`, loaded on `acquired` and `restored`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_orders: Option<usize>,
+    /// How long the symbol was halted, on `acquired` and `restored`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halted_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl RebalanceEvent {
+    pub fn requested(req: RebalanceRequest, at: DateTime<Utc>, deadline: DateTime<Utc>) -> Self {
+        Self {
+            rebalance_id: Uuid::new_v4(),
+            symbol: req.symbol,
+            from: req.from,
+            to: req.to,
+            phase: RebalancePhase::Requested,
+            at,
+            deadline,
+            last_price: None,
+            open_orders: None,
+            halted_ms: None,
+            reason: None,
+        }
+    }
+
+    /// The next step of the same move, without this step's details
+    pub fn next(&self, phase: RebalancePhase, at: DateTime<Utc>) -> Self {
+        Self {
+            phase,
+            at,
+            last_price: None,
+            open_orders: None,
+            halted_ms: None,
+            reason: None,
+            ..self.clone()
+        }
+    }
+
+    pub fn failed(&self, at: DateTime<Utc>, reason: impl Into<String>) -> Self {
+        Self { reason: Some(reason.into()), ..self.next(RebalancePhase::Failed, at) }
+    }
+
+    /// Whether the move is over: the coordinator replies with this event
+    pub fn is_settled(&self) -> bool {
+        matches!(self.phase, RebalancePhase::Acquired | RebalancePhase::Failed)
+    }
+}
+
+/// Record that `symbol` trades on `instance_id` from now on
+pub async fn assign(
+    pool: &PgPool,
+    symbol: &str,
+    instance_id: &str,
+    rebalance_id: Uuid,
+    at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO shard_assignments (symbol, instance_id, rebalance_id, assigned_at)
+           VALUES ($1, $2, $3, $4)
+           ON CONFLICT (symbol) DO UPDATE SET
+               instance_id = EXCLUDED.instance_id,
+               rebalance_id = EXCLUDED.rebalance_id,
+               assigned_at = EXCLUDED.assigned_at"#
+    )
+        .bind(symbol)
+        .bind(instance_id)
+        .bind(rebalance_id)
+        .bind(at)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Moves this instance takes part in
+#[derive(Default)]
+pub struct Rebalances {
+    /// Coordinated here, each waiting for the move to settle
+    waiting: Mutex<HashMap<Uuid, oneshot::Sender<RebalanceEvent>>>,
+    /// Released here and not yet acquired elsewhere, to take back if the move fails
+    released: Mutex<HashMap<Uuid, DateTime<Utc>>>,
+}
+
+impl Rebalances {
+    /// Wait here for a move coordinated here to settle
+    pub fn wait(&self, rebalance_id: Uuid) -> oneshot::Receiver<RebalanceEvent> {
+        let (tx, rx) = oneshot::channel();
+        self.waiting.lock().unwrap_or_else(|e| e.into_inner()).insert(rebalance_id, tx);
+        rx
+    }
+
+    /// Stop waiting for a move, once it timed out
+    pub fn abandon(&self, rebalance_id: Uuid) {
+        self.waiting.lock().unwrap_or_else(|e| e.into_inner()).remove(&rebalance_id);
+    }
+
+    /// Hand a settling event to the coordinator if it waits here
+    pub fn settle(&self, event: &RebalanceEvent) {
+        if let Some(tx) = self.waiting.lock().unwrap_or_else(|e| e.into_inner()).remove(&event.rebalance_id) {
+            let _ = tx.send(event.clone());
+        }
+    }
+
+    pub fn released(&self, rebalance_id: Uuid, at: DateTime<Utc>) {
+        self.released.lock().unwrap_or_else(|e| e.into_inner()).insert(rebalance_id, at);
+    }
+
+    /// When a move released here gave its symbol up, forgetting it; `None` if it was not
+    /// released here or is already settled
+    pub fn take_released(&self, rebalance_id: Uuid) -> Option<DateTime<Utc>> {
+        self.released.lock().unwrap_or_else(|e| e.into_inner()).remove(&rebalance_id)
+    }
+}
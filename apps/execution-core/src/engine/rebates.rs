@@ -0,0 +1,399 @@
+//! Fee Rebates
+//! Maker rebates credited with each fill, volume-tier rebates applied retroactively to a
+//! month's volume once it ends, and the referrer's share of the fees referred accounts
+//! generate, all posted to the ledger and listed on a monthly statement
+
+use crate::auth::{AuthContext, permissions};
+use crate::clock::SharedClock;
+use crate::engine::error::EngineError;
+use crate::engine::ledger::{spot_pair, Ledger, Posting, HOUSE_ACCOUNT};
+use crate::engine::order_processor::Order;
+use crate::observability::metrics::get_metrics;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use enthropic_domain::rebate::{
+    is_maker, last_closed_period, period_bounds, period_of, rebate_amount, tier_for, RebateTier,
+};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+pub struct RebateConfig {
+    /// Credited on every maker fill, in basis points of notional
+    pub maker_rebate_bps: Decimal,
+    /// Monthly volume tiers, applied to the month's whole volume
+    pub volume_tiers: Vec<RebateTier>,
+    /// Fee schedule referral shares are computed from, in basis points of notional
+    pub fee_rate_bps: Decimal,
+    /// Fraction of a referred account's fees credited to its referrer
+    pub referral_share: Decimal,
+}
+
+/// Parse `volume=bps[,volume=bps...]`, e.g. `1000000=0.5,10000000=1`
+pub fn parse_rebate_tiers(spec: &str) -> anyhow::Result<Vec<RebateTier>> {
+    let mut tiers: Vec<RebateTier> = Vec::new();
+
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (volume, bps) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("rebate tier '{}' is not volume=bps", entry))?;
+        let number = |value: &str| {
+            value
+                .trim()
+                .parse::<Decimal>()
+                .ok()
+                .filter(|v| *v >= Decimal::ZERO)
+                .ok_or_else(|| anyhow::anyhow!("invalid number '{}' in rebate tier '{}'", value.trim(), entry))
+        };
+        let tier = RebateTier { min_volume: number(volume)?, bps: number(bps)? };
+
+        if tiers.iter().any(|t| t.min_volume == tier.min_volume) {
+            anyhow::bail!("duplicate rebate tier at volume {}", tier.min_volume);
+        }
+        tiers.push(tier);
+    }
+
+    Ok(tiers)
+}
+
+// =====================================================
+// MODELS
+// =====================================================
+
+/// Rebates of one kind in one asset over a month
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct RebateLine {
+    pub kind: String,
+    pub asset: String,
+    pub notional: Decimal,
+    pub amount: Decimal,
+    pub credits: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebateStatement {
+    pub account_id: Uuid,
+    pub period: NaiveDate,
+    /// False until the month's volume-tier and referral rebates are credited
+    pub settled: bool,
+    pub lines: Vec<RebateLine>,
+}
+
+/// A rebate about to be credited
+struct Credit {
+    account_id: Uuid,
+    kind: &'static str,
+    asset: String,
+    notional: Decimal,
+    bps: Decimal,
+    amount: Decimal,
+    source_account_id: Option<Uuid>,
+}
+
+// =====================================================
+// REBATES
+// =====================================================
+
+pub struct Rebates {
+    pool: PgPool,
+    ledger: Arc<Ledger>,
+    config: RebateConfig,
+    clock: SharedClock,
+}
+
+impl Rebates {
+    pub fn new(pool: PgPool, ledger: Arc<Ledger>, config: RebateConfig, clock: SharedClock) -> Self {
+        Self { pool, ledger, config, clock }
+    }
+
+    /// Asset notional and rebates of a symbol are counted in
+    fn quote_asset(&self, symbol: &str) -> String {
+        spot_pair(symbol, self.ledger.default_quote())
+            .map(|(_, quote)| quote)
+            .unwrap_or_else(|| self.ledger.default_quote().to_uppercase())
+    }
+
+    /// Credit the maker rebate of a fill inside the transaction that settles it. A replayed
+    /// fill finds its rebate already credited.
+    pub async fn credit_maker(
+        &self,
+        conn: &mut PgConnection,
+        order: &Order,
+        trade_id: Uuid,
+        quantity: Decimal,
+        price: Decimal,
+        at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        if self.config.maker_rebate_bps.is_zero() || !is_maker(&order.order_type) {
+            return Ok(());
+        }
+
+        let notional = quantity * price;
+        let amount = rebate_amount(notional, self.config.maker_rebate_bps);
+        if amount.is_zero() {
+            return Ok(());
+        }
+
+        let credit = Credit {
+            account_id: order.account_id,
+            kind: "maker",
+            asset: self.quote_asset(&order.symbol),
+            notional,
+            bps: self.config.maker_rebate_bps,
+            amount,
+            source_account_id: None,
+        };
+        self.credit(conn, &credit, period_of(at.date_naive()), Some(trade_id)).await?;
+        Ok(())
+    }
+
+    /// Credit the volume-tier and referral rebates of the last month that has ended, once.
+    /// Returns how many rebates were credited.
+    pub async fn settle_month(&self) -> Result<u64, sqlx::Error> {
+        if self.config.volume_tiers.is_empty() && self.config.referral_share.is_zero() {
+            return Ok(0);
+        }
+
+        let period = last_closed_period(self.clock.now());
+        let (start, end) = period_bounds(period);
+        let mut tx = self.pool.begin().await?;
+
+        // Claimed first, so another replica settling the same month waits and then skips it
+        let claimed = sqlx::query("INSERT INTO fee_rebate_periods (period) VALUES ($1) ON CONFLICT DO NOTHING")
+            .bind(period)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if claimed == 0 {
+            return Ok(0);
+        }
+
+        let mut credits = Vec::new();
+        if !self.config.volume_tiers.is_empty() {
+            let volumes: Vec<(Uuid, String, Decimal)> = sqlx::query_as(
+                r#"SELECT account_id, symbol, SUM(quantity * price)
+                   FROM trades
+                   WHERE executed_at >= $1 AND executed_at < $2 AND account_id <> $3
+                   GROUP BY account_id, symbol"#
+            )
+                .bind(start)
+                .bind(end)
+                .bind(HOUSE_ACCOUNT)
+                .fetch_all(&mut *tx)
+                .await?;
+
+            for ((account_id, asset), notional) in self.by_quote_asset(volumes) {
+                let Some(tier) = tier_for(&self.config.volume_tiers, notional) else {
+                    continue;
+                };
+                credits.push(Credit {
+                    account_id,
+                    kind: "volume_tier",
+                    asset,
+                    notional,
+                    bps: tier.bps,
+                    amount: rebate_amount(notional, tier.bps),
+                    source_account_id: None,
+                });
+            }
+        }
+
+        if !self.config.referral_share.is_zero() {
+            // Only trades made after the referral was recorded earn the referrer a share
+            let referred: Vec<(Uuid, Uuid, String, Decimal)> = sqlx::query_as(
+                r#"SELECT r.referrer_id, t.account_id, t.symbol, SUM(t.quantity * t.price)
+                   FROM trades t JOIN account_referrals r ON r.account_id = t.account_id
+                   WHERE t.executed_at >= $1 AND t.executed_at < $2 AND t.executed_at >= r.created_at
+                   GROUP BY r.referrer_id, t.account_id, t.symbol"#
+            )
+                .bind(start)
+                .bind(end)
+                .fetch_all(&mut *tx)
+                .await?;
+
+            let share_bps = self.config.fee_rate_bps * self.config.referral_share;
+            let mut by_referral: HashMap<(Uuid, Uuid, String), Decimal> = HashMap::new();
+            for (referrer_id, account_id, symbol, notional) in referred {
+                *by_referral.entry((referrer_id, account_id, self.quote_asset(&symbol))).or_default() += notional;
+            }
+            for ((referrer_id, account_id, asset), notional) in by_referral {
+                credits.push(Credit {
+                    account_id: referrer_id,
+                    kind: "referral",
+                    asset,
+                    notional,
+                    bps: share_bps,
+                    amount: rebate_amount(notional, share_bps),
+                    source_account_id: Some(account_id),
+                });
+            }
+        }
+
+        let mut credited = 0;
+        for credit in credits.iter().filter(|c| !c.amount.is_zero()) {
+            if self.credit(&mut tx, credit, period, None).await? {
+                credited += 1;
+            }
+        }
+
+        sqlx::query("UPDATE fee_rebate_periods SET rebates = $2, settled_at = $3 WHERE period = $1")
+            .bind(period)
+            .bind(credited as i32)
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        tracing::info!(%period, rebates = credited, "Monthly rebates settled");
+        Ok(credited)
+    }
+
+    fn by_quote_asset(&self, volumes: Vec<(Uuid, String, Decimal)>) -> HashMap<(Uuid, String), Decimal> {
+        let mut totals: HashMap<(Uuid, String), Decimal> = HashMap::new();
+        for (account_id, symbol, notional) in volumes {
+            *totals.entry((account_id, self.quote_asset(&symbol))).or_default() += notional;
+        }
+        totals
+    }
+
+    /// Record a rebate and post it from the house account; false if it was already credited
+    async fn credit(
+        &self,
+        conn: &mut PgConnection,
+        credit: &Credit,
+        period: NaiveDate,
+        trade_id: Option<Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let id: Option<(Uuid,)> = sqlx::query_as(
+            r#"INSERT INTO fee_rebates (account_id, kind, period, asset, notional, bps, amount, trade_id,
+                                        source_account_id, created_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+               ON CONFLICT DO NOTHING
+               RETURNING id"#
+        )
+            .bind(credit.account_id)
+            .bind(credit.kind)
+            .bind(period)
+            .bind(&credit.asset)
+            .bind(credit.notional)
+            .bind(credit.bps)
+            .bind(credit.amount)
+            .bind(trade_id)
+            .bind(credit.source_account_id)
+            .bind(self.clock.now())
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        let Some((rebate_id,)) = id else {
+            return Ok(false);
+        };
+
+        // Balances are only kept while the ledger is enforced, as for the fills themselves
+        if self.ledger.enabled() {
+            self.ledger
+                .post(
+                    conn,
+                    &format!("{}_rebate", credit.kind),
+                    Some(rebate_id),
+                    &[
+                        Posting { account_id: credit.account_id, asset: credit.asset.clone(), amount: credit.amount },
+                        Posting { account_id: HOUSE_ACCOUNT, asset: credit.asset.clone(), amount: -credit.amount },
+                    ],
+                )
+                .await?;
+        }
+
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.rebates_credited_total.with_label_values(&[credit.kind]).inc();
+        }
+        Ok(true)
+    }
+
+    // =====================================================
+    // STATEMENTS & REFERRALS
+    // =====================================================
+
+    /// Rebates credited to an account over a month (the current one by default)
+    pub async fn statement(
+        &self,
+        auth: &AuthContext,
+        account_id: Option<Uuid>,
+        period: Option<NaiveDate>,
+    ) -> Result<RebateStatement, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_READ) {
+            return Err(EngineError::Auth(
+                "orders:read required".into()
+            ));
+        }
+
+        let account_id = account_id.unwrap_or(auth.account_id);
+        if !auth.can_access_account(&account_id) {
+            return Err(EngineError::Auth(
+                "Cannot view others' rebates".into()
+            ));
+        }
+
+        let period = period_of(period.unwrap_or_else(|| self.clock.now().date_naive()));
+        let lines: Vec<RebateLine> = sqlx::query_as(
+            r#"SELECT kind, asset, SUM(notional) AS notional, SUM(amount) AS amount, COUNT(*) AS credits
+               FROM fee_rebates
+               WHERE account_id = $1 AND period = $2
+               GROUP BY kind, asset
+               ORDER BY kind, asset"#
+        )
+            .bind(account_id)
+            .bind(period)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let (settled,): (bool,) = sqlx::query_as("SELECT EXISTS (SELECT 1 FROM fee_rebate_periods WHERE period = $1)")
+            .bind(period)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(RebateStatement { account_id, period, settled, lines })
+    }
+
+    /// Record who referred an account. An account is referred once.
+    pub async fn set_referrer(&self, auth: &AuthContext, account_id: Uuid, referrer_id: Uuid) -> Result<(), EngineError> {
+        if !auth.has_permission(permissions::ADMIN_FULL) {
+            return Err(EngineError::Auth(
+                "admin:full required".into()
+            ));
+        }
+        if account_id == referrer_id {
+            return Err(EngineError::Validation("An account cannot refer itself".into()));
+        }
+
+        let inserted = sqlx::query(
+            r#"INSERT INTO account_referrals (account_id, referrer_id, created_at)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (account_id) DO NOTHING"#
+        )
+            .bind(account_id)
+            .bind(referrer_id)
+            .bind(self.clock.now())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(ref db) if db.is_foreign_key_violation() => {
+                    EngineError::Validation("Unknown account".into())
+                }
+                e => e.into(),
+            })?
+            .rows_affected();
+
+        if inserted == 0 {
+            return Err(EngineError::Conflict("Account already has a referrer".into()));
+        }
+
+        tracing::info!(%account_id, %referrer_id, "Referral recorded");
+        Ok(())
+    }
+}
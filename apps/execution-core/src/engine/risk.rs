@@ -0,0 +1,272 @@
+//! Risk Limits
+//! Pre-trade checks against platform-wide exposure, and post-trade tracking fed by the fill topic
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::fill_topic::{FillEvent, FillSubscriber};
+use crate::engine::PositionKeeper;
+use crate::observability::metrics::get_metrics;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// What a breached concentration limit does to the order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConcentrationMode {
+    #[default]
+    Reject,
+    /// Accept the order and record the breach against it
+    Flag,
+}
+
+impl ConcentrationMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "reject" => Some(Self::Reject),
+            "flag" => Some(Self::Flag),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConcentrationConfig {
+    /// Largest share of a symbol's open interest one account may hold, in percent; zero disables
+    pub max_share_pct: Decimal,
+    /// Below this open interest every holder is a large share, so the limit waits for it
+    pub min_open_interest: Decimal,
+    /// Largest open interest across all accounts, by symbol
+    pub symbol_caps: HashMap<String, Decimal>,
+    pub mode: ConcentrationMode,
+}
+
+/// Parse `SYMBOL=quantity[,SYMBOL=quantity...]`, e.g. `BTC-PERP=5000,ETH-PERP=40000`
+pub fn parse_open_interest_caps(spec: &str) -> anyhow::Result<HashMap<String, Decimal>> {
+    let mut caps = HashMap::new();
+
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (symbol, cap) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("open interest cap '{}' is not SYMBOL=quantity", entry))?;
+        let cap: Decimal = cap
+            .trim()
+            .parse()
+            .ok()
+            .filter(|cap: &Decimal| *cap > Decimal::ZERO)
+            .ok_or_else(|| anyhow::anyhow!("open interest cap of {} must be a positive quantity", symbol.trim()))?;
+
+        if caps.insert(symbol.trim().to_string(), cap).is_some() {
+            anyhow::bail!("duplicate open interest cap for {}", symbol.trim());
+        }
+    }
+
+    Ok(caps)
+}
+
+/// How a breach was handled: rejected, accepted under `CONCENTRATION_MODE=flag`, or accepted
+/// because the caller holds `risk:override`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreachAction {
+    Rejected,
+    Flagged,
+    Overridden,
+}
+
+impl BreachAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rejected => "rejected",
+            Self::Flagged => "flagged",
+            Self::Overridden => "overridden",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConcentrationBreach {
+    pub reason: String,
+    pub action: BreachAction,
+}
+
+/// Share of open interest, in percent, a long position of `long_after` would be once the
+/// account's current long `long_before` is replaced by it
+pub fn projected_share(open_interest: Decimal, long_before: Decimal, long_after: Decimal) -> (Decimal, Decimal) {
+    let open_interest_after = (open_interest - long_before + long_after).max(Decimal::ZERO);
+    let share = if open_interest_after.is_zero() {
+        Decimal::ZERO
+    } else {
+        long_after / open_interest_after * Decimal::ONE_HUNDRED
+    };
+    (share, open_interest_after)
+}
+
+/// Realized P&L of an account as its fills land, against the best it has been
+#[derive(Debug, Clone, Default)]
+pub struct AccountRisk {
+    /// Realized P&L by symbol, as of each symbol's last fill
+    pub realized_pnl: HashMap<String, Decimal>,
+    /// Highest total realized P&L seen since the engine started
+    pub peak_pnl: Decimal,
+    /// Sequence of the last fill applied
+    pub sequence: u64,
+}
+
+impl AccountRisk {
+    pub fn total_pnl(&self) -> Decimal {
+        self.realized_pnl.values().copied().sum()
+    }
+
+    /// How far total realized P&L is below its peak
+    pub fn drawdown(&self) -> Decimal {
+        (self.peak_pnl - self.total_pnl()).max(Decimal::ZERO)
+    }
+
+    /// Take in one fill's position; returns the drawdown after it
+    pub fn apply(&mut self, symbol: &str, realized_pnl: Decimal, sequence: u64) -> Decimal {
+        self.realized_pnl.insert(symbol.to_string(), realized_pnl);
+        self.peak_pnl = self.peak_pnl.max(self.total_pnl());
+        self.sequence = sequence;
+        self.drawdown()
+    }
+}
+
+// =====================================================
+// RISK LIMITS
+// =====================================================
+
+pub struct RiskLimits {
+    concentration: ConcentrationConfig,
+    /// Drawdown from peak realized P&L that raises an alert; zero disables
+    drawdown_alert: Decimal,
+    position_keeper: Arc<PositionKeeper>,
+    accounts: RwLock<HashMap<Uuid, AccountRisk>>,
+}
+
+impl RiskLimits {
+    pub fn new(concentration: ConcentrationConfig, drawdown_alert: Decimal, position_keeper: Arc<PositionKeeper>) -> Self {
+        Self {
+            concentration,
+            drawdown_alert,
+            position_keeper,
+            accounts: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The limit a buy of `quantity` would breach, if any: the account's share of the symbol's
+    /// open interest, or the symbol's platform-wide cap. Only orders that grow a long position
+    /// are held to the limits.
+    pub async fn check_concentration(
+        &self,
+        auth: &AuthContext,
+        symbol: &str,
+        side: &str,
+        quantity: Decimal,
+    ) -> Option<ConcentrationBreach> {
+        let cap = self.concentration.symbol_caps.get(symbol).copied();
+        if (self.concentration.max_share_pct.is_zero() && cap.is_none()) || side != "buy" {
+            return None;
+        }
+
+        let net_quantity = self.position_keeper.net_quantity(auth.account_id, symbol).await;
+        let long_before = net_quantity.max(Decimal::ZERO);
+        let long_after = (net_quantity + quantity).max(Decimal::ZERO);
+        if long_after <= long_before {
+            return None;
+        }
+
+        let open_interest = self.position_keeper.open_interest(symbol).await;
+        let (share, open_interest_after) = projected_share(open_interest, long_before, long_after);
+
+        let reason = self
+            .share_breach(symbol, share, open_interest_after)
+            .or_else(|| {
+                cap.filter(|cap| open_interest_after > *cap).map(|cap| format!(
+                    "Open interest in {} would be {}, platform cap is {}",
+                    symbol, open_interest_after, cap
+                ))
+            })?;
+
+        let action = if auth.has_permission(permissions::RISK_OVERRIDE) {
+            BreachAction::Overridden
+        } else if self.concentration.mode == ConcentrationMode::Flag {
+            BreachAction::Flagged
+        } else {
+            BreachAction::Rejected
+        };
+
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.concentration_breaches_total.with_label_values(&[symbol, action.as_str()]).inc();
+        }
+        if action != BreachAction::Rejected {
+            tracing::warn!(
+                account_id = %auth.account_id,
+                symbol,
+                action = action.as_str(),
+                "Concentration limit breached: {}", reason
+            );
+        }
+
+        Some(ConcentrationBreach { reason, action })
+    }
+
+    fn share_breach(&self, symbol: &str, share: Decimal, open_interest_after: Decimal) -> Option<String> {
+        let limit = self.concentration.max_share_pct;
+        if limit.is_zero() || open_interest_after < self.concentration.min_open_interest || share <= limit {
+            return None;
+        }
+
+        Some(format!(
+            "Position would be {}% of open interest in {}, limit is {}%",
+            share.round_dp(2),
+            symbol,
+            limit
+        ))
+    }
+}
+
+/// Post-trade: drawdown and the concentration an account ended up with, updated with each fill
+#[async_trait]
+impl FillSubscriber for RiskLimits {
+    async fn on_fill(&self, event: &FillEvent) {
+        let drawdown = self
+            .accounts
+            .write()
+            .await
+            .entry(event.account_id)
+            .or_default()
+            .apply(&event.symbol, event.position.realized_pnl, event.sequence);
+
+        if !self.drawdown_alert.is_zero() && drawdown > self.drawdown_alert {
+            tracing::warn!(
+                account_id = %event.account_id,
+                order_id = ?event.order_id,
+                symbol = %event.symbol,
+                side = %event.side,
+                quantity = %event.quantity,
+                price = %event.price,
+                drawdown = %drawdown,
+                limit = %self.drawdown_alert,
+                "Drawdown alert after fill"
+            );
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.post_trade_alerts_total.with_label_values(&["drawdown"]).inc();
+            }
+        }
+
+        let long = event.position.net_quantity.max(Decimal::ZERO);
+        if long.is_zero() {
+            return;
+        }
+        let open_interest = self.position_keeper.open_interest(&event.symbol).await;
+        let (share, _) = projected_share(open_interest, long, long);
+        if let Some(reason) = self.share_breach(&event.symbol, share, open_interest) {
+            tracing::warn!(account_id = %event.account_id, symbol = %event.symbol, "Concentration after fill: {}", reason);
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.post_trade_alerts_total.with_label_values(&["concentration"]).inc();
+            }
+        }
+    }
+}
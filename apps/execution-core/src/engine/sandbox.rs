@@ -0,0 +1,384 @@
+//! Sandbox (Demo) Accounts
+//! Ephemeral onboarding accounts with a starting balance and automatic reset
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+use crate::clock::SharedClock;
+use crate::engine::{Ledger, OrderProcessor, PositionKeeper};
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Sandbox accounts can never log in with a password; tokens are minted by the onboarding flow
+const UNUSABLE_PASSWORD_HASH: &str = "!sandbox";
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    pub default_balance: Decimal,
+    pub max_balance: Decimal,
+    pub default_ttl_secs: i64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            default_balance: dec!(100000),
+            max_balance: dec!(10000000),
+            default_ttl_secs: 86_400,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvisionRequest {
+    #[serde(alias = "session_id", default)]
+    pub session_id: Option<String>,
+    #[serde(alias = "starting_balance", default)]
+    pub starting_balance: Option<Decimal>,
+    #[serde(alias = "reset_interval_secs", default)]
+    pub reset_interval_secs: Option<i32>,
+    #[serde(alias = "ttl_secs", default)]
+    pub ttl_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SandboxAccount {
+    pub account_id: Uuid,
+    pub session_id: Option<String>,
+    pub starting_balance: Decimal,
+    pub reset_interval_secs: Option<i32>,
+    pub reset_count: i32,
+    pub last_reset_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProvisionedSandbox {
+    pub account_id: Uuid,
+    pub username: String,
+    pub starting_balance: Decimal,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResetSummary {
+    pub account_id: Uuid,
+    pub orders_cancelled: u64,
+    pub positions_flattened: u64,
+    pub balance: Decimal,
+}
+
+// =====================================================
+// SANDBOX MANAGER
+// =====================================================
+
+pub struct SandboxManager {
+    pool: PgPool,
+    ledger: Arc<Ledger>,
+    order_processor: Arc<OrderProcessor>,
+    position_keeper: Arc<PositionKeeper>,
+    config: SandboxConfig,
+    clock: SharedClock,
+}
+
+impl SandboxManager {
+    pub fn new(
+        pool: PgPool,
+        ledger: Arc<Ledger>,
+        order_processor: Arc<OrderProcessor>,
+        position_keeper: Arc<PositionKeeper>,
+        config: SandboxConfig,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            pool,
+            ledger,
+            order_processor,
+            position_keeper,
+            config,
+            clock,
+        }
+    }
+
+    /// Create a new demo account with the trader role and a funded balance
+    pub async fn provision(
+        &self,
+        auth: &AuthContext,
+        req: ProvisionRequest,
+    ) -> Result<ProvisionedSandbox, EngineError> {
+        if !auth.has_permission(permissions::SANDBOX_MANAGE) {
+            return Err(EngineError::Auth(
+                "sandbox:manage required".into()
+            ));
+        }
+
+        let balance = req.starting_balance.unwrap_or(self.config.default_balance);
+        if balance < dec!(0) || balance > self.config.max_balance {
+            return Err(EngineError::Validation(format!(
+                "starting_balance must be between 0 and {}",
+                self.config.max_balance
+            )));
+        }
+
+        if matches!(req.reset_interval_secs, Some(secs) if secs <= 0) {
+            return Err(EngineError::Validation("reset_interval_secs must be positive".into()));
+        }
+
+        let ttl = req.ttl_secs.unwrap_or(self.config.default_ttl_secs);
+        if ttl <= 0 || ttl > self.config.default_ttl_secs * 7 {
+            return Err(EngineError::Validation("ttl_secs out of range".into()));
+        }
+
+        let account_id = Uuid::new_v4();
+        let username = format!("sandbox-{}", &account_id.simple().to_string()[..12]);
+        let now = self.clock.now();
+        let expires_at = now + Duration::seconds(ttl);
+
+        let mut tx = self.pool.begin().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        sqlx::query(
+            r#"INSERT INTO accounts (id, username, email, password_hash, role_id,
+                                     is_active, is_verified, balance, available_balance)
+               VALUES ($1, $2, $3, $4, (SELECT id FROM roles WHERE name = 'trader'),
+                       true, true, $5, $5)"#
+        )
+            .bind(account_id)
+            .bind(&username)
+            .bind(format!("{}@sandbox.local", username))
+            .bind(UNUSABLE_PASSWORD_HASH)
+            .bind(balance)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        sqlx::query(
+            r#"INSERT INTO sandbox_accounts (account_id, provisioned_by, session_id,
+                                             starting_balance, reset_interval_secs,
+                                             last_reset_at, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)"#
+        )
+            .bind(account_id)
+            .bind(auth.account_id)
+            .bind(&req.session_id)
+            .bind(balance)
+            .bind(req.reset_interval_secs)
+            .bind(now)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        self.ledger
+            .deposit(&mut tx, account_id, self.ledger.default_quote(), balance, "sandbox_funding")
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        tracing::info!(account_id = %account_id, "Sandbox account provisioned");
+
+        Ok(ProvisionedSandbox {
+            account_id,
+            username,
+            starting_balance: balance,
+            expires_at,
+        })
+    }
+
+    /// Reset on request - allowed for the sandbox account itself or a sandbox manager
+    pub async fn reset(
+        &self,
+        auth: &AuthContext,
+        account_id: Uuid,
+    ) -> Result<ResetSummary, EngineError> {
+        if account_id != auth.account_id && !auth.has_permission(permissions::SANDBOX_MANAGE) {
+            return Err(EngineError::Auth(
+                "Cannot reset another account".into()
+            ));
+        }
+
+        let sandbox = self.get(account_id).await?
+            .filter(|s| !s.expired)
+            .ok_or_else(|| EngineError::Validation("Account not found".into()))?;
+
+        self.reset_account(&sandbox)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    pub async fn get(&self, account_id: Uuid) -> Result<Option<SandboxAccount>, EngineError> {
+        sqlx::query_as(
+            r#"SELECT account_id, session_id, starting_balance, reset_interval_secs,
+                      reset_count, last_reset_at, expires_at, expired
+               FROM sandbox_accounts WHERE account_id = $1"#
+        )
+            .bind(account_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    /// Cancel open orders, flatten positions and restore the starting balance atomically
+    async fn reset_account(&self, sandbox: &SandboxAccount) -> anyhow::Result<ResetSummary> {
+        let mut tx = self.pool.begin().await?;
+
+        let orders_cancelled = cancel_open_orders(&mut tx, sandbox.account_id).await?;
+        self.ledger.release_account(&mut tx, sandbox.account_id).await?;
+        self.ledger.zero_account(&mut tx, sandbox.account_id).await?;
+        self.ledger
+            .deposit(&mut tx, sandbox.account_id, self.ledger.default_quote(), sandbox.starting_balance, "sandbox_funding")
+            .await?;
+
+        let positions_flattened = sqlx::query("DELETE FROM positions WHERE account_id = $1")
+            .bind(sandbox.account_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        sqlx::query(
+            r#"UPDATE accounts
+               SET balance = $2, available_balance = $2, margin_used = 0, updated_at = NOW()
+               WHERE id = $1"#
+        )
+            .bind(sandbox.account_id)
+            .bind(sandbox.starting_balance)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"UPDATE sandbox_accounts
+               SET reset_count = reset_count + 1, last_reset_at = $2
+               WHERE account_id = $1"#
+        )
+            .bind(sandbox.account_id)
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        self.order_processor.evict_account(sandbox.account_id).await;
+        self.position_keeper.evict_account(sandbox.account_id).await;
+
+        tracing::info!(
+            account_id = %sandbox.account_id,
+            orders_cancelled,
+            positions_flattened,
+            "Sandbox account reset"
+        );
+
+        Ok(ResetSummary {
+            account_id: sandbox.account_id,
+            orders_cancelled,
+            positions_flattened,
+            balance: sandbox.starting_balance,
+        })
+    }
+
+    /// Scheduled pass: reset accounts whose interval elapsed and retire expired ones
+    pub async fn run_scheduled(&self) -> anyhow::Result<(usize, usize)> {
+        let due: Vec<SandboxAccount> = sqlx::query_as(
+            r#"SELECT account_id, session_id, starting_balance, reset_interval_secs,
+                      reset_count, last_reset_at, expires_at, expired
+               FROM sandbox_accounts
+               WHERE NOT expired
+                 AND expires_at > $1
+                 AND reset_interval_secs IS NOT NULL
+                 AND last_reset_at + reset_interval_secs * INTERVAL '1 second' <= $1"#
+        )
+            .bind(self.clock.now())
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut reset = 0;
+        for sandbox in &due {
+            match self.reset_account(sandbox).await {
+                Ok(_) => reset += 1,
+                Err(e) => tracing::error!(account_id = %sandbox.account_id, "Sandbox reset failed: {}", e),
+            }
+        }
+
+        let expired = self.expire_accounts().await?;
+        Ok((reset, expired))
+    }
+
+    async fn expire_accounts(&self) -> anyhow::Result<usize> {
+        let expired: Vec<(Uuid,)> = sqlx::query_as(
+            r#"SELECT account_id FROM sandbox_accounts
+               WHERE NOT expired AND expires_at <= $1"#
+        )
+            .bind(self.clock.now())
+            .fetch_all(&self.pool)
+            .await?;
+
+        for (account_id,) in &expired {
+            let mut tx = self.pool.begin().await?;
+
+            cancel_open_orders(&mut tx, *account_id).await?;
+            self.ledger.release_account(&mut tx, *account_id).await?;
+
+            sqlx::query("UPDATE accounts SET is_active = false, updated_at = NOW() WHERE id = $1")
+                .bind(account_id)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query("UPDATE sandbox_accounts SET expired = true WHERE account_id = $1")
+                .bind(account_id)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+
+            self.order_processor.evict_account(*account_id).await;
+            self.position_keeper.evict_account(*account_id).await;
+            tracing::info!(account_id = %account_id, "Sandbox account expired");
+        }
+
+        Ok(expired.len())
+    }
+}
+
+/// Cancel every working order, strategy and armed trigger of an account
+pub(crate) async fn cancel_open_orders(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    account_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE order_strategies SET status = 'cancelled', updated_at = NOW()
+           WHERE account_id = $1 AND status = 'pending'"#
+    )
+        .bind(account_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query(
+        r#"UPDATE order_triggers SET status = 'cancelled'
+           WHERE account_id = $1 AND status = 'armed'"#
+    )
+        .bind(account_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let result = sqlx::query(
+        r#"UPDATE orders SET status = 'cancelled', updated_at = NOW()
+           WHERE account_id = $1 AND status IN ('waiting', 'pending', 'partially_filled')"#
+    )
+        .bind(account_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,168 @@
+//! Symbol Sharding
+//! Several active engines, each owning a set of symbols: their order flow, ticks, book and
+//! scheduled slices. Owned symbols are resolved at startup, listed explicitly or taken from the
+//! instruments whose hash falls in the instance's bucket range. A rebalance moves a symbol to
+//! another running instance and records the move, so it outlives restarts.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::BTreeSet;
+use std::sync::{RwLock, RwLockReadGuard};
+use uuid::Uuid;
+
+/// Reject code for order flow that reached an instance not owning its symbol
+pub const WRONG_SHARD: &str = "WRONG_SHARD";
+
+/// Orders a mass cancel closed, for every instance to drop from its book
+pub const EVICTIONS_SUBJECT: &str = "system.evictions";
+
+/// Orders closed by an instance that may not own their symbols. Mass and strategy cancels run
+/// on a shared instance, while the orders rest with the instances owning their symbols.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Evictions {
+    pub order_ids: Vec<Uuid>,
+    /// Triggers cancelled along with them
+    #[serde(default)]
+    pub disarmed: Vec<Uuid>,
+    #[serde(default)]
+    pub strategy_ids: Vec<Uuid>,
+}
+
+impl Evictions {
+    pub fn is_empty(&self) -> bool {
+        self.order_ids.is_empty() && self.strategy_ids.is_empty()
+    }
+}
+
+/// How an instance's symbols are chosen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShardSpec {
+    Symbols(BTreeSet<String>),
+    /// Buckets `first..=last` of `buckets`
+    HashRange { first: u32, last: u32, buckets: u32 },
+}
+
+impl ShardSpec {
+    /// Parse `SHARD_SYMBOLS` (`SYMBOL[,SYMBOL...]`) or `SHARD_HASH_RANGE` (`first-last/buckets`,
+    /// e.g. `0-7/16`); `None` when neither is set
+    pub fn parse(symbols: &str, hash_range: &str) -> anyhow::Result<Option<Self>> {
+        let (symbols, hash_range) = (symbols.trim(), hash_range.trim());
+        match (symbols.is_empty(), hash_range.is_empty()) {
+            (true, true) => Ok(None),
+            (false, false) => anyhow::bail!("set SHARD_SYMBOLS or SHARD_HASH_RANGE, not both"),
+            (false, true) => {
+                let mut owned = BTreeSet::new();
+                for symbol in symbols.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                    if !is_subject_token(symbol) {
+                        anyhow::bail!("shard symbol {:?} cannot be a NATS subject token", symbol);
+                    }
+                    if !owned.insert(symbol.to_string()) {
+                        anyhow::bail!("duplicate shard symbol {}", symbol);
+                    }
+                }
+                Ok(Some(Self::Symbols(owned)))
+            }
+            (true, false) => {
+                let invalid = || anyhow::anyhow!("SHARD_HASH_RANGE {:?} is not first-last/buckets", hash_range);
+                let (range, buckets) = hash_range.split_once('/').ok_or_else(invalid)?;
+                let (first, last) = range.split_once('-').ok_or_else(invalid)?;
+                let parse = |n: &str| n.trim().parse::<u32>().map_err(|_| invalid());
+                let (first, last, buckets) = (parse(first)?, parse(last)?, parse(buckets)?);
+                if buckets == 0 || first > last || last >= buckets {
+                    anyhow::bail!("SHARD_HASH_RANGE {:?} must satisfy first <= last < buckets", hash_range);
+                }
+                Ok(Some(Self::HashRange { first, last, buckets }))
+            }
+        }
+    }
+
+    /// The symbols this instance owns: an explicit list as given, a hash range as the listed
+    /// instruments falling in it, with the symbols rebalances moved here added and those they
+    /// moved to another instance removed
+    pub async fn resolve(&self, pool: &PgPool, instance_id: &str) -> Result<Shard, sqlx::Error> {
+        let mut symbols = match self {
+            Self::Symbols(symbols) => symbols.clone(),
+            Self::HashRange { first, last, buckets } => {
+                let listed: Vec<String> = sqlx::query_scalar("SELECT symbol FROM instruments")
+                    .fetch_all(pool)
+                    .await?;
+                listed
+                    .into_iter()
+                    .filter(|symbol| (*first..=*last).contains(&bucket(symbol, *buckets)))
+                    .filter(|symbol| {
+                        let routable = is_subject_token(symbol);
+                        if !routable {
+                            tracing::warn!(symbol = %symbol, "Symbol cannot be a NATS subject token, not sharded");
+                        }
+                        routable
+                    })
+                    .collect()
+            }
+        };
+
+        let assignments: Vec<(String, String)> = sqlx::query_as("SELECT symbol, instance_id FROM shard_assignments")
+            .fetch_all(pool)
+            .await?;
+        for (symbol, assigned_to) in assignments {
+            if assigned_to == instance_id {
+                symbols.insert(symbol);
+            } else {
+                symbols.remove(&symbol);
+            }
+        }
+        Ok(Shard { symbols: RwLock::new(symbols) })
+    }
+}
+
+/// Bucket of `symbol` among `buckets`: FNV-1a over its bytes, the same in every process
+pub fn bucket(symbol: &str, buckets: u32) -> u32 {
+    let hash = symbol
+        .bytes()
+        .fold(0x811c_9dc5_u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193));
+    hash % buckets
+}
+
+/// Symbols go into subjects as a single token
+pub fn is_subject_token(symbol: &str) -> bool {
+    !symbol.is_empty() && !symbol.contains(['.', '*', '>', ' '])
+}
+
+/// The symbols an instance owns; a rebalance moves one to another instance while both run
+#[derive(Debug, Default)]
+pub struct Shard {
+    symbols: RwLock<BTreeSet<String>>,
+}
+
+impl Shard {
+    pub fn owns(&self, symbol: &str) -> bool {
+        self.read().contains(symbol)
+    }
+
+    /// Owned symbols, in order
+    pub fn symbols(&self) -> Vec<String> {
+        self.read().iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Take `symbol` on; false if already owned
+    pub fn acquire(&self, symbol: &str) -> bool {
+        self.symbols.write().unwrap_or_else(|e| e.into_inner()).insert(symbol.to_string())
+    }
+
+    /// Give `symbol` up; false if not owned
+    pub fn release(&self, symbol: &str) -> bool {
+        self.symbols.write().unwrap_or_else(|e| e.into_inner()).remove(symbol)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, BTreeSet<String>> {
+        self.symbols.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
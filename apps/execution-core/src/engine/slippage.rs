@@ -0,0 +1,83 @@
+//! Simulated Execution
+//! Market orders execute at the tick price moved against them by the configured slippage model:
+//! a fixed cost plus size impact, half a quoted spread, or impact growing with the order's share
+//! of the volume the tick traded. Orders reach the simulated market after a configured latency.
+
+use crate::engine::account_settings::MAX_SLIPPAGE_BPS;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::MathematicalOps;
+use rust_decimal::Decimal;
+
+/// How market order slippage is worked out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SlippageMode {
+    /// `base_bps` plus `impact_bps_per_unit` for every unit
+    #[default]
+    Fixed,
+    /// `base_bps` plus half of `spread_bps`: the order crosses from the last price to the far
+    /// side of the quote
+    Spread,
+    /// `base_bps` plus `volume_impact_bps` times the square root of the order's quantity over
+    /// the size the tick traded; fixed without a traded size
+    VolumeImpact,
+}
+
+impl SlippageMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "fixed" => Some(Self::Fixed),
+            "spread" => Some(Self::Spread),
+            "volume_impact" => Some(Self::VolumeImpact),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SlippageModel {
+    pub mode: SlippageMode,
+    /// Paid by every market order, in basis points
+    pub base_bps: Decimal,
+    /// Added per unit of quantity, in basis points
+    pub impact_bps_per_unit: Decimal,
+    /// Quoted spread around the last price, in basis points
+    pub spread_bps: Decimal,
+    /// Added for an order as large as the tick's traded size, in basis points
+    pub volume_impact_bps: Decimal,
+    /// Orders fill only on ticks at least this long after they were placed
+    pub latency: Duration,
+}
+
+impl SlippageModel {
+    /// Slippage of an order of `quantity` on a tick that traded `traded`, capped like the
+    /// account slippage allowance
+    pub fn bps(&self, quantity: Decimal, traded: Option<Decimal>) -> Decimal {
+        let fixed = || self.base_bps + self.impact_bps_per_unit * quantity.abs();
+        let bps = match self.mode {
+            SlippageMode::Fixed => fixed(),
+            SlippageMode::Spread => self.base_bps + self.spread_bps / Decimal::TWO,
+            SlippageMode::VolumeImpact => match traded.filter(|t| *t > Decimal::ZERO) {
+                Some(traded) => {
+                    let share = (quantity.abs() / traded).sqrt().unwrap_or_default();
+                    self.base_bps + self.volume_impact_bps * share
+                }
+                None => fixed(),
+            },
+        };
+        bps.clamp(Decimal::ZERO, MAX_SLIPPAGE_BPS)
+    }
+
+    /// Price a market order of `quantity` executes at on a tick at `tick_price` that traded
+    /// `traded`: higher for buys, lower for sells
+    pub fn fill_price(&self, side: &str, quantity: Decimal, tick_price: Decimal, traded: Option<Decimal>) -> Decimal {
+        let cost = tick_price * self.bps(quantity, traded) / Decimal::from(10_000);
+        let price = if side == "buy" { tick_price + cost } else { tick_price - cost };
+        price.round_dp(8)
+    }
+
+    /// Whether an order placed at `placed_at` has reached the market by `now`
+    pub fn arrived(&self, placed_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        placed_at + self.latency <= now
+    }
+}
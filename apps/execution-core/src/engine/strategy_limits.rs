@@ -0,0 +1,230 @@
+//! Strategy Limits
+//! Kill switches and order limits keyed by the strategy_tag an order carries, so one
+//! misbehaving algo can be stopped without halting the whole account
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+use crate::clock::SharedClock;
+use crate::engine::account_settings::STRATEGY_TAG_KEY;
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Same bound as the strategy_tag account default
+const MAX_STRATEGY_TAG_LEN: usize = 64;
+
+/// Controls on the orders of one strategy tag; a tag without a row is unrestricted
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct StrategyLimit {
+    #[serde(alias = "strategyTag")]
+    pub strategy_tag: String,
+    /// Kill switch: every new order under the tag is rejected
+    #[serde(default)]
+    pub halted: bool,
+    #[serde(alias = "maxOrdersPerMinute", default)]
+    pub max_orders_per_minute: Option<i32>,
+    /// Notional of the orders submitted under the tag per UTC day, at their limit or stop price
+    /// and at the last trade for market orders
+    #[serde(alias = "maxNotionalPerDay", default)]
+    pub max_notional_per_day: Option<Decimal>,
+}
+
+impl StrategyLimit {
+    fn validate(&self) -> Result<(), String> {
+        let tag = self.strategy_tag.trim();
+        if tag.is_empty() || tag.len() > MAX_STRATEGY_TAG_LEN {
+            return Err(format!("strategy_tag must be 1 to {} bytes", MAX_STRATEGY_TAG_LEN));
+        }
+        if self.max_orders_per_minute.is_some_and(|max| max <= 0) {
+            return Err("max_orders_per_minute must be positive".into());
+        }
+        if self.max_notional_per_day.is_some_and(|max| max <= Decimal::ZERO) {
+            return Err("max_notional_per_day must be positive".into());
+        }
+        Ok(())
+    }
+
+    fn counts_usage(&self) -> bool {
+        self.max_orders_per_minute.is_some() || self.max_notional_per_day.is_some()
+    }
+
+    /// Which limit an order breaches, given the tag's usage with that order counted
+    pub fn exceeded(&self, orders_this_minute: i32, notional_today: Decimal) -> Option<String> {
+        if let Some(max) = self.max_orders_per_minute.filter(|max| orders_this_minute > *max) {
+            return Some(format!("Strategy {} is limited to {} orders per minute", self.strategy_tag, max));
+        }
+        if let Some(max) = self.max_notional_per_day.filter(|max| notional_today > *max) {
+            return Some(format!("Strategy {} is limited to {} notional per day", self.strategy_tag, max));
+        }
+        None
+    }
+}
+
+/// Strategy tag an order's metadata carries, as set by the client or the account default
+pub fn strategy_tag(metadata: Option<&serde_json::Value>) -> Option<&str> {
+    metadata?.get(STRATEGY_TAG_KEY)?.as_str()
+}
+
+// =====================================================
+// STRATEGY LIMITS
+// =====================================================
+
+pub struct StrategyLimits {
+    pool: PgPool,
+    clock: SharedClock,
+    /// Limits by account, including accounts that have none
+    cache: RwLock<HashMap<Uuid, Vec<StrategyLimit>>>,
+}
+
+impl StrategyLimits {
+    pub fn new(pool: PgPool, clock: SharedClock) -> Self {
+        Self {
+            pool,
+            clock,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The caller's strategy limits
+    pub async fn list(&self, auth: &AuthContext) -> Result<Vec<StrategyLimit>, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_READ) {
+            return Err(EngineError::Auth(
+                "orders:read required".into()
+            ));
+        }
+
+        self.limits(auth.account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    /// Set the limits of one of the caller's tags, halting or resuming it
+    pub async fn update(
+        &self,
+        auth: &AuthContext,
+        mut limit: StrategyLimit,
+    ) -> Result<StrategyLimit, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CREATE) {
+            return Err(EngineError::Auth(
+                "orders:create required".into()
+            ));
+        }
+
+        limit.validate().map_err(EngineError::Validation)?;
+        limit.strategy_tag = limit.strategy_tag.trim().to_string();
+
+        let stored: StrategyLimit = sqlx::query_as(
+            r#"INSERT INTO strategy_tag_limits (account_id, strategy_tag, halted,
+                                                max_orders_per_minute, max_notional_per_day)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT (account_id, strategy_tag) DO UPDATE SET
+                   halted = $3,
+                   max_orders_per_minute = $4,
+                   max_notional_per_day = $5,
+                   updated_at = NOW()
+               RETURNING strategy_tag, halted, max_orders_per_minute, max_notional_per_day"#
+        )
+            .bind(auth.account_id)
+            .bind(&limit.strategy_tag)
+            .bind(limit.halted)
+            .bind(limit.max_orders_per_minute)
+            .bind(limit.max_notional_per_day)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        // Loads the account's other tags too if they were not cached yet
+        let mut limits = self.limits(auth.account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        limits.retain(|l| l.strategy_tag != stored.strategy_tag);
+        limits.push(stored.clone());
+        self.cache.write().await.insert(auth.account_id, limits);
+
+        tracing::info!(
+            account_id = %auth.account_id,
+            strategy_tag = %stored.strategy_tag,
+            halted = stored.halted,
+            "Strategy limits updated"
+        );
+        Ok(stored)
+    }
+
+    /// Why the tag may not place orders at all right now, if it may not
+    pub async fn check_halted(&self, account_id: Uuid, tag: &str) -> Result<Option<String>, sqlx::Error> {
+        let halted = self.limit(account_id, tag).await?.is_some_and(|l| l.halted);
+        Ok(halted.then(|| format!("Strategy {} is halted", tag)))
+    }
+
+    /// Count an order of `notional` against the tag's limits in the order's transaction.
+    /// Returns the breached limit, if any; the caller rolls back so the order is not counted.
+    pub async fn consume(
+        &self,
+        conn: &mut PgConnection,
+        account_id: Uuid,
+        tag: &str,
+        notional: Decimal,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let Some(limit) = self.limit(account_id, tag).await?.filter(StrategyLimit::counts_usage) else {
+            return Ok(None);
+        };
+
+        let now = self.clock.now();
+        let (orders_this_minute, notional_today): (i32, Decimal) = sqlx::query_as(
+            r#"INSERT INTO strategy_tag_usage (account_id, strategy_tag, day, notional, minute, minute_orders)
+               VALUES ($1, $2, $3, $4, $5, 1)
+               ON CONFLICT (account_id, strategy_tag, day) DO UPDATE SET
+                   notional = strategy_tag_usage.notional + $4,
+                   minute_orders = CASE WHEN strategy_tag_usage.minute = $5
+                                        THEN strategy_tag_usage.minute_orders + 1 ELSE 1 END,
+                   minute = $5
+               RETURNING minute_orders, notional"#
+        )
+            .bind(account_id)
+            .bind(tag)
+            .bind(now.date_naive())
+            .bind(notional)
+            .bind(minute_of(now))
+            .fetch_one(conn)
+            .await?;
+
+        Ok(limit.exceeded(orders_this_minute, notional_today))
+    }
+
+    async fn limit(&self, account_id: Uuid, tag: &str) -> Result<Option<StrategyLimit>, sqlx::Error> {
+        let limits = self.limits(account_id).await?;
+        Ok(limits.into_iter().find(|l| l.strategy_tag == tag))
+    }
+
+    /// Limits of the account, loaded once and then served from memory
+    async fn limits(&self, account_id: Uuid) -> Result<Vec<StrategyLimit>, sqlx::Error> {
+        if let Some(limits) = self.cache.read().await.get(&account_id) {
+            return Ok(limits.clone());
+        }
+
+        let limits: Vec<StrategyLimit> = sqlx::query_as(
+            r#"SELECT strategy_tag, halted, max_orders_per_minute, max_notional_per_day
+               FROM strategy_tag_limits WHERE account_id = $1 ORDER BY strategy_tag"#
+        )
+            .bind(account_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        self.cache.write().await.insert(account_id, limits.clone());
+        Ok(limits)
+    }
+
+    pub async fn evict(&self, account_id: Uuid) {
+        self.cache.write().await.remove(&account_id);
+    }
+}
+
+/// Start of the minute `now` falls in, which the per-minute order count is kept for
+fn minute_of(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.duration_trunc(TimeDelta::minutes(1)).unwrap_or(now)
+}
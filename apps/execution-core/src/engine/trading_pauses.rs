@@ -0,0 +1,266 @@
+//! Trading Pauses
+//! Self-imposed pauses an account holder sets on their own trading, enforced on every new order
+
+use crate::auth::{AuthContext, permissions};
+use crate::engine::error::EngineError;
+use crate::clock::SharedClock;
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+const MAX_PAUSE_WINDOWS: usize = 16;
+
+/// Longest pause a single request can add (one year)
+const MAX_PAUSE_MINUTES: i64 = 366 * 24 * 60;
+
+/// Daily window in which new orders are blocked, as UTC "HH:MM" times.
+/// A window that ends before it starts runs past midnight.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PauseWindow {
+    pub start: String,
+    pub end: String,
+}
+
+impl PauseWindow {
+    fn bounds(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let start = NaiveTime::parse_from_str(&self.start, "%H:%M").ok()?;
+        let end = NaiveTime::parse_from_str(&self.end, "%H:%M").ok()?;
+        Some((start, end))
+    }
+
+    /// Start inclusive, end exclusive
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        match self.bounds() {
+            Some((start, end)) if start <= end => start <= time && time < end,
+            Some((start, end)) => time >= start || time < end,
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PauseSchedule {
+    #[serde(default)]
+    pub windows: Vec<PauseWindow>,
+    /// No new orders for the rest of the UTC day after this many losing fills
+    #[serde(alias = "maxDailyLosses", default)]
+    pub max_daily_losses: Option<i32>,
+    /// No new orders at all until then
+    #[serde(alias = "pausedUntil", default)]
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
+impl PauseSchedule {
+    /// Why an order placed at `now` is blocked, given the day's losing fills so far
+    pub fn blocks(&self, now: DateTime<Utc>, losses_today: i32) -> Option<String> {
+        if let Some(until) = self.paused_until.filter(|until| *until > now) {
+            return Some(format!("Trading paused until {}", until.to_rfc3339()));
+        }
+
+        let time = now.time();
+        if let Some(window) = self.windows.iter().find(|w| w.contains(time)) {
+            return Some(format!("Trading paused between {} and {} UTC", window.start, window.end));
+        }
+
+        match self.max_daily_losses {
+            Some(limit) if losses_today >= limit => {
+                Some(format!("Trading paused for the day after {} losing fills", losses_today))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Replaces the caller's windows and loss limit; a pause can be started or extended but never shortened
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PauseRequest {
+    #[serde(default)]
+    pub windows: Vec<PauseWindow>,
+    #[serde(alias = "maxDailyLosses", default)]
+    pub max_daily_losses: Option<i32>,
+    /// Pause all trading for this long from now
+    #[serde(alias = "pauseForMinutes", default)]
+    pub pause_for_minutes: Option<i64>,
+}
+
+impl PauseRequest {
+    fn validate(&self) -> Result<(), String> {
+        if self.windows.len() > MAX_PAUSE_WINDOWS {
+            return Err(format!("At most {} pause windows", MAX_PAUSE_WINDOWS));
+        }
+        for window in &self.windows {
+            match window.bounds() {
+                Some((start, end)) if start != end => {}
+                Some(_) => return Err("Pause window start and end must differ".into()),
+                None => return Err("Pause window times must be HH:MM".into()),
+            }
+        }
+        if self.max_daily_losses.is_some_and(|limit| limit <= 0) {
+            return Err("max_daily_losses must be positive".into());
+        }
+        if self.pause_for_minutes.is_some_and(|m| m <= 0 || m > MAX_PAUSE_MINUTES) {
+            return Err(format!("pause_for_minutes must be between 1 and {}", MAX_PAUSE_MINUTES));
+        }
+        Ok(())
+    }
+}
+
+/// Count a fill that lowered the account's realized P&L, in the fill's transaction
+pub async fn record_loss(conn: &mut PgConnection, account_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO account_daily_losses (account_id, day, losses)
+           VALUES ($1, (NOW() AT TIME ZONE 'UTC')::date, 1)
+           ON CONFLICT (account_id, day) DO UPDATE SET losses = account_daily_losses.losses + 1"#
+    )
+        .bind(account_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+type ScheduleRow = (Json<Vec<PauseWindow>>, Option<i32>, Option<DateTime<Utc>>);
+
+fn from_row((windows, max_daily_losses, paused_until): ScheduleRow) -> PauseSchedule {
+    PauseSchedule { windows: windows.0, max_daily_losses, paused_until }
+}
+
+// =====================================================
+// TRADING PAUSES
+// =====================================================
+
+pub struct TradingPauses {
+    pool: PgPool,
+    clock: SharedClock,
+    /// Schedules by account, including accounts that have none
+    cache: RwLock<HashMap<Uuid, PauseSchedule>>,
+}
+
+impl TradingPauses {
+    pub fn new(pool: PgPool, clock: SharedClock) -> Self {
+        Self {
+            pool,
+            clock,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The caller's pause schedule
+    pub async fn get(&self, auth: &AuthContext) -> Result<PauseSchedule, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_READ) {
+            return Err(EngineError::Auth(
+                "orders:read required".into()
+            ));
+        }
+
+        self.schedule(auth.account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))
+    }
+
+    /// Replace the caller's schedule. Nothing can be loosened while trading is paused,
+    /// and a running pause only ever moves later.
+    pub async fn update(
+        &self,
+        auth: &AuthContext,
+        req: PauseRequest,
+    ) -> Result<PauseSchedule, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CREATE) {
+            return Err(EngineError::Auth(
+                "orders:create required".into()
+            ));
+        }
+
+        req.validate().map_err(EngineError::Validation)?;
+
+        let current = self.schedule(auth.account_id)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+        let changes_rules = req.windows != current.windows || req.max_daily_losses != current.max_daily_losses;
+        if changes_rules && self.check(auth.account_id).await
+            .map_err(|e| EngineError::Storage(e.to_string()))?
+            .is_some()
+        {
+            return Err(EngineError::Risk {
+                code: "TRADING_PAUSED",
+                reason: "Pause schedule cannot be changed while trading is paused".into(),
+            });
+        }
+
+        let pause_until = req.pause_for_minutes.map(|m| self.clock.now() + Duration::minutes(m));
+
+        let stored: ScheduleRow = sqlx::query_as(
+            r#"INSERT INTO account_trading_pauses (account_id, windows, max_daily_losses, paused_until)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (account_id) DO UPDATE SET
+                   windows = $2,
+                   max_daily_losses = $3,
+                   paused_until = GREATEST(account_trading_pauses.paused_until, $4),
+                   updated_at = NOW()
+               RETURNING windows, max_daily_losses, paused_until"#
+        )
+            .bind(auth.account_id)
+            .bind(Json(&req.windows))
+            .bind(req.max_daily_losses)
+            .bind(pause_until)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| EngineError::Storage(e.to_string()))?;
+
+        let schedule = from_row(stored);
+        self.cache.write().await.insert(auth.account_id, schedule.clone());
+        tracing::info!(
+            account_id = %auth.account_id,
+            paused_until = ?schedule.paused_until,
+            "Account trading pauses updated"
+        );
+        Ok(schedule)
+    }
+
+    /// Why the account may not place a new order right now, if it may not
+    pub async fn check(&self, account_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        let schedule = self.schedule(account_id).await?;
+
+        // Losses are only counted against accounts that set a limit
+        let losses_today = match schedule.max_daily_losses {
+            Some(_) => sqlx::query_scalar(
+                r#"SELECT losses FROM account_daily_losses
+                   WHERE account_id = $1 AND day = (NOW() AT TIME ZONE 'UTC')::date"#
+            )
+                .bind(account_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .unwrap_or(0),
+            None => 0,
+        };
+
+        Ok(schedule.blocks(self.clock.now(), losses_today))
+    }
+
+    /// Schedule of the account, loaded once and then served from memory
+    async fn schedule(&self, account_id: Uuid) -> Result<PauseSchedule, sqlx::Error> {
+        if let Some(schedule) = self.cache.read().await.get(&account_id) {
+            return Ok(schedule.clone());
+        }
+
+        let stored: Option<ScheduleRow> = sqlx::query_as(
+            r#"SELECT windows, max_daily_losses, paused_until
+               FROM account_trading_pauses WHERE account_id = $1"#
+        )
+            .bind(account_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let schedule = stored.map(from_row).unwrap_or_default();
+        self.cache.write().await.insert(account_id, schedule.clone());
+        Ok(schedule)
+    }
+
+    pub async fn evict(&self, account_id: Uuid) {
+        self.cache.write().await.remove(&account_id);
+    }
+}
@@ -0,0 +1,214 @@
+//! Conditional Order Triggers
+//! Orders can be armed with a condition that activates or cancels them when it fires
+
+use crate::market_data::{CrossDirection, SymbolSeries};
+
+pub use enthropic_domain::trigger::{TriggerAction, TriggerCondition, TriggerSpec};
+
+use rust_decimal::Decimal;
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone)]
+pub struct OrderTrigger {
+    pub order_id: Uuid,
+    pub account_id: Uuid,
+    pub condition: TriggerCondition,
+    pub action: TriggerAction,
+}
+
+/// Something that happened which armed triggers are evaluated against
+#[derive(Debug, Clone)]
+pub enum TriggerEvent {
+    /// `filled_quantity` is the order's total so far; `completed` once nothing is left
+    OrderFilled { order_id: Uuid, filled_quantity: Decimal, completed: bool },
+    MarketTick { symbol: String, series: SymbolSeries },
+}
+
+/// Whether an event meets a trigger's condition
+pub fn condition_met(condition: &TriggerCondition, event: &TriggerEvent) -> bool {
+    match (condition, event) {
+        (
+            TriggerCondition::ParentFill { parent_order_id, filled_quantity },
+            TriggerEvent::OrderFilled { order_id, filled_quantity: filled, completed },
+        ) => order_id == parent_order_id && filled_quantity.map_or(*completed, |q| *filled >= q),
+        (
+            TriggerCondition::MaCross { fast_period, slow_period, direction, .. },
+            TriggerEvent::MarketTick { symbol, series },
+        ) => condition.watched_symbol() == Some(symbol.as_str())
+            && series.ma_crossed(*fast_period, *slow_period, *direction),
+        (
+            TriggerCondition::PriceVwap { direction, .. },
+            TriggerEvent::MarketTick { symbol, series },
+        ) => condition.watched_symbol() == Some(symbol.as_str()) && series.price_vs_vwap(*direction),
+        _ => false,
+    }
+}
+
+#[derive(FromRow)]
+struct TriggerRow {
+    order_id: Uuid,
+    account_id: Uuid,
+    condition_type: String,
+    parent_order_id: Option<Uuid>,
+    fill_threshold: Option<Decimal>,
+    watch_symbol: Option<String>,
+    fast_period: Option<i32>,
+    slow_period: Option<i32>,
+    direction: Option<String>,
+    action: String,
+}
+
+impl TriggerRow {
+    fn into_trigger(self) -> Option<OrderTrigger> {
+        let condition = match self.condition_type.as_str() {
+            "parent_fill" => TriggerCondition::ParentFill {
+                parent_order_id: self.parent_order_id?,
+                filled_quantity: self.fill_threshold,
+            },
+            "ma_cross" => TriggerCondition::MaCross {
+                symbol: self.watch_symbol,
+                fast_period: usize::try_from(self.fast_period?).ok()?,
+                slow_period: usize::try_from(self.slow_period?).ok()?,
+                direction: parse_direction(self.direction.as_deref()?)?,
+            },
+            "price_vwap" => TriggerCondition::PriceVwap {
+                symbol: self.watch_symbol,
+                direction: parse_direction(self.direction.as_deref()?)?,
+            },
+            _ => return None,
+        };
+
+        let action = match self.action.as_str() {
+            "cancel" => TriggerAction::Cancel,
+            _ => TriggerAction::Activate,
+        };
+
+        Some(OrderTrigger {
+            order_id: self.order_id,
+            account_id: self.account_id,
+            condition,
+            action,
+        })
+    }
+}
+
+fn parse_direction(s: &str) -> Option<CrossDirection> {
+    match s {
+        "above" => Some(CrossDirection::Above),
+        "below" => Some(CrossDirection::Below),
+        _ => None,
+    }
+}
+
+// =====================================================
+// TRIGGER BOOK
+// =====================================================
+
+/// Armed triggers keyed by the order they control
+#[derive(Default)]
+pub struct TriggerBook {
+    triggers: RwLock<HashMap<Uuid, OrderTrigger>>,
+}
+
+impl TriggerBook {
+    pub async fn load(&self, pool: &PgPool) -> anyhow::Result<usize> {
+        let rows: Vec<TriggerRow> = sqlx::query_as(
+            r#"SELECT order_id, account_id, condition_type, parent_order_id, fill_threshold,
+                      watch_symbol, fast_period, slow_period, direction, action
+               FROM order_triggers WHERE status = 'armed'"#
+        )
+            .fetch_all(pool)
+            .await?;
+
+        let mut triggers = self.triggers.write().await;
+        triggers.clear();
+        for trigger in rows.into_iter().filter_map(TriggerRow::into_trigger) {
+            triggers.insert(trigger.order_id, trigger);
+        }
+        Ok(triggers.len())
+    }
+
+    /// Persist a trigger inside the caller's transaction; call `arm` once it commits
+    pub async fn insert(conn: &mut PgConnection, trigger: &OrderTrigger) -> Result<(), sqlx::Error> {
+        let (mut parent_order_id, mut fill_threshold) = (None, None);
+        let (mut fast, mut slow, mut direction) = (None, None, None);
+
+        match &trigger.condition {
+            TriggerCondition::ParentFill { parent_order_id: parent, filled_quantity } => {
+                parent_order_id = Some(*parent);
+                fill_threshold = *filled_quantity;
+            }
+            TriggerCondition::MaCross { fast_period, slow_period, direction: d, .. } => {
+                fast = Some(*fast_period as i32);
+                slow = Some(*slow_period as i32);
+                direction = Some(d.as_str());
+            }
+            TriggerCondition::PriceVwap { direction: d, .. } => {
+                direction = Some(d.as_str());
+            }
+        }
+
+        sqlx::query(
+            r#"INSERT INTO order_triggers (order_id, account_id, condition_type, parent_order_id,
+                                           fill_threshold, watch_symbol, fast_period, slow_period,
+                                           direction, action)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"#
+        )
+            .bind(trigger.order_id)
+            .bind(trigger.account_id)
+            .bind(trigger.condition.as_str())
+            .bind(parent_order_id)
+            .bind(fill_threshold)
+            .bind(trigger.condition.watched_symbol())
+            .bind(fast)
+            .bind(slow)
+            .bind(direction)
+            .bind(trigger.action.as_str())
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn arm(&self, trigger: OrderTrigger) {
+        self.triggers.write().await.insert(trigger.order_id, trigger);
+    }
+
+    pub async fn disarm(&self, order_ids: &[Uuid]) {
+        let mut triggers = self.triggers.write().await;
+        for id in order_ids {
+            triggers.remove(id);
+        }
+    }
+
+    pub async fn disarm_account(&self, account_id: Uuid) {
+        self.triggers.write().await.retain(|_, t| t.account_id != account_id);
+    }
+
+    /// Whether any armed trigger evaluates market data of this symbol
+    pub async fn watches(&self, symbol: &str) -> bool {
+        self.triggers
+            .read()
+            .await
+            .values()
+            .any(|t| t.condition.watched_symbol() == Some(symbol))
+    }
+
+    /// Armed triggers whose condition is met by the event
+    pub async fn fired(&self, event: &TriggerEvent) -> Vec<OrderTrigger> {
+        self.triggers
+            .read()
+            .await
+            .values()
+            .filter(|t| condition_met(&t.condition, event))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,467 @@
+//! TWAP Execution
+//! Slices a parent order into child orders spread evenly over time, equal in size (TWAP) or
+//! weighted by historical volume (VWAP), submitted through the order processor on schedule,
+//! with progress per slice, slippage against the market VWAP and cancellation of what is left
+
+use crate::auth::{AuthContext, permissions};
+use crate::clock::SharedClock;
+use crate::engine::error::EngineError;
+use crate::engine::order_processor::{validate_metadata, NewOrderRequest, OrderResult};
+use crate::engine::{OrderProcessor, PositionKeeper};
+use crate::market_data::VolumeBars;
+use crate::observability::metrics::get_metrics;
+use crate::observability::stages::StageTimer;
+use crate::storage::EncryptedJson;
+
+use chrono::{DateTime, Utc};
+use enthropic_domain::twap::{slice_client_id, slice_due_at, slice_quantities, validate_twap, ExecutionStrategy};
+use enthropic_domain::vwap::{slippage_bps, weighted_quantities};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub use enthropic_domain::twap::TwapRequest;
+
+/// Parents whose next slice is due, taken per scheduler pass
+const DUE_BATCH: i64 = 100;
+
+// =====================================================
+// MODELS
+// =====================================================
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Twap {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub client_twap_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub quantity: Decimal,
+    pub price: Option<Decimal>,
+    pub duration_secs: i32,
+    pub slices: i32,
+    pub strategy: String,
+    /// Quantity of every slice, fixed when a VWAP is scheduled; TWAP slices are equal
+    pub slice_quantities: Option<Vec<Decimal>>,
+    pub metadata: Option<EncryptedJson>,
+    pub submitted_by: String,
+    pub slices_sent: i32,
+    pub next_slice_at: DateTime<Utc>,
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A sent slice with the state of its child order
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TwapSlice {
+    pub slice_index: i32,
+    pub order_id: Option<Uuid>,
+    pub quantity: Decimal,
+    pub reject_code: Option<String>,
+    pub reject_reason: Option<String>,
+    pub submitted_at: DateTime<Utc>,
+    pub status: Option<String>,
+    pub filled_quantity: Option<Decimal>,
+    pub avg_fill_price: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TwapProgress {
+    #[serde(flatten)]
+    pub twap: Twap,
+    /// Quantity of the slices sent so far, rejected ones included
+    pub sent_quantity: Decimal,
+    pub filled_quantity: Decimal,
+    pub avg_fill_price: Option<Decimal>,
+    /// Market VWAP from the start of the execution to the end of its window, or now
+    pub benchmark_vwap: Option<Decimal>,
+    /// Average fill price against the benchmark in basis points, positive when worse
+    pub slippage_bps: Option<Decimal>,
+    pub sent_slices: Vec<TwapSlice>,
+}
+
+impl Twap {
+    /// Quantity of every slice, sent or not
+    pub fn planned_quantities(&self) -> Vec<Decimal> {
+        self.slice_quantities
+            .clone()
+            .unwrap_or_else(|| slice_quantities(self.quantity, self.slices as u32))
+    }
+
+    /// End of the window the execution is measured over: its full duration, or until it
+    /// was cancelled
+    fn window_end(&self) -> DateTime<Utc> {
+        let end = slice_due_at(self.started_at, self.duration_secs as u32, self.slices as u32, self.slices as u32);
+        match self.completed_at {
+            Some(at) if self.status == "cancelled" => end.min(at),
+            _ => end,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TwapResult {
+    Accepted(Twap),
+    Duplicate(Twap),
+}
+
+/// What became of one slice
+enum SliceOutcome {
+    Placed(Uuid),
+    Rejected { code: String, reason: String },
+    /// Sized to nothing; no order is sent
+    Skipped,
+}
+
+// =====================================================
+// TWAP SCHEDULER
+// =====================================================
+
+pub struct TwapScheduler {
+    pool: PgPool,
+    order_processor: Arc<OrderProcessor>,
+    position_keeper: Arc<PositionKeeper>,
+    volume: Arc<VolumeBars>,
+    clock: SharedClock,
+}
+
+impl TwapScheduler {
+    pub fn new(
+        pool: PgPool,
+        order_processor: Arc<OrderProcessor>,
+        position_keeper: Arc<PositionKeeper>,
+        volume: Arc<VolumeBars>,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            pool,
+            order_processor,
+            position_keeper,
+            volume,
+            clock,
+        }
+    }
+
+    /// Schedule a TWAP; its first slice goes out on the next scheduler pass
+    pub async fn submit(&self, auth: &AuthContext, req: TwapRequest) -> Result<TwapResult, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CREATE) {
+            return Err(EngineError::Auth(
+                "orders:create required".into()
+            ));
+        }
+
+        validate_twap(&req)
+            .and_then(|()| validate_metadata(req.metadata.as_ref()))
+            .map_err(EngineError::Validation)?;
+
+        let now = self.clock.now();
+        let planned = match req.strategy {
+            ExecutionStrategy::Twap => None,
+            ExecutionStrategy::Vwap => {
+                let profile = self.volume.profile(&req.symbol, now, req.duration_secs, req.slices).await?;
+                Some(weighted_quantities(req.quantity, &profile))
+            }
+        };
+
+        let inserted: Option<Twap> = sqlx::query_as(
+            r#"INSERT INTO twap_orders (account_id, client_twap_id, symbol, side, quantity, price,
+                                        duration_secs, slices, strategy, slice_quantities, metadata,
+                                        submitted_by, next_slice_at, started_at, created_at, updated_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $13, $13, $13)
+               ON CONFLICT (account_id, client_twap_id) DO NOTHING
+               RETURNING *"#
+        )
+            .bind(auth.account_id)
+            .bind(&req.client_twap_id)
+            .bind(&req.symbol)
+            .bind(&req.side)
+            .bind(req.quantity)
+            .bind(req.price)
+            .bind(req.duration_secs as i32)
+            .bind(req.slices as i32)
+            .bind(req.strategy.as_str())
+            .bind(planned)
+            .bind(req.metadata.map(EncryptedJson))
+            .bind(&auth.username)
+            .bind(now)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        if let Some(twap) = inserted {
+            tracing::info!(
+                twap_id = %twap.id,
+                symbol = %twap.symbol,
+                strategy = %twap.strategy,
+                slices = twap.slices,
+                "TWAP scheduled"
+            );
+            return Ok(TwapResult::Accepted(twap));
+        }
+
+        let existing: Twap = sqlx::query_as(
+            "SELECT * FROM twap_orders WHERE account_id = $1 AND client_twap_id = $2"
+        )
+            .bind(auth.account_id)
+            .bind(&req.client_twap_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(TwapResult::Duplicate(existing))
+    }
+
+    /// The TWAP with every slice sent so far
+    pub async fn progress(&self, auth: &AuthContext, twap_id: Uuid) -> Result<Option<TwapProgress>, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_READ) {
+            return Err(EngineError::Auth(
+                "orders:read required".into()
+            ));
+        }
+
+        let Some(twap) = self.find(auth, twap_id).await? else {
+            return Ok(None);
+        };
+
+        let slices: Vec<TwapSlice> = sqlx::query_as(
+            r#"SELECT s.slice_index, s.order_id, s.quantity, s.reject_code, s.reject_reason, s.submitted_at,
+                      o.status, o.filled_quantity, o.avg_fill_price
+               FROM twap_slices s LEFT JOIN orders o ON o.id = s.order_id
+               WHERE s.twap_id = $1
+               ORDER BY s.slice_index"#
+        )
+            .bind(twap_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let filled_quantity: Decimal = slices.iter().filter_map(|s| s.filled_quantity).sum();
+        let filled_notional: Decimal = slices
+            .iter()
+            .filter_map(|s| Some(s.filled_quantity? * s.avg_fill_price?))
+            .sum();
+        let avg_fill_price = (filled_quantity > Decimal::ZERO).then(|| (filled_notional / filled_quantity).round_dp(8));
+
+        let benchmark_end = twap.window_end().min(self.clock.now());
+        let benchmark_vwap = self.volume.benchmark(&twap.symbol, twap.started_at, benchmark_end).await?;
+
+        Ok(Some(TwapProgress {
+            sent_quantity: slices.iter().map(|s| s.quantity).sum(),
+            filled_quantity,
+            avg_fill_price,
+            slippage_bps: avg_fill_price
+                .zip(benchmark_vwap)
+                .and_then(|(fill, benchmark)| slippage_bps(&twap.side, fill, benchmark)),
+            benchmark_vwap,
+            sent_slices: slices,
+            twap,
+        }))
+    }
+
+    /// Stop sending slices and cancel the children still open
+    pub async fn cancel(&self, auth: &AuthContext, twap_id: Uuid) -> Result<Option<Twap>, EngineError> {
+        if !auth.has_permission(permissions::ORDERS_CANCEL) {
+            return Err(EngineError::Auth(
+                "orders:cancel required".into()
+            ));
+        }
+
+        if self.find(auth, twap_id).await?.is_none() {
+            return Ok(None);
+        }
+
+        let now = self.clock.now();
+        let cancelled: Option<Twap> = sqlx::query_as(
+            r#"UPDATE twap_orders SET status = 'cancelled', updated_at = $2, completed_at = $2
+               WHERE id = $1 AND status = 'active'
+               RETURNING *"#
+        )
+            .bind(twap_id)
+            .bind(now)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let Some(twap) = cancelled else {
+            return Err(EngineError::Conflict("TWAP is no longer active".into()));
+        };
+
+        let open: Vec<(Uuid,)> = sqlx::query_as(
+            r#"SELECT o.id FROM twap_slices s JOIN orders o ON o.id = s.order_id
+               WHERE s.twap_id = $1 AND o.status IN ('waiting', 'pending', 'partially_filled')"#
+        )
+            .bind(twap_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        for (order_id,) in open {
+            // A child that fills or is cancelled meanwhile is simply left as it is
+            match self.order_processor.cancel_order(auth, order_id).await {
+                Ok(_) | Err(EngineError::Conflict(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        tracing::info!(twap_id = %twap.id, slices_sent = twap.slices_sent, "TWAP cancelled");
+        Ok(Some(twap))
+    }
+
+    async fn find(&self, auth: &AuthContext, twap_id: Uuid) -> Result<Option<Twap>, EngineError> {
+        let twap: Option<Twap> = sqlx::query_as("SELECT * FROM twap_orders WHERE id = $1")
+            .bind(twap_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match twap {
+            Some(twap) if !auth.can_access_account(&twap.account_id) => Err(EngineError::Auth(
+                "Cannot access others' orders".into()
+            )),
+            twap => Ok(twap),
+        }
+    }
+
+    /// Send every slice that is due; returns how many were sent
+    pub async fn run_due(&self) -> Result<u64, EngineError> {
+        let due: Vec<Twap> = sqlx::query_as(
+            r#"SELECT * FROM twap_orders
+               WHERE status = 'active' AND next_slice_at <= $1
+                 AND ($3::text[] IS NULL OR symbol = ANY($3))
+               ORDER BY next_slice_at
+               LIMIT $2"#
+        )
+            .bind(self.clock.now())
+            .bind(DUE_BATCH)
+            .bind(self.order_processor.owned_symbols())
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut sent = 0;
+        for twap in due {
+            if self.send_slice(&twap).await? {
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Submit the next slice of a TWAP and advance its schedule. A slice resent after a
+    /// crash or by another replica resolves to the same child through its client id; a
+    /// VWAP slice sized to nothing advances the schedule without an order.
+    async fn send_slice(&self, twap: &Twap) -> Result<bool, EngineError> {
+        let index = twap.slices_sent as u32;
+        let slices = twap.slices as u32;
+        let Some(&quantity) = twap.planned_quantities().get(index as usize) else {
+            return Ok(false);
+        };
+
+        let outcome = if quantity.is_zero() {
+            SliceOutcome::Skipped
+        } else {
+            self.submit_child(twap, index, quantity).await?
+        };
+        let now = self.clock.now();
+
+        let mut tx = self.pool.begin().await?;
+        let recorded = match &outcome {
+            SliceOutcome::Placed(order_id) => Some((Some(*order_id), None, None)),
+            SliceOutcome::Rejected { code, reason } => Some((None, Some(code.as_str()), Some(reason.as_str()))),
+            SliceOutcome::Skipped => None,
+        };
+        if let Some((order_id, reject_code, reject_reason)) = recorded {
+            sqlx::query(
+                r#"INSERT INTO twap_slices (twap_id, slice_index, order_id, quantity, reject_code, reject_reason, submitted_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7)
+                   ON CONFLICT (twap_id, slice_index) DO NOTHING"#
+            )
+                .bind(twap.id)
+                .bind(index as i32)
+                .bind(order_id)
+                .bind(quantity)
+                .bind(reject_code)
+                .bind(reject_reason)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let last = index + 1 == slices;
+        let advanced = sqlx::query(
+            r#"UPDATE twap_orders
+               SET slices_sent = $3, next_slice_at = $4, updated_at = $5,
+                   status = CASE WHEN $6 THEN 'completed' ELSE status END,
+                   completed_at = CASE WHEN $6 THEN $5 ELSE completed_at END
+               WHERE id = $1 AND slices_sent = $2 AND status = 'active'"#
+        )
+            .bind(twap.id)
+            .bind(twap.slices_sent)
+            .bind(twap.slices_sent + 1)
+            .bind(slice_due_at(twap.started_at, twap.duration_secs as u32, slices, index + 1))
+            .bind(now)
+            .bind(last)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+
+        if let Some(ref metrics) = *get_metrics() {
+            let outcome = match outcome {
+                SliceOutcome::Placed(_) => "placed",
+                SliceOutcome::Rejected { .. } => "rejected",
+                SliceOutcome::Skipped => "skipped",
+            };
+            metrics.twap_slices_total.with_label_values(&[outcome]).inc();
+        }
+        if last && advanced > 0 {
+            tracing::info!(twap_id = %twap.id, "TWAP completed");
+        }
+        Ok(advanced > 0)
+    }
+
+    /// Submit one slice under the account of the TWAP, as the user who scheduled it
+    async fn submit_child(&self, twap: &Twap, index: u32, quantity: Decimal) -> Result<SliceOutcome, EngineError> {
+        let auth = AuthContext {
+            account_id: twap.account_id,
+            username: twap.submitted_by.clone(),
+            role: "twap".into(),
+            permissions: HashSet::from([permissions::ORDERS_CREATE.to_string()]),
+            token_jti: String::new(),
+            impersonation: None,
+        };
+        let req = NewOrderRequest {
+            client_order_id: slice_client_id(&twap.client_twap_id, index),
+            account_id: None,
+            symbol: twap.symbol.clone(),
+            side: twap.side.clone(),
+            order_type: Some(if twap.price.is_some() { "limit" } else { "market" }.into()),
+            quantity,
+            notional: None,
+            price: twap.price,
+            stop_price: None,
+            trail_amount: None,
+            trail_percent: None,
+            time_in_force: None,
+            expires_at: None,
+            max_slippage_bps: None,
+            trigger: None,
+            metadata: twap.metadata.clone().map(EncryptedJson::into_inner),
+            oco_with: None,
+            bracket: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+        };
+
+        let mut timer = StageTimer::start(self.clock.clone());
+        match self.order_processor.submit_order(&auth, req, &self.position_keeper, &mut timer).await {
+            Ok(OrderResult::Accepted(order)) | Ok(OrderResult::Duplicate(order)) => Ok(SliceOutcome::Placed(order.id)),
+            Ok(OrderResult::Rejected { code, reason }) => {
+                tracing::info!(twap_id = %twap.id, slice = index + 1, code = %code, "TWAP slice rejected");
+                Ok(SliceOutcome::Rejected { code: code.code().to_string(), reason })
+            }
+            // Storage failures leave the slice due, so the next pass retries it
+            Err(e) if e.is_unexpected() => Err(e),
+            Err(e) => Ok(SliceOutcome::Rejected { code: e.code().to_string(), reason: e.to_string() }),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+//! Execution Core - High-Performance Trading Engine
+//! The engine as a library: the binary serves it, and tests run it against a real database
+
+pub mod admin;
+pub mod auth;
+pub mod clock;
+pub mod config;
+pub mod engine;
+pub mod ids;
+pub mod market_data;
+pub mod nats_handler;
+pub mod observability;
+pub mod resilience;
+pub mod storage;
+pub mod proto;
@@ -1,120 +1,562 @@
 //! Execution Core - High-Performance Trading Engine
 //! Phase 1: Persistence | Phase 2: Authentication | Phase 3: Observability & Resilience
 
-mod auth;
-mod config;
-mod engine;
-mod nats_handler;
-mod observability;
-mod resilience;
-mod proto;
-
-use crate::auth::AuthService;
-use crate::config::Config;
-use crate::nats_handler::NatsSubscriber;
-use crate::observability::health::{start_health_server, HealthState};
-use crate::observability::metrics::get_metrics;
-use crate::resilience::{CircuitBreaker, CircuitBreakerConfig, RetryConfig, with_retry_async};
-use sqlx::postgres::PgPoolOptions;
-use std::sync::atomic::{AtomicBool, Ordering};
+use execution_core::{admin, observability, storage};
+
+use execution_core::auth::AuthService;
+use execution_core::clock::{SharedClock, SystemClock};
+use execution_core::config::Config;
+use execution_core::engine::analytics::{run_analytics_export, AnalyticsConfig, AnalyticsExporter};
+use execution_core::engine::netting::{parse_netting_rules, NettingEngine};
+use execution_core::engine::rebates::{parse_rebate_tiers, RebateConfig};
+use execution_core::engine::risk::{parse_open_interest_caps, ConcentrationConfig, ConcentrationMode};
+use execution_core::engine::shard::ShardSpec;
+use execution_core::engine::slippage::{SlippageMode, SlippageModel};
+use execution_core::nats_handler::intake::ORDER_SUBJECTS;
+use execution_core::nats_handler::{
+    server_addrs, BreakerBus, BufferedBus, FailoverBus, InProcessBus, IntakeSettings, JetStreamIntake, Lifecycle, NatsBus, NatsSubscriber,
+    Phase, Region, SharedBus,
+};
+use execution_core::observability::error_reporting::ReportSource;
+use execution_core::observability::health::{start_health_server, HealthState};
+use execution_core::observability::slo::SloConfig;
+use execution_core::observability::slow_ops::SlowThresholds;
+use execution_core::storage::{
+    monitor_pool, parse_rules, pool_options, report_pool_metrics, run_retention, Dialect, HedgeConfig,
+    HedgePolicy, ObjectStorage, PoolLifecycle, PoolSettings, ReadPool, RetentionEngine,
+};
+use execution_core::resilience::{
+    monitor_clock_skew, probe_dependencies, reconnect, sync_breaker, AdaptiveLimiter, BreakerStore, CircuitBreaker, CircuitBreakerConfig, ClockSkew, ClockSkewConfig,
+    Dependencies, LimiterConfig, RedisBreakerStore, RetryConfig, with_retry_async,
+};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Duration;
-use tracing::{info, error};
+use tracing::{info, error, warn};
+
+/// jemalloc exposes the heap statistics served at `/debug/pprof/heap`
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+/// How often a dependency missing at startup is retried
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+/// How long startup waits for any NATS region when failover regions are configured
+const NATS_REGION_WAIT: Duration = Duration::from_secs(30);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // An admin command runs once against the database instead of serving
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let admin_command = admin::AdminCommand::parse(&args)?;
+
     // Load configuration
     let config = Config::from_env()?;
 
+    // Redaction applies to every log line and error report from here on
+    observability::redaction::init_redaction(&config.log_redact_fields);
+
+    // Error reporting goes first so the tracing stack can forward error logs to it
+    let report_sinks = observability::error_reporting::init_error_reporting(
+        ReportSource {
+            service: "execution-core".to_string(),
+            environment: std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string()),
+        },
+        config.sentry_dsn.as_deref(),
+        config.error_report_otlp_endpoint.as_deref(),
+    )?;
+
     // Initialize observability (tracing, metrics)
     observability::init_observability("execution-core")?;
+    if !report_sinks.is_empty() {
+        observability::error_reporting::install_panic_hook();
+        info!(sinks = ?report_sinks, "Error reporting enabled");
+    }
+    observability::slow_ops::init_slow_ops(SlowThresholds {
+        db: Duration::from_millis(config.slow_db_threshold_ms),
+        publish: Duration::from_millis(config.slow_publish_threshold_ms),
+    });
 
     info!(
         version = env!("CARGO_PKG_VERSION"),
         "Starting Execution Core..."
     );
 
-    // Connection status flags for health checks
-    let nats_connected = Arc::new(AtomicBool::new(false));
-    let redis_connected = Arc::new(AtomicBool::new(false));
+    // Optional dependencies; losing one at startup degrades the engine instead of aborting
+    let dependencies = Arc::new(Dependencies::new(!config.is_dev()));
+
+    // Every component reads time through this clock
+    let clock: SharedClock = Arc::new(SystemClock::new());
+
+    // Remote clocks are compared with it from the timestamps they send
+    let clock_skew = Arc::new(ClockSkew::new(
+        ClockSkewConfig {
+            alarm: chrono::Duration::milliseconds(config.clock_skew_alarm_ms as i64),
+            disable_on_alarm: config.clock_skew_disables_timestamps,
+        },
+        clock.clone(),
+    ));
+
+    // SLO burn rates are computed from events the handlers record
+    observability::slo::init_slos(
+        SloConfig {
+            order_ack_threshold: Duration::from_millis(config.slo_order_ack_ms),
+            fill_delay_threshold: Duration::from_millis(config.slo_fill_delay_ms),
+            latency_objective: config.slo_latency_objective,
+            error_objective: config.slo_error_objective,
+        },
+        clock.clone(),
+    );
+    tokio::spawn(observability::slo::run_slo_evaluation(Duration::from_secs(
+        config.slo_eval_interval_secs.max(1),
+    )));
+
+    // Sensitive columns are sealed with these keys on write and opened on read
+    storage::encryption::init_keyring(&config.column_encryption_keys)?;
 
     // Initialize database pool with retry
-    let pool = with_retry_async(
+    let pool_settings = PoolSettings {
+        min_connections: config.pool_min_connections,
+        max_connections: config.pool_max_connections,
+        acquire_timeout: Duration::from_secs(5),
+        max_lifetime: Duration::from_secs(config.pool_max_lifetime_secs),
+        test_before_acquire: config.pool_test_before_acquire,
+    };
+    let primary_lifecycle = Arc::new(PoolLifecycle::new("primary"));
+    let connected = with_retry_async(
         "database_connect",
         &RetryConfig::default(),
         || async {
-            PgPoolOptions::new()
-                .min_connections(config.pool_min_connections)
-                .max_connections(config.pool_max_connections)
-                .acquire_timeout(Duration::from_secs(5))
+            pool_options(&pool_settings, primary_lifecycle.clone())
                 .connect(&config.database_url)
                 .await
         },
-    ).await?;
+    ).await;
+    // The dev profile stands in for NATS and Redis only; storage is Postgres in every profile
+    let pool = match connected {
+        Ok(pool) => pool,
+        Err(e) if config.is_dev() => {
+            anyhow::bail!("dev profile still needs Postgres initialised from infra/db/init at DATABASE_URL: {}", e)
+        }
+        Err(e) => return Err(e.into()),
+    };
 
     info!("Connected to PostgreSQL");
 
-    // Update DB pool metrics
-    if let Some(ref metrics) = *get_metrics() {
-        metrics.db_pool_connections.with_label_values(&["active"]).set(0.0);
-        metrics.db_pool_connections.with_label_values(&["idle"]).set(config.pool_min_connections as f64);
+    let dialect = Dialect::resolve(&config.database_dialect, &pool).await?;
+    info!(dialect = dialect.as_str(), "Database dialect");
+
+    // Replica connects lazily so an unreachable replica never blocks startup;
+    // reads fall back to the primary until it answers
+    let mut pool_lifecycles = vec![primary_lifecycle.clone()];
+    let replica = match &config.database_read_url {
+        Some(url) => {
+            let replica_settings = PoolSettings {
+                min_connections: 0,
+                acquire_timeout: Duration::from_secs(2),
+                ..pool_settings.clone()
+            };
+            let replica_lifecycle = Arc::new(PoolLifecycle::new("replica"));
+            let replica = pool_options(&replica_settings, replica_lifecycle.clone())
+                .connect_lazy(url)?;
+            pool_lifecycles.push(replica_lifecycle.clone());
+            info!("Read replica configured");
+            Some((replica, replica_lifecycle))
+        }
+        None => None,
+    };
+
+    // Probe pools: recycle after failover, rebuild after repeated acquire failures
+    let health_interval = Duration::from_secs(config.pool_health_interval_secs.max(1));
+    tokio::spawn(monitor_pool(
+        pool.clone(),
+        primary_lifecycle,
+        dialect,
+        true,
+        health_interval,
+        config.pool_repair_threshold,
+    ));
+    if let Some((replica, lifecycle)) = &replica {
+        tokio::spawn(monitor_pool(
+            replica.clone(),
+            lifecycle.clone(),
+            dialect,
+            false,
+            health_interval,
+            config.pool_repair_threshold,
+        ));
     }
 
-    // Initialize Redis with retry
-    let redis_client = redis::Client::open(config.redis_url.as_str())?;
-    let _redis_conn = with_retry_async(
-        "redis_connect",
-        &RetryConfig::default(),
-        || async {
-            redis::aio::ConnectionManager::new(redis_client.clone()).await
-        },
-    ).await?;
-    redis_connected.store(true, Ordering::Relaxed);
-    info!("Connected to Redis");
+    // In-flight DB operations per pool adapt to latency instead of a fixed bulkhead size
+    let max_limit = config.pool_max_connections as usize;
+    let limiter = |name: &'static str| {
+        let (min_limit, initial_limit) = if config.db_adaptive_limit {
+            (config.db_limit_min.min(max_limit), max_limit / 2)
+        } else {
+            (max_limit, max_limit)
+        };
+        Arc::new(AdaptiveLimiter::new(
+            LimiterConfig { name, min_limit, max_limit, initial_limit },
+            clock.clone(),
+        ))
+    };
+    let hedge = config.read_hedging_enabled.then(|| {
+        Arc::new(HedgePolicy::new(
+            HedgeConfig {
+                budget: config.read_hedge_budget,
+                min_delay: Duration::from_millis(config.read_hedge_min_delay_ms),
+            },
+            clock.clone(),
+        ))
+    });
+    let reads = ReadPool::new(
+        pool.clone(),
+        limiter("primary"),
+        replica.map(|(replica, _)| (replica, limiter("replica"))),
+        hedge,
+    );
+
+    if let Some(command) = admin_command {
+        info!(command = ?command, "Running admin command");
+        return command.run(pool, reads).await;
+    }
+
+    // Sample DB pool metrics
+    tokio::spawn(report_pool_metrics(reads.clone(), Duration::from_secs(15)));
+
+    // Business metrics: fills are counted as they commit, the rest is aggregated here
+    observability::business::init_business_metrics(config.fee_rate_bps);
+    if config.business_metrics_interval_secs > 0 {
+        tokio::spawn(observability::business::run_business_metrics(
+            reads.clone(),
+            Duration::from_secs(config.business_metrics_interval_secs),
+        ));
+    }
+
+    // Startup order: database (required, above), then Redis, then NATS.
+    // Initialize Redis with retry; the dev profile runs without it, and without it
+    // otherwise the engine serves queries only until it connects.
+    let redis_conn = if config.is_dev() {
+        info!("Dev profile: skipping Redis");
+        None
+    } else {
+        let redis_client = redis::Client::open(config.redis_url.as_str())?;
+        let connected = with_retry_async(
+            "redis_connect",
+            &RetryConfig::default(),
+            || async {
+                redis::aio::ConnectionManager::new(redis_client.clone()).await
+            },
+        ).await;
+
+        match connected {
+            Ok(redis_conn) => {
+                dependencies.set_redis(true);
+                info!("Connected to Redis");
+                Some(redis_conn)
+            }
+            Err(e) if config.degraded_startup => {
+                warn!(error = %e, "Redis unavailable, starting in query-only mode");
+                let dependencies = dependencies.clone();
+                tokio::spawn(async move {
+                    reconnect("redis", RECONNECT_INTERVAL, || {
+                        redis::aio::ConnectionManager::new(redis_client.clone())
+                    }).await;
+                    dependencies.set_redis(true);
+                });
+                None
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
 
     // Initialize auth service
     let auth_service = Arc::new(AuthService::new(&config.jwt_secret));
     info!("Auth service initialized");
 
-    // Circuit breaker for NATS (unused but prepared for resilience)
-    let _nats_circuit_breaker = Arc::new(CircuitBreaker::new(
+    // Circuit breaker for NATS publishes; the bus is wrapped in it once connected.
+    // Transitions are shared through Redis so every replica agrees and restarts restore them.
+    let breaker_store = redis_conn
+        .clone()
+        .map(|conn| Arc::new(RedisBreakerStore::new(conn)) as Arc<dyn BreakerStore>);
+    let nats_circuit_breaker = Arc::new(CircuitBreaker::new(
         CircuitBreakerConfig {
             name: "nats".to_string(),
             failure_threshold: 5,
             success_threshold: 3,
             timeout: Duration::from_secs(30),
             half_open_max_calls: 3,
+        },
+        clock.clone(),
+        breaker_store.clone(),
+    ));
+    if breaker_store.is_some() {
+        nats_circuit_breaker.sync().await;
+        tokio::spawn(sync_breaker(
+            nats_circuit_breaker.clone(),
+            Duration::from_millis(config.circuit_breaker_sync_interval_ms.max(100)),
+        ));
+    }
+
+    // Several active instances each trade their own symbols; fixed for the process lifetime
+    let shard = match ShardSpec::parse(&config.shard_symbols, &config.shard_hash_range)? {
+        Some(spec) => {
+            let shard = spec.resolve(&pool, &config.instance_id).await?;
+            if shard.is_empty() {
+                warn!(?spec, "Sharded instance owns no symbols");
+            }
+            info!(symbols = shard.len(), shared = config.shard_shared, "Symbol shard resolved");
+            Some(Arc::new(shard))
+        }
+        None => None,
+    };
+
+    // Pulled order intake needs JetStream, so the dev profile always subscribes
+    let intake = match config.order_intake.as_str() {
+        "jetstream" if config.is_dev() => {
+            warn!("Dev profile: JetStream intake unavailable, subscribing to order subjects");
+            None
         }
+        "jetstream" => Some(Arc::new(JetStreamIntake::new(IntakeSettings {
+            stream: config.intake_stream.clone(),
+            consumer: config.intake_consumer.clone(),
+            subjects: ORDER_SUBJECTS.iter().map(|s| s.to_string()).collect(),
+            batch_size: config.intake_batch_size,
+            max_in_flight: match config.intake_max_in_flight {
+                0 => config.pool_max_connections as usize,
+                limit => limit,
+            },
+            ack_wait: Duration::from_secs(config.intake_ack_wait_secs.max(1)),
+        }, clock_skew.clone()))),
+        "core" => None,
+        other => anyhow::bail!("ORDER_INTAKE must be \"core\" or \"jetstream\", got {:?}", other),
+    };
+    // The durable consumer pulls every symbol's orders; sharding needs the per-symbol subjects
+    if intake.is_some() && shard.is_some() {
+        anyhow::bail!("Symbol sharding needs ORDER_INTAKE=core");
+    }
+
+    // Connect to NATS with retry, or stand up the in-process bus for dev
+    let mut dev_bus = None;
+    let bus: SharedBus = if config.is_dev() {
+        let in_process = Arc::new(InProcessBus::new(1024));
+        dev_bus = Some(in_process.clone());
+        dependencies.set_nats(true);
+        info!("Dev profile: using in-process message bus");
+        in_process
+    } else if !config.nats_failover_urls.trim().is_empty() {
+        // Every region connects in the background; the bus routes through the first that is up
+        let mut regions = Vec::new();
+        let clusters = std::iter::once(config.nats_url.as_str()).chain(config.nats_failover_urls.split(';'));
+        for (index, urls) in clusters.filter(|urls| !urls.trim().is_empty()).enumerate() {
+            let client = async_nats::ConnectOptions::new()
+                .retry_on_initial_connect()
+                .connect(server_addrs(urls)?)
+                .await?;
+            let name = if index == 0 { "primary".to_string() } else { format!("failover-{}", index) };
+            regions.push(Region { name, client });
+        }
+        let failover = Arc::new(FailoverBus::new(regions, config.nats_regions_mirrored));
+
+        match tokio::time::timeout(NATS_REGION_WAIT, failover.wait_connected()).await {
+            Ok(()) => {
+                dependencies.set_nats(true);
+                info!(region = %failover.active_region(), regions = failover.regions().len(), "Connected to NATS");
+            }
+            Err(_) if config.degraded_startup => {
+                warn!("No NATS region reachable yet, messages wait for the first to connect");
+            }
+            Err(_) => anyhow::bail!("no NATS region reachable within {:?}", NATS_REGION_WAIT),
+        }
+        if let Some(intake) = intake.clone() {
+            for region in failover.regions() {
+                let (intake, client) = (intake.clone(), region.client.clone());
+                tokio::spawn(async move { intake.run(client).await });
+            }
+        }
+        tokio::spawn(failover.clone().watch_regions(Duration::from_millis(config.nats_failover_check_ms.max(100))));
+        failover
+    } else {
+        let servers = server_addrs(&config.nats_url)?;
+        let connected = with_retry_async(
+            "nats_connect",
+            &RetryConfig::default(),
+            || async {
+                async_nats::connect(servers.as_slice()).await
+            },
+        ).await;
+
+        match connected {
+            Ok(nats_client) => {
+                dependencies.set_nats(true);
+                info!(url = %config.nats_url, "Connected to NATS");
+                if let Some(intake) = intake.clone() {
+                    let client = nats_client.clone();
+                    tokio::spawn(async move { intake.run(client).await });
+                }
+                Arc::new(NatsBus::new(nats_client))
+            }
+            // No intake until NATS connects; background publishes queue locally
+            Err(e) if config.degraded_startup => {
+                warn!(error = %e, "NATS unavailable, buffering outbound messages");
+                let buffered = Arc::new(BufferedBus::new(config.outbound_buffer_capacity));
+                let (pending, dependencies) = (buffered.clone(), dependencies.clone());
+                let intake = intake.clone();
+                tokio::spawn(async move {
+                    let nats_client = reconnect("nats", RECONNECT_INTERVAL, || async_nats::connect(servers.as_slice())).await;
+                    pending.attach(Arc::new(NatsBus::new(nats_client.clone()))).await;
+                    dependencies.set_nats(true);
+                    if let Some(intake) = intake {
+                        intake.run(nats_client).await;
+                    }
+                });
+                buffered
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
+
+    // Every publish (replies, execution reports, events) goes through the breaker
+    let bus: SharedBus = if config.is_dev() {
+        bus
+    } else {
+        Arc::new(BreakerBus::new(bus, nats_circuit_breaker.clone()))
+    };
+
+    // Rollout tracking: every milestone from here on is published on system.lifecycle
+    let lifecycle = Lifecycle::new(bus.clone(), config.instance_id.clone(), config.profile.clone());
+    lifecycle.publish(Phase::Starting, &[]).await;
+
+    // Keep the NATS and Redis flags honest after startup: a broker that goes away
+    // later flips the engine into its degraded modes until the probes succeed again
+    if config.dependency_probe_interval_secs > 0 {
+        let redis_client = if config.is_dev() {
+            None
+        } else {
+            Some(redis::Client::open(config.redis_url.as_str())?)
+        };
+        tokio::spawn(probe_dependencies(
+            dependencies.clone(),
+            bus.clone(),
+            redis_client,
+            Duration::from_secs(config.dependency_probe_interval_secs),
+            Duration::from_millis(config.dependency_probe_timeout_ms.max(1)),
+        ));
+    }
+
+    if config.clock_skew_check_interval_secs > 0 {
+        tokio::spawn(monitor_clock_skew(
+            clock_skew.clone(),
+            pool.clone(),
+            Duration::from_secs(config.clock_skew_check_interval_secs),
+        ));
+    }
+
+    // Purge data past its retention period in small batches (or only report it in dry-run)
+    let retention = RetentionEngine::new(
+        pool.clone(),
+        parse_rules(&config.retention_rules)?,
+        config.retention_batch_size,
+        Duration::from_millis(100),
+        config.retention_dry_run,
+        clock.clone(),
+    );
+    tokio::spawn(run_retention(
+        retention,
+        Duration::from_secs(config.retention_interval_secs.max(1)),
     ));
 
-    // Connect to NATS with retry
-    let nats_client = with_retry_async(
-        "nats_connect",
-        &RetryConfig::default(),
-        || async {
-            async_nats::connect(&config.nats_url).await
-        },
-    ).await?;
-    nats_connected.store(true, Ordering::Relaxed);
-    info!(url = %config.nats_url, "Connected to NATS");
+    // Copy each finished day of trades and ticks to the analytics store, read from the replica
+    if let Some(url) = &config.analytics_store_url {
+        let exporter = AnalyticsExporter::new(
+            pool.clone(),
+            reads.clone(),
+            Arc::new(ObjectStorage::open(url)?),
+            AnalyticsConfig {
+                backfill_days: config.analytics_backfill_days as i64,
+                ..AnalyticsConfig::default()
+            },
+            clock.clone(),
+        );
+        tokio::spawn(run_analytics_export(
+            exporter,
+            Duration::from_secs(config.analytics_export_interval_secs.max(1)),
+        ));
+    }
+
+    // Account exports are written here; requests are refused without a store
+    let export_storage = match &config.export_store_url {
+        Some(url) => Some(Arc::new(ObjectStorage::open(url)?)),
+        None => None,
+    };
 
     // Initialize NATS subscriber
     let subscriber = NatsSubscriber::new(
-        nats_client,
+        bus,
         pool.clone(),
+        reads,
         auth_service,
+        &config,
+        clock,
+        dialect,
+        dependencies.clone(),
+        intake,
+        clock_skew,
+        NettingEngine::new(parse_netting_rules(&config.netting_rules)?),
+        ConcentrationConfig {
+            max_share_pct: config.concentration_limit_pct,
+            min_open_interest: config.concentration_min_open_interest,
+            symbol_caps: parse_open_interest_caps(&config.open_interest_caps)?,
+            mode: ConcentrationMode::parse(&config.concentration_mode)
+                .ok_or_else(|| anyhow::anyhow!("CONCENTRATION_MODE must be reject or flag"))?,
+        },
+        RebateConfig {
+            maker_rebate_bps: config.maker_rebate_bps,
+            volume_tiers: parse_rebate_tiers(&config.rebate_volume_tiers)?,
+            fee_rate_bps: Decimal::from_f64(config.fee_rate_bps).unwrap_or_default(),
+            referral_share: config.referral_share,
+        },
+        SlippageModel {
+            mode: SlippageMode::parse(&config.market_slippage_model)
+                .ok_or_else(|| anyhow::anyhow!("MARKET_SLIPPAGE_MODEL must be fixed, spread or volume_impact"))?,
+            base_bps: config.market_slippage_bps,
+            impact_bps_per_unit: config.market_impact_bps_per_unit,
+            spread_bps: config.market_spread_bps,
+            volume_impact_bps: config.market_volume_impact_bps,
+            latency: chrono::Duration::milliseconds(config.simulated_latency_ms as i64),
+        },
+        export_storage,
+        shard,
     );
 
     // Load state from database
-    subscriber.initialize().await?;
+    let (open_orders, positions) = subscriber.initialize().await?;
     info!("State loaded from database");
+    lifecycle.publish(Phase::StateLoaded, &[
+        ("open_orders", open_orders as u64),
+        ("positions", positions as u64),
+    ]).await;
+
+    // Re-seal rows written in plain form or under a rotated-out key
+    let reseal_pool = pool.clone();
+    tokio::spawn(async move {
+        match storage::encryption::reseal_order_metadata(&reseal_pool, 500).await {
+            Ok(0) => {}
+            Ok(count) => info!(count, "Re-sealed order metadata under the active key"),
+            Err(e) => error!(error = %e, "Order metadata re-seal failed"),
+        }
+    });
 
     // Start health/metrics server
     let health_state = HealthState {
         db_pool: pool.clone(),
-        nats_connected: nats_connected.clone(),
-        redis_connected: redis_connected.clone(),
+        db_pools: pool_lifecycles,
+        dependencies: dependencies.clone(),
         ready: Arc::new(AtomicBool::new(true)),
+        dev_bus,
+        config: Arc::new(config.redacted()),
     };
 
     let metrics_port: u16 = std::env::var("METRICS_PORT")
@@ -139,7 +581,7 @@ async fn main() -> anyhow::Result<()> {
 
     // Run subscriber
     tokio::select! {
-        result = subscriber.run() => {
+        result = subscriber.run(&lifecycle) => {
             if let Err(e) = result {
                 error!(error = %e, "Subscriber error");
             }
@@ -150,6 +592,19 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // Graceful shutdown
+    lifecycle.publish(Phase::Draining, &[("queued", subscriber.queued() as u64)]).await;
+    if config.state_handoff {
+        if let Err(e) = subscriber.hand_off().await {
+            warn!(error = %e, "Failed to hand off hot state");
+        }
+    }
+    let handled = observability::subjects::summaries();
+    lifecycle.publish(Phase::Stopped, &[
+        ("messages_received", handled.iter().map(|s| s.received).sum()),
+        ("replies", handled.iter().map(|s| s.replies).sum()),
+        ("errors", handled.iter().map(|s| s.errors).sum()),
+    ]).await;
+    lifecycle.flush(Duration::from_secs(2)).await;
     observability::shutdown_observability();
     info!("Execution Core stopped");
     Ok(())
@@ -0,0 +1,116 @@
+//! Book Feed
+//! Depth of the internal book for market-data consumers: a snapshot of a symbol on
+//! `orderbook.snapshot.{symbol}`, and the levels that changed since the last publish on
+//! `orderbook.delta.{symbol}`. Every delta of a symbol advances its sequence. While deltas are
+//! published a snapshot is the book as of the last one, with its sequence, so applying the
+//! deltas after it rebuilds the book exactly; without them it is the live book.
+
+use chrono::{DateTime, Utc};
+use enthropic_domain::Depth;
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+
+pub const SNAPSHOT_SUBJECT_PREFIX: &str = "orderbook.snapshot.";
+
+/// Reply on `orderbook.snapshot.{symbol}`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookSnapshot {
+    pub symbol: String,
+    /// Sequence of the last delta published for the symbol; zero before the first or when
+    /// deltas are not published
+    pub sequence: u64,
+    pub taken_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub depth: Depth,
+}
+
+/// Published on `orderbook.delta.{symbol}`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookDelta {
+    pub symbol: String,
+    pub sequence: u64,
+    pub published_at: DateTime<Utc>,
+    /// Changed levels; a quantity of zero removes the level
+    #[serde(flatten)]
+    pub changes: Depth,
+}
+
+impl BookDelta {
+    pub fn subject(&self) -> String {
+        format!("orderbook.delta.{}", self.symbol)
+    }
+}
+
+/// What was last published for a symbol
+#[derive(Default)]
+struct Published {
+    sequence: u64,
+    depth: Depth,
+    at: Option<DateTime<Utc>>,
+}
+
+pub struct BookFeed {
+    /// Price levels a side, in snapshots and in the depth deltas are taken against
+    levels: usize,
+    /// Symbols stay once seen, so a book that empties and refills keeps counting up
+    published: Mutex<HashMap<String, Published>>,
+}
+
+impl BookFeed {
+    pub fn new(levels: usize) -> Self {
+        Self {
+            levels,
+            published: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// The symbol's depth as of its last delta; empty at sequence zero before the first, since
+    /// the first delta then carries every level
+    pub async fn published(&self, symbol: &str, now: DateTime<Utc>) -> BookSnapshot {
+        let published = self.published.lock().await;
+        let last = published.get(symbol);
+        BookSnapshot {
+            symbol: symbol.to_string(),
+            sequence: last.map_or(0, |p| p.sequence),
+            taken_at: last.and_then(|p| p.at).unwrap_or(now),
+            depth: last.map(|p| p.depth.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Stop publishing `symbol` as a rebalance moves it to another instance, without the
+    /// removals of an emptied book; its sequence starts again where it is traded next
+    pub async fn forget(&self, symbol: &str) {
+        self.published.lock().await.remove(symbol);
+    }
+
+    /// Deltas of every symbol whose depth changed since its last publish, recorded as
+    /// published. `current` holds the depth of every symbol with open orders; symbols missing
+    /// from it emptied.
+    pub async fn deltas(&self, mut current: HashMap<String, Depth>, now: DateTime<Utc>) -> Vec<BookDelta> {
+        let mut published = self.published.lock().await;
+        for symbol in published.keys() {
+            current.entry(symbol.clone()).or_default();
+        }
+
+        let mut deltas = Vec::new();
+        for (symbol, depth) in current {
+            let last = published.entry(symbol.clone()).or_default();
+            let changes = depth.changes_since(&last.depth);
+            if changes.is_empty() {
+                continue;
+            }
+            last.sequence += 1;
+            last.depth = depth;
+            last.at = Some(now);
+            deltas.push(BookDelta { symbol, sequence: last.sequence, published_at: now, changes });
+        }
+        deltas
+    }
+}
@@ -0,0 +1,106 @@
+//! Indicators
+//! Simple moving averages and session VWAP over a bounded price history
+
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+
+pub use enthropic_domain::trigger::CrossDirection;
+
+#[derive(Debug, Clone)]
+pub struct SymbolSeries {
+    /// Most recent price last
+    prices: VecDeque<Decimal>,
+    capacity: usize,
+    vwap_notional: Decimal,
+    vwap_volume: Decimal,
+    last_cumulative_volume: Option<Decimal>,
+}
+
+impl SymbolSeries {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            prices: VecDeque::with_capacity(capacity + 1),
+            // One extra price so the longest average can also be taken a tick ago
+            capacity: capacity.max(1) + 1,
+            vwap_notional: Decimal::ZERO,
+            vwap_volume: Decimal::ZERO,
+            last_cumulative_volume: None,
+        }
+    }
+
+    /// Record a trade price. `size` weights the VWAP (1 if unknown); a drop in the
+    /// feed's cumulative `volume` marks a new session and restarts the VWAP.
+    pub fn push(&mut self, price: Decimal, size: Option<Decimal>, cumulative_volume: Option<Decimal>) {
+        if let (Some(previous), Some(current)) = (self.last_cumulative_volume, cumulative_volume) {
+            if current < previous {
+                self.vwap_notional = Decimal::ZERO;
+                self.vwap_volume = Decimal::ZERO;
+            }
+        }
+        self.last_cumulative_volume = cumulative_volume.or(self.last_cumulative_volume);
+
+        let weight = size.filter(|s| *s > Decimal::ZERO).unwrap_or(Decimal::ONE);
+        self.vwap_notional += price * weight;
+        self.vwap_volume += weight;
+
+        self.prices.push_back(price);
+        if self.prices.len() > self.capacity {
+            self.prices.pop_front();
+        }
+    }
+
+    /// Divide all recorded prices by a split ratio
+    pub fn rescale(&mut self, ratio: Decimal) {
+        for price in self.prices.iter_mut() {
+            *price /= ratio;
+        }
+        self.vwap_notional /= ratio;
+    }
+
+    pub fn last(&self) -> Option<Decimal> {
+        self.prices.back().copied()
+    }
+
+    pub fn vwap(&self) -> Option<Decimal> {
+        (!self.vwap_volume.is_zero()).then(|| self.vwap_notional / self.vwap_volume)
+    }
+
+    /// Simple moving average over `period` prices, ending `offset` ticks ago
+    pub fn sma(&self, period: usize, offset: usize) -> Option<Decimal> {
+        if period == 0 || self.prices.len() < period + offset {
+            return None;
+        }
+
+        let end = self.prices.len() - offset;
+        let sum: Decimal = self.prices.range(end - period..end).sum();
+        Some(sum / Decimal::from(period))
+    }
+
+    /// True on the tick where the fast average crosses the slow one in `direction`
+    pub fn ma_crossed(&self, fast: usize, slow: usize, direction: CrossDirection) -> bool {
+        let (Some(fast_now), Some(slow_now), Some(fast_prev), Some(slow_prev)) = (
+            self.sma(fast, 0),
+            self.sma(slow, 0),
+            self.sma(fast, 1),
+            self.sma(slow, 1),
+        ) else {
+            return false;
+        };
+
+        match direction {
+            CrossDirection::Above => fast_prev <= slow_prev && fast_now > slow_now,
+            CrossDirection::Below => fast_prev >= slow_prev && fast_now < slow_now,
+        }
+    }
+
+    /// Whether the last price is strictly on the given side of the session VWAP
+    pub fn price_vs_vwap(&self, direction: CrossDirection) -> bool {
+        match (self.last(), self.vwap()) {
+            (Some(price), Some(vwap)) => match direction {
+                CrossDirection::Above => price > vwap,
+                CrossDirection::Below => price < vwap,
+            },
+            _ => false,
+        }
+    }
+}
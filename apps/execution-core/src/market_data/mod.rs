@@ -0,0 +1,64 @@
+//! Market Data Module
+//! Per-symbol price history and simple indicators (moving averages, session VWAP), traded
+//! volume bars kept in the database, and the depth feed of the internal book
+
+pub mod book_feed;
+pub mod indicators;
+pub mod volume;
+
+pub use book_feed::BookFeed;
+pub use indicators::{CrossDirection, SymbolSeries};
+pub use volume::VolumeBars;
+
+use crate::engine::order_processor::MarketTick;
+
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Tracks recent prices for every symbol seen on `market.tick.*`.
+/// History is in-memory only; indicators warm up again after a restart.
+pub struct MarketData {
+    series: RwLock<HashMap<String, SymbolSeries>>,
+    max_history: usize,
+}
+
+impl MarketData {
+    pub fn new(max_history: usize) -> Self {
+        Self {
+            series: RwLock::new(HashMap::new()),
+            max_history,
+        }
+    }
+
+    /// Longest moving average period that can be evaluated
+    pub fn max_history(&self) -> usize {
+        self.max_history
+    }
+
+    pub async fn on_tick(&self, tick: &MarketTick) {
+        let Ok(price) = tick.last_price.parse::<Decimal>() else {
+            return;
+        };
+        let size = tick.last_size.as_deref().and_then(|s| s.parse().ok());
+        let volume = tick.volume.as_deref().and_then(|v| v.parse().ok());
+
+        let mut series = self.series.write().await;
+        series
+            .entry(tick.symbol.clone())
+            .or_insert_with(|| SymbolSeries::new(self.max_history))
+            .push(price, size, volume);
+    }
+
+    /// Restate history in post-split prices so averages do not see a false jump
+    pub async fn apply_split(&self, symbol: &str, ratio: Decimal) {
+        if let Some(series) = self.series.write().await.get_mut(symbol) {
+            series.rescale(ratio);
+        }
+    }
+
+    /// Copy of a symbol's series for evaluating triggers without holding the lock
+    pub async fn snapshot(&self, symbol: &str) -> Option<SymbolSeries> {
+        self.series.read().await.get(symbol).cloned()
+    }
+}
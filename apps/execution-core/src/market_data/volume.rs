@@ -0,0 +1,141 @@
+//! Volume Bars
+//! Traded volume per symbol in fixed bars, accumulated from market ticks and flushed to
+//! `market_volume_bars`, for sizing VWAP schedules and measuring executions against the
+//! market VWAP
+
+use crate::engine::order_processor::MarketTick;
+
+use chrono::{DateTime, Duration, Utc};
+use enthropic_domain::vwap::{benchmark_vwap, volume_profile, VolumeBar, MAX_PROFILE_DAYS};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+
+/// Volume and notional by symbol and bar start
+type PendingBars = HashMap<(String, DateTime<Utc>), (Decimal, Decimal)>;
+
+pub struct VolumeBars {
+    pool: PgPool,
+    /// Volume seen since the last flush
+    pending: Mutex<PendingBars>,
+    bar_secs: i64,
+    lookback_days: u32,
+    retention_days: i64,
+}
+
+impl VolumeBars {
+    pub fn new(pool: PgPool, bar_secs: u64, lookback_days: u32, retention_days: u64) -> Self {
+        Self {
+            pool,
+            pending: Mutex::new(HashMap::new()),
+            bar_secs: bar_secs as i64,
+            lookback_days: lookback_days.min(MAX_PROFILE_DAYS),
+            retention_days: retention_days as i64,
+        }
+    }
+
+    /// Add a tick's trade to its bar. Only ticks reporting `lastSize` count; the feed's
+    /// cumulative volume says nothing about when within the session it traded.
+    pub async fn on_tick(&self, tick: &MarketTick, at: DateTime<Utc>) {
+        if self.bar_secs <= 0 {
+            return;
+        }
+        let (Ok(price), Some(size)) = (
+            tick.last_price.parse::<Decimal>(),
+            tick.last_size.as_deref().and_then(|s| s.parse::<Decimal>().ok()),
+        ) else {
+            return;
+        };
+        if size <= Decimal::ZERO {
+            return;
+        }
+
+        let bar_start = DateTime::from_timestamp(at.timestamp() - at.timestamp().rem_euclid(self.bar_secs), 0)
+            .unwrap_or(at);
+        let mut pending = self.pending.lock().await;
+        let bar = pending.entry((tick.symbol.clone(), bar_start)).or_default();
+        bar.0 += size;
+        bar.1 += price * size;
+    }
+
+    /// Add the volume seen since the last flush to the stored bars and drop bars past
+    /// retention. Returns how many bars were written.
+    pub async fn flush(&self, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let pending: Vec<_> = self.pending.lock().await.drain().collect();
+
+        let mut written = 0;
+        for (i, ((symbol, bar_start), (volume, notional))) in pending.iter().enumerate() {
+            let result = sqlx::query(
+                r#"INSERT INTO market_volume_bars (symbol, bar_start, volume, notional)
+                   VALUES ($1, $2, $3, $4)
+                   ON CONFLICT (symbol, bar_start) DO UPDATE SET
+                       volume = market_volume_bars.volume + EXCLUDED.volume,
+                       notional = market_volume_bars.notional + EXCLUDED.notional"#
+            )
+                .bind(symbol)
+                .bind(bar_start)
+                .bind(volume)
+                .bind(notional)
+                .execute(&self.pool)
+                .await;
+
+            // Keep what was not written for the next flush
+            if let Err(e) = result {
+                let mut retained = self.pending.lock().await;
+                for (key, (volume, notional)) in pending.into_iter().skip(i) {
+                    let bar = retained.entry(key).or_default();
+                    bar.0 += volume;
+                    bar.1 += notional;
+                }
+                return Err(e);
+            }
+            written += 1;
+        }
+
+        sqlx::query("DELETE FROM market_volume_bars WHERE bar_start < $1")
+            .bind(now - Duration::days(self.retention_days))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(written)
+    }
+
+    /// Volume expected in each slice of a schedule starting at `start`, from the same
+    /// windows on the previous lookback days
+    pub async fn profile(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        duration_secs: u32,
+        slices: u32,
+    ) -> Result<Vec<Decimal>, sqlx::Error> {
+        let from = start - Duration::days(i64::from(self.lookback_days));
+        let to = start + Duration::seconds(i64::from(duration_secs)) - Duration::days(1);
+        let bars = self.bars(symbol, from, to).await?;
+        Ok(volume_profile(&bars, start, duration_secs, slices, self.lookback_days))
+    }
+
+    /// Market VWAP of the symbol over `[from, to)`, as far as bars have been flushed
+    pub async fn benchmark(&self, symbol: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Option<Decimal>, sqlx::Error> {
+        Ok(benchmark_vwap(&self.bars(symbol, from, to).await?))
+    }
+
+    async fn bars(&self, symbol: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<VolumeBar>, sqlx::Error> {
+        let rows: Vec<(DateTime<Utc>, Decimal, Decimal)> = sqlx::query_as(
+            r#"SELECT bar_start, volume, notional FROM market_volume_bars
+               WHERE symbol = $1 AND bar_start >= $2 AND bar_start < $3
+               ORDER BY bar_start"#
+        )
+            .bind(symbol)
+            .bind(from)
+            .bind(to)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(start, volume, notional)| VolumeBar { start, volume, notional })
+            .collect())
+    }
+}
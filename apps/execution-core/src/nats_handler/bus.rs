@@ -0,0 +1,507 @@
+//! Message Bus
+//! NATS in production, an in-process broadcast bus for the dev profile
+
+use async_nats::connection::State;
+use async_nats::{Client, Message, ServerAddr, Subject};
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, watch, Mutex};
+
+use crate::observability::metrics::get_metrics;
+use crate::observability::slow_ops::slow_publish;
+use crate::observability::tracing_setup::trace_headers;
+use crate::resilience::CircuitBreaker;
+
+pub type SharedBus = Arc<dyn MessageBus>;
+
+#[async_trait]
+pub trait MessageBus: Send + Sync {
+    /// Stream every message whose subject matches `subject` (NATS wildcards allowed)
+    async fn subscribe(&self, subject: &str) -> anyhow::Result<BoxStream<'static, Message>>;
+
+    async fn publish(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<()>;
+
+    /// One round trip through the broker, proving it still routes messages
+    async fn ping(&self) -> anyhow::Result<()>;
+}
+
+// =====================================================
+// NATS BUS
+// =====================================================
+
+pub struct NatsBus {
+    client: Client,
+}
+
+impl NatsBus {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl MessageBus for NatsBus {
+    async fn subscribe(&self, subject: &str) -> anyhow::Result<BoxStream<'static, Message>> {
+        let subscriber = self.client.subscribe(subject.to_string()).await?;
+        Ok(subscriber.boxed())
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %subject))]
+    async fn publish(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<()> {
+        let headers = trace_headers();
+        let label = subject.clone();
+        slow_publish(&label, async move {
+            if headers.is_empty() {
+                self.client.publish(subject, payload.into()).await
+            } else {
+                self.client.publish_with_headers(subject, headers, payload.into()).await
+            }
+        }).await?;
+        Ok(())
+    }
+
+    /// Echo a message off a private subject: a flush only proves the socket took the bytes
+    async fn ping(&self) -> anyhow::Result<()> {
+        let inbox = self.client.new_inbox();
+        let mut echo = self.client.subscribe(inbox.clone()).await?;
+        self.client.publish(inbox, Vec::new().into()).await?;
+        echo.next().await.ok_or_else(|| anyhow::anyhow!("subscription closed"))?;
+        Ok(())
+    }
+}
+
+// =====================================================
+// FAILOVER BUS
+// =====================================================
+
+/// Replies go to private subjects under this prefix
+const INBOX_PREFIX: &str = "_INBOX.";
+
+/// Wait before a subscription that ended is made again
+const RESUBSCRIBE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// One region's NATS cluster
+pub struct Region {
+    pub name: String,
+    pub client: Client,
+}
+
+/// Servers of one cluster, `nats://a:4222,nats://b:4222`
+pub fn server_addrs(urls: &str) -> anyhow::Result<Vec<ServerAddr>> {
+    let addrs = urls
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(|url| url.parse().map_err(|e| anyhow::anyhow!("invalid NATS URL {:?}: {}", url, e)))
+        .collect::<anyhow::Result<Vec<ServerAddr>>>()?;
+    if addrs.is_empty() {
+        anyhow::bail!("no NATS servers in {:?}", urls);
+    }
+    Ok(addrs)
+}
+
+/// NATS spread over several regions' clusters, in order of preference. Publishes go through
+/// the active region: the first that is connected. When the regions mirror subjects to each
+/// other (gateways), subscriptions follow the active region too; otherwise every region is
+/// subscribed so requests sent to any of them arrive, and replies go to every region since
+/// only the requester's has its inbox.
+pub struct FailoverBus {
+    regions: Vec<Region>,
+    mirrored: bool,
+    active: watch::Sender<usize>,
+}
+
+impl FailoverBus {
+    pub fn new(regions: Vec<Region>, mirrored: bool) -> Self {
+        assert!(!regions.is_empty(), "failover bus needs a region");
+        let (active, _) = watch::channel(0);
+        Self { regions, mirrored, active }
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    pub fn active_region(&self) -> &str {
+        &self.regions[*self.active.borrow()].name
+    }
+
+    fn connected(&self, index: usize) -> bool {
+        self.regions[index].client.connection_state() == State::Connected
+    }
+
+    /// Move to the most preferred connected region, back to an earlier one once it has
+    /// recovered. Returns the region switched to, if any.
+    pub fn failover(&self) -> Option<&str> {
+        let current = *self.active.borrow();
+        let best = (0..self.regions.len()).find(|&i| self.connected(i))?;
+        if best == current {
+            return None;
+        }
+        self.active.send_replace(best);
+        Some(&self.regions[best].name)
+    }
+
+    /// Wait until some region is connected and active
+    pub async fn wait_connected(&self) {
+        loop {
+            self.failover();
+            if self.connected(*self.active.borrow()) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Check the regions every `interval`, failing over when the active one is lost
+    pub async fn watch_regions(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            let from = self.active_region().to_string();
+            if let Some(to) = self.failover() {
+                tracing::warn!(from = %from, to = %to, "NATS region failover");
+            }
+            self.record_regions();
+        }
+    }
+
+    fn record_regions(&self) {
+        if let Some(ref metrics) = *get_metrics() {
+            let active = *self.active.borrow();
+            for (index, region) in self.regions.iter().enumerate() {
+                let connected = if self.connected(index) { 1.0 } else { 0.0 };
+                let is_active = if index == active { 1.0 } else { 0.0 };
+                metrics.nats_region_connected.with_label_values(&[&region.name]).set(connected);
+                metrics.nats_region_active.with_label_values(&[&region.name]).set(is_active);
+            }
+        }
+    }
+
+    fn active_bus(&self) -> NatsBus {
+        NatsBus::new(self.regions[*self.active.borrow()].client.clone())
+    }
+}
+
+/// Messages on `subject` from whichever region is active, subscribing again on failover
+fn follow_active(clients: Vec<Client>, subject: String, active: watch::Receiver<usize>) -> BoxStream<'static, Message> {
+    let state = (clients, subject, active, None::<async_nats::Subscriber>);
+    stream::unfold(state, |(clients, subject, mut active, mut current)| async move {
+        loop {
+            let Some(subscriber) = current.as_mut() else {
+                let index = *active.borrow_and_update();
+                match clients[index].subscribe(subject.clone()).await {
+                    Ok(subscriber) => current = Some(subscriber),
+                    Err(e) => {
+                        tracing::warn!(subject = %subject, error = %e, "Failed to subscribe in the active NATS region");
+                        tokio::time::sleep(RESUBSCRIBE_INTERVAL).await;
+                    }
+                }
+                continue;
+            };
+
+            tokio::select! {
+                msg = subscriber.next() => match msg {
+                    Some(msg) => return Some((msg, (clients, subject, active, current))),
+                    None => {
+                        current = None;
+                        tokio::time::sleep(RESUBSCRIBE_INTERVAL).await;
+                    }
+                },
+                changed = active.changed() => {
+                    changed.ok()?;
+                    // Dropping the old subscription unsubscribes it in the region left behind
+                    current = None;
+                }
+            }
+        }
+    })
+        .boxed()
+}
+
+#[async_trait]
+impl MessageBus for FailoverBus {
+    async fn subscribe(&self, subject: &str) -> anyhow::Result<BoxStream<'static, Message>> {
+        if self.mirrored {
+            let clients = self.regions.iter().map(|r| r.client.clone()).collect();
+            return Ok(follow_active(clients, subject.to_string(), self.active.subscribe()));
+        }
+
+        let mut subscriptions = Vec::with_capacity(self.regions.len());
+        for region in &self.regions {
+            subscriptions.push(region.client.subscribe(subject.to_string()).await?.boxed());
+        }
+        Ok(stream::select_all(subscriptions).boxed())
+    }
+
+    async fn publish(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<()> {
+        if self.mirrored || !subject.starts_with(INBOX_PREFIX) {
+            return self.active_bus().publish(subject, payload).await;
+        }
+
+        let mut delivered = false;
+        let mut last_error = None;
+        for (index, region) in self.regions.iter().enumerate() {
+            if !self.connected(index) {
+                continue;
+            }
+            match NatsBus::new(region.client.clone()).publish(subject.clone(), payload.clone()).await {
+                Ok(()) => delivered = true,
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match (delivered, last_error) {
+            (true, _) => Ok(()),
+            (false, Some(e)) => Err(e),
+            (false, None) => anyhow::bail!("no NATS region connected"),
+        }
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.active_bus().ping().await
+    }
+}
+
+// =====================================================
+// IN-PROCESS BUS
+// =====================================================
+
+/// Single-process stand-in for NATS so the engine runs without a broker.
+/// Slow subscribers that fall more than `capacity` messages behind lose the gap.
+pub struct InProcessBus {
+    sender: broadcast::Sender<Message>,
+}
+
+impl InProcessBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publish with a private reply subject and wait for the first answer
+    pub async fn request(
+        &self,
+        subject: String,
+        payload: Vec<u8>,
+        timeout: Duration,
+    ) -> anyhow::Result<Message> {
+        let inbox = format!("_INBOX.{}", uuid::Uuid::new_v4().simple());
+        let mut replies = self.subscribe(&inbox).await?;
+
+        self.send(subject, Some(inbox), payload);
+
+        tokio::time::timeout(timeout, replies.next())
+            .await
+            .map_err(|_| anyhow::anyhow!("no reply within {:?}", timeout))?
+            .ok_or_else(|| anyhow::anyhow!("bus closed"))
+    }
+
+    fn send(&self, subject: String, reply: Option<String>, payload: Vec<u8>) {
+        let message = Message {
+            length: subject.len() + payload.len(),
+            subject: Subject::from(subject),
+            reply: reply.map(Subject::from),
+            payload: payload.into(),
+            headers: None,
+            status: None,
+            description: None,
+        };
+
+        // No receivers is not an error: NATS drops unrouted messages too
+        let _ = self.sender.send(message);
+    }
+}
+
+#[async_trait]
+impl MessageBus for InProcessBus {
+    async fn subscribe(&self, subject: &str) -> anyhow::Result<BoxStream<'static, Message>> {
+        let pattern = subject.to_string();
+        let receiver = self.sender.subscribe();
+
+        let messages = stream::unfold(receiver, move |mut receiver| {
+            let pattern = pattern.clone();
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(msg) if subject_matches(&pattern, &msg.subject) => {
+                            return Some((msg, receiver));
+                        }
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!(subject = %pattern, skipped, "In-process subscriber lagged");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+
+        Ok(messages.boxed())
+    }
+
+    async fn publish(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<()> {
+        self.send(subject, None, payload);
+        Ok(())
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+// =====================================================
+// BUFFERED BUS
+// =====================================================
+
+/// Stand-in used when NATS is unreachable at startup. Publishes queue locally (oldest
+/// dropped past `capacity`) and subscriptions wait; `attach` flushes the queue in order.
+pub struct BufferedBus {
+    state: Mutex<BufferState>,
+    attached: watch::Sender<Option<SharedBus>>,
+    capacity: usize,
+}
+
+struct BufferState {
+    bus: Option<SharedBus>,
+    pending: VecDeque<(String, Vec<u8>)>,
+}
+
+impl BufferedBus {
+    pub fn new(capacity: usize) -> Self {
+        let (attached, _) = watch::channel(None);
+        Self {
+            state: Mutex::new(BufferState { bus: None, pending: VecDeque::new() }),
+            attached,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Route through `bus` from now on, after publishing everything queued so far
+    pub async fn attach(&self, bus: SharedBus) {
+        let mut state = self.state.lock().await;
+
+        let queued = state.pending.len();
+        while let Some((subject, payload)) = state.pending.pop_front() {
+            if let Err(e) = bus.publish(subject.clone(), payload).await {
+                tracing::warn!(subject = %subject, error = %e, "Failed to flush buffered message");
+            }
+        }
+        record_buffered(0);
+        tracing::info!(queued, "Message bus attached, buffered messages flushed");
+
+        state.bus = Some(bus.clone());
+        self.attached.send_replace(Some(bus));
+    }
+}
+
+#[async_trait]
+impl MessageBus for BufferedBus {
+    async fn subscribe(&self, subject: &str) -> anyhow::Result<BoxStream<'static, Message>> {
+        let mut attached = self.attached.subscribe();
+        let bus = attached
+            .wait_for(Option::is_some)
+            .await?
+            .clone()
+            .expect("waited for an attached bus");
+        bus.subscribe(subject).await
+    }
+
+    async fn publish(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        if let Some(bus) = state.bus.clone() {
+            drop(state);
+            return bus.publish(subject, payload).await;
+        }
+
+        if state.pending.len() >= self.capacity {
+            state.pending.pop_front();
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.bus_buffer_dropped_total.inc();
+            }
+        }
+        state.pending.push_back((subject, payload));
+        record_buffered(state.pending.len());
+        Ok(())
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        let bus = self.state.lock().await.bus.clone();
+        match bus {
+            Some(bus) => bus.ping().await,
+            None => anyhow::bail!("not attached to NATS yet"),
+        }
+    }
+}
+
+// =====================================================
+// BREAKER BUS
+// =====================================================
+
+/// Publishes through a circuit breaker: while the broker keeps failing them they fail fast
+/// instead of each waiting out the client, and the breaker's transitions are shared with the
+/// other replicas through its store. Subscriptions and probes pass straight through; the
+/// probes are how the engine notices the broker is back.
+pub struct BreakerBus {
+    inner: SharedBus,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl BreakerBus {
+    pub fn new(inner: SharedBus, breaker: Arc<CircuitBreaker>) -> Self {
+        Self { inner, breaker }
+    }
+}
+
+#[async_trait]
+impl MessageBus for BreakerBus {
+    async fn subscribe(&self, subject: &str) -> anyhow::Result<BoxStream<'static, Message>> {
+        self.inner.subscribe(subject).await
+    }
+
+    async fn publish(&self, subject: String, payload: Vec<u8>) -> anyhow::Result<()> {
+        if !self.breaker.allow_call().await {
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.circuit_breaker_rejections_total.with_label_values(&[self.breaker.name()]).inc();
+            }
+            anyhow::bail!("circuit breaker {} is open", self.breaker.name());
+        }
+
+        let result = self.inner.publish(subject, payload).await;
+        match result {
+            Ok(()) => self.breaker.record_success().await,
+            Err(_) => self.breaker.record_failure().await,
+        }
+        result
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.inner.ping().await
+    }
+}
+
+fn record_buffered(count: usize) {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.bus_buffered_messages.set(count as f64);
+    }
+}
+
+/// NATS subject matching: `*` matches one token, a trailing `>` matches one or more
+pub fn subject_matches(pattern: &str, subject: &str) -> bool {
+    let mut pattern_tokens = pattern.split('.');
+    let mut subject_tokens = subject.split('.');
+
+    loop {
+        match (pattern_tokens.next(), subject_tokens.next()) {
+            (Some(">"), Some(_)) => return pattern_tokens.next().is_none(),
+            (Some("*"), Some(_)) => continue,
+            (Some(p), Some(s)) if p == s => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
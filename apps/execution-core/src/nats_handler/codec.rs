@@ -0,0 +1,130 @@
+//! Gateway Order Codec
+//! Order submit payloads as the TypeScript gateway sends them, in either naming convention
+
+use async_nats::Message;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// Producers declare the payload version here; absent means version 1
+pub const SCHEMA_VERSION_HEADER: &str = "Enthropic-Schema-Version";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecVersion {
+    /// camelCase or snake_case field names, `tags` for metadata
+    V1,
+    /// camelCase only
+    V2,
+}
+
+impl CodecVersion {
+    fn parse(value: &str) -> Result<Self, CodecError> {
+        match value.trim() {
+            "1" => Ok(CodecVersion::V1),
+            "2" => Ok(CodecVersion::V2),
+            other => Err(CodecError::UnsupportedVersion(other.to_string())),
+        }
+    }
+}
+
+/// Order fields by canonical (camelCase) name, with the names only version 1 accepts
+const ORDER_FIELDS: &[(&str, &[&str])] = &[
+    ("clientOrderId", &["client_order_id"]),
+    ("accountId", &["account_id"]),
+    ("symbol", &[]),
+    ("side", &[]),
+    ("orderType", &["order_type"]),
+    ("quantity", &[]),
+    ("notional", &[]),
+    ("price", &[]),
+    ("stopPrice", &["stop_price"]),
+    ("trailAmount", &["trail_amount"]),
+    ("trailPercent", &["trail_percent"]),
+    ("timeInForce", &["time_in_force"]),
+    ("expiresAt", &["expires_at"]),
+    ("maxSlippageBps", &["max_slippage_bps"]),
+    ("trigger", &[]),
+    ("metadata", &["tags"]),
+    ("ocoWith", &["oco_with"]),
+    ("bracket", &[]),
+    ("displayQuantity", &["display_quantity"]),
+    ("postOnly", &["post_only"]),
+    ("reduceOnly", &["reduce_only"]),
+];
+
+/// Added by the gateway on the way through; accepted by every version and not order fields
+const ENVELOPE_FIELDS: &[&str] = &["auth", "username", "submittedAt"];
+
+#[derive(Error, Debug)]
+pub enum CodecError {
+    #[error("unsupported schema version {0:?}")]
+    UnsupportedVersion(String),
+    #[error("expected a JSON object")]
+    NotAnObject,
+    #[error("unknown field {0:?}")]
+    UnknownField(String),
+    #[error("field {0:?} given more than once")]
+    DuplicateField(String),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Normalizes order submit payloads to canonical field names before decoding them.
+/// Lenient by default: every known name is accepted and unknown fields are ignored.
+/// Strict mode accepts only the declared version's names and rejects anything else.
+#[derive(Debug, Clone)]
+pub struct OrderCodec {
+    strict: bool,
+}
+
+impl OrderCodec {
+    pub fn new(strict: bool) -> Self {
+        Self { strict }
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self, msg: &Message) -> Result<T, CodecError> {
+        let version = msg
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get(SCHEMA_VERSION_HEADER))
+            .map(|value| CodecVersion::parse(value.as_str()))
+            .transpose()?
+            .unwrap_or(CodecVersion::V1);
+        self.decode_payload(&msg.payload, version)
+    }
+
+    pub fn decode_payload<T: DeserializeOwned>(&self, payload: &[u8], version: CodecVersion) -> Result<T, CodecError> {
+        let Value::Object(fields) = serde_json::from_slice(payload)? else {
+            return Err(CodecError::NotAnObject);
+        };
+        let normalized = self.normalize(fields, version)?;
+        Ok(serde_json::from_value(Value::Object(normalized))?)
+    }
+
+    fn normalize(&self, fields: Map<String, Value>, version: CodecVersion) -> Result<Map<String, Value>, CodecError> {
+        let mut normalized = Map::with_capacity(fields.len());
+        for (name, value) in fields {
+            let key = match canonical(&name, version, self.strict) {
+                Some(canonical) => canonical.to_string(),
+                None if self.strict => return Err(CodecError::UnknownField(name)),
+                None => name,
+            };
+            if normalized.insert(key.clone(), value).is_some() {
+                return Err(CodecError::DuplicateField(key));
+            }
+        }
+        Ok(normalized)
+    }
+}
+
+/// Canonical name of a known field; strict mode holds `name` to the version's own names
+fn canonical(name: &str, version: CodecVersion, strict: bool) -> Option<&'static str> {
+    if let Some(envelope) = ENVELOPE_FIELDS.iter().find(|field| **field == name) {
+        return Some(envelope);
+    }
+    let accepts_aliases = !strict || version == CodecVersion::V1;
+    ORDER_FIELDS
+        .iter()
+        .find(|(field, aliases)| *field == name || (accepts_aliases && aliases.contains(&name)))
+        .map(|(field, _)| *field)
+}
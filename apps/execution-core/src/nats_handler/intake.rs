@@ -0,0 +1,210 @@
+//! JetStream Order Intake
+//! Order requests pulled from a durable consumer at the engine's own pace instead of pushed by core NATS
+
+use async_nats::jetstream::{self, consumer::{pull, AckPolicy}, stream::RetentionPolicy, AckKind};
+use async_nats::{Client, Message};
+use chrono::DateTime;
+use futures::stream::{BoxStream, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::resilience::{ClockSkew, SkewSource};
+
+/// Producers put their reply inbox here: the NATS reply subject of a stream
+/// publish receives the stream's own acknowledgement
+pub const REPLY_TO_HEADER: &str = "Reply-To";
+/// Set on pulled requests; the ack subject of the delivery to acknowledge once answered
+pub const ACK_HEADER: &str = "Enthropic-Intake-Ack";
+
+/// Subjects taken from the stream in JetStream intake mode
+pub const ORDER_SUBJECTS: &[&str] = &[
+    "orders.submit",
+    "orders.cancel",
+    "orders.cancel_all",
+    "orders.reduce",
+    "orders.amend",
+    "orders.strategy.submit",
+    "orders.strategy.cancel",
+];
+
+/// Wait before a stalled or failed pull is retried
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub struct IntakeSettings {
+    pub stream: String,
+    pub consumer: String,
+    /// Order subjects captured by the stream and handed to the matching subscription
+    pub subjects: Vec<String>,
+    pub batch_size: usize,
+    /// Deliveries outstanding at once; the server holds back the rest until they are acked
+    pub max_in_flight: usize,
+    /// Unacknowledged deliveries are redelivered after this
+    pub ack_wait: Duration,
+}
+
+/// Durable pull consumer feeding the order subscriptions. Requests are acknowledged
+/// only once answered, so a burst waits in the stream rather than in the engine.
+pub struct JetStreamIntake {
+    settings: IntakeSettings,
+    senders: HashMap<String, mpsc::Sender<Message>>,
+    receivers: Mutex<HashMap<String, mpsc::Receiver<Message>>>,
+    /// Deliveries handed to a handler and not yet answered, by ack subject
+    pending: Mutex<HashMap<String, jetstream::Message>>,
+    /// Compares the server's stored time of each delivery with ours
+    clock_skew: Arc<ClockSkew>,
+}
+
+impl JetStreamIntake {
+    pub fn new(settings: IntakeSettings, clock_skew: Arc<ClockSkew>) -> Self {
+        let capacity = settings.max_in_flight.max(1);
+        let (senders, receivers) = settings
+            .subjects
+            .iter()
+            .map(|subject| {
+                let (tx, rx) = mpsc::channel(capacity);
+                ((subject.clone(), tx), (subject.clone(), rx))
+            })
+            .unzip();
+
+        Self {
+            settings,
+            senders,
+            receivers: Mutex::new(receivers),
+            pending: Mutex::new(HashMap::new()),
+            clock_skew,
+        }
+    }
+
+    /// Requests on `subject`, if it is pulled from the stream; each subject can be taken once
+    pub fn subscribe(&self, subject: &str) -> Option<BoxStream<'static, Message>> {
+        let mut receivers = self.receivers.lock().unwrap_or_else(|e| e.into_inner());
+        let rx = receivers.remove(subject)?;
+        Some(futures::stream::unfold(rx, |mut rx| async move {
+            let msg = rx.recv().await?;
+            Some((msg, rx))
+        }).boxed())
+    }
+
+    /// Acknowledge the delivery behind `msg` once it has been answered; core NATS messages are ignored
+    pub async fn ack(&self, msg: &Message) {
+        let Some(ack_subject) = ack_subject(msg) else {
+            return;
+        };
+        let delivery = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&ack_subject);
+
+        if let Some(delivery) = delivery {
+            if let Err(e) = delivery.ack().await {
+                tracing::warn!(subject = %msg.subject, error = %e, "Failed to ack intake message");
+            }
+        }
+    }
+
+    /// Pull from the consumer until the process exits, recreating the stream and
+    /// consumer if they are missing
+    pub async fn run(&self, client: Client) {
+        let context = jetstream::new(client);
+        loop {
+            if let Err(e) = self.pull(&context).await {
+                tracing::warn!(stream = %self.settings.stream, error = %e, "JetStream intake interrupted, retrying");
+            }
+            tokio::time::sleep(RETRY_INTERVAL).await;
+        }
+    }
+
+    async fn pull(&self, context: &jetstream::Context) -> anyhow::Result<()> {
+        let settings = &self.settings;
+        let stream = context
+            .get_or_create_stream(jetstream::stream::Config {
+                name: settings.stream.clone(),
+                subjects: settings.subjects.clone(),
+                retention: RetentionPolicy::WorkQueue,
+                ..Default::default()
+            })
+            .await?;
+        let consumer: jetstream::consumer::PullConsumer = stream
+            .get_or_create_consumer(&settings.consumer, pull::Config {
+                durable_name: Some(settings.consumer.clone()),
+                ack_policy: AckPolicy::Explicit,
+                ack_wait: settings.ack_wait,
+                max_ack_pending: settings.max_in_flight as i64,
+                ..Default::default()
+            })
+            .await?;
+
+        let mut deliveries = consumer
+            .stream()
+            .max_messages_per_batch(settings.batch_size.max(1))
+            .messages()
+            .await?;
+        tracing::info!(
+            stream = %settings.stream,
+            consumer = %settings.consumer,
+            max_in_flight = settings.max_in_flight,
+            "Pulling orders from JetStream"
+        );
+
+        while let Some(delivery) = deliveries.next().await {
+            self.route(delivery?).await;
+        }
+        Ok(())
+    }
+
+    async fn route(&self, delivery: jetstream::Message) {
+        let Some(sender) = self.senders.get(delivery.subject.as_str()) else {
+            tracing::warn!(subject = %delivery.subject, "No intake handler for subject, terminating delivery");
+            let _ = delivery.ack_with(AckKind::Term).await;
+            return;
+        };
+        let Some(ack_subject) = delivery.reply.as_ref().map(|reply| reply.to_string()) else {
+            return;
+        };
+
+        // Time waiting in the stream only adds to the offset; the skew estimate keeps the smallest
+        if let Ok(info) = delivery.info() {
+            let published = DateTime::from_timestamp_nanos(info.published.unix_timestamp_nanos() as i64);
+            self.clock_skew.observe(SkewSource::Nats, published);
+        }
+
+        let request = to_request(&delivery.message, &ack_subject);
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(ack_subject.clone(), delivery);
+
+        // A full channel holds the pull back, so the consumer only fetches what handlers can take
+        if sender.send(request).await.is_err() {
+            self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&ack_subject);
+        }
+    }
+}
+
+/// The delivery as a core NATS request: replies go to the producer's `Reply-To`
+fn to_request(delivery: &Message, ack_subject: &str) -> Message {
+    let reply = delivery
+        .headers
+        .as_ref()
+        .and_then(|headers| headers.get(REPLY_TO_HEADER))
+        .map(|reply| reply.as_str().into());
+    let mut headers = delivery.headers.clone().unwrap_or_default();
+    headers.insert(ACK_HEADER, ack_subject);
+
+    Message {
+        reply,
+        headers: Some(headers),
+        ..delivery.clone()
+    }
+}
+
+fn ack_subject(msg: &Message) -> Option<String> {
+    msg.headers
+        .as_ref()?
+        .get(ACK_HEADER)
+        .map(|subject| subject.as_str().to_string())
+}
@@ -0,0 +1,86 @@
+//! Lifecycle Events
+//! Machine-readable startup and shutdown milestones published on `system.lifecycle`
+
+use super::bus::SharedBus;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+pub const LIFECYCLE_SUBJECT: &str = "system.lifecycle";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Connected to the bus, nothing loaded yet
+    Starting,
+    /// Open orders and positions loaded from the database
+    StateLoaded,
+    /// Every subscription is live
+    Ready,
+    /// Shutdown requested, intake stopping
+    Draining,
+    Stopped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LifecycleEvent<'a> {
+    pub event: Phase,
+    pub instance_id: &'a str,
+    pub version: &'static str,
+    pub profile: &'a str,
+    pub timestamp: DateTime<Utc>,
+    pub uptime_ms: u64,
+    /// Phase-specific counts, e.g. `open_orders` on `state_loaded`
+    pub counts: BTreeMap<&'static str, u64>,
+}
+
+/// Publishes this instance's lifecycle so rollouts can be followed replica by replica
+pub struct Lifecycle {
+    bus: SharedBus,
+    instance_id: String,
+    profile: String,
+    started: Instant,
+}
+
+impl Lifecycle {
+    pub fn new(bus: SharedBus, instance_id: String, profile: String) -> Self {
+        Self {
+            bus,
+            instance_id,
+            profile,
+            started: Instant::now(),
+        }
+    }
+
+    /// Best effort: a lifecycle event that cannot be published never holds up startup or shutdown
+    pub async fn publish(&self, phase: Phase, counts: &[(&'static str, u64)]) {
+        let event = LifecycleEvent {
+            event: phase,
+            instance_id: &self.instance_id,
+            version: env!("CARGO_PKG_VERSION"),
+            profile: &self.profile,
+            timestamp: Utc::now(),
+            uptime_ms: self.started.elapsed().as_millis() as u64,
+            counts: counts.iter().copied().collect(),
+        };
+        tracing::info!(event = ?phase, instance_id = %self.instance_id, counts = ?event.counts, "Lifecycle");
+
+        if let Err(e) = self
+            .bus
+            .publish(LIFECYCLE_SUBJECT.to_string(), serde_json::to_vec(&event).unwrap())
+            .await
+        {
+            tracing::warn!(event = ?phase, error = %e, "Failed to publish lifecycle event");
+        }
+    }
+
+    /// Wait until everything published so far has reached the broker, so the last
+    /// event is not lost when the process exits right after
+    pub async fn flush(&self, timeout: Duration) {
+        if tokio::time::timeout(timeout, self.bus.ping()).await.is_err() {
+            tracing::warn!("Lifecycle events may not have reached the broker before exit");
+        }
+    }
+}
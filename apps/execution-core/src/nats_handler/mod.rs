@@ -1,5 +1,13 @@
 //! NATS Message Handler Module
 
+pub mod bus;
+pub mod codec;
+pub mod intake;
+pub mod lifecycle;
+pub mod routes;
 pub mod subscriber;
 
-pub use subscriber::NatsSubscriber;
\ No newline at end of file
+pub use bus::{server_addrs, BreakerBus, BufferedBus, FailoverBus, InProcessBus, NatsBus, Region, SharedBus};
+pub use intake::{IntakeSettings, JetStreamIntake};
+pub use lifecycle::{Lifecycle, Phase};
+pub use subscriber::NatsSubscriber;
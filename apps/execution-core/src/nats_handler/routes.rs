@@ -0,0 +1,76 @@
+//! Symbol Routes
+//! Per-symbol subscriptions of a sharded instance. Each subject prefix has one stream the
+//! subscriber reads, fed by the `{prefix}.{symbol}` subscription of every symbol routed to it.
+//! When a rebalance moves a symbol to another instance its subscriptions stop taking messages
+//! and end once what they already queued has been handled.
+
+use async_nats::Message;
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::{mpsc, watch};
+
+/// Messages a prefix's stream holds before its symbols' subscriptions wait; each of those
+/// queues on its own behind it
+const FEED_CAPACITY: usize = 64;
+
+#[derive(Default)]
+pub struct SymbolRoutes {
+    /// Feed of each prefix's stream, in the order the streams were taken
+    feeds: Mutex<Vec<(String, mpsc::Sender<Message>)>>,
+    /// Dropped to stop each routed symbol's subscriptions
+    stops: Mutex<HashMap<String, watch::Sender<()>>>,
+}
+
+impl SymbolRoutes {
+    /// The stream of `prefix`, fed by every symbol routed from now on
+    pub fn stream(&self, prefix: &str) -> BoxStream<'static, Message> {
+        let (tx, rx) = mpsc::channel(FEED_CAPACITY);
+        self.feeds.lock().unwrap_or_else(|e| e.into_inner()).push((prefix.to_string(), tx));
+        stream::unfold(rx, |mut rx| async move {
+            let msg = rx.recv().await?;
+            Some((msg, rx))
+        })
+            .boxed()
+    }
+
+    /// Prefixes with a stream, in the order `route` takes their subscriptions
+    pub fn prefixes(&self) -> Vec<String> {
+        self.feeds
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(prefix, _)| prefix.clone())
+            .collect()
+    }
+
+    /// A stop for the subscriptions of `symbol` about to be routed: resolves once it is unrouted
+    pub fn stop(&self, symbol: &str) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let mut stops = self.stops.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stopped = stops.entry(symbol.to_string()).or_insert_with(|| watch::channel(()).0).subscribe();
+        async move {
+            let _ = stopped.changed().await;
+        }
+    }
+
+    /// Feed each prefix's stream from a symbol's subscription to it, given in the order of
+    /// `prefixes`; each ends once stopped and drained
+    pub fn route(&self, subscriptions: Vec<BoxStream<'static, Message>>) {
+        let feeds = self.feeds.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        for ((_, feed), mut subscription) in feeds.into_iter().zip(subscriptions) {
+            tokio::spawn(async move {
+                while let Some(msg) = subscription.next().await {
+                    if feed.send(msg).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    }
+
+    /// Stop `symbol`'s subscriptions; false if it was not routed
+    pub fn unroute(&self, symbol: &str) -> bool {
+        self.stops.lock().unwrap_or_else(|e| e.into_inner()).remove(symbol).is_some()
+    }
+}
@@ -1,17 +1,71 @@
 //! NATS Message Handler with Authentication
 //! Handles order submit, cancel, market tick execution, and position query
 
-use crate::auth::{AuthContext, AuthService};
-use crate::engine::{OrderProcessor, PositionKeeper};
-use crate::engine::order_processor::{NewOrderRequest, OrderResult, MarketTick};
+use crate::auth::{AuthContext, AuthService, permissions};
+use crate::clock::SharedClock;
+use crate::config::Config;
+use crate::ids;
+use crate::engine::{
+    AccountSettings, CancelOnDisconnect, CorporateActionProcessor, DustSweeper, EngineError, ExecutionReports, Exports, ExposureTracker, FillDelivery, Impersonation, Instruments, IntegrityChecker, Leaderboard, Ledger, MarginCalculator, MarketMakerProtection,
+    OrderHistory, OrderProcessor,
+    PositionKeeper,
+    PrivacyManager, Rebates, RejectReason, SandboxManager, StrategyLimits, TradingPauses, TwapScheduler,
+};
+use crate::engine::account_settings::OrderDefaults;
+use crate::engine::cancel_on_disconnect::{DisconnectConfig, RegisterSession, SessionRef};
+use crate::engine::corporate_actions::AnnounceRequest;
+use crate::engine::dust::{DustAction, DustConfig};
+use crate::engine::exports::{ExportConfig, ExportRequest};
+use crate::engine::fill_delivery::RegisterConsumer;
+use crate::engine::impersonation::{ImpersonationConfig, ImpersonationSession, StartImpersonation};
+use crate::engine::leaderboard::{LeaderboardConfig, LeaderboardPeriod, OptInRequest};
+use crate::engine::ledger::LedgerConfig;
+use crate::engine::executions::ExecutionReport;
+use crate::engine::handoff::{HandoffOutcome, HotState, HANDOFF_SUBJECT};
+use crate::engine::order_journal::{AckMode, JournalOutcome, OrderJournal, ACK_MODE_HEADER};
+use crate::engine::rebalance::{self, RebalanceEvent, RebalancePhase, RebalanceRequest, Rebalances};
+use crate::engine::mmp::{MmpSettings, MmpTrip};
+use crate::engine::netting::NettingEngine;
+use crate::engine::order_history::{CompactionConfig, HistoryView, OrderQuery};
+use crate::engine::order_processor::{AmendOrderRequest, CancelAllRequest, NewOrderRequest, NewStrategyRequest, DayOrderCancelled, OrderExpired, OrderResult, MarketTick, StrategyResult};
+use crate::engine::privacy::{ErasureRequest, PrivacyConfig};
+use crate::engine::rebates::RebateConfig;
+use crate::engine::risk::{ConcentrationConfig, RiskLimits};
+use crate::engine::slippage::SlippageModel;
+use crate::engine::strategy_limits::StrategyLimit;
+use crate::engine::trading_pauses::PauseRequest;
+use crate::engine::sandbox::{ProvisionRequest, SandboxConfig};
+use crate::engine::shard::{self, Evictions, Shard, EVICTIONS_SUBJECT};
+use crate::engine::twap::{TwapRequest, TwapResult};
+use crate::market_data::book_feed::{BookSnapshot, SNAPSHOT_SUBJECT_PREFIX};
+use crate::market_data::{BookFeed, MarketData, VolumeBars};
+use crate::nats_handler::bus::SharedBus;
+use crate::nats_handler::codec::OrderCodec;
+use crate::nats_handler::intake::JetStreamIntake;
+use crate::nats_handler::lifecycle::{Lifecycle, Phase};
+use crate::nats_handler::routes::SymbolRoutes;
+use crate::observability::exemplars::observe_order_latency;
+use crate::observability::metrics::get_metrics;
+use crate::observability::stages::{Stage, StageTimer, StageTimings};
+use crate::observability::{slo, subjects};
+use crate::observability::tracing_setup::link_message_trace;
+use crate::resilience::{monitor_load, ClockSkew, Dependencies, LoadShedder, LoadShedderConfig, Priority, SkewSource, Thresholds};
+use crate::storage::{Dialect, EncryptedJson, ObjectStorage, PgOrderRepository, ReadPool};
 
-use async_nats::Client;
+use enthropic_domain::TradingSession;
+use futures::future::{self, Future};
+use futures::stream::{self, BoxStream};
+use chrono::{DateTime, NaiveDate, Utc};
 use futures::StreamExt;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 
 use std::collections::HashSet;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
 // =====================================================
@@ -31,6 +85,9 @@ struct AuthPayload {
     username: String,
     role: String,
     permissions: Vec<String>,
+    /// A support agent's impersonation session to act through
+    #[serde(default, alias = "impersonationId")]
+    impersonation_id: Option<Uuid>,
 }
 
 impl From<AuthPayload> for AuthContext {
@@ -41,6 +98,7 @@ impl From<AuthPayload> for AuthContext {
             role: p.role,
             permissions: p.permissions.into_iter().collect::<HashSet<String>>(),
             token_jti: String::new(),
+            impersonation: None,
         }
     }
 }
@@ -54,6 +112,185 @@ struct OrderResponse {
     success: bool,
     order_id: Option<String>,
     error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<serde_json::Value>,
+}
+
+/// A reply with the per-stage timing of the request that produced it
+#[derive(Serialize)]
+struct TimedResponse<T> {
+    #[serde(flatten)]
+    response: T,
+    timings_us: StageTimings,
+}
+
+/// An order submit's reply, with when it was given
+#[derive(Serialize)]
+struct SubmitResponse {
+    #[serde(flatten)]
+    response: OrderResponse,
+    ack_mode: AckMode,
+    /// Set on fast acks: the journal entry the outcome is published for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    journal_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_order_id: Option<String>,
+}
+
+/// Reply code for requests turned away by load shedding
+const BUSY_CODE: &str = "BUSY";
+
+/// Reply code for new orders while running without Redis
+const QUERY_ONLY_CODE: &str = "QUERY_ONLY";
+
+/// Reply code for order entry that arrived past `ORDER_ENTRY_SLA_MS`
+const STALE_REQUEST_CODE: &str = "STALE_REQUEST";
+
+/// Gateways stamp order entry with the client's send time here, in Unix milliseconds
+const SENT_AT_HEADER: &str = "Enthropic-Sent-At";
+
+/// Messages buffered per subscription between the bus and the handlers
+const SUBSCRIPTION_QUEUE: usize = 10_000;
+
+/// How much longer than a rebalance's deadline its coordinator waits for the target's answer
+const REBALANCE_GRACE: Duration = Duration::from_secs(2);
+
+// =====================================================
+// HANDLER ERRORS
+// =====================================================
+
+/// Every failure is counted by class. Storage failures are logged at error level, which
+/// also reports them; failures caused by the request itself are only returned to the caller.
+/// Returns whether the error was unexpected.
+fn log_unexpected(handler: &'static str, error: &EngineError) -> bool {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.engine_errors_total
+            .with_label_values(&[handler, error.class()])
+            .inc();
+    }
+
+    let unexpected = error.is_unexpected();
+    if unexpected {
+        tracing::error!(handler, error = %error, "Unexpected handler error");
+    }
+    unexpected
+}
+
+/// The reply to a processed order submit, and whether it failed unexpectedly
+fn order_response(result: Result<OrderResult, EngineError>) -> (OrderResponse, bool) {
+    match result {
+        Ok(OrderResult::Accepted(order)) => (
+            OrderResponse {
+                success: true,
+                order_id: Some(order.id.to_string()),
+                error: None,
+                code: None,
+                metadata: order.metadata.map(EncryptedJson::into_inner),
+            },
+            false,
+        ),
+        Ok(OrderResult::Duplicate(order)) => (
+            OrderResponse {
+                success: true,
+                order_id: Some(order.id.to_string()),
+                error: Some("Duplicate order".into()),
+                code: None,
+                metadata: order.metadata.map(EncryptedJson::into_inner),
+            },
+            false,
+        ),
+        Ok(OrderResult::Rejected { reason, code }) => {
+            tracing::info!(code = %code, reason = %reason, "Order rejected");
+            count_rejection(code);
+            (
+                OrderResponse {
+                    success: false,
+                    order_id: None,
+                    error: Some(reason),
+                    code: Some(code.code()),
+                    metadata: None,
+                },
+                false,
+            )
+        }
+        Err(e) => {
+            let server_error = log_unexpected("order_submit", &e);
+            (
+                OrderResponse {
+                    success: false,
+                    order_id: None,
+                    error: Some(e.to_string()),
+                    code: Some(e.code()),
+                    metadata: None,
+                },
+                server_error,
+            )
+        }
+    }
+}
+
+/// The ack mode `msg` asks for by its `Enthropic-Ack-Mode` header; `None` if unknown
+fn ack_mode(msg: &async_nats::Message) -> Option<AckMode> {
+    match msg.headers.as_ref().and_then(|headers| headers.get(ACK_MODE_HEADER)) {
+        Some(mode) => AckMode::parse(mode.as_str()),
+        None => Some(AckMode::Sync),
+    }
+}
+
+/// Count an order submit answered in `mode`
+fn count_ack(mode: AckMode) {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.order_acks_total.with_label_values(&[mode.as_str()]).inc();
+    }
+}
+
+/// Count an order turned away by the engine under its class
+fn count_rejection(code: RejectReason) {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.orders_rejected_total
+            .with_label_values(&[code.class()])
+            .inc();
+    }
+}
+
+/// Publish a step of a shard rebalance on `system.rebalance`
+async fn publish_rebalance(bus: &SharedBus, event: &RebalanceEvent) {
+    let payload = serde_json::to_vec(event).unwrap();
+    if let Err(e) = bus.publish(rebalance::EVENTS_SUBJECT.to_string(), payload).await {
+        tracing::error!(rebalance_id = %event.rebalance_id, "Failed to publish rebalance event: {}", e);
+    }
+}
+
+/// Tell the sharded instances which orders a cancel closed, so they drop their copies
+async fn publish_evictions(bus: &SharedBus, evictions: &Evictions) {
+    if evictions.is_empty() {
+        return;
+    }
+    let payload = serde_json::to_vec(evictions).unwrap();
+    if let Err(e) = bus.publish(EVICTIONS_SUBJECT.to_string(), payload).await {
+        tracing::error!(orders = evictions.order_ids.len(), "Failed to publish order evictions: {}", e);
+    }
+}
+
+/// Reply for a failed engine call
+fn failure(handler: &'static str, error: &EngineError) -> serde_json::Value {
+    log_unexpected(handler, error);
+    serde_json::json!({ "success": false, "error": error.to_string(), "code": error.code() })
+}
+
+/// When the client sent `msg`, by its `Enthropic-Sent-At` header
+fn sent_at(msg: &async_nats::Message) -> Option<DateTime<Utc>> {
+    let sent_ms: i64 = msg
+        .headers
+        .as_ref()?
+        .get(SENT_AT_HEADER)?
+        .as_str()
+        .trim()
+        .parse()
+        .ok()?;
+    DateTime::<Utc>::from_timestamp_millis(sent_ms)
 }
 
 // =====================================================
@@ -61,172 +298,1476 @@ struct OrderResponse {
 // =====================================================
 
 pub struct NatsSubscriber {
-    client: Client,
+    bus: SharedBus,
+    #[allow(dead_code)]
     pool: PgPool,
     order_processor: Arc<OrderProcessor>,
     position_keeper: Arc<PositionKeeper>,
+    ledger: Arc<Ledger>,
+    margin: Arc<MarginCalculator>,
+    settings: Arc<AccountSettings>,
+    instruments: Arc<Instruments>,
+    pauses: Arc<TradingPauses>,
+    strategy_limits: Arc<StrategyLimits>,
+    mmp: Arc<MarketMakerProtection>,
+    /// Market-maker protection trips, taken by `run` to publish them
+    mmp_trips: Mutex<Option<mpsc::Receiver<MmpTrip>>>,
+    /// Execution reports of committed fills, taken by `run` to publish them
+    execution_reports: Mutex<Option<mpsc::Receiver<ExecutionReport>>>,
+    market_data: Arc<MarketData>,
+    book_feed: Arc<BookFeed>,
+    volume_bars: Arc<VolumeBars>,
+    leaderboard: Arc<Leaderboard>,
+    sandbox: Arc<SandboxManager>,
+    corporate_actions: Arc<CorporateActionProcessor>,
+    privacy: Arc<PrivacyManager>,
+    order_history: Arc<OrderHistory>,
+    impersonation: Arc<Impersonation>,
+    exports: Arc<Exports>,
+    disconnects: Arc<CancelOnDisconnect>,
+    twap: Arc<TwapScheduler>,
+    exposure: Arc<ExposureTracker>,
+    rebates: Arc<Rebates>,
+    dust: Arc<DustSweeper>,
+    integrity: Arc<IntegrityChecker>,
+    fill_delivery: Arc<FillDelivery>,
+    shedder: Arc<LoadShedder>,
+    /// Decodes order submits in the gateway's naming conventions
+    codec: OrderCodec,
+    /// Add the stage breakdown to order submit replies
+    timings_in_reply: bool,
+    /// Compare the caches with the database after every order transition
+    consistency_checks: bool,
+    /// Pull-based order intake; core NATS subscriptions when `None`
+    intake: Option<Arc<JetStreamIntake>>,
+    /// Subscriptions opened by `run`, reported when it is ready
+    subscriptions: AtomicUsize,
+    dependencies: Arc<Dependencies>,
+    clock: SharedClock,
     #[allow(dead_code)]
     auth_service: Arc<AuthService>,
+    leaderboard_interval: Duration,
+    sandbox_sweep_interval: Duration,
+    corporate_actions_interval: Duration,
+    privacy_sweep_interval: Duration,
+    order_compaction_interval: Duration,
+    export_interval: Duration,
+    disconnect_sweep_interval: Duration,
+    twap_interval: Duration,
+    exposure_close_interval: Duration,
+    rebate_settle_interval: Duration,
+    dust_sweep_interval: Duration,
+    volume_flush_interval: Duration,
+    integrity_check_interval: Duration,
+    instrument_refresh_interval: Duration,
+    order_expiry_interval: Duration,
+    trading_session: Option<TradingSession>,
+    fill_delivery_interval: Duration,
+    book_delta_interval: Duration,
+    load_shed_enabled: bool,
+    /// Order entry older than this is rejected unexecuted; zero disables
+    order_entry_sla: Duration,
+    /// Client clocks are sampled from order entry; the SLA is only enforced while they are trusted
+    clock_skew: Arc<ClockSkew>,
+    /// Identifies this instance's own state handoff, which it ignores
+    instance_id: String,
+    /// Symbols whose order flow, ticks and book this instance serves; all when `None`
+    shard: Option<Arc<Shard>>,
+    /// Whether a sharded instance also serves the subjects not routed by symbol and runs the
+    /// jobs that are not
+    shard_shared: bool,
+    routes: SymbolRoutes,
+    rebalances: Arc<Rebalances>,
+    /// A move that has not settled this long after it was requested goes back to its source
+    rebalance_timeout: Duration,
+    journal: OrderJournal,
 }
 
 impl NatsSubscriber {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        client: Client,
+        bus: SharedBus,
         pool: PgPool,
+        reads: ReadPool,
         auth_service: Arc<AuthService>,
+        config: &Config,
+        clock: SharedClock,
+        dialect: Dialect,
+        dependencies: Arc<Dependencies>,
+        intake: Option<Arc<JetStreamIntake>>,
+        clock_skew: Arc<ClockSkew>,
+        netting: NettingEngine,
+        concentration: ConcentrationConfig,
+        rebate_config: RebateConfig,
+        slippage: SlippageModel,
+        export_storage: Option<Arc<ObjectStorage>>,
+        shard: Option<Arc<Shard>>,
     ) -> Self {
+        let leaderboard_config = LeaderboardConfig {
+            reference_capital: config.leaderboard_reference_capital,
+            max_entries: config.leaderboard_max_entries,
+        };
+
+        let sandbox_config = SandboxConfig {
+            default_balance: config.sandbox_default_balance,
+            default_ttl_secs: config.sandbox_default_ttl_secs,
+            ..SandboxConfig::default()
+        };
+
+        let privacy_config = PrivacyConfig {
+            erasure_retention_secs: config.erasure_retention_days * 86_400,
+        };
+
+        let compaction_config = CompactionConfig {
+            min_age_secs: config.order_compaction_min_age_days * 86_400,
+            batch_size: config.order_compaction_batch_size,
+        };
+
+        let export_config = ExportConfig {
+            max_range_days: config.export_max_range_days as i64,
+            ..ExportConfig::default()
+        };
+
+        let shedder_config = LoadShedderConfig {
+            enabled: config.load_shed_enabled,
+            shed_queries: Thresholds {
+                event_loop_lag: Duration::from_millis(config.load_shed_query_lag_ms),
+                queue_depth: config.load_shed_query_queue_depth,
+                db_latency: Duration::from_millis(config.load_shed_query_db_ms),
+            },
+            shed_orders: Thresholds {
+                event_loop_lag: Duration::from_millis(config.load_shed_order_lag_ms),
+                queue_depth: config.load_shed_order_queue_depth,
+                db_latency: Duration::from_millis(config.load_shed_order_db_ms),
+            },
+            ..LoadShedderConfig::default()
+        };
+
+        let ledger_config = LedgerConfig {
+            enforce_balances: config.ledger_enforce_balances,
+            default_quote: config.ledger_default_quote.clone(),
+            market_hold_buffer: config.ledger_market_hold_buffer,
+        };
+
+        let ids = ids::from_strategy(&config.id_strategy, clock.clone());
+        // Client ids generated for requests that leave theirs out come from the same generator
+        enthropic_domain::order::set_client_id_source({
+            let ids = ids.clone();
+            move || ids.next_id()
+        });
+        let ledger = Arc::new(Ledger::new(reads.clone(), ledger_config, ids.clone()));
+        let market_data = Arc::new(MarketData::new(config.market_data_history));
+        let rebates = Arc::new(Rebates::new(pool.clone(), ledger.clone(), rebate_config, clock.clone()));
+        let volume_bars = Arc::new(VolumeBars::new(
+            pool.clone(),
+            config.volume_bar_secs,
+            config.vwap_lookback_days,
+            config.volume_bar_retention_days,
+        ));
+        let settings = Arc::new(AccountSettings::new(pool.clone()));
+        let instruments = Arc::new(Instruments::new(pool.clone()));
+        let position_keeper = Arc::new(PositionKeeper::new(pool.clone(), reads.clone()));
+        let risk = Arc::new(RiskLimits::new(concentration, config.drawdown_alert, position_keeper.clone()));
+        position_keeper.fill_topic().subscribe(risk.clone());
+        let pauses = Arc::new(TradingPauses::new(pool.clone(), clock.clone()));
+        let strategy_limits = Arc::new(StrategyLimits::new(pool.clone(), clock.clone()));
+        let (mmp, mmp_trips) = MarketMakerProtection::new(pool.clone(), clock.clone());
+        let mmp = Arc::new(mmp);
+        let (executions, execution_reports) = ExecutionReports::new();
+        let order_processor = Arc::new(OrderProcessor::new(
+            pool.clone(),
+            reads.primary_limiter(),
+            Arc::new(PgOrderRepository::new(pool.clone(), dialect)),
+            ledger.clone(),
+            rebates.clone(),
+            settings.clone(),
+            instruments.clone(),
+            pauses.clone(),
+            risk,
+            strategy_limits.clone(),
+            mmp.clone(),
+            executions,
+            slippage,
+            config.internal_crossing,
+            market_data.clone(),
+            clock.clone(),
+            ids.clone(),
+            shard.clone(),
+        ));
+        let impersonation_config = ImpersonationConfig {
+            max_consent_secs: config.support_consent_max_secs as i64,
+            max_session_secs: config.impersonation_max_secs as i64,
+        };
+        let trading_session = TradingSession::parse(&config.session_close);
+        if trading_session.is_none() && !config.session_close.is_empty() {
+            tracing::warn!(close = %config.session_close, "Invalid SESSION_CLOSE, day orders will not be cancelled");
+        }
+        let dust_config = DustConfig {
+            threshold: config.dust_threshold,
+            action: DustAction::parse(&config.dust_action).unwrap_or(DustAction::Flag),
+        };
+        let margin = Arc::new(MarginCalculator::new(
+            netting,
+            config.margin_rate,
+            order_processor.clone(),
+            position_keeper.clone(),
+        ));
+
         Self {
-            order_processor: Arc::new(OrderProcessor::new(pool.clone())),
-            position_keeper: Arc::new(PositionKeeper::new(pool.clone())),
-            client,
+            sandbox: Arc::new(SandboxManager::new(
+                pool.clone(),
+                ledger.clone(),
+                order_processor.clone(),
+                position_keeper.clone(),
+                sandbox_config,
+                clock.clone(),
+            )),
+            corporate_actions: Arc::new(CorporateActionProcessor::new(
+                pool.clone(),
+                reads.clone(),
+                ledger.clone(),
+                order_processor.clone(),
+                position_keeper.clone(),
+                clock.clone(),
+            )),
+            privacy: Arc::new(PrivacyManager::new(
+                pool.clone(),
+                ledger.clone(),
+                order_processor.clone(),
+                position_keeper.clone(),
+                privacy_config,
+                clock.clone(),
+            )),
+            order_history: Arc::new(OrderHistory::new(pool.clone(), reads.clone(), compaction_config, clock.clone())),
+            impersonation: Arc::new(Impersonation::new(pool.clone(), impersonation_config, clock.clone())),
+            exports: Arc::new(Exports::new(pool.clone(), export_storage, export_config, clock.clone())),
+            disconnects: Arc::new(CancelOnDisconnect::new(
+                pool.clone(),
+                order_processor.clone(),
+                DisconnectConfig { max_timeout_secs: config.disconnect_max_timeout_secs as i64 },
+                clock.clone(),
+            )),
+            twap: Arc::new(TwapScheduler::new(
+                pool.clone(),
+                order_processor.clone(),
+                position_keeper.clone(),
+                volume_bars.clone(),
+                clock.clone(),
+            )),
+            volume_bars,
+            exposure: Arc::new(ExposureTracker::new(pool.clone(), clock.clone())),
+            rebates,
+            dust: Arc::new(DustSweeper::new(
+                pool.clone(),
+                ledger.clone(),
+                order_processor.clone(),
+                position_keeper.clone(),
+                dust_config,
+                clock.clone(),
+                ids.clone(),
+            )),
+            integrity: Arc::new(IntegrityChecker::new(pool.clone(), clock.clone())),
+            journal: OrderJournal::new(pool.clone(), ids.clone()),
+            fill_delivery: Arc::new(FillDelivery::new(
+                pool.clone(),
+                clock.clone(),
+                config.fill_delivery_max_attempts,
+            )),
+            load_shed_enabled: shedder_config.enabled,
+            order_entry_sla: Duration::from_millis(config.order_entry_sla_ms),
+            instance_id: config.instance_id.clone(),
+            clock_skew,
+            shedder: Arc::new(LoadShedder::new(shedder_config, clock.clone())),
+            codec: OrderCodec::new(config.order_codec_strict),
+            timings_in_reply: config.order_timings_in_reply,
+            consistency_checks: config.consistency_checks,
+            intake,
+            subscriptions: AtomicUsize::new(0),
+            dependencies,
+            order_processor,
+            position_keeper,
+            ledger,
+            margin,
+            settings,
+            instruments,
+            pauses,
+            strategy_limits,
+            mmp,
+            mmp_trips: Mutex::new(Some(mmp_trips)),
+            execution_reports: Mutex::new(Some(execution_reports)),
+            market_data,
+            book_feed: Arc::new(BookFeed::new(config.book_depth_levels)),
+            leaderboard: Arc::new(Leaderboard::new(pool.clone(), reads, leaderboard_config, clock.clone())),
+            clock,
+            bus,
             pool,
             auth_service,
+            leaderboard_interval: Duration::from_secs(config.leaderboard_publish_interval_secs),
+            sandbox_sweep_interval: Duration::from_secs(config.sandbox_sweep_interval_secs),
+            corporate_actions_interval: Duration::from_secs(config.corporate_actions_interval_secs),
+            privacy_sweep_interval: Duration::from_secs(config.privacy_sweep_interval_secs),
+            order_compaction_interval: Duration::from_secs(config.order_compaction_interval_secs),
+            export_interval: Duration::from_secs(config.export_interval_secs),
+            disconnect_sweep_interval: Duration::from_secs(config.disconnect_sweep_interval_secs),
+            twap_interval: Duration::from_secs(config.twap_interval_secs),
+            exposure_close_interval: Duration::from_secs(config.exposure_close_interval_secs),
+            rebate_settle_interval: Duration::from_secs(config.rebate_settle_interval_secs),
+            dust_sweep_interval: Duration::from_secs(config.dust_sweep_interval_secs),
+            volume_flush_interval: Duration::from_secs(config.volume_bar_secs),
+            integrity_check_interval: Duration::from_secs(config.ledger_integrity_interval_secs),
+            instrument_refresh_interval: Duration::from_secs(config.instrument_refresh_interval_secs),
+            order_expiry_interval: Duration::from_secs(config.order_expiry_interval_secs),
+            trading_session,
+            fill_delivery_interval: Duration::from_secs(config.fill_delivery_interval_secs),
+            book_delta_interval: Duration::from_millis(config.book_delta_interval_ms),
+            shard,
+            shard_shared: config.shard_shared,
+            routes: SymbolRoutes::default(),
+            rebalances: Arc::new(Rebalances::default()),
+            rebalance_timeout: Duration::from_millis(config.rebalance_timeout_ms),
         }
     }
 
-    pub async fn initialize(&self) -> anyhow::Result<()> {
-        self.order_processor.load_open_orders().await?;
-        self.position_keeper.load_positions().await?;
+    /// Load instrument rules, open orders and positions; returns how many orders and positions
+    pub async fn initialize(&self) -> anyhow::Result<(usize, usize)> {
+        let instruments = self.instruments.load().await?;
+        tracing::info!(instruments, "Instrument trading rules loaded");
+        let open_orders = self.order_processor.load_open_orders().await?;
+        let positions = self.position_keeper.load_positions().await?;
         tracing::info!("Execution core initialized");
-        Ok(())
+        Ok((open_orders, positions))
+    }
+
+    /// Messages received but not yet handled, across every subscription
+    pub fn queued(&self) -> usize {
+        self.shedder.queue_depth()
     }
 
-    pub async fn run(&self) -> anyhow::Result<()> {
-        let mut order_sub = self.client.subscribe("orders.submit").await?;
-        let mut cancel_sub = self.client.subscribe("orders.cancel").await?;
-        let mut position_sub = self.client.subscribe("positions.query").await?;
-        let mut market_sub = self.client.subscribe("market.tick.*").await?;
+    pub async fn run(&self, lifecycle: &Lifecycle) -> anyhow::Result<()> {
+        // Background work starts first: subscribing waits while NATS is unreachable. When
+        // sharded, work on the book of a symbol runs where it is traded and the rest only where
+        // the shared subjects are served.
+        let shared = self.serves_shared();
+
+        if shared && !self.leaderboard_interval.is_zero() {
+            tokio::spawn(publish_leaderboards(
+                self.bus.clone(),
+                self.leaderboard.clone(),
+                self.leaderboard_interval,
+            ));
+        }
+
+        if shared && !self.sandbox_sweep_interval.is_zero() {
+            tokio::spawn(sweep_sandboxes(self.sandbox.clone(), self.sandbox_sweep_interval));
+        }
+
+        if !self.corporate_actions_interval.is_zero() {
+            tokio::spawn(process_corporate_actions(
+                self.corporate_actions.clone(),
+                self.corporate_actions_interval,
+            ));
+        }
+
+        if !self.order_expiry_interval.is_zero() {
+            tokio::spawn(sweep_expired_orders(
+                self.bus.clone(),
+                self.order_processor.clone(),
+                self.order_expiry_interval,
+            ));
+        }
+
+        if let Some(session) = self.trading_session {
+            tokio::spawn(close_day_orders(
+                self.bus.clone(),
+                self.order_processor.clone(),
+                session,
+                self.clock.clone(),
+            ));
+        }
+
+        if !self.twap_interval.is_zero() {
+            tokio::spawn(schedule_twaps(self.twap.clone(), self.dependencies.clone(), self.twap_interval));
+        }
+
+        if shared && !self.exposure_close_interval.is_zero() {
+            tokio::spawn(close_exposure_sessions(self.exposure.clone(), self.exposure_close_interval));
+        }
+
+        if shared && !self.rebate_settle_interval.is_zero() {
+            tokio::spawn(settle_rebates(self.rebates.clone(), self.rebate_settle_interval));
+        }
+
+        if shared && !self.dust_sweep_interval.is_zero() && self.dust.enabled() {
+            tokio::spawn(sweep_dust(self.dust.clone(), self.dust_sweep_interval));
+        }
+
+        if !self.volume_flush_interval.is_zero() {
+            tokio::spawn(flush_volume_bars(self.volume_bars.clone(), self.clock.clone(), self.volume_flush_interval));
+        }
+
+        if shared && !self.fill_delivery_interval.is_zero() {
+            tokio::spawn(deliver_fills(
+                self.bus.clone(),
+                self.fill_delivery.clone(),
+                self.fill_delivery_interval,
+            ));
+        }
+
+        let mmp_trips = self.mmp_trips.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(trips) = mmp_trips {
+            tokio::spawn(publish_mmp_trips(self.bus.clone(), trips));
+        }
+
+        let execution_reports = self.execution_reports.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(reports) = execution_reports {
+            tokio::spawn(publish_execution_reports(self.bus.clone(), reports));
+        }
+
+        if shared && !self.privacy_sweep_interval.is_zero() {
+            tokio::spawn(sweep_erasures(self.privacy.clone(), self.privacy_sweep_interval));
+        }
+
+        if shared && !self.order_compaction_interval.is_zero() {
+            tokio::spawn(compact_order_events(self.order_history.clone(), self.order_compaction_interval));
+        }
+
+        if shared && !self.export_interval.is_zero() && self.exports.enabled() {
+            tokio::spawn(generate_exports(self.bus.clone(), self.exports.clone(), self.export_interval));
+        }
+
+        if shared && !self.disconnect_sweep_interval.is_zero() {
+            tokio::spawn(sweep_disconnects(
+                self.bus.clone(),
+                self.disconnects.clone(),
+                self.disconnect_sweep_interval,
+                self.shard.is_some(),
+            ));
+        }
+
+        if self.load_shed_enabled {
+            tokio::spawn(monitor_load(self.shedder.clone(), self.pool.clone()));
+        }
+
+        if !self.book_delta_interval.is_zero() {
+            tokio::spawn(publish_book_deltas(
+                self.bus.clone(),
+                self.order_processor.clone(),
+                self.book_feed.clone(),
+                self.clock.clone(),
+                self.book_delta_interval,
+            ));
+        }
+
+        if !self.instrument_refresh_interval.is_zero() {
+            tokio::spawn(refresh_instruments(self.instruments.clone(), self.instrument_refresh_interval));
+        }
+
+        if shared && !self.integrity_check_interval.is_zero() {
+            tokio::spawn(check_ledger_integrity(
+                self.bus.clone(),
+                self.integrity.clone(),
+                self.integrity_check_interval,
+            ));
+        }
+
+        let mut order_sub = self.subscribe_orders("orders.submit").await?;
+        let mut cancel_sub = self.subscribe_orders("orders.cancel").await?;
+        let mut cancel_all_sub = self.subscribe_shared("orders.cancel_all").await?;
+        let mut session_register_sub = self.subscribe_shared("sessions.register").await?;
+        let mut session_heartbeat_sub = self.subscribe_shared("sessions.heartbeat").await?;
+        let mut session_close_sub = self.subscribe_shared("sessions.close").await?;
+        let mut reduce_sub = self.subscribe_orders("orders.reduce").await?;
+        let mut amend_sub = self.subscribe_orders("orders.amend").await?;
+        let mut strategy_sub = self.subscribe_orders("orders.strategy.submit").await?;
+        let mut strategy_cancel_sub = self.subscribe_shared("orders.strategy.cancel").await?;
+        let mut twap_sub = self.subscribe_shared("orders.twap.submit").await?;
+        let mut twap_cancel_sub = self.subscribe_shared("orders.twap.cancel").await?;
+        let mut twap_status_sub = self.subscribe_shared("orders.twap.status").await?;
+        let mut history_sub = self.subscribe_shared("orders.history").await?;
+        let mut timeline_sub = self.subscribe_shared("orders.timeline").await?;
+        let mut list_sub = self.subscribe_shared("orders.list").await?;
+        let mut position_sub = self.subscribe_shared("positions.query").await?;
+        let mut margin_sub = self.subscribe_shared("positions.margin").await?;
+        let mut open_interest_sub = self.subscribe_shared("positions.open_interest").await?;
+        let mut exposure_sub = self.subscribe_shared("positions.exposure").await?;
+        let mut balance_sub = self.subscribe_shared("balances.query").await?;
+        let mut rebate_statement_sub = self.subscribe_shared("fees.rebates.statement").await?;
+        let mut referral_sub = self.subscribe_shared("fees.referrals.set").await?;
+        let mut market_sub = self.subscribe_symbols("market.tick").await?;
+        let mut leaderboard_sub = self.subscribe_shared("leaderboard.query").await?;
+        let mut optin_sub = self.subscribe_shared("leaderboard.optin").await?;
+        let mut optout_sub = self.subscribe_shared("leaderboard.optout").await?;
+        let mut sandbox_provision_sub = self.subscribe_shared("sandbox.provision").await?;
+        let mut sandbox_reset_sub = self.subscribe_shared("sandbox.reset").await?;
+        let mut ca_announce_sub = self.subscribe_shared("corporate_actions.announce").await?;
+        let mut ca_cancel_sub = self.subscribe_shared("corporate_actions.cancel").await?;
+        let mut ca_query_sub = self.subscribe_shared("corporate_actions.query").await?;
+        let mut export_sub = self.subscribe_shared("accounts.export").await?;
+        let mut export_request_sub = self.subscribe_shared("exports.request").await?;
+        let mut export_status_sub = self.subscribe_shared("exports.status").await?;
+        let mut erase_sub = self.subscribe_shared("accounts.erase").await?;
+        let mut settings_sub = self.subscribe_shared("accounts.settings").await?;
+        let mut pauses_sub = self.subscribe_shared("accounts.pauses").await?;
+        let mut strategy_limits_sub = self.subscribe_shared("accounts.strategy_limits").await?;
+        let mut mmp_sub = self.subscribe_shared("accounts.mmp").await?;
+        let mut fill_consumers_sub = self.subscribe_shared("fills.consumers").await?;
+        let mut fill_ack_sub = self.subscribe_shared("fills.ack").await?;
+        let mut slo_sub = self.subscribe_shared("slo.status").await?;
+        let mut book_snapshot_sub = self.subscribe_symbols("orderbook.snapshot").await?;
+        let mut consent_sub = self.subscribe_shared("accounts.support_consent").await?;
+        let mut impersonation_sub = self.subscribe_shared("support.impersonation").await?;
+        let mut handoff_sub = self.subscribe(HANDOFF_SUBJECT).await?;
+        let mut rebalance_sub = self.subscribe_shared(rebalance::REBALANCE_SUBJECT).await?;
+        let mut rebalance_events_sub = match &self.shard {
+            Some(_) => self.subscribe(rebalance::EVENTS_SUBJECT).await?,
+            None => stream::pending().boxed(),
+        };
+        let mut evictions_sub = match &self.shard {
+            Some(_) => self.subscribe(EVICTIONS_SUBJECT).await?,
+            None => stream::pending().boxed(),
+        };
+        // Per-symbol subjects last, once every routed prefix has its stream
+        if let Some(shard) = &self.shard {
+            for symbol in shard.symbols() {
+                self.route_symbol(&symbol).await?;
+            }
+        }
+
+        // Orders already acknowledged come before any new ones
+        self.replay_journal().await?;
 
         tracing::info!("NATS subscriber running");
+        let subscriptions = self.subscriptions.load(Ordering::Relaxed) as u64;
+        let shard_symbols = self.shard.as_ref().map_or(0, |shard| shard.len()) as u64;
+        lifecycle
+            .publish(Phase::Ready, &[("subscriptions", subscriptions), ("shard_symbols", shard_symbols)])
+            .await;
 
         loop {
             tokio::select! {
                 Some(msg) = order_sub.next() => {
                     self.handle_order_submit(msg).await;
+                    self.check_consistency("submit").await;
                 }
                 Some(msg) = cancel_sub.next() => {
                     self.handle_order_cancel(msg).await;
+                    self.check_consistency("cancel").await;
+                }
+                Some(msg) = cancel_all_sub.next() => {
+                    self.handle_order_cancel_all(msg).await;
+                    self.check_consistency("cancel_all").await;
+                }
+                Some(msg) = session_register_sub.next() => {
+                    self.handle_session_register(msg).await;
+                }
+                Some(msg) = session_heartbeat_sub.next() => {
+                    self.handle_session_heartbeat(msg).await;
+                }
+                Some(msg) = session_close_sub.next() => {
+                    self.handle_session_close(msg).await;
+                }
+                Some(msg) = reduce_sub.next() => {
+                    self.handle_order_reduce(msg).await;
+                    self.check_consistency("reduce").await;
+                }
+                Some(msg) = amend_sub.next() => {
+                    self.handle_order_amend(msg).await;
+                    self.check_consistency("amend").await;
+                }
+                Some(msg) = strategy_sub.next() => {
+                    self.handle_strategy_submit(msg).await;
+                }
+                Some(msg) = strategy_cancel_sub.next() => {
+                    self.handle_strategy_cancel(msg).await;
+                }
+                Some(msg) = twap_sub.next() => {
+                    self.handle_twap_submit(msg).await;
+                }
+                Some(msg) = twap_cancel_sub.next() => {
+                    self.handle_twap_cancel(msg).await;
+                }
+                Some(msg) = twap_status_sub.next() => {
+                    self.handle_twap_status(msg).await;
+                }
+                Some(msg) = history_sub.next() => {
+                    self.handle_order_history(msg).await;
+                }
+                Some(msg) = timeline_sub.next() => {
+                    self.handle_order_timeline(msg).await;
+                }
+                Some(msg) = list_sub.next() => {
+                    self.handle_order_list(msg).await;
                 }
                 Some(msg) = position_sub.next() => {
                     self.handle_position_query(msg).await;
                 }
+                Some(msg) = margin_sub.next() => {
+                    self.handle_margin_query(msg).await;
+                }
+                Some(msg) = open_interest_sub.next() => {
+                    self.handle_open_interest_query(msg).await;
+                }
+                Some(msg) = exposure_sub.next() => {
+                    self.handle_exposure_query(msg).await;
+                }
+                Some(msg) = balance_sub.next() => {
+                    self.handle_balance_query(msg).await;
+                }
+                Some(msg) = rebate_statement_sub.next() => {
+                    self.handle_rebate_statement(msg).await;
+                }
+                Some(msg) = referral_sub.next() => {
+                    self.handle_referral_set(msg).await;
+                }
                 Some(msg) = market_sub.next() => {
                     self.handle_market_tick(msg).await;
+                    self.check_consistency("tick").await;
+                }
+                Some(msg) = leaderboard_sub.next() => {
+                    self.handle_leaderboard_query(msg).await;
+                }
+                Some(msg) = optin_sub.next() => {
+                    self.handle_leaderboard_optin(msg).await;
+                }
+                Some(msg) = optout_sub.next() => {
+                    self.handle_leaderboard_optout(msg).await;
+                }
+                Some(msg) = sandbox_provision_sub.next() => {
+                    self.handle_sandbox_provision(msg).await;
+                }
+                Some(msg) = sandbox_reset_sub.next() => {
+                    self.handle_sandbox_reset(msg).await;
+                }
+                Some(msg) = ca_announce_sub.next() => {
+                    self.handle_corporate_action_announce(msg).await;
+                }
+                Some(msg) = ca_cancel_sub.next() => {
+                    self.handle_corporate_action_cancel(msg).await;
+                }
+                Some(msg) = ca_query_sub.next() => {
+                    self.handle_corporate_action_query(msg).await;
+                }
+                Some(msg) = export_sub.next() => {
+                    self.handle_account_export(msg).await;
+                }
+                Some(msg) = export_request_sub.next() => {
+                    self.handle_export_request(msg).await;
+                }
+                Some(msg) = export_status_sub.next() => {
+                    self.handle_export_status(msg).await;
+                }
+                Some(msg) = erase_sub.next() => {
+                    self.handle_account_erase(msg).await;
+                }
+                Some(msg) = settings_sub.next() => {
+                    self.handle_account_settings(msg).await;
+                }
+                Some(msg) = pauses_sub.next() => {
+                    self.handle_account_pauses(msg).await;
+                }
+                Some(msg) = strategy_limits_sub.next() => {
+                    self.handle_strategy_limits(msg).await;
+                }
+                Some(msg) = mmp_sub.next() => {
+                    self.handle_mmp(msg).await;
+                }
+                Some(msg) = fill_consumers_sub.next() => {
+                    self.handle_fill_consumers(msg).await;
+                }
+                Some(msg) = fill_ack_sub.next() => {
+                    self.handle_fill_ack(msg).await;
+                }
+                Some(msg) = slo_sub.next() => {
+                    self.handle_slo_status(msg).await;
+                }
+                Some(msg) = book_snapshot_sub.next() => {
+                    self.handle_book_snapshot(msg).await;
+                }
+                Some(msg) = consent_sub.next() => {
+                    self.handle_support_consent(msg).await;
+                }
+                Some(msg) = impersonation_sub.next() => {
+                    self.handle_impersonation(msg).await;
+                }
+                Some(msg) = handoff_sub.next() => {
+                    self.handle_handoff(msg).await;
+                }
+                Some(msg) = rebalance_sub.next() => {
+                    self.handle_rebalance(msg).await;
+                }
+                Some(msg) = rebalance_events_sub.next() => {
+                    self.handle_rebalance_event(msg).await;
+                }
+                Some(msg) = evictions_sub.next() => {
+                    self.handle_evictions(msg).await;
                 }
             }
         }
     }
 
-    // =====================================================
-    // ORDER SUBMIT
-    // =====================================================
+    /// Subscribe through a local queue so the load shedder can see how far handlers are behind;
+    /// every message is counted towards its subscription's stats on the way in. Order subjects
+    /// come from the JetStream intake when it is configured.
+    async fn subscribe(&self, subject: &str) -> anyhow::Result<BoxStream<'static, async_nats::Message>> {
+        self.subscribe_as(subject, subject, future::pending()).await
+    }
 
-    async fn handle_order_submit(&self, msg: async_nats::Message) {
-        let parsed: Result<AuthenticatedMessage<NewOrderRequest>, _> =
-            serde_json::from_slice(&msg.payload);
+    /// `subscribe`, with the stats of `subject` counted under `pattern`. Once `stop` resolves
+    /// the subscription takes no more messages and ends after those already queued.
+    async fn subscribe_as(
+        &self,
+        subject: &str,
+        pattern: &str,
+        stop: impl Future<Output = ()> + Send + 'static,
+    ) -> anyhow::Result<BoxStream<'static, async_nats::Message>> {
+        let upstream = match self.intake.as_ref().and_then(|intake| intake.subscribe(subject)) {
+            Some(pulled) => pulled,
+            None => self.bus.subscribe(subject).await?,
+        };
+        self.subscriptions.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::channel(SUBSCRIPTION_QUEUE);
 
-        let response = match parsed {
-            Ok(auth_msg) => {
-                let auth: AuthContext = auth_msg.auth.into();
-                match self.order_processor.submit_order(&auth, auth_msg.data).await {
-                    Ok(OrderResult::Accepted(order)) => OrderResponse {
-                        success: true,
-                        order_id: Some(order.id.to_string()),
-                        error: None,
-                    },
-                    Ok(OrderResult::Duplicate(order)) => OrderResponse {
-                        success: true,
-                        order_id: Some(order.id.to_string()),
-                        error: Some("Duplicate order".into()),
-                    },
-                    Ok(OrderResult::Rejected { reason, .. }) => OrderResponse {
-                        success: false,
-                        order_id: None,
-                        error: Some(reason),
-                    },
-                    Err(e) => OrderResponse {
-                        success: false,
-                        order_id: None,
-                        error: Some(e.to_string()),
-                    },
+        let (shedder, pattern) = (self.shedder.clone(), pattern.to_string());
+        let mut upstream = upstream.take_until(Box::pin(stop));
+        tokio::spawn(async move {
+            while let Some(msg) = upstream.next().await {
+                subjects::record_received(&pattern, msg.payload.len());
+                shedder.enqueued();
+                if tx.send(msg).await.is_err() {
+                    shedder.dequeued();
+                    break;
                 }
             }
-            Err(e) => OrderResponse {
-                success: false,
-                order_id: None,
-                error: Some(format!("Invalid payload: {}", e)),
-            },
+        });
+
+        let shedder = self.shedder.clone();
+        let messages = stream::unfold(rx, move |mut rx| {
+            let shedder = shedder.clone();
+            async move {
+                let msg = rx.recv().await?;
+                shedder.dequeued();
+                Some((msg, rx))
+            }
+        });
+
+        Ok(messages.boxed())
+    }
+
+    /// Whether this instance serves the subjects not routed by symbol: always unless sharded
+    fn serves_shared(&self) -> bool {
+        self.shard.is_none() || self.shard_shared
+    }
+
+    /// A subject not routed by symbol; never delivers on a sharded instance that leaves it to
+    /// the one serving shared subjects
+    async fn subscribe_shared(&self, subject: &str) -> anyhow::Result<BoxStream<'static, async_nats::Message>> {
+        if !self.serves_shared() {
+            return Ok(stream::pending().boxed());
+        }
+        self.subscribe(subject).await
+    }
+
+    /// Order flow on `subject`. Sharded, also `{subject}.{symbol}` for every owned symbol; the
+    /// bare subject is then only served with the shared ones, answering other symbols
+    /// `WRONG_SHARD`.
+    async fn subscribe_orders(&self, subject: &str) -> anyhow::Result<BoxStream<'static, async_nats::Message>> {
+        if self.shard.is_none() {
+            return self.subscribe(subject).await;
+        }
+        let streams = vec![self.subscribe_shared(subject).await?, self.routes.stream(subject)];
+        Ok(stream::select_all(streams).boxed())
+    }
+
+    /// `{prefix}.{symbol}` for every symbol this instance trades
+    async fn subscribe_symbols(&self, prefix: &str) -> anyhow::Result<BoxStream<'static, async_nats::Message>> {
+        if self.shard.is_none() {
+            return self.subscribe(&format!("{}.*", prefix)).await;
+        }
+        Ok(self.routes.stream(prefix))
+    }
+
+    /// Subscribe to `symbol` on every routed prefix, once all are known
+    async fn route_symbol(&self, symbol: &str) -> anyhow::Result<()> {
+        let mut subscriptions = Vec::new();
+        for prefix in self.routes.prefixes() {
+            let subject = format!("{}.{}", prefix, symbol);
+            let pattern = format!("{}.*", prefix);
+            subscriptions.push(self.subscribe_as(&subject, &pattern, self.routes.stop(symbol)).await?);
+        }
+        self.routes.route(subscriptions);
+        Ok(())
+    }
+
+    /// Reject new orders while in query-only mode; true if the message was answered
+    async fn query_only(&self, msg: &async_nats::Message) -> bool {
+        if self.dependencies.accepts_orders() {
+            return false;
+        }
+
+        let response = serde_json::json!({
+            "success": false,
+            "error": "Order intake unavailable, execution core is serving queries only",
+            "code": QUERY_ONLY_CODE,
+        });
+        self.respond(msg, &response).await;
+        true
+    }
+
+    /// Reply `STALE_REQUEST` and return true when the request is older than the order-entry
+    /// SLA: the client has likely given up on it, so it must not trade. Skipped while clock
+    /// skew makes client timestamps untrustworthy.
+    async fn stale(&self, msg: &async_nats::Message) -> bool {
+        let Some(sent) = sent_at(msg) else {
+            return false;
         };
+        self.clock_skew.observe(SkewSource::Client, sent);
+        if self.order_entry_sla.is_zero() || !self.clock_skew.timestamps_trusted() {
+            return false;
+        }
 
-        if let Some(reply) = msg.reply {
-            let _ = self.client
-                .publish(reply, serde_json::to_vec(&response).unwrap().into())
-                .await;
+        // A client clock running ahead counts as no age at all
+        let age = (self.clock.now() - sent).to_std().unwrap_or_default();
+        if age <= self.order_entry_sla {
+            return false;
         }
+
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.stale_requests_total.with_label_values(&[msg.subject.as_str()]).inc();
+        }
+        let response = serde_json::json!({
+            "success": false,
+            "error": format!(
+                "Request is {}ms old, past the {}ms order-entry SLA",
+                age.as_millis(),
+                self.order_entry_sla.as_millis()
+            ),
+            "code": STALE_REQUEST_CODE,
+        });
+        self.respond(msg, &response).await;
+        true
     }
 
-    // =====================================================
-    // MARKET TICK
-    // =====================================================
+    /// Reply `BUSY` and return true when load shedding turns this request away
+    async fn shed(&self, msg: &async_nats::Message, priority: Priority) -> bool {
+        if self.shedder.admit(priority) {
+            return false;
+        }
 
-    async fn handle_market_tick(&self, msg: async_nats::Message) {
-        let tick: MarketTick = match serde_json::from_slice(&msg.payload) {
-            Ok(t) => t,
-            Err(e) => {
-                tracing::error!("Invalid market tick: {}", e);
-                return;
-            }
+        let response = serde_json::json!({
+            "success": false,
+            "error": "Execution core overloaded, retry later",
+            "code": BUSY_CODE,
+        });
+        self.respond(msg, &response).await;
+        true
+    }
+
+    /// The caller's context. A request carrying an impersonation session gets the customer's
+    /// read-only context instead, once the session is found active; each one is audited.
+    async fn auth_context(&self, payload: AuthPayload, msg: &async_nats::Message) -> Result<AuthContext, EngineError> {
+        let session_id = payload.impersonation_id;
+        let auth: AuthContext = payload.into();
+        let Some(session_id) = session_id else {
+            return Ok(auth);
         };
 
+        let customer = self.impersonation.assume(&auth, session_id, &msg.subject).await?;
         tracing::info!(
-            "Market tick {} @ {}",
-            tick.symbol,
-            tick.last_price
+            session_id = %session_id,
+            agent = %auth.username,
+            customer = %customer.account_id,
+            subject = %msg.subject,
+            "Impersonated request"
         );
+        Ok(customer)
+    }
 
-        self.order_processor
-            .process_market_tick(&tick, &self.position_keeper)
+    /// Tell the customer of a session that started or ended, when the agent chose to
+    async fn notify_impersonation(&self, event: &str, session: &ImpersonationSession) {
+        if !session.notify_customer {
+            return;
+        }
+        let notice = serde_json::json!({
+            "event": event,
+            "accountId": session.customer_account_id,
+            "sessionId": session.id,
+            "agent": session.agent_username,
+            "reason": session.reason,
+            "expiresAt": session.expires_at,
+        });
+        let _ = self.bus
+            .publish(format!("support.impersonation.{}", event), serde_json::to_vec(&notice).unwrap())
             .await;
     }
 
-    // =====================================================
-    // ORDER CANCEL
-    // =====================================================
+    /// Answer a request, counting it as an error for the subject's stats when
+    /// the response says `"success": false`, and acknowledge it if it was pulled from JetStream
+    async fn respond<T: Serialize>(&self, msg: &async_nats::Message, response: &T) {
+        let response = serde_json::to_value(response).unwrap();
+        let success = response.get("success").and_then(serde_json::Value::as_bool).unwrap_or(true);
+        subjects::record_reply(&msg.subject, success);
 
-    async fn handle_order_cancel(&self, msg: async_nats::Message) {
-        #[derive(Deserialize)]
-        struct CancelReq {
-            order_id: String,
+        if let Some(reply) = &msg.reply {
+            let _ = self.bus
+                .publish(reply.to_string(), serde_json::to_vec(&response).unwrap())
+                .await;
         }
 
-        let parsed: Result<AuthenticatedMessage<CancelReq>, _> =
-            serde_json::from_slice(&msg.payload);
+        // Pulled requests leave the stream only once answered
+        if let Some(intake) = &self.intake {
+            intake.ack(msg).await;
+        }
+    }
 
-        let response = match parsed {
-            Ok(auth_msg) => {
-                let auth: AuthContext = auth_msg.auth.into();
-                match Uuid::parse_str(&auth_msg.data.order_id) {
-                    Ok(id) => match self.order_processor.cancel_order(&auth, id).await {
-                        Ok(Some(order)) => OrderResponse {
-                            success: true,
+    /// Compare the order and position caches with the database once an order transition
+    /// handled here is done, when CONSISTENCY_CHECKS is on. A disagreement is logged and
+    /// counted, and fails debug builds outright.
+    async fn check_consistency(&self, after: &str) {
+        if !self.consistency_checks {
+            return;
+        }
+        let (orders, positions) = tokio::join!(
+            self.order_processor.check_consistency(),
+            self.position_keeper.check_consistency(),
+        );
+        let mismatches = match (orders, positions) {
+            (Ok(mut orders), Ok(positions)) => {
+                orders.extend(positions);
+                orders
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::warn!(after, error = %e, "Consistency check failed to run");
+                return;
+            }
+        };
+
+        for mismatch in &mismatches {
+            tracing::error!(
+                after,
+                kind = mismatch.kind,
+                key = %mismatch.key,
+                field = mismatch.field,
+                cached = %mismatch.cached,
+                stored = %mismatch.stored,
+                "Cache disagrees with the database"
+            );
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.cache_consistency_mismatches_total
+                    .with_label_values(&[mismatch.kind, mismatch.field])
+                    .inc();
+            }
+        }
+        debug_assert!(mismatches.is_empty(), "{} cache mismatch(es) after {}: {:?}", mismatches.len(), after, mismatches);
+    }
+
+    // =====================================================
+    // ORDER SUBMIT
+    // =====================================================
+
+    #[tracing::instrument(skip_all, fields(subject = %msg.subject))]
+    async fn handle_order_submit(&self, msg: async_nats::Message) {
+        link_message_trace(&msg);
+
+        if self.query_only(&msg).await || self.stale(&msg).await || self.shed(&msg, Priority::Order).await {
+            return;
+        }
+        let started = self.clock.elapsed();
+        let mut timer = StageTimer::start(self.clock.clone());
+        let mut server_error = false;
+
+        let parsed: Result<AuthenticatedMessage<NewOrderRequest>, _> = self.codec.decode(&msg);
+        timer.lap(Stage::Deserialize);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let Some(mode) = ack_mode(&msg) else {
+                    let response = serde_json::json!({ "success": false, "error": "Unknown ack mode" });
+                    return self.respond(&msg, &response).await;
+                };
+                if mode == AckMode::Fast {
+                    return self.submit_fast_ack(&msg, auth, auth_msg.data, started, timer).await;
+                }
+
+                let result = self.order_processor
+                    .submit_order(&auth, auth_msg.data, &self.position_keeper, &mut timer)
+                    .await;
+                let (response, failed) = order_response(result);
+                server_error = failed;
+                response
+            }
+            Err(e) => OrderResponse {
+                success: false,
+                order_id: None,
+                error: Some(format!("Invalid payload: {}", e)),
+                code: None,
+                metadata: None,
+            },
+        };
+        let response = SubmitResponse { response, ack_mode: AckMode::Sync, journal_id: None, client_order_id: None };
+
+        let latency = self.clock.elapsed().saturating_sub(started);
+        observe_order_latency("submit", latency.as_secs_f64());
+        slo::record_latency(slo::ORDER_ACK, latency);
+        slo::record_outcome(slo::ORDER_ERRORS, !server_error);
+        count_ack(AckMode::Sync);
+
+        if self.timings_in_reply {
+            let timings_us = timer.timings();
+            self.respond(&msg, &TimedResponse { response, timings_us }).await;
+        } else {
+            self.respond(&msg, &response).await;
+        }
+        timer.lap(Stage::Publish);
+        timer.finish("submit");
+    }
+
+    /// Journal an order submit and answer it, then process it like a synchronous one. The
+    /// outcome is published on `orders.results.{account_id}`.
+    async fn submit_fast_ack(
+        &self,
+        msg: &async_nats::Message,
+        auth: AuthContext,
+        req: NewOrderRequest,
+        started: Duration,
+        mut timer: StageTimer,
+    ) {
+        // Nothing the caller may not submit is journaled
+        if !auth.has_permission(permissions::ORDERS_CREATE) {
+            let e = EngineError::Auth("orders:create required".into());
+            return self.respond(msg, &failure("order_submit", &e)).await;
+        }
+        timer.lap(Stage::Auth);
+
+        let appended_at = self.clock.now();
+        let (journal_id, appended) = match self.journal.append(&auth, &req, appended_at).await {
+            Ok(entry) => entry,
+            Err(e) => {
+                let e = EngineError::Storage(e.to_string());
+                return self.respond(msg, &failure("order_submit", &e)).await;
+            }
+        };
+        // A resubmission whose first order exists already is answered as a synchronous one
+        // would be, refused if its terms differ; one still in flight is a plain duplicate
+        let resubmitted = match appended {
+            true => None,
+            false => self.order_processor.resubmission(auth.account_id, &req).await.transpose(),
+        };
+        timer.lap(Stage::Db);
+
+        let response = SubmitResponse {
+            response: match resubmitted {
+                Some(result) => order_response(result).0,
+                None => OrderResponse {
+                    success: true,
+                    order_id: None,
+                    error: (!appended).then(|| "Duplicate order".to_string()),
+                    code: None,
+                    metadata: None,
+                },
+            },
+            ack_mode: AckMode::Fast,
+            journal_id: Some(journal_id),
+            client_order_id: Some(req.client_order_id.clone()),
+        };
+
+        let latency = self.clock.elapsed().saturating_sub(started);
+        observe_order_latency("submit_fast_ack", latency.as_secs_f64());
+        slo::record_latency(slo::ORDER_ACK, latency);
+        count_ack(AckMode::Fast);
+
+        if self.timings_in_reply {
+            let timings_us = timer.timings();
+            self.respond(msg, &TimedResponse { response, timings_us }).await;
+        } else {
+            self.respond(msg, &response).await;
+        }
+        timer.lap(Stage::Publish);
+        timer.finish("submit_fast_ack");
+
+        // A resubmitted client order id is processed, or replayed, under its first entry
+        if appended {
+            self.process_journaled(journal_id, auth, req, appended_at).await;
+        }
+    }
+
+    /// Process a journaled order submit, complete its entry and publish the outcome
+    async fn process_journaled(&self, journal_id: Uuid, auth: AuthContext, req: NewOrderRequest, appended_at: DateTime<Utc>) {
+        let client_order_id = req.client_order_id.clone();
+        let mut timer = StageTimer::start(self.clock.clone());
+        let result = self.order_processor
+            .submit_order(&auth, req, &self.position_keeper, &mut timer)
+            .await;
+        let (response, server_error) = order_response(result);
+        slo::record_outcome(slo::ORDER_ERRORS, !server_error);
+
+        let outcome = match (response.success, server_error) {
+            (true, _) => JournalOutcome::Committed,
+            (false, false) => JournalOutcome::Rejected,
+            (false, true) => JournalOutcome::Failed,
+        };
+        let order_id = response.order_id.as_deref().and_then(|id| Uuid::parse_str(id).ok());
+        let now = self.clock.now();
+        if let Err(e) = self.journal.complete(journal_id, outcome, order_id, response.error.as_deref(), now).await {
+            tracing::error!(journal_id = %journal_id, "Failed to complete journal entry: {}", e);
+        }
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.order_journal_lag
+                .with_label_values(&[outcome.as_str()])
+                .observe((now - appended_at).to_std().unwrap_or_default().as_secs_f64());
+        }
+
+        let result = SubmitResponse {
+            response,
+            ack_mode: AckMode::Fast,
+            journal_id: Some(journal_id),
+            client_order_id: Some(client_order_id),
+        };
+        let _ = self.bus
+            .publish(format!("orders.results.{}", auth.account_id), serde_json::to_vec(&result).unwrap())
+            .await;
+    }
+
+    /// Process the journal entries answered by an instance that stopped before processing them
+    async fn replay_journal(&self) -> anyhow::Result<()> {
+        let pending = self.journal.pending(self.order_processor.owned_symbols()).await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        tracing::warn!(entries = pending.len(), "Replaying fast-acked orders left unprocessed");
+        for entry in pending {
+            self.process_journaled(entry.id, entry.auth.0.into(), entry.request.0, entry.appended_at).await;
+        }
+        Ok(())
+    }
+
+    // =====================================================
+    // MARKET TICK
+    // =====================================================
+
+    #[tracing::instrument(skip_all, fields(subject = %msg.subject))]
+    async fn handle_market_tick(&self, msg: async_nats::Message) {
+        link_message_trace(&msg);
+        let tick: MarketTick = match serde_json::from_slice(&msg.payload) {
+            Ok(t) => t,
+            Err(e) => {
+                tracing::error!("Invalid market tick: {}", e);
+                return;
+            }
+        };
+
+        tracing::info!(
+            "Market tick {} @ {}",
+            tick.symbol,
+            tick.last_price
+        );
+
+        self.market_data.on_tick(&tick).await;
+        self.volume_bars.on_tick(&tick, self.clock.now()).await;
+
+        self.order_processor
+            .process_market_tick(&tick, &self.position_keeper)
+            .await;
+    }
+
+    // =====================================================
+    // STATE HANDOFF
+    // =====================================================
+
+    /// Publish this instance's hot state on `system.handoff` for the instance taking over.
+    /// Called while draining, once no more requests are handled.
+    pub async fn hand_off(&self) -> anyhow::Result<()> {
+        let state = HotState {
+            instance_id: self.instance_id.clone(),
+            taken_at: self.clock.now(),
+            last_prices: self.order_processor.price_cache().await,
+            open_orders: self.order_processor.open_order_index().await,
+            mmp_windows: self.mmp.windows().await,
+            shard: self.order_processor.owned_symbols(),
+        };
+        tracing::info!(
+            prices = state.last_prices.len(),
+            symbols = state.open_orders.len(),
+            mmp_windows = state.mmp_windows.len(),
+            "Handing off hot state"
+        );
+        self.bus.publish(HANDOFF_SUBJECT.to_string(), serde_json::to_vec(&state)?).await
+    }
+
+    /// Take over the hot state of an instance that is draining
+    async fn handle_handoff(&self, msg: async_nats::Message) {
+        let state: HotState = match serde_json::from_slice(&msg.payload) {
+            Ok(state) => state,
+            Err(e) => {
+                tracing::error!("Invalid state handoff: {}", e);
+                return;
+            }
+        };
+        if state.instance_id == self.instance_id {
+            return;
+        }
+        if state.is_stale(self.clock.now()) {
+            tracing::warn!(from = %state.instance_id, taken_at = %state.taken_at, "Ignoring stale state handoff");
+            return;
+        }
+        if state.shard != self.order_processor.owned_symbols() {
+            tracing::debug!(from = %state.instance_id, "Ignoring state handoff of another shard");
+            return;
+        }
+
+        let local_index = self.order_processor.open_order_index().await;
+        let outcome = HandoffOutcome {
+            diverging_symbols: state.diverging(&local_index),
+            prices_seeded: self.order_processor.seed_prices(state.last_prices).await,
+            windows_seeded: self.mmp.seed_windows(state.mmp_windows).await,
+        };
+        if !outcome.diverging_symbols.is_empty() {
+            tracing::warn!(
+                from = %state.instance_id,
+                symbols = ?outcome.diverging_symbols,
+                "Open orders loaded from the database differ from the draining instance's index"
+            );
+        }
+        tracing::info!(
+            from = %state.instance_id,
+            prices_seeded = outcome.prices_seeded,
+            mmp_windows_seeded = outcome.windows_seeded,
+            "Hot state taken over"
+        );
+    }
+
+    // =====================================================
+    // SHARD REBALANCING
+    // =====================================================
+
+    /// Start moving a symbol between sharded instances. The reply is the event the move
+    /// settled with, or a timeout once it misses its deadline; the wait runs apart from the
+    /// message loop, which handles the move's own events.
+    async fn handle_rebalance(&self, msg: async_nats::Message) {
+        let parsed: Result<AuthenticatedMessage<RebalanceRequest>, _> = serde_json::from_slice(&msg.payload);
+        let auth_msg = match parsed {
+            Ok(auth_msg) => auth_msg,
+            Err(e) => return self.respond(&msg, &serde_json::json!({ "success": false, "error": e.to_string() })).await,
+        };
+        let auth = match self.auth_context(auth_msg.auth, &msg).await {
+            Ok(auth) => auth,
+            Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+        };
+        let req = auth_msg.data;
+
+        let refused = if !auth.has_permission(permissions::ADMIN_FULL) {
+            Some(EngineError::Auth("admin:full required".into()))
+        } else if self.shard.is_none() {
+            Some(EngineError::Validation("Symbol sharding is not enabled".into()))
+        } else if req.from == req.to {
+            Some(EngineError::Validation("from and to are the same instance".into()))
+        } else if !shard::is_subject_token(&req.symbol) {
+            Some(EngineError::Validation(format!("Invalid symbol: {}", req.symbol)))
+        } else {
+            None
+        };
+        if let Some(e) = refused {
+            return self.respond(&msg, &failure("rebalance", &e)).await;
+        }
+
+        let now = self.clock.now();
+        let deadline = now + chrono::Duration::from_std(self.rebalance_timeout).unwrap_or_default();
+        let requested = RebalanceEvent::requested(req, now, deadline);
+        let settled = self.rebalances.wait(requested.rebalance_id);
+        tracing::info!(
+            rebalance_id = %requested.rebalance_id,
+            symbol = %requested.symbol,
+            from = %requested.from,
+            to = %requested.to,
+            "Shard rebalance requested"
+        );
+        publish_rebalance(&self.bus, &requested).await;
+
+        let (bus, rebalances, clock) = (self.bus.clone(), self.rebalances.clone(), self.clock.clone());
+        let wait = self.rebalance_timeout + REBALANCE_GRACE;
+        tokio::spawn(async move {
+            let outcome = match tokio::time::timeout(wait, settled).await {
+                Ok(Ok(event)) => event,
+                _ => {
+                    rebalances.abandon(requested.rebalance_id);
+                    let timed_out = requested.failed(clock.now(), "Rebalance missed its deadline");
+                    publish_rebalance(&bus, &timed_out).await;
+                    timed_out
+                }
+            };
+
+            let success = outcome.phase == RebalancePhase::Acquired;
+            subjects::record_reply(&msg.subject, success);
+            if let Some(reply) = &msg.reply {
+                let response = serde_json::json!({ "success": success, "rebalance": outcome });
+                let _ = bus.publish(reply.to_string(), serde_json::to_vec(&response).unwrap()).await;
+            }
+        });
+    }
+
+    /// Act on a step of a move this instance is the source or target of
+    async fn handle_rebalance_event(&self, msg: async_nats::Message) {
+        let event: RebalanceEvent = match serde_json::from_slice(&msg.payload) {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::error!("Invalid rebalance event: {}", e);
+                return;
+            }
+        };
+        if event.is_settled() {
+            self.rebalances.settle(&event);
+        }
+
+        let next = match event.phase {
+            RebalancePhase::Requested if event.from == self.instance_id => self.release_for_rebalance(&event).await,
+            RebalancePhase::Released if event.to == self.instance_id => self.acquire_for_rebalance(&event).await,
+            RebalancePhase::Acquired if event.from == self.instance_id => {
+                self.rebalances.take_released(event.rebalance_id);
+                None
+            }
+            RebalancePhase::Failed if event.from == self.instance_id => self.restore_after_rebalance(&event).await,
+            _ => None,
+        };
+        if let Some(next) = next {
+            publish_rebalance(&self.bus, &next).await;
+        }
+    }
+
+    /// Give the symbol up: stop its subscriptions and drop it from memory, halting it until
+    /// the target takes it on
+    async fn release_for_rebalance(&self, event: &RebalanceEvent) -> Option<RebalanceEvent> {
+        let now = self.clock.now();
+        let Some((last_price, open_orders)) = self.order_processor.release_symbol(&event.symbol).await else {
+            return Some(event.failed(now, format!("{} does not trade {}", self.instance_id, event.symbol)));
+        };
+        self.routes.unroute(&event.symbol);
+        self.book_feed.forget(&event.symbol).await;
+        self.rebalances.released(event.rebalance_id, now);
+
+        tracing::info!(rebalance_id = %event.rebalance_id, symbol = %event.symbol, open_orders, "Symbol released for rebalance");
+        Some(RebalanceEvent {
+            last_price,
+            open_orders: Some(open_orders),
+            ..event.next(RebalancePhase::Released, now)
+        })
+    }
+
+    /// Take the symbol on: record the move, load its open orders and positions and subscribe
+    async fn acquire_for_rebalance(&self, event: &RebalanceEvent) -> Option<RebalanceEvent> {
+        let now = self.clock.now();
+        if now > event.deadline {
+            return Some(event.failed(now, "Released after the rebalance deadline"));
+        }
+
+        match self.take_on(&event.symbol, event.last_price, event.rebalance_id).await {
+            Ok(open_orders) => {
+                tracing::info!(rebalance_id = %event.rebalance_id, symbol = %event.symbol, open_orders, "Symbol acquired by rebalance");
+                Some(RebalanceEvent {
+                    open_orders: Some(open_orders),
+                    halted_ms: Some((now - event.at).num_milliseconds()),
+                    ..event.next(RebalancePhase::Acquired, now)
+                })
+            }
+            Err(e) => {
+                tracing::error!(rebalance_id = %event.rebalance_id, symbol = %event.symbol, "Failed to acquire symbol: {}", e);
+                Some(event.failed(now, format!("{} could not take the symbol on", self.instance_id)))
+            }
+        }
+    }
+
+    /// Take back a symbol released here for a move that failed
+    async fn restore_after_rebalance(&self, event: &RebalanceEvent) -> Option<RebalanceEvent> {
+        let released_at = self.rebalances.take_released(event.rebalance_id)?;
+        let now = self.clock.now();
+        match self.take_on(&event.symbol, None, event.rebalance_id).await {
+            Ok(open_orders) => {
+                tracing::warn!(rebalance_id = %event.rebalance_id, symbol = %event.symbol, "Rebalance failed, symbol restored");
+                Some(RebalanceEvent {
+                    open_orders: Some(open_orders),
+                    halted_ms: Some((now - released_at).num_milliseconds()),
+                    ..event.next(RebalancePhase::Restored, now)
+                })
+            }
+            Err(e) => {
+                tracing::error!(rebalance_id = %event.rebalance_id, symbol = %event.symbol, "Failed to restore symbol, it stays halted: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Record that `symbol` trades here, then load it and subscribe to it
+    async fn take_on(&self, symbol: &str, last_price: Option<Decimal>, rebalance_id: Uuid) -> anyhow::Result<usize> {
+        rebalance::assign(&self.pool, symbol, &self.instance_id, rebalance_id, self.clock.now()).await?;
+        let open_orders = self.order_processor.acquire_symbol(symbol, last_price).await?;
+        self.position_keeper.reload_symbol(symbol).await?;
+        self.route_symbol(symbol).await?;
+        Ok(open_orders)
+    }
+
+    // =====================================================
+    // ORDER CANCEL
+    // =====================================================
+
+    #[tracing::instrument(skip_all, fields(subject = %msg.subject))]
+    async fn handle_order_cancel(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Critical).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        struct CancelReq {
+            order_id: String,
+        }
+
+        link_message_trace(&msg);
+        let started = self.clock.elapsed();
+        let mut server_error = false;
+
+        let parsed: Result<AuthenticatedMessage<CancelReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match Uuid::parse_str(&auth_msg.data.order_id) {
+                    Ok(id) => match self.order_processor.cancel_order(&auth, id).await {
+                        Ok(Some(order)) => OrderResponse {
+                            success: true,
                             order_id: Some(order.id.to_string()),
                             error: None,
+                            code: None,
+                            metadata: order.metadata.map(EncryptedJson::into_inner),
                         },
                         Ok(None) => OrderResponse {
                             success: false,
                             order_id: None,
                             error: Some("Order not found".into()),
+                            code: None,
+                            metadata: None,
                         },
-                        Err(e) => OrderResponse {
-                            success: false,
-                            order_id: None,
-                            error: Some(e.to_string()),
-                        },
+                        Err(e) => {
+                            server_error = log_unexpected("order_cancel", &e);
+                            OrderResponse {
+                                success: false,
+                                order_id: None,
+                                error: Some(e.to_string()),
+                                code: Some(e.code()),
+                                metadata: None,
+                            }
+                        }
                     },
                     Err(_) => OrderResponse {
                         success: false,
                         order_id: None,
                         error: Some("Invalid order_id".into()),
+                        code: None,
+                        metadata: None,
                     },
                 }
             }
@@ -234,39 +1775,1963 @@ impl NatsSubscriber {
                 success: false,
                 order_id: None,
                 error: Some(e.to_string()),
+                code: None,
+                metadata: None,
             },
         };
 
-        if let Some(reply) = msg.reply {
-            let _ = self.client
-                .publish(reply, serde_json::to_vec(&response).unwrap().into())
-                .await;
+        let latency = self.clock.elapsed().saturating_sub(started);
+        observe_order_latency("cancel", latency.as_secs_f64());
+        slo::record_latency(slo::ORDER_ACK, latency);
+        slo::record_outcome(slo::ORDER_ERRORS, !server_error);
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Mass cancel of the account's open orders, optionally one symbol or side
+    #[tracing::instrument(skip_all, fields(subject = %msg.subject))]
+    async fn handle_order_cancel_all(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Critical).await {
+            return;
+        }
+        link_message_trace(&msg);
+
+        let parsed: Result<AuthenticatedMessage<CancelAllRequest>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.order_processor.cancel_all(&auth, &auth_msg.data).await {
+                    Ok(summary) => {
+                        if self.shard.is_some() {
+                            publish_evictions(&self.bus, &summary.evictions()).await;
+                        }
+                        serde_json::json!({ "success": true, "summary": summary })
+                    }
+                    Err(e) => failure("order_cancel_all", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid payload: {}", e) }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Drop orders a cancel on another instance closed
+    async fn handle_evictions(&self, msg: async_nats::Message) {
+        match serde_json::from_slice::<Evictions>(&msg.payload) {
+            Ok(evictions) => self.order_processor.evict_orders(&evictions).await,
+            Err(e) => tracing::error!("Invalid order evictions: {}", e),
         }
     }
 
     // =====================================================
-    // POSITION QUERY
+    // CANCEL-ON-DISCONNECT SESSIONS
     // =====================================================
 
-    async fn handle_position_query(&self, msg: async_nats::Message) {
-        let parsed: Result<AuthenticatedMessage<serde_json::Value>, _> =
+    async fn handle_session_register(&self, msg: async_nats::Message) {
+        let parsed: Result<AuthenticatedMessage<RegisterSession>, _> =
             serde_json::from_slice(&msg.payload);
 
         let response = match parsed {
             Ok(auth_msg) => {
-                let auth: AuthContext = auth_msg.auth.into();
-                match self.position_keeper.get_account_positions(&auth, None).await {
-                    Ok(p) => serde_json::json!({ "success": true, "positions": p }),
-                    Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.disconnects.register(&auth, &auth_msg.data).await {
+                    Ok(session) => serde_json::json!({ "success": true, "session": session }),
+                    Err(e) => failure("session_register", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid payload: {}", e) }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Never shed: a dropped heartbeat would cancel the account's orders
+    async fn handle_session_heartbeat(&self, msg: async_nats::Message) {
+        let parsed: Result<AuthenticatedMessage<SessionRef>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.disconnects.heartbeat(&auth, auth_msg.data.session_id).await {
+                    Ok(session) => serde_json::json!({ "success": true, "session": session }),
+                    Err(e) => failure("session_heartbeat", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid payload: {}", e) }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_session_close(&self, msg: async_nats::Message) {
+        let parsed: Result<AuthenticatedMessage<SessionRef>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.disconnects.close(&auth, auth_msg.data.session_id).await {
+                    Ok(session) => serde_json::json!({ "success": true, "session": session }),
+                    Err(e) => failure("session_close", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid payload: {}", e) }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Partial cancel: the reply carries the restated order as an execution report, which is
+    /// also published to `executions.{account_id}`
+    #[tracing::instrument(skip_all, fields(subject = %msg.subject))]
+    async fn handle_order_reduce(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Critical).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        struct ReduceReq {
+            order_id: String,
+            #[serde(alias = "newQuantity")]
+            new_quantity: Decimal,
+        }
+
+        link_message_trace(&msg);
+        let started = self.clock.elapsed();
+        let mut server_error = false;
+
+        let parsed: Result<AuthenticatedMessage<ReduceReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let req = auth_msg.data;
+                match Uuid::parse_str(&req.order_id) {
+                    Ok(id) => match self.order_processor.reduce_order(&auth, id, req.new_quantity).await {
+                        Ok(Some(order)) => serde_json::json!({
+                            "success": true,
+                            "executionReport": {
+                                "execType": "reduced",
+                                "orderId": order.id,
+                                "clientOrderId": order.client_order_id,
+                                "status": order.status,
+                                "orderQuantity": order.quantity,
+                                "filledQuantity": order.filled_quantity,
+                                "remainingQuantity": order.quantity - order.filled_quantity,
+                                "transactTime": order.updated_at,
+                                "metadata": order.metadata,
+                            }
+                        }),
+                        Ok(None) => serde_json::json!({ "success": false, "error": "Order not found" }),
+                        Err(e) => {
+                            server_error = log_unexpected("order_reduce", &e);
+                            serde_json::json!({ "success": false, "error": e.to_string(), "code": e.code() })
+                        }
+                    },
+                    Err(_) => serde_json::json!({ "success": false, "error": "Invalid order_id" }),
                 }
             }
             Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
         };
 
-        if let Some(reply) = msg.reply {
-            let _ = self.client
-                .publish(reply, serde_json::to_vec(&response).unwrap().into())
-                .await;
+        let latency = self.clock.elapsed().saturating_sub(started);
+        observe_order_latency("reduce", latency.as_secs_f64());
+        slo::record_latency(slo::ORDER_ACK, latency);
+        slo::record_outcome(slo::ORDER_ERRORS, !server_error);
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Cancel-replace: the reply carries the replacement as an execution report
+    #[tracing::instrument(skip_all, fields(subject = %msg.subject))]
+    async fn handle_order_amend(&self, msg: async_nats::Message) {
+        link_message_trace(&msg);
+
+        if self.query_only(&msg).await || self.stale(&msg).await || self.shed(&msg, Priority::Order).await {
+            return;
+        }
+        let started = self.clock.elapsed();
+        let mut server_error = false;
+
+        let parsed: Result<AuthenticatedMessage<AmendOrderRequest>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let replaced = auth_msg.data.order_id;
+                match self.order_processor.amend_order(&auth, auth_msg.data).await {
+                    Ok(Some(OrderResult::Accepted(order))) | Ok(Some(OrderResult::Duplicate(order))) => {
+                        serde_json::json!({
+                            "success": true,
+                            "executionReport": {
+                                "execType": "replaced",
+                                "orderId": order.id,
+                                "clientOrderId": order.client_order_id,
+                                "origOrderId": replaced,
+                                "status": order.status,
+                                "price": order.price,
+                                "orderQuantity": order.quantity,
+                                "filledQuantity": order.filled_quantity,
+                                "transactTime": order.updated_at,
+                                "metadata": order.metadata,
+                            }
+                        })
+                    }
+                    Ok(Some(OrderResult::Rejected { reason, code })) => {
+                        tracing::info!(code = %code, reason = %reason, "Amend rejected");
+                        count_rejection(code);
+                        serde_json::json!({ "success": false, "error": reason, "code": code.code() })
+                    }
+                    Ok(None) => serde_json::json!({ "success": false, "error": "Order not found" }),
+                    Err(e) => {
+                        server_error = log_unexpected("order_amend", &e);
+                        serde_json::json!({ "success": false, "error": e.to_string(), "code": e.code() })
+                    }
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid payload: {}", e) }),
+        };
+
+        let latency = self.clock.elapsed().saturating_sub(started);
+        observe_order_latency("amend", latency.as_secs_f64());
+        slo::record_latency(slo::ORDER_ACK, latency);
+        slo::record_outcome(slo::ORDER_ERRORS, !server_error);
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // MULTI-LEG STRATEGIES
+    // =====================================================
+
+    async fn handle_strategy_submit(&self, msg: async_nats::Message) {
+        if self.query_only(&msg).await || self.stale(&msg).await || self.shed(&msg, Priority::Order).await {
+            return;
+        }
+
+        let parsed: Result<AuthenticatedMessage<NewStrategyRequest>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.order_processor.submit_strategy(&auth, auth_msg.data).await {
+                    Ok(StrategyResult::Accepted(strategy)) => {
+                        serde_json::json!({ "success": true, "strategy": strategy })
+                    }
+                    Ok(StrategyResult::Duplicate(strategy)) => {
+                        serde_json::json!({ "success": true, "strategy": strategy, "error": "Duplicate strategy" })
+                    }
+                    Ok(StrategyResult::Rejected { reason, code }) => {
+                        tracing::info!(code = %code, reason = %reason, "Strategy rejected");
+                        count_rejection(code);
+                        serde_json::json!({ "success": false, "error": reason, "code": code.code() })
+                    }
+                    Err(e) => failure("strategy_submit", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid payload: {}", e) }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_strategy_cancel(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct CancelReq {
+            strategy_id: String,
+        }
+
+        let parsed: Result<AuthenticatedMessage<CancelReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match Uuid::parse_str(&auth_msg.data.strategy_id) {
+                    Ok(id) => match self.order_processor.cancel_strategy(&auth, id).await {
+                        Ok(Some(strategy)) => {
+                            if self.shard.is_some() {
+                                let evictions = Evictions {
+                                    order_ids: strategy.legs.iter().map(|leg| leg.id).collect(),
+                                    strategy_ids: vec![strategy.id],
+                                    ..Default::default()
+                                };
+                                publish_evictions(&self.bus, &evictions).await;
+                            }
+                            serde_json::json!({ "success": true, "strategy": strategy })
+                        }
+                        Ok(None) => serde_json::json!({ "success": false, "error": "Strategy not found" }),
+                        Err(e) => failure("strategy_cancel", &e),
+                    },
+                    Err(_) => serde_json::json!({ "success": false, "error": "Invalid strategy_id" }),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // TWAP
+    // =====================================================
+
+    async fn handle_twap_submit(&self, msg: async_nats::Message) {
+        if self.query_only(&msg).await || self.stale(&msg).await || self.shed(&msg, Priority::Order).await {
+            return;
+        }
+
+        let parsed: Result<AuthenticatedMessage<TwapRequest>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.twap.submit(&auth, auth_msg.data).await {
+                    Ok(TwapResult::Accepted(twap)) => serde_json::json!({ "success": true, "twap": twap }),
+                    Ok(TwapResult::Duplicate(twap)) => {
+                        serde_json::json!({ "success": true, "twap": twap, "error": "Duplicate TWAP" })
+                    }
+                    Err(e) => failure("twap_submit", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid payload: {}", e) }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_twap_cancel(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct CancelReq {
+            #[serde(alias = "twapId")]
+            twap_id: Uuid,
+        }
+
+        let parsed: Result<AuthenticatedMessage<CancelReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.twap.cancel(&auth, auth_msg.data.twap_id).await {
+                    Ok(Some(twap)) => serde_json::json!({ "success": true, "twap": twap }),
+                    Ok(None) => serde_json::json!({ "success": false, "error": "TWAP not found" }),
+                    Err(e) => failure("twap_cancel", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_twap_status(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        struct StatusReq {
+            #[serde(alias = "twapId")]
+            twap_id: Uuid,
+        }
+
+        let parsed: Result<AuthenticatedMessage<StatusReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.twap.progress(&auth, auth_msg.data.twap_id).await {
+                    Ok(Some(progress)) => serde_json::json!({ "success": true, "twap": progress }),
+                    Ok(None) => serde_json::json!({ "success": false, "error": "TWAP not found" }),
+                    Err(e) => failure("twap_status", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // ORDER HISTORY
+    // =====================================================
+
+    /// The order's compacted snapshot by default; `view: "full"` returns every event,
+    /// archived and live
+    async fn handle_order_history(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct HistoryReq {
+            #[serde(alias = "order_id")]
+            order_id: Uuid,
+            #[serde(default)]
+            view: HistoryView,
+        }
+
+        let parsed: Result<AuthenticatedMessage<HistoryReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let order_id = auth_msg.data.order_id;
+                let result = match auth_msg.data.view {
+                    HistoryView::Snapshot => self.order_history.snapshot(&auth, order_id).await
+                        .map(|s| s.map(|s| serde_json::json!({ "success": true, "snapshot": s }))),
+                    HistoryView::Full => self.order_history.history(&auth, order_id).await
+                        .map(|e| e.map(|e| serde_json::json!({ "success": true, "events": e }))),
+                };
+                match result {
+                    Ok(Some(reply)) => reply,
+                    Ok(None) => serde_json::json!({ "success": false, "error": "Order not found" }),
+                    Err(e) => failure("order_history", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Every step of one order's life, oldest first, for support investigations
+    async fn handle_order_timeline(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct TimelineReq {
+            #[serde(alias = "order_id")]
+            order_id: Uuid,
+        }
+
+        let parsed: Result<AuthenticatedMessage<TimelineReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.order_history.timeline(&auth, auth_msg.data.order_id).await {
+                    Ok(Some(timeline)) => serde_json::json!({ "success": true, "timeline": timeline }),
+                    Ok(None) => serde_json::json!({ "success": false, "error": "Order not found" }),
+                    Err(e) => failure("order_timeline", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// A page of an account's orders for a blotter, filtered and sorted by creation time
+    async fn handle_order_list(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        let parsed: Result<AuthenticatedMessage<OrderQuery>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.order_history.list(&auth, &auth_msg.data).await {
+                    Ok(page) => serde_json::json!({
+                        "success": true,
+                        "orders": page.orders,
+                        "nextCursor": page.next_cursor,
+                    }),
+                    Err(e) => failure("order_list", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // POSITION QUERY
+    // =====================================================
+
+    async fn handle_position_query(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        let parsed: Result<AuthenticatedMessage<serde_json::Value>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.position_keeper.get_account_positions(&auth, None).await {
+                    Ok(p) => serde_json::json!({ "success": true, "positions": p }),
+                    Err(e) => failure("position_query", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Margin of the caller's derivative positions, by netting set
+    async fn handle_margin_query(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        let parsed: Result<AuthenticatedMessage<serde_json::Value>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.margin.account_margin(&auth).await {
+                    Ok(margin) => serde_json::json!({ "success": true, "margin": margin }),
+                    Err(e) => failure("margin_query", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Platform-wide open interest of `symbol`, or of every symbol without one
+    async fn handle_open_interest_query(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        struct OpenInterestReq {
+            #[serde(default)]
+            symbol: Option<String>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<OpenInterestReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let symbol = auth_msg.data.symbol.as_deref();
+                match self.position_keeper.get_open_interest(&auth, symbol).await {
+                    Ok(oi) => serde_json::json!({ "success": true, "openInterest": oi }),
+                    Err(e) => failure("open_interest_query", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Time-weighted exposure per position over a session, today's unless `session` is given
+    async fn handle_exposure_query(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        struct ExposureReq {
+            #[serde(default, alias = "accountId")]
+            account_id: Option<Uuid>,
+            #[serde(default)]
+            session: Option<NaiveDate>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<ExposureReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let ExposureReq { account_id, session } = auth_msg.data;
+                match self.exposure.report(&auth, account_id, session).await {
+                    Ok(exposure) => serde_json::json!({ "success": true, "exposure": exposure }),
+                    Err(e) => failure("exposure_query", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // REBATES
+    // =====================================================
+
+    /// Rebates credited to an account over a month, the current one unless `period` is given
+    async fn handle_rebate_statement(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        struct StatementReq {
+            #[serde(default, alias = "accountId")]
+            account_id: Option<Uuid>,
+            /// Any day of the month
+            #[serde(default)]
+            period: Option<NaiveDate>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<StatementReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let StatementReq { account_id, period } = auth_msg.data;
+                match self.rebates.statement(&auth, account_id, period).await {
+                    Ok(statement) => serde_json::json!({ "success": true, "statement": statement }),
+                    Err(e) => failure("rebate_statement", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_referral_set(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct ReferralReq {
+            #[serde(alias = "accountId")]
+            account_id: Uuid,
+            #[serde(alias = "referrerId")]
+            referrer_id: Uuid,
+        }
+
+        let parsed: Result<AuthenticatedMessage<ReferralReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let ReferralReq { account_id, referrer_id } = auth_msg.data;
+                match self.rebates.set_referrer(&auth, account_id, referrer_id).await {
+                    Ok(()) => serde_json::json!({ "success": true }),
+                    Err(e) => failure("referral_set", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // BALANCE QUERY
+    // =====================================================
+
+    async fn handle_balance_query(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        let parsed: Result<AuthenticatedMessage<serde_json::Value>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                if !auth.has_permission(permissions::POSITIONS_READ) {
+                    serde_json::json!({ "success": false, "error": "positions:read required" })
+                } else {
+                    match self.ledger.balances(auth.account_id).await {
+                        Ok(b) => serde_json::json!({ "success": true, "balances": b }),
+                        Err(e) => {
+                            tracing::error!(handler = "balance_query", error = %e, "Unexpected handler error");
+                            serde_json::json!({ "success": false, "error": e.to_string() })
+                        }
+                    }
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // LEADERBOARD
+    // =====================================================
+
+    async fn handle_leaderboard_query(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        struct LeaderboardQuery {
+            #[serde(default)]
+            period: Option<LeaderboardPeriod>,
+            #[serde(default)]
+            limit: Option<usize>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<LeaderboardQuery>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let period = auth_msg.data.period.unwrap_or(LeaderboardPeriod::Day);
+                match self.leaderboard.query(&auth, period, auth_msg.data.limit).await {
+                    Ok(board) => serde_json::json!({ "success": true, "leaderboard": board }),
+                    Err(e) => failure("leaderboard_query", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_leaderboard_optin(&self, msg: async_nats::Message) {
+        let parsed: Result<AuthenticatedMessage<OptInRequest>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.leaderboard.opt_in(&auth, auth_msg.data).await {
+                    Ok(p) => serde_json::json!({
+                        "success": true,
+                        "display_name": p.display_name,
+                        "show_pnl": p.show_pnl,
+                    }),
+                    Err(e) => failure("leaderboard_optin", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_leaderboard_optout(&self, msg: async_nats::Message) {
+        let parsed: Result<AuthenticatedMessage<serde_json::Value>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.leaderboard.opt_out(&auth).await {
+                    Ok(removed) => serde_json::json!({ "success": true, "removed": removed }),
+                    Err(e) => failure("leaderboard_optout", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // SANDBOX ACCOUNTS
+    // =====================================================
+
+    async fn handle_sandbox_provision(&self, msg: async_nats::Message) {
+        let parsed: Result<AuthenticatedMessage<ProvisionRequest>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.sandbox.provision(&auth, auth_msg.data).await {
+                    Ok(sandbox) => serde_json::json!({ "success": true, "sandbox": sandbox }),
+                    Err(e) => failure("sandbox_provision", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_sandbox_reset(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct ResetReq {
+            #[serde(default)]
+            account_id: Option<String>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<ResetReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let target = match auth_msg.data.account_id {
+                    Some(id) => Uuid::parse_str(&id).ok(),
+                    None => Some(auth.account_id),
+                };
+                match target {
+                    Some(account_id) => match self.sandbox.reset(&auth, account_id).await {
+                        Ok(summary) => serde_json::json!({ "success": true, "reset": summary }),
+                        Err(e) => failure("sandbox_reset", &e),
+                    },
+                    None => serde_json::json!({ "success": false, "error": "Invalid account_id" }),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // CORPORATE ACTIONS
+    // =====================================================
+
+    async fn handle_corporate_action_announce(&self, msg: async_nats::Message) {
+        let parsed: Result<AuthenticatedMessage<AnnounceRequest>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.corporate_actions.announce(&auth, auth_msg.data).await {
+                    Ok(action) => {
+                        // Actions effective immediately are processed before replying
+                        if action.ex_date <= self.clock.now() {
+                            if let Err(e) = self.corporate_actions.process_due().await {
+                                tracing::error!("Corporate action processing failed: {}", e);
+                            }
+                        }
+                        serde_json::json!({ "success": true, "action": action })
+                    }
+                    Err(e) => failure("corporate_action_announce", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_corporate_action_cancel(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct CancelReq {
+            action_id: String,
+        }
+
+        let parsed: Result<AuthenticatedMessage<CancelReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match Uuid::parse_str(&auth_msg.data.action_id) {
+                    Ok(id) => match self.corporate_actions.cancel(&auth, id).await {
+                        Ok(cancelled) => serde_json::json!({ "success": cancelled, "cancelled": cancelled }),
+                        Err(e) => failure("corporate_action_cancel", &e),
+                    },
+                    Err(_) => serde_json::json!({ "success": false, "error": "Invalid action_id" }),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_corporate_action_query(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        struct QueryReq {
+            #[serde(default)]
+            symbol: Option<String>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<QueryReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.corporate_actions.list(&auth, auth_msg.data.symbol.as_deref()).await {
+                    Ok(actions) => serde_json::json!({ "success": true, "actions": actions }),
+                    Err(e) => failure("corporate_action_query", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // SUPPORT IMPERSONATION
+    // =====================================================
+
+    /// Without a field returns the caller's consent in force; `grant` consents to support
+    /// access for a window and `revoke` withdraws it, ending open sessions
+    async fn handle_support_consent(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ConsentGrant {
+            #[serde(alias = "window_secs", default)]
+            window_secs: Option<i64>,
+        }
+
+        #[derive(Deserialize)]
+        struct ConsentReq {
+            #[serde(default)]
+            grant: Option<ConsentGrant>,
+            #[serde(default)]
+            revoke: bool,
+        }
+
+        let parsed: Result<AuthenticatedMessage<ConsentReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                if auth.impersonation.is_some() {
+                    let e = EngineError::Auth("Consent cannot be changed while impersonating".into());
+                    return self.respond(&msg, &failure("support_consent", &e)).await;
+                }
+
+                match (auth_msg.data.grant, auth_msg.data.revoke) {
+                    (Some(grant), _) => match self.impersonation.grant_consent(&auth, grant.window_secs).await {
+                        Ok(consent) => serde_json::json!({ "success": true, "consent": consent }),
+                        Err(e) => failure("support_consent", &e),
+                    },
+                    (None, true) => match self.impersonation.revoke_consent(&auth).await {
+                        Ok(ended) => {
+                            for session in &ended {
+                                self.notify_impersonation("ended", session).await;
+                            }
+                            serde_json::json!({ "success": true, "endedSessions": ended.len() })
+                        }
+                        Err(e) => failure("support_consent", &e),
+                    },
+                    (None, false) => match self.impersonation.consent(&auth).await {
+                        Ok(consent) => serde_json::json!({ "success": true, "consent": consent }),
+                        Err(e) => failure("support_consent", &e),
+                    },
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// `start` opens a session on a consenting customer for an agent holding
+    /// `support:impersonate`; `end` closes one early
+    async fn handle_impersonation(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct ImpersonationReq {
+            #[serde(default)]
+            start: Option<StartImpersonation>,
+            #[serde(default)]
+            end: Option<Uuid>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<ImpersonationReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match (auth_msg.data.start, auth_msg.data.end) {
+                    (Some(start), _) => match self.impersonation.start(&auth, start).await {
+                        Ok(session) => {
+                            self.notify_impersonation("started", &session).await;
+                            serde_json::json!({ "success": true, "session": session })
+                        }
+                        Err(e) => failure("impersonation", &e),
+                    },
+                    (None, Some(session_id)) => match self.impersonation.end(&auth, session_id).await {
+                        Ok(Some(session)) => {
+                            self.notify_impersonation("ended", &session).await;
+                            serde_json::json!({ "success": true, "session": session })
+                        }
+                        Ok(None) => serde_json::json!({ "success": false, "error": "Session not found or already over" }),
+                        Err(e) => failure("impersonation", &e),
+                    },
+                    (None, None) => serde_json::json!({ "success": false, "error": "start or end required" }),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // SLO STATUS
+    // =====================================================
+
+    /// Burn rates and firing alerts per SLO. Neither shed nor authenticated, so
+    /// alerting can poll it while the engine is overloaded.
+    async fn handle_slo_status(&self, msg: async_nats::Message) {
+        let response = serde_json::json!({ "success": true, "slos": slo::slo_status() });
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // ORDER BOOK
+    // =====================================================
+
+    /// Depth of the symbol named by the subject, as of the last delta while deltas are published.
+    /// Levels aggregate orders of every account and name none of them, so the snapshot, like the
+    /// delta feed, needs no authentication.
+    async fn handle_book_snapshot(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        let symbol = msg.subject.as_str().strip_prefix(SNAPSHOT_SUBJECT_PREFIX).unwrap_or_default();
+        let book = if self.book_delta_interval.is_zero() {
+            BookSnapshot {
+                symbol: symbol.to_string(),
+                sequence: 0,
+                taken_at: self.clock.now(),
+                depth: self.order_processor.depth(symbol, self.book_feed.levels()).await,
+            }
+        } else {
+            self.book_feed.published(symbol, self.clock.now()).await
+        };
+
+        self.respond(&msg, &serde_json::json!({ "success": true, "book": book })).await;
+    }
+
+    // =====================================================
+    // ACCOUNT DATA EXPORT & ERASURE
+    // =====================================================
+
+    async fn handle_account_export(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        struct ExportReq {
+            #[serde(default)]
+            account_id: Option<String>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<ExportReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let target = match auth_msg.data.account_id {
+                    Some(id) => Uuid::parse_str(&id).ok(),
+                    None => Some(auth.account_id),
+                };
+                match target {
+                    Some(account_id) => match self.privacy.export(&auth, account_id).await {
+                        Ok(export) => serde_json::json!({ "success": true, "export": export }),
+                        Err(e) => failure("account_export", &e),
+                    },
+                    None => serde_json::json!({ "success": false, "error": "Invalid account_id" }),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// Queue a bulk export; the file is generated in the background and announced on
+    /// `exports.ready` or `exports.failed`
+    async fn handle_export_request(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        let parsed: Result<AuthenticatedMessage<ExportRequest>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.exports.request(&auth, auth_msg.data).await {
+                    Ok(job) => serde_json::json!({ "success": true, "job": job }),
+                    Err(e) => failure("export_request", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_export_status(&self, msg: async_nats::Message) {
+        if self.shed(&msg, Priority::Query).await {
+            return;
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct StatusReq {
+            #[serde(alias = "job_id")]
+            job_id: Uuid,
+        }
+
+        let parsed: Result<AuthenticatedMessage<StatusReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.exports.job(&auth, auth_msg.data.job_id).await {
+                    Ok(Some(job)) => serde_json::json!({ "success": true, "job": job }),
+                    Ok(None) => serde_json::json!({ "success": false, "error": "Export not found" }),
+                    Err(e) => failure("export_status", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    async fn handle_account_erase(&self, msg: async_nats::Message) {
+        let parsed: Result<AuthenticatedMessage<ErasureRequest>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match self.privacy.request_erasure(&auth, auth_msg.data).await {
+                    Ok(erasure) => serde_json::json!({ "success": true, "erasure": erasure }),
+                    Err(e) => failure("account_erase", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // ACCOUNT ORDER DEFAULTS
+    // =====================================================
+
+    /// Without `defaults` returns the caller's order defaults; with it replaces them
+    async fn handle_account_settings(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct SettingsReq {
+            #[serde(default)]
+            defaults: Option<OrderDefaults>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<SettingsReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let result = match auth_msg.data.defaults {
+                    Some(defaults) => self.settings.update(&auth, defaults).await,
+                    None => self.settings.get(&auth).await,
+                };
+                match result {
+                    Ok(defaults) => serde_json::json!({ "success": true, "defaults": defaults }),
+                    Err(e) => failure("account_settings", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // TRADING PAUSES
+    // =====================================================
+
+    /// Without `schedule` returns the caller's trading pauses; with it replaces them
+    async fn handle_account_pauses(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct PausesReq {
+            #[serde(default)]
+            schedule: Option<PauseRequest>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<PausesReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                let result = match auth_msg.data.schedule {
+                    Some(schedule) => self.pauses.update(&auth, schedule).await,
+                    None => self.pauses.get(&auth).await,
+                };
+                match result {
+                    Ok(schedule) => serde_json::json!({ "success": true, "schedule": schedule }),
+                    Err(e) => failure("account_pauses", &e),
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // STRATEGY LIMITS
+    // =====================================================
+
+    /// Without `limit` returns the caller's strategy limits; with it sets those of one tag
+    async fn handle_strategy_limits(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct StrategyLimitsReq {
+            #[serde(default)]
+            limit: Option<StrategyLimit>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<StrategyLimitsReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match auth_msg.data.limit {
+                    Some(limit) => match self.strategy_limits.update(&auth, limit).await {
+                        Ok(limit) => serde_json::json!({ "success": true, "limit": limit }),
+                        Err(e) => failure("strategy_limits", &e),
+                    },
+                    None => match self.strategy_limits.list(&auth).await {
+                        Ok(limits) => serde_json::json!({ "success": true, "limits": limits }),
+                        Err(e) => failure("strategy_limits", &e),
+                    },
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // MARKET-MAKER PROTECTION
+    // =====================================================
+
+    /// Without a field returns the caller's protections; `settings` protects a symbol and
+    /// `reset` lifts the trigger on one
+    async fn handle_mmp(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct MmpReq {
+            #[serde(default)]
+            settings: Option<MmpSettings>,
+            #[serde(default)]
+            reset: Option<String>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<MmpReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match (auth_msg.data.settings, auth_msg.data.reset) {
+                    (Some(settings), _) => match self.mmp.update(&auth, settings).await {
+                        Ok(settings) => serde_json::json!({ "success": true, "settings": settings }),
+                        Err(e) => failure("mmp", &e),
+                    },
+                    (None, Some(symbol)) => match self.mmp.reset(&auth, &symbol).await {
+                        Ok(settings) => serde_json::json!({ "success": true, "settings": settings }),
+                        Err(e) => failure("mmp", &e),
+                    },
+                    (None, None) => match self.mmp.get(&auth).await {
+                        Ok(settings) => serde_json::json!({ "success": true, "settings": settings }),
+                        Err(e) => failure("mmp", &e),
+                    },
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    // =====================================================
+    // FILL DELIVERY
+    // =====================================================
+
+    /// Without a field lists the fill consumers and their backlog; `register` adds one and
+    /// `release` returns a quarantined one to delivery
+    async fn handle_fill_consumers(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct FillConsumersReq {
+            #[serde(default)]
+            register: Option<RegisterConsumer>,
+            #[serde(default)]
+            release: Option<String>,
+        }
+
+        let parsed: Result<AuthenticatedMessage<FillConsumersReq>, _> =
+            serde_json::from_slice(&msg.payload);
+
+        let response = match parsed {
+            Ok(auth_msg) => {
+                let auth = match self.auth_context(auth_msg.auth, &msg).await {
+                    Ok(auth) => auth,
+                    Err(e) => return self.respond(&msg, &failure("impersonation", &e)).await,
+                };
+                match (auth_msg.data.register, auth_msg.data.release) {
+                    (Some(req), _) => match self.fill_delivery.register(&auth, req).await {
+                        Ok(consumer) => serde_json::json!({ "success": true, "consumer": consumer }),
+                        Err(e) => failure("fill_consumers", &e),
+                    },
+                    (None, Some(name)) => match self.fill_delivery.release(&auth, &name).await {
+                        Ok(consumer) => serde_json::json!({ "success": true, "consumer": consumer }),
+                        Err(e) => failure("fill_consumers", &e),
+                    },
+                    (None, None) => match self.fill_delivery.list(&auth).await {
+                        Ok(consumers) => serde_json::json!({ "success": true, "consumers": consumers }),
+                        Err(e) => failure("fill_consumers", &e),
+                    },
+                }
+            }
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+
+    /// A consumer acknowledging its copy of a fill. Sent by platform services rather than
+    /// clients, so it carries no auth; the reply, if asked for, says whether it was pending.
+    async fn handle_fill_ack(&self, msg: async_nats::Message) {
+        #[derive(Deserialize)]
+        struct FillAck {
+            consumer: String,
+            #[serde(alias = "tradeId")]
+            trade_id: Uuid,
+        }
+
+        let response = match serde_json::from_slice::<FillAck>(&msg.payload) {
+            Ok(ack) => match self.fill_delivery.ack(&ack.consumer, ack.trade_id).await {
+                Ok(acked) => serde_json::json!({ "success": true, "acked": acked }),
+                Err(e) => {
+                    tracing::error!(consumer = %ack.consumer, "Failed to record fill ack: {}", e);
+                    serde_json::json!({ "success": false, "error": "Failed to record ack" })
+                }
+            },
+            Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+        };
+
+        self.respond(&msg, &response).await;
+    }
+}
+
+// =====================================================
+// CORPORATE ACTION PROCESSOR
+// =====================================================
+
+/// Periodically apply splits and dividends whose ex-date or pay date has been reached
+async fn process_corporate_actions(processor: Arc<CorporateActionProcessor>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match processor.process_due().await {
+            Ok(processed) if processed > 0 => {
+                tracing::info!(processed, "Corporate actions processed");
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Corporate action processing failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// ACCOUNT EXPORTS
+// =====================================================
+
+/// Generate queued exports every period, publishing each finished job to `exports.ready`
+/// or `exports.failed`
+async fn generate_exports(bus: SharedBus, exports: Arc<Exports>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match exports.run().await {
+            Ok(finished) => {
+                for job in &finished {
+                    let subject = if job.status == "ready" { "exports.ready" } else { "exports.failed" };
+                    let _ = bus
+                        .publish(subject.to_string(), serde_json::to_vec(job).unwrap())
+                        .await;
+                }
+            }
+            Err(e) => tracing::error!("Export run failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// INSTRUMENT RULES
+// =====================================================
+
+/// Reload instrument trading rules every period so listing changes reach order entry
+async fn refresh_instruments(instruments: Arc<Instruments>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    // The first tick fires at once; `initialize` already loaded them
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = instruments.load().await {
+            tracing::error!("Instrument reload failed, keeping the previous rules: {}", e);
+        }
+    }
+}
+
+// =====================================================
+// LEDGER INTEGRITY
+// =====================================================
+
+/// Verify ledger invariants every period; a failed run is published to `ledger.integrity.alert`
+async fn check_ledger_integrity(bus: SharedBus, checker: Arc<IntegrityChecker>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match checker.run().await {
+            Ok(run) if run.passed() => tracing::info!(run_id = %run.run_id, "Ledger integrity check passed"),
+            Ok(run) => {
+                for result in run.results.iter().filter(|r| r.violations > 0) {
+                    tracing::error!(
+                        run_id = %run.run_id,
+                        check = result.check,
+                        violations = result.violations,
+                        "Ledger integrity violation"
+                    );
+                }
+                let _ = bus
+                    .publish("ledger.integrity.alert".to_string(), serde_json::to_vec(&run).unwrap())
+                    .await;
+            }
+            Err(e) => tracing::error!("Ledger integrity check failed to run: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// ORDER EXPIRY SWEEPER
+// =====================================================
+
+/// Expire good-till-date orders past their expiry, publishing each to `orders.expired`
+async fn sweep_expired_orders(bus: SharedBus, order_processor: Arc<OrderProcessor>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match order_processor.expire_orders().await {
+            Ok(expired) => {
+                if !expired.is_empty() {
+                    tracing::info!(expired = expired.len(), "Order expiry sweep completed");
+                }
+                for order in &expired {
+                    let event = OrderExpired::from(order);
+                    let _ = bus
+                        .publish("orders.expired".to_string(), serde_json::to_vec(&event).unwrap())
+                        .await;
+                }
+            }
+            Err(e) => tracing::error!("Order expiry sweep failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// DAY ORDER CLOSE
+// =====================================================
+
+/// Cancel day orders at every session close, publishing each to `orders.day_cancelled`.
+/// Runs once at startup too, for day orders left from a close the engine was down for.
+async fn close_day_orders(bus: SharedBus, order_processor: Arc<OrderProcessor>, session: TradingSession, clock: SharedClock) {
+    loop {
+        let close = session.last_close(clock.now());
+
+        match order_processor.cancel_day_orders(close).await {
+            Ok(cancelled) => {
+                if !cancelled.is_empty() {
+                    tracing::info!(cancelled = cancelled.len(), %close, "Day orders cancelled at session close");
+                }
+                for order in &cancelled {
+                    let event = DayOrderCancelled::new(order, close);
+                    let _ = bus
+                        .publish("orders.day_cancelled".to_string(), serde_json::to_vec(&event).unwrap())
+                        .await;
+                }
+            }
+            Err(e) => {
+                // Retried shortly rather than at the next close
+                tracing::error!("Day order close failed: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        }
+
+        let until_close = (session.next_close(clock.now()) - clock.now()).to_std().unwrap_or_default();
+        tokio::time::sleep(until_close).await;
+    }
+}
+
+// =====================================================
+// FILL DELIVERY
+// =====================================================
+
+/// Publish every due execution report to its consumer's subject, and each newly
+/// quarantined consumer to `fills.quarantined`
+async fn deliver_fills(bus: SharedBus, delivery: Arc<FillDelivery>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match delivery.poll().await {
+            Ok(round) => {
+                for consumer in &round.quarantined {
+                    let _ = bus
+                        .publish(
+                            "fills.quarantined".to_string(),
+                            serde_json::to_vec(&serde_json::json!({ "consumer": consumer })).unwrap(),
+                        )
+                        .await;
+                }
+                // An unpublished report is retried after its backoff like an unacknowledged one
+                for report in &round.reports {
+                    if let Err(e) = bus.publish(report.subject.clone(), serde_json::to_vec(report).unwrap()).await {
+                        tracing::warn!(consumer = %report.consumer, "Failed to publish fill report: {}", e);
+                    }
+                }
+            }
+            Err(e) => tracing::error!("Fill delivery round failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// EXECUTION REPORTS
+// =====================================================
+
+/// Publish each committed fill's and reduction's execution report to `executions.{account_id}`
+async fn publish_execution_reports(bus: SharedBus, mut reports: mpsc::Receiver<ExecutionReport>) {
+    while let Some(report) = reports.recv().await {
+        let _ = bus
+            .publish(report.subject(), serde_json::to_vec(&report).unwrap())
+            .await;
+    }
+}
+
+// =====================================================
+// ORDER BOOK DELTAS
+// =====================================================
+
+/// Publish the levels each symbol's book changed by to `orderbook.delta.{symbol}` every period.
+/// A failed publish is not retried: consumers see the gap in the sequence and take a snapshot.
+async fn publish_book_deltas(
+    bus: SharedBus,
+    order_processor: Arc<OrderProcessor>,
+    feed: Arc<BookFeed>,
+    clock: SharedClock,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        let depths = order_processor.depths(feed.levels()).await;
+        for delta in feed.deltas(depths, clock.now()).await {
+            let _ = bus
+                .publish(delta.subject(), serde_json::to_vec(&delta).unwrap())
+                .await;
+        }
+    }
+}
+
+// =====================================================
+// MARKET-MAKER PROTECTION PUBLISHER
+// =====================================================
+
+/// Publish each market-maker protection trip to `mmp.triggered`
+async fn publish_mmp_trips(bus: SharedBus, mut trips: mpsc::Receiver<MmpTrip>) {
+    while let Some(trip) = trips.recv().await {
+        let _ = bus
+            .publish("mmp.triggered".to_string(), serde_json::to_vec(&trip).unwrap())
+            .await;
+    }
+}
+
+// =====================================================
+// CANCEL-ON-DISCONNECT
+// =====================================================
+
+/// Cancel the orders of sessions whose heartbeats stopped, publishing each to `sessions.disconnected`
+/// and, `sharded`, the cancelled orders to the instances whose books hold them
+async fn sweep_disconnects(bus: SharedBus, disconnects: Arc<CancelOnDisconnect>, interval: Duration, sharded: bool) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match disconnects.sweep().await {
+            Ok(disconnected) => {
+                for event in &disconnected {
+                    tracing::warn!(
+                        session_id = %event.session.id,
+                        account_id = %event.session.account_id,
+                        cancelled = event.summary.cancelled,
+                        "Session heartbeats stopped, open orders cancelled"
+                    );
+                    if sharded {
+                        publish_evictions(&bus, &event.summary.evictions()).await;
+                    }
+                    let _ = bus
+                        .publish("sessions.disconnected".to_string(), serde_json::to_vec(event).unwrap())
+                        .await;
+                }
+            }
+            Err(e) => tracing::error!("Cancel-on-disconnect sweep failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// ERASURE SWEEPER
+// =====================================================
+
+/// Periodically scrub personal data of accounts whose retention window has ended
+async fn sweep_erasures(privacy: Arc<PrivacyManager>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match privacy.run_scheduled().await {
+            Ok(erased) if erased > 0 => tracing::info!(erased, "Account erasure sweep completed"),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Account erasure sweep failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// TWAP SCHEDULER
+// =====================================================
+
+/// Send the TWAP slices that have come due. Slices wait, rather than being skipped,
+/// while the engine serves queries only.
+async fn schedule_twaps(twap: Arc<TwapScheduler>, dependencies: Arc<Dependencies>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if !dependencies.accepts_orders() {
+            continue;
+        }
+        if let Err(e) = twap.run_due().await {
+            tracing::error!("TWAP scheduler pass failed: {}", e);
+        }
+    }
+}
+
+// =====================================================
+// VOLUME BARS
+// =====================================================
+
+/// Write the volume seen on market ticks to the stored bars
+async fn flush_volume_bars(volume_bars: Arc<VolumeBars>, clock: SharedClock, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = volume_bars.flush(clock.now()).await {
+            tracing::error!("Volume bar flush failed: {}", e);
+        }
+    }
+}
+
+// =====================================================
+// REBATE SETTLEMENT
+// =====================================================
+
+/// Credit the volume-tier and referral rebates of each month once it has ended
+async fn settle_rebates(rebates: Arc<Rebates>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = rebates.settle_month().await {
+            tracing::error!("Rebate settlement failed: {}", e);
+        }
+    }
+}
+
+// =====================================================
+// DUST SWEEP
+// =====================================================
+
+/// Flag or close dust positions and sweep dust balances
+async fn sweep_dust(dust: Arc<DustSweeper>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match dust.sweep().await {
+            Ok(summary) if summary.positions_flagged + summary.positions_closed + summary.balances_swept > 0 => {
+                tracing::info!(
+                    flagged = summary.positions_flagged,
+                    closed = summary.positions_closed,
+                    balances = summary.balances_swept,
+                    "Dust swept"
+                );
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Dust sweep failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// EXPOSURE SESSIONS
+// =====================================================
+
+/// Carry open positions' exposure into the current session
+async fn close_exposure_sessions(exposure: Arc<ExposureTracker>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match exposure.close_sessions().await {
+            Ok(carried) if carried > 0 => tracing::info!(carried, "Position exposure carried into new session"),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Exposure session close failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// ORDER EVENT COMPACTION
+// =====================================================
+
+/// Periodically fold the event streams of settled orders into snapshots
+async fn compact_order_events(history: Arc<OrderHistory>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match history.compact().await {
+            Ok(compacted) if compacted > 0 => tracing::info!(compacted, "Order event compaction completed"),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Order event compaction failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// SANDBOX SWEEPER
+// =====================================================
+
+/// Periodically apply scheduled sandbox resets and retire expired sandboxes
+async fn sweep_sandboxes(sandbox: Arc<SandboxManager>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match sandbox.run_scheduled().await {
+            Ok((reset, expired)) if reset > 0 || expired > 0 => {
+                tracing::info!(reset, expired, "Sandbox sweep completed");
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Sandbox sweep failed: {}", e),
+        }
+    }
+}
+
+// =====================================================
+// LEADERBOARD PUBLISHER
+// =====================================================
+
+/// Snapshot participant positions and publish every period to `leaderboard.{period}`
+async fn publish_leaderboards(bus: SharedBus, leaderboard: Arc<Leaderboard>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = leaderboard.snapshot_positions().await {
+            tracing::error!("Failed to snapshot positions for leaderboard: {}", e);
+            continue;
+        }
+
+        for period in LeaderboardPeriod::ALL {
+            match leaderboard.rankings(period, None).await {
+                Ok(board) => {
+                    let subject = format!("leaderboard.{}", period.as_str());
+                    let _ = bus
+                        .publish(subject, serde_json::to_vec(&board).unwrap())
+                        .await;
+                }
+                Err(e) => tracing::error!("Failed to compute {} leaderboard: {}", period.as_str(), e),
+            }
         }
     }
 }
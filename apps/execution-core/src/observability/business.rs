@@ -0,0 +1,84 @@
+//! Business Metrics
+//! Traded notional, fees and open interest kept on the fill path; active accounts aggregated periodically
+
+use super::metrics::get_metrics;
+use crate::storage::ReadPool;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Accounts count as active for this long after their last order
+const ACTIVE_WINDOW_SECS: i64 = 3_600;
+
+static FEE_RATE_BPS: OnceLock<f64> = OnceLock::new();
+
+/// Set the fee schedule once at startup; until then fees count as zero
+pub fn init_business_metrics(fee_rate_bps: f64) {
+    let _ = FEE_RATE_BPS.set(fee_rate_bps);
+}
+
+/// Fee earned on `notional` at `fee_rate_bps` basis points
+pub fn fee(notional: f64, fee_rate_bps: f64) -> f64 {
+    notional * fee_rate_bps / 10_000.0
+}
+
+/// Count a committed fill
+pub fn record_fill(symbol: &str, quantity: Decimal, price: Decimal) {
+    let Some(ref metrics) = *get_metrics() else {
+        return;
+    };
+
+    let notional = (quantity * price).abs().to_f64().unwrap_or_default();
+    metrics.traded_notional_total.with_label_values(&[symbol]).inc_by(notional);
+
+    let fee = fee(notional, FEE_RATE_BPS.get().copied().unwrap_or_default());
+    if fee > 0.0 {
+        metrics.fee_revenue_total.with_label_values(&[symbol]).inc_by(fee);
+    }
+}
+
+/// Publish a symbol's open interest after a fill moved it
+pub fn record_open_interest(symbol: &str, quantity: Decimal) {
+    let Some(ref metrics) = *get_metrics() else {
+        return;
+    };
+
+    // Symbols whose positions all closed drop out instead of sticking at zero
+    if quantity.is_zero() {
+        let _ = metrics.open_interest.remove_label_values(&[symbol]);
+    } else {
+        metrics.open_interest.with_label_values(&[symbol]).set(quantity.to_f64().unwrap_or_default());
+    }
+}
+
+/// Refresh the active-account gauge every `interval`
+pub async fn run_business_metrics(reads: ReadPool, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = aggregate(&reads).await {
+            tracing::warn!(error = %e, "Business metrics aggregation failed");
+        }
+    }
+}
+
+async fn aggregate(reads: &ReadPool) -> Result<(), sqlx::Error> {
+    let active: i64 = reads.run("business.active_accounts", |pool| async move {
+        sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT account_id) FROM orders \
+             WHERE created_at > NOW() - make_interval(secs => $1)"
+        )
+            .bind(ACTIVE_WINDOW_SECS as f64)
+            .fetch_one(&pool)
+            .await
+    }).await?;
+
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.active_accounts.set(active as f64);
+    }
+    Ok(())
+}
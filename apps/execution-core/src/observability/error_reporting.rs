@@ -0,0 +1,451 @@
+//! Error Reporting
+//! Panics, error-level logs and circuit breaker opens sent to Sentry or an OTLP logs endpoint
+
+use super::metrics::get_metrics;
+use super::redaction::{redactor, scrub_jwts};
+use super::tracing_setup::current_trace_id;
+
+use chrono::{DateTime, Utc};
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request};
+use hyper_rustls::HttpsConnector;
+use serde_json::{json, Map, Value};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+use uuid::Uuid;
+
+/// Events waiting for delivery; beyond this they are dropped rather than block the caller
+const QUEUE_CAPACITY: usize = 256;
+/// A report that takes longer than this is abandoned
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Fatal => "fatal",
+        }
+    }
+
+    /// OTLP `SeverityNumber`: WARN, ERROR and FATAL
+    pub fn otlp_number(&self) -> u8 {
+        match self {
+            Self::Warning => 13,
+            Self::Error => 17,
+            Self::Fatal => 21,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorEvent {
+    /// What raised it: `panic`, `error_log` or `circuit_breaker_open`
+    pub kind: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Log fields, span fields, panic location and the like
+    pub context: Map<String, Value>,
+    pub trace_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ErrorEvent {
+    /// An event stamped now, inside the current trace if there is one
+    pub fn new(kind: &'static str, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            severity,
+            message: message.into(),
+            context: Map::new(),
+            trace_id: current_trace_id(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.context.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+// =====================================================
+// SINKS
+// =====================================================
+
+/// Parts of a Sentry DSN (`https://<key>@<host>/<project>`) needed to post an event
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentryDsn {
+    pub public_key: String,
+    pub store_url: String,
+}
+
+impl SentryDsn {
+    pub fn parse(dsn: &str) -> Option<Self> {
+        let (scheme, rest) = dsn.split_once("://")?;
+        let (credentials, rest) = rest.split_once('@')?;
+        let public_key = credentials.split(':').next()?;
+        let (host, path) = rest.split_once('/')?;
+        let path = path.trim_end_matches('/');
+        let (prefix, project) = match path.rsplit_once('/') {
+            Some((prefix, project)) => (format!("/{}", prefix), project),
+            None => (String::new(), path),
+        };
+
+        if public_key.is_empty() || host.is_empty() || project.is_empty() {
+            return None;
+        }
+        Some(Self {
+            public_key: public_key.to_string(),
+            store_url: format!("{}://{}{}/api/{}/store/", scheme, host, prefix, project),
+        })
+    }
+
+    fn auth_header(&self) -> String {
+        format!(
+            "Sentry sentry_version=7, sentry_key={}, sentry_client=execution-core/{}",
+            self.public_key,
+            env!("CARGO_PKG_VERSION")
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Sink {
+    Sentry(SentryDsn),
+    /// Full OTLP/HTTP logs URL, e.g. `http://otel-collector:4318/v1/logs`
+    Otlp(String),
+}
+
+impl Sink {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sentry(_) => "sentry",
+            Self::Otlp(_) => "otlp",
+        }
+    }
+}
+
+/// Identifies the process in every report
+#[derive(Debug, Clone)]
+pub struct ReportSource {
+    pub service: String,
+    pub environment: String,
+}
+
+/// Sentry store-endpoint event
+pub fn sentry_payload(event: &ErrorEvent, source: &ReportSource) -> Value {
+    let mut tags = json!({ "kind": event.kind, "service": source.service });
+    if let Some(trace_id) = &event.trace_id {
+        tags["trace_id"] = json!(trace_id);
+    }
+
+    json!({
+        "event_id": Uuid::new_v4().simple().to_string(),
+        "timestamp": event.timestamp.to_rfc3339(),
+        "level": event.severity.as_str(),
+        "platform": "other",
+        "logger": source.service,
+        "release": format!("{}@{}", source.service, env!("CARGO_PKG_VERSION")),
+        "environment": source.environment,
+        "message": { "formatted": event.message },
+        "tags": tags,
+        "extra": event.context,
+    })
+}
+
+/// OTLP/HTTP JSON `ExportLogsServiceRequest` holding one log record
+pub fn otlp_payload(event: &ErrorEvent, source: &ReportSource) -> Value {
+    let mut attributes = vec![attribute("event.kind", &json!(event.kind))];
+    attributes.extend(event.context.iter().map(|(key, value)| attribute(key, value)));
+
+    let mut record = json!({
+        "timeUnixNano": event.timestamp.timestamp_nanos_opt().unwrap_or_default().to_string(),
+        "severityNumber": event.severity.otlp_number(),
+        "severityText": event.severity.as_str().to_uppercase(),
+        "body": { "stringValue": event.message },
+        "attributes": attributes,
+    });
+    if let Some(trace_id) = &event.trace_id {
+        record["traceId"] = json!(trace_id);
+    }
+
+    json!({
+        "resourceLogs": [{
+            "resource": {
+                "attributes": [
+                    attribute("service.name", &json!(source.service)),
+                    attribute("service.version", &json!(env!("CARGO_PKG_VERSION"))),
+                    attribute("deployment.environment", &json!(source.environment)),
+                ]
+            },
+            "scopeLogs": [{
+                "scope": { "name": "execution-core.error_reporting" },
+                "logRecords": [record],
+            }]
+        }]
+    })
+}
+
+/// OTLP key-value; non-string values are sent as their JSON text
+fn attribute(key: &str, value: &Value) -> Value {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    json!({ "key": key, "value": { "stringValue": text } })
+}
+
+// =====================================================
+// REPORTER
+// =====================================================
+
+struct Reporter {
+    queue: mpsc::Sender<ErrorEvent>,
+}
+
+static REPORTER: OnceLock<Reporter> = OnceLock::new();
+
+/// Start delivering reports to every configured sink. Returns the sink names, empty
+/// when neither a DSN nor an endpoint is set and reporting stays off.
+pub fn init_error_reporting(
+    source: ReportSource,
+    sentry_dsn: Option<&str>,
+    otlp_endpoint: Option<&str>,
+) -> anyhow::Result<Vec<&'static str>> {
+    let mut sinks = Vec::new();
+    if let Some(dsn) = sentry_dsn {
+        let dsn = SentryDsn::parse(dsn).ok_or_else(|| anyhow::anyhow!("Invalid SENTRY_DSN"))?;
+        sinks.push(Sink::Sentry(dsn));
+    }
+    if let Some(endpoint) = otlp_endpoint {
+        sinks.push(Sink::Otlp(endpoint.to_string()));
+    }
+    if sinks.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let names = sinks.iter().map(Sink::name).collect();
+    let (queue, events) = mpsc::channel(QUEUE_CAPACITY);
+    if REPORTER.set(Reporter { queue }).is_ok() {
+        tokio::spawn(deliver(events, sinks, source));
+    }
+    Ok(names)
+}
+
+pub fn reporting_enabled() -> bool {
+    REPORTER.get().is_some()
+}
+
+/// Queue an event for delivery; never blocks, so it is safe from a panic hook.
+/// Context goes through the same redaction as log lines.
+pub fn report(mut event: ErrorEvent) {
+    let Some(reporter) = REPORTER.get() else {
+        return;
+    };
+
+    let redactor = redactor();
+    let mut context = Value::Object(std::mem::take(&mut event.context));
+    redactor.redact_value(&mut context);
+    if let Value::Object(context) = context {
+        event.context = context;
+    }
+    if let Some(message) = scrub_jwts(&event.message) {
+        event.message = message;
+    }
+
+    if reporter.queue.try_send(event).is_err() {
+        record_report("all", "dropped");
+    }
+}
+
+async fn deliver(mut events: mpsc::Receiver<ErrorEvent>, sinks: Vec<Sink>, source: ReportSource) {
+    let client: Client<HttpsConnector<HttpConnector>> = Client::builder().build(
+        hyper_rustls::HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .build(),
+    );
+
+    while let Some(event) = events.recv().await {
+        for sink in &sinks {
+            let request = match sink {
+                Sink::Sentry(dsn) => Request::builder()
+                    .uri(&dsn.store_url)
+                    .header("X-Sentry-Auth", dsn.auth_header())
+                    .body(Body::from(sentry_payload(&event, &source).to_string())),
+                Sink::Otlp(endpoint) => Request::builder()
+                    .uri(endpoint)
+                    .body(Body::from(otlp_payload(&event, &source).to_string())),
+            };
+            let request = request.map(|mut request| {
+                *request.method_mut() = Method::POST;
+                request.headers_mut().insert("content-type", "application/json".parse().unwrap());
+                request
+            });
+
+            let outcome = match request {
+                Ok(request) => match tokio::time::timeout(SEND_TIMEOUT, client.request(request)).await {
+                    Ok(Ok(response)) if response.status().is_success() => "sent",
+                    Ok(Ok(response)) => {
+                        tracing::warn!(sink = sink.name(), status = %response.status(), "Error report rejected");
+                        "failed"
+                    }
+                    Ok(Err(e)) => {
+                        tracing::warn!(sink = sink.name(), error = %e, "Failed to send error report");
+                        "failed"
+                    }
+                    Err(_) => {
+                        tracing::warn!(sink = sink.name(), "Error report timed out");
+                        "failed"
+                    }
+                },
+                Err(e) => {
+                    tracing::warn!(sink = sink.name(), error = %e, "Invalid error report request");
+                    "failed"
+                }
+            };
+            record_report(sink.name(), outcome);
+        }
+    }
+}
+
+fn record_report(sink: &str, outcome: &str) {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.error_reports_total.with_label_values(&[sink, outcome]).inc();
+    }
+}
+
+// =====================================================
+// PANIC HOOK
+// =====================================================
+
+/// Report panics with their location and backtrace, then run the previous hook
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+
+        let mut event = ErrorEvent::new("panic", Severity::Fatal, message)
+            .with("thread", std::thread::current().name().unwrap_or("unnamed"))
+            .with("backtrace", std::backtrace::Backtrace::force_capture().to_string());
+        if let Some(location) = info.location() {
+            event = event.with("location", format!("{}:{}", location.file(), location.line()));
+        }
+        report(event);
+
+        previous(info);
+    }));
+}
+
+// =====================================================
+// TRACING LAYER
+// =====================================================
+
+/// Reports every ERROR-level event with its fields and those of its enclosing spans
+pub struct ErrorReportingLayer;
+
+/// Fields recorded on a span, kept so error events can carry them
+struct SpanFields(Map<String, Value>);
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+}
+
+impl<S> Layer<S> for ErrorReportingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, id: &tracing::span::Id, ctx: Context<'_, S>) {
+        let mut fields = Map::new();
+        attrs.record(&mut JsonVisitor(&mut fields));
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(fields));
+        }
+    }
+
+    fn on_record(&self, id: &tracing::span::Id, values: &tracing::span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(SpanFields(fields)) = span.extensions_mut().get_mut::<SpanFields>() {
+                values.record(&mut JsonVisitor(fields));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() != Level::ERROR || !reporting_enabled() {
+            return;
+        }
+
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+        let message = match fields.remove("message") {
+            Some(Value::String(message)) => message,
+            _ => metadata.name().to_string(),
+        };
+
+        let spans: Vec<Value> = ctx
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+            .map(|span| {
+                let extensions = span.extensions();
+                let fields = extensions.get::<SpanFields>().map(|f| f.0.clone()).unwrap_or_default();
+                json!({ "name": span.name(), "fields": fields })
+            })
+            .collect();
+
+        let mut report_event = ErrorEvent::new("error_log", Severity::Error, message)
+            .with("target", metadata.target())
+            .with("spans", spans);
+        if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+            report_event = report_event.with("location", format!("{}:{}", file, line));
+        }
+        report_event.context.extend(fields);
+        report(report_event);
+    }
+}
@@ -0,0 +1,105 @@
+//! Histogram Exemplars
+//! Trace IDs kept per latency bucket and exposed in OpenMetrics format, linking slow buckets to traces
+
+use super::metrics::{get_metrics, ORDER_LATENCY_BUCKETS};
+
+use once_cell::sync::Lazy;
+use opentelemetry::trace::TraceContextExt;
+use prometheus::core::Collector;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exemplar {
+    pub trace_id: String,
+    pub value: f64,
+}
+
+/// Latest exemplar per (operation, bucket index); the last index is `+Inf`
+static EXEMPLARS: Lazy<Mutex<HashMap<(String, usize), Exemplar>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Index of the first bucket whose upper bound holds `value`
+pub fn bucket_index(buckets: &[f64], value: f64) -> usize {
+    buckets.iter().position(|bound| value <= *bound).unwrap_or(buckets.len())
+}
+
+/// Observe an order operation's latency, keeping the current trace as the bucket's exemplar
+pub fn observe_order_latency(operation: &str, seconds: f64) {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.order_processing_duration.with_label_values(&[operation]).observe(seconds);
+    }
+
+    let context = tracing::Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    if !span_context.is_valid() || !span_context.is_sampled() {
+        return;
+    }
+
+    let exemplar = Exemplar {
+        trace_id: span_context.trace_id().to_string(),
+        value: seconds,
+    };
+    let key = (operation.to_string(), bucket_index(ORDER_LATENCY_BUCKETS, seconds));
+    EXEMPLARS.lock().unwrap_or_else(|e| e.into_inner()).insert(key, exemplar);
+}
+
+/// Convert text-format output to OpenMetrics: counter families drop their `_total`
+/// suffix, latency buckets carry their exemplar, and the exposition ends with `# EOF`
+pub fn to_openmetrics(text: &str) -> String {
+    let exemplars = EXEMPLARS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let histogram = get_metrics()
+        .as_ref()
+        .and_then(|m| m.order_processing_duration.desc().first().map(|d| d.fq_name.clone()))
+        .unwrap_or_default();
+    let bucket_prefix = format!("{}_bucket{{", histogram);
+
+    let counters: Vec<&str> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("# TYPE ")?.strip_suffix(" counter"))
+        .collect();
+    let mut out = String::with_capacity(text.len() + 64);
+
+    for line in text.lines() {
+        match openmetrics_line(line, &counters) {
+            Some(rewritten) => out.push_str(&rewritten),
+            None => out.push_str(line),
+        }
+
+        if line.starts_with(&bucket_prefix) {
+            let exemplar = label(line, "operation").zip(label(line, "le")).and_then(|(operation, le)| {
+                let index = bucket_index(ORDER_LATENCY_BUCKETS, le.parse().ok()?);
+                exemplars.get(&(operation.to_string(), index))
+            });
+            if let Some(exemplar) = exemplar {
+                out.push_str(&format!(" # {{trace_id=\"{}\"}} {}", exemplar.trace_id, exemplar.value));
+            }
+        }
+        out.push('\n');
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+/// `# TYPE`/`# HELP` lines of counters, renamed to the family name without `_total`
+fn openmetrics_line(line: &str, counters: &[&str]) -> Option<String> {
+    let (directive, rest) = line
+        .strip_prefix("# TYPE ")
+        .map(|rest| ("# TYPE ", rest))
+        .or_else(|| line.strip_prefix("# HELP ").map(|rest| ("# HELP ", rest)))?;
+
+    let (name, tail) = rest.split_once(' ')?;
+    let family = name.strip_suffix("_total")?;
+    counters.contains(&name).then(|| format!("{}{} {}", directive, family, tail))
+}
+
+/// Value of label `name` in a sample line
+fn label<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let labels = &line[line.find('{')? + 1..line.rfind('}')?];
+    labels.split("\",").find_map(|pair| {
+        let value = pair.strip_prefix(name)?.strip_prefix("=\"")?;
+        Some(value.strip_suffix('"').unwrap_or(value))
+    })
+}
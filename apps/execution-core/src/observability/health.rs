@@ -1,28 +1,42 @@
 //! Health Check & Metrics HTTP Server
-//! Provides /health, /health/live, /health/ready, /metrics endpoints
+//! Provides /health, /health/live, /health/ready, /info, /metrics, /debug/subjects endpoints
 
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::IntoResponse,
-    routing::get,
+    body::Bytes,
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use sqlx::PgPool;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tracing::{info, instrument};
 
+use super::exemplars::to_openmetrics;
 use super::metrics::encode_metrics;
+use super::subjects::{summaries, SubjectSummary};
+use crate::nats_handler::InProcessBus;
+use crate::resilience::Dependencies;
+use crate::storage::pool::{PoolLifecycle, PoolStatus};
 
 #[derive(Clone)]
 pub struct HealthState {
     pub db_pool: PgPool,
-    pub nats_connected: Arc<AtomicBool>,
-    pub redis_connected: Arc<AtomicBool>,
+    /// Lifecycle state of the primary and (if configured) replica pools
+    pub db_pools: Vec<Arc<PoolLifecycle>>,
+    /// Redis and NATS status, and the degradation modes they imply
+    pub dependencies: Arc<Dependencies>,
     pub ready: Arc<AtomicBool>,
+    /// Set in the dev profile to expose `POST /dev/bus/:subject`
+    pub dev_bus: Option<Arc<InProcessBus>>,
+    /// Effective configuration, already redacted, served at `/info`
+    pub config: Arc<serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -30,6 +44,8 @@ pub struct HealthResponse {
     status: String,
     version: String,
     uptime_seconds: u64,
+    /// Active degradation modes (`query_only`, `buffering`)
+    modes: Vec<&'static str>,
     checks: HealthChecks,
 }
 
@@ -38,6 +54,21 @@ pub struct HealthChecks {
     database: ComponentHealth,
     nats: ComponentHealth,
     redis: ComponentHealth,
+    db_pools: Vec<PoolStatus>,
+}
+
+#[derive(Serialize)]
+pub struct InfoResponse {
+    version: &'static str,
+    git_sha: &'static str,
+    build_timestamp: Option<DateTime<Utc>>,
+    /// Cargo features compiled in
+    features: Vec<&'static str>,
+    profile: String,
+    /// Highest init script recorded in `schema_version`; `None` when it could not be read
+    schema_version: Option<i32>,
+    uptime_seconds: u64,
+    config: Arc<serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -56,12 +87,28 @@ static START_TIME: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock
 pub async fn start_health_server(port: u16, state: HealthState) -> anyhow::Result<()> {
     START_TIME.get_or_init(std::time::Instant::now);
 
-    let app = Router::new()
+    let mut app = Router::new()
         .route("/health", get(health_check))
         .route("/health/live", get(liveness))
         .route("/health/ready", get(readiness))
+        .route("/info", get(info))
         .route("/metrics", get(prometheus_metrics))
-        .with_state(state);
+        .route("/debug/subjects", get(debug_subjects));
+
+    #[cfg(feature = "profiling")]
+    {
+        app = app
+            .route("/debug/pprof/profile", get(super::profiling::cpu_profile))
+            .route("/debug/pprof/heap", get(super::profiling::heap_stats));
+        info!("Profiling endpoints enabled at /debug/pprof");
+    }
+
+    if state.dev_bus.is_some() {
+        app = app.route("/dev/bus/:subject", post(dev_bus_request));
+        info!("Dev bus endpoint enabled at /dev/bus/:subject");
+    }
+
+    let app = app.with_state(state);
 
     let listener = TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
     info!(port = port, "Health/metrics server started");
@@ -86,50 +133,71 @@ async fn health_check(State(state): State<HealthState>) -> impl IntoResponse {
         },
     };
 
-    // Check NATS
-    let nats_health = if state.nats_connected.load(Ordering::Relaxed) {
+    // A pool whose probes are failing reports degraded until it recovers
+    let db_health = if db_health.status == "healthy" && state.db_pools.iter().any(|p| p.is_degraded()) {
+        ComponentHealth {
+            status: "degraded".to_string(),
+            ..db_health
+        }
+    } else {
+        db_health
+    };
+
+    // NATS and Redis are probed in the background; report the last result
+    let nats_health = if state.dependencies.nats_connected() {
         ComponentHealth {
             status: "healthy".to_string(),
-            latency_ms: None,
+            latency_ms: state.dependencies.nats_latency().map(|rtt| rtt.as_millis() as u64),
             error: None,
         }
     } else {
         ComponentHealth {
             status: "unhealthy".to_string(),
             latency_ms: None,
-            error: Some("NATS not connected".to_string()),
+            error: Some("NATS not connected, buffering outbound messages".to_string()),
         }
     };
 
-    // Check Redis
-    let redis_health = if state.redis_connected.load(Ordering::Relaxed) {
+    let redis_health = if !state.dependencies.redis_enabled() {
         ComponentHealth {
-            status: "healthy".to_string(),
+            status: "disabled".to_string(),
             latency_ms: None,
             error: None,
         }
+    } else if state.dependencies.redis_connected() {
+        ComponentHealth {
+            status: "healthy".to_string(),
+            latency_ms: state.dependencies.redis_latency().map(|rtt| rtt.as_millis() as u64),
+            error: None,
+        }
     } else {
+        // Queries are still served, so a missing Redis degrades rather than fails
         ComponentHealth {
-            status: "unhealthy".to_string(),
+            status: "degraded".to_string(),
             latency_ms: None,
-            error: Some("Redis not connected".to_string()),
+            error: Some("Redis not connected, serving queries only".to_string()),
         }
     };
 
-    let overall_healthy = db_health.status == "healthy"
-        && nats_health.status == "healthy"
-        && redis_health.status == "healthy";
+    let overall_healthy = db_health.status != "unhealthy" && nats_health.status == "healthy";
+    let degraded = db_health.status == "degraded" || redis_health.status == "degraded";
 
     let uptime = START_TIME.get().map(|t| t.elapsed().as_secs()).unwrap_or(0);
 
     let response = HealthResponse {
-        status: if overall_healthy { "healthy".to_string() } else { "unhealthy".to_string() },
+        status: match (overall_healthy, degraded) {
+            (false, _) => "unhealthy".to_string(),
+            (true, true) => "degraded".to_string(),
+            (true, false) => "healthy".to_string(),
+        },
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_seconds: uptime,
+        modes: state.dependencies.modes(),
         checks: HealthChecks {
             database: db_health,
             nats: nats_health,
             redis: redis_health,
+            db_pools: state.db_pools.iter().map(|p| p.status()).collect(),
         },
     };
 
@@ -151,6 +219,35 @@ async fn check_database(pool: &PgPool) -> Result<u64, sqlx::Error> {
     Ok(start.elapsed().as_millis() as u64)
 }
 
+/// What exactly is running: build identity, compiled features, schema and effective settings
+#[instrument(skip(state))]
+async fn info(State(state): State<HealthState>) -> Json<InfoResponse> {
+    let schema_version = match sqlx::query_scalar::<_, Option<i32>>("SELECT MAX(version) FROM schema_version")
+        .fetch_one(&state.db_pool)
+        .await
+    {
+        Ok(version) => version,
+        Err(e) => {
+            tracing::warn!(error = %e, "Could not read schema version");
+            None
+        }
+    };
+
+    Json(InfoResponse {
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: env!("BUILD_GIT_SHA"),
+        build_timestamp: env!("BUILD_TIMESTAMP")
+            .parse()
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+        features: env!("BUILD_FEATURES").split(',').filter(|f| !f.is_empty()).collect(),
+        profile: state.config["profile"].as_str().unwrap_or_default().to_string(),
+        schema_version,
+        uptime_seconds: START_TIME.get().map(|t| t.elapsed().as_secs()).unwrap_or(0),
+        config: state.config.clone(),
+    })
+}
+
 async fn liveness() -> impl IntoResponse {
     (StatusCode::OK, Json(serde_json::json!({ "status": "alive" })))
 }
@@ -169,14 +266,17 @@ async fn readiness(State(state): State<HealthState>) -> impl IntoResponse {
         .fetch_one(&state.db_pool)
         .await;
     let db_ok = db_result.is_ok();
-    let nats_ok = state.nats_connected.load(Ordering::Relaxed);
-    let redis_ok = state.redis_connected.load(Ordering::Relaxed);
+    let nats_ok = state.dependencies.nats_connected();
+    let redis_ok = state.dependencies.accepts_orders();
+    let modes = state.dependencies.modes();
 
-    if db_ok && nats_ok && redis_ok {
-        (StatusCode::OK, Json(serde_json::json!({ "status": "ready" })))
+    // Query-only mode still takes traffic; without NATS there is no intake at all
+    if db_ok && nats_ok {
+        (StatusCode::OK, Json(serde_json::json!({ "status": "ready", "modes": modes })))
     } else {
         (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
             "status": "not_ready",
+            "modes": modes,
             "database": db_ok,
             "nats": nats_ok,
             "redis": redis_ok
@@ -184,10 +284,52 @@ async fn readiness(State(state): State<HealthState>) -> impl IntoResponse {
     }
 }
 
-async fn prometheus_metrics() -> impl IntoResponse {
-    (
-        StatusCode::OK,
-        [("content-type", "text/plain; charset=utf-8")],
-        encode_metrics(),
-    )
+/// Dev profile only: request/reply over the in-process bus, body in and reply out
+async fn dev_bus_request(
+    State(state): State<HealthState>,
+    Path(subject): Path<String>,
+    body: Bytes,
+) -> Response {
+    let Some(bus) = state.dev_bus else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match bus.request(subject, body.to_vec(), Duration::from_secs(5)).await {
+        Ok(reply) => (
+            StatusCode::OK,
+            [("content-type", "application/json")],
+            reply.payload.to_vec(),
+        ).into_response(),
+        Err(e) => (
+            StatusCode::GATEWAY_TIMEOUT,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ).into_response(),
+    }
+}
+
+/// Text format by default; OpenMetrics (with latency exemplars) when the scraper asks for it
+async fn prometheus_metrics(headers: HeaderMap) -> impl IntoResponse {
+    let openmetrics = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/openmetrics-text"));
+
+    if openmetrics {
+        (
+            StatusCode::OK,
+            [("content-type", "application/openmetrics-text; version=1.0.0; charset=utf-8")],
+            to_openmetrics(&encode_metrics()),
+        )
+    } else {
+        (
+            StatusCode::OK,
+            [("content-type", "text/plain; version=0.0.4; charset=utf-8")],
+            encode_metrics(),
+        )
+    }
+}
+
+/// Live per-subject handler statistics: rates over the last minute, totals since startup
+async fn debug_subjects() -> Json<Vec<SubjectSummary>> {
+    Json(summaries())
 }
\ No newline at end of file
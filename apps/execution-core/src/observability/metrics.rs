@@ -8,21 +8,77 @@ use prometheus::{
 };
 use std::sync::Mutex;
 
+/// Upper bounds of the order processing latency histogram, in seconds
+pub const ORDER_LATENCY_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+/// Upper bounds of the per-stage order latency histogram, 50µs to 50ms
+const STAGE_LATENCY_BUCKETS: &[f64] = &[0.00005, 0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05];
+
+/// Payload size buckets, 64 B to 1 MiB (the NATS default max payload)
+const MESSAGE_SIZE_BUCKETS: &[f64] = &[64.0, 256.0, 1_024.0, 4_096.0, 16_384.0, 65_536.0, 262_144.0, 1_048_576.0];
+
 /// Global metrics registry
 static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
 
 /// Application metrics
+#[allow(dead_code)]
 pub struct Metrics {
     pub orders_processed_total: CounterVec,
     pub orders_rejected_total: CounterVec,
     pub order_processing_duration: HistogramVec,
+    pub order_stage_duration: HistogramVec,
     pub position_updates_total: Counter,
     pub active_positions: Gauge,
     pub position_pnl: GaugeVec,
     pub db_pool_connections: GaugeVec,
+    pub db_queries_total: CounterVec,
+    pub db_pool_events_total: CounterVec,
+    pub db_concurrency_limit: GaugeVec,
+    pub db_inflight: GaugeVec,
+    pub read_hedges_total: CounterVec,
+    pub slow_operations_total: CounterVec,
+    pub error_reports_total: CounterVec,
+    pub slo_burn_rate: GaugeVec,
+    pub slo_alert: GaugeVec,
+    pub traded_notional_total: CounterVec,
+    pub fee_revenue_total: CounterVec,
+    pub active_accounts: Gauge,
+    pub open_interest: GaugeVec,
+    pub concentration_breaches_total: CounterVec,
+    pub post_trade_alerts_total: CounterVec,
+    pub stale_requests_total: CounterVec,
+    pub order_acks_total: CounterVec,
+    pub order_journal_lag: HistogramVec,
+    pub clock_skew_ms: GaugeVec,
+    pub clock_skew_alarm: Gauge,
+    pub fill_deliveries_total: CounterVec,
+    pub engine_errors_total: CounterVec,
+    pub retention_purged_rows_total: CounterVec,
+    pub retention_pending_rows: GaugeVec,
+    pub order_events_compacted_total: Counter,
+    pub twap_slices_total: CounterVec,
+    pub rebates_credited_total: CounterVec,
+    pub dust_swept_total: CounterVec,
+    pub internal_crosses_total: Counter,
+    pub execution_reports_dropped_total: Counter,
+    pub exports_total: CounterVec,
+    pub analytics_rows_exported_total: CounterVec,
+    pub ledger_integrity_violations: GaugeVec,
+    pub cache_consistency_mismatches_total: CounterVec,
+    pub load_shed_level: Gauge,
+    pub degradation_mode: GaugeVec,
+    pub nats_region_connected: GaugeVec,
+    pub nats_region_active: GaugeVec,
+    pub bus_buffered_messages: Gauge,
+    pub bus_buffer_dropped_total: Counter,
+    pub load_shed_rejections_total: CounterVec,
+    pub overload_signal: GaugeVec,
     pub nats_messages_received: CounterVec,
     pub nats_messages_published: CounterVec,
+    pub nats_replies_total: CounterVec,
+    pub nats_message_size_bytes: HistogramVec,
     pub circuit_breaker_state: GaugeVec,
+    pub circuit_breaker_rejections_total: CounterVec,
     pub retry_attempts_total: CounterVec,
 }
 
@@ -49,12 +105,21 @@ pub fn init_metrics(service_name: &str) -> anyhow::Result<()> {
             "enthropic_order_processing_duration_seconds",
             "Order processing latency in seconds"
         )
-            .namespace("enthropic")
             .const_label("service", service_name)
-            .buckets(vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]),
+            .buckets(ORDER_LATENCY_BUCKETS.to_vec()),
         &["operation"]
     )?;
 
+    let order_stage_duration = HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "enthropic_order_stage_duration_seconds",
+            "Time an order spent in each processing stage"
+        )
+            .const_label("service", service_name)
+            .buckets(STAGE_LATENCY_BUCKETS.to_vec()),
+        &["operation", "stage"] // deserialize, auth, risk, db, publish
+    )?;
+
     let position_updates_total = Counter::new(
         "enthropic_position_updates_total",
         "Total position updates"
@@ -72,7 +137,220 @@ pub fn init_metrics(service_name: &str) -> anyhow::Result<()> {
 
     let db_pool_connections = GaugeVec::new(
         Opts::new("enthropic_db_pool_connections", "Database pool connections"),
-        &["state"] // active, idle
+        &["pool", "state"] // primary|replica, active|idle
+    )?;
+
+    let db_queries_total = CounterVec::new(
+        Opts::new("enthropic_db_queries_total", "Routed read queries by pool"),
+        &["pool", "outcome"] // ok, error, fallback
+    )?;
+
+    let db_pool_events_total = CounterVec::new(
+        Opts::new("enthropic_db_pool_events_total", "Connection pool lifecycle events"),
+        &["pool", "event"] // probe_failed, recovered, failover, repair
+    )?;
+
+    let db_concurrency_limit = GaugeVec::new(
+        Opts::new("enthropic_db_concurrency_limit", "Adaptive limit on in-flight database operations"),
+        &["pool"]
+    )?;
+
+    let db_inflight = GaugeVec::new(
+        Opts::new("enthropic_db_inflight", "Database operations currently holding a limiter slot"),
+        &["pool"]
+    )?;
+
+    let read_hedges_total = CounterVec::new(
+        Opts::new("enthropic_read_hedges_total", "Hedged read attempts by outcome"),
+        &["outcome"] // fired, budget_exhausted, original_won, hedge_won
+    )?;
+
+    let slow_operations_total = CounterVec::new(
+        Opts::new("enthropic_slow_operations_total", "DB statements and publishes over their slow threshold"),
+        &["operation"]
+    )?;
+
+    let error_reports_total = CounterVec::new(
+        Opts::new("enthropic_error_reports_total", "Error events sent to the reporting sinks"),
+        &["sink", "outcome"]
+    )?;
+
+    let slo_burn_rate = GaugeVec::new(
+        Opts::new("enthropic_slo_burn_rate", "Error budget burn rate per SLO and window"),
+        &["slo", "window"]
+    )?;
+
+    let slo_alert = GaugeVec::new(
+        Opts::new("enthropic_slo_alert", "1 while a multi-window burn rate alert is firing"),
+        &["slo", "severity"]
+    )?;
+
+    let traded_notional_total = CounterVec::new(
+        Opts::new("enthropic_traded_notional_total", "Filled quantity times fill price"),
+        &["symbol"]
+    )?;
+
+    let fee_revenue_total = CounterVec::new(
+        Opts::new("enthropic_fee_revenue_total", "Fees on traded notional at the configured fee rate"),
+        &["symbol"]
+    )?;
+
+    let active_accounts = Gauge::new(
+        "enthropic_active_accounts",
+        "Distinct accounts that placed an order in the last hour"
+    )?;
+
+    let open_interest = GaugeVec::new(
+        Opts::new("enthropic_open_interest", "Sum of long position quantities, updated on every fill"),
+        &["symbol"]
+    )?;
+
+    let concentration_breaches_total = CounterVec::new(
+        Opts::new("enthropic_concentration_breaches_total", "Orders that breached a concentration limit"),
+        &["symbol", "action"]
+    )?;
+
+    let post_trade_alerts_total = CounterVec::new(
+        Opts::new("enthropic_post_trade_alerts_total", "Fills that left an account past a post-trade risk alert"),
+        &["kind"]
+    )?;
+
+    let stale_requests_total = CounterVec::new(
+        Opts::new("enthropic_stale_requests_total", "Order-entry requests rejected unexecuted for arriving past the SLA"),
+        &["subject"]
+    )?;
+
+    let order_acks_total = CounterVec::new(
+        Opts::new("enthropic_order_acks_total", "Order submits answered, by ack mode"),
+        &["mode"]
+    )?;
+
+    let order_journal_lag = HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "enthropic_order_journal_lag_seconds",
+            "Time from a fast-acked order's journal append to its outcome"
+        ),
+        &["outcome"]
+    )?;
+
+    let clock_skew_ms = GaugeVec::new(
+        Opts::new("enthropic_clock_skew_ms", "Estimated offset of a source's clock behind the engine's"),
+        &["source"]
+    )?;
+
+    let clock_skew_alarm = Gauge::new(
+        "enthropic_clock_skew_alarm",
+        "1 while some clock is skewed past CLOCK_SKEW_ALARM_MS"
+    )?;
+
+    let fill_deliveries_total = CounterVec::new(
+        Opts::new("enthropic_fill_deliveries_total", "Execution report deliveries per consumer"),
+        &["consumer", "outcome"]
+    )?;
+
+    let engine_errors_total = CounterVec::new(
+        Opts::new("enthropic_engine_errors_total", "Failed engine calls by handler and failure class"),
+        &["handler", "class"]
+    )?;
+
+    let retention_purged_rows_total = CounterVec::new(
+        Opts::new("enthropic_retention_purged_rows_total", "Rows deleted by retention rules"),
+        &["class"]
+    )?;
+
+    let retention_pending_rows = GaugeVec::new(
+        Opts::new("enthropic_retention_pending_rows", "Rows past retention found by the last dry-run"),
+        &["class"]
+    )?;
+
+    let order_events_compacted_total = Counter::new(
+        "enthropic_order_events_compacted_total",
+        "Order events folded into snapshots and moved to the archive"
+    )?;
+
+    let twap_slices_total = CounterVec::new(
+        Opts::new("enthropic_twap_slices_total", "TWAP child orders sent, by outcome"),
+        &["outcome"]
+    )?;
+
+    let rebates_credited_total = CounterVec::new(
+        Opts::new("enthropic_rebates_credited_total", "Fee rebates credited to accounts, by kind"),
+        &["kind"]
+    )?;
+
+    let dust_swept_total = CounterVec::new(
+        Opts::new("enthropic_dust_swept_total", "Dust positions flagged or closed and dust balances swept"),
+        &["target", "action"]
+    )?;
+
+    let internal_crosses_total = Counter::new(
+        "enthropic_internal_crosses_total",
+        "Resting orders of different accounts crossed with each other in the internal book"
+    )?;
+
+    let execution_reports_dropped_total = Counter::new(
+        "enthropic_execution_reports_dropped_total",
+        "Execution reports not published on executions.* because the publish queue was full"
+    )?;
+
+    let exports_total = CounterVec::new(
+        Opts::new("enthropic_exports_total", "Account export jobs finished, by what they exported and how they ended"),
+        &["kind", "status"]
+    )?;
+
+    let analytics_rows_exported_total = CounterVec::new(
+        Opts::new("enthropic_analytics_rows_exported_total", "Trade and tick rows written to the analytics Parquet store"),
+        &["dataset"]
+    )?;
+
+    let ledger_integrity_violations = GaugeVec::new(
+        Opts::new("enthropic_ledger_integrity_violations", "Violations found by the last ledger integrity check"),
+        &["check"]
+    )?;
+
+    let cache_consistency_mismatches_total = CounterVec::new(
+        Opts::new("enthropic_cache_consistency_mismatches_total", "Cached orders and positions found to disagree with their rows"),
+        &["kind", "field"]
+    )?;
+
+    let load_shed_level = Gauge::new(
+        "enthropic_load_shed_level",
+        "Load shedding level (0=normal, 1=queries shed, 2=new orders shed)"
+    )?;
+
+    let degradation_mode = GaugeVec::new(
+        Opts::new("enthropic_degradation_mode", "Active degradation modes (1=active)"),
+        &["mode"] // query_only, buffering
+    )?;
+
+    let nats_region_connected = GaugeVec::new(
+        Opts::new("enthropic_nats_region_connected", "Whether each configured NATS region is connected (1=connected)"),
+        &["region"]
+    )?;
+
+    let nats_region_active = GaugeVec::new(
+        Opts::new("enthropic_nats_region_active", "NATS region the engine publishes through (1=active)"),
+        &["region"]
+    )?;
+
+    let bus_buffered_messages = Gauge::new(
+        "enthropic_bus_buffered_messages",
+        "Outbound messages queued locally while NATS is unreachable"
+    )?;
+
+    let bus_buffer_dropped_total = Counter::new(
+        "enthropic_bus_buffer_dropped_total",
+        "Queued outbound messages dropped because the local buffer was full"
+    )?;
+
+    let load_shed_rejections_total = CounterVec::new(
+        Opts::new("enthropic_load_shed_rejections_total", "Requests rejected by load shedding"),
+        &["priority"]
+    )?;
+
+    let overload_signal = GaugeVec::new(
+        Opts::new("enthropic_overload_signal", "Latest overload detector sample"),
+        &["signal"] // event_loop_lag_seconds, queue_depth, db_latency_seconds
     )?;
 
     let nats_messages_received = CounterVec::new(
@@ -85,11 +363,27 @@ pub fn init_metrics(service_name: &str) -> anyhow::Result<()> {
         &["subject"]
     )?;
 
+    let nats_replies_total = CounterVec::new(
+        Opts::new("enthropic_nats_replies_total", "Requests answered, by whether the reply reported success"),
+        &["subject", "outcome"]
+    )?;
+
+    let nats_message_size_bytes = HistogramVec::new(
+        prometheus::HistogramOpts::new("enthropic_nats_message_size_bytes", "Payload size of received messages")
+            .buckets(MESSAGE_SIZE_BUCKETS.to_vec()),
+        &["subject"]
+    )?;
+
     let circuit_breaker_state = GaugeVec::new(
         Opts::new("enthropic_circuit_breaker_state", "Circuit breaker state (0=closed, 0.5=half-open, 1=open)"),
         &["name"]
     )?;
 
+    let circuit_breaker_rejections_total = CounterVec::new(
+        Opts::new("enthropic_circuit_breaker_rejections_total", "Calls failed fast by an open circuit breaker"),
+        &["name"]
+    )?;
+
     let retry_attempts_total = CounterVec::new(
         Opts::new("enthropic_retry_attempts_total", "Total retry attempts"),
         &["operation", "outcome"]
@@ -99,26 +393,118 @@ pub fn init_metrics(service_name: &str) -> anyhow::Result<()> {
     REGISTRY.register(Box::new(orders_processed_total.clone()))?;
     REGISTRY.register(Box::new(orders_rejected_total.clone()))?;
     REGISTRY.register(Box::new(order_processing_duration.clone()))?;
+    REGISTRY.register(Box::new(order_stage_duration.clone()))?;
     REGISTRY.register(Box::new(position_updates_total.clone()))?;
     REGISTRY.register(Box::new(active_positions.clone()))?;
     REGISTRY.register(Box::new(position_pnl.clone()))?;
     REGISTRY.register(Box::new(db_pool_connections.clone()))?;
+    REGISTRY.register(Box::new(db_queries_total.clone()))?;
+    REGISTRY.register(Box::new(db_pool_events_total.clone()))?;
+    REGISTRY.register(Box::new(db_concurrency_limit.clone()))?;
+    REGISTRY.register(Box::new(db_inflight.clone()))?;
+    REGISTRY.register(Box::new(read_hedges_total.clone()))?;
+    REGISTRY.register(Box::new(slow_operations_total.clone()))?;
+    REGISTRY.register(Box::new(error_reports_total.clone()))?;
+    REGISTRY.register(Box::new(slo_burn_rate.clone()))?;
+    REGISTRY.register(Box::new(slo_alert.clone()))?;
+    REGISTRY.register(Box::new(traded_notional_total.clone()))?;
+    REGISTRY.register(Box::new(fee_revenue_total.clone()))?;
+    REGISTRY.register(Box::new(active_accounts.clone()))?;
+    REGISTRY.register(Box::new(open_interest.clone()))?;
+    REGISTRY.register(Box::new(concentration_breaches_total.clone()))?;
+    REGISTRY.register(Box::new(post_trade_alerts_total.clone()))?;
+    REGISTRY.register(Box::new(stale_requests_total.clone()))?;
+    REGISTRY.register(Box::new(order_acks_total.clone()))?;
+    REGISTRY.register(Box::new(order_journal_lag.clone()))?;
+    REGISTRY.register(Box::new(clock_skew_ms.clone()))?;
+    REGISTRY.register(Box::new(clock_skew_alarm.clone()))?;
+    REGISTRY.register(Box::new(fill_deliveries_total.clone()))?;
+    REGISTRY.register(Box::new(engine_errors_total.clone()))?;
+    REGISTRY.register(Box::new(retention_purged_rows_total.clone()))?;
+    REGISTRY.register(Box::new(retention_pending_rows.clone()))?;
+    REGISTRY.register(Box::new(order_events_compacted_total.clone()))?;
+    REGISTRY.register(Box::new(twap_slices_total.clone()))?;
+    REGISTRY.register(Box::new(rebates_credited_total.clone()))?;
+    REGISTRY.register(Box::new(dust_swept_total.clone()))?;
+    REGISTRY.register(Box::new(internal_crosses_total.clone()))?;
+    REGISTRY.register(Box::new(execution_reports_dropped_total.clone()))?;
+    REGISTRY.register(Box::new(exports_total.clone()))?;
+    REGISTRY.register(Box::new(analytics_rows_exported_total.clone()))?;
+    REGISTRY.register(Box::new(ledger_integrity_violations.clone()))?;
+    REGISTRY.register(Box::new(cache_consistency_mismatches_total.clone()))?;
+    REGISTRY.register(Box::new(load_shed_level.clone()))?;
+    REGISTRY.register(Box::new(degradation_mode.clone()))?;
+    REGISTRY.register(Box::new(nats_region_connected.clone()))?;
+    REGISTRY.register(Box::new(nats_region_active.clone()))?;
+    REGISTRY.register(Box::new(bus_buffered_messages.clone()))?;
+    REGISTRY.register(Box::new(bus_buffer_dropped_total.clone()))?;
+    REGISTRY.register(Box::new(load_shed_rejections_total.clone()))?;
+    REGISTRY.register(Box::new(overload_signal.clone()))?;
     REGISTRY.register(Box::new(nats_messages_received.clone()))?;
     REGISTRY.register(Box::new(nats_messages_published.clone()))?;
+    REGISTRY.register(Box::new(nats_replies_total.clone()))?;
+    REGISTRY.register(Box::new(nats_message_size_bytes.clone()))?;
     REGISTRY.register(Box::new(circuit_breaker_state.clone()))?;
+    REGISTRY.register(Box::new(circuit_breaker_rejections_total.clone()))?;
     REGISTRY.register(Box::new(retry_attempts_total.clone()))?;
 
     let metrics = Metrics {
         orders_processed_total,
         orders_rejected_total,
         order_processing_duration,
+        order_stage_duration,
         position_updates_total,
         active_positions,
         position_pnl,
         db_pool_connections,
+        db_queries_total,
+        db_pool_events_total,
+        db_concurrency_limit,
+        db_inflight,
+        read_hedges_total,
+        slow_operations_total,
+        error_reports_total,
+        slo_burn_rate,
+        slo_alert,
+        traded_notional_total,
+        fee_revenue_total,
+        active_accounts,
+        open_interest,
+        concentration_breaches_total,
+        post_trade_alerts_total,
+        stale_requests_total,
+        order_acks_total,
+        order_journal_lag,
+        clock_skew_ms,
+        clock_skew_alarm,
+        fill_deliveries_total,
+        engine_errors_total,
+        retention_purged_rows_total,
+        retention_pending_rows,
+        order_events_compacted_total,
+        twap_slices_total,
+        rebates_credited_total,
+        dust_swept_total,
+        internal_crosses_total,
+        execution_reports_dropped_total,
+        exports_total,
+        analytics_rows_exported_total,
+        ledger_integrity_violations,
+        cache_consistency_mismatches_total,
+        load_shed_level,
+        degradation_mode,
+        nats_region_connected,
+        nats_region_active,
+        bus_buffered_messages,
+        bus_buffer_dropped_total,
+        load_shed_rejections_total,
+        overload_signal,
         nats_messages_received,
         nats_messages_published,
+        nats_replies_total,
+        nats_message_size_bytes,
         circuit_breaker_state,
+        circuit_breaker_rejections_total,
         retry_attempts_total,
     };
 
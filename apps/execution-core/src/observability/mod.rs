@@ -1,7 +1,17 @@
 //! Observability Module - OpenTelemetry Tracing, Metrics, Structured Logging
 //! Phase 3: Enterprise-grade observability for trading systems
 
+pub mod business;
+pub mod error_reporting;
+pub mod exemplars;
 pub mod metrics;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+pub mod redaction;
+pub mod slo;
+pub mod slow_ops;
+pub mod stages;
+pub mod subjects;
 pub mod tracing_setup;
 pub mod health;
 
@@ -29,12 +39,14 @@ pub fn init_observability(service_name: &str) -> anyhow::Result<()> {
         .with_file(true)
         .with_line_number(true)
         .with_thread_ids(true)
-        .with_thread_names(true);
+        .with_thread_names(true)
+        .with_writer(redaction::RedactingWriter::new(std::io::stdout));
 
     tracing_subscriber::registry()
         .with(env_filter)
         .with(json_layer)
         .with(telemetry_layer)
+        .with(error_reporting::reporting_enabled().then_some(error_reporting::ErrorReportingLayer))
         .init();
 
     tracing::info!(
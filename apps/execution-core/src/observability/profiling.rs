@@ -0,0 +1,115 @@
+//! Profiling Endpoints
+//! pprof-compatible CPU profiles and jemalloc heap statistics, built with the `profiling` feature
+
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use pprof::protos::Message;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tikv_jemalloc_ctl::{epoch, stats};
+
+/// Longest CPU profile a request may ask for
+const MAX_PROFILE_SECS: u64 = 120;
+/// Frames from the signal handler and libc add nothing to a flamegraph
+const BLOCKLIST: &[&str] = &["libc", "libgcc", "pthread", "vdso"];
+
+/// The sampling timer is process-wide, so only one CPU profile runs at a time
+static PROFILING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Deserialize)]
+pub struct ProfileParams {
+    #[serde(default = "default_seconds")]
+    seconds: u64,
+    #[serde(default = "default_frequency")]
+    frequency: i32,
+    /// `pprof` (protobuf, for `go tool pprof`) or `flamegraph` (SVG)
+    #[serde(default)]
+    format: Option<String>,
+}
+
+fn default_seconds() -> u64 {
+    30
+}
+
+fn default_frequency() -> i32 {
+    99
+}
+
+/// `GET /debug/pprof/profile?seconds=30&frequency=99&format=pprof|flamegraph`
+pub async fn cpu_profile(Query(params): Query<ProfileParams>) -> Response {
+    let flamegraph = match params.format.as_deref() {
+        None | Some("pprof") => false,
+        Some("flamegraph") => true,
+        Some(other) => {
+            return (StatusCode::BAD_REQUEST, format!("unknown format {:?}", other)).into_response();
+        }
+    };
+    if PROFILING.swap(true, Ordering::AcqRel) {
+        return (StatusCode::CONFLICT, "a CPU profile is already running").into_response();
+    }
+
+    let duration = Duration::from_secs(params.seconds.clamp(1, MAX_PROFILE_SECS));
+    let frequency = params.frequency.clamp(1, 1_000);
+    tracing::info!(seconds = duration.as_secs(), frequency, flamegraph, "CPU profile started");
+
+    // The profiler samples every thread; this one only waits out the duration
+    let profile = tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<u8>> {
+        let guard = pprof::ProfilerGuardBuilder::default()
+            .frequency(frequency)
+            .blocklist(BLOCKLIST)
+            .build()?;
+        std::thread::sleep(duration);
+        let report = guard.report().build()?;
+
+        let mut body = Vec::new();
+        if flamegraph {
+            report.flamegraph(&mut body)?;
+        } else {
+            report.pprof()?.encode(&mut body)?;
+        }
+        Ok(body)
+    }).await;
+    PROFILING.store(false, Ordering::Release);
+
+    match profile {
+        Ok(Ok(body)) if flamegraph => ([("content-type", "image/svg+xml")], body).into_response(),
+        Ok(Ok(body)) => (
+            [
+                ("content-type", "application/octet-stream"),
+                ("content-disposition", "attachment; filename=\"profile.pb\""),
+            ],
+            body,
+        ).into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// `GET /debug/pprof/heap`: jemalloc's view of the heap, in bytes
+pub async fn heap_stats() -> Response {
+    // jemalloc caches its statistics until the epoch advances
+    if let Err(e) = epoch::advance() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+
+    let read = || -> Result<serde_json::Value, tikv_jemalloc_ctl::Error> {
+        Ok(serde_json::json!({
+            "allocated": stats::allocated::read()?,
+            "active": stats::active::read()?,
+            "metadata": stats::metadata::read()?,
+            "resident": stats::resident::read()?,
+            "mapped": stats::mapped::read()?,
+            "retained": stats::retained::read()?,
+        }))
+    };
+
+    match read() {
+        Ok(heap) => Json(heap).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
@@ -0,0 +1,214 @@
+//! Log Redaction
+//! Sensitive fields and raw JWTs scrubbed from every log line and error report before it leaves the process
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+use tracing_subscriber::fmt::MakeWriter;
+
+pub const REDACTED: &str = "[REDACTED]";
+pub const REDACTED_JWT: &str = "[REDACTED_JWT]";
+
+/// Always redacted; `LOG_REDACT_FIELDS` adds to these
+pub const DEFAULT_FIELDS: &[&str] = &[
+    "token",
+    "access_token",
+    "refresh_token",
+    "jwt",
+    "authorization",
+    "password",
+    "secret",
+    "webhook_secret",
+    "api_key",
+    "username",
+    "email",
+    "metadata",
+];
+
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    fields: HashSet<String>,
+}
+
+impl Redactor {
+    pub fn new(extra_fields: &[String]) -> Self {
+        let fields = DEFAULT_FIELDS
+            .iter()
+            .map(|f| f.to_string())
+            .chain(extra_fields.iter().map(|f| f.trim().to_ascii_lowercase()))
+            .filter(|f| !f.is_empty())
+            .collect();
+        Self { fields }
+    }
+
+    /// Field names match case-insensitively, so `Authorization` is caught too
+    pub fn is_sensitive(&self, field: &str) -> bool {
+        self.fields.contains(&field.to_ascii_lowercase())
+    }
+
+    /// Blank sensitive fields at any depth and scrub JWTs out of every string
+    pub fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.is_sensitive(key) {
+                        *value = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_value(value);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::String(text) => {
+                if let Some(scrubbed) = scrub_jwts(text) {
+                    *text = scrubbed;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Redact one JSON log line; lines that are not JSON still have JWTs scrubbed.
+    /// Lines with neither a sensitive key nor a JWT are passed through untouched.
+    pub fn redact_line(&self, line: &str) -> String {
+        let lower = line.to_ascii_lowercase();
+        let suspicious = line.contains("eyJ")
+            || self.fields.iter().any(|field| lower.contains(&format!("\"{}\"", field)));
+        if !suspicious {
+            return line.to_string();
+        }
+
+        match serde_json::from_str::<Value>(line) {
+            Ok(mut value) => {
+                self.redact_value(&mut value);
+                value.to_string()
+            }
+            Err(_) => scrub_jwts(line).unwrap_or_else(|| line.to_string()),
+        }
+    }
+}
+
+/// Replace every compact JWT (`eyJ<header>.<payload>.<signature>`) in `text`;
+/// `None` when there was nothing to replace
+pub fn scrub_jwts(text: &str) -> Option<String> {
+    if !text.contains("eyJ") {
+        return None;
+    }
+
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let (mut copied, mut i, mut found) = (0, 0, false);
+
+    while let Some(offset) = text[i..].find("eyJ") {
+        let start = i + offset;
+        match jwt_end(bytes, start) {
+            Some(end) => {
+                out.push_str(&text[copied..start]);
+                out.push_str(REDACTED_JWT);
+                copied = end;
+                i = end;
+                found = true;
+            }
+            None => i = start + 3,
+        }
+    }
+
+    found.then(|| {
+        out.push_str(&text[copied..]);
+        out
+    })
+}
+
+/// End of the JWT starting at `start`: two non-empty segments, then a signature
+/// that is empty only for unsigned tokens
+fn jwt_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let segment = |from: usize| from + bytes[from..].iter().take_while(|b| is_base64url(**b)).count();
+
+    let header_end = segment(start);
+    if bytes.get(header_end) != Some(&b'.') {
+        return None;
+    }
+    let payload_end = segment(header_end + 1);
+    if payload_end == header_end + 1 || bytes.get(payload_end) != Some(&b'.') {
+        return None;
+    }
+    Some(segment(payload_end + 1))
+}
+
+fn is_base64url(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_'
+}
+
+static REDACTOR: OnceLock<Redactor> = OnceLock::new();
+
+/// Add the comma-separated `spec` fields once at startup; until then only the defaults apply
+pub fn init_redaction(spec: &str) {
+    let extra: Vec<String> = spec.split(',').map(str::to_string).collect();
+    let _ = REDACTOR.set(Redactor::new(&extra));
+}
+
+pub fn redactor() -> &'static Redactor {
+    REDACTOR.get_or_init(|| Redactor::new(&[]))
+}
+
+// =====================================================
+// WRITER
+// =====================================================
+
+/// Writer for the fmt layer: each event is buffered and redacted as a whole
+/// before it reaches `inner`, whatever the level or layer that formatted it
+pub struct RedactingWriter<M> {
+    inner: M,
+}
+
+impl<M> RedactingWriter<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingWriter<M> {
+    type Writer = RedactedEvent<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactedEvent {
+            buffer: Vec::new(),
+            inner: self.inner.make_writer(),
+        }
+    }
+}
+
+pub struct RedactedEvent<W: Write> {
+    buffer: Vec<u8>,
+    inner: W,
+}
+
+impl<W: Write> Write for RedactedEvent<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for RedactedEvent<W> {
+    fn drop(&mut self) {
+        let text = String::from_utf8_lossy(&self.buffer);
+        let redactor = redactor();
+        let mut out = String::with_capacity(text.len());
+        for line in text.split_inclusive('\n') {
+            let (line, newline) = match line.strip_suffix('\n') {
+                Some(line) => (line, "\n"),
+                None => (line, ""),
+            };
+            out.push_str(&redactor.redact_line(line));
+            out.push_str(newline);
+        }
+        let _ = self.inner.write_all(out.as_bytes());
+        let _ = self.inner.flush();
+    }
+}
@@ -0,0 +1,251 @@
+//! Service Level Objectives
+//! Good/total event counts per SLO, multi-window burn rates and self-evaluated burn alerts
+
+use super::error_reporting::{report, ErrorEvent, Severity};
+use super::metrics::get_metrics;
+use crate::clock::SharedClock;
+
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Submits and cancels acknowledged within the ack threshold
+pub const ORDER_ACK: &str = "order_ack_latency";
+/// Fills recorded within the fill threshold of the tick that triggered them
+pub const FILL_DELAY: &str = "fill_delay";
+/// Submits and cancels answered without a database or Redis failure
+pub const ORDER_ERRORS: &str = "order_error_rate";
+
+/// Events are counted in buckets of this many seconds
+const BUCKET_SECS: u64 = 10;
+
+/// Burn-rate windows, shortest first; the longest bounds how much history is kept
+pub const WINDOWS: [(&str, u64); 4] = [("5m", 300), ("30m", 1_800), ("1h", 3_600), ("6h", 21_600)];
+
+/// Multi-window alerts: fire when both the long and the short window burn faster than
+/// the threshold. A 14.4x burn spends 2% of a 30-day budget in an hour, 6x spends 5% in six.
+pub const ALERT_RULES: [AlertRule; 2] = [
+    AlertRule { severity: "page", long: "1h", short: "5m", burn_rate: 14.4 },
+    AlertRule { severity: "ticket", long: "6h", short: "30m", burn_rate: 6.0 },
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct AlertRule {
+    pub severity: &'static str,
+    pub long: &'static str,
+    pub short: &'static str,
+    pub burn_rate: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SloConfig {
+    pub order_ack_threshold: Duration,
+    pub fill_delay_threshold: Duration,
+    /// Share of events that must meet a latency threshold, e.g. 0.99
+    pub latency_objective: f64,
+    /// Share of order requests that must not fail server-side, e.g. 0.999
+    pub error_objective: f64,
+}
+
+/// How fast the error budget is being spent: 1.0 uses it up exactly over the SLO period
+pub fn burn_rate(good: u64, total: u64, objective: f64) -> f64 {
+    if total == 0 || objective >= 1.0 {
+        return 0.0;
+    }
+    let bad_ratio = (total - good.min(total)) as f64 / total as f64;
+    bad_ratio / (1.0 - objective)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Bucket {
+    index: u64,
+    good: u64,
+    total: u64,
+}
+
+struct Slo {
+    name: &'static str,
+    objective: f64,
+    threshold: Option<Duration>,
+    buckets: Mutex<VecDeque<Bucket>>,
+    /// Alert severities firing at the last evaluation
+    firing: Mutex<Vec<&'static str>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SloStatus {
+    pub name: &'static str,
+    pub objective: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold_ms: Option<u64>,
+    pub burn_rates: BTreeMap<&'static str, f64>,
+    /// Firing alert severities, `page` before `ticket`
+    pub alerts: Vec<&'static str>,
+}
+
+pub struct SloTracker {
+    slos: Vec<Slo>,
+    clock: SharedClock,
+}
+
+static TRACKER: OnceLock<SloTracker> = OnceLock::new();
+
+impl SloTracker {
+    pub fn new(config: SloConfig, clock: SharedClock) -> Self {
+        let slo = |name, objective, threshold| Slo {
+            name,
+            objective,
+            threshold,
+            buckets: Mutex::new(VecDeque::new()),
+            firing: Mutex::new(Vec::new()),
+        };
+
+        Self {
+            slos: vec![
+                slo(ORDER_ACK, config.latency_objective, Some(config.order_ack_threshold)),
+                slo(FILL_DELAY, config.latency_objective, Some(config.fill_delay_threshold)),
+                slo(ORDER_ERRORS, config.error_objective, None),
+            ],
+            clock,
+        }
+    }
+
+    fn record(&self, name: &str, good: bool) {
+        let Some(slo) = self.slos.iter().find(|slo| slo.name == name) else {
+            return;
+        };
+        let index = self.bucket_index();
+        let history = WINDOWS[WINDOWS.len() - 1].1 / BUCKET_SECS;
+
+        let mut buckets = slo.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.back().map(|b| b.index) != Some(index) {
+            buckets.push_back(Bucket { index, ..Bucket::default() });
+        }
+        while buckets.front().is_some_and(|b| b.index + history <= index) {
+            buckets.pop_front();
+        }
+        let bucket = buckets.back_mut().expect("bucket pushed above");
+        bucket.total += 1;
+        if good {
+            bucket.good += 1;
+        }
+    }
+
+    fn bucket_index(&self) -> u64 {
+        self.clock.elapsed().as_secs() / BUCKET_SECS
+    }
+
+    pub fn status(&self) -> Vec<SloStatus> {
+        let now = self.bucket_index();
+
+        self.slos
+            .iter()
+            .map(|slo| {
+                let buckets = slo.buckets.lock().unwrap_or_else(|e| e.into_inner());
+                let burn_rates: BTreeMap<&'static str, f64> = WINDOWS
+                    .iter()
+                    .map(|(label, secs)| {
+                        let span = secs / BUCKET_SECS;
+                        let (good, total) = buckets
+                            .iter()
+                            .filter(|b| b.index + span > now)
+                            .fold((0, 0), |(good, total), b| (good + b.good, total + b.total));
+                        (*label, burn_rate(good, total, slo.objective))
+                    })
+                    .collect();
+                drop(buckets);
+
+                let alerts = ALERT_RULES
+                    .iter()
+                    .filter(|rule| burn_rates[rule.long] > rule.burn_rate && burn_rates[rule.short] > rule.burn_rate)
+                    .map(|rule| rule.severity)
+                    .collect();
+
+                SloStatus {
+                    name: slo.name,
+                    objective: slo.objective,
+                    threshold_ms: slo.threshold.map(|t| t.as_millis() as u64),
+                    burn_rates,
+                    alerts,
+                }
+            })
+            .collect()
+    }
+
+    /// Publish burn rates and alert states, announcing alerts as they start firing
+    pub fn evaluate(&self) {
+        for status in self.status() {
+            if let Some(ref metrics) = *get_metrics() {
+                for (window, rate) in &status.burn_rates {
+                    metrics.slo_burn_rate.with_label_values(&[status.name, window]).set(*rate);
+                }
+                for rule in ALERT_RULES {
+                    let value = if status.alerts.contains(&rule.severity) { 1.0 } else { 0.0 };
+                    metrics.slo_alert.with_label_values(&[status.name, rule.severity]).set(value);
+                }
+            }
+
+            let Some(slo) = self.slos.iter().find(|slo| slo.name == status.name) else {
+                continue;
+            };
+            let mut firing = slo.firing.lock().unwrap_or_else(|e| e.into_inner());
+            for severity in status.alerts.iter().filter(|s| !firing.contains(s)) {
+                tracing::warn!(slo = status.name, severity, burn_rates = ?status.burn_rates, "SLO burn rate alert firing");
+                report(
+                    ErrorEvent::new("slo_alert", Severity::Warning, format!("SLO {} burning error budget", status.name))
+                        .with("slo", status.name)
+                        .with("severity", *severity)
+                        .with("objective", status.objective)
+                        .with("burn_rates", serde_json::json!(status.burn_rates)),
+                );
+            }
+            for severity in firing.iter().filter(|s| !status.alerts.contains(s)) {
+                tracing::info!(slo = status.name, severity, "SLO burn rate alert resolved");
+            }
+            *firing = status.alerts;
+        }
+    }
+}
+
+/// Start tracking; until then recorded events are ignored
+pub fn init_slos(config: SloConfig, clock: SharedClock) {
+    let _ = TRACKER.set(SloTracker::new(config, clock));
+}
+
+/// Count an event of a latency SLO, good when within its threshold
+pub fn record_latency(name: &str, latency: Duration) {
+    if let Some(tracker) = TRACKER.get() {
+        let good = tracker
+            .slos
+            .iter()
+            .find(|slo| slo.name == name)
+            .and_then(|slo| slo.threshold)
+            .is_none_or(|threshold| latency <= threshold);
+        tracker.record(name, good);
+    }
+}
+
+/// Count an event of an error-rate SLO
+pub fn record_outcome(name: &str, ok: bool) {
+    if let Some(tracker) = TRACKER.get() {
+        tracker.record(name, ok);
+    }
+}
+
+/// Current status of every SLO; empty before `init_slos`
+pub fn slo_status() -> Vec<SloStatus> {
+    TRACKER.get().map(SloTracker::status).unwrap_or_default()
+}
+
+/// Re-evaluate every `interval`
+pub async fn run_slo_evaluation(interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+        if let Some(tracker) = TRACKER.get() {
+            tracker.evaluate();
+        }
+    }
+}
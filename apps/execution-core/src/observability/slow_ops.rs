@@ -0,0 +1,84 @@
+//! Slow Operation Logging
+//! Times DB statements and NATS publishes, warning with the trace as correlation id past a threshold
+
+use super::metrics::get_metrics;
+use super::tracing_setup::current_trace_id;
+
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub struct SlowThresholds {
+    pub db: Duration,
+    pub publish: Duration,
+}
+
+impl Default for SlowThresholds {
+    fn default() -> Self {
+        Self {
+            db: Duration::from_millis(100),
+            publish: Duration::from_millis(50),
+        }
+    }
+}
+
+static THRESHOLDS: OnceLock<SlowThresholds> = OnceLock::new();
+
+/// Set the thresholds once at startup; until then the defaults apply
+pub fn init_slow_ops(thresholds: SlowThresholds) {
+    let _ = THRESHOLDS.set(thresholds);
+}
+
+fn thresholds() -> SlowThresholds {
+    THRESHOLDS.get().copied().unwrap_or_default()
+}
+
+/// Time a database statement; `statement` is a fixed label such as `orders.insert`
+pub async fn slow_query<T, Fut>(statement: &'static str, query: Fut) -> T
+where
+    Fut: Future<Output = T>,
+{
+    timed("db", statement, None, thresholds().db, query).await
+}
+
+/// Time a publish; the subject is logged but not used as a metric label
+pub async fn slow_publish<T, Fut>(subject: &str, publish: Fut) -> T
+where
+    Fut: Future<Output = T>,
+{
+    timed("nats", "nats.publish", Some(subject), thresholds().publish, publish).await
+}
+
+async fn timed<T, Fut>(
+    kind: &'static str,
+    operation: &'static str,
+    subject: Option<&str>,
+    threshold: Duration,
+    future: Fut,
+) -> T
+where
+    Fut: Future<Output = T>,
+{
+    let started = Instant::now();
+    let output = future.await;
+    let elapsed = started.elapsed();
+
+    if elapsed >= threshold {
+        tracing::warn!(
+            kind,
+            operation,
+            subject,
+            duration_ms = elapsed.as_millis() as u64,
+            threshold_ms = threshold.as_millis() as u64,
+            correlation_id = %current_trace_id().unwrap_or_default(),
+            "Slow operation"
+        );
+
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.slow_operations_total.with_label_values(&[operation]).inc();
+        }
+    }
+
+    output
+}
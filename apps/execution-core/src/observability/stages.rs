@@ -0,0 +1,123 @@
+//! Order Stage Timing
+//! Where an order spent its latency budget: deserialize, auth, risk, db and publish
+
+use super::metrics::get_metrics;
+
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::clock::SharedClock;
+
+/// End-to-end budget of an order, from receipt to reply
+pub const ORDER_LATENCY_BUDGET: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Deserialize,
+    Auth,
+    /// Validation, pre-funding and trigger checks
+    Risk,
+    /// Idempotency lookup, inserts and commit, including the wait for a permit
+    Db,
+    Publish,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [Stage::Deserialize, Stage::Auth, Stage::Risk, Stage::Db, Stage::Publish];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Stage::Deserialize => "deserialize",
+            Stage::Auth => "auth",
+            Stage::Risk => "risk",
+            Stage::Db => "db",
+            Stage::Publish => "publish",
+        }
+    }
+
+    /// This stage's share of `ORDER_LATENCY_BUDGET`
+    pub fn budget(self) -> Duration {
+        match self {
+            Stage::Deserialize => Duration::from_millis(1),
+            Stage::Auth => Duration::from_millis(1),
+            Stage::Risk => Duration::from_millis(5),
+            Stage::Db => Duration::from_millis(10),
+            Stage::Publish => Duration::from_millis(3),
+        }
+    }
+}
+
+/// Per-order stopwatch: each `lap` charges the time since the previous one to a stage
+pub struct StageTimer {
+    clock: SharedClock,
+    last: Duration,
+    spent: [Duration; 5],
+}
+
+/// Microseconds per stage, as sent in the execution report
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct StageTimings {
+    pub deserialize: u64,
+    pub auth: u64,
+    pub risk: u64,
+    pub db: u64,
+}
+
+impl StageTimer {
+    pub fn start(clock: SharedClock) -> Self {
+        let last = clock.elapsed();
+        Self { clock, last, spent: [Duration::ZERO; 5] }
+    }
+
+    pub fn lap(&mut self, stage: Stage) {
+        let now = self.clock.elapsed();
+        self.spent[stage as usize] += now.saturating_sub(self.last);
+        self.last = now;
+    }
+
+    pub fn spent(&self, stage: Stage) -> Duration {
+        self.spent[stage as usize]
+    }
+
+    /// Stages up to the reply; publishing is still ahead when the report is built
+    pub fn timings(&self) -> StageTimings {
+        let micros = |stage| self.spent(stage).as_micros() as u64;
+        StageTimings {
+            deserialize: micros(Stage::Deserialize),
+            auth: micros(Stage::Auth),
+            risk: micros(Stage::Risk),
+            db: micros(Stage::Db),
+        }
+    }
+
+    /// Observe every stage and name the ones over budget when the order overran its own
+    pub fn finish(&self, operation: &str) {
+        if let Some(ref metrics) = *get_metrics() {
+            for stage in Stage::ALL {
+                metrics
+                    .order_stage_duration
+                    .with_label_values(&[operation, stage.as_str()])
+                    .observe(self.spent(stage).as_secs_f64());
+            }
+        }
+
+        let total: Duration = self.spent.iter().sum();
+        if total <= ORDER_LATENCY_BUDGET {
+            return;
+        }
+        let over: Vec<&str> = Stage::ALL
+            .into_iter()
+            .filter(|stage| self.spent(*stage) > stage.budget())
+            .map(Stage::as_str)
+            .collect();
+        tracing::warn!(
+            operation,
+            total_us = total.as_micros() as u64,
+            budget_us = ORDER_LATENCY_BUDGET.as_micros() as u64,
+            over_budget = ?over,
+            timings = ?self.timings(),
+            publish_us = self.spent(Stage::Publish).as_micros() as u64,
+            "Order over latency budget"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Per-Subject Handler Statistics
+//! Message rate, reply error rate and payload sizes per subscription, for metrics and `/debug/subjects`
+
+use super::metrics::get_metrics;
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Live rates are taken over this many seconds
+const RATE_WINDOW_SECS: u64 = 60;
+/// Rate buckets are this many seconds wide
+const BUCKET_SECS: u64 = 5;
+
+#[derive(Debug, Default)]
+struct SubjectCounters {
+    received: u64,
+    replies: u64,
+    errors: u64,
+    payload_bytes: u64,
+    max_payload_bytes: usize,
+    last_received_at: Option<DateTime<Utc>>,
+    /// (bucket index, received, errors) over the rate window
+    recent: VecDeque<(u64, u64, u64)>,
+}
+
+impl SubjectCounters {
+    fn bucket(&mut self, index: u64) -> &mut (u64, u64, u64) {
+        if self.recent.back().map(|b| b.0) != Some(index) {
+            self.recent.push_back((index, 0, 0));
+        }
+        let window = RATE_WINDOW_SECS / BUCKET_SECS;
+        while self.recent.front().is_some_and(|b| b.0 + window <= index) {
+            self.recent.pop_front();
+        }
+        self.recent.back_mut().expect("bucket pushed above")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubjectSummary {
+    pub subject: String,
+    pub received: u64,
+    pub replies: u64,
+    pub errors: u64,
+    /// Errors over replies since startup
+    pub error_rate: f64,
+    /// Over the last minute
+    pub messages_per_sec: f64,
+    pub errors_per_sec: f64,
+    pub avg_payload_bytes: u64,
+    pub max_payload_bytes: usize,
+    pub last_received_at: Option<DateTime<Utc>>,
+}
+
+static STARTED: Lazy<Instant> = Lazy::new(Instant::now);
+static SUBJECTS: Lazy<Mutex<BTreeMap<String, SubjectCounters>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+fn bucket_index() -> u64 {
+    STARTED.elapsed().as_secs() / BUCKET_SECS
+}
+
+/// A message arrived on `subject`, the subscription pattern it matched
+pub fn record_received(subject: &str, payload_len: usize) {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.nats_messages_received.with_label_values(&[subject]).inc();
+        metrics.nats_message_size_bytes.with_label_values(&[subject]).observe(payload_len as f64);
+    }
+
+    let index = bucket_index();
+    let mut subjects = SUBJECTS.lock().unwrap_or_else(|e| e.into_inner());
+    let counters = subjects.entry(subject.to_string()).or_default();
+    counters.received += 1;
+    counters.payload_bytes += payload_len as u64;
+    counters.max_payload_bytes = counters.max_payload_bytes.max(payload_len);
+    counters.last_received_at = Some(Utc::now());
+    counters.bucket(index).1 += 1;
+}
+
+/// A request on `subject` was answered
+pub fn record_reply(subject: &str, success: bool) {
+    let outcome = if success { "success" } else { "error" };
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.nats_replies_total.with_label_values(&[subject, outcome]).inc();
+    }
+
+    let index = bucket_index();
+    let mut subjects = SUBJECTS.lock().unwrap_or_else(|e| e.into_inner());
+    let counters = subjects.entry(subject.to_string()).or_default();
+    counters.replies += 1;
+    if !success {
+        counters.errors += 1;
+        counters.bucket(index).2 += 1;
+    }
+}
+
+/// Statistics of every subject seen since startup, by subject
+pub fn summaries() -> Vec<SubjectSummary> {
+    let index = bucket_index();
+    let window = RATE_WINDOW_SECS / BUCKET_SECS;
+    // Young processes have not filled the window yet
+    let seconds = STARTED.elapsed().as_secs().clamp(1, RATE_WINDOW_SECS) as f64;
+
+    let subjects = SUBJECTS.lock().unwrap_or_else(|e| e.into_inner());
+    subjects
+        .iter()
+        .map(|(subject, c)| {
+            let (received, errors) = c
+                .recent
+                .iter()
+                .filter(|b| b.0 + window > index)
+                .fold((0, 0), |(r, e), b| (r + b.1, e + b.2));
+
+            SubjectSummary {
+                subject: subject.clone(),
+                received: c.received,
+                replies: c.replies,
+                errors: c.errors,
+                error_rate: if c.replies == 0 { 0.0 } else { c.errors as f64 / c.replies as f64 },
+                messages_per_sec: received as f64 / seconds,
+                errors_per_sec: errors as f64 / seconds,
+                avg_payload_bytes: c.payload_bytes.checked_div(c.received).unwrap_or(0),
+                max_payload_bytes: c.max_payload_bytes,
+                last_received_at: c.last_received_at,
+            }
+        })
+        .collect()
+}
@@ -1,10 +1,13 @@
 //! OpenTelemetry Tracing Configuration
 //! Production-grade distributed tracing with OTLP export
 
-use opentelemetry_sdk::{runtime, trace as sdktrace, Resource};
-use opentelemetry::KeyValue;
+use opentelemetry_sdk::{propagation::TraceContextPropagator, runtime, trace as sdktrace, Resource};
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::{global, KeyValue};
 use opentelemetry_otlp::WithExportConfig;
 use std::env;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 /// Initialize OpenTelemetry tracer with OTLP exporter
 pub fn init_tracer(service_name: &str) -> anyhow::Result<sdktrace::Tracer> {
@@ -21,6 +24,9 @@ pub fn init_tracer(service_name: &str) -> anyhow::Result<sdktrace::Tracer> {
         sdktrace::Sampler::AlwaysOn
     };
 
+    // W3C `traceparent` headers carry traces across NATS messages
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
     // Build OTLP exporter with endpoint
     let exporter = opentelemetry_otlp::new_exporter()
         .tonic()
@@ -53,4 +59,56 @@ pub fn init_tracer(service_name: &str) -> anyhow::Result<sdktrace::Tracer> {
     );
 
     Ok(tracer)
-}
\ No newline at end of file
+}
+
+/// Trace ID of the current span, if it belongs to a trace
+pub fn current_trace_id() -> Option<String> {
+    let context = tracing::Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    span_context.is_valid().then(|| span_context.trace_id().to_string())
+}
+
+// =====================================================
+// MESSAGE TRACE CONTEXT
+// =====================================================
+
+struct HeaderExtractor<'a>(&'a async_nats::HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|value| value.as_str())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.iter().map(|(name, _)| name.as_ref()).collect()
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut async_nats::HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key, value.as_str());
+    }
+}
+
+/// Parent the current span on the trace the message was published under, if any
+pub fn link_message_trace(msg: &async_nats::Message) {
+    if let Some(headers) = &msg.headers {
+        let parent = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        });
+        tracing::Span::current().set_parent(parent);
+    }
+}
+
+/// Headers carrying the current span's trace context; empty outside a sampled trace
+pub fn trace_headers() -> async_nats::HeaderMap {
+    let mut headers = async_nats::HeaderMap::new();
+    let context = tracing::Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers))
+    });
+    headers
+}
@@ -4,6 +4,7 @@
 // Placeholder for generated protobuf modules
 // These would be generated by prost-build during compilation
 
+#[allow(dead_code)]
 pub mod types {
     use serde::{Deserialize, Serialize};
 
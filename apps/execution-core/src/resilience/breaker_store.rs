@@ -0,0 +1,77 @@
+//! Shared Circuit Breaker State
+//! Breaker transitions persisted in Redis so every replica agrees on an open dependency
+
+use super::circuit_breaker::{CircuitBreaker, CircuitBreakerState};
+
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stale breakers expire so a retired dependency leaves nothing behind
+const STATE_TTL_SECS: u64 = 86_400;
+
+/// The last Open or Closed transition any replica made. Half-open is a local
+/// probing phase and is never shared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedBreakerState {
+    pub open: bool,
+    /// Wall-clock time of the transition; the latest one wins
+    pub changed_at_ms: i64,
+}
+
+impl SharedBreakerState {
+    pub fn local_state(&self) -> CircuitBreakerState {
+        if self.open {
+            CircuitBreakerState::Open
+        } else {
+            CircuitBreakerState::Closed
+        }
+    }
+}
+
+#[async_trait]
+pub trait BreakerStore: Send + Sync {
+    async fn load(&self, name: &str) -> anyhow::Result<Option<SharedBreakerState>>;
+    async fn save(&self, name: &str, state: &SharedBreakerState) -> anyhow::Result<()>;
+}
+
+pub struct RedisBreakerStore {
+    conn: redis::aio::ConnectionManager,
+}
+
+impl RedisBreakerStore {
+    pub fn new(conn: redis::aio::ConnectionManager) -> Self {
+        Self { conn }
+    }
+
+    fn key(name: &str) -> String {
+        format!("circuit_breaker:{}", name)
+    }
+}
+
+#[async_trait]
+impl BreakerStore for RedisBreakerStore {
+    async fn load(&self, name: &str) -> anyhow::Result<Option<SharedBreakerState>> {
+        let mut conn = self.conn.clone();
+        let raw: Option<String> = conn.get(Self::key(name)).await?;
+        Ok(raw.map(|raw| serde_json::from_str(&raw)).transpose()?)
+    }
+
+    async fn save(&self, name: &str, state: &SharedBreakerState) -> anyhow::Result<()> {
+        let mut conn = self.conn.clone();
+        conn.set_ex::<_, _, ()>(Self::key(name), serde_json::to_string(state)?, STATE_TTL_SECS).await?;
+        Ok(())
+    }
+}
+
+/// Adopt transitions made by other replicas every `interval`
+pub async fn sync_breaker(breaker: Arc<CircuitBreaker>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+        breaker.sync().await;
+    }
+}
@@ -1,9 +1,14 @@
 //! Circuit Breaker Implementation
 //! Prevents cascading failures by failing fast when a service is unhealthy
 
-use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use super::breaker_store::{BreakerStore, SharedBreakerState};
+use crate::clock::SharedClock;
+use crate::observability::error_reporting::{report, ErrorEvent, Severity};
+use crate::observability::metrics::get_metrics;
+
+use std::sync::atomic::{AtomicI64, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 
@@ -14,6 +19,24 @@ pub enum CircuitBreakerState {
     HalfOpen,
 }
 
+impl CircuitBreakerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+
+    fn gauge(&self) -> f64 {
+        match self {
+            Self::Closed => 0.0,
+            Self::HalfOpen => 0.5,
+            Self::Open => 1.0,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CircuitBreakerConfig {
     pub name: String,
@@ -42,10 +65,19 @@ pub struct CircuitBreaker {
     success_count: AtomicU32,
     last_failure_time: AtomicU64,
     half_open_calls: AtomicU32,
+    /// Wall-clock millis of the last Open or Closed transition, local or adopted
+    changed_at_ms: AtomicI64,
+    /// Shares transitions with other replicas; `None` keeps the breaker local
+    store: Option<Arc<dyn BreakerStore>>,
+    clock: SharedClock,
 }
 
 impl CircuitBreaker {
-    pub fn new(config: CircuitBreakerConfig) -> Self {
+    pub fn new(
+        config: CircuitBreakerConfig,
+        clock: SharedClock,
+        store: Option<Arc<dyn BreakerStore>>,
+    ) -> Self {
         Self {
             config,
             state: RwLock::new(CircuitBreakerState::Closed),
@@ -53,9 +85,17 @@ impl CircuitBreaker {
             success_count: AtomicU32::new(0),
             last_failure_time: AtomicU64::new(0),
             half_open_calls: AtomicU32::new(0),
+            changed_at_ms: AtomicI64::new(0),
+            store,
+            clock,
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    #[allow(dead_code)]
     pub async fn state(&self) -> CircuitBreakerState {
         *self.state.read().await
     }
@@ -69,13 +109,14 @@ impl CircuitBreaker {
             CircuitBreakerState::Open => {
                 // Check if timeout has passed
                 let last_failure = self.last_failure_time.load(Ordering::Relaxed);
-                let now = Instant::now().elapsed().as_secs();
+                let now = self.clock.elapsed().as_secs();
 
                 if now - last_failure >= self.config.timeout.as_secs() {
                     // Transition to half-open
                     let mut state = self.state.write().await;
                     *state = CircuitBreakerState::HalfOpen;
                     self.half_open_calls.store(0, Ordering::Relaxed);
+                    self.record_state(CircuitBreakerState::HalfOpen);
                     info!(name = %self.config.name, "Circuit breaker transitioning to half-open");
                     true
                 } else {
@@ -105,6 +146,7 @@ impl CircuitBreaker {
                     *state = CircuitBreakerState::Closed;
                     self.failure_count.store(0, Ordering::Relaxed);
                     self.success_count.store(0, Ordering::Relaxed);
+                    self.publish(CircuitBreakerState::Closed);
                     info!(name = %self.config.name, "Circuit breaker closed after recovery");
                 }
             }
@@ -122,29 +164,106 @@ impl CircuitBreaker {
 
                 if failures >= self.config.failure_threshold {
                     let mut state = self.state.write().await;
-                    *state = CircuitBreakerState::Open;
-                    self.last_failure_time.store(
-                        Instant::now().elapsed().as_secs(),
-                        Ordering::Relaxed
-                    );
-                    warn!(
-                        name = %self.config.name,
-                        failures = failures,
-                        "Circuit breaker opened"
-                    );
+                    if *state == CircuitBreakerState::Closed {
+                        *state = CircuitBreakerState::Open;
+                        self.trip("failure_threshold", failures);
+                    }
                 }
             }
             CircuitBreakerState::HalfOpen => {
                 let mut state = self.state.write().await;
-                *state = CircuitBreakerState::Open;
-                self.last_failure_time.store(
-                    Instant::now().elapsed().as_secs(),
-                    Ordering::Relaxed
-                );
-                self.success_count.store(0, Ordering::Relaxed);
-                warn!(name = %self.config.name, "Circuit breaker re-opened from half-open");
+                if *state == CircuitBreakerState::HalfOpen {
+                    *state = CircuitBreakerState::Open;
+                    self.trip("half_open_failure", 1);
+                }
             }
             CircuitBreakerState::Open => {}
         }
     }
+
+    /// Adopt the shared state if another replica (or this one before a restart)
+    /// made a later transition. Called at startup and then periodically.
+    pub async fn sync(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        match store.load(&self.config.name).await {
+            Ok(Some(shared)) => self.adopt(&shared).await,
+            Ok(None) => {}
+            Err(e) => warn!(name = %self.config.name, error = %e, "Failed to load shared circuit breaker state"),
+        }
+    }
+
+    async fn adopt(&self, shared: &SharedBreakerState) {
+        let mut state = self.state.write().await;
+        if shared.changed_at_ms <= self.changed_at_ms.load(Ordering::Relaxed) {
+            return;
+        }
+        self.changed_at_ms.store(shared.changed_at_ms, Ordering::Relaxed);
+
+        let adopted = shared.local_state();
+        if adopted == CircuitBreakerState::Open {
+            // Keep the original open time so the timeout runs out on every replica together
+            let age_secs = (self.clock.now().timestamp_millis() - shared.changed_at_ms).max(0) as u64 / 1_000;
+            self.last_failure_time.store(
+                self.clock.elapsed().as_secs().saturating_sub(age_secs),
+                Ordering::Relaxed
+            );
+        }
+        self.failure_count.store(0, Ordering::Relaxed);
+        self.success_count.store(0, Ordering::Relaxed);
+
+        if *state != adopted {
+            *state = adopted;
+            self.record_state(adopted);
+            info!(name = %self.config.name, state = adopted.as_str(), "Circuit breaker state adopted from shared store");
+        }
+    }
+
+    /// Record an Open or Closed transition and share it
+    fn publish(&self, to: CircuitBreakerState) {
+        let shared = SharedBreakerState {
+            open: to == CircuitBreakerState::Open,
+            changed_at_ms: self.clock.now().timestamp_millis(),
+        };
+        self.changed_at_ms.store(shared.changed_at_ms, Ordering::Relaxed);
+        self.record_state(to);
+
+        if let Some(store) = self.store.clone() {
+            let name = self.config.name.clone();
+            tokio::spawn(async move {
+                if let Err(e) = store.save(&name, &shared).await {
+                    warn!(name = %name, error = %e, "Failed to share circuit breaker state");
+                }
+            });
+        }
+    }
+
+    /// Every local open goes through here, with the breaker's state already set to open:
+    /// start the timeout, share the transition and raise the alert. Opens adopted from the
+    /// shared store are not reported; the replica that tripped did.
+    fn trip(&self, reason: &'static str, failures: u32) {
+        self.last_failure_time.store(self.clock.elapsed().as_secs(), Ordering::Relaxed);
+        self.success_count.store(0, Ordering::Relaxed);
+        self.publish(CircuitBreakerState::Open);
+        warn!(name = %self.config.name, reason, failures, "Circuit breaker opened");
+        self.report_open(reason, failures);
+    }
+
+    fn report_open(&self, reason: &'static str, failures: u32) {
+        report(
+            ErrorEvent::new("circuit_breaker_open", Severity::Warning, format!("Circuit breaker {} opened", self.config.name))
+                .with("name", self.config.name.clone())
+                .with("reason", reason)
+                .with("failures", failures)
+                .with("timeout_secs", self.config.timeout.as_secs()),
+        );
+    }
+
+    fn record_state(&self, state: CircuitBreakerState) {
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.circuit_breaker_state.with_label_values(&[&self.config.name]).set(state.gauge());
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,167 @@
+//! Clock Skew
+//! How far client, NATS server and database clocks are from ours, estimated from the
+//! timestamps they send, with an alarm when any drifts past the threshold
+
+use crate::clock::SharedClock;
+use crate::observability::metrics::get_metrics;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{error, info};
+
+/// Clocks compared with the engine's
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkewSource {
+    /// `Enthropic-Sent-At` on order entry
+    Client,
+    /// Stored time of JetStream intake deliveries
+    Nats,
+    /// `clock_timestamp()` of the primary
+    Database,
+}
+
+impl SkewSource {
+    pub const ALL: [SkewSource; 3] = [SkewSource::Client, SkewSource::Nats, SkewSource::Database];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Client => "client",
+            Self::Nats => "nats",
+            Self::Database => "database",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClockSkewConfig {
+    /// Skew of any source past this raises the alarm
+    pub alarm: Duration,
+    /// Stop trusting remote timestamps while alarmed, turning off the features that rely on them
+    pub disable_on_alarm: bool,
+}
+
+impl Default for ClockSkewConfig {
+    fn default() -> Self {
+        Self {
+            alarm: Duration::milliseconds(1_000),
+            disable_on_alarm: true,
+        }
+    }
+}
+
+/// Whether any estimate is further than `alarm` from our clock, either way
+pub fn skewed(estimates: &[Option<Duration>], alarm: Duration) -> bool {
+    estimates.iter().flatten().any(|offset| offset.abs() > alarm)
+}
+
+// =====================================================
+// CLOCK SKEW
+// =====================================================
+
+pub struct ClockSkew {
+    config: ClockSkewConfig,
+    clock: SharedClock,
+    /// Smallest offset seen per source in the current window. Every offset is the skew plus
+    /// a transit delay that is never negative, so the fastest sample is closest to the skew.
+    window: Mutex<[Option<Duration>; 3]>,
+    alarmed: AtomicBool,
+}
+
+impl ClockSkew {
+    pub fn new(config: ClockSkewConfig, clock: SharedClock) -> Self {
+        Self {
+            config,
+            clock,
+            window: Mutex::new([None; 3]),
+            alarmed: AtomicBool::new(false),
+        }
+    }
+
+    /// Record a timestamp `source` stamped just now by its own clock. The offset is positive
+    /// when the source's clock is behind ours.
+    pub fn observe(&self, source: SkewSource, stamped_at: DateTime<Utc>) {
+        let offset = self.clock.now() - stamped_at;
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = &mut window[source.index()];
+        *slot = Some(slot.map_or(offset, |min| min.min(offset)));
+    }
+
+    pub fn alarmed(&self) -> bool {
+        self.alarmed.load(Ordering::Relaxed)
+    }
+
+    /// Whether remote timestamps can be relied on; false while alarmed with `disable_on_alarm`
+    pub fn timestamps_trusted(&self) -> bool {
+        !(self.config.disable_on_alarm && self.alarmed())
+    }
+
+    /// Close the window: publish each source's estimate and raise or clear the alarm. A source
+    /// that sent nothing in the window does not count.
+    pub fn evaluate(&self) {
+        let window = std::mem::take(&mut *self.window.lock().unwrap_or_else(|e| e.into_inner()));
+
+        if let Some(ref metrics) = *get_metrics() {
+            for source in SkewSource::ALL {
+                if let Some(offset) = window[source.index()] {
+                    metrics.clock_skew_ms.with_label_values(&[source.as_str()]).set(offset.num_milliseconds() as f64);
+                }
+            }
+        }
+
+        let alarmed = skewed(&window, self.config.alarm);
+        let was_alarmed = self.alarmed.swap(alarmed, Ordering::Relaxed);
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.clock_skew_alarm.set(if alarmed { 1.0 } else { 0.0 });
+        }
+
+        if alarmed && !was_alarmed {
+            let offsets: Vec<String> = SkewSource::ALL
+                .iter()
+                .filter_map(|source| {
+                    window[source.index()].map(|offset| format!("{}={}ms", source.as_str(), offset.num_milliseconds()))
+                })
+                .collect();
+            error!(
+                offsets = %offsets.join(","),
+                alarm_ms = self.config.alarm.num_milliseconds(),
+                timestamps_trusted = self.timestamps_trusted(),
+                "Clock skew past alarm threshold, check NTP"
+            );
+        } else if !alarmed && was_alarmed {
+            info!("Clock skew back within alarm threshold");
+        }
+    }
+}
+
+/// Every `interval`, sample the database clock and close the skew window
+pub async fn monitor_clock_skew(skew: Arc<ClockSkew>, pool: PgPool, interval: std::time::Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = sample_database(&skew, &pool).await {
+            tracing::warn!(error = %e, "Database clock sample failed");
+        }
+        skew.evaluate();
+    }
+}
+
+/// Read the database clock, taken as stamped halfway through the round trip
+async fn sample_database(skew: &ClockSkew, pool: &PgPool) -> Result<(), sqlx::Error> {
+    let sent = skew.clock.now();
+    let db_now: DateTime<Utc> = sqlx::query_scalar("SELECT clock_timestamp()")
+        .fetch_one(pool)
+        .await?;
+    let round_trip = skew.clock.now() - sent;
+
+    // Shift the stamp forward by the return leg, so `observe` compares it with the time it arrived
+    skew.observe(SkewSource::Database, db_now + round_trip / 2);
+    Ok(())
+}
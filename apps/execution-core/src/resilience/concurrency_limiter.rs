@@ -0,0 +1,188 @@
+//! Adaptive Concurrency Limiter
+//! Gradient-based limit on in-flight database operations that tracks the pool's sweet spot
+
+use crate::clock::SharedClock;
+use crate::observability::metrics::get_metrics;
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Latency above `TOLERANCE` x the long-run average counts as queueing
+const TOLERANCE: f64 = 1.5;
+/// Weight of each sample in the long-run latency average
+const LONG_RTT_WEIGHT: f64 = 0.05;
+/// Weight of each new estimate in the limit
+const SMOOTHING: f64 = 0.2;
+/// Multiplicative decrease on a pool timeout
+const BACKOFF: f64 = 0.9;
+
+#[derive(Debug, Clone)]
+pub struct LimiterConfig {
+    pub name: &'static str,
+    pub min_limit: usize,
+    pub max_limit: usize,
+    pub initial_limit: usize,
+}
+
+struct LimiterState {
+    limit: f64,
+    inflight: usize,
+    long_rtt: Option<f64>,
+}
+
+/// Next limit after a sample (Gradient2). Steady latency grows the limit by about
+/// sqrt(limit); latency rising over the long-run average shrinks it proportionally.
+/// The limit only grows while at least half of it is in use.
+pub fn next_limit(
+    config: &LimiterConfig,
+    limit: f64,
+    long_rtt: f64,
+    rtt: f64,
+    inflight: usize,
+    overloaded: bool,
+) -> f64 {
+    let min = config.min_limit.max(1) as f64;
+    let max = config.max_limit.max(config.min_limit.max(1)) as f64;
+
+    if overloaded {
+        return (limit * BACKOFF).clamp(min, max);
+    }
+
+    let gradient = if rtt > 0.0 { (TOLERANCE * long_rtt / rtt).clamp(0.5, 1.0) } else { 1.0 };
+    let mut estimate = limit * gradient + limit.sqrt();
+    if estimate > limit && (inflight as f64) * 2.0 < limit {
+        estimate = limit;
+    }
+
+    (limit * (1.0 - SMOOTHING) + estimate * SMOOTHING).clamp(min, max)
+}
+
+pub struct AdaptiveLimiter {
+    config: LimiterConfig,
+    state: Mutex<LimiterState>,
+    released: Notify,
+    clock: SharedClock,
+}
+
+impl AdaptiveLimiter {
+    pub fn new(config: LimiterConfig, clock: SharedClock) -> Self {
+        let initial = config.initial_limit.clamp(config.min_limit.max(1), config.max_limit.max(1));
+        let limiter = Self {
+            state: Mutex::new(LimiterState {
+                limit: initial as f64,
+                inflight: 0,
+                long_rtt: None,
+            }),
+            config,
+            released: Notify::new(),
+            clock,
+        };
+        limiter.report(initial, 0);
+        limiter
+    }
+
+    /// Wait for a slot under the current limit. The slot is returned, and its latency
+    /// sampled, when the permit drops.
+    pub async fn acquire(self: &Arc<Self>) -> LimiterPermit {
+        loop {
+            let notified = self.released.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(inflight) = self.try_acquire() {
+                return LimiterPermit {
+                    limiter: self.clone(),
+                    started: self.clock.elapsed(),
+                    inflight,
+                    overloaded: false,
+                };
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Run a database call under the limit; a pool timeout counts as overload
+    pub async fn run<T, Fut>(self: &Arc<Self>, query: Fut) -> Result<T, sqlx::Error>
+    where
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let permit = self.acquire().await;
+        let result = query.await;
+        if matches!(result, Err(sqlx::Error::PoolTimedOut)) {
+            permit.overloaded();
+        }
+        result
+    }
+
+    fn try_acquire(&self) -> Option<usize> {
+        let mut state = self.lock();
+        if state.inflight >= state.limit as usize {
+            return None;
+        }
+        state.inflight += 1;
+        let (limit, inflight) = (state.limit as usize, state.inflight);
+        drop(state);
+
+        self.report(limit, inflight);
+        Some(inflight)
+    }
+
+    fn release(&self, rtt: Duration, inflight_at_start: usize, overloaded: bool) {
+        let mut state = self.lock();
+        state.inflight = state.inflight.saturating_sub(1);
+
+        let rtt = rtt.as_secs_f64();
+        let long_rtt = match state.long_rtt {
+            Some(long) => long * (1.0 - LONG_RTT_WEIGHT) + rtt * LONG_RTT_WEIGHT,
+            None => rtt,
+        };
+        state.long_rtt = Some(long_rtt);
+
+        let previous = state.limit as usize;
+        state.limit = next_limit(&self.config, state.limit, long_rtt, rtt, inflight_at_start, overloaded);
+        let (limit, inflight) = (state.limit as usize, state.inflight);
+        drop(state);
+
+        self.report(limit, inflight);
+        if limit > previous {
+            self.released.notify_waiters();
+        }
+        self.released.notify_one();
+    }
+
+    fn report(&self, limit: usize, inflight: usize) {
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.db_concurrency_limit.with_label_values(&[self.config.name]).set(limit as f64);
+            metrics.db_inflight.with_label_values(&[self.config.name]).set(inflight as f64);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A held slot; dropping it samples the operation's latency
+pub struct LimiterPermit {
+    limiter: Arc<AdaptiveLimiter>,
+    started: Duration,
+    inflight: usize,
+    overloaded: bool,
+}
+
+impl LimiterPermit {
+    /// The operation hit the pool's limits; back off instead of sampling latency
+    pub fn overloaded(mut self) {
+        self.overloaded = true;
+    }
+}
+
+impl Drop for LimiterPermit {
+    fn drop(&mut self) {
+        let rtt = self.limiter.clock.elapsed().saturating_sub(self.started);
+        self.limiter.release(rtt, self.inflight, self.overloaded);
+    }
+}
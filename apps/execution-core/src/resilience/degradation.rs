@@ -0,0 +1,210 @@
+//! Degradation Modes
+//! Which optional dependencies are up, and what the engine still serves without the rest
+
+use crate::nats_handler::SharedBus;
+use crate::observability::metrics::get_metrics;
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Serving reads only: without Redis revoked tokens cannot be checked, so no new orders
+pub const QUERY_ONLY: &str = "query_only";
+/// NATS is unreachable: no intake, and outbound messages queue locally until it returns
+pub const BUFFERING: &str = "buffering";
+
+/// Connection status of the dependencies the engine can start without.
+/// The database is not here: nothing works without it, so startup still fails.
+pub struct Dependencies {
+    /// False in the dev profile, where Redis is not started
+    redis_enabled: bool,
+    redis: AtomicBool,
+    nats: AtomicBool,
+    /// Round trip of the last successful probe in microseconds, `NO_LATENCY` until then
+    redis_latency_us: AtomicU64,
+    nats_latency_us: AtomicU64,
+}
+
+const NO_LATENCY: u64 = u64::MAX;
+
+impl Dependencies {
+    pub fn new(redis_enabled: bool) -> Self {
+        let dependencies = Self {
+            redis_enabled,
+            redis: AtomicBool::new(false),
+            nats: AtomicBool::new(false),
+            redis_latency_us: AtomicU64::new(NO_LATENCY),
+            nats_latency_us: AtomicU64::new(NO_LATENCY),
+        };
+        dependencies.report();
+        dependencies
+    }
+
+    pub fn redis_enabled(&self) -> bool {
+        self.redis_enabled
+    }
+
+    pub fn redis_connected(&self) -> bool {
+        self.redis.load(Ordering::Relaxed)
+    }
+
+    pub fn nats_connected(&self) -> bool {
+        self.nats.load(Ordering::Relaxed)
+    }
+
+    pub fn set_redis(&self, connected: bool) {
+        self.redis.store(connected, Ordering::Relaxed);
+        self.report();
+    }
+
+    pub fn set_nats(&self, connected: bool) {
+        self.nats.store(connected, Ordering::Relaxed);
+        self.report();
+    }
+
+    /// Round trip measured by the last probe; `None` before the first or after a failed one
+    pub fn redis_latency(&self) -> Option<Duration> {
+        latency(&self.redis_latency_us)
+    }
+
+    pub fn nats_latency(&self) -> Option<Duration> {
+        latency(&self.nats_latency_us)
+    }
+
+    pub fn accepts_orders(&self) -> bool {
+        !self.redis_enabled || self.redis_connected()
+    }
+
+    /// Active degradation modes; empty when fully operational
+    pub fn modes(&self) -> Vec<&'static str> {
+        let mut modes = Vec::new();
+        if !self.accepts_orders() {
+            modes.push(QUERY_ONLY);
+        }
+        if !self.nats_connected() {
+            modes.push(BUFFERING);
+        }
+        modes
+    }
+
+    fn report(&self) {
+        if let Some(ref metrics) = *get_metrics() {
+            let active = self.modes();
+            for mode in [QUERY_ONLY, BUFFERING] {
+                let value = if active.contains(&mode) { 1.0 } else { 0.0 };
+                metrics.degradation_mode.with_label_values(&[mode]).set(value);
+            }
+        }
+    }
+}
+
+fn latency(micros: &AtomicU64) -> Option<Duration> {
+    match micros.load(Ordering::Relaxed) {
+        NO_LATENCY => None,
+        us => Some(Duration::from_micros(us)),
+    }
+}
+
+// =====================================================
+// ACTIVE PROBES
+// =====================================================
+
+/// Ping NATS and (when enabled) Redis every `interval`, so the connection flags
+/// follow the dependencies instead of only being set at startup and reconnect
+pub async fn probe_dependencies(
+    dependencies: Arc<Dependencies>,
+    bus: SharedBus,
+    redis: Option<redis::Client>,
+    interval: Duration,
+    timeout: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    // Kept across probes; dropped after a failure so the next probe reconnects
+    let mut redis_conn: Option<redis::aio::ConnectionManager> = None;
+
+    loop {
+        ticker.tick().await;
+
+        let nats = probe(timeout, bus.ping()).await;
+        dependencies.record_probe("nats", &dependencies.nats, &dependencies.nats_latency_us, nats);
+
+        if let Some(client) = &redis {
+            let redis = probe(timeout, ping_redis(client, &mut redis_conn)).await;
+            if redis.is_err() {
+                redis_conn = None;
+            }
+            dependencies.record_probe("redis", &dependencies.redis, &dependencies.redis_latency_us, redis);
+        }
+    }
+}
+
+impl Dependencies {
+    fn record_probe(&self, dependency: &str, flag: &AtomicBool, latency_us: &AtomicU64, result: Result<Duration, String>) {
+        let was_connected = flag.load(Ordering::Relaxed);
+        match result {
+            Ok(rtt) => {
+                latency_us.store(u64::try_from(rtt.as_micros()).unwrap_or(NO_LATENCY - 1), Ordering::Relaxed);
+                if !was_connected {
+                    info!(dependency, rtt_ms = rtt.as_millis() as u64, "Dependency probe succeeded, marking connected");
+                }
+                flag.store(true, Ordering::Relaxed);
+            }
+            Err(e) => {
+                latency_us.store(NO_LATENCY, Ordering::Relaxed);
+                if was_connected {
+                    warn!(dependency, error = %e, "Dependency probe failed, marking disconnected");
+                }
+                flag.store(false, Ordering::Relaxed);
+            }
+        }
+        self.report();
+    }
+}
+
+async fn probe<F, E>(timeout: Duration, ping: F) -> Result<Duration, String>
+where
+    F: Future<Output = Result<(), E>>,
+    E: std::fmt::Display,
+{
+    let started = Instant::now();
+    match tokio::time::timeout(timeout, ping).await {
+        Ok(Ok(())) => Ok(started.elapsed()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("no answer within {:?}", timeout)),
+    }
+}
+
+async fn ping_redis(
+    client: &redis::Client,
+    conn: &mut Option<redis::aio::ConnectionManager>,
+) -> redis::RedisResult<()> {
+    if conn.is_none() {
+        *conn = Some(redis::aio::ConnectionManager::new(client.clone()).await?);
+    }
+    let conn = conn.as_mut().expect("connected above");
+    let _: String = redis::cmd("PING").query_async(conn).await?;
+    Ok(())
+}
+
+/// Keep trying `connect` every `interval` until it succeeds. Used after startup
+/// went ahead without the dependency.
+pub async fn reconnect<T, E, F, Fut>(dependency: &str, interval: Duration, mut connect: F) -> T
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    loop {
+        tokio::time::sleep(interval).await;
+
+        match connect().await {
+            Ok(connection) => {
+                info!(dependency, "Dependency reconnected, leaving degraded mode");
+                return connection;
+            }
+            Err(e) => warn!(dependency, error = %e, "Dependency still unavailable"),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Load Shedding
+//! Overload detection from event-loop lag, queue depth and DB latency, with graduated shedding
+
+use crate::clock::SharedClock;
+use crate::observability::metrics::get_metrics;
+
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// What is being shed, in escalation order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShedLevel {
+    Normal = 0,
+    /// Low-priority read queries are rejected
+    Queries = 1,
+    /// New orders are rejected as well
+    Orders = 2,
+}
+
+impl ShedLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Normal,
+            1 => Self::Queries,
+            _ => Self::Orders,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Queries => "queries",
+            Self::Orders => "orders",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Cancels and risk-reducing requests; never shed
+    Critical,
+    Order,
+    Query,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Critical => "critical",
+            Self::Order => "order",
+            Self::Query => "query",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OverloadSignals {
+    pub event_loop_lag: Duration,
+    pub queue_depth: usize,
+    pub db_latency: Duration,
+}
+
+/// A level is entered when any signal reaches its threshold
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    pub event_loop_lag: Duration,
+    pub queue_depth: usize,
+    pub db_latency: Duration,
+}
+
+impl Thresholds {
+    fn exceeded_by(&self, signals: &OverloadSignals, ratio: f64) -> bool {
+        signals.event_loop_lag >= self.event_loop_lag.mul_f64(ratio)
+            || signals.queue_depth as f64 >= self.queue_depth as f64 * ratio
+            || signals.db_latency >= self.db_latency.mul_f64(ratio)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadShedderConfig {
+    pub enabled: bool,
+    pub shed_queries: Thresholds,
+    pub shed_orders: Thresholds,
+    /// A level is left only once every signal is below this fraction of its thresholds
+    pub recover_ratio: f64,
+    /// Minimum time at a level before stepping down
+    pub min_hold: Duration,
+    pub sample_interval: Duration,
+}
+
+impl Default for LoadShedderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            shed_queries: Thresholds {
+                event_loop_lag: Duration::from_millis(100),
+                queue_depth: 500,
+                db_latency: Duration::from_millis(250),
+            },
+            shed_orders: Thresholds {
+                event_loop_lag: Duration::from_millis(500),
+                queue_depth: 2_000,
+                db_latency: Duration::from_secs(1),
+            },
+            recover_ratio: 0.7,
+            min_hold: Duration::from_secs(10),
+            sample_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Level the signals call for, given the current level. Escalation is immediate;
+/// stepping down needs the signals to clear the lower `recover_ratio` bar.
+pub fn target_level(config: &LoadShedderConfig, current: ShedLevel, signals: &OverloadSignals) -> ShedLevel {
+    let level_at = |ratio: f64| {
+        if config.shed_orders.exceeded_by(signals, ratio) {
+            ShedLevel::Orders
+        } else if config.shed_queries.exceeded_by(signals, ratio) {
+            ShedLevel::Queries
+        } else {
+            ShedLevel::Normal
+        }
+    };
+
+    let raised = level_at(1.0);
+    if raised >= current {
+        return raised;
+    }
+    level_at(config.recover_ratio).min(current)
+}
+
+// =====================================================
+// SHEDDER
+// =====================================================
+
+pub struct LoadShedder {
+    config: LoadShedderConfig,
+    level: AtomicU8,
+    changed_at_nanos: AtomicU64,
+    queue_depth: AtomicUsize,
+    clock: SharedClock,
+}
+
+impl LoadShedder {
+    pub fn new(config: LoadShedderConfig, clock: SharedClock) -> Self {
+        Self {
+            config,
+            level: AtomicU8::new(ShedLevel::Normal as u8),
+            changed_at_nanos: AtomicU64::new(0),
+            queue_depth: AtomicUsize::new(0),
+            clock,
+        }
+    }
+
+    pub fn level(&self) -> ShedLevel {
+        ShedLevel::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    /// Whether a request of this priority should be served at the current level
+    pub fn admit(&self, priority: Priority) -> bool {
+        let admitted = match priority {
+            Priority::Critical => true,
+            Priority::Order => self.level() < ShedLevel::Orders,
+            Priority::Query => self.level() < ShedLevel::Queries,
+        };
+
+        if !admitted {
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.load_shed_rejections_total.with_label_values(&[priority.as_str()]).inc();
+            }
+        }
+        admitted
+    }
+
+    /// A message was received and is waiting for its handler
+    pub fn enqueued(&self) {
+        self.queue_depth.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A queued message was handed to its handler
+    pub fn dequeued(&self) {
+        self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    /// Move to the level the signals call for, honouring the minimum hold time
+    pub fn observe(&self, signals: &OverloadSignals) -> ShedLevel {
+        let current = self.level();
+        let mut target = target_level(&self.config, current, signals);
+
+        let now = self.clock.elapsed().as_nanos() as u64;
+        let held = Duration::from_nanos(now.saturating_sub(self.changed_at_nanos.load(Ordering::Relaxed)));
+        if target < current && held < self.config.min_hold {
+            target = current;
+        }
+
+        if target != current {
+            self.level.store(target as u8, Ordering::Relaxed);
+            self.changed_at_nanos.store(now, Ordering::Relaxed);
+
+            if target > current {
+                warn!(
+                    level = target.as_str(),
+                    lag_ms = signals.event_loop_lag.as_millis() as u64,
+                    queue_depth = signals.queue_depth,
+                    db_latency_ms = signals.db_latency.as_millis() as u64,
+                    "Overload detected, shedding load"
+                );
+            } else {
+                info!(level = target.as_str(), "Load easing, shedding reduced");
+            }
+        }
+
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.load_shed_level.set(target as u8 as f64);
+            metrics.overload_signal.with_label_values(&["event_loop_lag_seconds"]).set(signals.event_loop_lag.as_secs_f64());
+            metrics.overload_signal.with_label_values(&["queue_depth"]).set(signals.queue_depth as f64);
+            metrics.overload_signal.with_label_values(&["db_latency_seconds"]).set(signals.db_latency.as_secs_f64());
+        }
+
+        target
+    }
+}
+
+/// Sample the overload signals every `sample_interval` and update the shed level.
+/// Event-loop lag is how late a timer fires; DB latency is a `SELECT 1` round trip.
+pub async fn monitor_load(shedder: Arc<LoadShedder>, pool: PgPool) {
+    let interval = shedder.config.sample_interval;
+    let db_timeout = shedder.config.shed_orders.db_latency * 2;
+
+    loop {
+        let before = shedder.clock.elapsed();
+        tokio::time::sleep(interval).await;
+        let event_loop_lag = shedder.clock.elapsed().saturating_sub(before + interval);
+
+        let started = shedder.clock.elapsed();
+        let probe = tokio::time::timeout(db_timeout, sqlx::query("SELECT 1").execute(&pool)).await;
+        let db_latency = match probe {
+            Ok(Ok(_)) => shedder.clock.elapsed().saturating_sub(started),
+            // A failing database is not an overload signal; the pool monitor handles it
+            Ok(Err(_)) => Duration::ZERO,
+            Err(_) => db_timeout,
+        };
+
+        shedder.observe(&OverloadSignals {
+            event_loop_lag,
+            queue_depth: shedder.queue_depth(),
+            db_latency,
+        });
+    }
+}
@@ -1,10 +1,20 @@
 //! Resilience Module - Circuit Breakers, Retries, Bulkheads
 //! Phase 3: Fault tolerance patterns for distributed trading systems
 
+mod breaker_store;
 mod circuit_breaker;
+mod clock_skew;
+mod concurrency_limiter;
+mod degradation;
+mod load_shedder;
 mod retry;
 
-pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerState};
+pub use breaker_store::{sync_breaker, BreakerStore, RedisBreakerStore};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+pub use clock_skew::{monitor_clock_skew, ClockSkew, ClockSkewConfig, SkewSource};
+pub use concurrency_limiter::{AdaptiveLimiter, LimiterConfig};
+pub use degradation::{probe_dependencies, reconnect, Dependencies};
+pub use load_shedder::{monitor_load, LoadShedder, LoadShedderConfig, Priority, Thresholds};
 pub use retry::{RetryConfig, with_retry_async};
 
 // Bulkhead is optional - only include if the file exists
@@ -0,0 +1,108 @@
+//! SQL Dialects
+//! PostgreSQL, CockroachDB and YugabyteDB differences the engine has to care about
+
+use sqlx::PgPool;
+use std::future::Future;
+use std::time::Duration;
+
+/// Backend speaking the Postgres wire protocol.
+///
+/// Queries stick to the common subset: upserts name their conflict columns
+/// (`ON CONFLICT (a, b)`) instead of a constraint, and interval arithmetic avoids
+/// `make_interval`. What still differs is how often transactions must be retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Postgres,
+    CockroachDb,
+    YugabyteDb,
+}
+
+impl Dialect {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "postgres" | "postgresql" => Some(Dialect::Postgres),
+            "cockroach" | "cockroachdb" => Some(Dialect::CockroachDb),
+            "yugabyte" | "yugabytedb" => Some(Dialect::YugabyteDb),
+            _ => None,
+        }
+    }
+
+    /// Use the configured dialect, or ask the server (`DATABASE_DIALECT=auto`)
+    pub async fn resolve(configured: &str, pool: &PgPool) -> anyhow::Result<Self> {
+        if let Some(dialect) = Self::parse(configured) {
+            return Ok(dialect);
+        }
+
+        let (version,): (String,) = sqlx::query_as("SELECT version()")
+            .fetch_one(pool)
+            .await?;
+        Ok(Self::from_version(&version))
+    }
+
+    fn from_version(version: &str) -> Self {
+        if version.contains("CockroachDB") {
+            Dialect::CockroachDb
+        } else if version.contains("-YB-") {
+            Dialect::YugabyteDb
+        } else {
+            Dialect::Postgres
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dialect::Postgres => "postgres",
+            Dialect::CockroachDb => "cockroachdb",
+            Dialect::YugabyteDb => "yugabytedb",
+        }
+    }
+
+    /// Distributed backends run SERIALIZABLE and abort on contention far more often
+    pub fn max_tx_attempts(&self) -> u32 {
+        match self {
+            Dialect::Postgres => 2,
+            Dialect::CockroachDb | Dialect::YugabyteDb => 5,
+        }
+    }
+
+    /// Whether the transaction failed in a way that is safe to run again from the start
+    pub fn is_retryable(&self, err: &sqlx::Error) -> bool {
+        let Some(db) = err.as_database_error() else {
+            return false;
+        };
+
+        match db.code().as_deref() {
+            // serialization_failure, deadlock_detected
+            Some("40001") | Some("40P01") => true,
+            // CockroachDB reports some restarts only in the message
+            _ if *self == Dialect::CockroachDb => db.message().contains("restart transaction"),
+            _ => false,
+        }
+    }
+
+    fn is_retryable_any(&self, err: &anyhow::Error) -> bool {
+        err.chain()
+            .filter_map(|e| e.downcast_ref::<sqlx::Error>())
+            .any(|e| self.is_retryable(e))
+    }
+}
+
+/// Run a whole transaction again when the backend aborted it for contention.
+/// `f` must start its own transaction so every attempt begins from scratch.
+pub async fn retry_transient<F, Fut, T>(dialect: Dialect, operation: &str, mut f: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Err(e) if attempt < dialect.max_tx_attempts() && dialect.is_retryable_any(&e) => {
+                tracing::warn!(operation, attempt, error = %e, "Transaction aborted, retrying");
+                tokio::time::sleep(Duration::from_millis(10 << attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
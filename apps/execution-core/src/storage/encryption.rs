@@ -0,0 +1,243 @@
+//! Column Encryption
+//! AES-256-GCM for sensitive columns, sealed on bind and opened on decode
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+use sqlx::types::Json;
+use sqlx::{Decode, Encode, PgPool, Postgres, Type};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Object key marking a sealed value inside a JSONB column
+pub const SEALED_KEY: &str = "$enc";
+
+static KEYRING: OnceLock<KeyRing> = OnceLock::new();
+
+#[derive(Debug, Error)]
+pub enum EncryptionError {
+    #[error("Invalid key spec: {0}")]
+    InvalidKey(String),
+    #[error("Unknown key id: {0}")]
+    UnknownKey(String),
+    #[error("Column encryption keys are not configured")]
+    NotConfigured,
+    #[error("Malformed sealed value")]
+    Malformed,
+    #[error("Decryption failed (wrong key or tampered value)")]
+    DecryptFailed,
+}
+
+// =====================================================
+// KEY RING
+// =====================================================
+
+/// Data keys by id. The first configured key seals new values; every key can open,
+/// so a rotated-out key keeps working until its rows are re-sealed.
+pub struct KeyRing {
+    active: Option<String>,
+    keys: HashMap<String, LessSafeKey>,
+    rng: SystemRandom,
+}
+
+impl KeyRing {
+    /// Parse `id:base64key[,id:base64key...]` (32-byte keys, active key first).
+    /// An empty spec disables encryption: values are stored as plain JSON.
+    pub fn from_spec(spec: &str) -> Result<Self, EncryptionError> {
+        let mut active = None;
+        let mut keys = HashMap::new();
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, encoded) = entry
+                .split_once(':')
+                .ok_or_else(|| EncryptionError::InvalidKey(format!("{} is not id:key", mask(entry))))?;
+
+            if id.is_empty() || keys.contains_key(id) {
+                return Err(EncryptionError::InvalidKey(format!("missing or duplicate key id '{}'", id)));
+            }
+            if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                return Err(EncryptionError::InvalidKey(format!("key id '{}' must be alphanumeric", id)));
+            }
+
+            let bytes = BASE64
+                .decode(encoded)
+                .map_err(|_| EncryptionError::InvalidKey(format!("key '{}' is not base64", id)))?;
+            let unbound = UnboundKey::new(&AES_256_GCM, &bytes)
+                .map_err(|_| EncryptionError::InvalidKey(format!("key '{}' must be 32 bytes", id)))?;
+
+            active.get_or_insert_with(|| id.to_string());
+            keys.insert(id.to_string(), LessSafeKey::new(unbound));
+        }
+
+        Ok(Self { active, keys, rng: SystemRandom::new() })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.active.is_some()
+    }
+
+    pub fn active_key_id(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Seal `value` under the active key, or return it unchanged when disabled
+    pub fn seal(&self, value: &serde_json::Value) -> serde_json::Value {
+        let Some(id) = &self.active else {
+            return value.clone();
+        };
+
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng.fill(&mut nonce).expect("system RNG unavailable");
+
+        let mut in_out = serde_json::to_vec(value).expect("JSON value always serializes");
+        self.keys[id]
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(id.as_bytes()), &mut in_out)
+            .expect("AES-GCM input within size limit");
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&in_out);
+        serde_json::json!({ SEALED_KEY: format!("{}:{}", id, BASE64.encode(sealed)) })
+    }
+
+    /// Open a sealed value; anything not in sealed form is returned as is
+    pub fn open(&self, stored: serde_json::Value) -> Result<serde_json::Value, EncryptionError> {
+        let Some(envelope) = sealed_envelope(&stored) else {
+            return Ok(stored);
+        };
+
+        if !self.enabled() {
+            return Err(EncryptionError::NotConfigured);
+        }
+
+        let (id, encoded) = envelope.split_once(':').ok_or(EncryptionError::Malformed)?;
+        let key = self.keys.get(id).ok_or_else(|| EncryptionError::UnknownKey(id.to_string()))?;
+
+        let mut sealed = BASE64.decode(encoded).map_err(|_| EncryptionError::Malformed)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(EncryptionError::Malformed);
+        }
+
+        let mut in_out = sealed.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&sealed).map_err(|_| EncryptionError::Malformed)?;
+        let plaintext = key
+            .open_in_place(nonce, Aad::from(id.as_bytes()), &mut in_out)
+            .map_err(|_| EncryptionError::DecryptFailed)?;
+
+        serde_json::from_slice(plaintext).map_err(|_| EncryptionError::Malformed)
+    }
+}
+
+fn sealed_envelope(stored: &serde_json::Value) -> Option<&str> {
+    let object = stored.as_object()?;
+    if object.len() != 1 {
+        return None;
+    }
+    object.get(SEALED_KEY)?.as_str()
+}
+
+fn mask(entry: &str) -> String {
+    entry.chars().take(4).chain("…".chars()).collect()
+}
+
+/// Install the process-wide key ring; call once at startup before touching the database
+pub fn init_keyring(spec: &str) -> Result<(), EncryptionError> {
+    let ring = KeyRing::from_spec(spec)?;
+    if let Some(id) = ring.active_key_id() {
+        tracing::info!(active_key = id, keys = ring.keys.len(), "Column encryption enabled");
+    }
+    let _ = KEYRING.set(ring);
+    Ok(())
+}
+
+pub fn keyring() -> &'static KeyRing {
+    KEYRING.get_or_init(|| KeyRing::from_spec("").expect("empty spec is valid"))
+}
+
+// =====================================================
+// ENCRYPTED JSON COLUMN
+// =====================================================
+
+/// JSONB value that is sealed with the key ring when bound and opened when decoded.
+/// Serializes as the plain inner value, so clients never see the sealed form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EncryptedJson(pub serde_json::Value);
+
+impl EncryptedJson {
+    pub fn into_inner(self) -> serde_json::Value {
+        self.0
+    }
+}
+
+impl Type<Postgres> for EncryptedJson {
+    fn type_info() -> PgTypeInfo {
+        <Json<serde_json::Value> as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Json<serde_json::Value> as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for EncryptedJson {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        Json(keyring().seal(&self.0)).encode_by_ref(buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for EncryptedJson {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let Json(stored) = <Json<serde_json::Value> as Decode<Postgres>>::decode(value)?;
+        Ok(Self(keyring().open(stored)?))
+    }
+}
+
+// =====================================================
+// ROTATION
+// =====================================================
+
+/// Re-seal order metadata written in plain form or under a retired key, in batches.
+/// Returns the number of rows rewritten.
+pub async fn reseal_order_metadata(pool: &PgPool, batch_size: i64) -> anyhow::Result<u64> {
+    let ring = keyring();
+    let Some(active) = ring.active_key_id() else {
+        return Ok(0);
+    };
+
+    let mut resealed = 0;
+    loop {
+        // Sealed values start with their key id; plain values have no `$enc` at all
+        let rows: Vec<(Uuid, serde_json::Value)> = sqlx::query_as(
+            r#"SELECT id, metadata FROM orders
+               WHERE metadata IS NOT NULL
+                 AND split_part(metadata->>'$enc', ':', 1) IS DISTINCT FROM $1
+               ORDER BY id
+               LIMIT $2"#
+        )
+            .bind(active)
+            .bind(batch_size)
+            .fetch_all(pool)
+            .await?;
+
+        if rows.is_empty() {
+            return Ok(resealed);
+        }
+
+        for (id, stored) in rows {
+            let value = ring.open(stored)?;
+            sqlx::query("UPDATE orders SET metadata = $2 WHERE id = $1")
+                .bind(id)
+                .bind(EncryptedJson(value))
+                .execute(pool)
+                .await?;
+            resealed += 1;
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Read Hedging
+//! Second attempts for slow latency-critical reads, fired at the observed p95 and capped by a budget
+
+use crate::clock::SharedClock;
+use crate::observability::metrics::get_metrics;
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Recent latencies the p95 is taken over
+const WINDOW: usize = 256;
+/// No hedging until this many latencies have been seen
+const MIN_SAMPLES: usize = 32;
+/// The p95 is recomputed every this many samples
+const RECOMPUTE_EVERY: usize = 16;
+/// Hedges that can be banked during quiet periods
+const MAX_TOKENS: f64 = 10.0;
+
+#[derive(Debug, Clone)]
+pub struct HedgeConfig {
+    /// Hedges allowed per request, e.g. 0.05 for at most one hedge in twenty reads
+    pub budget: f64,
+    /// Floor on the hedge delay so fast queries are never doubled
+    pub min_delay: Duration,
+}
+
+/// Nearest-rank p95 of `samples`
+pub fn p95(samples: &[Duration]) -> Option<Duration> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let rank = (sorted.len() * 95).div_ceil(100);
+    Some(sorted[rank.saturating_sub(1)])
+}
+
+struct HedgeState {
+    samples: VecDeque<Duration>,
+    since_recompute: usize,
+    p95: Option<Duration>,
+    tokens: f64,
+}
+
+pub struct HedgePolicy {
+    config: HedgeConfig,
+    state: Mutex<HedgeState>,
+    clock: SharedClock,
+}
+
+impl HedgePolicy {
+    pub fn new(config: HedgeConfig, clock: SharedClock) -> Self {
+        Self {
+            config,
+            state: Mutex::new(HedgeState {
+                samples: VecDeque::with_capacity(WINDOW),
+                since_recompute: 0,
+                p95: None,
+                tokens: MAX_TOKENS,
+            }),
+            clock,
+        }
+    }
+
+    /// A request is starting: earn its share of the budget and return how long
+    /// to wait before hedging it, or `None` while latency is still unknown
+    pub fn begin(&self) -> Option<Duration> {
+        let mut state = self.lock();
+        state.tokens = (state.tokens + self.config.budget).min(MAX_TOKENS);
+        state.p95.map(|p95| p95.max(self.config.min_delay))
+    }
+
+    /// Spend budget on a hedge; `false` when the budget is exhausted
+    pub fn try_hedge(&self) -> bool {
+        let mut state = self.lock();
+        let allowed = state.tokens >= 1.0;
+        if allowed {
+            state.tokens -= 1.0;
+        }
+        drop(state);
+
+        record_hedge(if allowed { "fired" } else { "budget_exhausted" });
+        allowed
+    }
+
+    /// Record how long a request took to answer
+    pub fn record(&self, latency: Duration) {
+        let mut state = self.lock();
+        if state.samples.len() == WINDOW {
+            state.samples.pop_front();
+        }
+        state.samples.push_back(latency);
+        state.since_recompute += 1;
+
+        if state.samples.len() >= MIN_SAMPLES && (state.p95.is_none() || state.since_recompute >= RECOMPUTE_EVERY) {
+            state.since_recompute = 0;
+            state.p95 = p95(state.samples.make_contiguous());
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HedgeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub fn record_hedge(outcome: &str) {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.read_hedges_total.with_label_values(&[outcome]).inc();
+    }
+}
@@ -0,0 +1,20 @@
+//! Storage Layer
+//! Repository traits over the SQL backend, with dialect handling for distributed SQL
+
+pub mod dialect;
+pub mod encryption;
+pub mod hedging;
+pub mod objects;
+pub mod orders;
+pub mod pool;
+pub mod replica;
+pub mod retention;
+
+pub use dialect::{retry_transient, Dialect};
+pub use encryption::EncryptedJson;
+pub use hedging::{HedgeConfig, HedgePolicy};
+pub use objects::ObjectStorage;
+pub use orders::{OrderRepository, PgOrderRepository};
+pub use pool::{monitor_pool, pool_options, PoolLifecycle, PoolSettings};
+pub use replica::{report_pool_metrics, ReadPool};
+pub use retention::{parse_rules, run_retention, RetentionEngine};
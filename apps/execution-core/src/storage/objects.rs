@@ -0,0 +1,54 @@
+//! Object Storage
+//! Where generated files such as account exports are kept, addressed by a store URL
+
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use std::sync::Arc;
+
+/// A bucket or directory, and the prefix every key is written under
+pub struct ObjectStorage {
+    store: Arc<dyn ObjectStore>,
+    prefix: Path,
+    url: String,
+}
+
+impl ObjectStorage {
+    /// `s3://bucket/prefix` (credentials and region from the usual `AWS_*` variables),
+    /// `file:///directory` or `memory://`
+    pub fn open(url: &str) -> anyhow::Result<Self> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| anyhow::anyhow!("object store URL '{}' has no scheme", url))?;
+        let rest = rest.trim_end_matches('/');
+
+        let (store, prefix): (Arc<dyn ObjectStore>, &str) = match scheme {
+            "s3" => {
+                let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+                let store = AmazonS3Builder::from_env().with_bucket_name(bucket).build()?;
+                (Arc::new(store), prefix)
+            }
+            "file" => {
+                std::fs::create_dir_all(rest)?;
+                (Arc::new(LocalFileSystem::new_with_prefix(rest)?), "")
+            }
+            "memory" => (Arc::new(InMemory::new()), rest),
+            other => anyhow::bail!("unsupported object store scheme '{}'", other),
+        };
+
+        Ok(Self {
+            store,
+            prefix: Path::parse(prefix)?,
+            url: format!("{}://{}", scheme, rest),
+        })
+    }
+
+    /// Write `bytes` under `key`, returning the object's full URL
+    pub async fn put(&self, key: &str, bytes: Vec<u8>) -> object_store::Result<String> {
+        let path = self.prefix.parts().chain(Path::parse(key)?.parts()).collect::<Path>();
+        self.store.put(&path, bytes.into()).await?;
+        Ok(format!("{}/{}", self.url, key))
+    }
+}
@@ -0,0 +1,141 @@
+//! Order Repository
+//! Read access to orders and strategies, independent of the SQL backend
+
+use crate::engine::order_processor::{Order, Strategy};
+use crate::storage::Dialect;
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait OrderRepository: Send + Sync {
+    fn dialect(&self) -> Dialect;
+
+    async fn find(&self, order_id: Uuid) -> Result<Option<Order>, sqlx::Error>;
+
+    async fn find_by_client_id(
+        &self,
+        account_id: Uuid,
+        client_order_id: &str,
+    ) -> Result<Option<Order>, sqlx::Error>;
+
+    /// Resting single orders (not strategy legs), optionally for one symbol
+    async fn open_orders(&self, symbol: Option<&str>) -> Result<Vec<Order>, sqlx::Error>;
+
+    /// Pending strategies with their legs in leg order
+    async fn open_strategies(&self) -> Result<Vec<Strategy>, sqlx::Error>;
+
+    async fn find_strategy(
+        &self,
+        account_id: Uuid,
+        client_strategy_id: &str,
+    ) -> Result<Option<Strategy>, sqlx::Error>;
+}
+
+// =====================================================
+// POSTGRES-COMPATIBLE IMPLEMENTATION
+// =====================================================
+
+const STRATEGY_COLUMNS: &str = "id, account_id, client_strategy_id, quantity, net_price, status, created_at";
+
+/// Works unchanged on PostgreSQL, CockroachDB and YugabyteDB
+pub struct PgOrderRepository {
+    pool: PgPool,
+    dialect: Dialect,
+}
+
+impl PgOrderRepository {
+    pub fn new(pool: PgPool, dialect: Dialect) -> Self {
+        Self { pool, dialect }
+    }
+}
+
+#[async_trait]
+impl OrderRepository for PgOrderRepository {
+    fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    async fn find(&self, order_id: Uuid) -> Result<Option<Order>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM orders WHERE id = $1")
+            .bind(order_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    async fn find_by_client_id(
+        &self,
+        account_id: Uuid,
+        client_order_id: &str,
+    ) -> Result<Option<Order>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM orders WHERE account_id = $1 AND client_order_id = $2")
+            .bind(account_id)
+            .bind(client_order_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    async fn open_orders(&self, symbol: Option<&str>) -> Result<Vec<Order>, sqlx::Error> {
+        sqlx::query_as(
+            r#"SELECT * FROM orders
+               WHERE status IN ('pending', 'partially_filled') AND strategy_id IS NULL
+                 AND ($1::text IS NULL OR symbol = $1)"#
+        )
+            .bind(symbol)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn open_strategies(&self) -> Result<Vec<Strategy>, sqlx::Error> {
+        let rows: Vec<Strategy> = sqlx::query_as(&format!(
+            "SELECT {} FROM order_strategies WHERE status = 'pending'",
+            STRATEGY_COLUMNS
+        ))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let legs: Vec<Order> = sqlx::query_as(
+            r#"SELECT * FROM orders
+               WHERE strategy_id IN (SELECT id FROM order_strategies WHERE status = 'pending')
+               ORDER BY client_order_id"#
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut by_id: HashMap<Uuid, Strategy> = rows.into_iter().map(|s| (s.id, s)).collect();
+        for leg in legs {
+            if let Some(strategy) = leg.strategy_id.and_then(|id| by_id.get_mut(&id)) {
+                strategy.legs.push(leg);
+            }
+        }
+        Ok(by_id.into_values().collect())
+    }
+
+    async fn find_strategy(
+        &self,
+        account_id: Uuid,
+        client_strategy_id: &str,
+    ) -> Result<Option<Strategy>, sqlx::Error> {
+        let strategy: Option<Strategy> = sqlx::query_as(&format!(
+            "SELECT {} FROM order_strategies WHERE account_id = $1 AND client_strategy_id = $2",
+            STRATEGY_COLUMNS
+        ))
+            .bind(account_id)
+            .bind(client_strategy_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let Some(mut strategy) = strategy else {
+            return Ok(None);
+        };
+
+        strategy.legs = sqlx::query_as("SELECT * FROM orders WHERE strategy_id = $1 ORDER BY client_order_id")
+            .bind(strategy.id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Some(strategy))
+    }
+}
@@ -0,0 +1,203 @@
+//! Connection Pool Lifecycle
+//! Pool options, stale-connection recycling after failover, and a repair loop for stuck pools
+
+use crate::observability::metrics::get_metrics;
+use crate::storage::Dialect;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct PoolSettings {
+    pub min_connections: u32,
+    pub max_connections: u32,
+    pub acquire_timeout: Duration,
+    /// Connections older than this are closed and replaced
+    pub max_lifetime: Duration,
+    /// Ping each connection before handing it out
+    pub test_before_acquire: bool,
+}
+
+// =====================================================
+// LIFECYCLE STATE
+// =====================================================
+
+/// Shared between the pool's acquire hook and its monitor. Recycling marks every
+/// connection opened before now as stale; the hooks drop them when next acquired or
+/// released, so all clones of the pool pick up fresh connections without being replaced.
+pub struct PoolLifecycle {
+    name: &'static str,
+    origin: Instant,
+    recycled_at_nanos: AtomicU64,
+    consecutive_failures: AtomicU32,
+    recycles: AtomicU64,
+    last_event: Mutex<Option<PoolEvent>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolEvent {
+    pub kind: &'static str,
+    pub detail: String,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStatus {
+    pub pool: &'static str,
+    pub consecutive_failures: u32,
+    pub recycles: u64,
+    pub last_event: Option<PoolEvent>,
+}
+
+impl PoolLifecycle {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            origin: Instant::now(),
+            recycled_at_nanos: AtomicU64::new(0),
+            consecutive_failures: AtomicU32::new(0),
+            recycles: AtomicU64::new(0),
+            last_event: Mutex::new(None),
+        }
+    }
+
+    /// Retire every connection opened before this call
+    pub fn recycle(&self, kind: &'static str, detail: String) {
+        let now = self.origin.elapsed().as_nanos() as u64;
+        self.recycled_at_nanos.fetch_max(now, Ordering::AcqRel);
+        self.recycles.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!(pool = self.name, event = kind, %detail, "Recycling database connections");
+        self.record(kind, detail);
+    }
+
+    /// A connection of this age was opened before the last recycle
+    pub fn is_stale(&self, age: Duration) -> bool {
+        let recycled_at = self.recycled_at_nanos.load(Ordering::Acquire);
+        let opened_at = self.origin.elapsed().saturating_sub(age).as_nanos() as u64;
+        recycled_at > 0 && opened_at < recycled_at
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.consecutive_failures.load(Ordering::Relaxed) > 0
+    }
+
+    pub fn status(&self) -> PoolStatus {
+        PoolStatus {
+            pool: self.name,
+            consecutive_failures: self.consecutive_failures.load(Ordering::Relaxed),
+            recycles: self.recycles.load(Ordering::Relaxed),
+            last_event: self.last_event.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+
+    fn record(&self, kind: &'static str, detail: String) {
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.db_pool_events_total.with_label_values(&[self.name, kind]).inc();
+        }
+
+        *self.last_event.lock().unwrap_or_else(|e| e.into_inner()) = Some(PoolEvent {
+            kind,
+            detail,
+            at: Utc::now(),
+        });
+    }
+}
+
+/// Pool options with lifetime limits and the stale-connection hooks wired in.
+/// Stale connections are dropped both when handed out and when returned.
+pub fn pool_options(settings: &PoolSettings, lifecycle: Arc<PoolLifecycle>) -> PgPoolOptions {
+    let on_release = lifecycle.clone();
+
+    PgPoolOptions::new()
+        .min_connections(settings.min_connections)
+        .max_connections(settings.max_connections)
+        .acquire_timeout(settings.acquire_timeout)
+        .max_lifetime(settings.max_lifetime)
+        .test_before_acquire(settings.test_before_acquire)
+        .before_acquire(move |_conn, meta| {
+            let fresh = !lifecycle.is_stale(meta.age);
+            Box::pin(async move { Ok(fresh) })
+        })
+        .after_release(move |_conn, meta| {
+            let fresh = !on_release.is_stale(meta.age);
+            Box::pin(async move { Ok(fresh) })
+        })
+}
+
+// =====================================================
+// MONITOR
+// =====================================================
+
+/// Probe the pool every `interval`. Repeated acquire failures trigger a repair
+/// (all connections rebuilt); on the primary, a server restart or promotion of a
+/// different node (detected via recovery state and postmaster start time) triggers
+/// a recycle so no connection keeps talking to the old server.
+pub async fn monitor_pool(
+    pool: PgPool,
+    lifecycle: Arc<PoolLifecycle>,
+    dialect: Dialect,
+    expect_primary: bool,
+    interval: Duration,
+    repair_threshold: u32,
+) {
+    let repair_threshold = repair_threshold.max(1);
+    let mut ticker = tokio::time::interval(interval);
+    let mut server_started: Option<DateTime<Utc>> = None;
+
+    loop {
+        ticker.tick().await;
+
+        match probe(&pool, dialect).await {
+            Ok(identity) => {
+                if lifecycle.consecutive_failures.swap(0, Ordering::Relaxed) > 0 {
+                    lifecycle.record("recovered", "probe succeeded".to_string());
+                }
+
+                let Some(identity) = identity else { continue };
+
+                if expect_primary && identity.in_recovery {
+                    lifecycle.recycle("failover", "primary pool is connected to a standby".to_string());
+                } else if server_started.is_some_and(|s| s != identity.started_at) {
+                    lifecycle.recycle("failover", format!("server restarted at {}", identity.started_at));
+                }
+                server_started = Some(identity.started_at);
+            }
+            Err(e) => {
+                let failures = lifecycle.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                tracing::warn!(pool = lifecycle.name, failures, error = %e, "Database pool probe failed");
+                lifecycle.record("probe_failed", e.to_string());
+
+                if failures.is_multiple_of(repair_threshold) {
+                    lifecycle.recycle("repair", format!("{} consecutive probe failures", failures));
+                }
+            }
+        }
+    }
+}
+
+struct ServerIdentity {
+    in_recovery: bool,
+    started_at: DateTime<Utc>,
+}
+
+/// Acquire a connection and, on plain Postgres, read which server it reached
+async fn probe(pool: &PgPool, dialect: Dialect) -> Result<Option<ServerIdentity>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+
+    if dialect != Dialect::Postgres {
+        sqlx::query("SELECT 1").execute(&mut *conn).await?;
+        return Ok(None);
+    }
+
+    let (in_recovery, started_at): (bool, DateTime<Utc>) =
+        sqlx::query_as("SELECT pg_is_in_recovery(), pg_postmaster_start_time()")
+            .fetch_one(&mut *conn)
+            .await?;
+
+    Ok(Some(ServerIdentity { in_recovery, started_at }))
+}
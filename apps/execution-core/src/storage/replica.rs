@@ -0,0 +1,184 @@
+//! Read Replica Routing
+//! Query workloads try the read-only replica first and fall back to the primary
+
+use super::hedging::{record_hedge, HedgePolicy};
+use crate::observability::metrics::get_metrics;
+use crate::observability::slow_ops::slow_query;
+use crate::resilience::AdaptiveLimiter;
+
+use sqlx::PgPool;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Pools for read-only queries; without a replica every read goes to the primary.
+/// Each pool's queries run under that pool's concurrency limiter.
+#[derive(Clone)]
+pub struct ReadPool {
+    primary: PgPool,
+    primary_limiter: Arc<AdaptiveLimiter>,
+    replica: Option<(PgPool, Arc<AdaptiveLimiter>)>,
+    /// Hedging for `run_hedged`; `None` runs those reads once
+    hedge: Option<Arc<HedgePolicy>>,
+}
+
+impl ReadPool {
+    pub fn new(
+        primary: PgPool,
+        primary_limiter: Arc<AdaptiveLimiter>,
+        replica: Option<(PgPool, Arc<AdaptiveLimiter>)>,
+        hedge: Option<Arc<HedgePolicy>>,
+    ) -> Self {
+        Self { primary, primary_limiter, replica, hedge }
+    }
+
+    /// Limiter for writes to the primary, shared with primary reads
+    pub fn primary_limiter(&self) -> Arc<AdaptiveLimiter> {
+        self.primary_limiter.clone()
+    }
+
+    /// Run `query` on the replica, retrying on the primary if the replica errors.
+    /// `RowNotFound` is an answer, not a failure, so it is never retried.
+    /// `statement` labels the read in slow-operation logs.
+    pub async fn run<T, F, Fut>(&self, statement: &'static str, query: F) -> Result<T, sqlx::Error>
+    where
+        F: Fn(PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        slow_query(statement, self.route(&query)).await
+    }
+
+    async fn route<T, F, Fut>(&self, query: &F) -> Result<T, sqlx::Error>
+    where
+        F: Fn(PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        if let Some((replica, limiter)) = &self.replica {
+            match limiter.run(query(replica.clone())).await {
+                Err(sqlx::Error::RowNotFound) => {
+                    record_query("replica", "ok");
+                    return Err(sqlx::Error::RowNotFound);
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Replica query failed, falling back to primary");
+                    record_query("replica", "fallback");
+                }
+                Ok(value) => {
+                    record_query("replica", "ok");
+                    return Ok(value);
+                }
+            }
+        }
+
+        self.run_primary(query).await
+    }
+
+    /// Like `run`, for latency-critical reads: if no answer arrives within the observed
+    /// p95, a second attempt goes to the primary and the first answer wins. Hedges are
+    /// capped by the policy's budget so a slow database is not handed double the load.
+    pub async fn run_hedged<T, F, Fut>(&self, statement: &'static str, query: F) -> Result<T, sqlx::Error>
+    where
+        F: Fn(PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        slow_query(statement, self.route_hedged(&query)).await
+    }
+
+    async fn route_hedged<T, F, Fut>(&self, query: &F) -> Result<T, sqlx::Error>
+    where
+        F: Fn(PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let Some(hedge) = &self.hedge else {
+            return self.route(query).await;
+        };
+
+        let delay = hedge.begin();
+        let started = hedge.elapsed();
+        let first = self.route(query);
+        tokio::pin!(first);
+
+        let result = match delay {
+            None => first.await,
+            Some(delay) => tokio::select! {
+                result = &mut first => result,
+                _ = tokio::time::sleep(delay) => {
+                    if !hedge.try_hedge() {
+                        first.await
+                    } else {
+                        // The losing attempt is dropped, which cancels its query
+                        let second = self.run_primary(query);
+                        tokio::pin!(second);
+                        tokio::select! {
+                            result = &mut first => {
+                                if is_answer(&result) {
+                                    record_hedge("original_won");
+                                    result
+                                } else {
+                                    second.await
+                                }
+                            }
+                            result = &mut second => {
+                                if is_answer(&result) {
+                                    record_hedge("hedge_won");
+                                    result
+                                } else {
+                                    first.await
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+        };
+
+        if is_answer(&result) {
+            hedge.record(hedge.elapsed().saturating_sub(started));
+        }
+        result
+    }
+
+    async fn run_primary<T, F, Fut>(&self, query: &F) -> Result<T, sqlx::Error>
+    where
+        F: Fn(PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let result = self.primary_limiter.run(query(self.primary.clone())).await;
+        record_query("primary", if result.is_ok() { "ok" } else { "error" });
+        result
+    }
+
+    fn record_pool_metrics(&self) {
+        if let Some(ref metrics) = *get_metrics() {
+            let pools = std::iter::once(("primary", &self.primary))
+                .chain(self.replica.as_ref().map(|(p, _)| ("replica", p)));
+
+            for (name, pool) in pools {
+                let idle = pool.num_idle() as f64;
+                let active = pool.size() as f64 - idle;
+                metrics.db_pool_connections.with_label_values(&[name, "active"]).set(active);
+                metrics.db_pool_connections.with_label_values(&[name, "idle"]).set(idle);
+            }
+        }
+    }
+}
+
+fn is_answer<T>(result: &Result<T, sqlx::Error>) -> bool {
+    matches!(result, Ok(_) | Err(sqlx::Error::RowNotFound))
+}
+
+fn record_query(pool: &str, outcome: &str) {
+    if let Some(ref metrics) = *get_metrics() {
+        metrics.db_queries_total.with_label_values(&[pool, outcome]).inc();
+    }
+}
+
+/// Sample connection counts for every pool into `enthropic_db_pool_connections`
+pub async fn report_pool_metrics(reads: ReadPool, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+        reads.record_pool_metrics();
+    }
+}
@@ -0,0 +1,268 @@
+//! Data Retention
+//! Per-class age limits enforced on a schedule, purged in small batches or reported in dry-run
+
+use crate::clock::SharedClock;
+use crate::observability::metrics::get_metrics;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Classes of data with an independent retention period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataClass {
+    Ticks,
+    OrderEvents,
+    /// Order events moved out of `order_events` by compaction
+    ArchivedOrderEvents,
+    Audit,
+    /// Completed entries of the fast-ack order journal; pending ones are never purged
+    OrderJournal,
+}
+
+impl DataClass {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "ticks" => Some(Self::Ticks),
+            "order_events" => Some(Self::OrderEvents),
+            "order_events_archive" => Some(Self::ArchivedOrderEvents),
+            "audit" => Some(Self::Audit),
+            "order_journal" => Some(Self::OrderJournal),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ticks => "ticks",
+            Self::OrderEvents => "order_events",
+            Self::ArchivedOrderEvents => "order_events_archive",
+            Self::Audit => "audit",
+            Self::OrderJournal => "order_journal",
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Self::Ticks => "market_ticks",
+            Self::OrderEvents => "order_events",
+            Self::ArchivedOrderEvents => "order_events_archive",
+            Self::Audit => "audit_log",
+            Self::OrderJournal => "order_journal",
+        }
+    }
+
+    fn time_column(&self) -> &'static str {
+        match self {
+            Self::Ticks => "timestamp",
+            Self::OrderEvents | Self::ArchivedOrderEvents | Self::Audit => "created_at",
+            // Null while pending, so never past a cutoff
+            Self::OrderJournal => "completed_at",
+        }
+    }
+
+    /// Row identity used to delete one batch at a time
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Ticks => "(symbol, timestamp)",
+            Self::OrderEvents | Self::ArchivedOrderEvents | Self::Audit | Self::OrderJournal => "id",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionRule {
+    pub class: DataClass,
+    pub max_age: Duration,
+}
+
+/// Parse `class=age[,class=age...]`, e.g. `ticks=30d,order_events=2y,audit=7y`.
+/// Ages take an `h`, `d`, `w` or `y` (365 days) suffix.
+pub fn parse_rules(spec: &str) -> anyhow::Result<Vec<RetentionRule>> {
+    let mut rules: Vec<RetentionRule> = Vec::new();
+
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (class, age) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("retention rule '{}' is not class=age", entry))?;
+        let class = DataClass::parse(class.trim())
+            .ok_or_else(|| anyhow::anyhow!("unknown data class '{}'", class.trim()))?;
+        let max_age = parse_age(age.trim())
+            .ok_or_else(|| anyhow::anyhow!("invalid retention age '{}' for {}", age.trim(), class.as_str()))?;
+
+        if rules.iter().any(|r| r.class == class) {
+            anyhow::bail!("duplicate retention rule for {}", class.as_str());
+        }
+        rules.push(RetentionRule { class, max_age });
+    }
+
+    Ok(rules)
+}
+
+fn parse_age(age: &str) -> Option<Duration> {
+    let split = age.len().checked_sub(1)?;
+    let (count, unit) = age.split_at(split);
+    let count: u64 = count.parse().ok()?;
+    let unit_secs = match unit {
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        "y" => 365 * 86_400,
+        _ => return None,
+    };
+
+    (count > 0).then(|| Duration::from_secs(count * unit_secs))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionReport {
+    pub class: DataClass,
+    pub cutoff: DateTime<Utc>,
+    /// Rows deleted, or rows that would be deleted in dry-run
+    pub rows: u64,
+    pub dry_run: bool,
+}
+
+// =====================================================
+// ENGINE
+// =====================================================
+
+pub struct RetentionEngine {
+    pool: PgPool,
+    rules: Vec<RetentionRule>,
+    batch_size: i64,
+    /// Pause between batches so purges never hold locks for long stretches
+    batch_pause: Duration,
+    dry_run: bool,
+    clock: SharedClock,
+}
+
+impl RetentionEngine {
+    pub fn new(
+        pool: PgPool,
+        rules: Vec<RetentionRule>,
+        batch_size: i64,
+        batch_pause: Duration,
+        dry_run: bool,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            pool,
+            rules,
+            batch_size: batch_size.max(1),
+            batch_pause,
+            dry_run,
+            clock,
+        }
+    }
+
+    /// Apply every rule once. A failing class is logged and skipped so the others still run.
+    pub async fn enforce(&self) -> Vec<RetentionReport> {
+        let mut reports = Vec::with_capacity(self.rules.len());
+
+        for rule in &self.rules {
+            let cutoff = self.clock.now()
+                - chrono::Duration::from_std(rule.max_age).unwrap_or(chrono::Duration::MAX);
+
+            let result = if self.dry_run {
+                self.count_expired(rule.class, cutoff).await
+            } else {
+                self.purge(rule.class, cutoff).await
+            };
+
+            match result {
+                Ok(rows) => reports.push(RetentionReport {
+                    class: rule.class,
+                    cutoff,
+                    rows,
+                    dry_run: self.dry_run,
+                }),
+                Err(e) => tracing::error!(class = rule.class.as_str(), error = %e, "Retention enforcement failed"),
+            }
+        }
+
+        reports
+    }
+
+    async fn count_expired(&self, class: DataClass, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let (count,): (i64,) = sqlx::query_as(&format!(
+            "SELECT COUNT(*) FROM {} WHERE {} < $1",
+            class.table(),
+            class.time_column()
+        ))
+            .bind(cutoff)
+            .fetch_one(&self.pool)
+            .await?;
+
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.retention_pending_rows.with_label_values(&[class.as_str()]).set(count as f64);
+        }
+
+        Ok(count as u64)
+    }
+
+    /// Delete expired rows `batch_size` at a time, each batch its own statement
+    async fn purge(&self, class: DataClass, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let sql = format!(
+            "DELETE FROM {table} WHERE {key} IN (SELECT {key} FROM {table} WHERE {column} < $1 LIMIT $2)",
+            table = class.table(),
+            key = class.key(),
+            column = class.time_column(),
+        );
+
+        let mut purged = 0;
+        loop {
+            let deleted = sqlx::query(&sql)
+                .bind(cutoff)
+                .bind(self.batch_size)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+
+            purged += deleted;
+            if let Some(ref metrics) = *get_metrics() {
+                metrics.retention_purged_rows_total.with_label_values(&[class.as_str()]).inc_by(deleted as f64);
+            }
+
+            if deleted < self.batch_size as u64 {
+                break;
+            }
+            tokio::time::sleep(self.batch_pause).await;
+        }
+
+        if let Some(ref metrics) = *get_metrics() {
+            metrics.retention_pending_rows.with_label_values(&[class.as_str()]).set(0.0);
+        }
+
+        Ok(purged)
+    }
+}
+
+/// Enforce retention every `interval`
+pub async fn run_retention(engine: RetentionEngine, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        for report in engine.enforce().await {
+            if report.dry_run {
+                tracing::info!(
+                    class = report.class.as_str(),
+                    cutoff = %report.cutoff,
+                    rows = report.rows,
+                    "Retention dry-run: rows past retention"
+                );
+            } else if report.rows > 0 {
+                tracing::info!(
+                    class = report.class.as_str(),
+                    cutoff = %report.cutoff,
+                    rows = report.rows,
+                    "Retention purge completed"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Unit Tests for Account Order Defaults
+//! Standalone tests for filling omitted order fields from an account's defaults
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+
+#[cfg(test)]
+mod account_settings_tests {
+    use super::*;
+
+    const TIME_IN_FORCE: &[&str] = &["gtc", "ioc", "fok", "day", "gtd"];
+    const MAX_SLIPPAGE_BPS: Decimal = dec!(1000);
+
+    #[derive(Debug, Clone, Default)]
+    struct Defaults {
+        time_in_force: Option<String>,
+        order_type: Option<String>,
+        max_slippage_bps: Option<Decimal>,
+        strategy_tag: Option<String>,
+    }
+
+    #[derive(Debug, Clone)]
+    struct Request {
+        side: String,
+        order_type: Option<String>,
+        price: Option<Decimal>,
+        time_in_force: Option<String>,
+        max_slippage_bps: Option<Decimal>,
+        metadata: Option<Value>,
+    }
+
+    fn request(side: &str) -> Request {
+        Request {
+            side: side.into(),
+            order_type: None,
+            price: None,
+            time_in_force: None,
+            max_slippage_bps: None,
+            metadata: None,
+        }
+    }
+
+    /// Mirror of `apply_defaults`
+    fn apply(req: &mut Request, defaults: &Defaults, reference: Option<Decimal>) -> Result<(), &'static str> {
+        let order_type = req
+            .order_type
+            .take()
+            .or_else(|| defaults.order_type.clone())
+            .unwrap_or_else(|| if req.price.is_some() { "limit" } else { "market" }.to_string());
+        req.order_type = Some(order_type.to_lowercase());
+
+        let tif = req.time_in_force.take().or_else(|| defaults.time_in_force.clone()).map(|t| t.to_lowercase());
+        if tif.as_ref().is_some_and(|t| !TIME_IN_FORCE.contains(&t.as_str())) {
+            return Err("INVALID_TIME_IN_FORCE");
+        }
+        req.time_in_force = tif;
+
+        let slippage = req.max_slippage_bps.or(defaults.max_slippage_bps);
+        if slippage.is_some_and(|bps| bps < Decimal::ZERO || bps > MAX_SLIPPAGE_BPS) {
+            return Err("INVALID_SLIPPAGE");
+        }
+        if req.order_type.as_deref() == Some("market") && req.price.is_none() {
+            if let (Some(bps), Some(reference)) = (slippage, reference) {
+                let allowance = reference * bps / Decimal::from(10_000);
+                let limit = if req.side == "buy" { reference + allowance } else { reference - allowance };
+                req.price = Some(limit.round_dp(8));
+            }
+        }
+
+        if let Some(tag) = &defaults.strategy_tag {
+            match &mut req.metadata {
+                None => req.metadata = Some(json!({ "strategy_tag": tag })),
+                Some(Value::Object(fields)) => {
+                    fields.entry("strategy_tag").or_insert_with(|| tag.clone().into());
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_order_type_follows_price_without_default() {
+        let mut market = request("buy");
+        apply(&mut market, &Defaults::default(), None).unwrap();
+        assert_eq!(market.order_type.as_deref(), Some("market"));
+
+        let mut limit = Request { price: Some(dec!(100)), ..request("buy") };
+        apply(&mut limit, &Defaults::default(), None).unwrap();
+        assert_eq!(limit.order_type.as_deref(), Some("limit"));
+    }
+
+    #[test]
+    fn test_defaults_fill_only_omitted_fields() {
+        let defaults = Defaults {
+            time_in_force: Some("ioc".into()),
+            order_type: Some("limit".into()),
+            ..Defaults::default()
+        };
+
+        let mut omitted = Request { price: Some(dec!(100)), ..request("buy") };
+        apply(&mut omitted, &defaults, None).unwrap();
+        assert_eq!(omitted.time_in_force.as_deref(), Some("ioc"));
+        assert_eq!(omitted.order_type.as_deref(), Some("limit"));
+
+        let mut explicit = Request {
+            order_type: Some("MARKET".into()),
+            time_in_force: Some("DAY".into()),
+            ..request("buy")
+        };
+        apply(&mut explicit, &defaults, None).unwrap();
+        assert_eq!(explicit.time_in_force.as_deref(), Some("day"));
+        assert_eq!(explicit.order_type.as_deref(), Some("market"));
+    }
+
+    #[test]
+    fn test_unknown_time_in_force_is_rejected() {
+        let mut req = Request { time_in_force: Some("GTX".into()), ..request("buy") };
+        assert_eq!(apply(&mut req, &Defaults::default(), None), Err("INVALID_TIME_IN_FORCE"));
+    }
+
+    #[test]
+    fn test_slippage_sets_protective_limit_on_market_orders() {
+        let defaults = Defaults { max_slippage_bps: Some(dec!(50)), ..Defaults::default() };
+
+        let mut buy = request("buy");
+        apply(&mut buy, &defaults, Some(dec!(200))).unwrap();
+        assert_eq!(buy.price, Some(dec!(201)));
+
+        let mut sell = request("sell");
+        apply(&mut sell, &defaults, Some(dec!(200))).unwrap();
+        assert_eq!(sell.price, Some(dec!(199)));
+        assert_eq!(sell.order_type.as_deref(), Some("market"));
+    }
+
+    #[test]
+    fn test_order_slippage_overrides_default() {
+        let defaults = Defaults { max_slippage_bps: Some(dec!(50)), ..Defaults::default() };
+        let mut req = Request { max_slippage_bps: Some(dec!(10)), ..request("buy") };
+        apply(&mut req, &defaults, Some(dec!(100))).unwrap();
+        assert_eq!(req.price, Some(dec!(100.1)));
+    }
+
+    #[test]
+    fn test_slippage_needs_a_reference_price_and_leaves_limits_alone() {
+        let defaults = Defaults { max_slippage_bps: Some(dec!(50)), ..Defaults::default() };
+
+        let mut unpriced = request("buy");
+        apply(&mut unpriced, &defaults, None).unwrap();
+        assert_eq!(unpriced.price, None);
+
+        let mut limit = Request { price: Some(dec!(95)), ..request("buy") };
+        apply(&mut limit, &defaults, Some(dec!(100))).unwrap();
+        assert_eq!(limit.price, Some(dec!(95)));
+    }
+
+    #[test]
+    fn test_slippage_out_of_range_is_rejected() {
+        for bps in [dec!(-1), dec!(1000.01)] {
+            let mut req = Request { max_slippage_bps: Some(bps), ..request("buy") };
+            assert_eq!(apply(&mut req, &Defaults::default(), Some(dec!(100))), Err("INVALID_SLIPPAGE"));
+        }
+    }
+
+    #[test]
+    fn test_strategy_tag_is_added_unless_set() {
+        let defaults = Defaults { strategy_tag: Some("momentum".into()), ..Defaults::default() };
+
+        let mut bare = request("buy");
+        apply(&mut bare, &defaults, None).unwrap();
+        assert_eq!(bare.metadata, Some(json!({ "strategy_tag": "momentum" })));
+
+        let mut tagged = Request { metadata: Some(json!({ "strategy_tag": "carry", "desk": "a" })), ..request("buy") };
+        apply(&mut tagged, &defaults, None).unwrap();
+        assert_eq!(tagged.metadata, Some(json!({ "strategy_tag": "carry", "desk": "a" })));
+
+        let mut other = Request { metadata: Some(json!({ "desk": "a" })), ..request("buy") };
+        apply(&mut other, &defaults, None).unwrap();
+        assert_eq!(other.metadata, Some(json!({ "strategy_tag": "momentum", "desk": "a" })));
+    }
+}
@@ -0,0 +1,130 @@
+//! Unit Tests for Order Ack Modes
+//! Standalone tests for choosing the ack mode of an order submit, what a journaled request
+//! comes to and which journal entries are replayed
+
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod ack_mode_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum AckMode {
+        Sync,
+        Fast,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Outcome {
+        Pending,
+        Committed,
+        Rejected,
+        Failed,
+    }
+
+    /// Mirror of `ack_mode`: no header means sync, an unknown one is refused
+    fn ack_mode(header: Option<&str>) -> Option<AckMode> {
+        match header.map(str::trim) {
+            None => Some(AckMode::Sync),
+            Some("sync") => Some(AckMode::Sync),
+            Some("fast") => Some(AckMode::Fast),
+            Some(_) => None,
+        }
+    }
+
+    /// Mirror of the outcome `process_journaled` records
+    fn outcome(success: bool, server_error: bool) -> Outcome {
+        match (success, server_error) {
+            (true, _) => Outcome::Committed,
+            (false, false) => Outcome::Rejected,
+            (false, true) => Outcome::Failed,
+        }
+    }
+
+    /// Stand-in for `order_journal`: entries by (account, client order id), in append order
+    #[derive(Default)]
+    struct Journal {
+        entries: Vec<(u32, &'static str, &'static str, Outcome)>,
+        by_client_id: HashMap<(u32, &'static str), usize>,
+    }
+
+    impl Journal {
+        /// Mirror of `OrderJournal::append`: ON CONFLICT DO NOTHING, returning the first entry
+        fn append(&mut self, account: u32, client_order_id: &'static str, symbol: &'static str) -> (usize, bool) {
+            if let Some(&id) = self.by_client_id.get(&(account, client_order_id)) {
+                return (id, false);
+            }
+            self.entries.push((account, client_order_id, symbol, Outcome::Pending));
+            let id = self.entries.len() - 1;
+            self.by_client_id.insert((account, client_order_id), id);
+            (id, true)
+        }
+
+        /// Mirror of `OrderJournal::complete`: only pending entries
+        fn complete(&mut self, id: usize, outcome: Outcome) {
+            if self.entries[id].3 == Outcome::Pending {
+                self.entries[id].3 = outcome;
+            }
+        }
+
+        /// Mirror of `OrderJournal::pending`
+        fn pending(&self, symbols: Option<&[&str]>) -> Vec<usize> {
+            (0..self.entries.len())
+                .filter(|&id| self.entries[id].3 == Outcome::Pending)
+                .filter(|&id| symbols.is_none_or(|symbols| symbols.contains(&self.entries[id].2)))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_ack_mode_header() {
+        assert_eq!(ack_mode(None), Some(AckMode::Sync));
+        assert_eq!(ack_mode(Some("sync")), Some(AckMode::Sync));
+        assert_eq!(ack_mode(Some(" fast ")), Some(AckMode::Fast));
+        assert_eq!(ack_mode(Some("FAST")), None);
+        assert_eq!(ack_mode(Some("")), None);
+    }
+
+    #[test]
+    fn test_outcome_of_processing() {
+        assert_eq!(outcome(true, false), Outcome::Committed);
+        // A rejection and a refused request are both the client's to fix
+        assert_eq!(outcome(false, false), Outcome::Rejected);
+        assert_eq!(outcome(false, true), Outcome::Failed);
+    }
+
+    #[test]
+    fn test_resubmitted_client_order_id_keeps_first_entry() {
+        let mut journal = Journal::default();
+        let (first, appended) = journal.append(1, "c-1", "BTC-USD");
+        assert!(appended);
+
+        assert_eq!(journal.append(1, "c-1", "BTC-USD"), (first, false));
+        // Client order ids are per account
+        assert!(journal.append(2, "c-1", "BTC-USD").1);
+    }
+
+    #[test]
+    fn test_only_pending_entries_are_replayed() {
+        let mut journal = Journal::default();
+        let (done, _) = journal.append(1, "c-1", "BTC-USD");
+        let (acked, _) = journal.append(1, "c-2", "ETH-USD");
+        journal.complete(done, Outcome::Committed);
+
+        assert_eq!(journal.pending(None), vec![acked]);
+        // A completed entry keeps its first outcome
+        journal.complete(done, Outcome::Failed);
+        assert_eq!(journal.entries[done].3, Outcome::Committed);
+    }
+
+    #[test]
+    fn test_sharded_replay_takes_own_symbols() {
+        let mut journal = Journal::default();
+        let (btc, _) = journal.append(1, "c-1", "BTC-USD");
+        let (eth, _) = journal.append(1, "c-2", "ETH-USD");
+
+        assert_eq!(journal.pending(Some(&["BTC-USD"])), vec![btc]);
+        assert_eq!(journal.pending(Some(&["ETH-USD", "SOL-USD"])), vec![eth]);
+        assert_eq!(journal.pending(None), vec![btc, eth]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Unit Tests for Admin Commands
+//! Standalone tests for command-line parsing and the scope of a position rebuild
+
+use uuid::Uuid;
+
+#[cfg(test)]
+mod admin_tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq)]
+    enum AdminCommand {
+        RebuildPositions { account_id: Option<Uuid> },
+    }
+
+    /// Mirror of `AdminCommand::parse`
+    fn parse(args: &[&str]) -> Result<Option<AdminCommand>, String> {
+        let Some((command, rest)) = args.split_first() else {
+            return Ok(None);
+        };
+
+        match *command {
+            "rebuild-positions" => {
+                let account_id = match rest {
+                    [] => None,
+                    ["--account", account] => Some(account.parse().map_err(|_| "invalid account id".to_string())?),
+                    _ => return Err("unexpected arguments".into()),
+                };
+                Ok(Some(AdminCommand::RebuildPositions { account_id }))
+            }
+            _ => Err("unknown command".into()),
+        }
+    }
+
+    /// Mirror of the cache eviction after `rebuild_all`
+    fn evict(cache: &mut HashMap<(Uuid, String), i64>, account_id: Option<Uuid>) {
+        cache.retain(|(cached_account, _), _| account_id.is_some_and(|id| id != *cached_account));
+    }
+
+    #[test]
+    fn test_no_arguments_serves_the_engine() {
+        assert_eq!(parse(&[]), Ok(None));
+    }
+
+    #[test]
+    fn test_rebuild_every_account() {
+        assert_eq!(
+            parse(&["rebuild-positions"]),
+            Ok(Some(AdminCommand::RebuildPositions { account_id: None }))
+        );
+    }
+
+    #[test]
+    fn test_rebuild_one_account() {
+        let account = Uuid::new_v4();
+        let arg = account.to_string();
+        assert_eq!(
+            parse(&["rebuild-positions", "--account", &arg]),
+            Ok(Some(AdminCommand::RebuildPositions { account_id: Some(account) }))
+        );
+    }
+
+    #[test]
+    fn test_invalid_arguments_are_rejected() {
+        assert!(parse(&["rebuild-positions", "--account", "not-a-uuid"]).is_err());
+        assert!(parse(&["rebuild-positions", "--account"]).is_err());
+        assert!(parse(&["rebuild-positions", "--dry-run"]).is_err());
+        assert!(parse(&["rebuild"]).is_err());
+    }
+
+    #[test]
+    fn test_full_rebuild_evicts_every_cached_position() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut cache = HashMap::from([((a, "AAPL".to_string()), 3), ((b, "MSFT".to_string()), 1)]);
+
+        evict(&mut cache, None);
+
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_account_rebuild_keeps_other_accounts_cached() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut cache = HashMap::from([
+            ((a, "AAPL".to_string()), 3),
+            ((a, "MSFT".to_string()), 2),
+            ((b, "MSFT".to_string()), 1),
+        ]);
+
+        evict(&mut cache, Some(a));
+
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains_key(&(b, "MSFT".to_string())));
+    }
+}
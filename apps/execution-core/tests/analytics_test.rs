@@ -0,0 +1,65 @@
+//! Unit Tests for the Analytics Export
+//! Standalone tests for which days are due and where their partitions are written
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+#[cfg(test)]
+mod analytics_tests {
+    use super::*;
+
+    const SETTLE_SECS: i64 = 3_600;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn at(d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, d, h, min, 0).unwrap()
+    }
+
+    /// Mirror of `due_days`
+    fn due_days(last: Option<NaiveDate>, now: DateTime<Utc>, backfill_days: i64, max_days: i64) -> Vec<NaiveDate> {
+        let settled = (now - Duration::seconds(SETTLE_SECS)).date_naive() - Duration::days(1);
+        let first = match last {
+            Some(last) => last + Duration::days(1),
+            None => settled - Duration::days(backfill_days - 1),
+        };
+
+        first
+            .iter_days()
+            .take_while(|day| *day <= settled)
+            .take(max_days.max(0) as usize)
+            .collect()
+    }
+
+    /// Mirror of `partition_key`
+    fn partition_key(dataset: &str, day: NaiveDate, part: i32) -> String {
+        format!("{}/date={}/part-{:05}.parquet", dataset, day.format("%Y-%m-%d"), part)
+    }
+
+    #[test]
+    fn test_first_run_backfills() {
+        assert_eq!(due_days(None, at(10, 12, 0), 3, 7), vec![day(2024, 3, 7), day(2024, 3, 8), day(2024, 3, 9)]);
+    }
+
+    #[test]
+    fn test_day_waits_to_settle() {
+        // Just after midnight the previous day is still settling
+        assert_eq!(due_days(Some(day(2024, 3, 8)), at(10, 0, 30), 7, 7), vec![]);
+        assert_eq!(due_days(Some(day(2024, 3, 8)), at(10, 1, 0), 7, 7), vec![day(2024, 3, 9)]);
+    }
+
+    #[test]
+    fn test_backlog_caught_up_in_steps() {
+        let due = due_days(Some(day(2024, 2, 1)), at(10, 12, 0), 7, 7);
+        assert_eq!(due.len(), 7);
+        assert_eq!(due[0], day(2024, 2, 2));
+        assert!(due_days(Some(day(2024, 3, 9)), at(10, 12, 0), 7, 7).is_empty());
+    }
+
+    #[test]
+    fn test_partitions_are_hive_style() {
+        assert_eq!(partition_key("trades", day(2024, 3, 9), 0), "trades/date=2024-03-09/part-00000.parquet");
+        assert_eq!(partition_key("ticks", day(2024, 3, 9), 12), "ticks/date=2024-03-09/part-00012.parquet");
+    }
+}
@@ -0,0 +1,118 @@
+//! Unit Tests for the Order Book Feed
+//! Standalone tests for how delta sequences advance and how a consumer rebuilds a book from a
+//! snapshot and the deltas after it
+
+use enthropic_domain::{Depth, DepthLevel};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::{BTreeMap, HashMap};
+
+#[cfg(test)]
+mod book_feed_tests {
+    use super::*;
+
+    /// Mirror of `BookFeed`: sequence and depth last published per symbol
+    #[derive(Default)]
+    struct Feed {
+        published: HashMap<String, (u64, Depth)>,
+    }
+
+    impl Feed {
+        /// Mirror of `BookFeed::deltas`: (symbol, sequence, changes)
+        fn deltas(&mut self, mut current: HashMap<String, Depth>) -> Vec<(String, u64, Depth)> {
+            for symbol in self.published.keys() {
+                current.entry(symbol.clone()).or_default();
+            }
+            let mut deltas = Vec::new();
+            for (symbol, depth) in current {
+                let last = self.published.entry(symbol.clone()).or_default();
+                let changes = depth.changes_since(&last.1);
+                if changes.is_empty() {
+                    continue;
+                }
+                last.0 += 1;
+                last.1 = depth;
+                deltas.push((symbol, last.0, changes));
+            }
+            deltas.sort_by(|a, b| a.0.cmp(&b.0));
+            deltas
+        }
+    }
+
+    fn level(price: Decimal, quantity: Decimal) -> DepthLevel {
+        DepthLevel { price, quantity, orders: 1 }
+    }
+
+    fn book(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> Depth {
+        Depth {
+            bids: bids.iter().map(|(p, q)| level(*p, *q)).collect(),
+            asks: asks.iter().map(|(p, q)| level(*p, *q)).collect(),
+        }
+    }
+
+    /// A consumer's copy of one side: price to quantity
+    fn apply(side: &mut BTreeMap<Decimal, Decimal>, changes: &[DepthLevel]) {
+        for level in changes {
+            if level.quantity.is_zero() {
+                side.remove(&level.price);
+            } else {
+                side.insert(level.price, level.quantity);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sequence_advances_only_on_change() {
+        let mut feed = Feed::default();
+        let depth = book(&[(dec!(100), dec!(5))], &[(dec!(101), dec!(3))]);
+
+        let first = feed.deltas(HashMap::from([("BTC-USD".to_string(), depth.clone())]));
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].1, 1);
+        // The first delta carries every level
+        assert_eq!(first[0].2, depth);
+
+        assert!(feed.deltas(HashMap::from([("BTC-USD".to_string(), depth)])).is_empty());
+    }
+
+    #[test]
+    fn test_emptied_book_publishes_removals_and_keeps_counting() {
+        let mut feed = Feed::default();
+        feed.deltas(HashMap::from([("BTC-USD".to_string(), book(&[(dec!(100), dec!(5))], &[]))]));
+
+        // No open orders left: the symbol is missing from the book
+        let emptied = feed.deltas(HashMap::new());
+        assert_eq!(emptied[0].1, 2);
+        assert_eq!(emptied[0].2.bids, vec![DepthLevel { price: dec!(100), quantity: dec!(0), orders: 0 }]);
+
+        let refilled = feed.deltas(HashMap::from([("BTC-USD".to_string(), book(&[(dec!(99), dec!(1))], &[]))]));
+        assert_eq!(refilled[0].1, 3);
+    }
+
+    #[test]
+    fn test_snapshot_plus_deltas_rebuilds_the_book() {
+        let mut feed = Feed::default();
+        let states = [
+            book(&[(dec!(100), dec!(5)), (dec!(99), dec!(2))], &[(dec!(101), dec!(3))]),
+            book(&[(dec!(100), dec!(1))], &[(dec!(101), dec!(3)), (dec!(102), dec!(8))]),
+            book(&[(dec!(100), dec!(1)), (dec!(98), dec!(4))], &[(dec!(102), dec!(6))]),
+        ];
+
+        feed.deltas(HashMap::from([("BTC-USD".to_string(), states[0].clone())]));
+        // Snapshot as of the last published delta
+        let (_, snapshot) = &feed.published["BTC-USD"];
+        let mut bids: BTreeMap<_, _> = snapshot.bids.iter().map(|l| (l.price, l.quantity)).collect();
+        let mut asks: BTreeMap<_, _> = snapshot.asks.iter().map(|l| (l.price, l.quantity)).collect();
+
+        for state in &states[1..] {
+            for (_, _, changes) in feed.deltas(HashMap::from([("BTC-USD".to_string(), state.clone())])) {
+                apply(&mut bids, &changes.bids);
+                apply(&mut asks, &changes.asks);
+            }
+        }
+
+        let last = &states[2];
+        assert_eq!(bids, last.bids.iter().map(|l| (l.price, l.quantity)).collect());
+        assert_eq!(asks, last.asks.iter().map(|l| (l.price, l.quantity)).collect());
+    }
+}
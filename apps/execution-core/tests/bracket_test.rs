@@ -0,0 +1,93 @@
+//! Unit Tests for Bracket Orders
+//! Standalone tests for where a bracket's take-profit and stop-loss may sit
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod bracket_tests {
+    use super::*;
+
+    struct Entry {
+        side: &'static str,
+        price: Option<Decimal>,
+    }
+
+    /// Mirror of `validate_bracket`
+    fn validate_bracket(entry: &Entry, take_profit: Option<Decimal>, stop_loss: Option<Decimal>) -> Result<(), &'static str> {
+        if take_profit.is_none() && stop_loss.is_none() {
+            return Err("empty");
+        }
+        if [take_profit, stop_loss].into_iter().flatten().any(|p| p <= Decimal::ZERO) {
+            return Err("not positive");
+        }
+
+        let sign = if entry.side == "buy" { Decimal::ONE } else { Decimal::NEGATIVE_ONE };
+        if let (Some(tp), Some(price)) = (take_profit, entry.price) {
+            if (tp - price) * sign <= Decimal::ZERO {
+                return Err("take profit");
+            }
+        }
+        if let (Some(sl), Some(price)) = (stop_loss, entry.price) {
+            if (price - sl) * sign <= Decimal::ZERO {
+                return Err("stop loss");
+            }
+        }
+        if let (Some(tp), Some(sl)) = (take_profit, stop_loss) {
+            if (tp - sl) * sign <= Decimal::ZERO {
+                return Err("crossed");
+            }
+        }
+        Ok(())
+    }
+
+    /// Mirror of the exit legs `insert_bracket` creates: (client id suffix, side, type)
+    fn exits(side: &str, take_profit: bool, stop_loss: bool) -> Vec<(&'static str, &'static str, &'static str)> {
+        let exit_side = if side == "buy" { "sell" } else { "buy" };
+        let mut legs = Vec::new();
+        if take_profit {
+            legs.push(("tp", exit_side, "limit"));
+        }
+        if stop_loss {
+            legs.push(("sl", exit_side, "stop"));
+        }
+        legs
+    }
+
+    #[test]
+    fn test_buy_bracket_exits_surround_entry() {
+        let entry = Entry { side: "buy", price: Some(dec!(100)) };
+        assert_eq!(validate_bracket(&entry, Some(dec!(110)), Some(dec!(95))), Ok(()));
+        assert_eq!(validate_bracket(&entry, Some(dec!(100)), Some(dec!(95))), Err("take profit"));
+        assert_eq!(validate_bracket(&entry, Some(dec!(110)), Some(dec!(105))), Err("stop loss"));
+    }
+
+    #[test]
+    fn test_sell_bracket_is_mirrored() {
+        let entry = Entry { side: "sell", price: Some(dec!(100)) };
+        assert_eq!(validate_bracket(&entry, Some(dec!(90)), Some(dec!(105))), Ok(()));
+        assert_eq!(validate_bracket(&entry, Some(dec!(110)), None), Err("take profit"));
+        assert_eq!(validate_bracket(&entry, None, Some(dec!(95))), Err("stop loss"));
+    }
+
+    #[test]
+    fn test_market_entry_only_orders_the_exits() {
+        let entry = Entry { side: "buy", price: None };
+        assert_eq!(validate_bracket(&entry, Some(dec!(110)), Some(dec!(95))), Ok(()));
+        assert_eq!(validate_bracket(&entry, Some(dec!(95)), Some(dec!(110))), Err("crossed"));
+        assert_eq!(validate_bracket(&entry, Some(dec!(1)), None), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_brackets() {
+        let entry = Entry { side: "buy", price: Some(dec!(100)) };
+        assert_eq!(validate_bracket(&entry, None, None), Err("empty"));
+        assert_eq!(validate_bracket(&entry, Some(dec!(110)), Some(dec!(0))), Err("not positive"));
+    }
+
+    #[test]
+    fn test_exits_take_the_opposite_side() {
+        assert_eq!(exits("buy", true, true), vec![("tp", "sell", "limit"), ("sl", "sell", "stop")]);
+        assert_eq!(exits("sell", false, true), vec![("sl", "buy", "stop")]);
+    }
+}
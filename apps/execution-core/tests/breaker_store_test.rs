@@ -0,0 +1,174 @@
+//! Unit Tests for Shared Circuit Breaker State
+//! Standalone tests for adopting transitions made by other replicas, and for a trip made by
+//! publishes failing on one replica reaching the others through the store
+
+#[cfg(test)]
+mod breaker_store_tests {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum State {
+        Closed,
+        Open,
+        HalfOpen,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Shared {
+        open: bool,
+        changed_at_ms: i64,
+    }
+
+    /// Mirror of the local breaker fields touched by `adopt`
+    struct Breaker {
+        state: State,
+        changed_at_ms: i64,
+        last_failure_secs: u64,
+    }
+
+    impl Breaker {
+        fn closed() -> Self {
+            Self { state: State::Closed, changed_at_ms: 0, last_failure_secs: 0 }
+        }
+
+        /// Mirror of `CircuitBreaker::adopt`
+        fn adopt(&mut self, shared: Shared, now_ms: i64, elapsed_secs: u64) {
+            if shared.changed_at_ms <= self.changed_at_ms {
+                return;
+            }
+            self.changed_at_ms = shared.changed_at_ms;
+
+            if shared.open {
+                let age_secs = (now_ms - shared.changed_at_ms).max(0) as u64 / 1_000;
+                self.last_failure_secs = elapsed_secs.saturating_sub(age_secs);
+                self.state = State::Open;
+            } else {
+                self.state = State::Closed;
+            }
+        }
+    }
+
+    /// Mirror of the Redis store: the last transition per breaker name
+    type Store = std::collections::HashMap<&'static str, Shared>;
+
+    const THRESHOLD: u32 = 3;
+    const TIMEOUT_SECS: u64 = 30;
+
+    /// Mirror of a replica's `BreakerBus` over its `CircuitBreaker`
+    struct Replica {
+        breaker: Breaker,
+        failures: u32,
+    }
+
+    impl Replica {
+        fn new() -> Self {
+            Self { breaker: Breaker::closed(), failures: 0 }
+        }
+
+        /// Mirror of `CircuitBreaker::allow_call` for the states a publish can meet
+        fn allow_call(&mut self, elapsed_secs: u64) -> bool {
+            match self.breaker.state {
+                State::Closed | State::HalfOpen => true,
+                State::Open if elapsed_secs - self.breaker.last_failure_secs >= TIMEOUT_SECS => {
+                    self.breaker.state = State::HalfOpen;
+                    true
+                }
+                State::Open => false,
+            }
+        }
+
+        /// Mirror of `BreakerBus::publish`: fail fast while open, otherwise count the outcome;
+        /// reaching the threshold opens the breaker and saves the transition (`publish`)
+        fn publish(&mut self, store: &mut Store, delivered: bool, now_ms: i64, elapsed_secs: u64) -> Result<(), &'static str> {
+            if !self.allow_call(elapsed_secs) {
+                return Err("circuit breaker nats is open");
+            }
+            if delivered {
+                self.failures = 0;
+                return Ok(());
+            }
+            self.failures += 1;
+            if self.failures >= THRESHOLD && self.breaker.state == State::Closed {
+                self.breaker.state = State::Open;
+                self.breaker.last_failure_secs = elapsed_secs;
+                self.breaker.changed_at_ms = now_ms;
+                store.insert("nats", Shared { open: true, changed_at_ms: now_ms });
+            }
+            Err("publish failed")
+        }
+
+        /// Mirror of `CircuitBreaker::sync`
+        fn sync(&mut self, store: &Store, now_ms: i64, elapsed_secs: u64) {
+            if let Some(shared) = store.get("nats") {
+                self.breaker.adopt(*shared, now_ms, elapsed_secs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_trip_on_one_replica_reaches_another() {
+        let mut store = Store::new();
+        let (mut a, mut b) = (Replica::new(), Replica::new());
+
+        for second in 0..THRESHOLD as i64 {
+            assert!(a.publish(&mut store, false, 10_000 + second * 1_000, 100 + second as u64).is_err());
+        }
+        assert_eq!(a.breaker.state, State::Open);
+        assert_eq!(store["nats"], Shared { open: true, changed_at_ms: 12_000 });
+
+        // B never saw a failure, but fails fast once it syncs
+        assert!(b.publish(&mut store, true, 12_500, 50).is_ok());
+        b.sync(&store, 13_000, 51);
+        assert_eq!(b.breaker.state, State::Open);
+        assert_eq!(b.publish(&mut store, true, 13_100, 52), Err("circuit breaker nats is open"));
+
+        // Both probe again when the timeout from A's trip runs out
+        assert_eq!(b.publish(&mut store, true, 41_000, 79), Err("circuit breaker nats is open"));
+        assert!(b.publish(&mut store, true, 42_000, 80).is_ok());
+        assert_eq!(b.breaker.state, State::HalfOpen);
+    }
+
+    #[test]
+    fn test_adopts_open_from_another_replica() {
+        let mut breaker = Breaker::closed();
+        breaker.adopt(Shared { open: true, changed_at_ms: 1_000 }, 1_500, 100);
+        assert_eq!(breaker.state, State::Open);
+    }
+
+    #[test]
+    fn test_open_time_carries_over() {
+        let mut breaker = Breaker::closed();
+        // Opened 12s ago elsewhere: the local timeout should have 12s already spent
+        breaker.adopt(Shared { open: true, changed_at_ms: 10_000 }, 22_000, 500);
+        assert_eq!(breaker.last_failure_secs, 488);
+    }
+
+    #[test]
+    fn test_restart_restores_open_state() {
+        // A fresh process has elapsed ~0; the age saturates instead of underflowing
+        let mut breaker = Breaker::closed();
+        breaker.adopt(Shared { open: true, changed_at_ms: 10_000 }, 40_000, 2);
+        assert_eq!(breaker.state, State::Open);
+        assert_eq!(breaker.last_failure_secs, 0);
+    }
+
+    #[test]
+    fn test_older_shared_state_is_ignored() {
+        let mut breaker = Breaker { state: State::Open, changed_at_ms: 5_000, last_failure_secs: 10 };
+        breaker.adopt(Shared { open: false, changed_at_ms: 4_000 }, 6_000, 20);
+        assert_eq!(breaker.state, State::Open);
+        assert_eq!(breaker.last_failure_secs, 10);
+    }
+
+    #[test]
+    fn test_own_transition_is_not_readopted() {
+        let mut breaker = Breaker { state: State::HalfOpen, changed_at_ms: 5_000, last_failure_secs: 10 };
+        breaker.adopt(Shared { open: true, changed_at_ms: 5_000 }, 40_000, 50);
+        assert_eq!(breaker.state, State::HalfOpen);
+    }
+
+    #[test]
+    fn test_recovery_elsewhere_closes_breaker() {
+        let mut breaker = Breaker { state: State::Open, changed_at_ms: 5_000, last_failure_secs: 10 };
+        breaker.adopt(Shared { open: false, changed_at_ms: 9_000 }, 9_100, 20);
+        assert_eq!(breaker.state, State::Closed);
+    }
+}
@@ -0,0 +1,86 @@
+//! Unit Tests for the In-Process Message Bus
+//! Standalone tests for NATS subject wildcard matching used by the dev profile, and for how
+//! the failover bus picks and routes through NATS regions
+
+#[cfg(test)]
+mod bus_tests {
+    fn subject_matches(pattern: &str, subject: &str) -> bool {
+        let mut pattern_tokens = pattern.split('.');
+        let mut subject_tokens = subject.split('.');
+
+        loop {
+            match (pattern_tokens.next(), subject_tokens.next()) {
+                (Some(">"), Some(_)) => return pattern_tokens.next().is_none(),
+                (Some("*"), Some(_)) => continue,
+                (Some(p), Some(s)) if p == s => continue,
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_subject() {
+        assert!(subject_matches("orders.submit", "orders.submit"));
+        assert!(!subject_matches("orders.submit", "orders.cancel"));
+        assert!(!subject_matches("orders.submit", "orders.submit.extra"));
+        assert!(!subject_matches("orders.strategy.submit", "orders.strategy"));
+    }
+
+    #[test]
+    fn test_single_token_wildcard() {
+        assert!(subject_matches("market.tick.*", "market.tick.BTC-USD"));
+        assert!(!subject_matches("market.tick.*", "market.tick"));
+        assert!(!subject_matches("market.tick.*", "market.tick.BTC.USD"));
+    }
+
+    #[test]
+    fn test_tail_wildcard() {
+        assert!(subject_matches("leaderboard.>", "leaderboard.daily"));
+        assert!(subject_matches("leaderboard.>", "leaderboard.daily.top"));
+        assert!(!subject_matches("leaderboard.>", "leaderboard"));
+        // `>` is only a wildcard in the last position
+        assert!(!subject_matches("leaderboard.>.top", "leaderboard.daily.top"));
+    }
+
+    #[test]
+    fn test_inbox_reply_subjects_are_private() {
+        let inbox = "_INBOX.6f1c2a";
+        assert!(subject_matches(inbox, "_INBOX.6f1c2a"));
+        assert!(!subject_matches(inbox, "_INBOX.9d0e4b"));
+    }
+
+    /// Mirror of `FailoverBus::failover`: the most preferred connected region, if it is not
+    /// already the active one
+    fn failover(connected: &[bool], active: usize) -> Option<usize> {
+        let best = (0..connected.len()).find(|&i| connected[i])?;
+        (best != active).then_some(best)
+    }
+
+    /// Mirror of the routing in `FailoverBus::publish`: whether a publish goes to every region
+    fn fans_out(mirrored: bool, subject: &str) -> bool {
+        !mirrored && subject.starts_with("_INBOX.")
+    }
+
+    #[test]
+    fn test_fails_over_to_next_connected_region() {
+        assert_eq!(failover(&[true, true], 0), None);
+        assert_eq!(failover(&[false, true, true], 0), Some(1));
+        // Nothing to move to
+        assert_eq!(failover(&[false, false], 0), None);
+    }
+
+    #[test]
+    fn test_returns_to_recovered_primary() {
+        assert_eq!(failover(&[true, true], 1), Some(0));
+        assert_eq!(failover(&[false, true], 1), None);
+    }
+
+    #[test]
+    fn test_replies_reach_every_unmirrored_region() {
+        assert!(fans_out(false, "_INBOX.6f1c2a"));
+        assert!(!fans_out(false, "executions.7d0c3a52"));
+        // Gateways route the reply to the requester's region
+        assert!(!fans_out(true, "_INBOX.6f1c2a"));
+    }
+}
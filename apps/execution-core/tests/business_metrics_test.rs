@@ -0,0 +1,40 @@
+//! Unit Tests for Business Metrics
+//! Standalone tests for notional and fee computation on fills
+
+#[cfg(test)]
+mod business_metrics_tests {
+    use rust_decimal::prelude::ToPrimitive;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    /// Mirror of `fee`
+    fn fee(notional: f64, fee_rate_bps: f64) -> f64 {
+        notional * fee_rate_bps / 10_000.0
+    }
+
+    /// Mirror of the notional taken in `record_fill`
+    fn notional(quantity: Decimal, price: Decimal) -> f64 {
+        (quantity * price).abs().to_f64().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_notional() {
+        assert_eq!(notional(dec!(2), dec!(150.25)), 300.5);
+        assert_eq!(notional(dec!(0.001), dec!(42000)), 42.0);
+
+        // Signed quantities still add positive notional
+        assert_eq!(notional(dec!(-3), dec!(10)), 30.0);
+    }
+
+    #[test]
+    fn test_fee() {
+        // 10 bps on 10,000 is 10
+        assert!((fee(10_000.0, 10.0) - 10.0).abs() < 1e-9);
+
+        // 2.5 bps
+        assert!((fee(40_000.0, 2.5) - 10.0).abs() < 1e-9);
+
+        // No schedule, no revenue
+        assert_eq!(fee(10_000.0, 0.0), 0.0);
+    }
+}
@@ -0,0 +1,102 @@
+//! Unit Tests for Mass Cancel
+//! Standalone tests for which open orders `orders.cancel_all` takes and how the reply counts them
+
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+mod cancel_all_tests {
+    use super::*;
+
+    struct Order {
+        id: u32,
+        symbol: &'static str,
+        side: &'static str,
+        status: &'static str,
+        strategy_leg: bool,
+    }
+
+    fn order(id: u32, symbol: &'static str, side: &'static str, status: &'static str) -> Order {
+        Order { id, symbol, side, status, strategy_leg: false }
+    }
+
+    /// Mirror of the side check in `OrderProcessor::cancel_all`
+    fn check_side(side: Option<&str>) -> Result<(), String> {
+        if matches!(side, Some(side) if side != "buy" && side != "sell") {
+            return Err("side must be buy or sell".into());
+        }
+        Ok(())
+    }
+
+    /// Mirror of the WHERE clause in `OrderProcessor::cancel_all`
+    fn selected(order: &Order, symbol: Option<&str>, side: Option<&str>) -> bool {
+        !order.strategy_leg
+            && matches!(order.status, "waiting" | "pending" | "partially_filled")
+            && symbol.is_none_or(|s| order.symbol == s)
+            && side.is_none_or(|s| order.side == s)
+    }
+
+    /// Mirror of `CancelAllSummary::new`
+    fn summary(orders: &[&Order]) -> (usize, Vec<u32>, BTreeMap<String, usize>) {
+        let mut by_symbol = BTreeMap::new();
+        for order in orders {
+            *by_symbol.entry(order.symbol.to_string()).or_insert(0) += 1;
+        }
+        (orders.len(), orders.iter().map(|o| o.id).collect(), by_symbol)
+    }
+
+    fn book() -> Vec<Order> {
+        let mut leg = order(6, "AAPL", "buy", "pending");
+        leg.strategy_leg = true;
+        vec![
+            order(1, "AAPL", "buy", "pending"),
+            order(2, "AAPL", "sell", "partially_filled"),
+            order(3, "MSFT", "buy", "waiting"),
+            order(4, "MSFT", "buy", "filled"),
+            order(5, "TSLA", "sell", "cancelled"),
+            leg,
+        ]
+    }
+
+    fn ids(symbol: Option<&str>, side: Option<&str>) -> Vec<u32> {
+        book().iter().filter(|o| selected(o, symbol, side)).map(|o| o.id).collect()
+    }
+
+    #[test]
+    fn test_unfiltered_takes_every_open_order_but_strategy_legs() {
+        assert_eq!(ids(None, None), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_symbol_and_side_filters_combine() {
+        assert_eq!(ids(Some("AAPL"), None), vec![1, 2]);
+        assert_eq!(ids(None, Some("buy")), vec![1, 3]);
+        assert_eq!(ids(Some("AAPL"), Some("sell")), vec![2]);
+        assert!(ids(Some("TSLA"), None).is_empty());
+    }
+
+    #[test]
+    fn test_side_must_be_buy_or_sell() {
+        assert!(check_side(None).is_ok());
+        assert!(check_side(Some("sell")).is_ok());
+        assert!(check_side(Some("short")).is_err());
+    }
+
+    #[test]
+    fn test_summary_counts_per_symbol() {
+        let book = book();
+        let cancelled: Vec<&Order> = book.iter().filter(|o| selected(o, None, None)).collect();
+        let (count, ids, by_symbol) = summary(&cancelled);
+        assert_eq!(count, 3);
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(by_symbol.get("AAPL"), Some(&2));
+        assert_eq!(by_symbol.get("MSFT"), Some(&1));
+        assert_eq!(by_symbol.get("TSLA"), None);
+    }
+
+    #[test]
+    fn test_nothing_open_is_an_empty_summary() {
+        let (count, ids, by_symbol) = summary(&[]);
+        assert_eq!(count, 0);
+        assert!(ids.is_empty() && by_symbol.is_empty());
+    }
+}
@@ -0,0 +1,52 @@
+//! Unit Tests for Cancel-on-Disconnect
+//! Standalone tests for when a session's missed heartbeats cancel its orders
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+#[cfg(test)]
+mod cancel_on_disconnect_tests {
+    use super::*;
+
+    const MAX_TIMEOUT_SECS: i64 = 300;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap() + Duration::seconds(secs)
+    }
+
+    /// Mirror of `timed_out`
+    fn timed_out(last_heartbeat_at: DateTime<Utc>, timeout_secs: i32, started_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        last_heartbeat_at.max(started_at) + Duration::seconds(timeout_secs as i64) < now
+    }
+
+    /// Mirror of the timeout check in `CancelOnDisconnect::register`
+    fn check_timeout(timeout_secs: i64) -> Result<(), String> {
+        if timeout_secs <= 0 || timeout_secs > MAX_TIMEOUT_SECS {
+            return Err(format!("timeoutSecs must be between 1 and {}", MAX_TIMEOUT_SECS));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_times_out_only_past_its_timeout() {
+        let started = at(0);
+        assert!(!timed_out(at(10), 5, started, at(14)));
+        assert!(!timed_out(at(10), 5, started, at(15)));
+        assert!(timed_out(at(10), 5, started, at(16)));
+    }
+
+    #[test]
+    fn test_engine_downtime_is_forgiven() {
+        // Last heartbeat an hour before the engine came back up
+        let started = at(3_600);
+        assert!(!timed_out(at(0), 5, started, at(3_604)));
+        assert!(timed_out(at(0), 5, started, at(3_606)));
+    }
+
+    #[test]
+    fn test_timeout_must_be_in_range() {
+        assert!(check_timeout(1).is_ok());
+        assert!(check_timeout(MAX_TIMEOUT_SECS).is_ok());
+        assert!(check_timeout(0).is_err());
+        assert!(check_timeout(MAX_TIMEOUT_SECS + 1).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! Unit Tests for Clock Skew Detection
+//! Standalone tests for the per-window skew estimate and when it raises the alarm
+
+use chrono::Duration;
+
+#[cfg(test)]
+mod clock_skew_tests {
+    use super::*;
+
+    fn ms(n: i64) -> Duration {
+        Duration::milliseconds(n)
+    }
+
+    /// Mirror of the window update in `ClockSkew::observe`
+    fn estimate(offsets: &[Duration]) -> Option<Duration> {
+        let mut slot: Option<Duration> = None;
+        for offset in offsets {
+            slot = Some(slot.map_or(*offset, |min| min.min(*offset)));
+        }
+        slot
+    }
+
+    /// Mirror of `skewed`
+    fn skewed(estimates: &[Option<Duration>], alarm: Duration) -> bool {
+        estimates.iter().flatten().any(|offset| offset.abs() > alarm)
+    }
+
+    /// Mirror of `ClockSkew::timestamps_trusted`
+    fn timestamps_trusted(disable_on_alarm: bool, alarmed: bool) -> bool {
+        !(disable_on_alarm && alarmed)
+    }
+
+    #[test]
+    fn test_fastest_sample_is_the_estimate() {
+        // A client 2s behind, seen through 5-300ms of transit
+        assert_eq!(estimate(&[ms(2_300), ms(2_005), ms(2_040)]), Some(ms(2_005)));
+        // A client 2s ahead
+        assert_eq!(estimate(&[ms(-1_950), ms(-1_990)]), Some(ms(-1_990)));
+        assert_eq!(estimate(&[]), None);
+    }
+
+    #[test]
+    fn test_alarm_on_skew_either_way() {
+        let alarm = ms(1_000);
+        assert!(!skewed(&[Some(ms(999)), Some(ms(-999)), None], alarm));
+        assert!(skewed(&[Some(ms(10)), Some(ms(1_001)), None], alarm));
+        assert!(skewed(&[None, None, Some(ms(-1_500))], alarm));
+    }
+
+    #[test]
+    fn test_silent_sources_never_alarm() {
+        assert!(!skewed(&[None, None, None], ms(1_000)));
+    }
+
+    #[test]
+    fn test_timestamps_distrusted_only_when_configured() {
+        assert!(timestamps_trusted(true, false));
+        assert!(!timestamps_trusted(true, true));
+        assert!(timestamps_trusted(false, true));
+    }
+}
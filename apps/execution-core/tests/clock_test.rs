@@ -0,0 +1,74 @@
+//! Unit Tests for the Engine Clock
+//! Standalone tests for virtual time and non-decreasing wall timestamps
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+#[cfg(test)]
+mod clock_tests {
+    use super::*;
+
+    struct ManualClock {
+        start: DateTime<Utc>,
+        current: DateTime<Utc>,
+    }
+
+    impl ManualClock {
+        fn advance(&mut self, by: Duration) {
+            self.current += by;
+        }
+
+        fn set(&mut self, to: DateTime<Utc>) {
+            if to > self.current {
+                self.current = to;
+            }
+        }
+
+        fn elapsed(&self) -> Duration {
+            self.current - self.start
+        }
+    }
+
+    /// Wall time clamped to the latest value handed out
+    fn clamp(last: &AtomicI64, wall: DateTime<Utc>) -> DateTime<Utc> {
+        let nanos = wall.timestamp_nanos_opt().unwrap();
+        let previous = last.fetch_max(nanos, Ordering::AcqRel);
+        if previous > nanos {
+            DateTime::from_timestamp_nanos(previous)
+        } else {
+            wall
+        }
+    }
+
+    #[test]
+    fn test_manual_clock_moves_only_when_advanced() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 9, 30, 0).unwrap();
+        let mut clock = ManualClock { start, current: start };
+
+        assert_eq!(clock.elapsed(), Duration::zero());
+        clock.advance(Duration::seconds(90));
+        assert_eq!(clock.current, start + Duration::seconds(90));
+        assert_eq!(clock.elapsed(), Duration::seconds(90));
+    }
+
+    #[test]
+    fn test_manual_clock_ignores_jumps_backwards() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 9, 30, 0).unwrap();
+        let mut clock = ManualClock { start, current: start };
+
+        clock.set(start + Duration::hours(1));
+        clock.set(start);
+        assert_eq!(clock.current, start + Duration::hours(1));
+    }
+
+    #[test]
+    fn test_wall_time_never_goes_backwards() {
+        let last = AtomicI64::new(i64::MIN);
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 9, 30, 0).unwrap();
+
+        assert_eq!(clamp(&last, t0), t0);
+        // An NTP step back returns the last timestamp instead
+        assert_eq!(clamp(&last, t0 - Duration::milliseconds(250)), t0);
+        assert_eq!(clamp(&last, t0 + Duration::seconds(1)), t0 + Duration::seconds(1));
+    }
+}
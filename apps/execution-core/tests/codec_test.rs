@@ -0,0 +1,354 @@
+//! Unit Tests for the Gateway Order Codec
+//! Standalone tests for both naming conventions, legacy fields, schema versions and strict mode
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+#[cfg(test)]
+mod codec_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Version {
+        V1,
+        V2,
+    }
+
+    const ORDER_FIELDS: &[(&str, &[&str])] = &[
+        ("clientOrderId", &["client_order_id"]),
+        ("accountId", &["account_id"]),
+        ("symbol", &[]),
+        ("side", &[]),
+        ("orderType", &["order_type"]),
+        ("quantity", &[]),
+        ("notional", &[]),
+        ("price", &[]),
+        ("stopPrice", &["stop_price"]),
+        ("trailAmount", &["trail_amount"]),
+        ("trailPercent", &["trail_percent"]),
+        ("timeInForce", &["time_in_force"]),
+        ("expiresAt", &["expires_at"]),
+        ("maxSlippageBps", &["max_slippage_bps"]),
+        ("trigger", &[]),
+        ("metadata", &["tags"]),
+        ("ocoWith", &["oco_with"]),
+        ("bracket", &[]),
+        ("displayQuantity", &["display_quantity"]),
+        ("postOnly", &["post_only"]),
+        ("reduceOnly", &["reduce_only"]),
+    ];
+    const ENVELOPE_FIELDS: &[&str] = &["auth", "username", "submittedAt"];
+
+    #[derive(Debug, PartialEq)]
+    enum CodecError {
+        UnsupportedVersion(String),
+        NotAnObject,
+        UnknownField(String),
+        DuplicateField(String),
+        Json,
+    }
+
+    /// Mirror of `NewOrderRequest` behind the auth envelope
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Order {
+        client_order_id: Option<String>,
+        account_id: Option<String>,
+        symbol: String,
+        side: String,
+        order_type: String,
+        /// Left out when the order is given as a notional
+        #[serde(default)]
+        quantity: Decimal,
+        #[serde(default)]
+        notional: Option<Decimal>,
+        price: Option<Decimal>,
+        time_in_force: Option<String>,
+        metadata: Option<Value>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Authenticated {
+        auth: Value,
+        #[serde(flatten)]
+        data: Order,
+    }
+
+    fn parse_version(header: Option<&str>) -> Result<Version, CodecError> {
+        match header.map(str::trim) {
+            None | Some("1") => Ok(Version::V1),
+            Some("2") => Ok(Version::V2),
+            Some(other) => Err(CodecError::UnsupportedVersion(other.to_string())),
+        }
+    }
+
+    /// Mirror of `canonical`
+    fn canonical(name: &str, version: Version, strict: bool) -> Option<&'static str> {
+        if let Some(envelope) = ENVELOPE_FIELDS.iter().find(|f| **f == name) {
+            return Some(envelope);
+        }
+        let accepts_aliases = !strict || version == Version::V1;
+        ORDER_FIELDS
+            .iter()
+            .find(|(field, aliases)| *field == name || (accepts_aliases && aliases.contains(&name)))
+            .map(|(field, _)| *field)
+    }
+
+    /// Mirror of `OrderCodec::decode_payload`
+    fn decode(payload: &Value, version: Version, strict: bool) -> Result<Authenticated, CodecError> {
+        let Value::Object(fields) = payload.clone() else {
+            return Err(CodecError::NotAnObject);
+        };
+        let mut normalized = Map::new();
+        for (name, value) in fields {
+            let key = match canonical(&name, version, strict) {
+                Some(c) => c.to_string(),
+                None if strict => return Err(CodecError::UnknownField(name)),
+                None => name,
+            };
+            if normalized.insert(key.clone(), value).is_some() {
+                return Err(CodecError::DuplicateField(key));
+            }
+        }
+        serde_json::from_value(Value::Object(normalized)).map_err(|_| CodecError::Json)
+    }
+
+    fn auth() -> Value {
+        json!({ "account_id": "acc-1", "username": "trader", "role": "trader", "permissions": ["orders:create"] })
+    }
+
+    fn expected() -> Order {
+        Order {
+            client_order_id: Some("c-1".into()),
+            account_id: None,
+            symbol: "AAPL".into(),
+            side: "buy".into(),
+            order_type: "limit".into(),
+            quantity: dec!(10),
+            notional: None,
+            price: Some(dec!(150.25)),
+            time_in_force: Some("GTC".into()),
+            metadata: None,
+        }
+    }
+
+    /// Shape of `OrderSubmission` in the gateway's order handler
+    fn camel_case() -> Value {
+        json!({
+            "auth": auth(),
+            "clientOrderId": "c-1",
+            "symbol": "AAPL",
+            "side": "buy",
+            "orderType": "limit",
+            "quantity": "10",
+            "price": "150.25",
+            "timeInForce": "GTC",
+        })
+    }
+
+    fn snake_case() -> Value {
+        json!({
+            "auth": auth(),
+            "client_order_id": "c-1",
+            "symbol": "AAPL",
+            "side": "buy",
+            "order_type": "limit",
+            "quantity": "10",
+            "price": "150.25",
+            "time_in_force": "GTC",
+        })
+    }
+
+    // =====================================================
+    // NAMING CONVENTIONS
+    // =====================================================
+
+    #[test]
+    fn test_camel_case_decodes_in_every_mode() {
+        for version in [Version::V1, Version::V2] {
+            for strict in [false, true] {
+                let decoded = decode(&camel_case(), version, strict).unwrap();
+                assert_eq!(decoded.data, expected(), "{:?} strict={}", version, strict);
+                assert_eq!(decoded.auth["username"], "trader");
+            }
+        }
+    }
+
+    #[test]
+    fn test_snake_case_decodes_like_camel_case() {
+        assert_eq!(decode(&snake_case(), Version::V1, false).unwrap().data, expected());
+        assert_eq!(decode(&snake_case(), Version::V1, true).unwrap().data, expected());
+        // Lenient mode accepts every known name whatever the version
+        assert_eq!(decode(&snake_case(), Version::V2, false).unwrap().data, expected());
+    }
+
+    #[test]
+    fn test_mixed_conventions_decode() {
+        let payload = json!({
+            "auth": auth(),
+            "clientOrderId": "c-1",
+            "symbol": "AAPL",
+            "side": "buy",
+            "order_type": "limit",
+            "quantity": 10,
+            "price": 150.25,
+            "time_in_force": "GTC",
+        });
+        assert_eq!(decode(&payload, Version::V1, true).unwrap().data, expected());
+    }
+
+    #[test]
+    fn test_every_alias_maps_to_its_canonical_name() {
+        for (field, aliases) in ORDER_FIELDS {
+            assert_eq!(canonical(field, Version::V2, true), Some(*field));
+            for alias in *aliases {
+                assert_eq!(canonical(alias, Version::V1, false), Some(*field));
+                assert_eq!(canonical(alias, Version::V1, true), Some(*field));
+                assert_eq!(canonical(alias, Version::V2, false), Some(*field));
+                assert_eq!(canonical(alias, Version::V2, true), None, "{} is version 1 only", alias);
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonical_names_are_camel_case() {
+        for (field, aliases) in ORDER_FIELDS {
+            assert!(!field.contains('_'), "{}", field);
+            assert!(!aliases.contains(field));
+        }
+    }
+
+    // =====================================================
+    // LEGACY AND GATEWAY FIELDS
+    // =====================================================
+
+    #[test]
+    fn test_legacy_tags_become_metadata() {
+        let mut payload = camel_case();
+        payload["tags"] = json!({ "desk": "alpha" });
+        let decoded = decode(&payload, Version::V1, true).unwrap();
+        assert_eq!(decoded.data.metadata, Some(json!({ "desk": "alpha" })));
+    }
+
+    #[test]
+    fn test_gateway_enrichment_is_accepted_in_strict_mode() {
+        // Shape published by the gateway service: the order plus who sent it and when
+        let mut payload = camel_case();
+        payload["accountId"] = json!("acc-1");
+        payload["username"] = json!("trader");
+        payload["submittedAt"] = json!("2024-01-01T00:00:00.000Z");
+
+        let decoded = decode(&payload, Version::V2, true).unwrap();
+        assert_eq!(decoded.data.account_id.as_deref(), Some("acc-1"));
+    }
+
+    #[test]
+    fn test_snake_case_account_id_is_recognised() {
+        let mut payload = snake_case();
+        payload["account_id"] = json!("acc-1");
+        let decoded = decode(&payload, Version::V1, false).unwrap();
+        assert_eq!(decoded.data.account_id.as_deref(), Some("acc-1"));
+    }
+
+    #[test]
+    fn test_client_order_id_is_optional() {
+        let mut payload = camel_case();
+        payload.as_object_mut().unwrap().remove("clientOrderId");
+        assert_eq!(decode(&payload, Version::V2, true).unwrap().data.client_order_id, None);
+    }
+
+    #[test]
+    fn test_both_names_for_one_field_are_rejected() {
+        let mut payload = camel_case();
+        payload["client_order_id"] = json!("c-2");
+        assert_eq!(
+            decode(&payload, Version::V1, false).err(),
+            Some(CodecError::DuplicateField("clientOrderId".into()))
+        );
+
+        let mut payload = camel_case();
+        payload["metadata"] = json!({});
+        payload["tags"] = json!({});
+        assert_eq!(
+            decode(&payload, Version::V1, false).err(),
+            Some(CodecError::DuplicateField("metadata".into()))
+        );
+    }
+
+    // =====================================================
+    // STRICT MODE
+    // =====================================================
+
+    #[test]
+    fn test_lenient_mode_ignores_unknown_fields() {
+        let mut payload = camel_case();
+        payload["pegOffset"] = json!("5");
+        assert_eq!(decode(&payload, Version::V2, false).unwrap().data, expected());
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_unknown_fields() {
+        let mut payload = camel_case();
+        payload["pegOffset"] = json!("5");
+        for version in [Version::V1, Version::V2] {
+            assert_eq!(
+                decode(&payload, version, true).err(),
+                Some(CodecError::UnknownField("pegOffset".into()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_strict_version_two_rejects_version_one_names() {
+        assert_eq!(
+            decode(&snake_case(), Version::V2, true).err().map(|e| matches!(e, CodecError::UnknownField(_))),
+            Some(true)
+        );
+
+        let mut payload = camel_case();
+        payload["tags"] = json!({});
+        assert_eq!(decode(&payload, Version::V2, true).err(), Some(CodecError::UnknownField("tags".into())));
+    }
+
+    #[test]
+    fn test_missing_required_field_fails_in_every_mode() {
+        let mut payload = camel_case();
+        payload.as_object_mut().unwrap().remove("symbol");
+        for strict in [false, true] {
+            assert_eq!(decode(&payload, Version::V2, strict).err(), Some(CodecError::Json));
+        }
+    }
+
+    #[test]
+    fn test_notional_stands_in_for_quantity() {
+        let mut payload = camel_case();
+        payload.as_object_mut().unwrap().remove("quantity");
+        payload["notional"] = json!("500");
+        for strict in [false, true] {
+            let order = decode(&payload, Version::V2, strict).unwrap().data;
+            assert_eq!(order.quantity, dec!(0));
+            assert_eq!(order.notional, Some(dec!(500)));
+        }
+    }
+
+    #[test]
+    fn test_payload_must_be_an_object() {
+        assert_eq!(decode(&json!([1, 2]), Version::V1, false).err(), Some(CodecError::NotAnObject));
+        assert_eq!(decode(&json!("order"), Version::V1, true).err(), Some(CodecError::NotAnObject));
+    }
+
+    // =====================================================
+    // SCHEMA VERSION
+    // =====================================================
+
+    #[test]
+    fn test_schema_version_header() {
+        assert_eq!(parse_version(None), Ok(Version::V1));
+        assert_eq!(parse_version(Some("1")), Ok(Version::V1));
+        assert_eq!(parse_version(Some(" 2 ")), Ok(Version::V2));
+        assert_eq!(parse_version(Some("3")), Err(CodecError::UnsupportedVersion("3".into())));
+        assert_eq!(parse_version(Some("v2")), Err(CodecError::UnsupportedVersion("v2".into())));
+    }
+}
@@ -0,0 +1,151 @@
+//! Unit Tests for Concentration Limits
+//! Standalone tests for platform-wide open interest caps, flag mode and the operator override
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod concentration_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Action {
+        Rejected,
+        Flagged,
+        Overridden,
+    }
+
+    struct Limits {
+        max_share_pct: Decimal,
+        min_open_interest: Decimal,
+        caps: HashMap<String, Decimal>,
+        flag: bool,
+    }
+
+    impl Default for Limits {
+        fn default() -> Self {
+            Self { max_share_pct: dec!(0), min_open_interest: dec!(0), caps: HashMap::new(), flag: false }
+        }
+    }
+
+    /// Mirror of `parse_open_interest_caps`
+    fn parse_caps(spec: &str) -> Result<HashMap<String, Decimal>, String> {
+        let mut caps = HashMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (symbol, cap) = entry.split_once('=').ok_or("not SYMBOL=quantity")?;
+            let cap: Decimal = cap
+                .trim()
+                .parse()
+                .ok()
+                .filter(|cap: &Decimal| *cap > dec!(0))
+                .ok_or("not a positive quantity")?;
+            if caps.insert(symbol.trim().to_string(), cap).is_some() {
+                return Err("duplicate".into());
+            }
+        }
+        Ok(caps)
+    }
+
+    /// Mirror of `RiskLimits::check_concentration`, from the account's net quantity and the
+    /// symbol's open interest before the order
+    fn check(
+        limits: &Limits,
+        symbol: &str,
+        net_quantity: Decimal,
+        open_interest: Decimal,
+        buy: Decimal,
+        can_override: bool,
+    ) -> Option<(&'static str, Action)> {
+        let cap = limits.caps.get(symbol).copied();
+        if limits.max_share_pct.is_zero() && cap.is_none() {
+            return None;
+        }
+
+        let long_before = net_quantity.max(dec!(0));
+        let long_after = (net_quantity + buy).max(dec!(0));
+        if long_after <= long_before {
+            return None;
+        }
+
+        let open_interest_after = (open_interest - long_before + long_after).max(dec!(0));
+        let share = long_after / open_interest_after * dec!(100);
+
+        let share_breached = !limits.max_share_pct.is_zero()
+            && open_interest_after >= limits.min_open_interest
+            && share > limits.max_share_pct;
+        let limit = if share_breached {
+            "share"
+        } else if cap.is_some_and(|cap| open_interest_after > cap) {
+            "cap"
+        } else {
+            return None;
+        };
+
+        let action = if can_override {
+            Action::Overridden
+        } else if limits.flag {
+            Action::Flagged
+        } else {
+            Action::Rejected
+        };
+        Some((limit, action))
+    }
+
+    fn capped(spec: &str) -> Limits {
+        Limits { caps: parse_caps(spec).unwrap(), ..Limits::default() }
+    }
+
+    #[test]
+    fn test_parse_caps() {
+        let caps = parse_caps(" BTC-PERP=5000 , ETH-PERP=40000.5 ").unwrap();
+        assert_eq!(caps.get("BTC-PERP"), Some(&dec!(5000)));
+        assert_eq!(caps.get("ETH-PERP"), Some(&dec!(40000.5)));
+        assert_eq!(parse_caps(""), Ok(HashMap::new()));
+        assert!(parse_caps("BTC-PERP").is_err());
+        assert!(parse_caps("BTC-PERP=0").is_err());
+        assert!(parse_caps("BTC-PERP=lots").is_err());
+        assert!(parse_caps("BTC-PERP=1,BTC-PERP=2").is_err());
+    }
+
+    #[test]
+    fn test_symbol_cap_rejects_buys_past_it() {
+        let limits = capped("BTC-PERP=1000");
+        assert_eq!(check(&limits, "BTC-PERP", dec!(0), dec!(900), dec!(100), false), None);
+        assert_eq!(check(&limits, "BTC-PERP", dec!(0), dec!(900), dec!(101), false), Some(("cap", Action::Rejected)));
+    }
+
+    #[test]
+    fn test_cap_only_applies_to_its_symbol() {
+        let limits = capped("BTC-PERP=1000");
+        assert_eq!(check(&limits, "ETH-PERP", dec!(0), dec!(5000), dec!(5000), false), None);
+    }
+
+    #[test]
+    fn test_closing_a_short_never_breaches_the_cap() {
+        let limits = capped("BTC-PERP=1000");
+        assert_eq!(check(&limits, "BTC-PERP", dec!(-500), dec!(2000), dec!(500), false), None);
+        // Only the part of the buy that opens a long counts
+        assert_eq!(check(&limits, "BTC-PERP", dec!(-500), dec!(1000), dec!(501), false), Some(("cap", Action::Rejected)));
+    }
+
+    #[test]
+    fn test_share_limit_is_reported_before_the_cap() {
+        let limits = Limits { max_share_pct: dec!(10), ..capped("BTC-PERP=1000") };
+        assert_eq!(check(&limits, "BTC-PERP", dec!(0), dec!(1000), dec!(200), false), Some(("share", Action::Rejected)));
+    }
+
+    #[test]
+    fn test_flag_mode_accepts_breaches() {
+        let limits = Limits { flag: true, ..capped("BTC-PERP=1000") };
+        assert_eq!(check(&limits, "BTC-PERP", dec!(0), dec!(1000), dec!(1), false), Some(("cap", Action::Flagged)));
+    }
+
+    #[test]
+    fn test_override_accepts_breaches_in_either_mode() {
+        let limits = capped("BTC-PERP=1000");
+        assert_eq!(check(&limits, "BTC-PERP", dec!(0), dec!(1000), dec!(1), true), Some(("cap", Action::Overridden)));
+        let limits = Limits { flag: true, ..capped("BTC-PERP=1000") };
+        assert_eq!(check(&limits, "BTC-PERP", dec!(0), dec!(1000), dec!(1), true), Some(("cap", Action::Overridden)));
+    }
+}
@@ -0,0 +1,82 @@
+//! Unit Tests for the Adaptive Concurrency Limiter
+//! Standalone tests for the gradient limit update
+
+#[cfg(test)]
+mod concurrency_limiter_tests {
+    const TOLERANCE: f64 = 1.5;
+    const SMOOTHING: f64 = 0.2;
+    const BACKOFF: f64 = 0.9;
+    const MIN: f64 = 2.0;
+    const MAX: f64 = 20.0;
+
+    /// Mirror of `next_limit` in the concurrency limiter
+    fn next_limit(limit: f64, long_rtt: f64, rtt: f64, inflight: usize, overloaded: bool) -> f64 {
+        if overloaded {
+            return (limit * BACKOFF).clamp(MIN, MAX);
+        }
+        let gradient = if rtt > 0.0 { (TOLERANCE * long_rtt / rtt).clamp(0.5, 1.0) } else { 1.0 };
+        let mut estimate = limit * gradient + limit.sqrt();
+        if estimate > limit && (inflight as f64) * 2.0 < limit {
+            estimate = limit;
+        }
+        (limit * (1.0 - SMOOTHING) + estimate * SMOOTHING).clamp(MIN, MAX)
+    }
+
+    #[test]
+    fn test_steady_latency_under_load_grows_limit() {
+        let next = next_limit(10.0, 0.010, 0.010, 10, false);
+        assert!(next > 10.0);
+    }
+
+    #[test]
+    fn test_idle_pool_does_not_grow_limit() {
+        assert_eq!(next_limit(10.0, 0.010, 0.010, 2, false), 10.0);
+    }
+
+    #[test]
+    fn test_latency_spike_shrinks_limit() {
+        // Four times the long-run latency: gradient bottoms out at 0.5
+        let next = next_limit(10.0, 0.010, 0.040, 10, false);
+        assert!(next < 10.0);
+    }
+
+    #[test]
+    fn test_latency_within_tolerance_is_not_queueing() {
+        let next = next_limit(10.0, 0.010, 0.014, 10, false);
+        assert!(next >= 10.0);
+    }
+
+    #[test]
+    fn test_pool_timeout_backs_off() {
+        assert!((next_limit(10.0, 0.010, 0.010, 10, true) - 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_limit_stays_within_bounds() {
+        let mut limit = 10.0;
+        for _ in 0..200 {
+            limit = next_limit(limit, 0.010, 0.010, 100, false);
+        }
+        assert_eq!(limit, MAX);
+
+        for _ in 0..200 {
+            limit = next_limit(limit, 0.010, 0.010, 100, true);
+        }
+        assert_eq!(limit, MIN);
+    }
+
+    #[test]
+    fn test_limit_converges_back_after_spike() {
+        let mut limit = 16.0;
+        for _ in 0..10 {
+            limit = next_limit(limit, 0.010, 0.050, 16, false);
+        }
+        let shrunk = limit;
+        assert!(shrunk < 16.0);
+
+        for _ in 0..50 {
+            limit = next_limit(limit, 0.010, 0.010, limit as usize, false);
+        }
+        assert!(limit > shrunk);
+    }
+}
@@ -0,0 +1,73 @@
+//! Unit Tests for Corporate Actions
+//! Standalone tests for split adjustments and dividend entitlements
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod corporate_action_tests {
+    use super::*;
+
+    struct Position {
+        net_quantity: Decimal,
+        avg_price: Decimal,
+    }
+
+    fn split_position(pos: &Position, ratio: Decimal) -> Position {
+        Position {
+            net_quantity: pos.net_quantity * ratio,
+            avg_price: pos.avg_price / ratio,
+        }
+    }
+
+    fn split_total(total: Decimal, ratio: Decimal) -> Decimal {
+        (total * ratio).round_dp_with_strategy(8, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    fn split_holds(holds: &[Decimal], ratio: Decimal) -> Decimal {
+        holds
+            .iter()
+            .map(|h| (h * ratio).round_dp_with_strategy(8, RoundingStrategy::ToZero))
+            .sum()
+    }
+
+    fn entitlement(net_quantity: Decimal, dividend_per_share: Decimal) -> Option<Decimal> {
+        (net_quantity > dec!(0)).then(|| (net_quantity * dividend_per_share).round_dp(8))
+    }
+
+    #[test]
+    fn test_forward_split_preserves_cost() {
+        let pos = Position { net_quantity: dec!(10), avg_price: dec!(150) };
+        let split = split_position(&pos, dec!(2));
+
+        assert_eq!(split.net_quantity, dec!(20));
+        assert_eq!(split.avg_price, dec!(75));
+        assert_eq!(split.net_quantity * split.avg_price, pos.net_quantity * pos.avg_price);
+    }
+
+    #[test]
+    fn test_reverse_split_short_position() {
+        let pos = Position { net_quantity: dec!(-100), avg_price: dec!(2) };
+        let split = split_position(&pos, dec!(0.1));
+
+        assert_eq!(split.net_quantity, dec!(-10));
+        assert_eq!(split.avg_price, dec!(20));
+    }
+
+    #[test]
+    fn test_split_holds_never_exceed_total() {
+        let holds = [dec!(0.33333333), dec!(0.33333333), dec!(0.33333334)];
+        let total = dec!(1);
+
+        for ratio in [dec!(3), dec!(1.5), dec!(0.33333333), dec!(7)] {
+            assert!(split_holds(&holds, ratio) <= split_total(total, ratio), "ratio {}", ratio);
+        }
+    }
+
+    #[test]
+    fn test_dividend_only_for_longs() {
+        assert_eq!(entitlement(dec!(50), dec!(0.24)), Some(dec!(12.00)));
+        assert_eq!(entitlement(dec!(-50), dec!(0.24)), None);
+        assert_eq!(entitlement(dec!(0), dec!(0.24)), None);
+    }
+}
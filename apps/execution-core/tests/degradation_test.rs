@@ -0,0 +1,124 @@
+//! Unit Tests for Degradation Modes
+//! Standalone tests for mode selection, the outbound buffer and dependency probes
+
+#[cfg(test)]
+mod degradation_tests {
+    use std::collections::VecDeque;
+    use std::future::Future;
+    use std::time::{Duration, Instant};
+
+    const NO_LATENCY: u64 = u64::MAX;
+
+    /// Mirror of `Dependencies::modes`
+    fn modes(redis_enabled: bool, redis: bool, nats: bool) -> Vec<&'static str> {
+        let mut modes = Vec::new();
+        if redis_enabled && !redis {
+            modes.push("query_only");
+        }
+        if !nats {
+            modes.push("buffering");
+        }
+        modes
+    }
+
+    /// Mirror of the queueing side of `BufferedBus::publish`
+    fn buffer(pending: &mut VecDeque<&'static str>, capacity: usize, message: &'static str) -> bool {
+        let dropped = pending.len() >= capacity;
+        if dropped {
+            pending.pop_front();
+        }
+        pending.push_back(message);
+        dropped
+    }
+
+    /// Mirror of `probe`
+    async fn probe<F>(timeout: Duration, ping: F) -> Result<Duration, String>
+    where
+        F: Future<Output = Result<(), String>>,
+    {
+        let started = Instant::now();
+        match tokio::time::timeout(timeout, ping).await {
+            Ok(Ok(())) => Ok(started.elapsed()),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(format!("no answer within {:?}", timeout)),
+        }
+    }
+
+    /// Mirror of `Dependencies::record_probe`: (connected, latency in microseconds)
+    fn record_probe(result: Result<Duration, String>) -> (bool, u64) {
+        match result {
+            Ok(rtt) => (true, u64::try_from(rtt.as_micros()).unwrap_or(NO_LATENCY - 1)),
+            Err(_) => (false, NO_LATENCY),
+        }
+    }
+
+    #[test]
+    fn test_fully_operational() {
+        assert!(modes(true, true, true).is_empty());
+    }
+
+    #[test]
+    fn test_redis_down_is_query_only() {
+        assert_eq!(modes(true, false, true), vec!["query_only"]);
+    }
+
+    #[test]
+    fn test_dev_profile_without_redis_takes_orders() {
+        assert!(modes(false, false, true).is_empty());
+    }
+
+    #[test]
+    fn test_nats_down_buffers() {
+        assert_eq!(modes(true, true, false), vec!["buffering"]);
+    }
+
+    #[test]
+    fn test_both_down() {
+        assert_eq!(modes(true, false, false), vec!["query_only", "buffering"]);
+    }
+
+    #[test]
+    fn test_buffer_keeps_order() {
+        let mut pending = VecDeque::new();
+        for message in ["a", "b", "c"] {
+            assert!(!buffer(&mut pending, 10, message));
+        }
+        assert_eq!(pending.iter().copied().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_full_buffer_drops_oldest() {
+        let mut pending = VecDeque::new();
+        buffer(&mut pending, 2, "a");
+        buffer(&mut pending, 2, "b");
+        assert!(buffer(&mut pending, 2, "c"));
+        assert_eq!(pending.iter().copied().collect::<Vec<_>>(), vec!["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_probe_measures_round_trip() {
+        let rtt = probe(Duration::from_secs(1), async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(())
+        }).await.unwrap();
+        assert!(rtt >= Duration::from_millis(20));
+
+        let (connected, latency_us) = record_probe(Ok(rtt));
+        assert!(connected);
+        assert!(latency_us >= 20_000 && latency_us != NO_LATENCY);
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_disconnects() {
+        let result = probe(Duration::from_secs(1), async { Err("connection refused".to_string()) }).await;
+        assert_eq!(result, Err("connection refused".to_string()));
+        assert_eq!(record_probe(result), (false, NO_LATENCY));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_hung_dependency_times_out() {
+        let result = probe(Duration::from_millis(500), std::future::pending()).await;
+        assert!(result.unwrap_err().starts_with("no answer within"));
+        assert_eq!(record_probe(Err(String::new())), (false, NO_LATENCY));
+    }
+}
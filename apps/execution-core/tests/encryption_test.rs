@@ -0,0 +1,117 @@
+//! Unit Tests for Column Encryption
+//! Standalone tests for AES-256-GCM sealing, key rotation and tamper detection
+
+#[cfg(test)]
+mod encryption_tests {
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+    use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+    use std::collections::HashMap;
+
+    struct KeyRing {
+        active: String,
+        keys: HashMap<String, LessSafeKey>,
+    }
+
+    impl KeyRing {
+        fn new(ids: &[(&str, u8)]) -> Self {
+            let keys = ids
+                .iter()
+                .map(|(id, fill)| {
+                    let key = UnboundKey::new(&AES_256_GCM, &[*fill; 32]).unwrap();
+                    (id.to_string(), LessSafeKey::new(key))
+                })
+                .collect();
+            Self { active: ids[0].0.to_string(), keys }
+        }
+
+        fn seal(&self, value: &serde_json::Value, nonce: [u8; NONCE_LEN]) -> serde_json::Value {
+            let mut in_out = serde_json::to_vec(value).unwrap();
+            self.keys[&self.active]
+                .seal_in_place_append_tag(
+                    Nonce::assume_unique_for_key(nonce),
+                    Aad::from(self.active.as_bytes()),
+                    &mut in_out,
+                )
+                .unwrap();
+            let mut sealed = nonce.to_vec();
+            sealed.extend_from_slice(&in_out);
+            serde_json::json!({ "$enc": format!("{}:{}", self.active, BASE64.encode(sealed)) })
+        }
+
+        fn open(&self, stored: &serde_json::Value) -> Result<serde_json::Value, String> {
+            let Some(envelope) = stored.get("$enc").and_then(|v| v.as_str()) else {
+                return Ok(stored.clone());
+            };
+            let (id, encoded) = envelope.split_once(':').ok_or("malformed")?;
+            let key = self.keys.get(id).ok_or("unknown key")?;
+            let mut sealed = BASE64.decode(encoded).map_err(|_| "malformed")?;
+            let mut in_out = sealed.split_off(NONCE_LEN);
+            let nonce = Nonce::try_assume_unique_for_key(&sealed).map_err(|_| "malformed")?;
+            let plain = key
+                .open_in_place(nonce, Aad::from(id.as_bytes()), &mut in_out)
+                .map_err(|_| "decrypt failed")?;
+            serde_json::from_slice(plain).map_err(|e| e.to_string())
+        }
+    }
+
+    #[test]
+    fn test_seal_round_trip_hides_plaintext() {
+        let ring = KeyRing::new(&[("k1", 7)]);
+        let tags = serde_json::json!({ "strategy": "mm-1", "desk": "alpha" });
+
+        let sealed = ring.seal(&tags, [1; NONCE_LEN]);
+        assert!(!sealed.to_string().contains("mm-1"));
+        assert!(sealed["$enc"].as_str().unwrap().starts_with("k1:"));
+        assert_eq!(ring.open(&sealed).unwrap(), tags);
+    }
+
+    #[test]
+    fn test_plain_values_pass_through() {
+        let ring = KeyRing::new(&[("k1", 7)]);
+        let legacy = serde_json::json!({ "strategy": "mm-1" });
+        assert_eq!(ring.open(&legacy).unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_rotated_key_still_opens_old_rows() {
+        let old = KeyRing::new(&[("k1", 7)]);
+        let sealed = old.seal(&serde_json::json!({ "a": 1 }), [2; NONCE_LEN]);
+
+        let rotated = KeyRing::new(&[("k2", 9), ("k1", 7)]);
+        assert_eq!(rotated.open(&sealed).unwrap(), serde_json::json!({ "a": 1 }));
+
+        let resealed = rotated.seal(&serde_json::json!({ "a": 1 }), [3; NONCE_LEN]);
+        assert!(resealed["$enc"].as_str().unwrap().starts_with("k2:"));
+
+        // Once k1 is dropped, rows still under it can no longer be read
+        let retired = KeyRing::new(&[("k2", 9)]);
+        assert_eq!(retired.open(&sealed), Err("unknown key".to_string()));
+    }
+
+    #[test]
+    fn test_tampered_ciphertext_is_rejected() {
+        let ring = KeyRing::new(&[("k1", 7)]);
+        let sealed = ring.seal(&serde_json::json!({ "a": 1 }), [4; NONCE_LEN]);
+
+        let envelope = sealed["$enc"].as_str().unwrap();
+        let (id, encoded) = envelope.split_once(':').unwrap();
+        let mut bytes = BASE64.decode(encoded).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        let tampered = serde_json::json!({ "$enc": format!("{}:{}", id, BASE64.encode(bytes)) });
+
+        assert_eq!(ring.open(&tampered), Err("decrypt failed".to_string()));
+    }
+
+    #[test]
+    fn test_key_id_is_authenticated() {
+        // Relabelling a value with another key id fails even if both keys are loaded
+        let ring = KeyRing::new(&[("k1", 7), ("k2", 7)]);
+        let sealed = ring.seal(&serde_json::json!({ "a": 1 }), [5; NONCE_LEN]);
+        let relabelled = serde_json::json!({
+            "$enc": sealed["$enc"].as_str().unwrap().replacen("k1:", "k2:", 1)
+        });
+        assert_eq!(ring.open(&relabelled), Err("decrypt failed".to_string()));
+    }
+}
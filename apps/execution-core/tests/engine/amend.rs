@@ -0,0 +1,52 @@
+//! An amend whose client order id another order takes first answers like a resubmitted one
+
+use crate::support::engine;
+
+use execution_core::engine::order_processor::{AmendOrderRequest, OrderResult};
+use rust_decimal_macros::dec;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_amend_losing_its_client_order_id_returns_the_duplicate() {
+    let Some(engine) = engine().await else { return };
+    let auth = engine.account(dec!(1000)).await;
+    let order = engine.limit(&auth, "BTC-USD", "buy", dec!(2), dec!(100)).await;
+    let client_order_id = format!("amend-{}", Uuid::new_v4());
+
+    // A concurrent submit holds the client order id, uncommitted, while the amend checks it
+    let taken_by = Uuid::new_v4();
+    let mut concurrent = engine.pool.begin().await.unwrap();
+    sqlx::query(
+        r#"INSERT INTO orders (id, account_id, client_order_id, symbol, side, order_type, quantity, price, status)
+           VALUES ($1, $2, $3, 'BTC-USD', 'buy', 'limit', 1, 100, 'pending')"#
+    )
+        .bind(taken_by)
+        .bind(auth.account_id)
+        .bind(&client_order_id)
+        .execute(&mut *concurrent)
+        .await
+        .unwrap();
+
+    let amend = tokio::spawn({
+        let (orders, auth) = (engine.orders.clone(), auth.clone());
+        let req = AmendOrderRequest { order_id: order.id, client_order_id, price: Some(dec!(99)), quantity: None };
+        async move { orders.amend_order(&auth, req).await }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    concurrent.commit().await.unwrap();
+
+    match amend.await.unwrap() {
+        Ok(Some(OrderResult::Duplicate(existing))) => assert_eq!(existing.id, taken_by),
+        other => panic!("expected the duplicate, got {:?}", other),
+    }
+
+    // The rolled-back amend left the original open with its hold
+    let (status,): (String,) = sqlx::query_as("SELECT status FROM orders WHERE id = $1")
+        .bind(order.id)
+        .fetch_one(&engine.pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "pending");
+    assert_eq!(engine.balance(auth.account_id, "USD").await, (dec!(1000), dec!(200)));
+}
@@ -0,0 +1,53 @@
+//! Cancelling releases an order's hold once, and only for an order that was still open
+
+use crate::support::engine;
+
+use execution_core::engine::EngineError;
+use rust_decimal_macros::dec;
+
+#[tokio::test]
+async fn test_cancel_releases_the_hold() {
+    let Some(engine) = engine().await else { return };
+    let auth = engine.account(dec!(1000)).await;
+    let order = engine.limit(&auth, "BTC-USD", "buy", dec!(2), dec!(100)).await;
+    assert_eq!(engine.balance(auth.account_id, "USD").await, (dec!(1000), dec!(200)));
+
+    let cancelled = engine.orders.cancel_order(&auth, order.id).await.unwrap().unwrap();
+
+    assert_eq!(cancelled.status, "cancelled");
+    assert_eq!(engine.balance(auth.account_id, "USD").await, (dec!(1000), dec!(0)));
+}
+
+#[tokio::test]
+async fn test_second_cancel_changes_nothing() {
+    let Some(engine) = engine().await else { return };
+    let auth = engine.account(dec!(1000)).await;
+    let order = engine.limit(&auth, "BTC-USD", "buy", dec!(2), dec!(100)).await;
+    // A second open order keeps a hold a repeated release would eat into
+    engine.limit(&auth, "BTC-USD", "buy", dec!(3), dec!(100)).await;
+
+    engine.orders.cancel_order(&auth, order.id).await.unwrap();
+    let again = engine.orders.cancel_order(&auth, order.id).await;
+
+    assert!(matches!(again, Err(EngineError::Conflict(_))));
+    assert_eq!(engine.balance(auth.account_id, "USD").await, (dec!(1000), dec!(300)));
+}
+
+#[tokio::test]
+async fn test_cancel_after_the_final_fill_releases_nothing() {
+    let Some(engine) = engine().await else { return };
+    let auth = engine.account(dec!(1000)).await;
+    let order = engine.limit(&auth, "BTC-USD", "buy", dec!(2), dec!(100)).await;
+
+    // The final fill commits between the cancel's read and its update
+    sqlx::query("UPDATE orders SET status = 'filled', filled_quantity = quantity WHERE id = $1")
+        .bind(order.id)
+        .execute(&engine.pool)
+        .await
+        .unwrap();
+
+    let cancelled = engine.orders.cancel_order(&auth, order.id).await;
+
+    assert!(matches!(cancelled, Err(EngineError::Conflict(_))));
+    assert_eq!(engine.balance(auth.account_id, "USD").await, (dec!(1000), dec!(200)));
+}
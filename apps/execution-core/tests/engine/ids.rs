@@ -0,0 +1,74 @@
+//! Ids the engine writes come from its id generator, so a replay with a fixed one repeats them
+
+use crate::support::engine;
+
+use execution_core::engine::ledger::LedgerConfig;
+use execution_core::engine::order_journal::OrderJournal;
+use execution_core::engine::order_processor::NewOrderRequest;
+use execution_core::engine::Ledger;
+use execution_core::ids::IdGenerator;
+use rust_decimal_macros::dec;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Ids numbered from zero under a prefix of their own
+struct Sequence {
+    prefix: u64,
+    next: AtomicU64,
+}
+
+impl Sequence {
+    fn new() -> Arc<Self> {
+        Arc::new(Self { prefix: Uuid::new_v4().as_u64_pair().0, next: AtomicU64::new(0) })
+    }
+
+    fn nth(&self, n: u64) -> Uuid {
+        Uuid::from_u64_pair(self.prefix, n)
+    }
+}
+
+impl IdGenerator for Sequence {
+    fn next_id(&self) -> Uuid {
+        self.nth(self.next.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[tokio::test]
+async fn test_ledger_journals_take_generated_ids() {
+    let Some(engine) = engine().await else { return };
+    let auth = engine.account(dec!(0)).await;
+    let ids = Sequence::new();
+    let ledger = Ledger::new(engine.reads.clone(), LedgerConfig::default(), ids.clone());
+
+    let mut conn = engine.pool.acquire().await.unwrap();
+    ledger.deposit(&mut conn, auth.account_id, "USD", dec!(10), "deposit").await.unwrap();
+    ledger.deposit(&mut conn, auth.account_id, "USD", dec!(5), "deposit").await.unwrap();
+
+    let journals: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT journal_id FROM ledger_entries WHERE account_id = $1 AND amount > 0 ORDER BY amount DESC"
+    )
+        .bind(auth.account_id)
+        .fetch_all(&engine.pool)
+        .await
+        .unwrap();
+    assert_eq!(journals, [ids.nth(0), ids.nth(1)]);
+}
+
+#[tokio::test]
+async fn test_order_journal_entries_take_generated_ids() {
+    let Some(engine) = engine().await else { return };
+    let auth = engine.account(dec!(0)).await;
+    let ids = Sequence::new();
+    let journal = OrderJournal::new(engine.pool.clone(), ids.clone());
+
+    let req: NewOrderRequest = serde_json::from_value(serde_json::json!({
+        "clientOrderId": "journaled", "symbol": "BTC-USD", "side": "buy", "quantity": "1",
+    }))
+        .unwrap();
+    let appended = journal.append(&auth, &req, engine.clock.now()).await.unwrap();
+    let again = journal.append(&auth, &req, engine.clock.now()).await.unwrap();
+
+    assert_eq!(appended, (ids.nth(0), true));
+    assert_eq!(again, (ids.nth(0), false));
+}
@@ -0,0 +1,53 @@
+//! Spot pair parsing, hold sizing and reservation against the account's balance
+
+use crate::support::engine;
+
+use execution_core::engine::ledger::{spot_pair, LedgerConfig, LedgerError};
+use execution_core::engine::order_processor::{NewOrderRequest, OrderResult};
+use execution_core::engine::{Ledger, RejectReason};
+use execution_core::ids;
+use execution_core::observability::stages::StageTimer;
+use rust_decimal_macros::dec;
+
+#[test]
+fn test_spot_pair_parsing() {
+    assert_eq!(spot_pair("BTC/USD", "USD"), Some(("BTC".into(), "USD".into())));
+    assert_eq!(spot_pair("eth-usdt", "USD"), Some(("ETH".into(), "USDT".into())));
+    assert_eq!(spot_pair("AAPL", "USD"), Some(("AAPL".into(), "USD".into())));
+    assert_eq!(spot_pair("BTC-PERP", "USD"), None);
+}
+
+#[tokio::test]
+async fn test_hold_sizes() {
+    let Some(engine) = engine().await else { return };
+    let auth = engine.account(dec!(1000)).await;
+    let ledger = Ledger::new(engine.reads.clone(), LedgerConfig::default(), ids::from_strategy("uuid_v4", engine.clock.clone()));
+
+    let mut order = engine.limit(&auth, "BTC-USD", "buy", dec!(2), dec!(100)).await;
+    assert_eq!(ledger.required_hold(&order, None).unwrap(), Some(("USD".into(), dec!(200))));
+
+    // A market buy reserves at the reference price plus the buffer, and needs one
+    order.price = None;
+    assert_eq!(ledger.required_hold(&order, Some(dec!(100))).unwrap(), Some(("USD".into(), dec!(210.00))));
+    assert!(matches!(ledger.required_hold(&order, None), Err(LedgerError::NoReferencePrice(_))));
+
+    order.side = "sell".into();
+    assert_eq!(ledger.required_hold(&order, None).unwrap(), Some(("BTC".into(), dec!(2))));
+}
+
+#[tokio::test]
+async fn test_insufficient_funds_rejected() {
+    let Some(engine) = engine().await else { return };
+    let auth = engine.account(dec!(1000)).await;
+    engine.limit(&auth, "BTC-USD", "buy", dec!(6), dec!(100)).await;
+
+    let req: NewOrderRequest = serde_json::from_value(serde_json::json!({
+        "symbol": "BTC-USD", "side": "buy", "orderType": "limit", "quantity": "6", "price": "100",
+    }))
+        .unwrap();
+    let mut timer = StageTimer::start(engine.clock.clone());
+    let result = engine.orders.submit_order(&auth, req, &engine.positions, &mut timer).await.unwrap();
+
+    assert!(matches!(result, OrderResult::Rejected { code: RejectReason::RiskBreach("INSUFFICIENT_FUNDS"), .. }));
+    assert_eq!(engine.balance(auth.account_id, "USD").await, (dec!(1000), dec!(600)));
+}
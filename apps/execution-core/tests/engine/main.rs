@@ -0,0 +1,9 @@
+//! Engine Tests Against Postgres
+//! The engine's own code run on a real database; skipped unless TEST_DATABASE_URL is set
+
+mod amend;
+mod cancel;
+mod ids;
+mod ledger;
+mod shard;
+mod support;
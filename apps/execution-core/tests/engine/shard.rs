@@ -0,0 +1,33 @@
+//! Mass cancels across sharded instances: the instance cancelling is not the one whose book
+//! holds every order
+
+use crate::support;
+use execution_core::engine::order_processor::CancelAllRequest;
+use execution_core::engine::shard::Evictions;
+use rust_decimal_macros::dec;
+
+#[tokio::test]
+async fn test_cancel_all_evicts_from_the_owning_shard() {
+    let Some(btc) = support::shard(&["BTC-USD"]).await else { return };
+    let Some(eth) = support::shard(&["ETH-USD"]).await else { return };
+    let auth = btc.account(dec!(1000)).await;
+    btc.limit(&auth, "BTC-USD", "buy", dec!(1), dec!(100)).await;
+    eth.limit(&auth, "ETH-USD", "buy", dec!(2), dec!(100)).await;
+
+    let summary = btc
+        .orders
+        .cancel_account_orders(auth.account_id, &CancelAllRequest::default(), "cancel_all")
+        .await
+        .unwrap();
+    assert_eq!(summary.cancelled, 2);
+    assert!(btc.orders.depth("BTC-USD", 10).await.bids.is_empty());
+    // Cancelled in the database, still resting in the other instance's book
+    assert_eq!(eth.orders.depth("ETH-USD", 10).await.bids.len(), 1);
+
+    let published = serde_json::to_vec(&summary.evictions()).unwrap();
+    let evictions: Evictions = serde_json::from_slice(&published).unwrap();
+    eth.orders.evict_orders(&evictions).await;
+
+    assert!(eth.orders.depth("ETH-USD", 10).await.bids.is_empty());
+    assert!(eth.orders.open_order_index().await.is_empty());
+}
@@ -0,0 +1,153 @@
+//! Engine wired to a real database, the way the subscriber wires it
+//! TEST_DATABASE_URL names a database initialised from infra/db/init; without it tests skip
+
+use execution_core::auth::{permissions, AuthContext};
+use execution_core::clock::{SharedClock, SystemClock};
+use execution_core::engine::ledger::LedgerConfig;
+use execution_core::engine::order_processor::{NewOrderRequest, Order, OrderResult};
+use execution_core::engine::risk::RiskLimits;
+use execution_core::engine::shard::Shard;
+use execution_core::engine::{
+    AccountSettings, ExecutionReports, Instruments, Ledger, MarketMakerProtection, OrderProcessor, PositionKeeper,
+    Rebates,
+};
+use execution_core::engine::strategy_limits::StrategyLimits;
+use execution_core::engine::trading_pauses::TradingPauses;
+use execution_core::ids;
+use execution_core::market_data::MarketData;
+use execution_core::observability::stages::StageTimer;
+use execution_core::resilience::{AdaptiveLimiter, LimiterConfig};
+use execution_core::storage::{Dialect, PgOrderRepository, ReadPool};
+
+use rust_decimal::Decimal;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub struct Engine {
+    pub pool: PgPool,
+    pub ledger: Arc<Ledger>,
+    pub orders: Arc<OrderProcessor>,
+    pub positions: Arc<PositionKeeper>,
+    pub reads: ReadPool,
+    pub clock: SharedClock,
+}
+
+/// The engine on TEST_DATABASE_URL, or `None` when it is not set
+pub async fn engine() -> Option<Engine> {
+    engine_on(None).await
+}
+
+/// A sharded instance owning `symbols`, on the same database as every other
+pub async fn shard(symbols: &[&str]) -> Option<Engine> {
+    let shard = Shard::default();
+    for symbol in symbols {
+        shard.acquire(symbol);
+    }
+    engine_on(Some(Arc::new(shard))).await
+}
+
+async fn engine_on(shard: Option<Arc<Shard>>) -> Option<Engine> {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set, skipping");
+        return None;
+    };
+    let pool = PgPoolOptions::new()
+        .max_connections(4)
+        .connect(&url)
+        .await
+        .expect("TEST_DATABASE_URL is reachable");
+
+    let clock: SharedClock = Arc::new(SystemClock::new());
+    let limiter = Arc::new(AdaptiveLimiter::new(
+        LimiterConfig { name: "test", min_limit: 1, max_limit: 4, initial_limit: 4 },
+        clock.clone(),
+    ));
+    let reads = ReadPool::new(pool.clone(), limiter.clone(), None, None);
+    let ids = ids::from_strategy("uuid_v7", clock.clone());
+    let ledger = Arc::new(Ledger::new(reads.clone(), LedgerConfig::default(), ids.clone()));
+    let positions = Arc::new(PositionKeeper::new(pool.clone(), reads.clone()));
+    let (mmp, _) = MarketMakerProtection::new(pool.clone(), clock.clone());
+    let (executions, _) = ExecutionReports::new();
+
+    let orders = Arc::new(OrderProcessor::new(
+        pool.clone(),
+        limiter,
+        Arc::new(PgOrderRepository::new(pool.clone(), Dialect::Postgres)),
+        ledger.clone(),
+        Arc::new(Rebates::new(pool.clone(), ledger.clone(), Default::default(), clock.clone())),
+        Arc::new(AccountSettings::new(pool.clone())),
+        Arc::new(Instruments::new(pool.clone())),
+        Arc::new(TradingPauses::new(pool.clone(), clock.clone())),
+        Arc::new(RiskLimits::new(Default::default(), Decimal::ZERO, positions.clone())),
+        Arc::new(StrategyLimits::new(pool.clone(), clock.clone())),
+        Arc::new(mmp),
+        executions,
+        Default::default(),
+        false,
+        Arc::new(MarketData::new(16)),
+        clock.clone(),
+        ids,
+        shard,
+    ));
+
+    Some(Engine { pool, ledger, orders, positions, reads, clock })
+}
+
+impl Engine {
+    /// A new trading account funded with `usd`
+    pub async fn account(&self, usd: Decimal) -> AuthContext {
+        let account_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO accounts (id, username, email, password_hash) VALUES ($1, $2, $3, '')")
+            .bind(account_id)
+            .bind(account_id.to_string())
+            .bind(format!("{}@test.local", account_id))
+            .execute(&self.pool)
+            .await
+            .unwrap();
+
+        let mut conn = self.pool.acquire().await.unwrap();
+        self.ledger.deposit(&mut conn, account_id, "USD", usd, "deposit").await.unwrap();
+
+        AuthContext {
+            account_id,
+            username: account_id.to_string(),
+            role: "trader".into(),
+            permissions: [permissions::ORDERS_CREATE, permissions::ORDERS_CANCEL]
+                .map(String::from)
+                .into(),
+            token_jti: String::new(),
+            impersonation: None,
+        }
+    }
+
+    /// Submit a limit order that must be accepted
+    pub async fn limit(&self, auth: &AuthContext, symbol: &str, side: &str, quantity: Decimal, price: Decimal) -> Order {
+        let req: NewOrderRequest = serde_json::from_value(serde_json::json!({
+            "symbol": symbol,
+            "side": side,
+            "orderType": "limit",
+            "quantity": quantity,
+            "price": price,
+        }))
+            .unwrap();
+        let mut timer = StageTimer::start(self.clock.clone());
+        match self.orders.submit_order(auth, req, &self.positions, &mut timer).await.unwrap() {
+            OrderResult::Accepted(order) => order,
+            other => panic!("order not accepted: {:?}", other),
+        }
+    }
+
+    /// (total, held) of one asset
+    pub async fn balance(&self, account_id: Uuid, asset: &str) -> (Decimal, Decimal) {
+        self.ledger
+            .balances(account_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|b| b.asset == asset)
+            .map(|b| (b.total, b.held))
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,109 @@
+//! Unit Tests for Engine Errors
+//! Standalone tests for the wire code and failure class of each kind of engine error, and the
+//! class each order rejection code is counted under
+
+#[cfg(test)]
+mod engine_error_tests {
+    /// Mirror of `EngineError`
+    enum EngineError {
+        Auth(String),
+        Validation(String),
+        Risk { code: &'static str, reason: String },
+        Conflict(String),
+        Storage(String),
+    }
+
+    impl EngineError {
+        /// Mirror of `EngineError::code`
+        fn code(&self) -> &'static str {
+            match self {
+                EngineError::Auth(_) => "FORBIDDEN",
+                EngineError::Validation(_) => "INVALID_REQUEST",
+                EngineError::Risk { code, .. } => code,
+                EngineError::Conflict(_) => "CONFLICT",
+                EngineError::Storage(_) => "STORAGE_ERROR",
+            }
+        }
+
+        /// Mirror of `EngineError::class`
+        fn class(&self) -> &'static str {
+            match self {
+                EngineError::Auth(_) => "auth",
+                EngineError::Validation(_) => "validation",
+                EngineError::Risk { .. } => "risk",
+                EngineError::Conflict(_) => "conflict",
+                EngineError::Storage(_) => "storage",
+            }
+        }
+
+        /// Mirror of the `Display` impl
+        fn message(&self) -> String {
+            match self {
+                EngineError::Auth(m) => format!("Insufficient permissions: {}", m),
+                EngineError::Validation(m) => format!("Invalid request: {}", m),
+                EngineError::Risk { reason, .. } => reason.clone(),
+                EngineError::Conflict(m) => format!("Conflict: {}", m),
+                EngineError::Storage(m) => format!("Database error: {}", m),
+            }
+        }
+
+        fn is_unexpected(&self) -> bool {
+            matches!(self, EngineError::Storage(_))
+        }
+    }
+
+    /// Mirror of `RejectReason::of` followed by `RejectReason::class`
+    fn rejection_class(code: &str) -> &'static str {
+        match code {
+            "INSTRUMENT_INACTIVE" => "invalid_symbol",
+            "INVALID_TICK_SIZE" | "INVALID_STOP" | "INVALID_BRACKET" | "POST_ONLY_WOULD_TAKE"
+            | "NO_REFERENCE_PRICE" => "bad_price",
+            "INVALID_QUANTITY" | "INVALID_NOTIONAL" | "NOTIONAL_BELOW_MIN_QUANTITY" | "INVALID_LOT_SIZE"
+            | "BELOW_MIN_QUANTITY" | "ABOVE_MAX_QUANTITY" | "BELOW_MIN_NOTIONAL" | "INVALID_DISPLAY" => "bad_quantity",
+            _ => "invalid_order",
+        }
+    }
+
+    #[test]
+    fn test_each_class_has_its_own_code() {
+        let errors = [
+            EngineError::Auth("orders:create required".into()),
+            EngineError::Validation("price must be positive".into()),
+            EngineError::Risk { code: "TRADING_PAUSED", reason: "paused".into() },
+            EngineError::Conflict("Order is no longer open".into()),
+            EngineError::Storage("connection reset".into()),
+        ];
+        let codes: Vec<_> = errors.iter().map(EngineError::code).collect();
+        assert_eq!(codes, ["FORBIDDEN", "INVALID_REQUEST", "TRADING_PAUSED", "CONFLICT", "STORAGE_ERROR"]);
+        let classes: Vec<_> = errors.iter().map(EngineError::class).collect();
+        assert_eq!(classes, ["auth", "validation", "risk", "conflict", "storage"]);
+    }
+
+    #[test]
+    fn test_only_storage_failures_are_unexpected() {
+        assert!(EngineError::Storage("timeout".into()).is_unexpected());
+        assert!(!EngineError::Conflict("Strategy is no longer pending".into()).is_unexpected());
+        assert!(!EngineError::Auth("admin:full required".into()).is_unexpected());
+    }
+
+    #[test]
+    fn test_messages_keep_their_previous_wording() {
+        // Clients matched on these before the errors carried codes
+        assert_eq!(EngineError::Auth("admin:full required".into()).message(), "Insufficient permissions: admin:full required");
+        assert_eq!(EngineError::Validation("symbol is required".into()).message(), "Invalid request: symbol is required");
+        assert_eq!(EngineError::Storage("pool timed out".into()).message(), "Database error: pool timed out");
+        assert_eq!(EngineError::Risk { code: "TRADING_PAUSED", reason: "paused".into() }.message(), "paused");
+    }
+
+    #[test]
+    fn test_validator_codes_fall_into_classes() {
+        assert_eq!(rejection_class("INSTRUMENT_INACTIVE"), "invalid_symbol");
+        assert_eq!(rejection_class("INVALID_TICK_SIZE"), "bad_price");
+        assert_eq!(rejection_class("NO_REFERENCE_PRICE"), "bad_price");
+        assert_eq!(rejection_class("BELOW_MIN_NOTIONAL"), "bad_quantity");
+        assert_eq!(rejection_class("INVALID_LOT_SIZE"), "bad_quantity");
+        // Codes added later are still counted
+        assert_eq!(rejection_class("INVALID_TIME_IN_FORCE"), "invalid_order");
+        assert_eq!(rejection_class("SOMETHING_NEW"), "invalid_order");
+    }
+}
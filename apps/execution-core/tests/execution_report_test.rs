@@ -0,0 +1,53 @@
+//! Unit Tests for Execution Reports
+//! Standalone tests for the subject, liquidity flag and order state each fill's or reduction's
+//! report carries
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod execution_report_tests {
+    use super::*;
+
+    /// Mirror of `ExecutionReport::subject`
+    fn subject(account_id: Uuid) -> String {
+        format!("executions.{}", account_id)
+    }
+
+    /// Mirror of `tick_liquidity`
+    fn tick_liquidity(order_type: &str) -> &'static str {
+        if order_type == "limit" { "maker" } else { "taker" }
+    }
+
+    /// Mirror of the quantities in `ExecutionReport::new`: (cumulative, leaves)
+    fn quantities(order_quantity: Decimal, filled_after: Decimal) -> (Decimal, Decimal) {
+        (filled_after, (order_quantity - filled_after).max(Decimal::ZERO))
+    }
+
+    #[test]
+    fn test_subject_is_per_account() {
+        let account = Uuid::parse_str("7d0c3a52-3f1e-4c1e-9b8f-2f6f1c0d9a11").unwrap();
+        assert_eq!(subject(account), "executions.7d0c3a52-3f1e-4c1e-9b8f-2f6f1c0d9a11");
+    }
+
+    #[test]
+    fn test_resting_limit_orders_make() {
+        assert_eq!(tick_liquidity("limit"), "maker");
+        assert_eq!(tick_liquidity("market"), "taker");
+        // A triggered stop-limit became a limit order before it could fill
+        assert_eq!(tick_liquidity("stop"), "taker");
+    }
+
+    #[test]
+    fn test_partial_then_final_fill() {
+        assert_eq!(quantities(dec!(10), dec!(4)), (dec!(4), dec!(6)));
+        assert_eq!(quantities(dec!(10), dec!(10)), (dec!(10), dec!(0)));
+    }
+
+    #[test]
+    fn test_reduction_lowers_what_is_left() {
+        // 10 ordered, 4 filled, reduced to 7: nothing more fills, 3 stay open
+        assert_eq!(quantities(dec!(7), dec!(4)), (dec!(4), dec!(3)));
+    }
+}
@@ -0,0 +1,89 @@
+//! Unit Tests for Histogram Exemplars
+//! Standalone tests for bucket selection and the OpenMetrics rewrite
+
+#[cfg(test)]
+mod exemplars_tests {
+    const BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+    /// Mirror of `bucket_index`
+    fn bucket_index(buckets: &[f64], value: f64) -> usize {
+        buckets.iter().position(|bound| value <= *bound).unwrap_or(buckets.len())
+    }
+
+    /// Mirror of `openmetrics_line`
+    fn openmetrics_line(line: &str, counters: &[&str]) -> Option<String> {
+        let (directive, rest) = line
+            .strip_prefix("# TYPE ")
+            .map(|rest| ("# TYPE ", rest))
+            .or_else(|| line.strip_prefix("# HELP ").map(|rest| ("# HELP ", rest)))?;
+
+        let (name, tail) = rest.split_once(' ')?;
+        let family = name.strip_suffix("_total")?;
+        counters.contains(&name).then(|| format!("{}{} {}", directive, family, tail))
+    }
+
+    /// Mirror of `label`
+    fn label<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+        let labels = &line[line.find('{')? + 1..line.rfind('}')?];
+        labels.split("\",").find_map(|pair| {
+            let value = pair.strip_prefix(name)?.strip_prefix("=\"")?;
+            Some(value.strip_suffix('"').unwrap_or(value))
+        })
+    }
+
+    #[test]
+    fn test_bucket_index_on_bound_is_inclusive() {
+        assert_eq!(bucket_index(BUCKETS, 0.001), 0);
+        assert_eq!(bucket_index(BUCKETS, 0.0011), 1);
+        assert_eq!(bucket_index(BUCKETS, 1.0), 8);
+    }
+
+    #[test]
+    fn test_bucket_index_overflow_is_inf() {
+        assert_eq!(bucket_index(BUCKETS, 3.0), BUCKETS.len());
+        assert_eq!(bucket_index(BUCKETS, "+Inf".parse().unwrap()), BUCKETS.len());
+    }
+
+    #[test]
+    fn test_le_labels_map_back_to_buckets() {
+        for (i, bound) in BUCKETS.iter().enumerate() {
+            let le: f64 = bound.to_string().parse().unwrap();
+            assert_eq!(bucket_index(BUCKETS, le), i);
+        }
+    }
+
+    #[test]
+    fn test_counter_family_drops_total_suffix() {
+        let counters = ["orders_total"];
+        assert_eq!(
+            openmetrics_line("# HELP orders_total Orders seen", &counters).as_deref(),
+            Some("# HELP orders Orders seen")
+        );
+        assert_eq!(
+            openmetrics_line("# TYPE orders_total counter", &counters).as_deref(),
+            Some("# TYPE orders counter")
+        );
+    }
+
+    #[test]
+    fn test_non_counters_are_untouched() {
+        assert_eq!(openmetrics_line("# TYPE queue_total gauge", &[]), None);
+        assert_eq!(openmetrics_line("# TYPE latency_seconds histogram", &["orders_total"]), None);
+        assert_eq!(openmetrics_line("orders_total 5", &["orders_total"]), None);
+    }
+
+    #[test]
+    fn test_label_lookup() {
+        let line = r#"latency_bucket{operation="submit",role="x",le="0.005"} 3"#;
+        assert_eq!(label(line, "operation"), Some("submit"));
+        assert_eq!(label(line, "le"), Some("0.005"));
+        assert_eq!(label(line, "missing"), None);
+    }
+
+    #[test]
+    fn test_label_name_must_match_exactly() {
+        // `role` ends in `le` but must not be read as `le`
+        let line = r#"latency_bucket{role="admin",le="+Inf"} 1"#;
+        assert_eq!(label(line, "le"), Some("+Inf"));
+    }
+}
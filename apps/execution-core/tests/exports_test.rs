@@ -0,0 +1,113 @@
+//! Unit Tests for Account Exports
+//! Standalone tests for export ranges, CSV encoding and where files are written
+
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod exports_tests {
+    use super::*;
+
+    const MAX_RANGE_DAYS: i64 = 366;
+
+    enum Cell {
+        Text(Option<&'static str>),
+        Decimal(Option<Decimal>),
+        Timestamp(Option<DateTime<Utc>>),
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Mirror of the range check in `Exports::request`
+    fn check_range(from: NaiveDate, to: NaiveDate) -> Result<i64, String> {
+        let days = (to - from).num_days() + 1;
+        if days <= 0 || days > MAX_RANGE_DAYS {
+            return Err(format!("Export range must be between 1 and {} days", MAX_RANGE_DAYS));
+        }
+        Ok(days)
+    }
+
+    /// Mirror of the query bounds in `Exports::generate`
+    fn window(from: NaiveDate, to: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let start = from.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        (start, start + Duration::days((to - from).num_days() + 1))
+    }
+
+    /// Mirror of `csv_field`
+    fn csv_field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// Mirror of the row encoding in `to_csv`
+    fn csv_line(row: &[Cell]) -> String {
+        row.iter()
+            .map(|cell| match cell {
+                Cell::Text(v) => v.map(csv_field).unwrap_or_default(),
+                Cell::Decimal(v) => v.map(|d| d.to_string()).unwrap_or_default(),
+                Cell::Timestamp(v) => v.map(|t| t.to_rfc3339_opts(SecondsFormat::Micros, true)).unwrap_or_default(),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Mirror of the Decimal128 conversion in `to_parquet`
+    fn mantissa(d: Decimal) -> i128 {
+        let mut d = d;
+        d.rescale(8);
+        d.mantissa()
+    }
+
+    #[test]
+    fn test_range_is_inclusive_and_bounded() {
+        assert_eq!(check_range(day(2024, 3, 1), day(2024, 3, 1)), Ok(1));
+        assert_eq!(check_range(day(2024, 1, 1), day(2024, 12, 31)), Ok(366));
+        assert!(check_range(day(2024, 1, 1), day(2025, 1, 1)).is_err());
+        assert!(check_range(day(2024, 3, 2), day(2024, 3, 1)).is_err());
+    }
+
+    #[test]
+    fn test_window_covers_whole_utc_days() {
+        let (start, end) = window(day(2024, 3, 1), day(2024, 3, 2));
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2024, 3, 3, 0, 0, 0).unwrap());
+
+        // The last instant of `to` is in, midnight after it is not
+        let last = Utc.with_ymd_and_hms(2024, 3, 2, 23, 59, 59).unwrap();
+        assert!(last >= start && last < end);
+    }
+
+    #[test]
+    fn test_csv_quotes_only_when_needed() {
+        assert_eq!(csv_field("AAPL"), "AAPL");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn test_csv_line_leaves_missing_values_empty() {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let row = [
+            Cell::Text(Some("client,1")),
+            Cell::Decimal(Some(dec!(10.5))),
+            Cell::Decimal(None),
+            Cell::Timestamp(Some(at)),
+            Cell::Text(None),
+        ];
+        assert_eq!(csv_line(&row), "\"client,1\",10.5,,2024-03-01T12:00:00.000000Z,");
+    }
+
+    #[test]
+    fn test_parquet_decimals_share_one_scale() {
+        assert_eq!(mantissa(dec!(1)), 100_000_000);
+        assert_eq!(mantissa(dec!(101.25)), 10_125_000_000);
+        assert_eq!(mantissa(dec!(-0.00000001)), -1);
+    }
+}
@@ -0,0 +1,174 @@
+//! Unit Tests for Fill Deduplication
+//! Standalone tests proving redelivered or replayed fills are recorded and applied once
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod fill_dedup_tests {
+    use super::*;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct FillKey {
+        order_id: Uuid,
+        filled_before: Decimal,
+    }
+
+    /// An order as a fill event carries it: the state it matched in
+    #[derive(Debug, Clone, Copy)]
+    struct Order {
+        id: Uuid,
+        filled_quantity: Decimal,
+    }
+
+    /// Mirror of the key `fill_order` claims: the order and its fill before this one
+    fn fill_key(order: &Order) -> FillKey {
+        FillKey { order_id: order.id, filled_before: order.filled_quantity }
+    }
+
+    /// Stand-in for `fill_dedup`, `trades` and `positions`
+    #[derive(Default, Clone)]
+    struct Store {
+        /// key -> position applied
+        dedup: HashMap<FillKey, bool>,
+        trades: Vec<(Uuid, Decimal)>,
+        net_quantity: Decimal,
+    }
+
+    impl Store {
+        /// Mirror of `claim_fill`: ON CONFLICT DO NOTHING
+        fn claim_fill(&mut self, key: FillKey) -> bool {
+            if self.dedup.contains_key(&key) {
+                return false;
+            }
+            self.dedup.insert(key, false);
+            true
+        }
+
+        /// Mirror of `mark_applied`: only while `position_applied_at IS NULL`
+        fn mark_applied(&mut self, key: FillKey) -> bool {
+            match self.dedup.get_mut(&key) {
+                Some(applied) if !*applied => {
+                    *applied = true;
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        /// Mirror of `fill_order`
+        fn fill(&mut self, order: Order, quantity: Decimal) {
+            self.fill_in_transaction(order, quantity, false);
+        }
+
+        /// Mirror of `fill_order`'s transaction: the trade and position change commit
+        /// together, or a failure before the commit leaves neither
+        fn fill_in_transaction(&mut self, order: Order, quantity: Decimal, fails: bool) {
+            let mut tx = self.clone();
+            let key = fill_key(&order);
+            if !tx.claim_fill(key) {
+                return;
+            }
+            tx.trades.push((order.id, quantity));
+            tx.apply(key, quantity);
+            if !fails {
+                *self = tx;
+            }
+        }
+
+        fn apply(&mut self, key: FillKey, quantity: Decimal) {
+            if self.mark_applied(key) {
+                self.net_quantity += quantity;
+            }
+        }
+    }
+
+    fn order(filled_quantity: Decimal) -> Order {
+        Order { id: Uuid::new_v4(), filled_quantity }
+    }
+
+    #[test]
+    fn test_redelivered_fill_counts_once() {
+        let mut store = Store::default();
+        let order = order(dec!(0));
+
+        store.fill(order, dec!(10));
+        store.fill(order, dec!(10));
+        store.fill(order, dec!(10));
+
+        assert_eq!(store.trades.len(), 1);
+        assert_eq!(store.net_quantity, dec!(10));
+    }
+
+    #[test]
+    fn test_redelivery_after_a_later_fill_is_rejected() {
+        let mut store = Store::default();
+        let first = order(dec!(0));
+        let second = Order { filled_quantity: dec!(4), ..first };
+
+        store.fill(first, dec!(4));
+        store.fill(second, dec!(6));
+        // The first event again: a count of claimed fills would number it 3 and record it
+        store.fill(first, dec!(4));
+
+        assert_eq!(store.trades, vec![(first.id, dec!(4)), (first.id, dec!(6))]);
+        assert_eq!(store.net_quantity, dec!(10));
+    }
+
+    #[test]
+    fn test_partial_fills_of_one_order_all_count() {
+        let mut store = Store::default();
+        let first = order(dec!(0));
+        for filled_before in [dec!(0), dec!(2), dec!(5)] {
+            store.fill(Order { filled_quantity: filled_before, ..first }, dec!(1));
+        }
+        assert_eq!(store.trades.len(), 3);
+    }
+
+    #[test]
+    fn test_distinct_orders_all_count() {
+        let mut store = Store::default();
+        store.fill(order(dec!(0)), dec!(10));
+        store.fill(order(dec!(0)), dec!(5));
+
+        assert_eq!(store.trades.len(), 2);
+        assert_eq!(store.net_quantity, dec!(15));
+    }
+
+    #[test]
+    fn test_position_applied_once() {
+        let mut store = Store::default();
+        let key = FillKey { order_id: Uuid::new_v4(), filled_before: dec!(0) };
+        assert!(store.claim_fill(key));
+
+        // A retried position update after the first one committed moves nothing
+        store.apply(key, dec!(3));
+        store.apply(key, dec!(3));
+        assert_eq!(store.net_quantity, dec!(3));
+    }
+
+    #[test]
+    fn test_failed_fill_leaves_nothing_and_retries() {
+        let mut store = Store::default();
+        let order = order(dec!(0));
+
+        store.fill_in_transaction(order, dec!(4), true);
+        assert!(store.trades.is_empty());
+        assert!(store.dedup.is_empty());
+        assert_eq!(store.net_quantity, dec!(0));
+
+        // The key was never claimed, so the next tick records the fill in full
+        store.fill(order, dec!(4));
+        assert_eq!(store.trades.len(), 1);
+        assert_eq!(store.net_quantity, dec!(4));
+    }
+
+    #[test]
+    fn test_unclaimed_fill_not_applied() {
+        let mut store = Store::default();
+        store.apply(FillKey { order_id: Uuid::new_v4(), filled_before: dec!(0) }, dec!(3));
+        assert_eq!(store.net_quantity, dec!(0));
+    }
+}
@@ -0,0 +1,149 @@
+//! Unit Tests for Fill Delivery
+//! Standalone tests for consumer registration, redelivery backoff and quarantine
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+#[cfg(test)]
+mod fill_delivery_tests {
+    use super::*;
+
+    const CONSUMER_KINDS: &[&str] = &["webhook", "websocket", "drop_copy"];
+    const MAX_BACKOFF_SECS: f64 = 300.0;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Status {
+        Pending,
+        Delivered,
+    }
+
+    /// Row of `fill_deliveries`
+    struct Delivery {
+        status: Status,
+        attempts: i32,
+        next_attempt_at: DateTime<Utc>,
+    }
+
+    impl Delivery {
+        fn new(now: DateTime<Utc>) -> Self {
+            Self { status: Status::Pending, attempts: 0, next_attempt_at: now }
+        }
+    }
+
+    struct Consumer {
+        quarantined: bool,
+        deliveries: Vec<Delivery>,
+    }
+
+    /// Mirror of `RegisterConsumer::validate`
+    fn validate(name: &str, kind: &str, subject: &str) -> Result<(), &'static str> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > 64 {
+            return Err("name");
+        }
+        if !CONSUMER_KINDS.contains(&kind) {
+            return Err("kind");
+        }
+        let subject = subject.trim();
+        if subject.is_empty() || subject.contains(['*', '>']) || subject.contains(char::is_whitespace) {
+            return Err("subject");
+        }
+        Ok(())
+    }
+
+    /// Mirror of the `LEAST(power(2, attempts), $3)` backoff in `FillDelivery::poll`
+    fn backoff_secs(attempts: i32) -> f64 {
+        2f64.powi(attempts).min(MAX_BACKOFF_SECS)
+    }
+
+    /// Mirror of `FillDelivery::poll`: quarantine first, then claim what is due.
+    /// Returns the attempt numbers sent.
+    fn poll(consumer: &mut Consumer, now: DateTime<Utc>, max_attempts: i32) -> Vec<i32> {
+        let exhausted = consumer.deliveries.iter()
+            .any(|d| d.status == Status::Pending && d.attempts >= max_attempts && d.next_attempt_at <= now);
+        if exhausted {
+            consumer.quarantined = true;
+        }
+        if consumer.quarantined {
+            return Vec::new();
+        }
+
+        let mut sent = Vec::new();
+        for d in consumer.deliveries.iter_mut().filter(|d| d.status == Status::Pending && d.next_attempt_at <= now) {
+            d.next_attempt_at = now + Duration::milliseconds((backoff_secs(d.attempts) * 1000.0) as i64);
+            d.attempts += 1;
+            sent.push(d.attempts);
+        }
+        sent
+    }
+
+    /// Mirror of `FillDelivery::release`
+    fn release(consumer: &mut Consumer, now: DateTime<Utc>) {
+        consumer.quarantined = false;
+        for d in consumer.deliveries.iter_mut().filter(|d| d.status == Status::Pending) {
+            d.attempts = 0;
+            d.next_attempt_at = now;
+        }
+    }
+
+    fn at(second: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap() + Duration::seconds(second)
+    }
+
+    #[test]
+    fn test_registration_validation() {
+        assert_eq!(validate("crm-webhook", "webhook", "fills.deliver.crm"), Ok(()));
+        assert_eq!(validate("  ", "webhook", "fills.deliver.crm"), Err("name"));
+        assert_eq!(validate("crm", "email", "fills.deliver.crm"), Err("kind"));
+        assert_eq!(validate("crm", "drop_copy", "fills.deliver.*"), Err("subject"));
+        assert_eq!(validate("crm", "drop_copy", "fills.>"), Err("subject"));
+        assert_eq!(validate("crm", "websocket", "fills deliver"), Err("subject"));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        assert_eq!(backoff_secs(0), 1.0);
+        assert_eq!(backoff_secs(1), 2.0);
+        assert_eq!(backoff_secs(4), 16.0);
+        assert_eq!(backoff_secs(9), 300.0);
+        assert_eq!(backoff_secs(30), 300.0);
+    }
+
+    #[test]
+    fn test_unacknowledged_fill_is_redelivered_after_backoff() {
+        let mut consumer = Consumer { quarantined: false, deliveries: vec![Delivery::new(at(0))] };
+        assert_eq!(poll(&mut consumer, at(0), 10), vec![1]);
+        assert!(poll(&mut consumer, at(0), 10).is_empty());
+        assert_eq!(poll(&mut consumer, at(1), 10), vec![2]);
+        assert!(poll(&mut consumer, at(2), 10).is_empty());
+        assert_eq!(poll(&mut consumer, at(3), 10), vec![3]);
+    }
+
+    #[test]
+    fn test_acknowledged_fill_is_not_redelivered() {
+        let mut consumer = Consumer { quarantined: false, deliveries: vec![Delivery::new(at(0))] };
+        poll(&mut consumer, at(0), 10);
+        consumer.deliveries[0].status = Status::Delivered;
+        assert!(poll(&mut consumer, at(3600), 10).is_empty());
+        assert!(!consumer.quarantined);
+    }
+
+    #[test]
+    fn test_consumer_quarantined_after_max_attempts() {
+        let mut consumer = Consumer { quarantined: false, deliveries: vec![Delivery::new(at(0))] };
+        for second in [0, 1, 3] {
+            assert_eq!(poll(&mut consumer, at(second), 3).len(), 1);
+        }
+        // The third send's backoff has not run out yet
+        assert!(poll(&mut consumer, at(6), 3).is_empty());
+        assert!(!consumer.quarantined);
+
+        assert!(poll(&mut consumer, at(7), 3).is_empty());
+        assert!(consumer.quarantined);
+
+        // Fills queue up while quarantined and go out again once released
+        consumer.deliveries.push(Delivery::new(at(8)));
+        assert!(poll(&mut consumer, at(60), 3).is_empty());
+        release(&mut consumer, at(60));
+        assert_eq!(poll(&mut consumer, at(60), 3), vec![1, 1]);
+    }
+}
@@ -0,0 +1,80 @@
+//! Unit Tests for the Fill Topic
+//! Standalone tests for per-account ordering and the post-trade drawdown the risk limits keep
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod fill_topic_tests {
+    use super::*;
+
+    const SHARDS: usize = 16;
+
+    /// Mirror of `shard_of`
+    fn shard_of(account_id: Uuid) -> usize {
+        (account_id.as_u128() % SHARDS as u128) as usize
+    }
+
+    /// Mirror of the sequence numbering in `FillTopic::publish`
+    fn number(accounts: &[Uuid]) -> Vec<u64> {
+        let mut sequences: HashMap<Uuid, u64> = HashMap::new();
+        accounts
+            .iter()
+            .map(|account| {
+                let sequence = sequences.entry(*account).or_insert(0);
+                *sequence += 1;
+                *sequence
+            })
+            .collect()
+    }
+
+    /// Mirror of `AccountRisk`
+    #[derive(Default)]
+    struct AccountRisk {
+        realized_pnl: HashMap<String, Decimal>,
+        peak_pnl: Decimal,
+    }
+
+    impl AccountRisk {
+        fn apply(&mut self, symbol: &str, realized_pnl: Decimal) -> Decimal {
+            self.realized_pnl.insert(symbol.to_string(), realized_pnl);
+            let total: Decimal = self.realized_pnl.values().copied().sum();
+            self.peak_pnl = self.peak_pnl.max(total);
+            (self.peak_pnl - total).max(Decimal::ZERO)
+        }
+    }
+
+    #[test]
+    fn test_an_account_always_takes_the_same_shard() {
+        let account = Uuid::new_v4();
+        assert_eq!(shard_of(account), shard_of(account));
+        assert!(shard_of(account) < SHARDS);
+        assert_eq!(shard_of(Uuid::from_u128(17)), 1);
+    }
+
+    #[test]
+    fn test_sequences_count_per_account() {
+        let (a, b) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        assert_eq!(number(&[a, b, a, a, b]), vec![1, 1, 2, 3, 2]);
+    }
+
+    #[test]
+    fn test_drawdown_is_measured_from_the_peak() {
+        let mut risk = AccountRisk::default();
+        assert_eq!(risk.apply("AAPL", dec!(100)), dec!(0));
+        assert_eq!(risk.apply("AAPL", dec!(40)), dec!(60));
+        // A gain elsewhere recovers part of it
+        assert_eq!(risk.apply("MSFT", dec!(30)), dec!(30));
+        assert_eq!(risk.apply("AAPL", dec!(120)), dec!(0));
+        assert_eq!(risk.peak_pnl, dec!(150));
+    }
+
+    #[test]
+    fn test_losses_from_the_start_count_from_a_zero_peak() {
+        let mut risk = AccountRisk::default();
+        assert_eq!(risk.apply("AAPL", dec!(-50)), dec!(50));
+        assert_eq!(risk.peak_pnl, dec!(0));
+    }
+}
@@ -0,0 +1,80 @@
+//! Unit Tests for Good-Till-Date Orders
+//! Standalone tests for expiry validation and which orders the expiry sweeper picks up
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+#[cfg(test)]
+mod good_till_date_tests {
+    use super::*;
+
+    struct Order {
+        status: &'static str,
+        expires_at: Option<DateTime<Utc>>,
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    /// Mirror of the time in force chosen in `apply_defaults`
+    fn time_in_force(explicit: Option<&str>, expires_at: Option<DateTime<Utc>>, account_default: Option<&str>) -> Option<String> {
+        explicit
+            .map(str::to_string)
+            .or_else(|| expires_at.map(|_| "gtd".to_string()))
+            .or_else(|| account_default.map(str::to_string))
+    }
+
+    /// Mirror of `validate_expiry`
+    fn validate_expiry(time_in_force: Option<&str>, expires_at: Option<DateTime<Utc>>) -> Result<(), &'static str> {
+        match (time_in_force, expires_at) {
+            (Some("gtd"), None) => Err("missing expires_at"),
+            (Some("gtd"), Some(at)) if at <= now() => Err("in the past"),
+            (Some("gtd"), Some(_)) | (_, None) => Ok(()),
+            (_, Some(_)) => Err("expires_at on a non-gtd order"),
+        }
+    }
+
+    /// Mirror of the `WHERE` of `OrderProcessor::expire_orders`
+    fn sweeps(order: &Order, at: DateTime<Utc>) -> bool {
+        matches!(order.status, "waiting" | "pending" | "partially_filled")
+            && order.expires_at.is_some_and(|expires_at| expires_at <= at)
+    }
+
+    #[test]
+    fn test_expiry_implies_gtd() {
+        let expiry = Some(now() + Duration::hours(1));
+        assert_eq!(time_in_force(None, expiry, Some("day")).as_deref(), Some("gtd"));
+        assert_eq!(time_in_force(Some("gtc"), expiry, None).as_deref(), Some("gtc"));
+        assert_eq!(time_in_force(None, None, Some("day")).as_deref(), Some("day"));
+    }
+
+    #[test]
+    fn test_expiry_validation() {
+        assert_eq!(validate_expiry(Some("gtd"), Some(now() + Duration::seconds(1))), Ok(()));
+        assert_eq!(validate_expiry(Some("gtc"), None), Ok(()));
+        assert_eq!(validate_expiry(None, None), Ok(()));
+        assert!(validate_expiry(Some("gtd"), None).is_err());
+        assert!(validate_expiry(Some("gtd"), Some(now())).is_err());
+        // An explicit gtc with an expiry is contradictory
+        assert!(validate_expiry(Some("gtc"), Some(now() + Duration::hours(1))).is_err());
+    }
+
+    #[test]
+    fn test_sweeper_expires_open_orders_past_their_expiry() {
+        let expiry = now() + Duration::hours(1);
+        let open = Order { status: "pending", expires_at: Some(expiry) };
+        assert!(!sweeps(&open, now()));
+        assert!(sweeps(&open, expiry));
+
+        let waiting = Order { status: "waiting", expires_at: Some(expiry) };
+        assert!(sweeps(&waiting, expiry + Duration::minutes(1)));
+    }
+
+    #[test]
+    fn test_sweeper_leaves_closed_and_gtc_orders() {
+        let later = now() + Duration::days(1);
+        assert!(!sweeps(&Order { status: "filled", expires_at: Some(now()) }, later));
+        assert!(!sweeps(&Order { status: "cancelled", expires_at: Some(now()) }, later));
+        assert!(!sweeps(&Order { status: "pending", expires_at: None }, later));
+    }
+}
@@ -0,0 +1,91 @@
+//! Unit Tests for the State Handoff
+//! Standalone tests for the open-order digest, divergence check and what a successor seeds
+
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+#[cfg(test)]
+mod handoff_tests {
+    use super::*;
+
+    /// Mirror of `index_digest`: (count, digest) per symbol
+    fn index_digest(orders: &[(&str, Uuid)]) -> BTreeMap<String, (usize, String)> {
+        let mut by_symbol: BTreeMap<String, Vec<Uuid>> = BTreeMap::new();
+        for (symbol, id) in orders {
+            by_symbol.entry(symbol.to_string()).or_default().push(*id);
+        }
+        by_symbol
+            .into_iter()
+            .map(|(symbol, mut ids)| {
+                ids.sort_unstable();
+                let mut hasher = Sha256::new();
+                for id in &ids {
+                    hasher.update(id.as_bytes());
+                }
+                (symbol, (ids.len(), hex::encode(hasher.finalize())))
+            })
+            .collect()
+    }
+
+    /// Mirror of `HotState::diverging`
+    fn diverging(handed: &BTreeMap<String, (usize, String)>, local: &BTreeMap<String, (usize, String)>) -> Vec<String> {
+        let mut symbols: Vec<String> = handed
+            .keys()
+            .chain(local.keys())
+            .filter(|symbol| handed.get(*symbol) != local.get(*symbol))
+            .cloned()
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
+    /// Mirror of `OrderProcessor::seed_prices`
+    fn seed_prices(cache: &mut HashMap<String, u32>, handed: HashMap<String, u32>) -> usize {
+        let mut seeded = 0;
+        for (symbol, price) in handed {
+            if let Entry::Vacant(slot) = cache.entry(symbol) {
+                slot.insert(price);
+                seeded += 1;
+            }
+        }
+        seeded
+    }
+
+    #[test]
+    fn test_digest_ignores_order() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(
+            index_digest(&[("BTC-USD", a), ("BTC-USD", b)]),
+            index_digest(&[("BTC-USD", b), ("BTC-USD", a)])
+        );
+    }
+
+    #[test]
+    fn test_divergence_names_the_symbols() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let handed = index_digest(&[("BTC-USD", a), ("ETH-USD", b)]);
+
+        assert!(diverging(&handed, &index_digest(&[("ETH-USD", b), ("BTC-USD", a)])).is_empty());
+        // Same count, different order
+        assert_eq!(diverging(&handed, &index_digest(&[("BTC-USD", c), ("ETH-USD", b)])), vec!["BTC-USD"]);
+        // Symbols on one side only
+        assert_eq!(diverging(&handed, &index_digest(&[("BTC-USD", a)])), vec!["ETH-USD"]);
+        assert_eq!(
+            diverging(&handed, &index_digest(&[("BTC-USD", a), ("ETH-USD", b), ("SOL-USD", c)])),
+            vec!["SOL-USD"]
+        );
+    }
+
+    #[test]
+    fn test_own_ticks_win_over_handed_prices() {
+        let mut cache = HashMap::from([("BTC-USD".to_string(), 41_000)]);
+        let handed = HashMap::from([("BTC-USD".to_string(), 40_000), ("ETH-USD".to_string(), 2_500)]);
+
+        assert_eq!(seed_prices(&mut cache, handed), 1);
+        assert_eq!(cache["BTC-USD"], 41_000);
+        assert_eq!(cache["ETH-USD"], 2_500);
+    }
+}
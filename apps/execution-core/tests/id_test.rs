@@ -0,0 +1,44 @@
+//! Unit Tests for Identifier Generation
+//! Standalone tests for UUIDv7 ordering and embedded timestamps
+
+use uuid::{ContextV7, Timestamp, Uuid};
+
+#[cfg(test)]
+mod id_tests {
+    use super::*;
+
+    fn v7_at(context: &ContextV7, millis: u64) -> Uuid {
+        Uuid::new_v7(Timestamp::from_unix(context, millis / 1000, (millis % 1000) as u32 * 1_000_000))
+    }
+
+    #[test]
+    fn test_ids_sort_by_creation_time() {
+        let context = ContextV7::new();
+        let ids: Vec<Uuid> = [1_700_000_000_000, 1_700_000_000_001, 1_700_000_060_000]
+            .into_iter()
+            .map(|ms| v7_at(&context, ms))
+            .collect();
+
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+    }
+
+    #[test]
+    fn test_ids_within_one_millisecond_stay_ordered() {
+        let context = ContextV7::new();
+        let ids: Vec<Uuid> = (0..100).map(|_| v7_at(&context, 1_700_000_000_000)).collect();
+
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_creation_time_is_recoverable() {
+        let id = v7_at(&ContextV7::new(), 1_700_000_123_456);
+        let (seconds, nanos) = id.get_timestamp().unwrap().to_unix();
+
+        assert_eq!(id.get_version_num(), 7);
+        assert_eq!(seconds, 1_700_000_123);
+        assert_eq!(nanos / 1_000_000, 456);
+    }
+}
@@ -0,0 +1,93 @@
+//! Unit Tests for Support Impersonation
+//! Standalone tests for consent and session windows and what a session may do
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod impersonation_tests {
+    use super::*;
+
+    const MAX_CONSENT_SECS: i64 = 86_400;
+    const MAX_SESSION_SECS: i64 = 3_600;
+    const IMPERSONATED_PERMISSIONS: [&str; 3] = ["orders:read", "positions:read", "market:read"];
+
+    struct Consent {
+        expires_at: DateTime<Utc>,
+        revoked: bool,
+    }
+
+    struct Session {
+        expires_at: DateTime<Utc>,
+        ended: bool,
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    /// Mirror of the window check on `grant_consent` and `start`
+    fn window(requested: Option<i64>, max: i64) -> Result<i64, String> {
+        let window = requested.unwrap_or(max);
+        if window <= 0 || window > max {
+            return Err(format!("window must be between 1 and {} seconds", max));
+        }
+        Ok(window)
+    }
+
+    /// Mirror of the session expiry set by `start`
+    fn session_expiry(now: DateTime<Utc>, window_secs: i64, consent: &Consent) -> DateTime<Utc> {
+        (now + Duration::seconds(window_secs)).min(consent.expires_at)
+    }
+
+    /// Mirror of the session filter in `assume`
+    fn active(session: &Session, consent: &Consent, now: DateTime<Utc>) -> bool {
+        !session.ended && session.expires_at > now && !consent.revoked && consent.expires_at > now
+    }
+
+    fn impersonated_permissions() -> HashSet<String> {
+        IMPERSONATED_PERMISSIONS.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_windows_default_to_and_stop_at_the_maximum() {
+        assert_eq!(window(None, MAX_CONSENT_SECS), Ok(MAX_CONSENT_SECS));
+        assert_eq!(window(Some(600), MAX_SESSION_SECS), Ok(600));
+        assert!(window(Some(MAX_SESSION_SECS + 1), MAX_SESSION_SECS).is_err());
+        assert!(window(Some(0), MAX_SESSION_SECS).is_err());
+        assert!(window(Some(-5), MAX_CONSENT_SECS).is_err());
+    }
+
+    #[test]
+    fn test_session_never_outlives_consent() {
+        let consent = Consent { expires_at: now() + Duration::minutes(20), revoked: false };
+        assert_eq!(session_expiry(now(), MAX_SESSION_SECS, &consent), now() + Duration::minutes(20));
+        assert_eq!(session_expiry(now(), 600, &consent), now() + Duration::minutes(10));
+    }
+
+    #[test]
+    fn test_session_ends_with_its_window_or_consent() {
+        let consent = Consent { expires_at: now() + Duration::hours(2), revoked: false };
+        let session = Session { expires_at: now() + Duration::hours(1), ended: false };
+        assert!(active(&session, &consent, now()));
+        assert!(!active(&session, &consent, now() + Duration::hours(1)));
+
+        let revoked = Consent { revoked: true, ..consent };
+        assert!(!active(&session, &revoked, now()));
+
+        let consent = Consent { expires_at: now() + Duration::hours(2), revoked: false };
+        let ended = Session { ended: true, ..session };
+        assert!(!active(&ended, &consent, now()));
+    }
+
+    #[test]
+    fn test_impersonation_is_read_only() {
+        let permissions = impersonated_permissions();
+        assert!(permissions.contains("orders:read"));
+        assert!(permissions.contains("positions:read"));
+        for write in ["orders:create", "orders:cancel", "accounts:privacy", "sandbox:manage", "admin:full"] {
+            assert!(!permissions.contains(write));
+        }
+        assert!(permissions.iter().all(|p| p.ends_with(":read")));
+    }
+}
@@ -0,0 +1,103 @@
+//! Unit Tests for the Info Endpoint
+//! Standalone tests proving the exposed configuration never carries secrets or credentials
+
+#[cfg(test)]
+mod info_tests {
+    use serde_json::{json, Value};
+
+    const REDACTED: &str = "[REDACTED]";
+    const SECRET_FIELDS: &[&str] = &["jwt_secret", "column_encryption_keys", "sentry_dsn"];
+    const URL_FIELDS: &[&str] = &[
+        "database_url", "database_read_url", "nats_url", "nats_failover_urls", "redis_url", "error_report_otlp_endpoint",
+    ];
+
+    /// Mirror of `strip_credentials`
+    fn strip_credentials(url: &str) -> String {
+        let Some(scheme_end) = url.find("://").map(|i| i + 3) else {
+            return url.to_string();
+        };
+        let authority_end = url[scheme_end..].find('/').map_or(url.len(), |i| scheme_end + i);
+        match url[scheme_end..authority_end].rfind('@') {
+            Some(at) => format!("{}{}{}", &url[..scheme_end], REDACTED, &url[scheme_end + at..]),
+            None => url.to_string(),
+        }
+    }
+
+    /// Mirror of `strip_list_credentials`
+    fn strip_list_credentials(urls: &str) -> String {
+        urls.split(';')
+            .map(|group| group.split(',').map(strip_credentials).collect::<Vec<_>>().join(","))
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    /// Mirror of the field pass in `Config::redacted`
+    fn redact(mut value: Value) -> Value {
+        if let Value::Object(fields) = &mut value {
+            for (name, field) in fields.iter_mut() {
+                match field {
+                    Value::String(text) if SECRET_FIELDS.contains(&name.as_str()) && !text.is_empty() => {
+                        *text = REDACTED.to_string();
+                    }
+                    Value::String(urls) if URL_FIELDS.contains(&name.as_str()) => {
+                        *urls = strip_list_credentials(urls);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        value
+    }
+
+    #[test]
+    fn test_strip_credentials() {
+        assert_eq!(
+            strip_credentials("postgres://postgres:hunter2@db:5432/enthropic"),
+            "postgres://[REDACTED]@db:5432/enthropic"
+        );
+        assert_eq!(strip_credentials("redis://:pw@redis:6379"), "redis://[REDACTED]@redis:6379");
+        assert_eq!(strip_credentials("nats://localhost:4222"), "nats://localhost:4222");
+
+        // An @ in the path is not a credential
+        assert_eq!(
+            strip_credentials("http://collector:4318/v1/logs@x"),
+            "http://collector:4318/v1/logs@x"
+        );
+        assert_eq!(strip_credentials("not a url"), "not a url");
+    }
+
+    #[test]
+    fn test_strip_list_credentials() {
+        assert_eq!(
+            strip_list_credentials("nats://u:pw@a:4222,nats://u:pw@b:4222;nats://u:pw@c:4222"),
+            "nats://[REDACTED]@a:4222,nats://[REDACTED]@b:4222;nats://[REDACTED]@c:4222"
+        );
+        assert_eq!(strip_list_credentials(""), "");
+    }
+
+    #[test]
+    fn test_redacted_config() {
+        let config = redact(json!({
+            "profile": "production",
+            "jwt_secret": "super-secret-signing-key",
+            "column_encryption_keys": "",
+            "sentry_dsn": null,
+            "database_url": "postgres://app:pw@db/enthropic",
+            "database_read_url": null,
+            "nats_failover_urls": "nats://u:pw@east:4222;nats://u:pw@west:4222",
+            "pool_max_connections": 20,
+        }));
+
+        assert_eq!(config["jwt_secret"], REDACTED);
+        assert_eq!(config["database_url"], "postgres://[REDACTED]@db/enthropic");
+        assert_eq!(config["nats_failover_urls"], "nats://[REDACTED]@east:4222;nats://[REDACTED]@west:4222");
+        assert_eq!(config["profile"], "production");
+        assert_eq!(config["pool_max_connections"], 20);
+
+        // Unset secrets stay visibly unset
+        assert_eq!(config["column_encryption_keys"], "");
+        assert!(config["sentry_dsn"].is_null());
+        assert!(!config.to_string().contains("super-secret"));
+        assert!(!config.to_string().contains(":pw@"));
+    }
+}
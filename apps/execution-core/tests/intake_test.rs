@@ -0,0 +1,88 @@
+//! Unit Tests for JetStream Order Intake
+//! Standalone tests for turning pulled deliveries into requests and acking them once answered
+
+#[cfg(test)]
+mod intake_tests {
+    use async_nats::{HeaderMap, Message, Subject};
+    use std::collections::HashMap;
+
+    const REPLY_TO_HEADER: &str = "Reply-To";
+    const ACK_HEADER: &str = "Enthropic-Intake-Ack";
+
+    fn delivery(subject: &str, reply_to: Option<&str>) -> Message {
+        let headers = reply_to.map(|inbox| {
+            let mut headers = HeaderMap::new();
+            headers.insert(REPLY_TO_HEADER, inbox);
+            headers
+        });
+        Message {
+            subject: Subject::from(subject),
+            reply: Some(Subject::from("$JS.ACK.ORDERS.execution-core.1.7.7.1700000000.0")),
+            payload: b"{}".to_vec().into(),
+            headers,
+            status: None,
+            description: None,
+            length: 0,
+        }
+    }
+
+    /// Mirror of `to_request`
+    fn to_request(delivery: &Message, ack_subject: &str) -> Message {
+        let reply = delivery
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get(REPLY_TO_HEADER))
+            .map(|reply| reply.as_str().into());
+        let mut headers = delivery.headers.clone().unwrap_or_default();
+        headers.insert(ACK_HEADER, ack_subject);
+
+        Message {
+            reply,
+            headers: Some(headers),
+            ..delivery.clone()
+        }
+    }
+
+    /// Mirror of `ack_subject`
+    fn ack_subject(msg: &Message) -> Option<String> {
+        msg.headers
+            .as_ref()?
+            .get(ACK_HEADER)
+            .map(|subject| subject.as_str().to_string())
+    }
+
+    #[test]
+    fn test_reply_goes_to_producer() {
+        let pulled = delivery("orders.submit", Some("_INBOX.client.42"));
+        let ack = pulled.reply.as_ref().unwrap().to_string();
+        let request = to_request(&pulled, &ack);
+
+        assert_eq!(request.reply.as_deref(), Some("_INBOX.client.42"));
+        assert_eq!(request.subject.as_str(), "orders.submit");
+        assert_eq!(request.payload, pulled.payload);
+        assert_eq!(ack_subject(&request).as_deref(), Some(ack.as_str()));
+    }
+
+    #[test]
+    fn test_fire_and_forget_still_acks() {
+        let pulled = delivery("orders.cancel", None);
+        let request = to_request(&pulled, "$JS.ACK.x");
+        assert!(request.reply.is_none());
+        assert_eq!(ack_subject(&request).as_deref(), Some("$JS.ACK.x"));
+    }
+
+    #[test]
+    fn test_core_messages_are_not_acked() {
+        let core = Message { headers: None, ..delivery("orders.submit", None) };
+        assert_eq!(ack_subject(&core), None);
+    }
+
+    #[test]
+    fn test_pending_acked_once() {
+        // Mirror of the pending map: answering twice acks the delivery once
+        let mut pending = HashMap::new();
+        pending.insert("$JS.ACK.a".to_string(), 1u64);
+        assert_eq!(pending.remove("$JS.ACK.a"), Some(1));
+        assert_eq!(pending.remove("$JS.ACK.a"), None);
+    }
+}
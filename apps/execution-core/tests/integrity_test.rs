@@ -0,0 +1,119 @@
+//! Unit Tests for Ledger Integrity Checks
+//! Standalone tests mirroring the double-entry and position invariants
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod integrity_tests {
+    use super::*;
+
+    struct Entry {
+        journal: u32,
+        account: &'static str,
+        asset: &'static str,
+        amount: Decimal,
+    }
+
+    fn entry(journal: u32, account: &'static str, asset: &'static str, amount: Decimal) -> Entry {
+        Entry { journal, account, asset, amount }
+    }
+
+    /// Journals whose entries do not net to zero per asset
+    fn unbalanced_journals(entries: &[Entry]) -> Vec<(u32, &'static str)> {
+        let mut sums: HashMap<(u32, &str), Decimal> = HashMap::new();
+        for e in entries {
+            *sums.entry((e.journal, e.asset)).or_default() += e.amount;
+        }
+        let mut out: Vec<_> = sums.into_iter().filter(|(_, s)| !s.is_zero()).map(|(k, _)| k).collect();
+        out.sort();
+        out
+    }
+
+    /// Balances that disagree with the sum of their entries (missing rows count as zero)
+    fn balance_mismatches(
+        balances: &HashMap<(&'static str, &'static str), Decimal>,
+        entries: &[Entry],
+    ) -> Vec<(&'static str, &'static str)> {
+        let mut sums: HashMap<(&str, &str), Decimal> = HashMap::new();
+        for e in entries {
+            *sums.entry((e.account, e.asset)).or_default() += e.amount;
+        }
+        let mut keys: Vec<_> = balances.keys().chain(sums.keys()).copied().collect();
+        keys.sort();
+        keys.dedup();
+        keys.into_iter()
+            .filter(|k| balances.get(k).copied().unwrap_or_default() != sums.get(k).copied().unwrap_or_default())
+            .collect()
+    }
+
+    /// Signed fills plus split adjustments (after - before)
+    fn expected_position(fills: &[(&str, Decimal)], adjustments: &[(Decimal, Decimal)]) -> Decimal {
+        let traded: Decimal = fills
+            .iter()
+            .map(|(side, qty)| if *side == "buy" { *qty } else { -*qty })
+            .sum();
+        let adjusted: Decimal = adjustments.iter().map(|(before, after)| after - before).sum();
+        traded + adjusted
+    }
+
+    #[test]
+    fn test_balanced_journals_pass() {
+        let entries = vec![
+            entry(1, "alice", "USD", dec!(-500)),
+            entry(1, "house", "USD", dec!(500)),
+            entry(1, "alice", "BTC", dec!(0.01)),
+            entry(1, "house", "BTC", dec!(-0.01)),
+        ];
+        assert!(unbalanced_journals(&entries).is_empty());
+    }
+
+    #[test]
+    fn test_unbalanced_asset_is_reported() {
+        let entries = vec![
+            entry(1, "alice", "USD", dec!(-500)),
+            entry(1, "house", "USD", dec!(500)),
+            entry(2, "alice", "BTC", dec!(0.01)),
+        ];
+        assert_eq!(unbalanced_journals(&entries), vec![(2, "BTC")]);
+    }
+
+    #[test]
+    fn test_balance_without_entries_is_a_mismatch() {
+        let entries = vec![
+            entry(1, "alice", "USD", dec!(100)),
+            entry(1, "house", "USD", dec!(-100)),
+        ];
+        let balances = HashMap::from([
+            (("alice", "USD"), dec!(100)),
+            (("house", "USD"), dec!(-100)),
+            (("bob", "USD"), dec!(5)),
+        ]);
+        assert_eq!(balance_mismatches(&balances, &entries), vec![("bob", "USD")]);
+    }
+
+    #[test]
+    fn test_entries_without_balance_row_are_a_mismatch() {
+        let entries = vec![
+            entry(1, "alice", "USD", dec!(100)),
+            entry(1, "house", "USD", dec!(-100)),
+        ];
+        let balances = HashMap::from([(("alice", "USD"), dec!(100))]);
+        assert_eq!(balance_mismatches(&balances, &entries), vec![("house", "USD")]);
+    }
+
+    #[test]
+    fn test_position_from_fills() {
+        let fills = [("buy", dec!(10)), ("sell", dec!(4)), ("buy", dec!(1))];
+        assert_eq!(expected_position(&fills, &[]), dec!(7));
+    }
+
+    #[test]
+    fn test_split_adjustment_reconciles_position() {
+        // 10 bought, 2-for-1 split, then 5 sold
+        let fills = [("buy", dec!(10)), ("sell", dec!(5))];
+        let adjustments = [(dec!(10), dec!(20))];
+        assert_eq!(expected_position(&fills, &adjustments), dec!(15));
+    }
+}
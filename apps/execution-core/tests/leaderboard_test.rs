@@ -0,0 +1,141 @@
+//! Unit Tests for Leaderboard Ranking
+//! Standalone tests for period boundaries, tie handling and privacy masking
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod leaderboard_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Period {
+        Day,
+        Week,
+        Month,
+        AllTime,
+    }
+
+    fn period_start(period: Period, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let midnight = Utc
+            .with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
+            .single()?;
+
+        match period {
+            Period::Day => Some(midnight),
+            Period::Week => {
+                Some(midnight - Duration::days(now.weekday().num_days_from_monday() as i64))
+            }
+            Period::Month => Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0).single(),
+            Period::AllTime => None,
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Standing {
+        display_name: String,
+        show_pnl: bool,
+        pnl: Decimal,
+    }
+
+    #[derive(Debug)]
+    struct Entry {
+        rank: u32,
+        display_name: String,
+        return_pct: Decimal,
+        pnl: Option<Decimal>,
+    }
+
+    fn rank(mut standings: Vec<Standing>, capital: Decimal, limit: usize) -> Vec<Entry> {
+        standings.sort_by(|a, b| {
+            b.pnl.cmp(&a.pnl).then_with(|| a.display_name.cmp(&b.display_name))
+        });
+
+        let mut entries = Vec::new();
+        let mut rank = 0u32;
+        let mut previous: Option<Decimal> = None;
+
+        for (index, s) in standings.into_iter().take(limit).enumerate() {
+            if previous != Some(s.pnl) {
+                rank = index as u32 + 1;
+                previous = Some(s.pnl);
+            }
+            entries.push(Entry {
+                rank,
+                display_name: s.display_name,
+                return_pct: (s.pnl / capital * dec!(100)).round_dp(4),
+                pnl: s.show_pnl.then_some(s.pnl),
+            });
+        }
+        entries
+    }
+
+    fn standing(name: &str, pnl: Decimal, show_pnl: bool) -> Standing {
+        Standing { display_name: name.to_string(), show_pnl, pnl }
+    }
+
+    #[test]
+    fn test_period_start_boundaries() {
+        // Thursday 2024-02-15 13:45 UTC
+        let now = Utc.with_ymd_and_hms(2024, 2, 15, 13, 45, 0).unwrap();
+
+        assert_eq!(period_start(Period::Day, now), Utc.with_ymd_and_hms(2024, 2, 15, 0, 0, 0).single());
+        assert_eq!(period_start(Period::Week, now), Utc.with_ymd_and_hms(2024, 2, 12, 0, 0, 0).single());
+        assert_eq!(period_start(Period::Month, now), Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).single());
+        assert_eq!(period_start(Period::AllTime, now), None);
+    }
+
+    #[test]
+    fn test_ranking_orders_by_pnl_descending() {
+        let entries = rank(
+            vec![
+                standing("alice", dec!(500), true),
+                standing("bob", dec!(1500), true),
+                standing("carol", dec!(-200), true),
+            ],
+            dec!(100000),
+            10,
+        );
+
+        let names: Vec<_> = entries.iter().map(|e| e.display_name.as_str()).collect();
+        assert_eq!(names, vec!["bob", "alice", "carol"]);
+        assert_eq!(entries[0].return_pct, dec!(1.5));
+        assert_eq!(entries[2].return_pct, dec!(-0.2));
+    }
+
+    #[test]
+    fn test_ties_share_rank() {
+        let entries = rank(
+            vec![
+                standing("a", dec!(100), true),
+                standing("b", dec!(100), true),
+                standing("c", dec!(50), true),
+            ],
+            dec!(100000),
+            10,
+        );
+
+        let ranks: Vec<_> = entries.iter().map(|e| e.rank).collect();
+        assert_eq!(ranks, vec![1, 1, 3]);
+    }
+
+    #[test]
+    fn test_hidden_pnl_only_shows_return() {
+        let entries = rank(vec![standing("private", dec!(2500), false)], dec!(100000), 10);
+
+        assert_eq!(entries[0].pnl, None);
+        assert_eq!(entries[0].return_pct, dec!(2.5));
+    }
+
+    #[test]
+    fn test_limit_truncates_entries() {
+        let standings = (0..20)
+            .map(|i| standing(&format!("t{:02}", i), Decimal::from(i), true))
+            .collect();
+
+        let entries = rank(standings, dec!(100000), 5);
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].display_name, "t19");
+    }
+}
@@ -0,0 +1,67 @@
+//! Unit Tests for Lifecycle Events
+//! Standalone tests for the event payload published on system.lifecycle
+
+#[cfg(test)]
+mod lifecycle_tests {
+    use serde::Serialize;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    /// Mirror of `Phase`
+    #[derive(Debug, Clone, Copy, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Phase {
+        Starting,
+        StateLoaded,
+        Ready,
+        Draining,
+        Stopped,
+    }
+
+    /// Mirror of `LifecycleEvent`, without the timestamp
+    #[derive(Serialize)]
+    struct LifecycleEvent<'a> {
+        event: Phase,
+        instance_id: &'a str,
+        version: &'static str,
+        profile: &'a str,
+        uptime_ms: u64,
+        counts: BTreeMap<&'static str, u64>,
+    }
+
+    fn event(phase: Phase, counts: &[(&'static str, u64)]) -> serde_json::Value {
+        serde_json::to_value(LifecycleEvent {
+            event: phase,
+            instance_id: "execution-core-7d9f-x2",
+            version: "1.0.0",
+            profile: "production",
+            uptime_ms: 1_250,
+            counts: counts.iter().copied().collect(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_phase_names() {
+        let names: Vec<_> = [Phase::Starting, Phase::StateLoaded, Phase::Ready, Phase::Draining, Phase::Stopped]
+            .into_iter()
+            .map(|phase| serde_json::to_value(phase).unwrap())
+            .collect();
+        assert_eq!(names, vec![json!("starting"), json!("state_loaded"), json!("ready"), json!("draining"), json!("stopped")]);
+    }
+
+    #[test]
+    fn test_state_loaded_payload() {
+        let payload = event(Phase::StateLoaded, &[("open_orders", 42), ("positions", 7)]);
+        assert_eq!(payload["event"], "state_loaded");
+        assert_eq!(payload["instance_id"], "execution-core-7d9f-x2");
+        assert_eq!(payload["counts"], json!({ "open_orders": 42, "positions": 7 }));
+    }
+
+    #[test]
+    fn test_starting_has_empty_counts() {
+        let payload = event(Phase::Starting, &[]);
+        assert_eq!(payload["counts"], json!({}));
+        assert_eq!(payload["uptime_ms"], 1_250);
+    }
+}
@@ -0,0 +1,150 @@
+//! Unit Tests for Load Shedding
+//! Standalone tests for graduated shedding levels and hysteresis
+
+#[cfg(test)]
+mod load_shedder_tests {
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    enum Level {
+        Normal,
+        Queries,
+        Orders,
+    }
+
+    #[derive(Clone, Copy)]
+    struct Signals {
+        lag: Duration,
+        queue: usize,
+        db: Duration,
+    }
+
+    struct Thresholds {
+        lag: Duration,
+        queue: usize,
+        db: Duration,
+    }
+
+    impl Thresholds {
+        fn exceeded_by(&self, s: &Signals, ratio: f64) -> bool {
+            s.lag >= self.lag.mul_f64(ratio)
+                || s.queue as f64 >= self.queue as f64 * ratio
+                || s.db >= self.db.mul_f64(ratio)
+        }
+    }
+
+    const RECOVER: f64 = 0.7;
+
+    fn queries() -> Thresholds {
+        Thresholds { lag: Duration::from_millis(100), queue: 500, db: Duration::from_millis(250) }
+    }
+
+    fn orders() -> Thresholds {
+        Thresholds { lag: Duration::from_millis(500), queue: 2_000, db: Duration::from_secs(1) }
+    }
+
+    /// Mirror of `target_level` in the load shedder
+    fn target_level(current: Level, s: &Signals) -> Level {
+        let level_at = |ratio: f64| {
+            if orders().exceeded_by(s, ratio) {
+                Level::Orders
+            } else if queries().exceeded_by(s, ratio) {
+                Level::Queries
+            } else {
+                Level::Normal
+            }
+        };
+        let raised = level_at(1.0);
+        if raised >= current {
+            return raised;
+        }
+        level_at(RECOVER).min(current)
+    }
+
+    fn admits_order(level: Level) -> bool {
+        level < Level::Orders
+    }
+
+    fn admits_query(level: Level) -> bool {
+        level < Level::Queries
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Priority {
+        Critical,
+        Order,
+        Query,
+    }
+
+    /// Mirror of `LoadShedder::admit`
+    fn admit(level: Level, priority: Priority) -> bool {
+        match priority {
+            Priority::Critical => true,
+            Priority::Order => admits_order(level),
+            Priority::Query => admits_query(level),
+        }
+    }
+
+    /// Mirror of the priorities the subscriber's handlers pass to `shed`
+    fn priority_of(subject: &str) -> Priority {
+        match subject {
+            "orders.cancel" | "orders.cancel_all" | "orders.reduce" => Priority::Critical,
+            "orders.submit" | "orders.amend" | "orders.strategy.submit" | "orders.twap.submit" => Priority::Order,
+            _ => Priority::Query,
+        }
+    }
+
+    fn signals(lag_ms: u64, queue: usize, db_ms: u64) -> Signals {
+        Signals { lag: Duration::from_millis(lag_ms), queue, db: Duration::from_millis(db_ms) }
+    }
+
+    #[test]
+    fn test_idle_engine_is_normal() {
+        assert_eq!(target_level(Level::Normal, &signals(1, 0, 2)), Level::Normal);
+    }
+
+    #[test]
+    fn test_any_signal_escalates() {
+        assert_eq!(target_level(Level::Normal, &signals(150, 0, 2)), Level::Queries);
+        assert_eq!(target_level(Level::Normal, &signals(1, 600, 2)), Level::Queries);
+        assert_eq!(target_level(Level::Normal, &signals(1, 0, 300)), Level::Queries);
+        assert_eq!(target_level(Level::Normal, &signals(1, 2_500, 2)), Level::Orders);
+    }
+
+    #[test]
+    fn test_escalation_skips_levels() {
+        assert_eq!(target_level(Level::Normal, &signals(800, 0, 0)), Level::Orders);
+    }
+
+    #[test]
+    fn test_hysteresis_holds_level_between_bars() {
+        // Below the queries threshold (100ms) but above 70% of it
+        assert_eq!(target_level(Level::Queries, &signals(80, 0, 0)), Level::Queries);
+        assert_eq!(target_level(Level::Queries, &signals(60, 0, 0)), Level::Normal);
+    }
+
+    #[test]
+    fn test_orders_step_down_to_queries() {
+        // Under the order bar's recovery point, still over the query threshold
+        assert_eq!(target_level(Level::Orders, &signals(200, 0, 0)), Level::Queries);
+        assert_eq!(target_level(Level::Orders, &signals(400, 0, 0)), Level::Orders);
+    }
+
+    #[test]
+    fn test_graduated_admission() {
+        assert!(admits_query(Level::Normal) && admits_order(Level::Normal));
+        assert!(!admits_query(Level::Queries) && admits_order(Level::Queries));
+        assert!(!admits_query(Level::Orders) && !admits_order(Level::Orders));
+    }
+
+    #[test]
+    fn test_risk_reducing_requests_are_never_shed() {
+        for subject in ["orders.cancel", "orders.cancel_all", "orders.reduce"] {
+            for level in [Level::Normal, Level::Queries, Level::Orders] {
+                assert!(admit(level, priority_of(subject)), "{} shed at {:?}", subject, level);
+            }
+        }
+        assert!(!admit(Level::Orders, priority_of("orders.submit")));
+        assert!(!admit(Level::Queries, priority_of("orders.history")));
+    }
+}
@@ -0,0 +1,191 @@
+//! Unit Tests for Market Order Execution
+//! Standalone tests for the slippage models, simulated latency and which orders a tick executes
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::MathematicalOps;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod market_order_tests {
+    use super::*;
+
+    const MAX_SLIPPAGE_BPS: Decimal = dec!(1000);
+
+    #[derive(Clone, Copy)]
+    enum Mode {
+        Fixed,
+        Spread,
+        VolumeImpact,
+    }
+
+    struct Slippage {
+        mode: Mode,
+        base_bps: Decimal,
+        impact_bps_per_unit: Decimal,
+        spread_bps: Decimal,
+        volume_impact_bps: Decimal,
+    }
+
+    impl Slippage {
+        fn none() -> Self {
+            Self {
+                mode: Mode::Fixed,
+                base_bps: dec!(0),
+                impact_bps_per_unit: dec!(0),
+                spread_bps: dec!(0),
+                volume_impact_bps: dec!(0),
+            }
+        }
+
+        /// Mirror of `SlippageModel::bps`
+        fn bps(&self, quantity: Decimal, traded: Option<Decimal>) -> Decimal {
+            let fixed = || self.base_bps + self.impact_bps_per_unit * quantity.abs();
+            let bps = match self.mode {
+                Mode::Fixed => fixed(),
+                Mode::Spread => self.base_bps + self.spread_bps / dec!(2),
+                Mode::VolumeImpact => match traded.filter(|t| *t > dec!(0)) {
+                    Some(traded) => {
+                        let share = (quantity.abs() / traded).sqrt().unwrap_or_default();
+                        self.base_bps + self.volume_impact_bps * share
+                    }
+                    None => fixed(),
+                },
+            };
+            bps.clamp(dec!(0), MAX_SLIPPAGE_BPS)
+        }
+
+        /// Mirror of `SlippageModel::fill_price`
+        fn fill_price(&self, side: &str, quantity: Decimal, tick: Decimal, traded: Option<Decimal>) -> Decimal {
+            let cost = tick * self.bps(quantity, traded) / dec!(10000);
+            let price = if side == "buy" { tick + cost } else { tick - cost };
+            price.round_dp(8)
+        }
+    }
+
+    struct Order {
+        side: &'static str,
+        order_type: &'static str,
+        quantity: Decimal,
+        price: Option<Decimal>,
+    }
+
+    /// Mirror of `execution_price`
+    fn execution_price(order: &Order, tick: Decimal, slippage: &Slippage) -> Option<Decimal> {
+        let fill = if order.order_type == "market" {
+            slippage.fill_price(order.side, order.quantity, tick, None)
+        } else {
+            tick
+        };
+        match (order.side, order.price) {
+            ("buy", Some(limit)) => (fill <= limit).then_some(fill),
+            ("sell", Some(limit)) => (fill >= limit).then_some(fill),
+            (_, None) if order.order_type == "market" => Some(fill),
+            _ => None,
+        }
+    }
+
+    fn market(side: &'static str, quantity: Decimal) -> Order {
+        Order { side, order_type: "market", quantity, price: None }
+    }
+
+    #[test]
+    fn test_market_orders_fill_at_tick_without_slippage() {
+        let none = Slippage::none();
+        assert_eq!(execution_price(&market("buy", dec!(5)), dec!(100), &none), Some(dec!(100)));
+        assert_eq!(execution_price(&market("sell", dec!(5)), dec!(100), &none), Some(dec!(100)));
+    }
+
+    #[test]
+    fn test_slippage_moves_price_against_the_order() {
+        let model = Slippage { base_bps: dec!(10), impact_bps_per_unit: dec!(0), ..Slippage::none() };
+        assert_eq!(execution_price(&market("buy", dec!(1)), dec!(100), &model), Some(dec!(100.1)));
+        assert_eq!(execution_price(&market("sell", dec!(1)), dec!(100), &model), Some(dec!(99.9)));
+    }
+
+    #[test]
+    fn test_impact_grows_with_quantity() {
+        let model = Slippage { base_bps: dec!(5), impact_bps_per_unit: dec!(1), ..Slippage::none() };
+        assert_eq!(model.fill_price("buy", dec!(10), dec!(100), None), dec!(100.15));
+        assert_eq!(model.fill_price("buy", dec!(100), dec!(100), None), dec!(101.05));
+    }
+
+    #[test]
+    fn test_slippage_is_capped() {
+        let model = Slippage { base_bps: dec!(0), impact_bps_per_unit: dec!(100), ..Slippage::none() };
+        assert_eq!(model.fill_price("sell", dec!(1000), dec!(100), None), dec!(90));
+        assert_eq!(model.fill_price("buy", dec!(1000), dec!(100), None), dec!(110));
+    }
+
+    #[test]
+    fn test_protective_limit_holds_back_slipped_market_orders() {
+        let model = Slippage { base_bps: dec!(50), impact_bps_per_unit: dec!(0), ..Slippage::none() };
+        let protected = Order { price: Some(dec!(100.25)), ..market("buy", dec!(1)) };
+        // 100 + 0.5% = 100.5 breaches the 100.25 protection
+        assert_eq!(execution_price(&protected, dec!(100), &model), None);
+        // A lower tick brings the slipped price back inside it
+        assert_eq!(execution_price(&protected, dec!(99.5), &model), Some(dec!(99.9975)));
+    }
+
+    #[test]
+    fn test_limit_orders_are_unchanged() {
+        let model = Slippage { base_bps: dec!(50), impact_bps_per_unit: dec!(1), ..Slippage::none() };
+        let buy = Order { side: "buy", order_type: "limit", quantity: dec!(1), price: Some(dec!(100)) };
+        let sell = Order { side: "sell", order_type: "limit", quantity: dec!(1), price: Some(dec!(100)) };
+
+        assert_eq!(execution_price(&buy, dec!(99), &model), Some(dec!(99)));
+        assert_eq!(execution_price(&buy, dec!(101), &model), None);
+        assert_eq!(execution_price(&sell, dec!(101), &model), Some(dec!(101)));
+        assert_eq!(execution_price(&sell, dec!(99), &model), None);
+    }
+
+    #[test]
+    fn test_unpriced_non_market_orders_never_fill() {
+        let stop = Order { side: "buy", order_type: "stop", quantity: dec!(1), price: None };
+        assert_eq!(execution_price(&stop, dec!(100), &Slippage::none()), None);
+    }
+
+    #[test]
+    fn test_spread_model_crosses_half_the_spread() {
+        let model = Slippage { mode: Mode::Spread, base_bps: dec!(1), spread_bps: dec!(20), ..Slippage::none() };
+        // Size does not matter: 1 + 20 / 2 = 11 bps either way
+        assert_eq!(model.fill_price("buy", dec!(1), dec!(100), None), dec!(100.11));
+        assert_eq!(model.fill_price("sell", dec!(500), dec!(100), Some(dec!(1))), dec!(99.89));
+    }
+
+    #[test]
+    fn test_volume_impact_grows_with_share_of_traded_size() {
+        let model = Slippage { mode: Mode::VolumeImpact, volume_impact_bps: dec!(40), ..Slippage::none() };
+        // A quarter of the traded size pays half the full impact, four times it pays double
+        assert_eq!(model.bps(dec!(25), Some(dec!(100))), dec!(20));
+        assert_eq!(model.bps(dec!(400), Some(dec!(100))), dec!(80));
+        assert_eq!(model.fill_price("buy", dec!(100), dec!(100), Some(dec!(100))), dec!(100.4));
+    }
+
+    #[test]
+    fn test_volume_impact_without_traded_size_falls_back_to_fixed() {
+        let model = Slippage {
+            mode: Mode::VolumeImpact,
+            base_bps: dec!(5),
+            impact_bps_per_unit: dec!(1),
+            volume_impact_bps: dec!(40),
+            ..Slippage::none()
+        };
+        assert_eq!(model.bps(dec!(10), None), dec!(15));
+        assert_eq!(model.bps(dec!(10), Some(dec!(0))), dec!(15));
+    }
+
+    /// Mirror of `SlippageModel::arrived`
+    fn arrived(placed_at: DateTime<Utc>, latency: Duration, now: DateTime<Utc>) -> bool {
+        placed_at + latency <= now
+    }
+
+    #[test]
+    fn test_orders_wait_out_the_latency() {
+        let placed = Utc::now();
+        let latency = Duration::milliseconds(250);
+        assert!(!arrived(placed, latency, placed + Duration::milliseconds(100)));
+        assert!(arrived(placed, latency, placed + Duration::milliseconds(250)));
+        assert!(arrived(placed, Duration::zero(), placed));
+    }
+}
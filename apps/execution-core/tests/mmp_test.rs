@@ -0,0 +1,137 @@
+//! Unit Tests for Market-Maker Protection
+//! Standalone tests for the rolling fill window, when it trips and when it resets
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::VecDeque;
+
+#[cfg(test)]
+mod mmp_tests {
+    use super::*;
+
+    type FillWindow = VecDeque<(DateTime<Utc>, Decimal)>;
+
+    #[derive(Default)]
+    struct Settings {
+        fill_limit: Option<usize>,
+        quantity_limit: Option<Decimal>,
+        window_secs: i64,
+        freeze_secs: Option<i64>,
+        triggered_at: Option<DateTime<Utc>>,
+    }
+
+    impl Settings {
+        /// Mirror of `MmpSettings::resets_at`
+        fn resets_at(&self) -> Option<DateTime<Utc>> {
+            Some(self.triggered_at? + Duration::seconds(self.freeze_secs?))
+        }
+
+        /// Mirror of `MmpSettings::blocks`
+        fn blocks(&self, now: DateTime<Utc>) -> bool {
+            self.triggered_at.is_some() && self.resets_at().is_none_or(|at| at > now)
+        }
+
+        /// Mirror of `MmpSettings::breached`
+        fn breached(&self, fills: &FillWindow) -> Option<&'static str> {
+            if self.fill_limit.is_some_and(|limit| fills.len() > limit) {
+                return Some("fills");
+            }
+            let quantity: Decimal = fills.iter().map(|(_, q)| *q).sum();
+            if self.quantity_limit.is_some_and(|limit| quantity > limit) {
+                return Some("quantity");
+            }
+            None
+        }
+    }
+
+    /// Mirror of `prune`
+    fn prune(fills: &mut FillWindow, window_secs: i64, now: DateTime<Utc>) {
+        let start = now - Duration::seconds(window_secs);
+        while fills.front().is_some_and(|(at, _)| *at <= start) {
+            fills.pop_front();
+        }
+    }
+
+    /// Mirror of `MarketMakerProtection::record_fill`
+    fn record_fill(settings: &mut Settings, fills: &mut FillWindow, now: DateTime<Utc>, quantity: Decimal) -> Option<&'static str> {
+        if settings.blocks(now) {
+            return None;
+        }
+        fills.push_back((now, quantity));
+        prune(fills, settings.window_secs, now);
+        let reason = settings.breached(fills)?;
+        fills.clear();
+        settings.triggered_at = Some(now);
+        Some(reason)
+    }
+
+    fn at(second: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap() + Duration::seconds(second)
+    }
+
+    #[test]
+    fn test_fill_count_trips_within_window() {
+        let mut settings = Settings { fill_limit: Some(2), window_secs: 10, ..Settings::default() };
+        let mut fills = FillWindow::new();
+        assert_eq!(record_fill(&mut settings, &mut fills, at(0), dec!(1)), None);
+        assert_eq!(record_fill(&mut settings, &mut fills, at(5), dec!(1)), None);
+        assert_eq!(record_fill(&mut settings, &mut fills, at(9), dec!(1)), Some("fills"));
+        assert_eq!(settings.triggered_at, Some(at(9)));
+        assert!(fills.is_empty());
+    }
+
+    #[test]
+    fn test_fills_outside_window_do_not_count() {
+        let mut settings = Settings { fill_limit: Some(2), window_secs: 10, ..Settings::default() };
+        let mut fills = FillWindow::new();
+        record_fill(&mut settings, &mut fills, at(0), dec!(1));
+        record_fill(&mut settings, &mut fills, at(5), dec!(1));
+        // The first fill left the window exactly ten seconds later
+        assert_eq!(record_fill(&mut settings, &mut fills, at(10), dec!(1)), None);
+        assert_eq!(fills.len(), 2);
+    }
+
+    #[test]
+    fn test_quantity_trips_within_window() {
+        let mut settings = Settings { quantity_limit: Some(dec!(100)), window_secs: 60, ..Settings::default() };
+        let mut fills = FillWindow::new();
+        assert_eq!(record_fill(&mut settings, &mut fills, at(0), dec!(60)), None);
+        assert_eq!(record_fill(&mut settings, &mut fills, at(30), dec!(40)), None);
+        assert_eq!(record_fill(&mut settings, &mut fills, at(59), dec!(0.5)), Some("quantity"));
+    }
+
+    #[test]
+    fn test_freeze_resets_on_its_own() {
+        let mut settings = Settings { fill_limit: Some(1), window_secs: 10, freeze_secs: Some(30), ..Settings::default() };
+        let mut fills = FillWindow::new();
+        record_fill(&mut settings, &mut fills, at(0), dec!(1));
+        assert_eq!(record_fill(&mut settings, &mut fills, at(1), dec!(1)), Some("fills"));
+
+        assert!(settings.blocks(at(30)));
+        assert!(!settings.blocks(at(31)));
+        assert_eq!(settings.resets_at(), Some(at(31)));
+    }
+
+    #[test]
+    fn test_without_freeze_a_reset_is_required() {
+        let mut settings = Settings { fill_limit: Some(1), window_secs: 10, ..Settings::default() };
+        let mut fills = FillWindow::new();
+        record_fill(&mut settings, &mut fills, at(0), dec!(1));
+        record_fill(&mut settings, &mut fills, at(1), dec!(1));
+        assert!(settings.blocks(at(86_400)));
+
+        settings.triggered_at = None;
+        assert!(!settings.blocks(at(86_400)));
+    }
+
+    #[test]
+    fn test_fills_while_triggered_are_not_counted() {
+        let mut settings = Settings { fill_limit: Some(1), window_secs: 10, ..Settings::default() };
+        let mut fills = FillWindow::new();
+        record_fill(&mut settings, &mut fills, at(0), dec!(1));
+        record_fill(&mut settings, &mut fills, at(1), dec!(1));
+        assert_eq!(record_fill(&mut settings, &mut fills, at(2), dec!(1)), None);
+        assert!(fills.is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+//! Unit Tests for Multi-Leg Strategies
+//! Standalone tests for net and per-leg strategy pricing
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod multi_leg_tests {
+    use super::*;
+
+    struct Leg {
+        side: &'static str,
+        quantity: Decimal,
+        price: Option<Decimal>,
+    }
+
+    fn marketable(quantity: Decimal, net_price: Option<Decimal>, legs: &[Leg], prices: &[Decimal]) -> bool {
+        let mut legs = legs.iter().zip(prices);
+        match net_price {
+            Some(limit) => {
+                let net_debit: Decimal = legs
+                    .map(|(leg, price)| {
+                        let per_unit = leg.quantity / quantity * price;
+                        if leg.side == "buy" { per_unit } else { -per_unit }
+                    })
+                    .sum();
+                net_debit <= limit
+            }
+            None => legs.all(|(leg, price)| match (leg.side, leg.price) {
+                ("buy", Some(limit)) => *price <= limit,
+                ("sell", Some(limit)) => *price >= limit,
+                _ => true,
+            }),
+        }
+    }
+
+    fn pair_trade(buy_price: Option<Decimal>, sell_price: Option<Decimal>) -> Vec<Leg> {
+        // 10 units of: buy 1 A, sell 2 B
+        vec![
+            Leg { side: "buy", quantity: dec!(10), price: buy_price },
+            Leg { side: "sell", quantity: dec!(20), price: sell_price },
+        ]
+    }
+
+    #[test]
+    fn test_net_debit_within_limit() {
+        let legs = pair_trade(None, None);
+        // Per unit: 100 - 2 * 45 = 10 debit
+        assert!(marketable(dec!(10), Some(dec!(10)), &legs, &[dec!(100), dec!(45)]));
+        assert!(!marketable(dec!(10), Some(dec!(9.99)), &legs, &[dec!(100), dec!(45)]));
+    }
+
+    #[test]
+    fn test_net_credit_limit() {
+        let legs = pair_trade(None, None);
+        // Per unit: 100 - 2 * 55 = -10 (a credit of 10); require at least 5 credit
+        assert!(marketable(dec!(10), Some(dec!(-5)), &legs, &[dec!(100), dec!(55)]));
+        assert!(!marketable(dec!(10), Some(dec!(-15)), &legs, &[dec!(100), dec!(55)]));
+    }
+
+    #[test]
+    fn test_leg_prices_require_every_leg_marketable() {
+        let legs = pair_trade(Some(dec!(100)), Some(dec!(50)));
+        assert!(marketable(dec!(10), None, &legs, &[dec!(99), dec!(51)]));
+        assert!(!marketable(dec!(10), None, &legs, &[dec!(99), dec!(49)]));
+        assert!(!marketable(dec!(10), None, &legs, &[dec!(101), dec!(51)]));
+    }
+
+    #[test]
+    fn test_unpriced_legs_fill_at_market() {
+        let legs = pair_trade(None, None);
+        assert!(marketable(dec!(10), None, &legs, &[dec!(1000), dec!(1)]));
+    }
+}
@@ -0,0 +1,228 @@
+//! Unit Tests for Position Netting
+//! Standalone tests for asset classes, netting rules and the margin of netted positions
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod netting_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    enum Class {
+        Spot,
+        Future,
+        Perpetual,
+    }
+
+    fn parse_class(s: &str) -> Option<Class> {
+        match s {
+            "spot" => Some(Class::Spot),
+            "future" | "futures" => Some(Class::Future),
+            "perpetual" | "perp" => Some(Class::Perpetual),
+            _ => None,
+        }
+    }
+
+    /// Mirror of `classify`
+    fn classify(symbol: &str) -> (Class, String) {
+        let upper = symbol.to_uppercase();
+        if let Some((underlying, _)) = upper.split_once("-FUT") {
+            return (Class::Future, underlying.to_string());
+        }
+        if let Some(underlying) = upper.strip_suffix("-PERP").or_else(|| upper.strip_suffix("PERP")) {
+            return (Class::Perpetual, underlying.to_string());
+        }
+        (Class::Spot, upper.split(['/', '-']).next().unwrap_or_default().to_string())
+    }
+
+    fn relates(rule: (Class, Class), a: Class, b: Class) -> bool {
+        (rule.0 == a && rule.1 == b) || (rule.0 == b && rule.1 == a)
+    }
+
+    /// Mirror of `parse_netting_rules`
+    fn parse_rules(spec: &str) -> Result<Vec<(Class, Class)>, String> {
+        let mut rules: Vec<(Class, Class)> = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (a, b) = entry.split_once('+').ok_or("not class+class")?;
+            let a = parse_class(a.trim()).ok_or("unknown class")?;
+            let b = parse_class(b.trim()).ok_or("unknown class")?;
+            if rules.iter().any(|r| relates(*r, a, b)) {
+                return Err("duplicate".into());
+            }
+            rules.push((a, b));
+        }
+        Ok(rules)
+    }
+
+    /// Mirror of `NettingEngine::group_of`
+    fn group_of(rules: &[(Class, Class)], class: Class) -> Vec<Class> {
+        let mut group = vec![class];
+        let mut i = 0;
+        while i < group.len() {
+            for rule in rules {
+                for (from, to) in [(rule.0, rule.1), (rule.1, rule.0)] {
+                    if from == group[i] && !group.contains(&to) {
+                        group.push(to);
+                    }
+                }
+            }
+            i += 1;
+        }
+        group.sort();
+        group
+    }
+
+    #[derive(Debug)]
+    struct Set {
+        underlying: String,
+        symbols: Vec<String>,
+        derivatives: Decimal,
+        spot: Decimal,
+        has_derivatives: bool,
+        exposure: Decimal,
+    }
+
+    /// Mirror of `NettingEngine::netting_sets`
+    fn netting_sets(rules: &[(Class, Class)], exposures: &[(&str, Decimal)]) -> Vec<Set> {
+        let mut sets: Vec<(Set, Option<Vec<Class>>)> = Vec::new();
+
+        for (symbol, notional) in exposures.iter().filter(|(_, n)| !n.is_zero()) {
+            let (class, underlying) = classify(symbol);
+            let group = group_of(rules, class);
+            let nets = group.len() > 1 || rules.iter().any(|r| relates(*r, class, class));
+            let key = nets.then_some(group);
+
+            let existing = key.as_ref().and_then(|key| {
+                sets.iter().position(|(s, k)| s.underlying == underlying && k.as_ref() == Some(key))
+            });
+            let index = existing.unwrap_or_else(|| {
+                let set = Set {
+                    underlying: underlying.clone(),
+                    symbols: Vec::new(),
+                    derivatives: dec!(0),
+                    spot: dec!(0),
+                    has_derivatives: false,
+                    exposure: dec!(0),
+                };
+                sets.push((set, key));
+                sets.len() - 1
+            });
+
+            let set = &mut sets[index].0;
+            set.symbols.push(symbol.to_string());
+            if class != Class::Spot {
+                set.derivatives += notional;
+                set.has_derivatives = true;
+            } else {
+                set.spot += notional;
+            }
+        }
+
+        sets.into_iter()
+            .map(|(set, _)| set)
+            .filter(|set| set.has_derivatives)
+            .map(|mut set| {
+                // Mirror of `Offsets::exposure`
+                let hedged = set.spot.is_sign_negative() != set.derivatives.is_sign_negative();
+                set.exposure = if hedged {
+                    (set.derivatives.abs() - set.spot.abs()).max(dec!(0))
+                } else {
+                    set.derivatives.abs()
+                };
+                set
+            })
+            .collect()
+    }
+
+    fn net_exposure(rules: &[(Class, Class)], exposures: &[(&str, Decimal)]) -> Decimal {
+        netting_sets(rules, exposures).iter().map(|s| s.exposure).sum()
+    }
+
+    #[test]
+    fn test_classify_symbols() {
+        assert_eq!(classify("BTC-PERP"), (Class::Perpetual, "BTC".into()));
+        assert_eq!(classify("ethperp"), (Class::Perpetual, "ETH".into()));
+        assert_eq!(classify("BTC-FUT-DEC24"), (Class::Future, "BTC".into()));
+        assert_eq!(classify("BTC/USD"), (Class::Spot, "BTC".into()));
+        assert_eq!(classify("AAPL"), (Class::Spot, "AAPL".into()));
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(parse_rules("future+perpetual"), Ok(vec![(Class::Future, Class::Perpetual)]));
+        assert_eq!(
+            parse_rules(" futures+perp , spot+future "),
+            Ok(vec![(Class::Future, Class::Perpetual), (Class::Spot, Class::Future)])
+        );
+        assert_eq!(parse_rules(""), Ok(vec![]));
+        assert!(parse_rules("future").is_err());
+        assert!(parse_rules("future+bond").is_err());
+        assert!(parse_rules("future+perpetual,perpetual+future").is_err());
+    }
+
+    #[test]
+    fn test_future_and_perpetual_net_when_related() {
+        let hedge = [("BTC-FUT-DEC24", dec!(10000)), ("BTC-PERP", dec!(-9000))];
+        let rules = parse_rules("future+perpetual").unwrap();
+
+        let sets = netting_sets(&rules, &hedge);
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].symbols, vec!["BTC-FUT-DEC24", "BTC-PERP"]);
+        assert_eq!(sets[0].exposure, dec!(1000));
+
+        // Without the rule both legs are margined in full
+        assert_eq!(net_exposure(&[], &hedge), dec!(19000));
+    }
+
+    #[test]
+    fn test_different_underlyings_never_net() {
+        let rules = parse_rules("future+perpetual").unwrap();
+        let exposures = [("BTC-FUT-DEC24", dec!(10000)), ("ETH-PERP", dec!(-10000))];
+        assert_eq!(net_exposure(&rules, &exposures), dec!(20000));
+    }
+
+    #[test]
+    fn test_same_class_nets_only_with_a_self_rule() {
+        let calendar = [("BTC-FUT-DEC24", dec!(5000)), ("BTC-FUT-MAR25", dec!(-5000))];
+        assert_eq!(net_exposure(&[], &calendar), dec!(10000));
+        assert_eq!(net_exposure(&parse_rules("future+future").unwrap(), &calendar), dec!(0));
+    }
+
+    #[test]
+    fn test_spot_hedge_reduces_but_never_adds_margin() {
+        let rules = parse_rules("spot+perpetual").unwrap();
+
+        let hedged = [("BTC/USD", dec!(8000)), ("BTC-PERP", dec!(-10000))];
+        assert_eq!(net_exposure(&rules, &hedged), dec!(2000));
+
+        // Spot beyond the hedge is pre-funded and needs no margin
+        let overhedged = [("BTC/USD", dec!(50000)), ("BTC-PERP", dec!(-10000))];
+        assert_eq!(net_exposure(&rules, &overhedged), dec!(0));
+
+        // Spot on the same side as the derivative hedges nothing
+        let doubled = [("BTC/USD", dec!(8000)), ("BTC-PERP", dec!(10000))];
+        assert_eq!(net_exposure(&rules, &doubled), dec!(10000));
+    }
+
+    #[test]
+    fn test_spot_only_positions_need_no_margin() {
+        let rules = parse_rules("spot+future").unwrap();
+        assert!(netting_sets(&rules, &[("BTC/USD", dec!(8000)), ("AAPL", dec!(500))]).is_empty());
+    }
+
+    #[test]
+    fn test_rules_chain_through_a_shared_class() {
+        let rules = parse_rules("spot+future,future+perpetual").unwrap();
+        assert_eq!(group_of(&rules, Class::Spot), vec![Class::Spot, Class::Future, Class::Perpetual]);
+
+        let exposures = [("BTC/USD", dec!(3000)), ("BTC-PERP", dec!(-3000))];
+        assert_eq!(net_exposure(&rules, &exposures), dec!(0));
+    }
+
+    #[test]
+    fn test_flat_positions_are_skipped() {
+        let rules = parse_rules("future+perpetual").unwrap();
+        assert!(netting_sets(&rules, &[("BTC-PERP", dec!(0))]).is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+//! Unit Tests for One-Cancels-Other Groups
+//! Standalone tests for linking two orders and cancelling the sibling on the first fill
+
+#[cfg(test)]
+mod oco_tests {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Status {
+        Pending,
+        PartiallyFilled,
+        Filled,
+        Cancelled,
+    }
+
+    #[derive(Debug, Clone)]
+    struct Order {
+        id: u32,
+        account: u32,
+        symbol: &'static str,
+        status: Status,
+        strategy: bool,
+        group: Option<u32>,
+    }
+
+    struct Group {
+        id: u32,
+        active: bool,
+        filled_order: Option<u32>,
+    }
+
+    fn order(id: u32, symbol: &'static str) -> Order {
+        Order { id, account: 1, symbol, status: Status::Pending, strategy: false, group: None }
+    }
+
+    /// Mirror of `link_oco`
+    fn link(orders: &mut [Order], groups: &mut Vec<Group>, new_id: u32, sibling_id: u32) -> Result<u32, &'static str> {
+        let new = orders.iter().find(|o| o.id == new_id).cloned().unwrap();
+        let sibling = orders.iter()
+            .find(|o| o.id == sibling_id && o.account == new.account)
+            .ok_or("not found")?;
+        if sibling.symbol != new.symbol {
+            return Err("symbol");
+        }
+        if sibling.status != Status::Pending {
+            return Err("not open");
+        }
+        if sibling.strategy {
+            return Err("strategy leg");
+        }
+        if sibling.group.is_some() {
+            return Err("already grouped");
+        }
+
+        let group_id = groups.len() as u32 + 1;
+        groups.push(Group { id: group_id, active: true, filled_order: None });
+        for o in orders.iter_mut().filter(|o| o.id == new_id || o.id == sibling_id) {
+            o.group = Some(group_id);
+        }
+        Ok(group_id)
+    }
+
+    /// Mirror of `cancel_oco_siblings`, run by every fill of a grouped order
+    fn fill(orders: &mut [Order], groups: &mut [Group], id: u32, completes: bool) -> Vec<u32> {
+        let filled = orders.iter_mut().find(|o| o.id == id).unwrap();
+        filled.status = if completes { Status::Filled } else { Status::PartiallyFilled };
+        let Some(group_id) = filled.group else {
+            return Vec::new();
+        };
+
+        let group = groups.iter_mut().find(|g| g.id == group_id).unwrap();
+        if !group.active {
+            return Vec::new();
+        }
+        group.active = false;
+        group.filled_order = Some(id);
+
+        let mut cancelled = Vec::new();
+        for o in orders.iter_mut().filter(|o| o.group == Some(group_id) && o.id != id) {
+            if matches!(o.status, Status::Pending | Status::PartiallyFilled) {
+                o.status = Status::Cancelled;
+                cancelled.push(o.id);
+            }
+        }
+        cancelled
+    }
+
+    #[test]
+    fn test_take_profit_fill_cancels_stop_loss() {
+        let mut orders = vec![order(1, "AAPL"), order(2, "AAPL")];
+        let mut groups = Vec::new();
+        let group = link(&mut orders, &mut groups, 2, 1).unwrap();
+        assert!(orders.iter().all(|o| o.group == Some(group)));
+
+        assert_eq!(fill(&mut orders, &mut groups, 1, true), vec![2]);
+        assert_eq!(orders[1].status, Status::Cancelled);
+        assert!(!groups[0].active);
+        assert_eq!(groups[0].filled_order, Some(1));
+    }
+
+    #[test]
+    fn test_partial_fill_cancels_sibling_once() {
+        let mut orders = vec![order(1, "AAPL"), order(2, "AAPL")];
+        let mut groups = Vec::new();
+        link(&mut orders, &mut groups, 2, 1).unwrap();
+
+        assert_eq!(fill(&mut orders, &mut groups, 2, false), vec![1]);
+        // The rest of the filling order completes without touching the group again
+        assert!(fill(&mut orders, &mut groups, 2, true).is_empty());
+        assert_eq!(orders[0].status, Status::Cancelled);
+        assert_eq!(groups[0].filled_order, Some(2));
+    }
+
+    #[test]
+    fn test_ungrouped_fill_cancels_nothing() {
+        let mut orders = vec![order(1, "AAPL"), order(2, "AAPL")];
+        let mut groups = Vec::new();
+        assert!(fill(&mut orders, &mut groups, 1, true).is_empty());
+        assert_eq!(orders[1].status, Status::Pending);
+    }
+
+    #[test]
+    fn test_invalid_links() {
+        let mut orders = vec![order(1, "AAPL"), order(2, "MSFT"), order(3, "AAPL"), order(4, "AAPL"), order(5, "AAPL")];
+        orders[2].status = Status::PartiallyFilled;
+        orders[3].strategy = true;
+        orders[4].account = 2;
+        let mut groups = Vec::new();
+
+        assert_eq!(link(&mut orders, &mut groups, 1, 2), Err("symbol"));
+        assert_eq!(link(&mut orders, &mut groups, 1, 3), Err("not open"));
+        assert_eq!(link(&mut orders, &mut groups, 1, 4), Err("strategy leg"));
+        assert_eq!(link(&mut orders, &mut groups, 1, 5), Err("not found"));
+        assert_eq!(link(&mut orders, &mut groups, 1, 9), Err("not found"));
+        assert!(groups.is_empty());
+    }
+
+    #[test]
+    fn test_order_cannot_join_second_group() {
+        let mut orders = vec![order(1, "AAPL"), order(2, "AAPL"), order(3, "AAPL")];
+        let mut groups = Vec::new();
+        link(&mut orders, &mut groups, 2, 1).unwrap();
+        assert_eq!(link(&mut orders, &mut groups, 3, 1), Err("already grouped"));
+        assert_eq!(orders[2].group, None);
+    }
+}
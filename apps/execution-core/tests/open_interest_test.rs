@@ -0,0 +1,148 @@
+//! Unit Tests for Open Interest and Concentration Limits
+//! Standalone tests for keeping open interest in step with fills and the share one account may hold
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod open_interest_tests {
+    use super::*;
+
+    /// Mirror of the cache and open interest in `PositionKeeper`
+    #[derive(Default)]
+    struct Book {
+        positions: HashMap<(u32, &'static str), Decimal>,
+        open_interest: HashMap<&'static str, Decimal>,
+    }
+
+    impl Book {
+        /// Mirror of `cache_positions` for one updated position
+        fn cache(&mut self, account: u32, symbol: &'static str, net_quantity: Decimal) {
+            let previous = if net_quantity.is_zero() {
+                self.positions.remove(&(account, symbol))
+            } else {
+                self.positions.insert((account, symbol), net_quantity)
+            };
+            let change = net_quantity.max(dec!(0)) - previous.unwrap_or_default().max(dec!(0));
+            let total = self.open_interest.entry(symbol).or_default();
+            *total = (*total + change).max(dec!(0));
+        }
+
+        /// Mirror of `recount_open_interest`
+        fn recounted(&self) -> HashMap<&'static str, Decimal> {
+            let mut open_interest: HashMap<&'static str, Decimal> = HashMap::new();
+            for ((_, symbol), quantity) in &self.positions {
+                *open_interest.entry(symbol).or_default() += (*quantity).max(dec!(0));
+            }
+            open_interest
+        }
+
+        fn open_interest(&self, symbol: &str) -> Decimal {
+            self.open_interest.get(symbol).copied().unwrap_or_default()
+        }
+
+        /// Mirror of `RiskLimits::check_concentration`: true when the buy is rejected
+        fn breaches(&self, account: u32, symbol: &'static str, side: &str, quantity: Decimal, limit: Decimal, min_oi: Decimal) -> bool {
+            if limit.is_zero() || side != "buy" {
+                return false;
+            }
+            let net = self.positions.get(&(account, symbol)).copied().unwrap_or_default();
+            let long_before = net.max(dec!(0));
+            let long_after = (net + quantity).max(dec!(0));
+            if long_after <= long_before {
+                return false;
+            }
+            let (share, oi_after) = projected_share(self.open_interest(symbol), long_before, long_after);
+            oi_after >= min_oi && share > limit
+        }
+    }
+
+    /// Mirror of `projected_share`
+    fn projected_share(open_interest: Decimal, long_before: Decimal, long_after: Decimal) -> (Decimal, Decimal) {
+        let after = (open_interest - long_before + long_after).max(dec!(0));
+        let share = if after.is_zero() { dec!(0) } else { long_after / after * dec!(100) };
+        (share, after)
+    }
+
+    #[test]
+    fn test_open_interest_sums_long_positions_only() {
+        let mut book = Book::default();
+        book.cache(1, "AAPL", dec!(10));
+        book.cache(2, "AAPL", dec!(5));
+        book.cache(3, "AAPL", dec!(-8));
+        assert_eq!(book.open_interest("AAPL"), dec!(15));
+    }
+
+    #[test]
+    fn test_open_interest_follows_every_fill() {
+        let mut book = Book::default();
+        book.cache(1, "AAPL", dec!(10));
+        book.cache(1, "AAPL", dec!(4));
+        assert_eq!(book.open_interest("AAPL"), dec!(4));
+
+        // Flipping short removes the long entirely
+        book.cache(1, "AAPL", dec!(-6));
+        assert_eq!(book.open_interest("AAPL"), dec!(0));
+
+        book.cache(1, "AAPL", dec!(0));
+        book.cache(2, "AAPL", dec!(3));
+        assert_eq!(book.open_interest("AAPL"), dec!(3));
+    }
+
+    #[test]
+    fn test_incremental_open_interest_matches_a_recount() {
+        let mut book = Book::default();
+        let fills = [
+            (1, "AAPL", dec!(10)), (2, "AAPL", dec!(-3)), (1, "MSFT", dec!(7)),
+            (2, "AAPL", dec!(2)), (1, "AAPL", dec!(0)), (3, "MSFT", dec!(-1)),
+        ];
+        for (account, symbol, quantity) in fills {
+            book.cache(account, symbol, quantity);
+        }
+
+        let recounted = book.recounted();
+        for symbol in ["AAPL", "MSFT"] {
+            assert_eq!(book.open_interest(symbol), recounted.get(symbol).copied().unwrap_or_default(), "{}", symbol);
+        }
+    }
+
+    #[test]
+    fn test_projected_share() {
+        assert_eq!(projected_share(dec!(100), dec!(10), dec!(30)), (dec!(25), dec!(120)));
+        assert_eq!(projected_share(dec!(0), dec!(0), dec!(0)), (dec!(0), dec!(0)));
+    }
+
+    #[test]
+    fn test_buy_past_the_limit_is_rejected() {
+        let mut book = Book::default();
+        book.cache(1, "AAPL", dec!(20));
+        book.cache(2, "AAPL", dec!(80));
+
+        // 20 + 10 of 110 is 27%
+        assert!(!book.breaches(1, "AAPL", "buy", dec!(10), dec!(30), dec!(0)));
+        // 20 + 30 of 130 is 38%
+        assert!(book.breaches(1, "AAPL", "buy", dec!(30), dec!(30), dec!(0)));
+    }
+
+    #[test]
+    fn test_orders_that_do_not_grow_a_long_always_pass() {
+        let mut book = Book::default();
+        book.cache(1, "AAPL", dec!(90));
+        book.cache(2, "AAPL", dec!(10));
+
+        assert!(!book.breaches(1, "AAPL", "sell", dec!(50), dec!(10), dec!(0)));
+        // Covering a short back to flat adds nothing to open interest
+        book.cache(3, "AAPL", dec!(-40));
+        assert!(!book.breaches(3, "AAPL", "buy", dec!(40), dec!(10), dec!(0)));
+    }
+
+    #[test]
+    fn test_limit_waits_for_minimum_open_interest_and_zero_disables() {
+        let book = Book::default();
+        // The first buyer holds all of a new symbol
+        assert!(!book.breaches(1, "NEW", "buy", dec!(10), dec!(25), dec!(1000)));
+        assert!(book.breaches(1, "NEW", "buy", dec!(10), dec!(25), dec!(0)));
+        assert!(!book.breaches(1, "NEW", "buy", dec!(10), dec!(0), dec!(0)));
+    }
+}
@@ -0,0 +1,70 @@
+//! Unit Tests for Order Amend
+//! Standalone tests for the terms a cancel-replace gives the replacement order
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod order_amend_tests {
+    use super::*;
+
+    struct Order {
+        order_type: &'static str,
+        quantity: Decimal,
+        filled_quantity: Decimal,
+        price: Option<Decimal>,
+    }
+
+    fn limit(quantity: Decimal, filled_quantity: Decimal, price: Decimal) -> Order {
+        Order { order_type: "limit", quantity, filled_quantity, price: Some(price) }
+    }
+
+    /// Mirror of `amended_terms`: the replacement's price and quantity
+    fn amended_terms(order: &Order, price: Option<Decimal>, quantity: Option<Decimal>) -> Result<(Option<Decimal>, Decimal), &'static str> {
+        if price.is_none() && quantity.is_none() {
+            return Err("nothing to amend");
+        }
+        if price.is_some() && order.price.is_none() {
+            return Err("no limit price");
+        }
+        if price.is_some_and(|p| p <= Decimal::ZERO) {
+            return Err("price must be positive");
+        }
+
+        let quantity = quantity.unwrap_or(order.quantity);
+        if quantity <= order.filled_quantity {
+            return Err("quantity must be above filled");
+        }
+        Ok((price.or(order.price), quantity - order.filled_quantity))
+    }
+
+    #[test]
+    fn test_price_amend_keeps_remaining_quantity() {
+        let order = limit(dec!(10), dec!(0), dec!(100));
+        assert_eq!(amended_terms(&order, Some(dec!(101)), None), Ok((Some(dec!(101)), dec!(10))));
+    }
+
+    #[test]
+    fn test_quantity_is_the_new_total_including_fills() {
+        let order = limit(dec!(10), dec!(4), dec!(100));
+        // The replacement carries what is left of the new total; the 4 filled stay on the original
+        assert_eq!(amended_terms(&order, None, Some(dec!(15))), Ok((Some(dec!(100)), dec!(11))));
+        assert_eq!(amended_terms(&order, None, Some(dec!(6))), Ok((Some(dec!(100)), dec!(2))));
+        assert_eq!(amended_terms(&order, None, Some(dec!(4))), Err("quantity must be above filled"));
+    }
+
+    #[test]
+    fn test_market_orders_can_only_change_quantity() {
+        let market = Order { order_type: "market", quantity: dec!(5), filled_quantity: dec!(0), price: None };
+        assert_eq!(market.order_type, "market");
+        assert_eq!(amended_terms(&market, Some(dec!(100)), None), Err("no limit price"));
+        assert_eq!(amended_terms(&market, None, Some(dec!(8))), Ok((None, dec!(8))));
+    }
+
+    #[test]
+    fn test_invalid_amends() {
+        let order = limit(dec!(10), dec!(0), dec!(100));
+        assert_eq!(amended_terms(&order, None, None), Err("nothing to amend"));
+        assert_eq!(amended_terms(&order, Some(dec!(0)), None), Err("price must be positive"));
+    }
+}
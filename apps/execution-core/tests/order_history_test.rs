@@ -0,0 +1,314 @@
+//! Unit Tests for Order Event Compaction
+//! Standalone tests for folding event streams into snapshots, assembling timelines and paging
+//! order lists
+
+#[cfg(test)]
+mod order_history_tests {
+    use std::collections::BTreeMap;
+
+    struct Event {
+        event_type: &'static str,
+        at: i64,
+        data: Option<&'static str>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Summary {
+        count: i64,
+        first_at: i64,
+        last_at: i64,
+        last_data: Option<&'static str>,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq)]
+    struct Snapshot {
+        event_count: i64,
+        events: BTreeMap<&'static str, Summary>,
+        first_event_at: Option<i64>,
+        last_event_at: Option<i64>,
+    }
+
+    fn event(event_type: &'static str, at: i64, data: Option<&'static str>) -> Event {
+        Event { event_type, at, data }
+    }
+
+    /// Mirror of `OrderSnapshot::fold`
+    fn fold(snapshot: &mut Snapshot, stream: &[Event]) {
+        for event in stream {
+            snapshot.event_count += 1;
+            snapshot.first_event_at = Some(snapshot.first_event_at.map_or(event.at, |t| t.min(event.at)));
+            snapshot.last_event_at = Some(snapshot.last_event_at.map_or(event.at, |t| t.max(event.at)));
+
+            let summary = snapshot.events.entry(event.event_type).or_insert(Summary {
+                count: 0,
+                first_at: event.at,
+                last_at: event.at,
+                last_data: None,
+            });
+            summary.count += 1;
+            summary.first_at = summary.first_at.min(event.at);
+            if event.at >= summary.last_at {
+                summary.last_at = event.at;
+                summary.last_data = event.data;
+            }
+        }
+    }
+
+    /// Mirror of the compaction candidate filter
+    fn compactable(status: &str, settled_at: i64, has_events: bool, cutoff: i64) -> bool {
+        matches!(status, "filled" | "cancelled" | "rejected" | "expired") && settled_at < cutoff && has_events
+    }
+
+    #[test]
+    fn test_fold_counts_per_type() {
+        let mut snapshot = Snapshot::default();
+        fold(&mut snapshot, &[
+            event("iceberg_replenished", 1, Some("slice 1")),
+            event("iceberg_replenished", 2, Some("slice 2")),
+            event("oco_cancelled", 3, None),
+        ]);
+
+        assert_eq!(snapshot.event_count, 3);
+        assert_eq!(snapshot.first_event_at, Some(1));
+        assert_eq!(snapshot.last_event_at, Some(3));
+
+        let replenished = &snapshot.events["iceberg_replenished"];
+        assert_eq!(replenished.count, 2);
+        assert_eq!((replenished.first_at, replenished.last_at), (1, 2));
+        assert_eq!(replenished.last_data, Some("slice 2"));
+        assert_eq!(snapshot.events["oco_cancelled"].count, 1);
+    }
+
+    #[test]
+    fn test_folding_in_parts_matches_folding_at_once() {
+        let stream = [
+            event("reduced", 1, Some("a")),
+            event("replaced", 2, Some("b")),
+            event("reduced", 3, Some("c")),
+            event("expired", 4, None),
+        ];
+
+        let mut whole = Snapshot::default();
+        fold(&mut whole, &stream);
+
+        // A late event compacted on a second run extends the stored snapshot
+        let mut parts = Snapshot::default();
+        fold(&mut parts, &stream[..2]);
+        fold(&mut parts, &stream[2..]);
+
+        assert_eq!(whole, parts);
+    }
+
+    #[test]
+    fn test_out_of_order_event_keeps_latest_data() {
+        let mut snapshot = Snapshot::default();
+        fold(&mut snapshot, &[event("reduced", 5, Some("late")), event("reduced", 2, Some("early"))]);
+
+        let reduced = &snapshot.events["reduced"];
+        assert_eq!((reduced.first_at, reduced.last_at), (2, 5));
+        assert_eq!(reduced.last_data, Some("late"));
+        assert_eq!(snapshot.first_event_at, Some(2));
+    }
+
+    #[test]
+    fn test_empty_stream_leaves_snapshot_untouched() {
+        let mut snapshot = Snapshot::default();
+        fold(&mut snapshot, &[]);
+        assert_eq!(snapshot, Snapshot::default());
+    }
+
+    #[test]
+    fn test_only_settled_orders_past_cutoff_compact() {
+        assert!(compactable("filled", 10, true, 20));
+        assert!(compactable("expired", 10, true, 20));
+        assert!(!compactable("partially_filled", 10, true, 20));
+        assert!(!compactable("pending", 10, true, 20));
+        assert!(!compactable("cancelled", 20, true, 20));
+        assert!(!compactable("rejected", 10, false, 20));
+    }
+
+    /// Mirror of `assemble_timeline`, with events and fills as `(at, kind)` and `(at, quantity)`
+    fn timeline(
+        created_at: i64,
+        status: &str,
+        updated_at: i64,
+        quantity: u32,
+        events: &[(i64, &'static str)],
+        fills: &[(i64, u32)],
+    ) -> Vec<(i64, String)> {
+        let mut timeline = vec![(created_at, "created".to_string()), (created_at, "risk_checked".to_string())];
+        timeline.extend(events.iter().map(|(at, kind)| (*at, kind.to_string())));
+
+        let mut filled = 0;
+        for (at, fill) in fills {
+            filled += fill;
+            let kind = if filled >= quantity { "filled (complete)" } else { "filled" };
+            timeline.push((*at, kind.to_string()));
+        }
+
+        let closed = matches!(status, "cancelled" | "rejected" | "expired")
+            && !events.iter().any(|(_, kind)| *kind == status);
+        if closed {
+            timeline.push((updated_at, status.to_string()));
+        }
+
+        timeline.sort_by_key(|(at, _)| *at);
+        timeline
+    }
+
+    fn kinds(timeline: &[(i64, String)]) -> Vec<&str> {
+        timeline.iter().map(|(_, kind)| kind.as_str()).collect()
+    }
+
+    #[test]
+    fn test_timeline_interleaves_events_and_fills() {
+        let steps = timeline(1, "cancelled", 9, 10, &[(4, "reduced")], &[(3, 2), (6, 3)]);
+        assert_eq!(kinds(&steps), ["created", "risk_checked", "filled", "reduced", "filled", "cancelled"]);
+        assert_eq!(steps.last(), Some(&(9, "cancelled".to_string())));
+    }
+
+    #[test]
+    fn test_timeline_marks_completing_fill() {
+        let steps = timeline(1, "filled", 5, 10, &[], &[(2, 4), (5, 6)]);
+        assert_eq!(kinds(&steps), ["created", "risk_checked", "filled", "filled (complete)"]);
+    }
+
+    #[test]
+    fn test_final_status_recorded_once() {
+        // The expiry sweep already wrote an `expired` event
+        let steps = timeline(1, "expired", 8, 10, &[(8, "expired")], &[]);
+        assert_eq!(kinds(&steps), ["created", "risk_checked", "expired"]);
+
+        // Open orders have no final step yet
+        let open = timeline(1, "partially_filled", 3, 10, &[], &[(3, 4)]);
+        assert_eq!(kinds(&open), ["created", "risk_checked", "filled"]);
+    }
+
+    #[test]
+    fn test_same_instant_keeps_assembly_order() {
+        let steps = timeline(1, "filled", 1, 10, &[(1, "stop_triggered")], &[(1, 10)]);
+        assert_eq!(kinds(&steps), ["created", "risk_checked", "stop_triggered", "filled (complete)"]);
+    }
+
+    // =====================================================
+    // ORDER LIST PAGES
+    // =====================================================
+
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+    use base64::Engine;
+    use chrono::{DateTime, TimeZone, Utc};
+    use uuid::Uuid;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Cursor {
+        created_at: DateTime<Utc>,
+        id: Uuid,
+    }
+
+    /// Mirror of `OrderCursor::encode`
+    fn encode(cursor: &Cursor) -> String {
+        BASE64.encode(format!("{}:{}", cursor.created_at.timestamp_micros(), cursor.id))
+    }
+
+    /// Mirror of `OrderCursor::decode`
+    fn decode(cursor: &str) -> Option<Cursor> {
+        let raw = String::from_utf8(BASE64.decode(cursor).ok()?).ok()?;
+        let (micros, id) = raw.split_once(':')?;
+        Some(Cursor {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: id.parse().ok()?,
+        })
+    }
+
+    #[derive(Debug, Clone)]
+    struct Row {
+        id: Uuid,
+        created_at: DateTime<Utc>,
+        status: &'static str,
+    }
+
+    fn row(id: u128, second: u32, status: &'static str) -> Row {
+        Row { id: Uuid::from_u128(id), created_at: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, second).unwrap(), status }
+    }
+
+    /// Mirror of the `orders.list` query and `OrderPage::from_rows`: filter, keyset past the
+    /// cursor, order by (created_at, id), and a cursor only when a row beyond the page exists
+    fn page(rows: &[Row], statuses: &[&str], newest_first: bool, size: usize, cursor: Option<&str>) -> (Vec<u128>, Option<String>) {
+        let after = cursor.map(|c| decode(c).unwrap());
+        let mut matched: Vec<&Row> = rows
+            .iter()
+            .filter(|r| statuses.is_empty() || statuses.contains(&r.status))
+            .filter(|r| after.is_none_or(|c| {
+                let key = (r.created_at, r.id);
+                if newest_first { key < (c.created_at, c.id) } else { key > (c.created_at, c.id) }
+            }))
+            .collect();
+        matched.sort_by_key(|r| (r.created_at, r.id));
+        if newest_first {
+            matched.reverse();
+        }
+        matched.truncate(size + 1);
+
+        let more = matched.len() > size;
+        matched.truncate(size);
+        let next = matched.last().filter(|_| more).map(|r| encode(&Cursor { created_at: r.created_at, id: r.id }));
+        (matched.iter().map(|r| r.id.as_u128()).collect(), next)
+    }
+
+    /// Every id, following cursors page by page
+    fn walk(rows: &[Row], statuses: &[&str], newest_first: bool, size: usize) -> Vec<u128> {
+        let (mut ids, mut next) = page(rows, statuses, newest_first, size, None);
+        while let Some(cursor) = next {
+            let (more, after) = page(rows, statuses, newest_first, size, Some(&cursor));
+            ids.extend(more);
+            next = after;
+        }
+        ids
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            created_at: Utc.timestamp_micros(1_709_294_400_123_456).unwrap(),
+            id: Uuid::from_u128(42),
+        };
+        let encoded = encode(&cursor);
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode(&encoded), Some(cursor));
+
+        assert_eq!(decode("not a cursor"), None);
+        assert_eq!(decode(&BASE64.encode("123")), None);
+        assert_eq!(decode(&BASE64.encode("abc:00000000-0000-0000-0000-00000000002a")), None);
+    }
+
+    #[test]
+    fn test_pages_cover_every_order_once() {
+        // Two orders share a creation time; the id breaks the tie
+        let rows = vec![row(1, 1, "filled"), row(2, 2, "cancelled"), row(4, 3, "filled"), row(3, 3, "pending"), row(5, 4, "filled")];
+        assert_eq!(walk(&rows, &[], true, 2), vec![5, 4, 3, 2, 1]);
+        assert_eq!(walk(&rows, &[], false, 2), vec![1, 2, 3, 4, 5]);
+        assert_eq!(walk(&rows, &["filled"], true, 1), vec![5, 4, 1]);
+    }
+
+    #[test]
+    fn test_last_page_has_no_cursor() {
+        let rows = vec![row(1, 1, "filled"), row(2, 2, "filled")];
+        assert_eq!(page(&rows, &[], true, 2, None), (vec![2, 1], None));
+        let (ids, next) = page(&rows, &[], true, 1, None);
+        assert_eq!(ids, vec![2]);
+        assert!(next.is_some());
+        assert_eq!(page(&[], &[], true, 5, None), (vec![], None));
+    }
+
+    #[test]
+    fn test_new_orders_do_not_shift_pages() {
+        let mut rows = vec![row(1, 1, "filled"), row(2, 2, "filled"), row(3, 3, "filled")];
+        let (first, next) = page(&rows, &[], true, 2, None);
+        assert_eq!(first, vec![3, 2]);
+
+        // Placed between two requests of a newest-first walk
+        rows.push(row(9, 5, "pending"));
+        let (second, next) = page(&rows, &[], true, 2, next.as_deref());
+        assert_eq!((second, next), (vec![1], None));
+    }
+}
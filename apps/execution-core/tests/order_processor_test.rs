@@ -97,6 +97,37 @@ fn cancel_order(order: &mut Order) -> Result<(), String> {
     }
 }
 
+/// Lower the order quantity in place; returns the part of `hold` released
+fn reduce_order(order: &mut Order, new_quantity: Decimal, hold: Decimal) -> Result<Decimal, String> {
+    match order.status {
+        OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Rejected => {
+            return Err("Order is no longer open".to_string());
+        }
+        _ => {}
+    }
+    if new_quantity >= order.quantity || new_quantity <= order.filled_quantity {
+        return Err("New quantity must be below the order and above the filled quantity".to_string());
+    }
+
+    let open_before = order.quantity - order.filled_quantity;
+    let open_after = new_quantity - order.filled_quantity;
+    order.quantity = new_quantity;
+    order.updated_at = Utc::now();
+    Ok(hold - hold * open_after / open_before)
+}
+
+const MAX_ORDER_METADATA_BYTES: usize = 4096;
+
+fn validate_metadata(metadata: Option<&serde_json::Value>) -> Result<(), String> {
+    match metadata {
+        None => Ok(()),
+        Some(v) if !v.is_object() => Err("metadata must be a JSON object".to_string()),
+        Some(v) if v.get("$enc").is_some() => Err("metadata key '$enc' is reserved".to_string()),
+        Some(v) if v.to_string().len() > MAX_ORDER_METADATA_BYTES => Err("metadata too large".to_string()),
+        Some(_) => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod order_processor_tests {
     use super::*;
@@ -234,6 +265,56 @@ mod order_processor_tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_reduce_keeps_price_and_priority() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Open;
+        let created_at = order.created_at;
+
+        // 1.0 @ 50000 holds 50000; reducing to 0.4 releases 60%
+        let released = reduce_order(&mut order, dec!(0.4), dec!(50000)).unwrap();
+
+        assert_eq!(released, dec!(30000));
+        assert_eq!(order.quantity, dec!(0.4));
+        assert_eq!(order.price, Some(dec!(50000.0)));
+        assert_eq!(order.created_at, created_at);
+    }
+
+    #[test]
+    fn test_reduce_releases_only_the_open_part() {
+        let mut order = create_test_order();
+        apply_fill(&mut order, dec!(0.5), dec!(50000.0));
+
+        // Remaining hold covers the open 0.5; reducing to 0.75 leaves 0.25 open
+        let released = reduce_order(&mut order, dec!(0.75), dec!(25000)).unwrap();
+        assert_eq!(released, dec!(12500));
+    }
+
+    #[test]
+    fn test_reduce_rejects_increase_or_below_filled() {
+        let mut order = create_test_order();
+        apply_fill(&mut order, dec!(0.3), dec!(50000.0));
+
+        assert!(reduce_order(&mut order, dec!(1.5), dec!(35000)).is_err());
+        assert!(reduce_order(&mut order, dec!(0.3), dec!(35000)).is_err());
+        assert_eq!(order.quantity, dec!(1.0));
+    }
+
+    #[test]
+    fn test_metadata_must_be_small_object() {
+        let tags = serde_json::json!({ "strategy": "mm-1", "parentRef": 42 });
+        assert!(validate_metadata(None).is_ok());
+        assert!(validate_metadata(Some(&tags)).is_ok());
+        assert!(validate_metadata(Some(&serde_json::json!(["a", "b"]))).is_err());
+
+        let oversized = serde_json::json!({ "blob": "x".repeat(MAX_ORDER_METADATA_BYTES) });
+        assert!(validate_metadata(Some(&oversized)).is_err());
+
+        // Clients cannot forge a value that looks sealed at rest
+        let forged = serde_json::json!({ "$enc": "k1:AAAA" });
+        assert!(validate_metadata(Some(&forged)).is_err());
+    }
+
     #[test]
     fn test_order_unique_ids() {
         let order1 = create_test_order();
@@ -11,6 +11,7 @@ mod tests {
 
     // Mock structures for testing
     #[derive(Debug, Clone)]
+    #[allow(dead_code)]
     struct Order {
         id: Uuid,
         account_id: Uuid,
@@ -0,0 +1,103 @@
+//! Unit Tests for Partial Fills
+//! Standalone tests for splitting tick volume across orders and accumulating fills
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod partial_fill_tests {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Order {
+        seq: u32,
+        quantity: Decimal,
+        filled_quantity: Decimal,
+        avg_fill_price: Option<Decimal>,
+        status: &'static str,
+    }
+
+    fn order(seq: u32, quantity: Decimal) -> Order {
+        Order { seq, quantity, filled_quantity: Decimal::ZERO, avg_fill_price: None, status: "pending" }
+    }
+
+    /// Mirror of `allocate_volume`
+    fn allocate_volume(mut matched: Vec<Order>, available: Option<Decimal>) -> Vec<(u32, Decimal)> {
+        matched.sort_by_key(|o| o.seq);
+
+        let mut left = available;
+        let mut fills = Vec::new();
+        for order in matched {
+            let remaining = order.quantity - order.filled_quantity;
+            let quantity = left.map_or(remaining, |left| remaining.min(left));
+            if quantity <= Decimal::ZERO {
+                break;
+            }
+            if let Some(left) = left.as_mut() {
+                *left -= quantity;
+            }
+            fills.push((order.seq, quantity));
+        }
+        fills
+    }
+
+    /// Mirror of `average_fill_price`
+    fn average_fill_price(order: &Order, quantity: Decimal, price: Decimal) -> Decimal {
+        let filled = order.avg_fill_price.unwrap_or_default() * order.filled_quantity;
+        ((filled + price * quantity) / (order.filled_quantity + quantity)).round_dp(8)
+    }
+
+    /// Mirror of the order update in `fill_order`
+    fn fill(order: &mut Order, quantity: Decimal, price: Decimal) {
+        order.avg_fill_price = Some(average_fill_price(order, quantity, price));
+        order.filled_quantity += quantity;
+        order.status = if order.filled_quantity >= order.quantity { "filled" } else { "partially_filled" };
+    }
+
+    #[test]
+    fn test_without_volume_every_order_fills_in_full() {
+        let fills = allocate_volume(vec![order(1, dec!(5)), order(2, dec!(7))], None);
+        assert_eq!(fills, vec![(1, dec!(5)), (2, dec!(7))]);
+    }
+
+    #[test]
+    fn test_volume_goes_to_oldest_orders_first() {
+        let fills = allocate_volume(vec![order(2, dec!(5)), order(1, dec!(5)), order(3, dec!(5))], Some(dec!(8)));
+        assert_eq!(fills, vec![(1, dec!(5)), (2, dec!(3))]);
+    }
+
+    #[test]
+    fn test_partially_filled_orders_take_only_what_remains() {
+        let mut first = order(1, dec!(10));
+        first.filled_quantity = dec!(9);
+        let fills = allocate_volume(vec![first, order(2, dec!(10))], Some(dec!(4)));
+        assert_eq!(fills, vec![(1, dec!(1)), (2, dec!(3))]);
+    }
+
+    #[test]
+    fn test_order_fills_across_ticks() {
+        let mut o = order(1, dec!(10));
+
+        fill(&mut o, dec!(4), dec!(100));
+        assert_eq!(o.status, "partially_filled");
+        assert_eq!(o.avg_fill_price, Some(dec!(100)));
+
+        fill(&mut o, dec!(4), dec!(110));
+        assert_eq!(o.status, "partially_filled");
+        assert_eq!(o.avg_fill_price, Some(dec!(105)));
+
+        fill(&mut o, dec!(2), dec!(90));
+        assert_eq!(o.status, "filled");
+        assert_eq!(o.filled_quantity, dec!(10));
+        // (400 + 440 + 180) / 10
+        assert_eq!(o.avg_fill_price, Some(dec!(102)));
+    }
+
+    #[test]
+    fn test_average_price_rounds_to_eight_places() {
+        let mut o = order(1, dec!(3));
+        fill(&mut o, dec!(1), dec!(1));
+        fill(&mut o, dec!(2), dec!(2));
+        assert_eq!(o.avg_fill_price, Some(dec!(1.66666667)));
+    }
+}
@@ -0,0 +1,46 @@
+//! Unit Tests for Connection Pool Lifecycle
+//! Standalone tests for stale-connection detection and repair cadence
+
+#[cfg(test)]
+mod pool_tests {
+    use std::time::Duration;
+
+    /// Mirror of `PoolLifecycle::is_stale` with explicit times since the pool's origin
+    fn is_stale(recycled_at: Duration, now: Duration, age: Duration) -> bool {
+        let opened_at = now.saturating_sub(age);
+        !recycled_at.is_zero() && opened_at < recycled_at
+    }
+
+    fn should_repair(failures: u32, threshold: u32) -> bool {
+        failures.is_multiple_of(threshold.max(1))
+    }
+
+    #[test]
+    fn test_nothing_is_stale_before_first_recycle() {
+        let now = Duration::from_secs(600);
+        assert!(!is_stale(Duration::ZERO, now, Duration::from_secs(500)));
+    }
+
+    #[test]
+    fn test_connections_opened_before_recycle_are_stale() {
+        let recycled_at = Duration::from_secs(100);
+        let now = Duration::from_secs(120);
+
+        // Opened at t=50
+        assert!(is_stale(recycled_at, now, Duration::from_secs(70)));
+        // Opened at t=110, after the recycle
+        assert!(!is_stale(recycled_at, now, Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_repair_every_threshold_failures() {
+        let repairs: Vec<u32> = (1..=7).filter(|f| should_repair(*f, 3)).collect();
+        assert_eq!(repairs, vec![3, 6]);
+    }
+
+    #[test]
+    fn test_zero_threshold_repairs_on_every_failure() {
+        assert!(should_repair(1, 0));
+        assert!(should_repair(2, 0));
+    }
+}
@@ -26,6 +26,7 @@ mod position_tests {
     }
 
     #[derive(Debug, Clone)]
+    #[allow(dead_code)]
     struct Position {
         account_id: Uuid,
         symbol: String,
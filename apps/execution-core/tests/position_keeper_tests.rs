@@ -10,6 +10,7 @@ mod tests {
     use super::*;
 
     #[derive(Debug, Clone)]
+    #[allow(dead_code)]
     struct Position {
         account_id: Uuid,
         symbol: String,
@@ -57,7 +58,7 @@ mod tests {
                    || (old_qty < Decimal::ZERO && new_qty > Decimal::ZERO) {
                 // Crossing zero - close old, open new
                 let closing_qty = old_qty.abs();
-                let opening_qty = new_qty.abs();
+                let _opening_qty = new_qty.abs();
                 
                 let pnl = if old_qty > Decimal::ZERO {
                     closing_qty * (fill_price - self.avg_price)
@@ -0,0 +1,127 @@
+//! Unit Tests for Position Recovery
+//! Standalone tests for sequence guards and rebuilding positions from fills and splits
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod position_recovery_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position {
+        quantity: Decimal,
+        avg_price: Decimal,
+        realized_pnl: Decimal,
+        sequence: i64,
+    }
+
+    enum Movement {
+        Fill { buy: bool, quantity: Decimal, price: Decimal },
+        Split { ratio: Decimal },
+    }
+
+    fn round_column(value: Decimal) -> Decimal {
+        value.round_dp_with_strategy(8, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    fn sign(d: Decimal) -> Decimal {
+        if d > dec!(0) { dec!(1) } else { dec!(-1) }
+    }
+
+    /// Mirror of the weighted-average rules in `PositionKeeper`
+    fn apply_fill(pos: &Position, buy: bool, quantity: Decimal, price: Decimal) -> (Decimal, Decimal, Decimal) {
+        let signed = if buy { quantity } else { -quantity };
+        let new_quantity = pos.quantity + signed;
+
+        if pos.quantity.is_zero() {
+            return (new_quantity, price, dec!(0));
+        }
+        if sign(pos.quantity) == sign(signed) {
+            let cost = pos.quantity.abs() * pos.avg_price + quantity * price;
+            return (new_quantity, cost / new_quantity.abs(), dec!(0));
+        }
+        if !new_quantity.is_zero() && sign(new_quantity) == sign(pos.quantity) {
+            return (new_quantity, pos.avg_price, quantity * (price - pos.avg_price) * sign(pos.quantity));
+        }
+        let realized = pos.quantity.abs() * (price - pos.avg_price) * sign(pos.quantity);
+        if new_quantity.is_zero() {
+            return (dec!(0), dec!(0), realized);
+        }
+        (new_quantity, price, realized)
+    }
+
+    fn replay(movements: &[Movement]) -> Position {
+        let mut pos = Position { quantity: dec!(0), avg_price: dec!(0), realized_pnl: dec!(0), sequence: 0 };
+        for movement in movements {
+            match movement {
+                Movement::Fill { buy, quantity, price } => {
+                    let (q, avg, realized) = apply_fill(&pos, *buy, *quantity, *price);
+                    pos.quantity = round_column(q);
+                    pos.avg_price = round_column(avg);
+                    pos.realized_pnl = round_column(pos.realized_pnl + realized);
+                }
+                Movement::Split { ratio } => {
+                    pos.quantity = round_column(pos.quantity * ratio);
+                    pos.avg_price = round_column(pos.avg_price / ratio);
+                }
+            }
+            pos.sequence += 1;
+        }
+        pos
+    }
+
+    /// Mirror of the upsert guard: the write only lands on the sequence it started from
+    fn guarded_write(stored: &mut Position, expected: i64, next: Position) -> bool {
+        if stored.sequence != expected {
+            return false;
+        }
+        *stored = Position { sequence: stored.sequence + 1, ..next };
+        true
+    }
+
+    #[test]
+    fn test_sequence_counts_every_movement() {
+        let pos = replay(&[
+            Movement::Fill { buy: true, quantity: dec!(10), price: dec!(100) },
+            Movement::Split { ratio: dec!(2) },
+            Movement::Fill { buy: false, quantity: dec!(5), price: dec!(60) },
+        ]);
+        assert_eq!(pos.sequence, 3);
+    }
+
+    #[test]
+    fn test_replay_applies_split_between_fills() {
+        let pos = replay(&[
+            Movement::Fill { buy: true, quantity: dec!(10), price: dec!(100) },
+            Movement::Split { ratio: dec!(2) },
+            Movement::Fill { buy: false, quantity: dec!(5), price: dec!(60) },
+        ]);
+        assert_eq!(pos.quantity, dec!(15));
+        assert_eq!(pos.avg_price, dec!(50));
+        assert_eq!(pos.realized_pnl, dec!(50));
+    }
+
+    #[test]
+    fn test_replay_rounds_like_the_column() {
+        let pos = replay(&[
+            Movement::Fill { buy: true, quantity: dec!(1), price: dec!(10) },
+            Movement::Fill { buy: true, quantity: dec!(2), price: dec!(11) },
+        ]);
+        // 32 / 3 stored with 8 decimal places
+        assert_eq!(pos.avg_price, dec!(10.66666667));
+    }
+
+    #[test]
+    fn test_stale_sequence_is_rejected() {
+        let mut stored = Position { quantity: dec!(5), avg_price: dec!(10), realized_pnl: dec!(0), sequence: 4 };
+        let next = Position { quantity: dec!(6), avg_price: dec!(10), realized_pnl: dec!(0), sequence: 0 };
+
+        assert!(!guarded_write(&mut stored, 3, next.clone()));
+        assert_eq!(stored.sequence, 4);
+
+        assert!(guarded_write(&mut stored, 4, next));
+        assert_eq!(stored.sequence, 5);
+        assert_eq!(stored.quantity, dec!(6));
+    }
+}
@@ -0,0 +1,62 @@
+//! Unit Tests for Account Privacy
+//! Standalone tests for export access, erasure scheduling and pseudonyms
+
+#[cfg(test)]
+mod privacy_tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use sha2::{Digest, Sha256};
+    use uuid::Uuid;
+
+    const ACCOUNTS_PRIVACY: &str = "accounts:privacy";
+
+    /// Mirror of the pseudonym used for erased usernames and emails
+    fn pseudonym(account_id: Uuid) -> String {
+        let digest = Sha256::digest(account_id.as_bytes());
+        format!("erased-{}", hex::encode(&digest[..6]))
+    }
+
+    fn can_export(caller: Uuid, target: Uuid, permissions: &[&str]) -> bool {
+        caller == target || permissions.contains(&ACCOUNTS_PRIVACY)
+    }
+
+    #[test]
+    fn test_pseudonym_is_stable_and_short() {
+        let id = Uuid::new_v4();
+        assert_eq!(pseudonym(id), pseudonym(id));
+        assert_eq!(pseudonym(id).len(), "erased-".len() + 12);
+    }
+
+    #[test]
+    fn test_pseudonyms_differ_per_account() {
+        assert_ne!(pseudonym(Uuid::new_v4()), pseudonym(Uuid::new_v4()));
+    }
+
+    #[test]
+    fn test_pseudonym_does_not_contain_account_id() {
+        let id = Uuid::new_v4();
+        assert!(!pseudonym(id).contains(&id.simple().to_string()[..12]));
+    }
+
+    #[test]
+    fn test_account_can_export_itself() {
+        let id = Uuid::new_v4();
+        assert!(can_export(id, id, &[]));
+    }
+
+    #[test]
+    fn test_export_of_other_account_needs_privacy_permission() {
+        let caller = Uuid::new_v4();
+        let target = Uuid::new_v4();
+        assert!(!can_export(caller, target, &["orders:read"]));
+        assert!(can_export(caller, target, &[ACCOUNTS_PRIVACY]));
+    }
+
+    #[test]
+    fn test_erasure_due_after_retention() {
+        let requested = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let erase_after = requested + Duration::days(30);
+
+        assert!(requested + Duration::days(29) < erase_after);
+        assert!(requested + Duration::days(30) >= erase_after);
+    }
+}
@@ -0,0 +1,49 @@
+//! Unit Tests for Profiling Endpoints
+//! Standalone tests for CPU profile request handling
+
+#[cfg(test)]
+mod profiling_tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    const MAX_PROFILE_SECS: u64 = 120;
+
+    /// Mirror of the format check in `cpu_profile`: `Some(true)` for a flamegraph
+    fn flamegraph(format: Option<&str>) -> Option<bool> {
+        match format {
+            None | Some("pprof") => Some(false),
+            Some("flamegraph") => Some(true),
+            Some(_) => None,
+        }
+    }
+
+    /// Mirror of the duration and frequency clamps in `cpu_profile`
+    fn limits(seconds: u64, frequency: i32) -> (Duration, i32) {
+        (Duration::from_secs(seconds.clamp(1, MAX_PROFILE_SECS)), frequency.clamp(1, 1_000))
+    }
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(flamegraph(None), Some(false));
+        assert_eq!(flamegraph(Some("pprof")), Some(false));
+        assert_eq!(flamegraph(Some("flamegraph")), Some(true));
+        assert_eq!(flamegraph(Some("svg")), None);
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(limits(30, 99), (Duration::from_secs(30), 99));
+        assert_eq!(limits(0, 0), (Duration::from_secs(1), 1));
+        assert_eq!(limits(3_600, 100_000), (Duration::from_secs(MAX_PROFILE_SECS), 1_000));
+    }
+
+    #[test]
+    fn test_one_profile_at_a_time() {
+        let profiling = AtomicBool::new(false);
+        assert!(!profiling.swap(true, Ordering::AcqRel));
+        // A second request while the first runs is turned away
+        assert!(profiling.swap(true, Ordering::AcqRel));
+        profiling.store(false, Ordering::Release);
+        assert!(!profiling.swap(true, Ordering::AcqRel));
+    }
+}
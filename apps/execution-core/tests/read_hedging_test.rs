@@ -0,0 +1,105 @@
+//! Unit Tests for Read Hedging
+//! Standalone tests for the hedge delay and budget
+
+#[cfg(test)]
+mod read_hedging_tests {
+    use std::time::Duration;
+
+    const MAX_TOKENS: f64 = 10.0;
+
+    /// Mirror of `p95` in the hedging module
+    fn p95(samples: &[Duration]) -> Option<Duration> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let rank = (sorted.len() * 95).div_ceil(100);
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    /// Mirror of the token bucket in `HedgePolicy`
+    struct Budget {
+        per_request: f64,
+        tokens: f64,
+    }
+
+    impl Budget {
+        fn new(per_request: f64) -> Self {
+            Self { per_request, tokens: MAX_TOKENS }
+        }
+
+        fn begin(&mut self) {
+            self.tokens = (self.tokens + self.per_request).min(MAX_TOKENS);
+        }
+
+        fn try_hedge(&mut self) -> bool {
+            let allowed = self.tokens >= 1.0;
+            if allowed {
+                self.tokens -= 1.0;
+            }
+            allowed
+        }
+    }
+
+    fn ms(values: impl IntoIterator<Item = u64>) -> Vec<Duration> {
+        values.into_iter().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn test_p95_of_empty_window_is_unknown() {
+        assert_eq!(p95(&[]), None);
+    }
+
+    #[test]
+    fn test_p95_nearest_rank() {
+        let samples = ms(1..=100);
+        assert_eq!(p95(&samples), Some(Duration::from_millis(95)));
+    }
+
+    #[test]
+    fn test_p95_ignores_input_order() {
+        let samples = ms((1..=20).rev());
+        assert_eq!(p95(&samples), Some(Duration::from_millis(19)));
+    }
+
+    #[test]
+    fn test_p95_single_sample() {
+        assert_eq!(p95(&ms([7])), Some(Duration::from_millis(7)));
+    }
+
+    #[test]
+    fn test_budget_caps_sustained_hedging() {
+        let mut budget = Budget::new(0.05);
+        let mut hedges = 0;
+        for _ in 0..1_000 {
+            budget.begin();
+            if budget.try_hedge() {
+                hedges += 1;
+            }
+        }
+        // The banked burst plus 5% of requests
+        assert!(hedges <= 10 + 50);
+        assert!(hedges >= 50);
+    }
+
+    #[test]
+    fn test_budget_banks_only_a_bounded_burst() {
+        let mut budget = Budget::new(0.05);
+        for _ in 0..10_000 {
+            budget.begin();
+        }
+        let burst = (0..100).take_while(|_| budget.try_hedge()).count();
+        assert_eq!(burst, 10);
+    }
+
+    #[test]
+    fn test_zero_budget_never_hedges_after_burst() {
+        let mut budget = Budget::new(0.0);
+        for _ in 0..10 {
+            assert!(budget.try_hedge());
+        }
+        budget.begin();
+        assert!(!budget.try_hedge());
+    }
+}
@@ -0,0 +1,149 @@
+//! Unit Tests for Shard Rebalancing
+//! Standalone tests for the steps of a move, when it settles and how recorded moves change
+//! the symbols a shard resolves to
+
+use std::collections::{BTreeSet, HashMap};
+
+#[cfg(test)]
+mod rebalance_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Phase {
+        Requested,
+        Released,
+        Acquired,
+        Failed,
+        Restored,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Event {
+        phase: Phase,
+        from: &'static str,
+        to: &'static str,
+        at: i64,
+        deadline: i64,
+        last_price: Option<i64>,
+        reason: Option<String>,
+    }
+
+    impl Event {
+        /// Mirror of `RebalanceEvent::next`
+        fn next(&self, phase: Phase, at: i64) -> Self {
+            Self { phase, at, last_price: None, reason: None, ..self.clone() }
+        }
+
+        /// Mirror of `RebalanceEvent::failed`
+        fn failed(&self, at: i64, reason: &str) -> Self {
+            Self { reason: Some(reason.to_string()), ..self.next(Phase::Failed, at) }
+        }
+
+        /// Mirror of `RebalanceEvent::is_settled`
+        fn is_settled(&self) -> bool {
+            matches!(self.phase, Phase::Acquired | Phase::Failed)
+        }
+    }
+
+    /// Mirror of how an instance answers an event: `owned` is whether it trades the symbol
+    /// and `released` whether it gave the symbol up for this move
+    fn answer(event: &Event, me: &str, owned: bool, released: bool, now: i64) -> Option<Event> {
+        match event.phase {
+            Phase::Requested if event.from == me => Some(if owned {
+                Event { last_price: Some(100), ..event.next(Phase::Released, now) }
+            } else {
+                event.failed(now, "not traded here")
+            }),
+            Phase::Released if event.to == me => Some(if now > event.deadline {
+                event.failed(now, "deadline")
+            } else {
+                event.next(Phase::Acquired, now)
+            }),
+            Phase::Failed if event.from == me && released => Some(event.next(Phase::Restored, now)),
+            _ => None,
+        }
+    }
+
+    /// Mirror of `ShardSpec::resolve` applying `shard_assignments`
+    fn resolve(configured: &[&str], assignments: &HashMap<&str, &str>, me: &str) -> BTreeSet<String> {
+        let mut symbols: BTreeSet<String> = configured.iter().map(|s| s.to_string()).collect();
+        for (symbol, instance) in assignments {
+            if *instance == me {
+                symbols.insert(symbol.to_string());
+            } else {
+                symbols.remove(*symbol);
+            }
+        }
+        symbols
+    }
+
+    fn requested() -> Event {
+        Event {
+            phase: Phase::Requested,
+            from: "core-a",
+            to: "core-b",
+            at: 0,
+            deadline: 5_000,
+            last_price: None,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_move_steps_through_source_then_target() {
+        let released = answer(&requested(), "core-a", true, false, 10).unwrap();
+        assert_eq!(released.phase, Phase::Released);
+        assert_eq!(released.last_price, Some(100));
+        // Only the source answers a request, only the target a release
+        assert!(answer(&requested(), "core-b", true, false, 10).is_none());
+        assert!(answer(&released, "core-a", false, true, 20).is_none());
+
+        let acquired = answer(&released, "core-b", false, false, 40).unwrap();
+        assert_eq!(acquired.phase, Phase::Acquired);
+        // Each step carries only its own details
+        assert_eq!(acquired.last_price, None);
+        assert!(acquired.is_settled());
+    }
+
+    #[test]
+    fn test_source_without_the_symbol_fails_the_move() {
+        let failed = answer(&requested(), "core-a", false, false, 10).unwrap();
+        assert_eq!(failed.phase, Phase::Failed);
+        assert!(failed.is_settled());
+        // Nothing was released, so nothing is restored
+        assert!(answer(&failed, "core-a", false, false, 20).is_none());
+    }
+
+    #[test]
+    fn test_release_after_deadline_is_restored_to_source() {
+        let released = answer(&requested(), "core-a", true, false, 10).unwrap();
+        let failed = answer(&released, "core-b", false, false, 5_001).unwrap();
+        assert_eq!(failed.phase, Phase::Failed);
+
+        let restored = answer(&failed, "core-a", false, true, 5_010).unwrap();
+        assert_eq!(restored.phase, Phase::Restored);
+        assert_eq!(restored.reason, None);
+        assert!(!restored.is_settled());
+    }
+
+    #[test]
+    fn test_only_acquired_and_failed_settle() {
+        let event = requested();
+        assert!(!event.is_settled());
+        assert!(!event.next(Phase::Released, 1).is_settled());
+        assert!(event.next(Phase::Acquired, 1).is_settled());
+        assert!(event.failed(1, "timed out").is_settled());
+        assert!(!event.next(Phase::Restored, 1).is_settled());
+    }
+
+    #[test]
+    fn test_assignments_override_configured_symbols() {
+        let assignments = HashMap::from([("ETH-USD", "core-b"), ("SOL-USD", "core-a")]);
+
+        let a = resolve(&["BTC-USD", "ETH-USD"], &assignments, "core-a");
+        let b = resolve(&["SOL-USD"], &assignments, "core-b");
+        assert_eq!(a, ["BTC-USD".to_string(), "SOL-USD".to_string()].into());
+        assert_eq!(b, ["ETH-USD".to_string()].into());
+        assert!(a.is_disjoint(&b));
+    }
+}
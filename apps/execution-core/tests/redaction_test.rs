@@ -0,0 +1,193 @@
+//! Unit Tests for Log Redaction
+//! Standalone tests proving sensitive fields and raw JWTs never reach log output
+
+#[cfg(test)]
+mod redaction_tests {
+    use serde_json::{json, Value};
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::MakeWriter;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Layer;
+
+    const JWT: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+        eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ.\
+        SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c";
+    const FIELDS: &[&str] = &["token", "authorization", "username", "webhook_secret", "metadata"];
+
+    /// Mirror of `scrub_jwts`
+    fn scrub_jwts(text: &str) -> Option<String> {
+        if !text.contains("eyJ") {
+            return None;
+        }
+        let bytes = text.as_bytes();
+        let mut out = String::with_capacity(text.len());
+        let (mut copied, mut i, mut found) = (0, 0, false);
+        while let Some(offset) = text[i..].find("eyJ") {
+            let start = i + offset;
+            match jwt_end(bytes, start) {
+                Some(end) => {
+                    out.push_str(&text[copied..start]);
+                    out.push_str("[REDACTED_JWT]");
+                    copied = end;
+                    i = end;
+                    found = true;
+                }
+                None => i = start + 3,
+            }
+        }
+        found.then(|| {
+            out.push_str(&text[copied..]);
+            out
+        })
+    }
+
+    fn jwt_end(bytes: &[u8], start: usize) -> Option<usize> {
+        let base64url = |b: u8| b.is_ascii_alphanumeric() || b == b'-' || b == b'_';
+        let segment = |from: usize| from + bytes[from..].iter().take_while(|b| base64url(**b)).count();
+        let header_end = segment(start);
+        if bytes.get(header_end) != Some(&b'.') {
+            return None;
+        }
+        let payload_end = segment(header_end + 1);
+        if payload_end == header_end + 1 || bytes.get(payload_end) != Some(&b'.') {
+            return None;
+        }
+        Some(segment(payload_end + 1))
+    }
+
+    /// Mirror of `Redactor::redact_value`
+    fn redact_value(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if FIELDS.contains(&key.to_ascii_lowercase().as_str()) {
+                        *value = json!("[REDACTED]");
+                    } else {
+                        redact_value(value);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(redact_value),
+            Value::String(text) => {
+                if let Some(scrubbed) = scrub_jwts(text) {
+                    *text = scrubbed;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn redact_line(line: &str) -> String {
+        match serde_json::from_str::<Value>(line) {
+            Ok(mut value) => {
+                redact_value(&mut value);
+                value.to_string()
+            }
+            Err(_) => scrub_jwts(line).unwrap_or_else(|| line.to_string()),
+        }
+    }
+
+    /// Mirror of `RedactingWriter`, collecting into a shared buffer
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    struct Event {
+        buffer: Vec<u8>,
+        sink: Captured,
+    }
+
+    impl Write for Event {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.buffer.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for Event {
+        fn drop(&mut self) {
+            let text = String::from_utf8_lossy(&self.buffer);
+            let redacted: Vec<String> = text.lines().map(redact_line).collect();
+            let mut sink = self.sink.0.lock().unwrap();
+            for line in redacted {
+                sink.extend_from_slice(line.as_bytes());
+                sink.push(b'\n');
+            }
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Event;
+
+        fn make_writer(&'a self) -> Event {
+            Event { buffer: Vec::new(), sink: self.clone() }
+        }
+    }
+
+    #[test]
+    fn test_scrub_jwts() {
+        assert_eq!(scrub_jwts(&format!("Bearer {}", JWT)).as_deref(), Some("Bearer [REDACTED_JWT]"));
+        assert_eq!(
+            scrub_jwts(&format!("a={} b={}", JWT, JWT)).as_deref(),
+            Some("a=[REDACTED_JWT] b=[REDACTED_JWT]")
+        );
+
+        // Unsigned tokens have an empty signature
+        assert_eq!(scrub_jwts("eyJhbGciOiJub25lIn0.eyJzdWIiOiIxIn0.").as_deref(), Some("[REDACTED_JWT]"));
+
+        // Not a JWT
+        assert_eq!(scrub_jwts("no tokens here"), None);
+        assert_eq!(scrub_jwts("eyJ alone"), None);
+    }
+
+    #[test]
+    fn test_redact_fields() {
+        let mut value = json!({
+            "message": "Order submitted",
+            "fields": { "Authorization": "Bearer abc", "order_id": "42" },
+            "spans": [{ "name": "submit", "username": "alice", "metadata": { "note": "x" } }],
+        });
+        redact_value(&mut value);
+
+        assert_eq!(value["fields"]["Authorization"], "[REDACTED]");
+        assert_eq!(value["fields"]["order_id"], "42");
+        assert_eq!(value["spans"][0]["username"], "[REDACTED]");
+        assert_eq!(value["spans"][0]["metadata"], "[REDACTED]");
+        assert_eq!(value["spans"][0]["name"], "submit");
+    }
+
+    #[test]
+    fn test_jwt_never_logged_at_debug() {
+        let captured = Captured::default();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+                .with_writer(captured.clone())
+                .with_filter(tracing_subscriber::filter::LevelFilter::TRACE),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::debug_span!("auth", token = JWT, username = "alice");
+            let _guard = span.enter();
+            tracing::debug!(token = JWT, "Validating token");
+            tracing::debug!("Raw header was Bearer {}", JWT);
+            tracing::trace!(payload = %format!("{{\"jwt\":\"{}\"}}", JWT), "Payload");
+            tracing::error!(webhook_secret = "s3cr3t", "Webhook failed");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output.lines().count(), 4);
+        assert!(!output.contains(JWT));
+        assert!(!output.contains("SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c"));
+        assert!(!output.contains("alice"));
+        assert!(!output.contains("s3cr3t"));
+        assert!(output.contains("[REDACTED_JWT]"));
+        assert!(output.contains("Webhook failed"));
+    }
+}
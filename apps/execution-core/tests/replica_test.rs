@@ -0,0 +1,72 @@
+//! Unit Tests for Read Replica Routing
+//! Standalone tests for replica-first reads with fallback to the primary
+
+#[cfg(test)]
+mod replica_tests {
+    use std::cell::RefCell;
+
+    #[derive(Debug, PartialEq)]
+    enum QueryError {
+        RowNotFound,
+        Connection,
+    }
+
+    /// Mirror of `ReadPool::run`, recording which pools were hit and the outcome
+    fn run<T>(
+        has_replica: bool,
+        log: &RefCell<Vec<(&'static str, &'static str)>>,
+        query: impl Fn(&'static str) -> Result<T, QueryError>,
+    ) -> Result<T, QueryError> {
+        if has_replica {
+            match query("replica") {
+                Err(QueryError::RowNotFound) => {
+                    log.borrow_mut().push(("replica", "ok"));
+                    return Err(QueryError::RowNotFound);
+                }
+                Err(_) => log.borrow_mut().push(("replica", "fallback")),
+                Ok(value) => {
+                    log.borrow_mut().push(("replica", "ok"));
+                    return Ok(value);
+                }
+            }
+        }
+
+        let result = query("primary");
+        log.borrow_mut().push(("primary", if result.is_ok() { "ok" } else { "error" }));
+        result
+    }
+
+    #[test]
+    fn test_replica_serves_reads_when_healthy() {
+        let log = RefCell::new(Vec::new());
+        let result = run(true, &log, Ok);
+        assert_eq!(result, Ok("replica"));
+        assert_eq!(*log.borrow(), vec![("replica", "ok")]);
+    }
+
+    #[test]
+    fn test_falls_back_to_primary_on_replica_error() {
+        let log = RefCell::new(Vec::new());
+        let result = run(true, &log, |pool| {
+            if pool == "replica" { Err(QueryError::Connection) } else { Ok(pool) }
+        });
+        assert_eq!(result, Ok("primary"));
+        assert_eq!(*log.borrow(), vec![("replica", "fallback"), ("primary", "ok")]);
+    }
+
+    #[test]
+    fn test_row_not_found_is_not_retried() {
+        let log = RefCell::new(Vec::new());
+        let result: Result<(), _> = run(true, &log, |_| Err(QueryError::RowNotFound));
+        assert_eq!(result, Err(QueryError::RowNotFound));
+        assert_eq!(log.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_without_replica_reads_go_to_primary() {
+        let log = RefCell::new(Vec::new());
+        let result = run(false, &log, Ok);
+        assert_eq!(result, Ok("primary"));
+        assert_eq!(*log.borrow(), vec![("primary", "ok")]);
+    }
+}
@@ -0,0 +1,69 @@
+//! Unit Tests for Data Retention
+//! Standalone tests for rule parsing and batch scheduling
+
+#[cfg(test)]
+mod retention_tests {
+    use std::time::Duration;
+
+    /// Mirror of `parse_age` in the retention module
+    fn parse_age(age: &str) -> Option<Duration> {
+        let split = age.len().checked_sub(1)?;
+        let (count, unit) = age.split_at(split);
+        let count: u64 = count.parse().ok()?;
+        let unit_secs = match unit {
+            "h" => 3_600,
+            "d" => 86_400,
+            "w" => 7 * 86_400,
+            "y" => 365 * 86_400,
+            _ => return None,
+        };
+
+        (count > 0).then(|| Duration::from_secs(count * unit_secs))
+    }
+
+    /// Batch sizes a purge of `expired` rows goes through; stops on a short batch
+    fn batches(mut expired: u64, batch_size: u64) -> Vec<u64> {
+        let mut out = Vec::new();
+        loop {
+            let deleted = expired.min(batch_size);
+            expired -= deleted;
+            out.push(deleted);
+            if deleted < batch_size {
+                return out;
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_default_ages() {
+        assert_eq!(parse_age("30d"), Some(Duration::from_secs(30 * 86_400)));
+        assert_eq!(parse_age("2y"), Some(Duration::from_secs(730 * 86_400)));
+        assert_eq!(parse_age("7y"), Some(Duration::from_secs(7 * 365 * 86_400)));
+        assert_eq!(parse_age("12h"), Some(Duration::from_secs(12 * 3_600)));
+    }
+
+    #[test]
+    fn test_reject_invalid_ages() {
+        assert_eq!(parse_age(""), None);
+        assert_eq!(parse_age("d"), None);
+        assert_eq!(parse_age("0d"), None);
+        assert_eq!(parse_age("30"), None);
+        assert_eq!(parse_age("30m"), None);
+        assert_eq!(parse_age("-1d"), None);
+    }
+
+    #[test]
+    fn test_purge_runs_in_batches() {
+        assert_eq!(batches(12_000, 5_000), vec![5_000, 5_000, 2_000]);
+    }
+
+    #[test]
+    fn test_exact_multiple_ends_with_empty_batch() {
+        assert_eq!(batches(10_000, 5_000), vec![5_000, 5_000, 0]);
+    }
+
+    #[test]
+    fn test_nothing_expired_is_one_empty_batch() {
+        assert_eq!(batches(0, 5_000), vec![0]);
+    }
+}
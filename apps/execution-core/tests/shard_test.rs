@@ -0,0 +1,115 @@
+//! Unit Tests for Symbol Sharding
+//! Standalone tests for parsing a shard, how symbols fall into hash buckets and which
+//! instance owns a symbol
+
+use std::collections::BTreeSet;
+
+#[cfg(test)]
+mod shard_tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Spec {
+        Symbols(BTreeSet<String>),
+        HashRange { first: u32, last: u32, buckets: u32 },
+    }
+
+    /// Mirror of `ShardSpec::parse`, errors as plain strings
+    fn parse(symbols: &str, hash_range: &str) -> Result<Option<Spec>, String> {
+        let (symbols, hash_range) = (symbols.trim(), hash_range.trim());
+        match (symbols.is_empty(), hash_range.is_empty()) {
+            (true, true) => Ok(None),
+            (false, false) => Err("both".into()),
+            (false, true) => {
+                let mut owned = BTreeSet::new();
+                for symbol in symbols.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                    if !is_subject_token(symbol) {
+                        return Err(format!("token {}", symbol));
+                    }
+                    if !owned.insert(symbol.to_string()) {
+                        return Err(format!("duplicate {}", symbol));
+                    }
+                }
+                Ok(Some(Spec::Symbols(owned)))
+            }
+            (true, false) => {
+                let invalid = || format!("format {}", hash_range);
+                let (range, buckets) = hash_range.split_once('/').ok_or_else(invalid)?;
+                let (first, last) = range.split_once('-').ok_or_else(invalid)?;
+                let parse = |n: &str| n.trim().parse::<u32>().map_err(|_| invalid());
+                let (first, last, buckets) = (parse(first)?, parse(last)?, parse(buckets)?);
+                if buckets == 0 || first > last || last >= buckets {
+                    return Err(format!("bounds {}", hash_range));
+                }
+                Ok(Some(Spec::HashRange { first, last, buckets }))
+            }
+        }
+    }
+
+    /// Mirror of `bucket`
+    fn bucket(symbol: &str, buckets: u32) -> u32 {
+        let hash = symbol
+            .bytes()
+            .fold(0x811c_9dc5_u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193));
+        hash % buckets
+    }
+
+    /// Mirror of `is_subject_token`
+    fn is_subject_token(symbol: &str) -> bool {
+        !symbol.is_empty() && !symbol.contains(['.', '*', '>', ' '])
+    }
+
+    fn owns(spec: &Spec, symbol: &str) -> bool {
+        match spec {
+            Spec::Symbols(symbols) => symbols.contains(symbol),
+            Spec::HashRange { first, last, buckets } => (*first..=*last).contains(&bucket(symbol, *buckets)),
+        }
+    }
+
+    #[test]
+    fn test_unsharded_without_either_setting() {
+        assert_eq!(parse("", " "), Ok(None));
+        assert!(parse("BTC-USD", "0-1/2").is_err());
+    }
+
+    #[test]
+    fn test_symbol_list() {
+        let spec = parse(" BTC-USD, ETH-USD ,", "").unwrap().unwrap();
+        assert_eq!(spec, Spec::Symbols(["BTC-USD".to_string(), "ETH-USD".to_string()].into()));
+
+        assert!(parse("BTC-USD,BTC-USD", "").is_err());
+        // A symbol routes as a single subject token
+        assert!(parse("BTC.USD", "").is_err());
+        assert!(parse("BTC-*", "").is_err());
+    }
+
+    #[test]
+    fn test_hash_range() {
+        assert_eq!(parse("", "0-7/16"), Ok(Some(Spec::HashRange { first: 0, last: 7, buckets: 16 })));
+        assert_eq!(parse("", "3-3/4"), Ok(Some(Spec::HashRange { first: 3, last: 3, buckets: 4 })));
+
+        for invalid in ["0-7", "7/16", "8-7/16", "0-16/16", "0-0/0", "a-b/c"] {
+            assert!(parse("", invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_bucket_is_fnv1a() {
+        // FNV-1a 32 of the empty string and of "a"
+        assert_eq!(bucket("", u32::MAX), 0x811c_9dc5);
+        assert_eq!(bucket("a", u32::MAX), 0xe40c_292c);
+    }
+
+    #[test]
+    fn test_complementary_ranges_split_every_symbol_once() {
+        let shards = [parse("", "0-4/16").unwrap().unwrap(), parse("", "5-15/16").unwrap().unwrap()];
+        let symbols: Vec<String> = (0..500).map(|i| format!("SYM{}-USD", i)).collect();
+
+        for symbol in &symbols {
+            let owners = shards.iter().filter(|shard| owns(shard, symbol)).count();
+            assert_eq!(owners, 1, "{}", symbol);
+        }
+        // Both shards get a share of the symbols
+        assert!(shards.iter().all(|shard| symbols.iter().any(|s| owns(shard, s))));
+    }
+}
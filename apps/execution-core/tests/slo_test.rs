@@ -0,0 +1,54 @@
+//! Unit Tests for SLO Burn Rates
+//! Standalone tests for burn-rate math and multi-window alert evaluation
+
+#[cfg(test)]
+mod slo_tests {
+    /// Mirror of `burn_rate`
+    fn burn_rate(good: u64, total: u64, objective: f64) -> f64 {
+        if total == 0 || objective >= 1.0 {
+            return 0.0;
+        }
+        let bad_ratio = (total - good.min(total)) as f64 / total as f64;
+        bad_ratio / (1.0 - objective)
+    }
+
+    /// Mirror of the alert rule check: both windows must exceed the threshold
+    fn fires(long: f64, short: f64, threshold: f64) -> bool {
+        long > threshold && short > threshold
+    }
+
+    #[test]
+    fn test_burn_rate() {
+        // No traffic burns nothing
+        assert_eq!(burn_rate(0, 0, 0.99), 0.0);
+
+        // Exactly on objective: budget spent over the whole period
+        assert!((burn_rate(99, 100, 0.99) - 1.0).abs() < 1e-9);
+
+        // 10% bad against a 1% budget
+        assert!((burn_rate(90, 100, 0.99) - 10.0).abs() < 1e-9);
+
+        // Everything bad against 99.9%
+        assert!((burn_rate(0, 1_000, 0.999) - 1_000.0).abs() < 1e-6);
+
+        // All good
+        assert_eq!(burn_rate(500, 500, 0.999), 0.0);
+    }
+
+    #[test]
+    fn test_multiwindow_alerts() {
+        // A short spike alone does not page
+        assert!(!fires(2.0, 50.0, 14.4));
+
+        // A long burn that has already recovered does not page either
+        assert!(!fires(20.0, 1.0, 14.4));
+
+        // Both windows hot
+        assert!(fires(20.0, 30.0, 14.4));
+
+        // Slow burn raises a ticket but not a page
+        let (long, short) = (8.0, 7.0);
+        assert!(fires(long, short, 6.0));
+        assert!(!fires(long, short, 14.4));
+    }
+}
@@ -0,0 +1,99 @@
+//! Unit Tests for Order Stage Timing
+//! Standalone tests for charging laps to stages and finding the stages over budget
+
+use std::time::Duration;
+
+#[cfg(test)]
+mod stages_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Stage {
+        Deserialize,
+        Auth,
+        Risk,
+        Db,
+        Publish,
+    }
+
+    const ALL: [Stage; 5] = [Stage::Deserialize, Stage::Auth, Stage::Risk, Stage::Db, Stage::Publish];
+    const BUDGET: Duration = Duration::from_millis(20);
+
+    fn budget(stage: Stage) -> Duration {
+        match stage {
+            Stage::Deserialize | Stage::Auth => Duration::from_millis(1),
+            Stage::Risk => Duration::from_millis(5),
+            Stage::Db => Duration::from_millis(10),
+            Stage::Publish => Duration::from_millis(3),
+        }
+    }
+
+    /// Mirror of `StageTimer` driven by explicit clock readings
+    struct Timer {
+        last: Duration,
+        spent: [Duration; 5],
+    }
+
+    impl Timer {
+        fn start(now: Duration) -> Self {
+            Self { last: now, spent: [Duration::ZERO; 5] }
+        }
+
+        fn lap(&mut self, stage: Stage, now: Duration) {
+            self.spent[stage as usize] += now.saturating_sub(self.last);
+            self.last = now;
+        }
+
+        fn over_budget(&self) -> Vec<Stage> {
+            let total: Duration = self.spent.iter().sum();
+            if total <= BUDGET {
+                return Vec::new();
+            }
+            ALL.into_iter().filter(|s| self.spent[*s as usize] > budget(*s)).collect()
+        }
+    }
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_stage_budgets_add_up_to_order_budget() {
+        let total: Duration = ALL.into_iter().map(budget).sum();
+        assert_eq!(total, BUDGET);
+    }
+
+    #[test]
+    fn test_laps_charge_time_since_previous_lap() {
+        let mut timer = Timer::start(ms(100));
+        timer.lap(Stage::Deserialize, ms(101));
+        timer.lap(Stage::Auth, ms(101));
+        timer.lap(Stage::Db, ms(104));
+        timer.lap(Stage::Risk, ms(106));
+        timer.lap(Stage::Db, ms(111));
+
+        assert_eq!(timer.spent[Stage::Deserialize as usize], ms(1));
+        assert_eq!(timer.spent[Stage::Auth as usize], ms(0));
+        assert_eq!(timer.spent[Stage::Risk as usize], ms(2));
+        // Both database laps accumulate
+        assert_eq!(timer.spent[Stage::Db as usize], ms(8));
+    }
+
+    #[test]
+    fn test_order_within_budget_names_no_stage() {
+        let mut timer = Timer::start(ms(0));
+        // Risk overran its share but the order as a whole did not
+        timer.lap(Stage::Risk, ms(8));
+        timer.lap(Stage::Db, ms(12));
+        assert!(timer.over_budget().is_empty());
+    }
+
+    #[test]
+    fn test_order_over_budget_names_stages_that_overran() {
+        let mut timer = Timer::start(ms(0));
+        timer.lap(Stage::Deserialize, ms(1));
+        timer.lap(Stage::Db, ms(26));
+        timer.lap(Stage::Publish, ms(30));
+        assert_eq!(timer.over_budget(), vec![Stage::Db, Stage::Publish]);
+    }
+}
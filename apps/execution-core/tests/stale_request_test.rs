@@ -0,0 +1,64 @@
+//! Unit Tests for the Order-Entry SLA
+//! Standalone tests for how old a request is by its client send time, and when it is stale
+
+use chrono::{DateTime, TimeZone, Utc};
+use std::time::Duration;
+
+#[cfg(test)]
+mod stale_request_tests {
+    use super::*;
+
+    /// Mirror of `sent_at` and the age taken from it in `NatsSubscriber::stale`
+    fn request_age(sent_at: Option<&str>, now: DateTime<Utc>) -> Option<Duration> {
+        let sent_ms: i64 = sent_at?.trim().parse().ok()?;
+        let sent = DateTime::<Utc>::from_timestamp_millis(sent_ms)?;
+        Some((now - sent).to_std().unwrap_or_default())
+    }
+
+    /// Mirror of the check in `NatsSubscriber::stale`
+    fn stale(sent_at: Option<&str>, now: DateTime<Utc>, sla: Duration, trusted: bool) -> bool {
+        if sla.is_zero() || !trusted {
+            return false;
+        }
+        matches!(request_age(sent_at, now), Some(age) if age > sla)
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn sent(ms_before: i64) -> String {
+        (now().timestamp_millis() - ms_before).to_string()
+    }
+
+    #[test]
+    fn test_age_is_measured_from_the_header() {
+        assert_eq!(request_age(Some(&sent(250)), now()), Some(Duration::from_millis(250)));
+        assert_eq!(request_age(Some(&format!(" {} ", sent(5))), now()), Some(Duration::from_millis(5)));
+    }
+
+    #[test]
+    fn test_missing_or_invalid_header_has_no_age() {
+        assert_eq!(request_age(None, now()), None);
+        assert_eq!(request_age(Some("2024-03-01T12:00:00Z"), now()), None);
+    }
+
+    #[test]
+    fn test_sender_clock_ahead_counts_as_fresh() {
+        assert_eq!(request_age(Some(&sent(-1_000)), now()), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_stale_only_past_the_sla() {
+        let sla = Duration::from_millis(500);
+        assert!(!stale(Some(&sent(500)), now(), sla, true));
+        assert!(stale(Some(&sent(501)), now(), sla, true));
+        assert!(!stale(None, now(), sla, true));
+        assert!(!stale(Some(&sent(60_000)), now(), Duration::ZERO, true));
+    }
+
+    #[test]
+    fn test_never_stale_while_client_clocks_are_untrusted() {
+        assert!(!stale(Some(&sent(60_000)), now(), Duration::from_millis(500), false));
+    }
+}
@@ -0,0 +1,160 @@
+//! Unit Tests for Stop and Stop-Limit Orders
+//! Standalone tests for stop validation, triggering on ticks and conversion once triggered
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod stop_order_tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Order {
+        side: &'static str,
+        order_type: &'static str,
+        price: Option<Decimal>,
+        stop_price: Option<Decimal>,
+    }
+
+    fn stop(side: &'static str, stop_price: Decimal) -> Order {
+        Order { side, order_type: "stop", price: None, stop_price: Some(stop_price) }
+    }
+
+    fn stop_limit(side: &'static str, stop_price: Decimal, price: Decimal) -> Order {
+        Order { side, order_type: "stop_limit", price: Some(price), stop_price: Some(stop_price) }
+    }
+
+    /// Mirror of `triggered_type`
+    fn triggered_type(order_type: &str) -> Option<&'static str> {
+        match order_type {
+            "stop" => Some("market"),
+            "stop_limit" => Some("limit"),
+            _ => None,
+        }
+    }
+
+    /// Mirror of `stop_triggered`
+    fn stop_triggered(order: &Order, tick: Decimal) -> bool {
+        if triggered_type(order.order_type).is_none() {
+            return false;
+        }
+        match (order.side, order.stop_price) {
+            ("buy", Some(stop)) => tick >= stop,
+            ("sell", Some(stop)) => tick <= stop,
+            _ => false,
+        }
+    }
+
+    /// Mirror of `execution_price` without slippage
+    fn execution_price(order: &Order, tick: Decimal) -> Option<Decimal> {
+        if triggered_type(order.order_type).is_some() {
+            return None;
+        }
+        match (order.side, order.price) {
+            ("buy", Some(limit)) => (tick <= limit).then_some(tick),
+            ("sell", Some(limit)) => (tick >= limit).then_some(tick),
+            (_, None) if order.order_type == "market" => Some(tick),
+            _ => None,
+        }
+    }
+
+    /// Mirror of `validate_stop`
+    fn validate_stop(order: &Order) -> Result<(), &'static str> {
+        match (triggered_type(order.order_type), order.stop_price) {
+            (None, None) => Ok(()),
+            (None, Some(_)) => Err("stop_price on a non-stop order"),
+            (Some(_), None) => Err("missing stop_price"),
+            (Some(_), Some(stop)) if stop <= Decimal::ZERO => Err("non-positive stop_price"),
+            (Some("limit"), Some(_)) if order.price.is_none() => Err("stop_limit without price"),
+            (Some("market"), Some(_)) if order.price.is_some() => Err("stop with price"),
+            (Some(_), Some(_)) => Ok(()),
+        }
+    }
+
+    /// Mirror of the order type chosen in `apply_defaults`
+    fn default_type(price: Option<Decimal>, stop_price: Option<Decimal>, account_default: Option<&'static str>) -> &'static str {
+        stop_price
+            .map(|_| if price.is_some() { "stop_limit" } else { "stop" })
+            .or(account_default)
+            .unwrap_or(if price.is_some() { "limit" } else { "market" })
+    }
+
+    /// One tick: trigger stops, then match, as `process_market_tick` does
+    fn on_tick(order: &mut Order, tick: Decimal) -> Option<Decimal> {
+        if stop_triggered(order, tick) {
+            order.order_type = triggered_type(order.order_type).unwrap();
+        }
+        execution_price(order, tick)
+    }
+
+    #[test]
+    fn test_buy_stop_triggers_at_or_above_stop_price() {
+        let order = stop("buy", dec!(105));
+        assert!(!stop_triggered(&order, dec!(104.99)));
+        assert!(stop_triggered(&order, dec!(105)));
+        assert!(stop_triggered(&order, dec!(110)));
+    }
+
+    #[test]
+    fn test_sell_stop_triggers_at_or_below_stop_price() {
+        let order = stop("sell", dec!(95));
+        assert!(!stop_triggered(&order, dec!(95.01)));
+        assert!(stop_triggered(&order, dec!(95)));
+        assert!(stop_triggered(&order, dec!(90)));
+    }
+
+    #[test]
+    fn test_untriggered_stops_never_execute() {
+        // The limit of a stop-limit is marketable here, but the stop has not traded
+        let order = stop_limit("buy", dec!(105), dec!(106));
+        assert_eq!(execution_price(&order, dec!(100)), None);
+        assert_eq!(execution_price(&stop("sell", dec!(95)), dec!(100)), None);
+    }
+
+    #[test]
+    fn test_triggered_stop_fills_as_market_order() {
+        let mut order = stop("sell", dec!(95));
+        assert_eq!(on_tick(&mut order, dec!(96)), None);
+        assert_eq!(on_tick(&mut order, dec!(94.5)), Some(dec!(94.5)));
+        assert_eq!(order.order_type, "market");
+    }
+
+    #[test]
+    fn test_triggered_stop_limit_rests_until_its_limit_crosses() {
+        let mut order = stop_limit("buy", dec!(105), dec!(106));
+        assert_eq!(on_tick(&mut order, dec!(107)), None);
+        assert_eq!(order.order_type, "limit");
+        // Once triggered the order stays a limit order, even if the price falls back below the stop
+        assert_eq!(on_tick(&mut order, dec!(104)), Some(dec!(104)));
+    }
+
+    #[test]
+    fn test_plain_orders_are_never_triggered() {
+        let limit = Order { side: "buy", order_type: "limit", price: Some(dec!(100)), stop_price: None };
+        assert!(!stop_triggered(&limit, dec!(1000)));
+    }
+
+    #[test]
+    fn test_stop_price_validation() {
+        assert_eq!(validate_stop(&stop("buy", dec!(105))), Ok(()));
+        assert_eq!(validate_stop(&stop_limit("buy", dec!(105), dec!(106))), Ok(()));
+        assert!(validate_stop(&stop("buy", dec!(0))).is_err());
+        assert!(validate_stop(&Order { stop_price: None, ..stop("buy", dec!(1)) }).is_err());
+        assert!(validate_stop(&Order { price: None, ..stop_limit("buy", dec!(105), dec!(1)) }).is_err());
+        assert!(validate_stop(&Order { price: Some(dec!(106)), ..stop("buy", dec!(105)) }).is_err());
+
+        let limit = Order { side: "buy", order_type: "limit", price: Some(dec!(100)), stop_price: Some(dec!(99)) };
+        assert!(validate_stop(&limit).is_err());
+    }
+
+    #[test]
+    fn test_order_type_defaults_from_stop_price() {
+        assert_eq!(default_type(None, Some(dec!(105)), None), "stop");
+        assert_eq!(default_type(Some(dec!(106)), Some(dec!(105)), None), "stop_limit");
+        assert_eq!(default_type(Some(dec!(106)), None, None), "limit");
+        assert_eq!(default_type(None, None, None), "market");
+        // The account default does not override a stop price
+        assert_eq!(default_type(Some(dec!(106)), Some(dec!(105)), Some("limit")), "stop_limit");
+        assert_eq!(default_type(Some(dec!(106)), None, Some("market")), "market");
+    }
+}
@@ -0,0 +1,58 @@
+//! Unit Tests for Storage Dialects
+//! Standalone tests for backend detection and transaction retry classification
+
+#[cfg(test)]
+mod storage_tests {
+    #[derive(Debug, PartialEq)]
+    enum Dialect {
+        Postgres,
+        CockroachDb,
+        YugabyteDb,
+    }
+
+    fn from_version(version: &str) -> Dialect {
+        if version.contains("CockroachDB") {
+            Dialect::CockroachDb
+        } else if version.contains("-YB-") {
+            Dialect::YugabyteDb
+        } else {
+            Dialect::Postgres
+        }
+    }
+
+    fn is_retryable(dialect: &Dialect, code: Option<&str>, message: &str) -> bool {
+        match code {
+            Some("40001") | Some("40P01") => true,
+            _ if *dialect == Dialect::CockroachDb => message.contains("restart transaction"),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_dialect_from_server_version() {
+        assert_eq!(
+            from_version("CockroachDB CCL v23.1.11 (x86_64-pc-linux-gnu, built 2023/09/27)"),
+            Dialect::CockroachDb
+        );
+        assert_eq!(
+            from_version("PostgreSQL 11.2-YB-2.20.1.0-b0 on x86_64-pc-linux-gnu"),
+            Dialect::YugabyteDb
+        );
+        assert_eq!(from_version("PostgreSQL 16.1 on x86_64-pc-linux-musl"), Dialect::Postgres);
+    }
+
+    #[test]
+    fn test_serialization_failures_are_retried() {
+        for dialect in [Dialect::Postgres, Dialect::CockroachDb, Dialect::YugabyteDb] {
+            assert!(is_retryable(&dialect, Some("40001"), "could not serialize access"));
+            assert!(!is_retryable(&dialect, Some("23505"), "duplicate key value"));
+        }
+    }
+
+    #[test]
+    fn test_cockroach_restart_message_is_retried() {
+        let message = "restart transaction: TransactionRetryWithProtoRefreshError";
+        assert!(is_retryable(&Dialect::CockroachDb, None, message));
+        assert!(!is_retryable(&Dialect::Postgres, None, message));
+    }
+}
@@ -0,0 +1,128 @@
+//! Unit Tests for Strategy Limits
+//! Standalone tests for per-tag kill switches, order rate and daily notional limits
+
+use chrono::{DateTime, Duration, DurationRound, NaiveDate, TimeDelta, TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+
+#[cfg(test)]
+mod strategy_limits_tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Limit {
+        halted: bool,
+        max_orders_per_minute: Option<i32>,
+        max_notional_per_day: Option<Decimal>,
+    }
+
+    /// Row of `strategy_tag_usage`
+    struct Usage {
+        day: NaiveDate,
+        notional: Decimal,
+        minute: DateTime<Utc>,
+        minute_orders: i32,
+    }
+
+    /// Mirror of `strategy_tag`
+    fn strategy_tag(metadata: Option<&Value>) -> Option<&str> {
+        metadata?.get("strategy_tag")?.as_str()
+    }
+
+    fn minute_of(now: DateTime<Utc>) -> DateTime<Utc> {
+        now.duration_trunc(TimeDelta::minutes(1)).unwrap_or(now)
+    }
+
+    /// Mirror of the upsert in `StrategyLimits::consume`
+    fn count(usage: &mut Option<Usage>, now: DateTime<Utc>, notional: Decimal) -> (i32, Decimal) {
+        let minute = minute_of(now);
+        match usage {
+            Some(u) if u.day == now.date_naive() => {
+                u.notional += notional;
+                u.minute_orders = if u.minute == minute { u.minute_orders + 1 } else { 1 };
+                u.minute = minute;
+            }
+            _ => {
+                *usage = Some(Usage { day: now.date_naive(), notional, minute, minute_orders: 1 });
+            }
+        }
+        let u = usage.as_ref().unwrap();
+        (u.minute_orders, u.notional)
+    }
+
+    /// Mirror of `StrategyLimit::exceeded`
+    fn exceeded(limit: &Limit, orders_this_minute: i32, notional_today: Decimal) -> Option<&'static str> {
+        if limit.max_orders_per_minute.is_some_and(|max| orders_this_minute > max) {
+            return Some("orders per minute");
+        }
+        if limit.max_notional_per_day.is_some_and(|max| notional_today > max) {
+            return Some("notional per day");
+        }
+        None
+    }
+
+    /// Submit one order: a halted tag rejects it outright, a breach rolls the count back
+    fn submit(limit: &Limit, usage: &mut Option<Usage>, now: DateTime<Utc>, notional: Decimal) -> Result<(), &'static str> {
+        if limit.halted {
+            return Err("halted");
+        }
+        let before = usage.as_ref().map(|u| (u.day, u.notional, u.minute, u.minute_orders));
+        let (orders, total) = count(usage, now, notional);
+        if let Some(reason) = exceeded(limit, orders, total) {
+            *usage = before.map(|(day, notional, minute, minute_orders)| Usage { day, notional, minute, minute_orders });
+            return Err(reason);
+        }
+        Ok(())
+    }
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, hour, minute, second).unwrap()
+    }
+
+    #[test]
+    fn test_tag_comes_from_metadata() {
+        assert_eq!(strategy_tag(Some(&json!({ "strategy_tag": "momo" }))), Some("momo"));
+        assert_eq!(strategy_tag(Some(&json!({ "strategy_tag": 7 }))), None);
+        assert_eq!(strategy_tag(Some(&json!({}))), None);
+        assert_eq!(strategy_tag(None), None);
+    }
+
+    #[test]
+    fn test_halted_tag_rejects_everything() {
+        let limit = Limit { halted: true, ..Limit::default() };
+        let mut usage = None;
+        assert_eq!(submit(&limit, &mut usage, at(12, 0, 0), dec!(1)), Err("halted"));
+        assert!(usage.is_none());
+    }
+
+    #[test]
+    fn test_orders_per_minute() {
+        let limit = Limit { max_orders_per_minute: Some(2), ..Limit::default() };
+        let mut usage = None;
+        assert_eq!(submit(&limit, &mut usage, at(12, 0, 1), dec!(1)), Ok(()));
+        assert_eq!(submit(&limit, &mut usage, at(12, 0, 30), dec!(1)), Ok(()));
+        assert_eq!(submit(&limit, &mut usage, at(12, 0, 59), dec!(1)), Err("orders per minute"));
+        // The rejected order was not counted, and the next minute starts over
+        assert_eq!(usage.as_ref().unwrap().minute_orders, 2);
+        assert_eq!(submit(&limit, &mut usage, at(12, 1, 0), dec!(1)), Ok(()));
+    }
+
+    #[test]
+    fn test_notional_per_day() {
+        let limit = Limit { max_notional_per_day: Some(dec!(10000)), ..Limit::default() };
+        let mut usage = None;
+        assert_eq!(submit(&limit, &mut usage, at(9, 0, 0), dec!(6000)), Ok(()));
+        assert_eq!(submit(&limit, &mut usage, at(10, 0, 0), dec!(5000)), Err("notional per day"));
+        assert_eq!(submit(&limit, &mut usage, at(10, 0, 0), dec!(4000)), Ok(()));
+        assert_eq!(submit(&limit, &mut usage, at(10, 0, 0) + Duration::days(1), dec!(9000)), Ok(()));
+    }
+
+    #[test]
+    fn test_unlimited_tag_passes() {
+        let mut usage = None;
+        for second in 0..50 {
+            assert_eq!(submit(&Limit::default(), &mut usage, at(12, 0, second), dec!(1000000)), Ok(()));
+        }
+    }
+}
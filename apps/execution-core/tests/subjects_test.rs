@@ -0,0 +1,63 @@
+//! Unit Tests for Per-Subject Handler Statistics
+//! Standalone tests for the sliding rate window and error-rate summary
+
+#[cfg(test)]
+mod subjects_tests {
+    use std::collections::VecDeque;
+
+    const RATE_WINDOW_SECS: u64 = 60;
+    const BUCKET_SECS: u64 = 5;
+
+    /// Mirror of `SubjectCounters::bucket`: (bucket index, received, errors)
+    fn bucket(recent: &mut VecDeque<(u64, u64, u64)>, index: u64) -> &mut (u64, u64, u64) {
+        if recent.back().map(|b| b.0) != Some(index) {
+            recent.push_back((index, 0, 0));
+        }
+        let window = RATE_WINDOW_SECS / BUCKET_SECS;
+        while recent.front().is_some_and(|b| b.0 + window <= index) {
+            recent.pop_front();
+        }
+        recent.back_mut().unwrap()
+    }
+
+    /// Mirror of the windowed sums in `summaries`
+    fn in_window(recent: &VecDeque<(u64, u64, u64)>, index: u64) -> (u64, u64) {
+        let window = RATE_WINDOW_SECS / BUCKET_SECS;
+        recent
+            .iter()
+            .filter(|b| b.0 + window > index)
+            .fold((0, 0), |(r, e), b| (r + b.1, e + b.2))
+    }
+
+    #[test]
+    fn test_rate_window() {
+        let mut recent = VecDeque::new();
+
+        // 10 messages in the first bucket, 5 (2 errors) a bucket later
+        bucket(&mut recent, 0).1 += 10;
+        let b = bucket(&mut recent, 1);
+        b.1 += 5;
+        b.2 += 2;
+        assert_eq!(in_window(&recent, 1), (15, 2));
+
+        // Bucket 0 leaves the 12-bucket window at index 12
+        assert_eq!(in_window(&recent, 11), (15, 2));
+        assert_eq!(in_window(&recent, 12), (5, 2));
+
+        // Recording far later drops everything old
+        bucket(&mut recent, 100).1 += 1;
+        assert_eq!(recent.len(), 1);
+        assert_eq!(in_window(&recent, 100), (1, 0));
+    }
+
+    #[test]
+    fn test_error_rate_and_average_payload() {
+        let (replies, errors) = (200u64, 5u64);
+        let error_rate = if replies == 0 { 0.0 } else { errors as f64 / replies as f64 };
+        assert!((error_rate - 0.025).abs() < 1e-9);
+
+        let (payload_bytes, received) = (4_096u64, 0u64);
+        assert_eq!(payload_bytes.checked_div(received).unwrap_or(0), 0);
+        assert_eq!(payload_bytes.checked_div(16).unwrap_or(0), 256);
+    }
+}
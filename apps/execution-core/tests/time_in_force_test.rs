@@ -0,0 +1,119 @@
+//! Unit Tests for Time in Force
+//! Standalone tests for immediate-or-cancel and fill-or-kill orders matched at submission
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod time_in_force_tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Outcome {
+        Filled(Decimal),
+        Resting,
+        Cancelled,
+        Rejected(&'static str),
+    }
+
+    struct Order {
+        side: &'static str,
+        order_type: &'static str,
+        price: Option<Decimal>,
+        time_in_force: Option<&'static str>,
+        has_trigger: bool,
+    }
+
+    fn order(side: &'static str, price: Option<Decimal>, time_in_force: &'static str) -> Order {
+        Order {
+            side,
+            order_type: if price.is_some() { "limit" } else { "market" },
+            price,
+            time_in_force: Some(time_in_force),
+            has_trigger: false,
+        }
+    }
+
+    /// Mirror of `executes_immediately`
+    fn executes_immediately(time_in_force: Option<&str>) -> bool {
+        matches!(time_in_force, Some("ioc" | "fok"))
+    }
+
+    /// Mirror of `validate_time_in_force`
+    fn validate(order: &Order) -> Result<(), &'static str> {
+        if !executes_immediately(order.time_in_force) {
+            return Ok(());
+        }
+        if matches!(order.order_type, "stop" | "stop_limit" | "trailing_stop") {
+            return Err("stop");
+        }
+        if order.has_trigger {
+            return Err("trigger");
+        }
+        Ok(())
+    }
+
+    /// Mirror of `execution_price` without slippage
+    fn execution_price(order: &Order, tick: Decimal) -> Option<Decimal> {
+        match (order.side, order.price) {
+            ("buy", Some(limit)) => (tick <= limit).then_some(tick),
+            ("sell", Some(limit)) => (tick >= limit).then_some(tick),
+            (_, None) => Some(tick),
+            _ => None,
+        }
+    }
+
+    /// What `submit_order` does with an order given the last trade
+    fn submit(order: &Order, last_price: Option<Decimal>) -> Outcome {
+        if let Err(reason) = validate(order) {
+            return Outcome::Rejected(reason);
+        }
+        let immediate = executes_immediately(order.time_in_force);
+        let fill = last_price.filter(|_| immediate).and_then(|p| execution_price(order, p));
+        match (fill, order.time_in_force) {
+            (Some(price), _) => Outcome::Filled(price),
+            (None, Some("fok")) => Outcome::Rejected("NOT_FILLABLE"),
+            (None, Some("ioc")) => Outcome::Cancelled,
+            (None, _) => Outcome::Resting,
+        }
+    }
+
+    #[test]
+    fn test_marketable_ioc_fills_at_once() {
+        assert_eq!(submit(&order("buy", Some(dec!(101)), "ioc"), Some(dec!(100))), Outcome::Filled(dec!(100)));
+        assert_eq!(submit(&order("sell", None, "ioc"), Some(dec!(100))), Outcome::Filled(dec!(100)));
+    }
+
+    #[test]
+    fn test_unmarketable_ioc_is_cancelled() {
+        assert_eq!(submit(&order("buy", Some(dec!(99)), "ioc"), Some(dec!(100))), Outcome::Cancelled);
+        assert_eq!(submit(&order("sell", Some(dec!(101)), "ioc"), Some(dec!(100))), Outcome::Cancelled);
+    }
+
+    #[test]
+    fn test_unfillable_fok_is_rejected() {
+        assert_eq!(submit(&order("buy", Some(dec!(99)), "fok"), Some(dec!(100))), Outcome::Rejected("NOT_FILLABLE"));
+        assert_eq!(submit(&order("buy", Some(dec!(100)), "fok"), Some(dec!(100))), Outcome::Filled(dec!(100)));
+    }
+
+    #[test]
+    fn test_without_a_last_trade_nothing_is_fillable() {
+        assert_eq!(submit(&order("buy", None, "ioc"), None), Outcome::Cancelled);
+        assert_eq!(submit(&order("buy", None, "fok"), None), Outcome::Rejected("NOT_FILLABLE"));
+    }
+
+    #[test]
+    fn test_gtc_and_day_orders_rest() {
+        assert_eq!(submit(&order("buy", Some(dec!(101)), "gtc"), Some(dec!(100))), Outcome::Resting);
+        assert_eq!(submit(&order("buy", Some(dec!(101)), "day"), Some(dec!(100))), Outcome::Resting);
+    }
+
+    #[test]
+    fn test_stops_and_triggers_cannot_be_immediate() {
+        let stop = Order { order_type: "stop", ..order("sell", None, "ioc") };
+        assert_eq!(submit(&stop, Some(dec!(100))), Outcome::Rejected("stop"));
+
+        let triggered = Order { has_trigger: true, ..order("buy", Some(dec!(101)), "fok") };
+        assert_eq!(submit(&triggered, Some(dec!(100))), Outcome::Rejected("trigger"));
+    }
+}
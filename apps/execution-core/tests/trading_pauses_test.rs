@@ -0,0 +1,124 @@
+//! Unit Tests for Trading Pauses
+//! Standalone tests for pause windows, daily loss limits and pauses that cannot be cut short
+
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+
+#[cfg(test)]
+mod trading_pauses_tests {
+    use super::*;
+
+    struct Window {
+        start: &'static str,
+        end: &'static str,
+    }
+
+    #[derive(Default)]
+    struct Schedule {
+        windows: Vec<Window>,
+        max_daily_losses: Option<i32>,
+        paused_until: Option<DateTime<Utc>>,
+    }
+
+    fn time(s: &str) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(s, "%H:%M").ok()
+    }
+
+    /// Mirror of `PauseWindow::contains`
+    fn contains(window: &Window, at: NaiveTime) -> bool {
+        match (time(window.start), time(window.end)) {
+            (Some(start), Some(end)) if start <= end => start <= at && at < end,
+            (Some(start), Some(end)) => at >= start || at < end,
+            _ => false,
+        }
+    }
+
+    /// Mirror of `PauseSchedule::blocks`, returning which rule applies
+    fn blocks(schedule: &Schedule, now: DateTime<Utc>, losses_today: i32) -> Option<&'static str> {
+        if schedule.paused_until.is_some_and(|until| until > now) {
+            return Some("paused_until");
+        }
+        if schedule.windows.iter().any(|w| contains(w, now.time())) {
+            return Some("window");
+        }
+        match schedule.max_daily_losses {
+            Some(limit) if losses_today >= limit => Some("losses"),
+            _ => None,
+        }
+    }
+
+    /// Mirror of the `GREATEST` in `TradingPauses::update`
+    fn extend(current: Option<DateTime<Utc>>, requested: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        match (current, requested) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_window_within_a_day() {
+        let window = Window { start: "09:00", end: "17:30" };
+        assert!(!contains(&window, time("08:59").unwrap()));
+        assert!(contains(&window, time("09:00").unwrap()));
+        assert!(contains(&window, time("17:29").unwrap()));
+        assert!(!contains(&window, time("17:30").unwrap()));
+    }
+
+    #[test]
+    fn test_window_wraps_past_midnight() {
+        let window = Window { start: "22:00", end: "06:00" };
+        assert!(contains(&window, time("23:15").unwrap()));
+        assert!(contains(&window, time("00:00").unwrap()));
+        assert!(contains(&window, time("05:59").unwrap()));
+        assert!(!contains(&window, time("06:00").unwrap()));
+        assert!(!contains(&window, time("12:00").unwrap()));
+    }
+
+    #[test]
+    fn test_unparsable_window_never_applies() {
+        let window = Window { start: "9am", end: "17:00" };
+        assert!(!contains(&window, time("12:00").unwrap()));
+    }
+
+    #[test]
+    fn test_empty_schedule_never_blocks() {
+        assert_eq!(blocks(&Schedule::default(), at(12, 0), 100), None);
+    }
+
+    #[test]
+    fn test_loss_limit_blocks_once_reached() {
+        let schedule = Schedule { max_daily_losses: Some(3), ..Schedule::default() };
+        assert_eq!(blocks(&schedule, at(12, 0), 2), None);
+        assert_eq!(blocks(&schedule, at(12, 0), 3), Some("losses"));
+    }
+
+    #[test]
+    fn test_pause_expires() {
+        let schedule = Schedule { paused_until: Some(at(12, 0)), ..Schedule::default() };
+        assert_eq!(blocks(&schedule, at(11, 59), 0), Some("paused_until"));
+        assert_eq!(blocks(&schedule, at(12, 0), 0), None);
+    }
+
+    #[test]
+    fn test_running_pause_applies_before_windows() {
+        let schedule = Schedule {
+            windows: vec![Window { start: "11:00", end: "13:00" }],
+            paused_until: Some(at(18, 0)),
+            ..Schedule::default()
+        };
+        assert_eq!(blocks(&schedule, at(12, 0), 0), Some("paused_until"));
+        assert_eq!(blocks(&schedule, at(18, 0) + Duration::days(1), 0), None);
+    }
+
+    #[test]
+    fn test_pause_is_extended_but_never_shortened() {
+        let running = Some(at(18, 0));
+        assert_eq!(extend(running, Some(at(20, 0))), Some(at(20, 0)));
+        assert_eq!(extend(running, Some(at(13, 0))), running);
+        assert_eq!(extend(running, None), running);
+        assert_eq!(extend(None, Some(at(13, 0))), Some(at(13, 0)));
+    }
+}
@@ -0,0 +1,155 @@
+//! Unit Tests for Trailing Stop Orders
+//! Standalone tests for trail validation and how the stop price follows the market
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+#[cfg(test)]
+mod trailing_stop_tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Order {
+        side: &'static str,
+        order_type: &'static str,
+        trail_amount: Option<Decimal>,
+        trail_percent: Option<Decimal>,
+        stop_price: Option<Decimal>,
+    }
+
+    fn trailing(side: &'static str, amount: Option<Decimal>, percent: Option<Decimal>) -> Order {
+        Order { side, order_type: "trailing_stop", trail_amount: amount, trail_percent: percent, stop_price: None }
+    }
+
+    /// Mirror of `trail_stop`
+    fn trail_stop(side: &str, amount: Option<Decimal>, percent: Option<Decimal>, best: Decimal) -> Option<Decimal> {
+        let distance = amount.or_else(|| percent.map(|pct| best * pct / dec!(100)))?;
+        let stop = if side == "buy" { best + distance } else { best - distance };
+        let stop = stop.round_dp(8);
+        (stop > dec!(0)).then_some(stop)
+    }
+
+    /// Mirror of `ratchet`
+    fn ratchet(order: &Order, tick: Decimal) -> Option<Decimal> {
+        if order.order_type != "trailing_stop" {
+            return None;
+        }
+        let stop = trail_stop(order.side, order.trail_amount, order.trail_percent, tick)?;
+        match (order.side, order.stop_price) {
+            (_, None) => Some(stop),
+            ("sell", Some(current)) => (stop > current).then_some(stop),
+            ("buy", Some(current)) => (stop < current).then_some(stop),
+            _ => None,
+        }
+    }
+
+    /// Mirror of `stop_triggered` for trailing stops
+    fn stop_triggered(order: &Order, tick: Decimal) -> bool {
+        if order.order_type != "trailing_stop" {
+            return false;
+        }
+        match (order.side, order.stop_price) {
+            ("buy", Some(stop)) => tick >= stop,
+            ("sell", Some(stop)) => tick <= stop,
+            _ => false,
+        }
+    }
+
+    /// Mirror of `validate_trail`
+    fn validate_trail(amount: Option<Decimal>, percent: Option<Decimal>, stop_price: Option<Decimal>) -> Result<(), &'static str> {
+        if stop_price.is_some() {
+            return Err("stop_price");
+        }
+        match (amount, percent) {
+            (Some(_), Some(_)) | (None, None) => Err("one trail"),
+            (Some(a), None) if a <= dec!(0) => Err("amount"),
+            (None, Some(p)) if p <= dec!(0) || p >= dec!(100) => Err("percent"),
+            _ => Ok(()),
+        }
+    }
+
+    /// One tick as `process_market_tick` handles it: trigger, then move the survivors
+    fn on_tick(order: &mut Order, tick: Decimal) -> bool {
+        if stop_triggered(order, tick) {
+            order.order_type = "market";
+            return true;
+        }
+        if let Some(stop) = ratchet(order, tick) {
+            order.stop_price = Some(stop);
+        }
+        false
+    }
+
+    #[test]
+    fn test_first_tick_sets_the_stop() {
+        let order = trailing("sell", Some(dec!(5)), None);
+        assert_eq!(ratchet(&order, dec!(100)), Some(dec!(95)));
+
+        let order = trailing("buy", None, Some(dec!(2)));
+        assert_eq!(ratchet(&order, dec!(100)), Some(dec!(102)));
+    }
+
+    #[test]
+    fn test_sell_stop_only_rises() {
+        let mut order = trailing("sell", Some(dec!(5)), None);
+        assert!(!on_tick(&mut order, dec!(100)));
+        assert!(!on_tick(&mut order, dec!(110)));
+        assert_eq!(order.stop_price, Some(dec!(105)));
+
+        // A pullback that stays above the stop leaves it where it is
+        assert!(!on_tick(&mut order, dec!(107)));
+        assert_eq!(order.stop_price, Some(dec!(105)));
+
+        assert!(on_tick(&mut order, dec!(104.5)));
+        assert_eq!(order.order_type, "market");
+    }
+
+    #[test]
+    fn test_buy_stop_only_falls() {
+        let mut order = trailing("buy", None, Some(dec!(10)));
+        assert!(!on_tick(&mut order, dec!(100)));
+        assert_eq!(order.stop_price, Some(dec!(110)));
+        assert!(!on_tick(&mut order, dec!(80)));
+        assert_eq!(order.stop_price, Some(dec!(88)));
+        assert!(!on_tick(&mut order, dec!(85)));
+        assert_eq!(order.stop_price, Some(dec!(88)));
+        assert!(on_tick(&mut order, dec!(88)));
+    }
+
+    #[test]
+    fn test_persisted_level_survives_a_restart() {
+        // Reloaded from the orders table with the stop it had reached
+        let mut order = Order { stop_price: Some(dec!(105)), ..trailing("sell", Some(dec!(5)), None) };
+        assert!(!on_tick(&mut order, dec!(106)));
+        assert_eq!(order.stop_price, Some(dec!(105)));
+    }
+
+    #[test]
+    fn test_sell_trail_wider_than_price_waits() {
+        let order = trailing("sell", Some(dec!(50)), None);
+        assert_eq!(ratchet(&order, dec!(40)), None);
+        assert_eq!(ratchet(&order, dec!(60)), Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_other_orders_never_move() {
+        let stop = Order { order_type: "stop", stop_price: Some(dec!(95)), ..trailing("sell", Some(dec!(5)), None) };
+        assert_eq!(ratchet(&stop, dec!(200)), None);
+    }
+
+    #[test]
+    fn test_percent_stop_is_rounded() {
+        assert_eq!(trail_stop("sell", None, Some(dec!(3)), dec!(0.123456789)), Some(dec!(0.11975309)));
+    }
+
+    #[test]
+    fn test_trail_validation() {
+        assert_eq!(validate_trail(Some(dec!(5)), None, None), Ok(()));
+        assert_eq!(validate_trail(None, Some(dec!(2.5)), None), Ok(()));
+        assert!(validate_trail(None, None, None).is_err());
+        assert!(validate_trail(Some(dec!(5)), Some(dec!(2)), None).is_err());
+        assert!(validate_trail(Some(dec!(0)), None, None).is_err());
+        assert!(validate_trail(None, Some(dec!(100)), None).is_err());
+        assert!(validate_trail(Some(dec!(5)), None, Some(dec!(95))).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! Unit Tests for Conditional Order Triggers
+//! Standalone tests for trigger conditions, indicators and dependent cancellation
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+#[cfg(test)]
+mod trigger_tests {
+    use super::*;
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Action {
+        Activate,
+        Cancel,
+    }
+
+    struct Trigger {
+        parent: Uuid,
+        threshold: Option<Decimal>,
+        action: Action,
+    }
+
+    /// Without a threshold the parent must fill completely
+    fn parent_fill_met(trigger: &Trigger, order_id: Uuid, filled: Decimal, completed: bool) -> bool {
+        trigger.parent == order_id && trigger.threshold.map_or(completed, |q| filled >= q)
+    }
+
+    /// Orders cancelled when `root` will never fill: waiting children, recursively
+    fn cancel_dependents(triggers: &HashMap<Uuid, Trigger>, root: Uuid) -> Vec<Uuid> {
+        let mut cancelled = Vec::new();
+        let mut parents = vec![root];
+        while let Some(parent) = parents.pop() {
+            for (child, trigger) in triggers.iter().filter(|(_, t)| t.parent == parent) {
+                if trigger.action == Action::Activate {
+                    cancelled.push(*child);
+                    parents.push(*child);
+                }
+            }
+        }
+        cancelled
+    }
+
+    #[test]
+    fn test_fires_at_threshold() {
+        let parent = Uuid::new_v4();
+        let trigger = Trigger { parent, threshold: Some(dec!(5)), action: Action::Activate };
+
+        assert!(!parent_fill_met(&trigger, parent, dec!(4.99), false));
+        assert!(parent_fill_met(&trigger, parent, dec!(5), false));
+        assert!(parent_fill_met(&trigger, parent, dec!(10), true));
+    }
+
+    #[test]
+    fn test_without_threshold_waits_for_complete_fill() {
+        let parent = Uuid::new_v4();
+        let trigger = Trigger { parent, threshold: None, action: Action::Activate };
+
+        assert!(!parent_fill_met(&trigger, parent, dec!(6), false));
+        assert!(parent_fill_met(&trigger, parent, dec!(10), true));
+    }
+
+    #[test]
+    fn test_ignores_other_orders() {
+        let trigger = Trigger { parent: Uuid::new_v4(), threshold: None, action: Action::Activate };
+        assert!(!parent_fill_met(&trigger, Uuid::new_v4(), dec!(100), true));
+    }
+
+    #[test]
+    fn test_cancel_cascades_through_waiting_children_only() {
+        let root = Uuid::new_v4();
+        let take_profit = Uuid::new_v4();
+        let trailing = Uuid::new_v4();
+        let hedge = Uuid::new_v4();
+
+        let mut triggers = HashMap::new();
+        triggers.insert(take_profit, Trigger { parent: root, threshold: None, action: Action::Activate });
+        triggers.insert(trailing, Trigger { parent: take_profit, threshold: None, action: Action::Activate });
+        // A live order that would be cancelled by the fill stays live when the parent goes away
+        triggers.insert(hedge, Trigger { parent: root, threshold: None, action: Action::Cancel });
+
+        let mut cancelled = cancel_dependents(&triggers, root);
+        cancelled.sort();
+        let mut expected = vec![take_profit, trailing];
+        expected.sort();
+
+        assert_eq!(cancelled, expected);
+    }
+
+    // =====================================================
+    // INDICATORS
+    // =====================================================
+
+    #[derive(Default)]
+    struct Series {
+        prices: VecDeque<Decimal>,
+        notional: Decimal,
+        volume: Decimal,
+        last_cumulative: Option<Decimal>,
+    }
+
+    impl Series {
+        fn push(&mut self, price: Decimal, size: Decimal, cumulative: Decimal) {
+            if self.last_cumulative.is_some_and(|c| cumulative < c) {
+                self.notional = Decimal::ZERO;
+                self.volume = Decimal::ZERO;
+            }
+            self.last_cumulative = Some(cumulative);
+            self.notional += price * size;
+            self.volume += size;
+            self.prices.push_back(price);
+        }
+
+        fn sma(&self, period: usize, offset: usize) -> Option<Decimal> {
+            if self.prices.len() < period + offset {
+                return None;
+            }
+            let end = self.prices.len() - offset;
+            let sum: Decimal = self.prices.range(end - period..end).sum();
+            Some(sum / Decimal::from(period))
+        }
+
+        fn crossed_above(&self, fast: usize, slow: usize) -> bool {
+            match (self.sma(fast, 0), self.sma(slow, 0), self.sma(fast, 1), self.sma(slow, 1)) {
+                (Some(f), Some(s), Some(fp), Some(sp)) => fp <= sp && f > s,
+                _ => false,
+            }
+        }
+
+        fn vwap(&self) -> Decimal {
+            self.notional / self.volume
+        }
+    }
+
+    #[test]
+    fn test_ma_cross_fires_only_on_crossing_tick() {
+        let mut series = Series::default();
+        let mut fired = Vec::new();
+
+        for (i, price) in [dec!(10), dec!(9), dec!(8), dec!(9), dec!(11), dec!(12)].into_iter().enumerate() {
+            series.push(price, dec!(1), Decimal::from(i + 1));
+            fired.push(series.crossed_above(2, 3));
+        }
+
+        // fast(2) vs slow(3): 8.5/9 -> 8.5/8.67 -> 10/9.33 (cross) -> 11.5/10.67
+        assert_eq!(fired, vec![false, false, false, false, true, false]);
+    }
+
+    #[test]
+    fn test_ma_cross_needs_warm_up() {
+        let mut series = Series::default();
+        series.push(dec!(1), dec!(1), dec!(1));
+        series.push(dec!(5), dec!(1), dec!(2));
+        assert!(!series.crossed_above(1, 2));
+    }
+
+    #[test]
+    fn test_vwap_is_size_weighted_and_resets_each_session() {
+        let mut series = Series::default();
+        series.push(dec!(100), dec!(1), dec!(1));
+        series.push(dec!(110), dec!(3), dec!(4));
+        assert_eq!(series.vwap(), dec!(107.5));
+
+        // Cumulative volume dropped: a new session starts
+        series.push(dec!(90), dec!(2), dec!(2));
+        assert_eq!(series.vwap(), dec!(90));
+    }
+}
@@ -367,5 +367,6 @@ export const Permissions = {
   ACCOUNTS_READ_ALL: 'accounts:read_all',
   RISK_READ: 'risk:read',
   RISK_MANAGE: 'risk:manage',
+  RISK_OVERRIDE: 'risk:override',
   ADMIN_FULL: 'admin:full',
 } as const;
\ No newline at end of file
@@ -0,0 +1,48 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Leaderboard Schema
+-- File: infra/db/init/04_leaderboard.sql
+-- =============================================================================
+-- Run after 03_trading_tables.sql
+-- =============================================================================
+
+-- =============================================================================
+-- LEADERBOARD PARTICIPANTS (opt-in + privacy settings)
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS leaderboard_participants (
+                                                        account_id UUID PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+                                                        display_name VARCHAR(32) NOT NULL,
+                                                        show_pnl BOOLEAN NOT NULL DEFAULT false,
+                                                        is_active BOOLEAN NOT NULL DEFAULT true,
+                                                        opted_in_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                                        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_leaderboard_participants_active
+    ON leaderboard_participants(is_active) WHERE is_active = true;
+
+COMMENT ON TABLE leaderboard_participants IS 'Accounts that opted in to the paper-trading leaderboard';
+COMMENT ON COLUMN leaderboard_participants.display_name IS 'Public alias shown instead of the username';
+COMMENT ON COLUMN leaderboard_participants.show_pnl IS 'When false only the return percentage is published';
+
+-- =============================================================================
+-- POSITION SNAPSHOTS (periodic copies of the positions table)
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS position_snapshots (
+                                                  account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                                                  symbol VARCHAR(20) NOT NULL,
+                                                  snapshot_at TIMESTAMPTZ NOT NULL,
+                                                  net_quantity NUMERIC(20, 8) NOT NULL,
+                                                  avg_price NUMERIC(20, 8) NOT NULL,
+                                                  realized_pnl NUMERIC(20, 8) NOT NULL,
+                                                  unrealized_pnl NUMERIC(20, 8) NOT NULL,
+                                                  cost_basis NUMERIC(20, 8) NOT NULL,
+
+                                                  PRIMARY KEY (account_id, symbol, snapshot_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_position_snapshots_account_time
+    ON position_snapshots(account_id, snapshot_at DESC);
+
+COMMENT ON TABLE position_snapshots IS 'Point-in-time position copies used for period PnL (leaderboard)';
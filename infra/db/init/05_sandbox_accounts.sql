@@ -0,0 +1,26 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Sandbox (Demo) Accounts
+-- File: infra/db/init/05_sandbox_accounts.sql
+-- =============================================================================
+-- Run after 04_leaderboard.sql
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS sandbox_accounts (
+                                                account_id UUID PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+                                                provisioned_by UUID REFERENCES accounts(id) ON DELETE SET NULL,
+                                                session_id VARCHAR(100),
+                                                starting_balance NUMERIC(20, 8) NOT NULL CHECK (starting_balance >= 0),
+                                                reset_interval_secs INTEGER CHECK (reset_interval_secs IS NULL OR reset_interval_secs > 0),
+                                                reset_count INTEGER NOT NULL DEFAULT 0,
+                                                last_reset_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                                expires_at TIMESTAMPTZ NOT NULL,
+                                                expired BOOLEAN NOT NULL DEFAULT false,
+                                                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sandbox_accounts_session ON sandbox_accounts(session_id);
+CREATE INDEX IF NOT EXISTS idx_sandbox_accounts_expires ON sandbox_accounts(expires_at) WHERE expired = false;
+
+COMMENT ON TABLE sandbox_accounts IS 'Ephemeral demo accounts for onboarding, reset on schedule or on demand';
+COMMENT ON COLUMN sandbox_accounts.reset_interval_secs IS 'Automatic reset period (NULL = only reset via API)';
+COMMENT ON COLUMN sandbox_accounts.expires_at IS 'After this time the account is deactivated and its orders cancelled';
@@ -0,0 +1,78 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Asset Ledger & Order Holds
+-- File: infra/db/init/06_ledger.sql
+-- =============================================================================
+-- Run after 05_sandbox_accounts.sql
+-- =============================================================================
+
+-- =============================================================================
+-- ACCOUNT BALANCES (per asset, with amount held by open orders)
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS account_balances (
+                                                account_id UUID NOT NULL,
+                                                asset VARCHAR(20) NOT NULL,
+                                                total NUMERIC(28, 8) NOT NULL DEFAULT 0,
+                                                held NUMERIC(28, 8) NOT NULL DEFAULT 0 CHECK (held >= 0),
+                                                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+                                                PRIMARY KEY (account_id, asset),
+                                                CONSTRAINT held_within_total CHECK (held <= total OR account_id = '00000000-0000-0000-0000-000000000000')
+);
+
+COMMENT ON TABLE account_balances IS 'Per-asset balances; the nil account is the house (counterparty) account';
+COMMENT ON COLUMN account_balances.held IS 'Amount reserved by open orders (available = total - held)';
+
+-- =============================================================================
+-- BALANCE HOLDS (one per open order)
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS balance_holds (
+                                             order_id UUID PRIMARY KEY,
+                                             account_id UUID NOT NULL,
+                                             asset VARCHAR(20) NOT NULL,
+                                             amount NUMERIC(28, 8) NOT NULL CHECK (amount >= 0),
+                                             remaining NUMERIC(28, 8) NOT NULL CHECK (remaining >= 0),
+                                             created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                             released_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_balance_holds_account_open
+    ON balance_holds(account_id) WHERE released_at IS NULL;
+
+COMMENT ON TABLE balance_holds IS 'Funds reserved at order acceptance, consumed by fills and released on cancel/expiry';
+
+-- =============================================================================
+-- LEDGER ENTRIES (double-entry journal)
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS ledger_entries (
+                                              id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                                              journal_id UUID NOT NULL,
+                                              account_id UUID NOT NULL,
+                                              asset VARCHAR(20) NOT NULL,
+                                              amount NUMERIC(28, 8) NOT NULL,
+                                              entry_type VARCHAR(30) NOT NULL,
+                                              reference_id UUID,
+                                              created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_account ON ledger_entries(account_id, asset);
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_journal ON ledger_entries(journal_id);
+
+COMMENT ON TABLE ledger_entries IS 'Signed ledger postings; entries of one journal sum to zero per asset';
+
+-- =============================================================================
+-- OPENING BALANCES FROM EXISTING DATA
+-- =============================================================================
+
+INSERT INTO ledger_entries (journal_id, account_id, asset, amount, entry_type)
+SELECT a.id, a.id, 'USD', a.balance, 'opening_balance' FROM accounts a WHERE a.balance > 0
+UNION ALL
+SELECT a.id, '00000000-0000-0000-0000-000000000000', 'USD', -a.balance, 'opening_balance' FROM accounts a WHERE a.balance > 0;
+
+INSERT INTO account_balances (account_id, asset, total)
+SELECT account_id, asset, SUM(amount) FROM ledger_entries
+WHERE entry_type = 'opening_balance'
+GROUP BY account_id, asset
+ON CONFLICT (account_id, asset) DO NOTHING;
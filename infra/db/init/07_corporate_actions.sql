@@ -0,0 +1,80 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Corporate Actions (Splits & Dividends)
+-- File: infra/db/init/07_corporate_actions.sql
+-- =============================================================================
+-- Run after 06_ledger.sql
+-- =============================================================================
+
+-- =============================================================================
+-- CORPORATE ACTIONS
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS corporate_actions (
+                                                 id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                                                 symbol VARCHAR(20) NOT NULL,
+                                                 action_type VARCHAR(20) NOT NULL CHECK (action_type IN ('split', 'cash_dividend')),
+                                                 split_ratio NUMERIC(20, 8) CHECK (split_ratio IS NULL OR split_ratio > 0),
+                                                 dividend_per_share NUMERIC(20, 8) CHECK (dividend_per_share IS NULL OR dividend_per_share > 0),
+                                                 currency VARCHAR(20),
+                                                 ex_date TIMESTAMPTZ NOT NULL,
+                                                 pay_date TIMESTAMPTZ,
+                                                 status VARCHAR(20) NOT NULL DEFAULT 'announced'
+                                                     CHECK (status IN ('announced', 'entitled', 'applied', 'paid', 'cancelled')),
+                                                 announced_by UUID REFERENCES accounts(id) ON DELETE SET NULL,
+                                                 created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                                 processed_at TIMESTAMPTZ,
+
+                                                 CONSTRAINT split_has_ratio CHECK (action_type != 'split' OR split_ratio IS NOT NULL),
+                                                 CONSTRAINT dividend_has_terms CHECK (
+                                                     action_type != 'cash_dividend'
+                                                         OR (dividend_per_share IS NOT NULL AND currency IS NOT NULL
+                                                             AND pay_date IS NOT NULL AND pay_date >= ex_date)
+                                                     )
+);
+
+CREATE INDEX IF NOT EXISTS idx_corporate_actions_pending
+    ON corporate_actions(ex_date) WHERE status IN ('announced', 'entitled');
+CREATE INDEX IF NOT EXISTS idx_corporate_actions_symbol ON corporate_actions(symbol, ex_date DESC);
+
+COMMENT ON TABLE corporate_actions IS 'Admin-published splits and cash dividends';
+COMMENT ON COLUMN corporate_actions.split_ratio IS 'New shares per old share (2 = 2-for-1, 0.1 = 1-for-10 reverse split)';
+COMMENT ON COLUMN corporate_actions.ex_date IS 'Splits are applied and dividend entitlements fixed at this time';
+
+-- =============================================================================
+-- DIVIDEND ENTITLEMENTS (holdings fixed at ex-date, paid on pay date)
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS dividend_entitlements (
+                                                     action_id UUID NOT NULL REFERENCES corporate_actions(id) ON DELETE CASCADE,
+                                                     account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                                                     quantity NUMERIC(20, 8) NOT NULL,
+                                                     amount NUMERIC(28, 8) NOT NULL,
+                                                     paid_at TIMESTAMPTZ,
+
+                                                     PRIMARY KEY (action_id, account_id)
+);
+
+COMMENT ON COLUMN dividend_entitlements.quantity IS 'Long position held at ex-date (shorts are not charged)';
+
+-- =============================================================================
+-- ADJUSTMENT AUDIT TRAIL
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS corporate_action_adjustments (
+                                                            id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                                                            action_id UUID NOT NULL REFERENCES corporate_actions(id) ON DELETE CASCADE,
+                                                            account_id UUID NOT NULL,
+                                                            target VARCHAR(20) NOT NULL CHECK (target IN ('position', 'order', 'balance', 'dividend')),
+                                                            order_id UUID,
+                                                            quantity_before NUMERIC(28, 8),
+                                                            quantity_after NUMERIC(28, 8),
+                                                            price_before NUMERIC(20, 8),
+                                                            price_after NUMERIC(20, 8),
+                                                            cash_amount NUMERIC(28, 8),
+                                                            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ca_adjustments_action ON corporate_action_adjustments(action_id);
+CREATE INDEX IF NOT EXISTS idx_ca_adjustments_account ON corporate_action_adjustments(account_id, created_at DESC);
+
+COMMENT ON TABLE corporate_action_adjustments IS 'Every position, order, balance and cash change made by a corporate action';
@@ -0,0 +1,32 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Multi-Leg (Spread) Orders
+-- File: infra/db/init/08_multi_leg_orders.sql
+-- =============================================================================
+-- Run after 07_corporate_actions.sql
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS order_strategies (
+                                                id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                                                account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                                                client_strategy_id VARCHAR(100) NOT NULL,
+                                                quantity NUMERIC(20, 8) NOT NULL CHECK (quantity > 0),
+                                                net_price NUMERIC(20, 8),
+                                                status VARCHAR(20) NOT NULL DEFAULT 'pending'
+                                                    CHECK (status IN ('pending', 'filled', 'cancelled')),
+                                                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                                filled_at TIMESTAMPTZ,
+
+                                                CONSTRAINT order_strategies_unique_client_id UNIQUE (account_id, client_strategy_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_strategies_open ON order_strategies(account_id) WHERE status = 'pending';
+
+COMMENT ON TABLE order_strategies IS 'Multi-leg orders whose legs are risk-checked and filled as one unit';
+COMMENT ON COLUMN order_strategies.net_price IS 'Limit on the net debit per strategy unit (negative = minimum net credit); NULL = leg prices';
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS strategy_id UUID REFERENCES order_strategies(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_orders_strategy ON orders(strategy_id) WHERE strategy_id IS NOT NULL;
+
+COMMENT ON COLUMN orders.strategy_id IS 'Set for strategy legs; quantity = leg ratio x strategy quantity';
@@ -0,0 +1,35 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Conditional Order Triggers
+-- File: infra/db/init/09_order_triggers.sql
+-- =============================================================================
+-- Run after 08_multi_leg_orders.sql
+-- =============================================================================
+
+-- Orders armed with an activation trigger wait outside the book until it fires
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_status_check;
+ALTER TABLE orders ADD CONSTRAINT orders_status_check
+    CHECK (status IN ('waiting', 'pending', 'accepted', 'partially_filled', 'filled', 'cancelled', 'rejected', 'expired'));
+
+CREATE TABLE IF NOT EXISTS order_triggers (
+                                              order_id UUID PRIMARY KEY REFERENCES orders(id) ON DELETE CASCADE,
+                                              account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                                              condition_type VARCHAR(30) NOT NULL CHECK (condition_type IN ('parent_fill')),
+                                              parent_order_id UUID REFERENCES orders(id) ON DELETE CASCADE,
+                                              fill_threshold NUMERIC(20, 8) CHECK (fill_threshold IS NULL OR fill_threshold > 0),
+                                              action VARCHAR(10) NOT NULL CHECK (action IN ('activate', 'cancel')),
+                                              status VARCHAR(20) NOT NULL DEFAULT 'armed'
+                                                  CHECK (status IN ('armed', 'fired', 'cancelled')),
+                                              created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                              fired_at TIMESTAMPTZ,
+
+                                              CONSTRAINT parent_fill_has_parent CHECK (
+                                                  condition_type != 'parent_fill'
+                                                      OR (parent_order_id IS NOT NULL AND fill_threshold IS NOT NULL)
+                                                  )
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_triggers_parent
+    ON order_triggers(parent_order_id) WHERE status = 'armed';
+
+COMMENT ON TABLE order_triggers IS 'Conditions that activate or cancel an order when they fire';
+COMMENT ON COLUMN order_triggers.fill_threshold IS 'Parent filled quantity at which a parent_fill trigger fires';
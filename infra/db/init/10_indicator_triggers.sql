@@ -0,0 +1,28 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Indicator Order Triggers
+-- File: infra/db/init/10_indicator_triggers.sql
+-- =============================================================================
+-- Run after 09_order_triggers.sql
+-- =============================================================================
+
+ALTER TABLE order_triggers DROP CONSTRAINT IF EXISTS order_triggers_condition_type_check;
+ALTER TABLE order_triggers ADD CONSTRAINT order_triggers_condition_type_check
+    CHECK (condition_type IN ('parent_fill', 'ma_cross', 'price_vwap'));
+
+ALTER TABLE order_triggers ADD COLUMN IF NOT EXISTS watch_symbol VARCHAR(20);
+ALTER TABLE order_triggers ADD COLUMN IF NOT EXISTS fast_period INTEGER CHECK (fast_period IS NULL OR fast_period > 0);
+ALTER TABLE order_triggers ADD COLUMN IF NOT EXISTS slow_period INTEGER CHECK (slow_period IS NULL OR slow_period > 0);
+ALTER TABLE order_triggers ADD COLUMN IF NOT EXISTS direction VARCHAR(10) CHECK (direction IS NULL OR direction IN ('above', 'below'));
+
+ALTER TABLE order_triggers DROP CONSTRAINT IF EXISTS indicator_has_terms;
+ALTER TABLE order_triggers ADD CONSTRAINT indicator_has_terms CHECK (
+    condition_type = 'parent_fill'
+        OR (watch_symbol IS NOT NULL AND direction IS NOT NULL
+            AND (condition_type != 'ma_cross' OR (fast_period IS NOT NULL AND slow_period > fast_period)))
+    );
+
+CREATE INDEX IF NOT EXISTS idx_order_triggers_symbol
+    ON order_triggers(watch_symbol) WHERE status = 'armed';
+
+COMMENT ON COLUMN order_triggers.watch_symbol IS 'Symbol whose market data an indicator trigger is evaluated on';
+COMMENT ON COLUMN order_triggers.direction IS 'ma_cross: fast average crosses above/below slow; price_vwap: last price above/below VWAP';
@@ -0,0 +1,14 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Order Metadata
+-- File: infra/db/init/11_order_metadata.sql
+-- =============================================================================
+-- Run after 10_indicator_triggers.sql
+-- =============================================================================
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS metadata JSONB;
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_metadata_check;
+ALTER TABLE orders ADD CONSTRAINT orders_metadata_check
+    CHECK (metadata IS NULL OR (jsonb_typeof(metadata) = 'object' AND octet_length(metadata::text) <= 8192));
+
+COMMENT ON COLUMN orders.metadata IS 'Opaque client references (tags), echoed on every execution report';
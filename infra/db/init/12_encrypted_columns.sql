@@ -0,0 +1,17 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Encrypted Columns
+-- File: infra/db/init/12_encrypted_columns.sql
+-- =============================================================================
+-- Run after 11_order_metadata.sql
+-- =============================================================================
+
+-- Sealed values are stored in place as {"$enc": "<key id>:<base64 nonce||ciphertext||tag>"}
+-- (AES-256-GCM, key id as AAD). A 4 KB plaintext seals to about 5.5 KB, inside the
+-- existing 8 KB column limit.
+COMMENT ON COLUMN orders.metadata IS
+    'Opaque client references (tags), echoed on every execution report; sealed at rest when COLUMN_ENCRYPTION_KEYS is set';
+
+-- Finds rows still to be re-sealed after a key rotation
+CREATE INDEX IF NOT EXISTS idx_orders_metadata_key
+    ON orders ((split_part(metadata->>'$enc', ':', 1)))
+    WHERE metadata IS NOT NULL;
@@ -0,0 +1,23 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Account Data Export & Erasure
+-- File: infra/db/init/13_account_privacy.sql
+-- =============================================================================
+-- Run after 12_encrypted_columns.sql
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS account_erasure_requests (
+                                                        account_id UUID PRIMARY KEY REFERENCES accounts(id),
+                                                        requested_by UUID REFERENCES accounts(id) ON DELETE SET NULL,
+                                                        reason TEXT,
+                                                        status VARCHAR(20) NOT NULL DEFAULT 'scheduled'
+                                                            CHECK (status IN ('scheduled', 'completed', 'withdrawn')),
+                                                        requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                                        erase_after TIMESTAMPTZ NOT NULL,
+                                                        completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_erasure_due
+    ON account_erasure_requests(erase_after) WHERE status = 'scheduled';
+
+COMMENT ON TABLE account_erasure_requests IS 'Right-to-erasure requests; the account is frozen at once and pseudonymized after erase_after';
+COMMENT ON COLUMN account_erasure_requests.erase_after IS 'End of the retention window; personal identifiers are scrubbed by the sweeper after this';
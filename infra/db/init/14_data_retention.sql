@@ -0,0 +1,14 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Data Retention
+-- File: infra/db/init/14_data_retention.sql
+-- =============================================================================
+-- Run after 13_account_privacy.sql
+-- =============================================================================
+
+-- Retention is enforced by execution-core (RETENTION_RULES), which deletes expired
+-- rows in small batches. The TimescaleDB policy on market_ticks is dropped so a
+-- single retention rule owns tick lifetime.
+SELECT remove_retention_policy('market_ticks', if_exists => TRUE);
+
+-- Expired-row scans for the order_events class (audit_log already has idx_audit_log_created)
+CREATE INDEX IF NOT EXISTS idx_order_events_created ON order_events(created_at);
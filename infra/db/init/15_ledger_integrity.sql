@@ -0,0 +1,30 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Ledger Integrity Checks
+-- File: infra/db/init/15_ledger_integrity.sql
+-- =============================================================================
+-- Run after 14_data_retention.sql
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS ledger_integrity_checks (
+                                                       id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                                                       run_id UUID NOT NULL,
+                                                       check_name VARCHAR(50) NOT NULL,
+                                                       passed BOOLEAN NOT NULL,
+                                                       violations BIGINT NOT NULL DEFAULT 0,
+                                                       sample JSONB NOT NULL DEFAULT '[]'::jsonb,
+                                                       checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_integrity_checks_run ON ledger_integrity_checks(run_id);
+CREATE INDEX IF NOT EXISTS idx_ledger_integrity_checks_failed
+    ON ledger_integrity_checks(checked_at DESC) WHERE NOT passed;
+
+COMMENT ON TABLE ledger_integrity_checks IS 'One row per invariant per scheduled run; kept for auditors';
+COMMENT ON COLUMN ledger_integrity_checks.sample IS 'Up to 50 violating rows (journal, balance or position and the values that disagree)';
+
+-- Positions are reconciled against the full fill history, so fills are no longer
+-- dropped after two years
+SELECT remove_retention_policy('trades', if_exists => TRUE);
+
+-- Fills per position for the positions_match_trades check
+CREATE INDEX IF NOT EXISTS idx_trades_account_symbol ON trades(account_id, symbol);
@@ -0,0 +1,32 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Position Sequence Numbers
+-- File: infra/db/init/16_position_sequence.sql
+-- =============================================================================
+-- Run after 15_ledger_integrity.sql
+-- =============================================================================
+
+-- Number of fills and split adjustments applied to the position since it was opened
+-- (or its sandbox account was last reset). Every update increments it and is only
+-- written if the stored value still matches the one the update started from.
+ALTER TABLE positions ADD COLUMN IF NOT EXISTS sequence BIGINT NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN positions.sequence IS
+    'Movements applied; checked against trades + split adjustments on startup, rebuilt from them on mismatch';
+
+-- Backfill existing positions so startup only rebuilds genuine mismatches
+UPDATE positions p
+SET sequence = (
+    SELECT COUNT(*) FROM (
+        SELECT t.executed_at AS at FROM trades t
+        WHERE t.account_id = p.account_id AND t.symbol = p.symbol
+        UNION ALL
+        SELECT a.created_at FROM corporate_action_adjustments a
+        JOIN corporate_actions c ON c.id = a.action_id
+        WHERE a.target = 'position' AND a.account_id = p.account_id AND c.symbol = p.symbol
+    ) m
+    WHERE NOT EXISTS (
+        SELECT 1 FROM sandbox_accounts s
+        WHERE s.account_id = p.account_id AND m.at <= s.last_reset_at
+    )
+)
+WHERE p.sequence = 0;
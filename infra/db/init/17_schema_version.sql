@@ -0,0 +1,37 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Schema Version
+-- File: infra/db/init/17_schema_version.sql
+-- =============================================================================
+-- Run after 16_position_sequence.sql
+-- =============================================================================
+
+-- One row per init script applied, so a running engine can report which schema it
+-- is talking to. Every later script ends by inserting its own row.
+CREATE TABLE IF NOT EXISTS schema_version (
+    version INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE schema_version IS
+    'Init scripts applied; MAX(version) is reported by the execution engine at /info';
+
+INSERT INTO schema_version (version, name) VALUES
+    (1, 'extensions'),
+    (2, 'schema'),
+    (3, 'trading_tables'),
+    (4, 'leaderboard'),
+    (5, 'sandbox_accounts'),
+    (6, 'ledger'),
+    (7, 'corporate_actions'),
+    (8, 'multi_leg_orders'),
+    (9, 'order_triggers'),
+    (10, 'indicator_triggers'),
+    (11, 'order_metadata'),
+    (12, 'encrypted_columns'),
+    (13, 'account_privacy'),
+    (14, 'data_retention'),
+    (15, 'ledger_integrity'),
+    (16, 'position_sequence'),
+    (17, 'schema_version')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,35 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Fill Deduplication
+-- File: infra/db/init/18_fill_dedup.sql
+-- =============================================================================
+-- Run after 17_schema_version.sql
+-- =============================================================================
+
+-- One row per fill, keyed by order and fill sequence (1 for an order's first fill).
+-- A redelivered or replayed fill finds its key taken and records nothing; trades is
+-- a hypertable, so the key cannot be a unique constraint there.
+CREATE TABLE IF NOT EXISTS fill_dedup (
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    fill_seq INTEGER NOT NULL CHECK (fill_seq > 0),
+    trade_id UUID NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Set once the fill has moved its position; a second application finds it set
+    position_applied_at TIMESTAMPTZ,
+    PRIMARY KEY (order_id, fill_seq)
+);
+
+COMMENT ON TABLE fill_dedup IS
+    'Idempotency keys of recorded fills; guards trade insertion and position updates';
+
+-- Existing trades are numbered in execution order and count as applied
+INSERT INTO fill_dedup (order_id, fill_seq, trade_id, recorded_at, position_applied_at)
+SELECT order_id,
+       ROW_NUMBER() OVER (PARTITION BY order_id ORDER BY executed_at, id),
+       id,
+       executed_at,
+       executed_at
+FROM trades
+ON CONFLICT (order_id, fill_seq) DO NOTHING;
+
+INSERT INTO schema_version (version, name) VALUES (18, 'fill_dedup')
+ON CONFLICT (version) DO NOTHING;
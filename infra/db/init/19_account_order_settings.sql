@@ -0,0 +1,24 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Account Order Defaults
+-- File: infra/db/init/19_account_order_settings.sql
+-- =============================================================================
+-- Run after 18_fill_dedup.sql
+-- =============================================================================
+
+-- Filled into an order submission for every field it leaves out; NULL means no default
+CREATE TABLE IF NOT EXISTS account_order_settings (
+    account_id UUID PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    default_time_in_force VARCHAR(10) CHECK (default_time_in_force IN ('gtc', 'ioc', 'fok', 'day')),
+    default_order_type VARCHAR(20) CHECK (default_order_type IN ('market', 'limit')),
+    max_slippage_bps NUMERIC(10, 2) CHECK (max_slippage_bps >= 0),
+    strategy_tag VARCHAR(64),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE account_order_settings IS 'Per-account defaults for order submissions, managed on accounts.settings';
+COMMENT ON COLUMN account_order_settings.max_slippage_bps IS
+    'Market orders without a price get a protective limit this far from the last traded price';
+COMMENT ON COLUMN account_order_settings.strategy_tag IS 'Stored as metadata.strategy_tag unless the order sets it';
+
+INSERT INTO schema_version (version, name) VALUES (19, 'account_order_settings')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,32 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Self-Imposed Trading Pauses
+-- File: infra/db/init/20_trading_pauses.sql
+-- =============================================================================
+-- Run after 19_account_order_settings.sql
+-- =============================================================================
+
+-- Pauses an account holder set on themselves; new orders are rejected while one applies
+CREATE TABLE IF NOT EXISTS account_trading_pauses (
+    account_id UUID PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    -- [{"start": "HH:MM", "end": "HH:MM"}] in UTC; a window may wrap past midnight
+    windows JSONB NOT NULL DEFAULT '[]'::jsonb,
+    max_daily_losses INTEGER CHECK (max_daily_losses > 0),
+    -- Only ever extended: a self-exclusion cannot be cut short
+    paused_until TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE account_trading_pauses IS 'Self-imposed trading pauses, managed on accounts.pauses';
+COMMENT ON COLUMN account_trading_pauses.max_daily_losses IS
+    'New orders are rejected for the rest of the UTC day after this many losing fills';
+
+-- Fills that lowered an account's realized P&L, per UTC day
+CREATE TABLE IF NOT EXISTS account_daily_losses (
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    losses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (account_id, day)
+);
+
+INSERT INTO schema_version (version, name) VALUES (20, 'trading_pauses')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,28 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Trailing Stop Orders
+-- File: infra/db/init/21_trailing_stops.sql
+-- =============================================================================
+-- Run after 20_trading_pauses.sql
+-- =============================================================================
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_order_type_check;
+ALTER TABLE orders ADD CONSTRAINT orders_order_type_check
+    CHECK (order_type IN ('market', 'limit', 'stop', 'stop_limit', 'trailing_stop'));
+
+-- A trailing stop follows the price at a fixed distance, given as an amount or a percentage.
+-- Its current level is stop_price, moved on every tick that goes the order's way.
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS trail_amount NUMERIC(20, 8);
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS trail_percent NUMERIC(10, 4);
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_trail_check;
+ALTER TABLE orders ADD CONSTRAINT orders_trail_check CHECK (
+    (trail_amount IS NULL AND trail_percent IS NULL)
+    OR (trail_amount > 0 AND trail_percent IS NULL)
+    OR (trail_percent > 0 AND trail_percent < 100 AND trail_amount IS NULL)
+);
+
+COMMENT ON COLUMN orders.trail_amount IS 'Trailing stop distance from the best price since submission';
+COMMENT ON COLUMN orders.trail_percent IS 'Trailing stop distance as a percentage of the best price since submission';
+
+INSERT INTO schema_version (version, name) VALUES (21, 'trailing_stops')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,26 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Good-Till-Date Orders
+-- File: infra/db/init/22_good_till_date.sql
+-- =============================================================================
+-- Run after 21_trailing_stops.sql
+-- =============================================================================
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_time_in_force_check;
+ALTER TABLE orders ADD CONSTRAINT orders_time_in_force_check
+    CHECK (time_in_force IN ('gtc', 'ioc', 'fok', 'day', 'gtd'));
+
+-- Good-till-date orders are expired by the engine's sweeper once this passes
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_expires_at_check;
+ALTER TABLE orders ADD CONSTRAINT orders_expires_at_check
+    CHECK ((time_in_force = 'gtd') = (expires_at IS NOT NULL));
+
+CREATE INDEX IF NOT EXISTS idx_orders_expires_at ON orders(expires_at)
+    WHERE expires_at IS NOT NULL AND status IN ('waiting', 'pending', 'partially_filled');
+
+COMMENT ON COLUMN orders.time_in_force IS 'gtc=Good Till Cancelled, ioc=Immediate Or Cancel, fok=Fill Or Kill, day=Day Order, gtd=Good Till Date';
+COMMENT ON COLUMN orders.expires_at IS 'When a gtd order expires if still open';
+
+INSERT INTO schema_version (version, name) VALUES (22, 'good_till_date')
+ON CONFLICT (version) DO NOTHING;
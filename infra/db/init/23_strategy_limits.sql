@@ -0,0 +1,35 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Per-Strategy Kill Switches and Limits
+-- File: infra/db/init/23_strategy_limits.sql
+-- =============================================================================
+-- Run after 22_good_till_date.sql
+-- =============================================================================
+
+-- Controls on the orders an account submits under one strategy_tag, so a single algo
+-- can be stopped without halting the whole account
+CREATE TABLE IF NOT EXISTS strategy_tag_limits (
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    strategy_tag VARCHAR(64) NOT NULL,
+    -- Kill switch: every new order under the tag is rejected
+    halted BOOLEAN NOT NULL DEFAULT FALSE,
+    max_orders_per_minute INTEGER CHECK (max_orders_per_minute > 0),
+    max_notional_per_day NUMERIC(20, 8) CHECK (max_notional_per_day > 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (account_id, strategy_tag)
+);
+
+COMMENT ON TABLE strategy_tag_limits IS 'Per-strategy kill switches and limits, managed on accounts.strategy_limits';
+
+-- Orders accepted under a limited tag: notional per UTC day, and the count in the current minute
+CREATE TABLE IF NOT EXISTS strategy_tag_usage (
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    strategy_tag VARCHAR(64) NOT NULL,
+    day DATE NOT NULL,
+    notional NUMERIC(20, 8) NOT NULL DEFAULT 0,
+    minute TIMESTAMPTZ NOT NULL,
+    minute_orders INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (account_id, strategy_tag, day)
+);
+
+INSERT INTO schema_version (version, name) VALUES (23, 'strategy_limits')
+ON CONFLICT (version) DO NOTHING;
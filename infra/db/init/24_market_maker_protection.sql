@@ -0,0 +1,27 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Market-Maker Protection
+-- File: infra/db/init/24_market_maker_protection.sql
+-- =============================================================================
+-- Run after 23_strategy_limits.sql
+-- =============================================================================
+
+-- A quoting account's protection per symbol: too many fills, or too much filled quantity,
+-- within the window pulls every open order on the symbol and blocks new ones until reset
+CREATE TABLE IF NOT EXISTS account_mmp_settings (
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    symbol VARCHAR(20) NOT NULL,
+    fill_limit INTEGER CHECK (fill_limit > 0),
+    quantity_limit NUMERIC(20, 8) CHECK (quantity_limit > 0),
+    window_secs INTEGER NOT NULL CHECK (window_secs > 0),
+    -- Blocked this long after triggering; NULL until the account resets it
+    freeze_secs INTEGER CHECK (freeze_secs > 0),
+    triggered_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (account_id, symbol),
+    CHECK (fill_limit IS NOT NULL OR quantity_limit IS NOT NULL)
+);
+
+COMMENT ON TABLE account_mmp_settings IS 'Market-maker protection, managed on accounts.mmp';
+
+INSERT INTO schema_version (version, name) VALUES (24, 'market_maker_protection')
+ON CONFLICT (version) DO NOTHING;
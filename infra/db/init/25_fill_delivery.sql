@@ -0,0 +1,45 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Fill Delivery
+-- File: infra/db/init/25_fill_delivery.sql
+-- =============================================================================
+-- Run after 24_market_maker_protection.sql
+-- =============================================================================
+
+-- Downstream systems that must receive every fill: webhook dispatchers, WebSocket
+-- sessions and drop copies. Each gets its own copy of an execution report on its
+-- subject and acknowledges it on fills.ack.
+CREATE TABLE IF NOT EXISTS fill_consumers (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(64) NOT NULL UNIQUE,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('webhook', 'websocket', 'drop_copy')),
+    subject VARCHAR(255) NOT NULL,
+    -- NULL: fills of every account
+    account_id UUID REFERENCES accounts(id) ON DELETE CASCADE,
+    -- Quarantined consumers stopped acknowledging; their fills queue up until released
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'quarantined')),
+    quarantined_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One row per fill and consumer, written in the fill's transaction
+CREATE TABLE IF NOT EXISTS fill_deliveries (
+    trade_id UUID NOT NULL,
+    consumer_id UUID NOT NULL REFERENCES fill_consumers(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_attempt_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (trade_id, consumer_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_fill_deliveries_due
+    ON fill_deliveries (next_attempt_at) WHERE status = 'pending';
+
+COMMENT ON TABLE fill_consumers IS 'Registered fill consumers, managed on fills.consumers';
+COMMENT ON TABLE fill_deliveries IS 'Delivery state of each fill per consumer; pending until acknowledged';
+
+INSERT INTO schema_version (version, name) VALUES (25, 'fill_delivery')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,18 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Order Amend
+-- File: infra/db/init/26_order_amend.sql
+-- =============================================================================
+-- Run after 25_fill_delivery.sql
+-- =============================================================================
+
+-- An amend cancels the order and replaces it with a new one under a new client_order_id;
+-- the replacement points back at the order it replaced
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS replaces_order_id UUID REFERENCES orders(id);
+
+CREATE INDEX IF NOT EXISTS idx_orders_replaces ON orders(replaces_order_id)
+    WHERE replaces_order_id IS NOT NULL;
+
+COMMENT ON COLUMN orders.replaces_order_id IS 'Order this one replaced through orders.amend';
+
+INSERT INTO schema_version (version, name) VALUES (26, 'order_amend')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,29 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Order Groups
+-- File: infra/db/init/27_order_groups.sql
+-- =============================================================================
+-- Run after 26_order_amend.sql
+-- =============================================================================
+
+-- Linked orders of one account. In a one-cancels-other group the first fill of any
+-- member cancels the others in the same transaction.
+CREATE TABLE IF NOT EXISTS order_groups (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    group_type VARCHAR(20) NOT NULL DEFAULT 'oco' CHECK (group_type IN ('oco')),
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+    -- Member whose fill completed the group
+    filled_order_id UUID REFERENCES orders(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS group_id UUID REFERENCES order_groups(id);
+
+CREATE INDEX IF NOT EXISTS idx_orders_group ON orders(group_id) WHERE group_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_order_groups_account ON order_groups(account_id);
+
+COMMENT ON TABLE order_groups IS 'One-cancels-other groups, formed by submitting an order with ocoWith';
+
+INSERT INTO schema_version (version, name) VALUES (27, 'order_groups')
+ON CONFLICT (version) DO NOTHING;
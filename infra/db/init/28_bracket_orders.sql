@@ -0,0 +1,18 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Bracket Orders
+-- File: infra/db/init/28_bracket_orders.sql
+-- =============================================================================
+-- Run after 27_order_groups.sql
+-- =============================================================================
+
+-- Take-profit and stop-loss exits of a bracket point at their entry order. They wait
+-- on a parent_fill trigger until the entry fills and form a one-cancels-other group.
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS parent_order_id UUID REFERENCES orders(id);
+
+CREATE INDEX IF NOT EXISTS idx_orders_parent ON orders(parent_order_id)
+    WHERE parent_order_id IS NOT NULL;
+
+COMMENT ON COLUMN orders.parent_order_id IS 'Entry order of the bracket this exit belongs to';
+
+INSERT INTO schema_version (version, name) VALUES (28, 'bracket_orders')
+ON CONFLICT (version) DO NOTHING;
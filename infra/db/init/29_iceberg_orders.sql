@@ -0,0 +1,16 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Iceberg Orders
+-- File: infra/db/init/29_iceberg_orders.sql
+-- =============================================================================
+-- Run after 28_bracket_orders.sql
+-- =============================================================================
+
+-- An iceberg shows and matches display_quantity at a time; each time that slice fills the
+-- next one is shown from the hidden remainder and an 'iceberg_replenished' event is written
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS display_quantity NUMERIC(20, 8)
+    CHECK (display_quantity IS NULL OR display_quantity > 0);
+
+COMMENT ON COLUMN orders.display_quantity IS 'Visible slice of an iceberg order; NULL shows the whole order';
+
+INSERT INTO schema_version (version, name) VALUES (29, 'iceberg_orders')
+ON CONFLICT (version) DO NOTHING;
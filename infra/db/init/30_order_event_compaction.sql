@@ -0,0 +1,45 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Order Event Compaction
+-- File: infra/db/init/30_order_event_compaction.sql
+-- =============================================================================
+-- Run after 29_iceberg_orders.sql
+-- =============================================================================
+
+-- Folded event stream of a settled order. Compaction writes one row per order and moves the
+-- raw events to order_events_archive in the same transaction.
+CREATE TABLE IF NOT EXISTS order_snapshots (
+    order_id UUID PRIMARY KEY REFERENCES orders(id),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    -- Order status when it was compacted: filled, cancelled, rejected or expired
+    status VARCHAR(20) NOT NULL,
+    event_count BIGINT NOT NULL,
+    -- Per event type: count, first and last time, and the data of the last one
+    events JSONB NOT NULL DEFAULT '{}'::jsonb,
+    first_event_at TIMESTAMPTZ NOT NULL,
+    last_event_at TIMESTAMPTZ NOT NULL,
+    compacted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Raw events of compacted orders, kept for full-history reads
+CREATE TABLE IF NOT EXISTS order_events_archive (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id),
+    event_type VARCHAR(50) NOT NULL,
+    event_data JSONB,
+    created_at TIMESTAMPTZ NOT NULL,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_snapshots_account ON order_snapshots(account_id);
+CREATE INDEX IF NOT EXISTS idx_order_events_archive_order ON order_events_archive(order_id);
+-- Expired-row scans for the order_events_archive retention class
+CREATE INDEX IF NOT EXISTS idx_order_events_archive_created ON order_events_archive(created_at);
+-- Settled orders waiting for compaction
+CREATE INDEX IF NOT EXISTS idx_orders_settled ON orders(updated_at)
+    WHERE status IN ('filled', 'cancelled', 'rejected', 'expired');
+
+COMMENT ON TABLE order_snapshots IS 'Compacted order event streams, one row per settled order';
+COMMENT ON TABLE order_events_archive IS 'Order events moved out of order_events by compaction';
+
+INSERT INTO schema_version (version, name) VALUES (30, 'order_event_compaction')
+ON CONFLICT (version) DO NOTHING;
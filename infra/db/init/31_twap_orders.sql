@@ -0,0 +1,52 @@
+-- =============================================================================
+-- Enthropic Trading Platform - TWAP Orders
+-- File: infra/db/init/31_twap_orders.sql
+-- =============================================================================
+-- Run after 30_order_event_compaction.sql
+-- =============================================================================
+
+-- Parent of a time-weighted execution. The scheduler submits one child order per slice,
+-- evenly spaced over duration_secs, until every slice is sent or the TWAP is cancelled.
+CREATE TABLE IF NOT EXISTS twap_orders (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    client_twap_id VARCHAR(100) NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(4) NOT NULL CHECK (side IN ('buy', 'sell')),
+    quantity NUMERIC(20, 8) NOT NULL CHECK (quantity > 0),
+    -- Limit price of every slice; market slices when NULL
+    price NUMERIC(20, 8),
+    duration_secs INTEGER NOT NULL CHECK (duration_secs > 0),
+    slices INTEGER NOT NULL CHECK (slices > 0),
+    metadata JSONB,
+    -- Username of the submitter; child orders are submitted under it
+    submitted_by VARCHAR(100) NOT NULL,
+    slices_sent INTEGER NOT NULL DEFAULT 0,
+    next_slice_at TIMESTAMPTZ NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed', 'cancelled')),
+    started_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    CONSTRAINT client_twap_unique UNIQUE (account_id, client_twap_id)
+);
+
+-- One row per slice sent; order_id is NULL when the child order was rejected
+CREATE TABLE IF NOT EXISTS twap_slices (
+    twap_id UUID NOT NULL REFERENCES twap_orders(id) ON DELETE CASCADE,
+    slice_index INTEGER NOT NULL,
+    order_id UUID REFERENCES orders(id),
+    quantity NUMERIC(20, 8) NOT NULL,
+    reject_code VARCHAR(50),
+    reject_reason TEXT,
+    submitted_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (twap_id, slice_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_twap_orders_due ON twap_orders(next_slice_at) WHERE status = 'active';
+CREATE INDEX IF NOT EXISTS idx_twap_orders_account ON twap_orders(account_id);
+
+COMMENT ON TABLE twap_orders IS 'Time-weighted parent orders, sliced into child orders by execution-core';
+
+INSERT INTO schema_version (version, name) VALUES (31, 'twap_orders')
+ON CONFLICT (version) DO NOTHING;
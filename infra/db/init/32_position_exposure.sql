@@ -0,0 +1,33 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Position Exposure
+-- File: infra/db/init/32_position_exposure.sql
+-- =============================================================================
+-- Run after 31_twap_orders.sql
+-- =============================================================================
+
+-- Time-weighted exposure of a position per session (UTC day). Each position change accrues
+-- the quantity held since accrued_until; a scheduled pass carries open positions across
+-- session boundaries so every session they were held in has a row.
+CREATE TABLE IF NOT EXISTS position_exposure (
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    symbol VARCHAR(20) NOT NULL,
+    session_date DATE NOT NULL,
+    -- Integral of |net_quantity| over the session, in quantity-seconds
+    gross_quantity_seconds NUMERIC(38, 8) NOT NULL DEFAULT 0,
+    -- Integral of the signed net_quantity; shorts offset longs
+    net_quantity_seconds NUMERIC(38, 8) NOT NULL DEFAULT 0,
+    -- Seconds the position was not flat
+    held_seconds NUMERIC(20, 3) NOT NULL DEFAULT 0,
+    max_quantity NUMERIC(20, 8) NOT NULL DEFAULT 0,
+    -- Net quantity held from accrued_until on
+    closing_quantity NUMERIC(20, 8) NOT NULL DEFAULT 0,
+    accrued_until TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (account_id, symbol, session_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_position_exposure_session ON position_exposure(session_date);
+
+COMMENT ON TABLE position_exposure IS 'Time-weighted position exposure per account, symbol and UTC day';
+
+INSERT INTO schema_version (version, name) VALUES (32, 'position_exposure')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,29 @@
+-- =============================================================================
+-- Enthropic Trading Platform - VWAP Orders
+-- File: infra/db/init/33_vwap_orders.sql
+-- =============================================================================
+-- Run after 32_position_exposure.sql
+-- =============================================================================
+
+-- Traded volume per symbol in fixed bars, aggregated from market ticks. VWAP schedules
+-- are sized from it and measured against the market VWAP over their window.
+CREATE TABLE IF NOT EXISTS market_volume_bars (
+    symbol VARCHAR(20) NOT NULL,
+    bar_start TIMESTAMPTZ NOT NULL,
+    volume NUMERIC(30, 8) NOT NULL DEFAULT 0,
+    notional NUMERIC(38, 8) NOT NULL DEFAULT 0,
+    PRIMARY KEY (symbol, bar_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_volume_bars_start ON market_volume_bars(bar_start);
+
+-- TWAPs slice evenly; VWAPs fix each slice's quantity from the volume profile when they
+-- are scheduled, and slices sized to nothing are skipped without an order
+ALTER TABLE twap_orders
+    ADD COLUMN IF NOT EXISTS strategy VARCHAR(10) NOT NULL DEFAULT 'twap' CHECK (strategy IN ('twap', 'vwap')),
+    ADD COLUMN IF NOT EXISTS slice_quantities NUMERIC(20, 8)[];
+
+COMMENT ON TABLE market_volume_bars IS 'Per-symbol traded volume bars, written by execution-core from market ticks';
+
+INSERT INTO schema_version (version, name) VALUES (33, 'vwap_orders')
+ON CONFLICT (version) DO NOTHING;
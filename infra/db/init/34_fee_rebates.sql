@@ -0,0 +1,55 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Fee Rebates & Referrals
+-- File: infra/db/init/34_fee_rebates.sql
+-- =============================================================================
+-- Run after 33_vwap_orders.sql
+-- =============================================================================
+
+-- Who referred an account; the referrer earns a share of the fees the account generates
+CREATE TABLE IF NOT EXISTS account_referrals (
+    account_id UUID PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    referrer_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT referral_not_self CHECK (account_id <> referrer_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_referrals_referrer ON account_referrals(referrer_id);
+
+-- Every rebate credited to an account, the statement it is shown from. Maker rebates are
+-- credited per fill; volume-tier and referral rebates once per month when it is settled.
+CREATE TABLE IF NOT EXISTS fee_rebates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('maker', 'volume_tier', 'referral')),
+    -- First day of the month the rebate counts towards
+    period DATE NOT NULL,
+    asset VARCHAR(20) NOT NULL,
+    -- Notional the rate applied to; for referrals, the referred account's notional
+    notional NUMERIC(28, 8) NOT NULL,
+    bps NUMERIC(10, 4) NOT NULL,
+    amount NUMERIC(28, 8) NOT NULL CHECK (amount >= 0),
+    -- The fill of a maker rebate
+    trade_id UUID,
+    -- The referred account of a referral rebate
+    source_account_id UUID REFERENCES accounts(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_fee_rebates_maker ON fee_rebates(trade_id) WHERE kind = 'maker';
+CREATE UNIQUE INDEX IF NOT EXISTS idx_fee_rebates_tier
+    ON fee_rebates(account_id, period, asset) WHERE kind = 'volume_tier';
+CREATE UNIQUE INDEX IF NOT EXISTS idx_fee_rebates_referral
+    ON fee_rebates(account_id, period, asset, source_account_id) WHERE kind = 'referral';
+CREATE INDEX IF NOT EXISTS idx_fee_rebates_statement ON fee_rebates(account_id, period);
+
+-- Months whose volume-tier and referral rebates have been credited
+CREATE TABLE IF NOT EXISTS fee_rebate_periods (
+    period DATE PRIMARY KEY,
+    rebates INTEGER NOT NULL DEFAULT 0,
+    settled_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE fee_rebates IS 'Maker, volume-tier and referral rebates credited by execution-core';
+
+INSERT INTO schema_version (version, name) VALUES (34, 'fee_rebates')
+ON CONFLICT (version) DO NOTHING;
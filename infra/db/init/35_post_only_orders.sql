@@ -0,0 +1,15 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Post-Only Orders
+-- File: infra/db/init/35_post_only_orders.sql
+-- =============================================================================
+-- Run after 34_fee_rebates.sql
+-- =============================================================================
+
+-- A post-only limit order is rejected instead of accepted when its price would execute
+-- against the last trade, so it only ever rests and earns the maker rebate
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS post_only BOOLEAN NOT NULL DEFAULT false;
+
+COMMENT ON COLUMN orders.post_only IS 'Limit order that is rejected rather than take liquidity';
+
+INSERT INTO schema_version (version, name) VALUES (35, 'post_only_orders')
+ON CONFLICT (version) DO NOTHING;
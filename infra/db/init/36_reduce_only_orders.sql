@@ -0,0 +1,16 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Reduce-Only Orders
+-- File: infra/db/init/36_reduce_only_orders.sql
+-- =============================================================================
+-- Run after 35_post_only_orders.sql
+-- =============================================================================
+
+-- A reduce-only order is capped at the account's position when submitted and again on each
+-- fill; once the position is closed the rest of the order is cancelled with a
+-- 'reduce_only_cancelled' event
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS reduce_only BOOLEAN NOT NULL DEFAULT false;
+
+COMMENT ON COLUMN orders.reduce_only IS 'Order that only ever reduces the account''s position in its symbol';
+
+INSERT INTO schema_version (version, name) VALUES (36, 'reduce_only_orders')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,35 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Dust Sweeps
+-- File: infra/db/init/37_dust_sweeps.sql
+-- =============================================================================
+-- Run after 36_reduce_only_orders.sql
+-- =============================================================================
+
+-- Set while a position is worth less than DUST_THRESHOLD, so clients can hide it;
+-- cleared by the next sweep once it no longer is
+ALTER TABLE positions ADD COLUMN IF NOT EXISTS dust_flagged_at TIMESTAMPTZ;
+
+-- Every dust position closed and dust balance swept against the house account. Position
+-- closes are movements of the position like fills, so rebuilds replay them.
+CREATE TABLE IF NOT EXISTS dust_sweeps (
+    id UUID PRIMARY KEY,
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    target VARCHAR(10) NOT NULL CHECK (target IN ('position', 'balance')),
+    -- The position's symbol, or the swept balance's asset
+    symbol VARCHAR(20) NOT NULL,
+    -- Side of the closing trade; NULL for balances
+    side VARCHAR(4) CHECK (side IN ('buy', 'sell')),
+    quantity NUMERIC(28, 8) NOT NULL CHECK (quantity > 0),
+    price NUMERIC(20, 8) NOT NULL,
+    -- Quote asset credited (or, closing a short, debited)
+    value NUMERIC(28, 8) NOT NULL,
+    swept_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_dust_sweeps_account ON dust_sweeps(account_id, symbol, swept_at);
+
+COMMENT ON TABLE dust_sweeps IS 'Dust positions closed and dust balances swept by execution-core';
+COMMENT ON COLUMN positions.dust_flagged_at IS 'When the position was flagged as dust; NULL when it is not';
+
+INSERT INTO schema_version (version, name) VALUES (37, 'dust_sweeps')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,16 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Internal Crossing
+-- File: infra/db/init/38_internal_crossing.sql
+-- =============================================================================
+-- Run after 37_dust_sweeps.sql
+-- =============================================================================
+
+-- The resting order of the other account a trade crossed with in the internal book;
+-- NULL for trades filled against market ticks
+ALTER TABLE trades ADD COLUMN IF NOT EXISTS contra_order_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_trades_contra_order
+    ON trades(contra_order_id) WHERE contra_order_id IS NOT NULL;
+
+INSERT INTO schema_version (version, name) VALUES (38, 'internal_crossing')
+ON CONFLICT (version) DO NOTHING;
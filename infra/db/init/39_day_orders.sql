@@ -0,0 +1,15 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Day Orders
+-- File: infra/db/init/39_day_orders.sql
+-- =============================================================================
+-- Run after 38_internal_crossing.sql
+-- =============================================================================
+
+-- Open day orders, for the cancellation at each session close (SESSION_CLOSE). The
+-- cancellation itself is an order event of type 'cancelled' with reason 'session_close'.
+CREATE INDEX IF NOT EXISTS idx_orders_open_day
+    ON orders(created_at)
+    WHERE time_in_force = 'day' AND status IN ('waiting', 'pending', 'partially_filled');
+
+INSERT INTO schema_version (version, name) VALUES (39, 'day_orders')
+ON CONFLICT (version) DO NOTHING;
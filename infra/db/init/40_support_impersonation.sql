@@ -0,0 +1,44 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Support Impersonation
+-- File: infra/db/init/40_support_impersonation.sql
+-- =============================================================================
+-- Run after 39_day_orders.sql
+-- =============================================================================
+
+-- A customer's consent for support to query their account, for a limited window. Granting
+-- again replaces the consent in force; revoking ends every session opened under it.
+CREATE TABLE IF NOT EXISTS support_consents (
+    id UUID PRIMARY KEY,
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    granted_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    CHECK (expires_at > granted_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_support_consents_account
+    ON support_consents(account_id, granted_at DESC);
+
+-- A support agent's read-only session on a consenting customer. Every request made through
+-- it is written to audit_log as 'impersonated_request' under the customer's account.
+CREATE TABLE IF NOT EXISTS impersonation_sessions (
+    id UUID PRIMARY KEY,
+    agent_account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    agent_username VARCHAR(255) NOT NULL,
+    customer_account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    consent_id UUID NOT NULL REFERENCES support_consents(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    notify_customer BOOLEAN NOT NULL DEFAULT TRUE,
+    started_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ,
+    CHECK (customer_account_id <> agent_account_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_impersonation_sessions_customer
+    ON impersonation_sessions(customer_account_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_impersonation_sessions_consent
+    ON impersonation_sessions(consent_id) WHERE ended_at IS NULL;
+
+INSERT INTO schema_version (version, name) VALUES (40, 'support_impersonation')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,40 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Account Exports
+-- File: infra/db/init/41_export_jobs.sql
+-- =============================================================================
+-- Run after 40_support_impersonation.sql
+-- =============================================================================
+
+-- Bulk exports of an account's orders, trades or position snapshots over a date range.
+-- Requested as 'pending', claimed by the export worker as 'running', then 'ready' with the
+-- file's object store URL in location, or 'failed' with the error.
+CREATE TABLE IF NOT EXISTS export_jobs (
+    id UUID PRIMARY KEY,
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('orders', 'trades', 'positions')),
+    format VARCHAR(10) NOT NULL CHECK (format IN ('csv', 'parquet')),
+    from_date DATE NOT NULL,
+    to_date DATE NOT NULL,
+    status VARCHAR(10) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'running', 'ready', 'failed')),
+    location TEXT,
+    row_count BIGINT,
+    size_bytes BIGINT,
+    error TEXT,
+    requested_at TIMESTAMPTZ NOT NULL,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    CHECK (to_date >= from_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_export_jobs_account
+    ON export_jobs(account_id, requested_at DESC);
+CREATE INDEX IF NOT EXISTS idx_export_jobs_queue
+    ON export_jobs(requested_at) WHERE status IN ('pending', 'running');
+
+-- Orders exported by creation time
+CREATE INDEX IF NOT EXISTS idx_orders_account_created
+    ON orders(account_id, created_at);
+
+INSERT INTO schema_version (version, name) VALUES (41, 'export_jobs')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,21 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Analytics Export
+-- File: infra/db/init/42_analytics_partitions.sql
+-- =============================================================================
+-- Run after 41_export_jobs.sql
+-- =============================================================================
+
+-- Days of trades and market ticks copied to the analytics store (ANALYTICS_STORE_URL) as
+-- <dataset>/date=YYYY-MM-DD/part-NNNNN.parquet. The exporter resumes from the latest day
+-- recorded per dataset; delete rows to have days written again.
+CREATE TABLE IF NOT EXISTS analytics_partitions (
+    dataset VARCHAR(20) NOT NULL CHECK (dataset IN ('trades', 'ticks')),
+    day DATE NOT NULL,
+    files INTEGER NOT NULL,
+    rows BIGINT NOT NULL,
+    exported_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (dataset, day)
+);
+
+INSERT INTO schema_version (version, name) VALUES (42, 'analytics_partitions')
+ON CONFLICT (version) DO NOTHING;
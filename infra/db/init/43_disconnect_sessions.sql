@@ -0,0 +1,29 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Cancel-on-Disconnect Sessions
+-- File: infra/db/init/43_disconnect_sessions.sql
+-- =============================================================================
+-- Run after 42_analytics_partitions.sql
+-- =============================================================================
+
+-- Gateway sessions that asked for their account's orders to be cancelled if they go quiet.
+-- 'active' while heartbeats arrive within timeout_secs, 'closed' when the gateway ends the
+-- session itself, 'triggered' once the engine mass-cancelled after missed heartbeats.
+CREATE TABLE IF NOT EXISTS disconnect_sessions (
+    id UUID PRIMARY KEY,
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    timeout_secs INTEGER NOT NULL CHECK (timeout_secs > 0),
+    status VARCHAR(10) NOT NULL DEFAULT 'active'
+        CHECK (status IN ('active', 'closed', 'triggered')),
+    registered_at TIMESTAMPTZ NOT NULL,
+    last_heartbeat_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ,
+    cancelled_orders INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_disconnect_sessions_active
+    ON disconnect_sessions(last_heartbeat_at) WHERE status = 'active';
+CREATE INDEX IF NOT EXISTS idx_disconnect_sessions_account
+    ON disconnect_sessions(account_id, registered_at DESC);
+
+INSERT INTO schema_version (version, name) VALUES (43, 'disconnect_sessions')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,23 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Instrument Order Rules
+-- File: infra/db/init/44_instrument_rules.sql
+-- =============================================================================
+-- Run after 43_disconnect_sessions.sql
+-- =============================================================================
+
+-- The execution engine holds every instruments row in memory and rejects orders off the
+-- tick or lot grid, outside min_quantity..max_quantity, or below min_notional. Symbols
+-- without a row are not restricted.
+ALTER TABLE instruments ADD COLUMN IF NOT EXISTS min_notional NUMERIC(20, 8)
+    CHECK (min_notional IS NULL OR min_notional > 0);
+
+ALTER TABLE instruments DROP CONSTRAINT IF EXISTS instruments_quantity_bounds;
+ALTER TABLE instruments ADD CONSTRAINT instruments_quantity_bounds
+    CHECK (max_quantity IS NULL OR max_quantity >= COALESCE(min_quantity, 0));
+
+COMMENT ON COLUMN instruments.lot_size IS 'Order quantities must be whole multiples of this';
+COMMENT ON COLUMN instruments.min_notional IS
+    'Smallest quantity times price an order may have, in the instrument currency';
+
+INSERT INTO schema_version (version, name) VALUES (44, 'instrument_rules')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,22 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Shard Assignments
+-- File: infra/db/init/45_shard_assignments.sql
+-- =============================================================================
+-- Run after 44_instrument_rules.sql
+-- =============================================================================
+
+-- Symbols moved between sharded execution-core instances by a rebalance. At startup an
+-- instance trades its configured symbols plus those assigned to its INSTANCE_ID, minus those
+-- assigned to another instance, so a move outlives restarts of either side.
+CREATE TABLE IF NOT EXISTS shard_assignments (
+    symbol VARCHAR(20) PRIMARY KEY,
+    instance_id VARCHAR(255) NOT NULL,
+    rebalance_id UUID NOT NULL,
+    assigned_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_shard_assignments_instance
+    ON shard_assignments(instance_id);
+
+INSERT INTO schema_version (version, name) VALUES (45, 'shard_assignments')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,31 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Order Journal
+-- File: infra/db/init/46_order_journal.sql
+-- =============================================================================
+-- Run after 45_shard_assignments.sql
+-- =============================================================================
+
+-- Order submits answered in fast-ack mode before they were processed. An entry stays
+-- 'pending' until its order is committed or rejected; pending entries are replayed at startup.
+-- The request, with its metadata, is dropped once the entry completes.
+CREATE TABLE IF NOT EXISTS order_journal (
+    id UUID PRIMARY KEY,
+    account_id UUID NOT NULL,
+    client_order_id VARCHAR(255) NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    auth JSONB NOT NULL,
+    request JSONB,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'committed', 'rejected', 'failed')),
+    order_id UUID,
+    error TEXT,
+    appended_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ,
+    UNIQUE (account_id, client_order_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_journal_pending
+    ON order_journal(appended_at) WHERE status = 'pending';
+
+INSERT INTO schema_version (version, name) VALUES (46, 'order_journal')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,20 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Client Order Id Key
+-- File: infra/db/init/47_client_order_id_key.sql
+-- =============================================================================
+-- Run after 46_order_journal.sql
+-- =============================================================================
+
+-- One order per client order id and account, under the one name the engine recognises.
+-- A submit racing another with the same client order id fails its insert on this key and
+-- is answered from the order that won, as a duplicate or, with different terms, rejected.
+-- Depending on which schema file created the table, the key was one of the two constraints
+-- dropped below; the index replaces it before it goes.
+CREATE UNIQUE INDEX IF NOT EXISTS orders_client_order_id_key
+    ON orders(account_id, client_order_id);
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS client_order_unique;
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_unique_client_id;
+
+INSERT INTO schema_version (version, name) VALUES (47, 'client_order_id_key')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,22 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Order List Indexes
+-- File: infra/db/init/48_order_list_indexes.sql
+-- =============================================================================
+-- Run after 47_client_order_id_key.sql
+-- =============================================================================
+
+-- Blotter pages (orders.list) walk an account's orders by creation time, resuming after
+-- the (created_at, id) of the last order served; with the id in the key a page is one index
+-- range in either direction. The symbol and status filters get their own prefix.
+CREATE INDEX IF NOT EXISTS idx_orders_account_created_id
+    ON orders(account_id, created_at, id);
+CREATE INDEX IF NOT EXISTS idx_orders_account_symbol_created
+    ON orders(account_id, symbol, created_at, id);
+CREATE INDEX IF NOT EXISTS idx_orders_account_status_created
+    ON orders(account_id, status, created_at, id);
+
+-- Covered by the first of them, which account exports now use
+DROP INDEX IF EXISTS idx_orders_account_created;
+
+INSERT INTO schema_version (version, name) VALUES (48, 'order_list_indexes')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,38 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Fill Identity Key
+-- File: infra/db/init/49_fill_identity_key.sql
+-- =============================================================================
+-- Run after 48_order_list_indexes.sql
+-- =============================================================================
+
+-- A fill is keyed by its order and how much of the order was filled before it. A fill
+-- redelivered or replayed from the same order state carries the key it had the first time;
+-- numbering it one past the fills already claimed gave it a new one.
+ALTER TABLE fill_dedup ADD COLUMN IF NOT EXISTS filled_before NUMERIC(20, 8);
+
+-- Existing keys take the quantity of their order's earlier trades
+UPDATE fill_dedup d
+SET filled_before = t.filled_before
+FROM (
+    SELECT id,
+           COALESCE(SUM(quantity) OVER (
+               PARTITION BY order_id ORDER BY executed_at, id
+               ROWS BETWEEN UNBOUNDED PRECEDING AND 1 PRECEDING
+           ), 0) AS filled_before
+    FROM trades
+) t
+WHERE t.id = d.trade_id AND d.filled_before IS NULL;
+
+-- A key whose trade is gone has no fill left to guard
+DELETE FROM fill_dedup WHERE filled_before IS NULL;
+
+ALTER TABLE fill_dedup ALTER COLUMN filled_before SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS fill_dedup_fill_key
+    ON fill_dedup(order_id, filled_before);
+
+ALTER TABLE fill_dedup DROP CONSTRAINT IF EXISTS fill_dedup_pkey;
+ALTER TABLE fill_dedup DROP COLUMN IF EXISTS fill_seq;
+
+INSERT INTO schema_version (version, name) VALUES (49, 'fill_identity_key')
+ON CONFLICT (version) DO NOTHING;
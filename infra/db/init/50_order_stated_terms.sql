@@ -0,0 +1,18 @@
+-- =============================================================================
+-- Enthropic Trading Platform - Order Stated Terms
+-- File: infra/db/init/50_order_stated_terms.sql
+-- =============================================================================
+-- Run after 49_fill_identity_key.sql
+-- =============================================================================
+
+-- Terms of the submitted request that no column keeps as stated: the notional its quantity
+-- was resolved from, its trigger, bracket and one-cancels-other link. A submit reusing the
+-- order's client order id is compared with them as well as with the columns; NULL when the
+-- request stated none of them.
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS stated_terms JSONB;
+
+COMMENT ON COLUMN orders.stated_terms IS
+    'notional, trigger, bracket and ocoWith as submitted, to compare a resubmission with';
+
+INSERT INTO schema_version (version, name) VALUES (50, 'order_stated_terms')
+ON CONFLICT (version) DO NOTHING;
@@ -4,11 +4,15 @@ export interface Order {
   clientOrderId: string;
   symbol: string;
   side: 'buy' | 'sell';
-  orderType: 'market' | 'limit' | 'stop' | 'stop_limit';
-  timeInForce: 'gtc' | 'ioc' | 'fok' | 'day';
+  orderType: 'market' | 'limit' | 'stop' | 'stop_limit' | 'trailing_stop';
+  timeInForce: 'gtc' | 'ioc' | 'fok' | 'day' | 'gtd';
+  expiresAt?: string;
   quantity: string;
   price?: string;
   stopPrice?: string;
+  trailAmount?: string;
+  trailPercent?: string;
+  displayQuantity?: string;
   filledQuantity: string;
   avgFillPrice?: string;
   status: 'pending' | 'partially_filled' | 'filled' | 'cancelled' | 'rejected' | 'expired';
@@ -64,6 +68,7 @@ export interface MarketTick {
   lastPrice: string;
   lastSize: string;
   volume: string;
+  availableVolume?: string;
   timestamp: number;
 }
 
@@ -96,6 +101,7 @@ export const Permissions = {
   ACCOUNTS_READ_ALL: 'accounts:read_all',
   RISK_READ: 'risk:read',
   RISK_MANAGE: 'risk:manage',
+  RISK_OVERRIDE: 'risk:override',
   STRATEGIES_READ: 'strategies:read',
   STRATEGIES_CREATE: 'strategies:create',
   STRATEGIES_EXECUTE: 'strategies:execute',
@@ -24,6 +24,9 @@ async function main() {
         { name: 'risk:read', resource: 'risk', action: 'read' },
         { name: 'risk:manage', resource: 'risk', action: 'manage' },
 
+        // Sandbox (demo accounts for onboarding)
+        { name: 'sandbox:manage', resource: 'sandbox', action: 'manage' },
+
         // Admin
         { name: 'admin:full', resource: 'admin', action: 'full' },
     ];